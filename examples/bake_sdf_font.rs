@@ -0,0 +1,33 @@
+//! 离线烘焙距离场字体
+//!
+//! 用法：`cargo run --example bake_sdf_font -- <字体文件> <输出.toml> [字号] [扩展范围] [字符集文件]`
+//!
+//! 默认烘焙 ASCII 与 Latin-1 字符，指定字符集文件时额外烘焙其中出现的所有字符
+use gle::*;
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.len() < 2 {
+        eprintln!("用法: bake_sdf_font <字体文件> <输出.toml> [字号] [扩展范围] [字符集文件]");
+        std::process::exit(1);
+    }
+    let mut baker = SdfFontBaker::default();
+    if let Some(size) = args.get(2).and_then(|s| s.parse().ok()) {
+        baker.size = size;
+    }
+    if let Some(spread) = args.get(3).and_then(|s| s.parse().ok()) {
+        baker.spread = spread;
+    }
+    let mut chars: Vec<char> = (' '..='~').chain('\u{a0}'..='ÿ').collect();
+    if let Some(charset) = args.get(4) {
+        let text = std::fs::read_to_string(charset).expect("无法读取字符集文件");
+        chars.extend(text.chars().filter(|c| !c.is_control()));
+    }
+    chars.sort_unstable();
+    chars.dedup();
+
+    let font = std::fs::read(&args[0]).expect("无法读取字体文件");
+    let data = baker.bake(&font, chars).expect("烘焙失败");
+    data.save(&args[1]).expect("保存失败");
+    println!("已烘焙 {} 个字形到 {}", data.glyph_count(), args[1]);
+}
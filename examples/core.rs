@@ -35,6 +35,7 @@ const VET: [f32; 25] = [
 ];
 
 const VAO: &str = id!(VAO);
+const PROGRAM: &str = id!(PROGRAM);
 
 fn event_init() {
     debug!(self, "事件初始化函数执行...");
@@ -45,21 +46,6 @@ fn event_loop() {
     {
         println!("W key pressed");
     }
-
-    if Registry::apply(WINDOW, |w: &mut Window| {
-        w.get_key(Key::LeftAlt) == Action::Press
-    })
-    .unwrap_or(false)
-    {
-        debug!(
-            "event_loop",
-            "E_MS: {:>8.2}\tE_FPS: {:>8.2}\tR_MS: {:>8.2}\tR_FPS: {:>8.2}",
-            App::event_ms(),
-            App::event_fps(),
-            App::render_ms(),
-            App::render_fps()
-        );
-    }
 }
 
 fn render_init() {
@@ -159,8 +145,7 @@ fn render_init() {
             );
             println!("Program link error: {}", std::str::from_utf8(&buf).unwrap());
         }
-        gl::UseProgram(program);
-        // Registry::register(PROGRAM, program).unwrap();
+        Registry::register(PROGRAM, program).unwrap();
     }
 }
 
@@ -169,18 +154,23 @@ fn render_loop() {
         gl::ClearColor(0.3, 0.4, 0.5, 1.0);
         gl::Clear(gl::COLOR_BUFFER_BIT);
 
+        Registry::with(PROGRAM, |program: &u32| gl::UseProgram(*program));
         Registry::with(VAO, |vao: &u32| {
             gl::BindVertexArray(*vao);
             gl::DrawArrays(gl::TRIANGLES, 0, 3);
             gl::DrawArrays(gl::LINES, 3, 2);
             gl::BindVertexArray(0);
         });
+        RenderStats::record_draw(1);
+        RenderStats::record_draw(0);
     }
 }
 
 fn main() {
     Log::set_level(Level::Debug);
     let mut app = AppBuilder::new(800, 600, "RustCraft")
+        // 按 F3 显示或隐藏帧率、绘制调用与显存统计
+        .add_plugin(DebugOverlayPlugin)
         .set_render_init(render_init)
         .set_render_loop(render_loop)
         .set_event_init(event_init)
@@ -0,0 +1,451 @@
+use std::f32::consts::PI;
+
+use crate::math::*;
+use crate::{App, Transform};
+
+/// 可插值的类型
+pub trait Tweenable: Copy + Send + 'static {
+    /// 在两个值之间插值
+    ///
+    /// # 参数
+    /// + `a` - 起始值
+    /// + `b` - 结束值
+    /// + `t` - 插值系数，缓动函数可能使其略微超出`[0, 1]`
+    fn interpolate(a: Self, b: Self, t: f32) -> Self;
+}
+
+impl Tweenable for f32 {
+    fn interpolate(a: Self, b: Self, t: f32) -> Self {
+        a + (b - a) * t
+    }
+}
+
+impl Tweenable for Vec2 {
+    fn interpolate(a: Self, b: Self, t: f32) -> Self {
+        a.lerp(b, t)
+    }
+}
+
+impl Tweenable for Vec3 {
+    fn interpolate(a: Self, b: Self, t: f32) -> Self {
+        a.lerp(b, t)
+    }
+}
+
+impl Tweenable for Vec4 {
+    fn interpolate(a: Self, b: Self, t: f32) -> Self {
+        a.lerp(b, t)
+    }
+}
+
+impl Tweenable for Quat {
+    fn interpolate(a: Self, b: Self, t: f32) -> Self {
+        a.slerp(b, t)
+    }
+}
+
+impl Tweenable for Transform {
+    fn interpolate(a: Self, b: Self, t: f32) -> Self {
+        Transform {
+            translation: a.translation.lerp(b.translation, t),
+            rotation: a.rotation.slerp(b.rotation, t),
+            scale: a.scale.lerp(b.scale, t),
+        }
+    }
+}
+
+/// 缓动函数
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Easing {
+    /// 线性
+    #[default]
+    Linear,
+    /// 二次缓入
+    QuadIn,
+    /// 二次缓出
+    QuadOut,
+    /// 二次缓入缓出
+    QuadInOut,
+    /// 三次缓入
+    CubicIn,
+    /// 三次缓出
+    CubicOut,
+    /// 三次缓入缓出
+    CubicInOut,
+    /// 正弦缓入
+    SineIn,
+    /// 正弦缓出
+    SineOut,
+    /// 正弦缓入缓出
+    SineInOut,
+    /// 指数缓入
+    ExpoIn,
+    /// 指数缓出
+    ExpoOut,
+    /// 回退缓出，结束前会略微越过目标值
+    BackOut,
+    /// 弹性缓出
+    ElasticOut,
+    /// 弹跳缓出
+    BounceOut,
+}
+
+impl Easing {
+    /// 计算缓动值
+    ///
+    /// # 参数
+    /// + `t` - 归一化时间，范围为`[0, 1]`
+    ///
+    /// # 返回值
+    /// 返回缓动后的插值系数，`0`与`1`分别映射到`0`与`1`
+    pub fn apply(&self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Easing::Linear => t,
+            Easing::QuadIn => t * t,
+            Easing::QuadOut => 1.0 - (1.0 - t) * (1.0 - t),
+            Easing::QuadInOut => {
+                if t < 0.5 {
+                    2.0 * t * t
+                } else {
+                    1.0 - (-2.0 * t + 2.0).powi(2) / 2.0
+                }
+            }
+            Easing::CubicIn => t * t * t,
+            Easing::CubicOut => 1.0 - (1.0 - t).powi(3),
+            Easing::CubicInOut => {
+                if t < 0.5 {
+                    4.0 * t * t * t
+                } else {
+                    1.0 - (-2.0 * t + 2.0).powi(3) / 2.0
+                }
+            }
+            Easing::SineIn => 1.0 - (t * PI / 2.0).cos(),
+            Easing::SineOut => (t * PI / 2.0).sin(),
+            Easing::SineInOut => -((PI * t).cos() - 1.0) / 2.0,
+            Easing::ExpoIn => {
+                if t == 0.0 {
+                    0.0
+                } else {
+                    2f32.powf(10.0 * t - 10.0)
+                }
+            }
+            Easing::ExpoOut => {
+                if t == 1.0 {
+                    1.0
+                } else {
+                    1.0 - 2f32.powf(-10.0 * t)
+                }
+            }
+            Easing::BackOut => {
+                let c1 = 1.70158;
+                let c3 = c1 + 1.0;
+                1.0 + c3 * (t - 1.0).powi(3) + c1 * (t - 1.0).powi(2)
+            }
+            Easing::ElasticOut => {
+                if t == 0.0 || t == 1.0 {
+                    t
+                } else {
+                    2f32.powf(-10.0 * t) * ((t * 10.0 - 0.75) * (2.0 * PI / 3.0)).sin() + 1.0
+                }
+            }
+            Easing::BounceOut => {
+                let (n1, d1) = (7.5625, 2.75);
+                if t < 1.0 / d1 {
+                    n1 * t * t
+                } else if t < 2.0 / d1 {
+                    let t = t - 1.5 / d1;
+                    n1 * t * t + 0.75
+                } else if t < 2.5 / d1 {
+                    let t = t - 2.25 / d1;
+                    n1 * t * t + 0.9375
+                } else {
+                    let t = t - 2.625 / d1;
+                    n1 * t * t + 0.984375
+                }
+            }
+        }
+    }
+}
+
+/// 动画的循环方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LoopMode {
+    /// 播放一次
+    #[default]
+    Once,
+    /// 播放指定次数
+    Repeat(u32),
+    /// 无限循环
+    Forever,
+}
+
+/// 补间动画
+///
+/// 在若干关键帧之间随时间插值，并将结果传递给设置函数
+///
+/// # 示例
+///
+/// ```
+/// use gle::{*, math::*};
+///
+/// let tween = Tween::new(0.0f32, 1.0, 0.5, |alpha| println!("alpha = {}", alpha))
+///     .with_easing(Easing::CubicOut)
+///     .on_complete(|| println!("fade in done"));
+/// let path = Tween::keyframes(
+///     vec![(0.0, Vec3::ZERO), (1.0, Vec3::X), (3.0, Vec3::Y)],
+///     |p| println!("position = {}", p),
+/// )
+/// .with_loop(LoopMode::Forever)
+/// .with_yoyo(true);
+/// ```
+pub struct Tween<T: Tweenable> {
+    keys: Vec<(f32, T)>,
+    easing: Easing,
+    loop_mode: LoopMode,
+    yoyo: bool,
+    delay: f32,
+    elapsed: f32,
+    setter: Box<dyn FnMut(T) + Send>,
+    on_complete: Option<Box<dyn FnOnce() + Send>>,
+}
+
+impl<T: Tweenable> Tween<T> {
+    /// 创建从起始值到结束值的补间动画
+    ///
+    /// # 参数
+    /// + `from` - 起始值
+    /// + `to` - 结束值
+    /// + `duration` - 时长，单位为秒
+    /// + `setter` - 一个函数，它将在每次更新时接受插值结果
+    pub fn new<F: FnMut(T) + Send + 'static>(from: T, to: T, duration: f32, setter: F) -> Self {
+        Self::keyframes(vec![(0.0, from), (duration.max(0.0), to)], setter)
+    }
+
+    /// 创建关键帧动画
+    ///
+    /// # 参数
+    /// + `keys` - 关键帧列表，每项为`(时间, 值)`，时间单位为秒且应按升序排列，动画时长为最后一个关键帧的时间
+    /// + `setter` - 一个函数，它将在每次更新时接受插值结果
+    ///
+    /// # 注解
+    ///
+    /// 缓动函数作用于整个动画时长，而非单个关键帧区间
+    pub fn keyframes<F: FnMut(T) + Send + 'static>(keys: Vec<(f32, T)>, setter: F) -> Self {
+        assert!(!keys.is_empty(), "关键帧列表不能为空");
+        Self {
+            keys,
+            easing: Easing::Linear,
+            loop_mode: LoopMode::Once,
+            yoyo: false,
+            delay: 0.0,
+            elapsed: 0.0,
+            setter: Box::new(setter),
+            on_complete: None,
+        }
+    }
+
+    /// 设置缓动函数
+    pub fn with_easing(mut self, easing: Easing) -> Self {
+        self.easing = easing;
+        self
+    }
+
+    /// 设置循环方式
+    pub fn with_loop(mut self, loop_mode: LoopMode) -> Self {
+        self.loop_mode = loop_mode;
+        self
+    }
+
+    /// 设置是否往返播放，为`true`时每个奇数次播放将倒放
+    pub fn with_yoyo(mut self, yoyo: bool) -> Self {
+        self.yoyo = yoyo;
+        self
+    }
+
+    /// 设置开始前的延迟，单位为秒
+    pub fn with_delay(mut self, delay: f32) -> Self {
+        self.delay = delay.max(0.0);
+        self
+    }
+
+    /// 设置动画完成时的回调函数，无限循环的动画不会完成
+    pub fn on_complete<F: FnOnce() + Send + 'static>(mut self, f: F) -> Self {
+        self.on_complete = Some(Box::new(f));
+        self
+    }
+
+    /// 获取单次播放的时长，单位为秒
+    pub fn duration(&self) -> f32 {
+        self.keys.last().map_or(0.0, |k| k.0)
+    }
+
+    /// 获取指定动画时间的插值结果(不考虑缓动)，超出关键帧范围时沿首尾区间外推
+    fn sample(&self, time: f32) -> T {
+        if self.keys.len() == 1 {
+            return self.keys[0].1;
+        }
+        let next = self
+            .keys
+            .partition_point(|k| k.0 <= time)
+            .clamp(1, self.keys.len() - 1);
+        let (t0, a) = self.keys[next - 1];
+        let (t1, b) = self.keys[next];
+        let t = if t1 > t0 { (time - t0) / (t1 - t0) } else { 1.0 };
+        T::interpolate(a, b, t)
+    }
+
+    /// 推进动画
+    ///
+    /// # 参数
+    /// + `dt` - 时间增量，单位为秒
+    ///
+    /// # 返回值
+    /// 动画已完成时返回`true`
+    pub fn advance(&mut self, dt: f32) -> bool {
+        self.elapsed += dt;
+        let time = self.elapsed - self.delay;
+        if time < 0.0 {
+            return false;
+        }
+        let duration = self.duration();
+        let plays = match self.loop_mode {
+            LoopMode::Once => Some(1),
+            LoopMode::Repeat(n) => Some(n.max(1)),
+            LoopMode::Forever => None,
+        };
+        let (cycle, local, finished) = if duration <= 0.0 {
+            (0, 1.0, plays.is_some())
+        } else {
+            let cycle = (time / duration).floor() as u32;
+            match plays {
+                Some(n) if cycle >= n => (n - 1, 1.0, true),
+                _ => (cycle, (time - cycle as f32 * duration) / duration, false),
+            }
+        };
+        let mut u = local;
+        if self.yoyo && cycle % 2 == 1 {
+            u = 1.0 - u;
+        }
+        let value = self.sample(self.easing.apply(u) * duration);
+        (self.setter)(value);
+        if finished {
+            if let Some(f) = self.on_complete.take() {
+                f();
+            }
+        }
+        finished
+    }
+}
+
+trait ActiveTween: Send {
+    fn advance(&mut self, dt: f32) -> bool;
+}
+
+impl<T: Tweenable> ActiveTween for Tween<T> {
+    fn advance(&mut self, dt: f32) -> bool {
+        Tween::advance(self, dt)
+    }
+}
+
+/// 补间动画的标识
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TweenId(u64);
+
+/// 动画管理器
+///
+/// 统一推进多个任意类型的补间动画，已完成的动画会被自动移除
+///
+/// # 示例
+///
+/// ```
+/// use gle::*;
+///
+/// let mut animator = Animator::new();
+/// animator.play(Tween::new(0.0f32, 1.0, 2.0, |v| println!("{}", v)));
+/// // 在事件循环中
+/// animator.tick();
+/// ```
+pub struct Animator {
+    tweens: Vec<(TweenId, Box<dyn ActiveTween>)>,
+    next_id: u64,
+    /// 时间缩放系数(默认值为1.0)
+    pub time_scale: f32,
+}
+
+impl Default for Animator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Animator {
+    /// 创建一个动画管理器
+    pub fn new() -> Self {
+        Self {
+            tweens: Vec::new(),
+            next_id: 0,
+            time_scale: 1.0,
+        }
+    }
+
+    /// 开始播放补间动画
+    ///
+    /// # 参数
+    /// + `tween` - 补间动画
+    ///
+    /// # 返回值
+    /// 返回动画的标识
+    pub fn play<T: Tweenable>(&mut self, tween: Tween<T>) -> TweenId {
+        let id = TweenId(self.next_id);
+        self.next_id += 1;
+        self.tweens.push((id, Box::new(tween)));
+        id
+    }
+
+    /// 停止补间动画，不会触发完成回调
+    ///
+    /// # 参数
+    /// + `id` - 动画的标识
+    pub fn stop(&mut self, id: TweenId) {
+        self.tweens.retain(|(i, _)| *i != id);
+    }
+
+    /// 停止所有补间动画
+    pub fn clear(&mut self) {
+        self.tweens.clear();
+    }
+
+    /// 判断补间动画是否正在播放
+    pub fn is_playing(&self, id: TweenId) -> bool {
+        self.tweens.iter().any(|(i, _)| *i == id)
+    }
+
+    /// 获取正在播放的动画数量
+    pub fn len(&self) -> usize {
+        self.tweens.len()
+    }
+
+    /// 判断是否没有正在播放的动画
+    pub fn is_empty(&self) -> bool {
+        self.tweens.is_empty()
+    }
+
+    /// 推进所有动画
+    ///
+    /// # 参数
+    /// + `dt` - 时间增量，单位为秒
+    pub fn update(&mut self, dt: f32) {
+        let dt = dt * self.time_scale;
+        self.tweens.retain_mut(|(_, tween)| !tween.advance(dt));
+    }
+
+    /// 以事件循环最近一帧的运行时间推进所有动画
+    ///
+    /// # 注解
+    ///
+    /// 应在事件循环函数中每帧调用一次，时间增量取自 [`App::event_ms`]
+    pub fn tick(&mut self) {
+        self.update((App::event_ms() / 1000.0) as f32);
+    }
+}
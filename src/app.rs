@@ -1,25 +1,50 @@
 use std::{
-    collections::HashMap,
-    sync::mpsc::{channel, Receiver},
-    thread::{current, spawn, yield_now, ThreadId},
+    collections::{HashMap, VecDeque},
+    path::{Path, PathBuf},
+    sync::{
+        mpsc::{channel, Receiver},
+        Arc, Mutex,
+    },
+    thread::{current, sleep, spawn, yield_now, ThreadId},
+    time::{Duration, Instant},
 };
 
 use glfw::*;
 use gom::*;
+use lazy_static::lazy_static;
 
-use crate::{debug, error, warn};
+use crate::error::Result;
+use crate::{
+    debug, error, warn, Camera, CommandLine, DebugOverlay, EngineConfig, EnginePlugin, GlObjects,
+    Input, Log, RenderStats, ResourceKey, Resources, Stage, WindowGeometry,
+};
 const GLFW: &str = id!(GLFW);
 const APP: &str = id!(APP);
 /// 窗口实例ID
 pub const WINDOW: &str = id!(@GLFW.WINODW);
-const EVENT_MS: &str = id!(@WINDOW.EVENT_MS);
-const RENDER_MS: &str = id!(@WINDOW.RENDER_MS);
-const CATON: &str = id!(@WINDOW.CATON);
+/// 窗口实例的资源键，通过 [`Resources`] 访问
+pub const WINDOW_KEY: ResourceKey<PWindow> = ResourceKey::new(WINDOW);
+const EVENT_MS: ResourceKey<f64> = ResourceKey::new(id!(@WINDOW.EVENT_MS));
+const RENDER_MS: ResourceKey<f64> = ResourceKey::new(id!(@WINDOW.RENDER_MS));
+const CATON: ResourceKey<f64> = ResourceKey::new(id!(@WINDOW.CATON));
+const RENDER_COMMAND_MS: ResourceKey<f64> = ResourceKey::new(id!(@WINDOW.RENDER_COMMAND_MS));
+const HEADLESS: ResourceKey<bool> = ResourceKey::new(id!(@APP.HEADLESS));
+const EXIT: ResourceKey<bool> = ResourceKey::new(id!(@APP.EXIT));
+const ASSET_ROOT: ResourceKey<PathBuf> = ResourceKey::new(id!(@APP.ASSET_ROOT));
+
+type RenderCommand = Box<dyn FnOnce() + Send>;
+type System = Box<dyn FnMut() + 'static + Send>;
+type EventHandler = Box<dyn FnMut(&WindowEvent) + 'static + Send>;
+type SharedGeometry = Arc<Mutex<WindowGeometry>>;
+
+lazy_static! {
+    static ref RENDER_COMMANDS: Mutex<VecDeque<RenderCommand>> = Mutex::new(VecDeque::new());
+}
 
-const THREAD_NAMES: &str = id!(@APP.THREAD_NAMES);
 type NameTable = HashMap<ThreadId, String>;
+const THREAD_NAMES: ResourceKey<NameTable> = ResourceKey::new(id!(@APP.THREAD_NAMES));
 
-pub use glfw::{Action, CursorMode, Key, Modifiers};
+pub use glfw::{Action, CursorMode, Key, Modifiers, MouseButton, WindowEvent};
 
 /// 用于构建App实例
 ///
@@ -33,17 +58,29 @@ pub use glfw::{Action, CursorMode, Key, Modifiers};
 pub struct AppBuilder {
     size: (i32, i32),
     title: String,
+    headless: bool,
+    fullscreen: bool,
+    tick_rate: f64,
+    upload_thread: bool,
+    vsync: Option<bool>,
+    samples: u32,
+    asset_root: PathBuf,
+    remember_geometry: Option<String>,
     render_init: Option<Box<dyn FnOnce() + 'static + Send>>,
     render_loop: Option<Box<dyn FnMut() + 'static + Send>>,
     event_init: Option<Box<dyn FnOnce() + 'static + Send>>,
     event_loop: Option<Box<dyn FnMut() + 'static + Send>>,
     window_size_callback: Option<Box<dyn FnMut(i32, i32) + 'static + Send>>,
     window_pos_callback: Option<Box<dyn FnMut(i32, i32) + 'static + Send>>,
+    framebuffer_size_callback: Option<Box<dyn FnMut(i32, i32) + 'static + Send>>,
     window_close_callback: Option<Box<dyn FnMut() + 'static + Send>>,
     key_callback: Option<Box<dyn FnMut(Key, i32, Action, Modifiers) + 'static + Send>>,
     mouse_button_callback: Option<Box<dyn FnMut(MouseButton, Action, Modifiers) + 'static + Send>>,
     cursor_pos_callback: Option<Box<dyn FnMut(f64, f64) + 'static + Send>>,
     scroll_callback: Option<Box<dyn FnMut(f64, f64) + 'static + Send>>,
+    plugins: Vec<String>,
+    systems: Vec<(Stage, System)>,
+    event_handlers: Vec<EventHandler>,
 }
 
 impl AppBuilder {
@@ -60,18 +97,279 @@ impl AppBuilder {
         Self {
             size: (width, height),
             title: title.to_string(),
+            headless: false,
+            fullscreen: false,
+            tick_rate: 60.0,
+            upload_thread: false,
+            vsync: None,
+            samples: 0,
+            asset_root: PathBuf::from("assets"),
+            remember_geometry: None,
             render_init: None,
             render_loop: None,
             event_init: None,
             event_loop: None,
             window_size_callback: None,
             window_pos_callback: None,
+            framebuffer_size_callback: None,
             window_close_callback: None,
             key_callback: None,
             mouse_button_callback: None,
             cursor_pos_callback: None,
             scroll_callback: None,
+            plugins: Vec::new(),
+            systems: Vec::new(),
+            event_handlers: Vec::new(),
+        }
+    }
+
+    /// 创建一个无头模式的`AppBuilder`实例
+    ///
+    /// 无头模式下不初始化 GLFW，不创建窗口与OpenGL上下文，只在主线程中以固定频率运行事件循环，
+    /// 用于在没有显示设备的机器上运行专用服务器
+    ///
+    /// # 返回值
+    /// 返回一个新的`AppBuilder`实例
+    ///
+    /// # 示例
+    ///
+    /// ```
+    /// use gle::AppBuilder;
+    ///
+    /// let mut app = AppBuilder::headless()
+    ///     .set_tick_rate(20.0)
+    ///     .set_event_loop(|| {
+    ///         // 更新世界、处理网络消息
+    ///     })
+    ///     .build();
+    /// app.exec();
+    /// ```
+    ///
+    /// # 注解
+    ///
+    /// 渲染线程的函数与窗口、输入回调函数均被忽略，
+    /// 通过 [`App::run_on_render_thread`] 提交的命令被直接丢弃，程序通过 [`App::exit`] 退出
+    pub fn headless() -> Self {
+        Self {
+            headless: true,
+            ..Self::new(0, 0, "")
+        }
+    }
+
+    /// 从配置文件创建`AppBuilder`实例
+    ///
+    /// # 参数
+    /// + `path` - 配置文件路径，通常为`gle.toml`，格式见 [`EngineConfig`]；文件不存在时使用默认配置
+    ///
+    /// # 返回值
+    /// 成功时返回按配置设置好窗口、日志与资源目录的`AppBuilder`实例，配置文件格式错误时返回错误
+    ///
+    /// # 示例
+    ///
+    /// ```no_run
+    /// use gle::AppBuilder;
+    ///
+    /// let mut app = AppBuilder::from_config("gle.toml").unwrap().build();
+    /// app.exec();
+    /// ```
+    ///
+    /// # 注解
+    ///
+    /// 日志级别与日志文件在调用时立即生效，以便记录构建过程中的日志
+    pub fn from_config<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let config = if path.exists() {
+            EngineConfig::load(path)?
+        } else {
+            debug!(Self, "配置文件 {} 不存在，使用默认配置", path.display());
+            EngineConfig::default()
+        };
+        let mut builder = Self::new(config.window.width, config.window.height, "");
+        builder.apply_config(&config);
+        Ok(builder)
+    }
+
+    /// 从命令行参数与配置文件创建`AppBuilder`实例
+    ///
+    /// 先加载`--config`指定的配置文件(默认为`gle.toml`)，再用命令行选项覆盖其中的设置，
+    /// 选项见 [`CommandLine`]
+    ///
+    /// # 返回值
+    /// 成功时返回`AppBuilder`实例，命令行参数或配置文件格式错误时返回错误
+    ///
+    /// # 示例
+    ///
+    /// ```no_run
+    /// use gle::AppBuilder;
+    ///
+    /// // game.exe --width 1920 --height 1080 --fullscreen --log-level debug
+    /// let mut app = AppBuilder::from_env().unwrap().build();
+    /// app.exec();
+    /// ```
+    pub fn from_env() -> Result<Self> {
+        Self::from_command_line(&CommandLine::from_env()?)
+    }
+
+    /// 从命令行设置与配置文件创建`AppBuilder`实例
+    ///
+    /// # 参数
+    /// + `cli` - 命令行设置，覆盖配置文件中的设置
+    ///
+    /// # 返回值
+    /// 成功时返回`AppBuilder`实例，配置文件格式错误时返回错误
+    pub fn from_command_line(cli: &CommandLine) -> Result<Self> {
+        let path = cli.config.clone().unwrap_or_else(|| PathBuf::from("gle.toml"));
+        let mut config = if path.exists() {
+            EngineConfig::load(&path)?
+        } else {
+            EngineConfig::default()
+        };
+        cli.apply(&mut config);
+        let mut builder = Self::new(config.window.width, config.window.height, "");
+        builder.apply_config(&config);
+        if cli.headless {
+            builder.set_headless(true);
         }
+        Ok(builder)
+    }
+
+    /// 应用引擎配置
+    ///
+    /// # 参数
+    /// + `config` - 引擎配置，覆盖窗口大小、标题、全屏、垂直同步、多重采样、日志与资源目录的设置
+    ///
+    /// # 返回值
+    /// 返回`AppBuilder`实例本身
+    pub fn apply_config(&mut self, config: &EngineConfig) -> &mut Self {
+        Log::set_level(config.log.level);
+        Log::set_file(config.log.file.as_ref().map(|f| f.display().to_string()));
+        self.set_size(config.window.width, config.window.height)
+            .set_title(&config.window.title)
+            .set_fullscreen(config.window.fullscreen)
+            .set_vsync(config.window.vsync)
+            .set_samples(config.window.msaa)
+            .set_asset_root(&config.assets.root)
+    }
+
+    /// 设置窗口大小
+    ///
+    /// # 参数
+    /// + `width` - 窗口宽度
+    /// + `height` - 窗口高度
+    ///
+    /// # 返回值
+    /// 返回`AppBuilder`实例本身
+    pub fn set_size(&mut self, width: i32, height: i32) -> &mut Self {
+        self.size = (width, height);
+        self
+    }
+
+    /// 设置窗口标题
+    ///
+    /// # 返回值
+    /// 返回`AppBuilder`实例本身
+    pub fn set_title(&mut self, title: &str) -> &mut Self {
+        self.title = title.to_string();
+        self
+    }
+
+    /// 设置是否全屏
+    ///
+    /// # 参数
+    /// + `enabled` - 为`true`时在主显示器上以其当前分辨率全屏显示，忽略窗口大小(默认值为`false`)
+    ///
+    /// # 返回值
+    /// 返回`AppBuilder`实例本身
+    pub fn set_fullscreen(&mut self, enabled: bool) -> &mut Self {
+        self.fullscreen = enabled;
+        self
+    }
+
+    /// 设置是否以无头模式运行，见 [`AppBuilder::headless`]
+    ///
+    /// # 返回值
+    /// 返回`AppBuilder`实例本身
+    pub fn set_headless(&mut self, enabled: bool) -> &mut Self {
+        self.headless = enabled;
+        self
+    }
+
+    /// 设置是否开启垂直同步
+    ///
+    /// # 参数
+    /// + `enabled` - 为`true`时交换缓冲等待显示器刷新，未设置时使用驱动的默认行为
+    ///
+    /// # 返回值
+    /// 返回`AppBuilder`实例本身
+    pub fn set_vsync(&mut self, enabled: bool) -> &mut Self {
+        self.vsync = Some(enabled);
+        self
+    }
+
+    /// 设置默认帧缓冲的多重采样数
+    ///
+    /// # 参数
+    /// + `samples` - 采样数，为 0 时关闭多重采样(默认值为0)
+    ///
+    /// # 返回值
+    /// 返回`AppBuilder`实例本身
+    pub fn set_samples(&mut self, samples: u32) -> &mut Self {
+        self.samples = samples;
+        self
+    }
+
+    /// 设置资源根目录
+    ///
+    /// # 参数
+    /// + `root` - 资源根目录(默认值为`assets`)，见 [`App::asset_path`]
+    ///
+    /// # 返回值
+    /// 返回`AppBuilder`实例本身
+    pub fn set_asset_root<P: AsRef<Path>>(&mut self, root: P) -> &mut Self {
+        self.asset_root = root.as_ref().to_path_buf();
+        self
+    }
+
+    /// 设置是否记住窗口的位置与大小
+    ///
+    /// # 参数
+    /// + `name` - 程序名称，为`Some`时在退出时将窗口的位置、大小与最大化状态保存到平台配置目录，
+    ///   并在下次启动时恢复，保存位置见 [`WindowGeometry::path`]；为`None`时不保存(默认值)
+    ///
+    /// # 返回值
+    /// 返回`AppBuilder`实例本身
+    ///
+    /// # 注解
+    ///
+    /// 全屏与无头模式下不保存也不恢复。保存的位置不在任何已连接的显示器上时只恢复大小
+    pub fn set_remember_geometry(&mut self, name: Option<&str>) -> &mut Self {
+        self.remember_geometry = name.map(str::to_string);
+        self
+    }
+
+    /// 设置无头模式下事件循环的频率
+    ///
+    /// # 参数
+    /// + `hz` - 每秒运行事件循环的次数(默认值为60)，窗口模式下不生效
+    ///
+    /// # 返回值
+    /// 返回`AppBuilder`实例本身
+    pub fn set_tick_rate(&mut self, hz: f64) -> &mut Self {
+        self.tick_rate = hz;
+        self
+    }
+
+    /// 设置是否启用上传线程
+    ///
+    /// # 参数
+    /// + `enabled` - 为`true`时创建一个与主窗口共享对象的隐藏上下文，并在名为`UploadThread`的线程中执行
+    ///   [`App::upload`] 提交的上传任务(默认值为`false`)
+    ///
+    /// # 返回值
+    /// 返回`AppBuilder`实例本身
+    pub fn set_upload_thread(&mut self, enabled: bool) -> &mut Self {
+        self.upload_thread = enabled;
+        self
     }
 
     /// 设置渲染线程的初始化函数
@@ -160,6 +458,27 @@ impl AppBuilder {
         self
     }
 
+    /// 设置帧缓冲大小变化回调函数
+    ///
+    /// # 参数
+    /// + `f` - 一个函数，它将在窗口帧缓冲大小发生变化时被调用，该函数接受两个参数：`fn(width: i32, height: i32)`
+    ///         + `width` - 帧缓冲宽度，单位为像素
+    ///         + `height` - 帧缓冲高度，单位为像素
+    ///
+    /// # 返回值
+    /// 返回`AppBuilder`实例本身
+    ///
+    /// # 注解
+    ///
+    /// 主摄像机的宽高比会在该回调函数被调用前自动更新
+    pub fn set_framebuffer_size_callback<F: 'static + FnMut(i32, i32) + Send>(
+        &mut self,
+        f: F,
+    ) -> &mut Self {
+        self.framebuffer_size_callback = Some(Box::new(f));
+        self
+    }
+
     /// 设置窗口关闭回调函数
     ///
     /// # 参数
@@ -240,124 +559,368 @@ impl AppBuilder {
         self
     }
 
+    /// 添加插件
+    ///
+    /// # 参数
+    /// + `plugin` - 插件，其 [`EnginePlugin::build`] 将被立即调用；同名插件只会被添加一次
+    ///
+    /// # 返回值
+    /// 返回`AppBuilder`实例本身
+    pub fn add_plugin<P: EnginePlugin>(&mut self, plugin: P) -> &mut Self {
+        let name = plugin.name().to_string();
+        if self.has_plugin(&name) {
+            warn!(Self, "插件 {} 已添加，忽略重复添加", name);
+            return self;
+        }
+        debug!(Self, "正在添加插件 {}", name);
+        self.plugins.push(name);
+        plugin.build(self);
+        self
+    }
+
+    /// 判断插件是否已添加
+    ///
+    /// # 参数
+    /// + `name` - 插件名称，见 [`EnginePlugin::name`]
+    pub fn has_plugin(&self, name: &str) -> bool {
+        self.plugins.iter().any(|p| p == name)
+    }
+
+    /// 添加系统
+    ///
+    /// # 参数
+    /// + `stage` - 系统运行的阶段
+    /// + `f` - 一个函数，它将在指定阶段被调用；与`set_*`系列函数不同，多次添加的系统不会相互覆盖
+    ///
+    /// # 返回值
+    /// 返回`AppBuilder`实例本身
+    ///
+    /// # 注解
+    ///
+    /// 无头模式下渲染线程阶段的系统被忽略
+    pub fn add_system<F: 'static + FnMut() + Send>(&mut self, stage: Stage, f: F) -> &mut Self {
+        self.systems.push((stage, Box::new(f)));
+        self
+    }
+
+    /// 添加窗口事件处理函数
+    ///
+    /// # 参数
+    /// + `f` - 一个函数，它将在主线程处理窗口事件时被调用，先于`set_*_callback`设置的回调函数；
+    ///   会收到窗口位置、大小、帧缓冲大小、关闭、焦点、按键、字符输入、鼠标按键、光标位置、滚轮与文件拖放事件
+    ///
+    /// # 返回值
+    /// 返回`AppBuilder`实例本身
+    pub fn add_event_handler<F: 'static + FnMut(&WindowEvent) + Send>(
+        &mut self,
+        f: F,
+    ) -> &mut Self {
+        self.event_handlers.push(Box::new(f));
+        self
+    }
+
+    /// 在全局注册表中注册资源
+    ///
+    /// # 参数
+    /// + `id` - 资源ID，通常由`id!`宏生成
+    /// + `value` - 资源，已存在的同ID资源将被替换
+    ///
+    /// # 返回值
+    /// 返回`AppBuilder`实例本身
+    pub fn insert_resource<T: 'static + Send + Sync>(&mut self, id: &str, value: T) -> &mut Self {
+        if Registry::register(id, value).is_err() {
+            error!(Self, "无法注册资源 {}", id);
+        }
+        self
+    }
+
+    /// 取出指定阶段的系统
+    fn take_systems(&mut self, stage: Stage) -> Vec<System> {
+        let (taken, rest): (Vec<_>, Vec<_>) =
+            std::mem::take(&mut self.systems).into_iter().partition(|(s, _)| *s == stage);
+        self.systems = rest;
+        taken.into_iter().map(|(_, f)| f).collect()
+    }
+
     /// 构建`App`实例
     ///
     /// # 返回值
     /// 返回一个新的`App`实例
     pub fn build(&mut self) -> App {
         App::set_current_thread_name("MainThread");
-        if Registry::<PWindow>::exists(WINDOW) {
+        if Resources::exists(WINDOW_KEY) || Resources::exists(HEADLESS) {
             error!(Self, "已存在一个 App 实例");
             panic!("重复创建 App 实例");
         }
+        Resources::insert(EXIT, false);
+        Resources::insert(HEADLESS, self.headless);
+        Resources::insert(ASSET_ROOT, self.asset_root.clone());
+        let event_systems = EventSystems {
+            startup: self.take_systems(Stage::EventStartup),
+            pre: self.take_systems(Stage::PreEvent),
+            post: self.take_systems(Stage::PostEvent),
+        };
+        if self.headless {
+            debug!(Self, "以无头模式运行，不创建窗口");
+            return App {
+                glfw: None,
+                tick: Some(Duration::from_secs_f64(1.0 / self.tick_rate.max(1e-3))),
+                event_init: self.event_init.take(),
+                event_loop: self.event_loop.take(),
+                event_systems,
+                geometry: None,
+                render_thread_exit: None,
+            };
+        }
         // 初始化GLFW环境并创建窗口实例
         debug!(Self, "正在初始化 GLFW 环境...");
         let mut glfw = init(fail_on_errors).unwrap();
         glfw.window_hint(WindowHint::Visible(false));
-        let (window, _) = glfw
-            .create_window(
-                self.size.0 as _,
-                self.size.1 as _,
-                &self.title,
-                WindowMode::Windowed,
-            )
+        if self.samples > 0 {
+            glfw.window_hint(WindowHint::Samples(Some(self.samples)));
+        }
+        let (size, title, fullscreen) = (self.size, self.title.as_str(), self.fullscreen);
+        let (mut window, _) = glfw
+            .with_primary_monitor(|glfw, monitor| {
+                let mode = monitor.and_then(|m| Some((m.get_video_mode()?, m)));
+                match mode {
+                    Some((video, monitor)) if fullscreen => glfw.create_window(
+                        video.width,
+                        video.height,
+                        title,
+                        WindowMode::FullScreen(monitor),
+                    ),
+                    _ => glfw.create_window(size.0 as _, size.1 as _, title, WindowMode::Windowed),
+                }
+            })
             .unwrap();
-        Registry::register(WINDOW, window).unwrap();
+        let geometry_path = match self.remember_geometry.as_deref() {
+            Some(name) if !fullscreen => WindowGeometry::path(name),
+            _ => None,
+        };
+        let saved = geometry_path.as_ref().and_then(|path| WindowGeometry::load(path).ok());
+        if let Some(saved) = saved {
+            let (x, y, w, h) = (saved.x, saved.y, saved.width, saved.height);
+            debug!(Self, "恢复窗口位置 ({}, {})，大小 {}x{}", x, y, w, h);
+            window.set_size(saved.width.max(1), saved.height.max(1));
+            let visible = glfw.with_connected_monitors(|_, monitors| {
+                monitors.iter().any(|m| {
+                    let (mx, my) = m.get_pos();
+                    m.get_video_mode().is_some_and(|v| {
+                        (mx..mx + v.width as i32).contains(&saved.x)
+                            && (my..my + v.height as i32).contains(&saved.y)
+                    })
+                })
+            });
+            if visible {
+                window.set_pos(saved.x, saved.y);
+            }
+        }
+        let geometry: Option<(PathBuf, SharedGeometry)> = geometry_path.map(|path| {
+            let ((x, y), (width, height)) = (window.get_pos(), window.get_size());
+            let maximized = false;
+            let current = WindowGeometry { x, y, width, height, maximized };
+            (path, Arc::new(Mutex::new(current)))
+        });
+        let upload_window = if self.upload_thread {
+            debug!(Self, "正在创建共享上下文...");
+            let shared = window.create_shared(1, 1, "", WindowMode::Windowed);
+            if shared.is_none() {
+                warn!(Self, "无法创建共享上下文，上传任务将在渲染线程中执行");
+            }
+            shared.map(|(w, _)| w)
+        } else {
+            None
+        };
+        Resources::insert(WINDOW_KEY, window);
         // 注册窗口回调函数
         debug!(Self, "正在注册回调函数...");
         let mut window_size_callback = self.window_size_callback.take();
         let mut window_pos_callback = self.window_pos_callback.take();
+        let mut framebuffer_size_callback = self.framebuffer_size_callback.take();
         let mut window_close_callback = self.window_close_callback.take();
         let mut key_callback = self.key_callback.take();
         let mut mouse_button_callback = self.mouse_button_callback.take();
         let mut cursor_pos_callback = self.cursor_pos_callback.take();
         let mut scroll_callback = self.scroll_callback.take();
-        Registry::apply(WINDOW, |w: &mut PWindow| {
-            w.set_size_callback(move |_, width, height| {
+        let handlers = Arc::new(Mutex::new(std::mem::take(&mut self.event_handlers)));
+        let tracked_size = geometry.as_ref().map(|(_, g)| g.clone());
+        let tracked_pos = tracked_size.clone();
+        let emitter = move || {
+            let handlers = handlers.clone();
+            move |event: WindowEvent| {
+                for f in handlers.lock().unwrap().iter_mut() {
+                    f(&event);
+                }
+            }
+        };
+        Resources::apply(WINDOW_KEY, |w| {
+            let emit = emitter();
+            w.set_size_callback(move |w, width, height| {
+                if let Some(g) = tracked_size.as_ref().filter(|_| is_restored(w)) {
+                    let mut g = g.lock().unwrap();
+                    (g.width, g.height) = (width, height);
+                }
+                emit(WindowEvent::Size(width, height));
                 if let Some(f) = window_size_callback.as_mut() {
                     f(width, height);
                 }
             });
-            w.set_pos_callback(move |_, x: i32, y: i32| {
+            let emit = emitter();
+            w.set_pos_callback(move |w, x: i32, y: i32| {
+                if let Some(g) = tracked_pos.as_ref().filter(|_| is_restored(w)) {
+                    let mut g = g.lock().unwrap();
+                    (g.x, g.y) = (x, y);
+                }
+                emit(WindowEvent::Pos(x, y));
                 if let Some(f) = window_pos_callback.as_mut() {
                     f(x, y);
                 }
             });
+            let emit = emitter();
+            w.set_framebuffer_size_callback(move |_, width, height| {
+                Camera::apply_main(|c| c.set_target_size(width, height));
+                emit(WindowEvent::FramebufferSize(width, height));
+                if let Some(f) = framebuffer_size_callback.as_mut() {
+                    f(width, height);
+                }
+            });
+            let emit = emitter();
             w.set_close_callback(move |_| {
+                emit(WindowEvent::Close);
                 if let Some(f) = window_close_callback.as_mut() {
                     f();
                 }
             });
+            let emit = emitter();
+            w.set_focus_callback(move |_, focused| emit(WindowEvent::Focus(focused)));
+            let emit = emitter();
             w.set_key_callback(move |_, k, s, a, m| {
+                Input::_on_key(k, a);
+                emit(WindowEvent::Key(k, s, a, m));
                 if let Some(f) = key_callback.as_mut() {
                     f(k, s, a, m);
                 }
             });
+            let emit = emitter();
+            w.set_char_callback(move |_, ch| {
+                Input::_on_char(ch);
+                emit(WindowEvent::Char(ch));
+            });
+            let emit = emitter();
             w.set_mouse_button_callback(move |_, mb, a, m| {
+                Input::_on_mouse_button(mb, a);
+                emit(WindowEvent::MouseButton(mb, a, m));
                 if let Some(f) = mouse_button_callback.as_mut() {
                     f(mb, a, m);
                 }
             });
+            let emit = emitter();
             w.set_cursor_pos_callback(move |_, x, y| {
+                Input::_on_cursor_pos(x, y);
+                emit(WindowEvent::CursorPos(x, y));
                 if let Some(f) = cursor_pos_callback.as_mut() {
                     f(x, y);
                 }
             });
+            let emit = emitter();
             w.set_scroll_callback(move |_, x, y| {
+                Input::_on_scroll(x, y);
+                emit(WindowEvent::Scroll(x, y));
                 if let Some(f) = scroll_callback.as_mut() {
                     f(x, y);
                 }
             });
+            let emit = emitter();
+            w.set_drag_and_drop_callback(move |_, paths| emit(WindowEvent::FileDrop(paths)));
         });
         // 启动渲染循环
         debug!(Self, "正在启动渲染线程...");
         let (show_window, render_initialized) = channel();
         let render_init = self.render_init.take().unwrap_or_else(|| Box::new(|| {}));
         let mut render_loop = self.render_loop.take().unwrap_or_else(|| Box::new(|| {}));
+        let mut render_startup = self.take_systems(Stage::RenderStartup);
+        let mut pre_render = self.take_systems(Stage::PreRender);
+        let mut post_render = self.take_systems(Stage::PostRender);
         let (event_loop_exit, render_thread_exit) = channel();
+        let vsync = self.vsync;
         spawn(move || {
             App::set_current_thread_name("RenderThread");
-            Registry::apply(WINDOW, |w: &mut PWindow| w.make_current());
+            Resources::apply(WINDOW_KEY, |w| w.make_current());
             gl::load_with(|s| {
-                Registry::apply(WINDOW, |w: &mut PWindow| w.get_proc_address(s)).unwrap()
+                Resources::apply(WINDOW_KEY, |w| w.get_proc_address(s)).unwrap()
             });
+            if let Some(vsync) = vsync {
+                // 交换间隔作用于当前线程的上下文
+                unsafe { glfw::ffi::glfwSwapInterval(vsync as i32) };
+            }
 
+            render_startup.iter_mut().for_each(|f| f());
             render_init();
             show_window.send(()).unwrap();
             let mut last_render_ms = chrono::Local::now().timestamp_micros() as f64 / 1000.0;
-            while Registry::with(WINDOW, |w: &PWindow| !w.should_close()).unwrap_or(false) {
+            while Resources::with(WINDOW_KEY, |w| !w.should_close()).unwrap_or(false) {
                 let render_ms = chrono::Local::now().timestamp_micros() as f64 / 1000.0;
                 let dt = render_ms - last_render_ms;
                 last_render_ms = render_ms;
-                let caton = Registry::with(CATON, |caton: &f64| *caton).unwrap_or(16.67);
+                let caton = Resources::with(CATON, |caton| *caton).unwrap_or(16.67);
                 if dt > caton {
                     warn!(Self, "渲染时间 {:.2}ms 超过 {:.2}ms", dt, caton);
                 }
-                Registry::register(RENDER_MS, dt).unwrap();
-                Registry::with(WINDOW, |window: &PWindow| {
+                Resources::insert(RENDER_MS, dt);
+                Resources::with(WINDOW_KEY, |window| {
                     let (w, h) = window.get_size();
                     unsafe { gl::Viewport(0, 0, w, h) };
                 });
 
+                App::_run_render_commands();
+                pre_render.iter_mut().for_each(|f| f());
                 render_loop();
-                Registry::apply(WINDOW, |w: &mut PWindow| w.swap_buffers());
+                post_render.iter_mut().for_each(|f| f());
+                RenderStats::_end_frame();
+                Resources::apply(WINDOW_KEY, |w| w.swap_buffers());
             }
+            // 先释放渲染循环与系统持有的资源，剩余的对象即为泄漏
+            drop((render_loop, render_startup, pre_render, post_render));
+            crate::fullscreen::_release_fullscreen_vao();
+            GlObjects::report_leaks();
             debug!(Self, "渲染线程退出");
             event_loop_exit.send(()).unwrap();
         });
         render_initialized.recv().unwrap();
+        if let Some(upload_window) = upload_window {
+            crate::upload::start_upload_thread(upload_window);
+        }
         debug!(Self, "显示窗口");
-        Registry::apply(WINDOW, |w: &mut PWindow| w.show());
+        Resources::apply(WINDOW_KEY, |w| w.show());
+        if saved.is_some_and(|g| g.maximized) {
+            Resources::apply(WINDOW_KEY, |w| w.maximize());
+        }
         // 返回 App 实例
         App {
-            glfw,
+            glfw: Some(glfw),
+            tick: None,
             event_init: self.event_init.take(),
             event_loop: self.event_loop.take(),
-            render_thread_exit,
+            event_systems,
+            geometry,
+            render_thread_exit: Some(render_thread_exit),
         }
     }
 }
 
+/// 窗口是否处于既未最大化也未最小化的状态
+fn is_restored(window: &Window) -> bool {
+    !window.is_maximized() && !window.is_iconified()
+}
+
+/// 在事件线程中运行的插件系统
+struct EventSystems {
+    startup: Vec<System>,
+    pre: Vec<System>,
+    post: Vec<System>,
+}
+
 /// 用于运行App实例
 ///
 /// # 示例
@@ -369,10 +932,13 @@ impl AppBuilder {
 /// app.exec();
 /// ```
 pub struct App {
-    glfw: Glfw,
+    glfw: Option<Glfw>,
+    tick: Option<Duration>,
     event_init: Option<Box<dyn FnOnce() + 'static + Send>>,
     event_loop: Option<Box<dyn FnMut() + 'static + Send>>,
-    render_thread_exit: Receiver<()>,
+    event_systems: EventSystems,
+    geometry: Option<(PathBuf, SharedGeometry)>,
+    render_thread_exit: Option<Receiver<()>>,
 }
 
 impl App {
@@ -381,38 +947,127 @@ impl App {
         debug!(Self, "正在启动事件循环...");
         let event_init = self.event_init.take().unwrap_or_else(|| Box::new(|| {}));
         let mut event_loop = self.event_loop.take().unwrap_or_else(|| Box::new(|| {}));
+        self.event_systems.startup.iter_mut().for_each(|f| f());
         event_init();
         let mut last_event_ms = chrono::Local::now().timestamp_micros() as f64 / 1000.0;
+        let mut next_tick = Instant::now();
         loop {
-            if let Ok(_) = self.render_thread_exit.try_recv() {
-                break;
+            if let Some(exit) = self.render_thread_exit.as_ref() {
+                if let Ok(_) = exit.try_recv() {
+                    break;
+                }
+            }
+            match self.tick {
+                Some(tick) => {
+                    // 无头模式下没有渲染线程，由 App::exit 设置的标志结束循环
+                    if Resources::with(EXIT, |exit| *exit).unwrap_or(false) {
+                        break;
+                    }
+                    // 无头模式下按固定频率运行，落后过多时不追赶
+                    let now = Instant::now();
+                    if next_tick > now {
+                        sleep(next_tick - now);
+                    }
+                    next_tick = (next_tick + tick).max(Instant::now() - tick);
+                }
+                None => yield_now(),
             }
-            yield_now();
 
             let event_ms = chrono::Local::now().timestamp_micros() as f64 / 1000.0;
             let dt = event_ms - last_event_ms;
             last_event_ms = event_ms;
-            Registry::register(EVENT_MS, dt).unwrap();
+            Resources::insert(EVENT_MS, dt);
 
+            self.event_systems.pre.iter_mut().for_each(|f| f());
             event_loop();
-            self.glfw.poll_events();
+            self.event_systems.post.iter_mut().for_each(|f| f());
+            DebugOverlay::_poll_toggle();
+            Input::_end_frame();
+            if let Some(glfw) = self.glfw.as_mut() {
+                glfw.poll_events();
+            }
         }
         debug!(Self, "事件循环退出");
+        if let Some((path, geometry)) = self.geometry.take() {
+            let mut geometry = *geometry.lock().unwrap();
+            geometry.maximized =
+                Resources::with(WINDOW_KEY, |w| w.is_maximized()).unwrap_or(false);
+            match geometry.save(&path) {
+                Ok(()) => {
+                    debug!(Self, "窗口状态已保存到 {}", path.display());
+                }
+                Err(e) => {
+                    warn!(Self, "无法保存窗口状态到 {}: {}", path.display(), e);
+                }
+            }
+        }
     }
 
     /// 退出程序
+    ///
+    /// # 注解
+    ///
+    /// 有窗口时关闭窗口，事件循环在渲染线程完成清理并退出后结束；无头模式下在下一次循环时结束
     pub fn exit() {
-        Registry::apply(WINDOW, |w: &mut PWindow| {
+        if App::is_headless() {
+            Resources::insert(EXIT, true);
+            return;
+        }
+        Resources::apply(WINDOW_KEY, |w| {
             w.set_should_close(true);
         });
     }
 
+    /// 是否以无头模式运行
+    ///
+    /// # 返回值
+    /// 通过 [`AppBuilder::headless`] 构建时返回`true`
+    pub fn is_headless() -> bool {
+        Resources::with(HEADLESS, |headless| *headless).unwrap_or(false)
+    }
+
     /// 获取窗口大小
     ///
     /// # 返回值
-    /// 返回窗口的宽度和高度
+    /// 返回窗口的宽度和高度，无头模式下返回`(0, 0)`
     pub fn window_size() -> (i32, i32) {
-        Registry::with(WINDOW, |w: &PWindow| w.get_size()).unwrap()
+        Resources::with(WINDOW_KEY, |w| w.get_size()).unwrap_or((0, 0))
+    }
+
+    /// 获取窗口的内容缩放比例
+    ///
+    /// # 返回值
+    /// 返回操作系统设置的横向与纵向缩放比例，如 150% 缩放时为`(1.5, 1.5)`，无头模式下返回`(1.0, 1.0)`
+    pub fn content_scale() -> (f32, f32) {
+        Resources::with(WINDOW_KEY, |w| w.get_content_scale()).unwrap_or((1.0, 1.0))
+    }
+
+    /// 获取资源根目录
+    ///
+    /// # 返回值
+    /// 返回通过 [`AppBuilder::set_asset_root`] 或配置文件设置的目录，默认为`assets`
+    pub fn asset_root() -> PathBuf {
+        Resources::with(ASSET_ROOT, |root| root.clone())
+            .unwrap_or_else(|| PathBuf::from("assets"))
+    }
+
+    /// 获取资源的路径
+    ///
+    /// # 参数
+    /// + `path` - 相对于资源根目录的路径
+    ///
+    /// # 返回值
+    /// 返回资源根目录与`path`拼接后的路径
+    ///
+    /// # 示例
+    ///
+    /// ```no_run
+    /// use gle::*;
+    ///
+    /// let sfx = SoundBank::load_dir(App::asset_path("sfx")).unwrap();
+    /// ```
+    pub fn asset_path<P: AsRef<Path>>(path: P) -> PathBuf {
+        Self::asset_root().join(path)
     }
 
     /// 获取事件循环最近一帧的运行时间
@@ -420,7 +1075,7 @@ impl App {
     /// # 返回值
     /// 返回事件循环最近一帧的运行时间，单位为毫秒
     pub fn event_ms() -> f64 {
-        Registry::with(EVENT_MS, |ms: &f64| *ms).unwrap_or(0.0)
+        Resources::with(EVENT_MS, |ms| *ms).unwrap_or(0.0)
     }
 
     /// 获取渲染循环最近一帧的运行时间
@@ -428,7 +1083,7 @@ impl App {
     /// # 返回值
     /// 返回渲染循环最近一帧的运行时间，单位为毫秒
     pub fn render_ms() -> f64 {
-        Registry::with(RENDER_MS, |ms: &f64| *ms).unwrap_or(0.0)
+        Resources::with(RENDER_MS, |ms| *ms).unwrap_or(0.0)
     }
 
     /// 获取事件循环的帧率
@@ -455,12 +1110,12 @@ impl App {
     ///   + `CursorMode::Hidden` - 隐藏模式
     ///   + `CursorMode::Disabled` - 禁用模式
     pub fn set_cursor_mode(mode: CursorMode) {
-        Registry::apply(WINDOW, |w: &mut PWindow| w.set_cursor_mode(mode));
+        Resources::apply(WINDOW_KEY, |w| w.set_cursor_mode(mode));
     }
 
     fn _lazy_init_thread_names() {
-        if !Registry::<NameTable>::exists(THREAD_NAMES) {
-            Registry::<NameTable>::register(THREAD_NAMES, HashMap::new()).unwrap();
+        if !Resources::exists(THREAD_NAMES) {
+            Resources::insert(THREAD_NAMES, HashMap::new());
         }
     }
 
@@ -471,7 +1126,7 @@ impl App {
     pub fn set_current_thread_name(name: &str) {
         Self::_lazy_init_thread_names();
         let thread_id = current().id();
-        Registry::apply(THREAD_NAMES, |map: &mut NameTable| {
+        Resources::apply(THREAD_NAMES, |map| {
             map.insert(thread_id, String::from(name));
         });
     }
@@ -479,7 +1134,7 @@ impl App {
     fn _get_thread_name() -> Option<String> {
         Self::_lazy_init_thread_names();
         let thread_id = current().id();
-        Registry::with(THREAD_NAMES, |map: &NameTable| {
+        Resources::with(THREAD_NAMES, |map| {
             map.get(&thread_id).cloned()
         })?
     }
@@ -497,6 +1152,52 @@ impl App {
     /// # 参数
     /// + `caton` - 临界时长，单位为毫秒(默认值为16.67)
     pub fn set_caton(caton: f64) {
-        Registry::register(CATON, caton).unwrap();
+        Resources::insert(CATON, caton);
+    }
+
+    /// 将命令提交到渲染线程执行
+    ///
+    /// # 参数
+    /// + `f` - 一个函数，它将在渲染线程中、下一次调用渲染循环函数之前被调用
+    ///
+    /// # 注解
+    ///
+    /// 用于在其他线程中完成耗时工作后，将上传缓冲等需要OpenGL上下文的操作交给渲染线程。
+    /// 命令按提交顺序执行，每帧执行的总时长受 [`App::set_render_command_budget`] 限制，未执行的命令顺延到下一帧。
+    /// 无头模式下没有渲染线程，提交的命令被丢弃
+    pub fn run_on_render_thread<F: 'static + FnOnce() + Send>(f: F) {
+        if App::is_headless() {
+            warn!(Self, "无头模式下没有渲染线程，已丢弃提交的渲染命令");
+            return;
+        }
+        RENDER_COMMANDS.lock().unwrap().push_back(Box::new(f));
+    }
+
+    /// 获取等待在渲染线程执行的命令数量
+    pub fn pending_render_commands() -> usize {
+        RENDER_COMMANDS.lock().unwrap().len()
+    }
+
+    /// 设置渲染线程每帧执行提交命令的时间预算
+    ///
+    /// # 参数
+    /// + `ms` - 时间预算，单位为毫秒(默认值为2)，每帧至少执行一条命令
+    pub fn set_render_command_budget(ms: f64) {
+        Resources::insert(RENDER_COMMAND_MS, ms);
+    }
+
+    fn _run_render_commands() {
+        let budget = Resources::with(RENDER_COMMAND_MS, |ms| *ms).unwrap_or(2.0);
+        let deadline = Instant::now() + Duration::from_secs_f64(budget.max(0.0) / 1000.0);
+        loop {
+            // 执行命令时不持有锁，命令中可以继续提交命令
+            let Some(command) = RENDER_COMMANDS.lock().unwrap().pop_front() else {
+                break;
+            };
+            command();
+            if Instant::now() >= deadline {
+                break;
+            }
+        }
     }
 }
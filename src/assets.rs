@@ -0,0 +1,596 @@
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::hash::{Hash, Hasher};
+use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::Arc;
+use std::thread::{sleep, spawn};
+use std::time::{Duration, Instant, SystemTime};
+
+use crate::error::{Error, Result};
+use crate::{debug, info, warn, App, GpuMesh, JobPool, ObjModel, Sound, Texture2D};
+
+static NEXT_ASSET_ID: AtomicU64 = AtomicU64::new(1);
+
+/// 在渲染线程中完成资源创建的函数，通常负责上传到 GPU
+type Finalize<T> = Box<dyn FnOnce() -> Result<T> + Send>;
+/// 在工作线程中读取并解码资源的函数
+type AsyncLoader<T> = Arc<dyn Fn(&str) -> Result<Finalize<T>> + Send + Sync>;
+
+/// 资源的加载状态
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LoadState {
+    /// 正在加载
+    Loading,
+    /// 已加载，可以使用
+    Ready,
+    /// 加载失败，附带错误信息
+    Failed(String),
+}
+
+/// 资源句柄
+///
+/// 指向 [`Assets`] 中的一个资源，克隆句柄只增加引用计数。
+/// 所有句柄都被释放后，资源可以由 [`Assets::remove_unused`] 卸载
+pub struct Handle<T> {
+    id: Arc<u64>,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T> Handle<T> {
+    /// 获取句柄的唯一ID
+    pub fn id(&self) -> u64 {
+        *self.id
+    }
+}
+
+impl<T> Clone for Handle<T> {
+    fn clone(&self) -> Self {
+        Self {
+            id: self.id.clone(),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T> PartialEq for Handle<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.id() == other.id()
+    }
+}
+
+impl<T> Eq for Handle<T> {}
+
+impl<T> Hash for Handle<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.id().hash(state);
+    }
+}
+
+impl<T> Debug for Handle<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Handle<{}>({})", std::any::type_name::<T>(), self.id())
+    }
+}
+
+struct Entry<T> {
+    /// 资源库自身持有的引用，强引用计数为 1 时说明外部已没有句柄
+    id: Arc<u64>,
+    path: Option<String>,
+    state: LoadState,
+    asset: Option<T>,
+    loader: Option<AsyncLoader<T>>,
+}
+
+/// 类型化的资源库
+///
+/// 以 [`Handle`] 引用资源，替代以字符串为键将 OpenGL 对象ID存入 `Registry` 的做法。
+/// 按路径加载的资源会被去重，同一路径只加载一次
+///
+/// # 示例
+///
+/// ```
+/// use gle::*;
+///
+/// let mut meshes: Assets<Vec<f32>> = Assets::new();
+/// let a = meshes.load("quad", |_| Ok(vec![0.0, 1.0, 2.0]));
+/// let b = meshes.load("quad", |_| unreachable!());
+/// assert_eq!(a, b);
+/// assert!(meshes.is_ready(&a));
+/// assert_eq!(meshes.get(&a).unwrap().len(), 3);
+///
+/// drop((a, b));
+/// assert_eq!(meshes.remove_unused(), 1);
+/// assert!(meshes.is_empty());
+/// ```
+///
+/// # 注解
+///
+/// 持有 OpenGL 对象的资源(如 [`Texture2D`])在被释放时删除对象，
+/// 这类资源库应只在渲染线程中使用
+pub struct Assets<T> {
+    /// 每次调用 [`Assets::update`] 完成资源创建的时间预算，单位为毫秒，每次至少完成一个
+    pub upload_budget: f64,
+    entries: HashMap<u64, Entry<T>>,
+    paths: HashMap<String, u64>,
+    sender: Sender<(u64, Result<Finalize<T>>)>,
+    receiver: Receiver<(u64, Result<Finalize<T>>)>,
+}
+
+impl<T> Default for Assets<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Assets<T> {
+    /// 创建空的资源库
+    pub fn new() -> Self {
+        let (sender, receiver) = channel();
+        Self {
+            upload_budget: 2.0,
+            entries: HashMap::new(),
+            paths: HashMap::new(),
+            sender,
+            receiver,
+        }
+    }
+
+    /// 添加资源
+    ///
+    /// # 返回值
+    /// 返回指向该资源的句柄
+    pub fn add(&mut self, asset: T) -> Handle<T> {
+        let handle = self.reserve(None);
+        self.insert(&handle, asset);
+        handle
+    }
+
+    /// 为尚未加载的资源预留句柄，状态为 [`LoadState::Loading`]
+    ///
+    /// # 参数
+    /// + `path` - 资源路径，为`Some`时可以通过 [`Assets::handle`] 按路径查找
+    pub fn reserve(&mut self, path: Option<&str>) -> Handle<T> {
+        let id = Arc::new(NEXT_ASSET_ID.fetch_add(1, Ordering::Relaxed));
+        let entry = Entry {
+            id: id.clone(),
+            path: path.map(str::to_string),
+            state: LoadState::Loading,
+            asset: None,
+            loader: None,
+        };
+        if let Some(path) = path {
+            self.paths.insert(path.to_string(), *id);
+        }
+        self.entries.insert(*id, entry);
+        Handle {
+            id,
+            _marker: PhantomData,
+        }
+    }
+
+    /// 按路径同步加载资源
+    ///
+    /// # 参数
+    /// + `path` - 资源路径，已加载或正在加载的路径直接返回已有的句柄
+    /// + `loader` - 加载函数，接受资源路径
+    ///
+    /// # 返回值
+    /// 返回句柄，加载失败时句柄的状态为 [`LoadState::Failed`]
+    pub fn load<F: FnOnce(&str) -> Result<T>>(&mut self, path: &str, loader: F) -> Handle<T> {
+        if let Some(handle) = self.handle(path) {
+            return handle;
+        }
+        let handle = self.reserve(Some(path));
+        match loader(path) {
+            Ok(asset) => {
+                self.insert(&handle, asset);
+            }
+            Err(e) => self.fail(&handle, e.to_string()),
+        }
+        handle
+    }
+
+    /// 设置句柄指向的资源，状态变为 [`LoadState::Ready`]
+    ///
+    /// # 返回值
+    /// 返回被替换的旧资源，可用于重新加载时替换已有资源而不使句柄失效
+    pub fn insert(&mut self, handle: &Handle<T>, asset: T) -> Option<T> {
+        let entry = self.entries.get_mut(&handle.id())?;
+        entry.state = LoadState::Ready;
+        entry.asset.replace(asset)
+    }
+
+    /// 将句柄标记为加载失败
+    pub fn fail(&mut self, handle: &Handle<T>, message: String) {
+        self.fail_id(handle.id(), message);
+    }
+
+    fn fail_id(&mut self, id: u64, message: String) {
+        if let Some(entry) = self.entries.get_mut(&id) {
+            warn!(
+                Self,
+                "资源 {} 加载失败: {}",
+                entry.path.as_deref().unwrap_or("<unnamed>"),
+                message
+            );
+            entry.state = LoadState::Failed(message);
+        }
+    }
+
+    /// 按路径查找句柄
+    pub fn handle(&self, path: &str) -> Option<Handle<T>> {
+        let entry = self.entries.get(self.paths.get(path)?)?;
+        Some(Handle {
+            id: entry.id.clone(),
+            _marker: PhantomData,
+        })
+    }
+
+    /// 获取资源的路径
+    pub fn path(&self, handle: &Handle<T>) -> Option<&str> {
+        self.entries.get(&handle.id())?.path.as_deref()
+    }
+
+    /// 获取资源
+    ///
+    /// # 返回值
+    /// 资源尚未加载完成或加载失败时返回`None`
+    pub fn get(&self, handle: &Handle<T>) -> Option<&T> {
+        self.entries.get(&handle.id())?.asset.as_ref()
+    }
+
+    /// 获取资源的可变引用
+    pub fn get_mut(&mut self, handle: &Handle<T>) -> Option<&mut T> {
+        self.entries.get_mut(&handle.id())?.asset.as_mut()
+    }
+
+    /// 获取资源的加载状态
+    ///
+    /// # 返回值
+    /// 句柄不属于该资源库时返回`None`
+    pub fn state(&self, handle: &Handle<T>) -> Option<LoadState> {
+        self.entries.get(&handle.id()).map(|e| e.state.clone())
+    }
+
+    /// 资源是否已加载
+    pub fn is_ready(&self, handle: &Handle<T>) -> bool {
+        self.entries
+            .get(&handle.id())
+            .is_some_and(|e| e.state == LoadState::Ready)
+    }
+
+    /// 获取资源数量
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// 资源库是否为空
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// 遍历所有已加载的资源
+    pub fn iter(&self) -> impl Iterator<Item = (u64, &T)> {
+        self.entries
+            .iter()
+            .filter_map(|(id, e)| e.asset.as_ref().map(|a| (*id, a)))
+    }
+
+    /// 获取正在加载的资源数量
+    pub fn loading_count(&self) -> usize {
+        self.entries
+            .values()
+            .filter(|e| e.state == LoadState::Loading)
+            .count()
+    }
+
+    /// 卸载所有不再被句柄引用的资源
+    ///
+    /// # 返回值
+    /// 返回卸载的资源数量
+    pub fn remove_unused(&mut self) -> usize {
+        let before = self.entries.len();
+        let paths = &mut self.paths;
+        self.entries.retain(|_, entry| {
+            let used = Arc::strong_count(&entry.id) > 1;
+            if !used {
+                if let Some(path) = &entry.path {
+                    paths.remove(path);
+                }
+            }
+            used
+        });
+        let removed = before - self.entries.len();
+        if removed > 0 {
+            debug!(Self, "已卸载 {} 个资源", removed);
+        }
+        removed
+    }
+}
+
+impl<T: 'static> Assets<T> {
+    /// 按路径异步加载资源
+    ///
+    /// 文件读取与解码在线程池中完成，解码结果由 [`Assets::update`] 在渲染线程中交给`finalize`，
+    /// 句柄的状态随之由 [`LoadState::Loading`] 变为 [`LoadState::Ready`]
+    ///
+    /// # 参数
+    /// + `path` - 资源路径，已加载或正在加载的路径直接返回已有的句柄
+    /// + `pool` - 线程池
+    /// + `decode` - 在工作线程中调用的解码函数，接受资源路径
+    /// + `finalize` - 在渲染线程中调用的函数，由解码结果创建资源，通常负责上传到 GPU
+    ///
+    /// # 返回值
+    /// 立即返回句柄
+    pub fn load_async<D, Dec, Fin>(
+        &mut self,
+        path: &str,
+        pool: &JobPool,
+        decode: Dec,
+        finalize: Fin,
+    ) -> Handle<T>
+    where
+        D: Send + 'static,
+        Dec: Fn(&str) -> Result<D> + Send + Sync + 'static,
+        Fin: Fn(D) -> Result<T> + Send + Sync + 'static,
+    {
+        if let Some(handle) = self.handle(path) {
+            return handle;
+        }
+        let finalize = Arc::new(finalize);
+        let loader: AsyncLoader<T> = Arc::new(move |path: &str| {
+            let data = decode(path)?;
+            let finalize = finalize.clone();
+            Ok(Box::new(move || finalize(data)) as Finalize<T>)
+        });
+        let handle = self.reserve(Some(path));
+        self.entries.get_mut(&handle.id()).unwrap().loader = Some(loader.clone());
+        self.spawn_load(handle.id(), path, pool, loader);
+        handle
+    }
+
+    /// 重新加载发生变化的资源
+    ///
+    /// 只有通过 [`Assets::load_async`] 加载的资源可以重新加载，新数据在 [`Assets::update`] 中替换旧资源，
+    /// 已有的句柄保持有效。重新加载期间及失败时仍可使用旧资源
+    ///
+    /// # 参数
+    /// + `changed` - 发生变化的文件，通常来自 [`AssetWatcher::poll`]
+    /// + `pool` - 线程池
+    ///
+    /// # 返回值
+    /// 返回开始重新加载的资源数量
+    pub fn reload(&mut self, changed: &[PathBuf], pool: &JobPool) -> usize {
+        if changed.is_empty() {
+            return 0;
+        }
+        let changed: Vec<PathBuf> = changed.iter().map(|p| normalize(p)).collect();
+        let mut reloads = Vec::new();
+        for (id, entry) in &self.entries {
+            let (Some(path), Some(loader)) = (&entry.path, &entry.loader) else {
+                continue;
+            };
+            if changed.contains(&normalize(Path::new(path))) {
+                reloads.push((*id, path.clone(), loader.clone()));
+            }
+        }
+        for (id, path, loader) in &reloads {
+            debug!(Self, "正在重新加载 {}", path);
+            self.spawn_load(*id, path, pool, loader.clone());
+        }
+        reloads.len()
+    }
+
+    fn spawn_load(&self, id: u64, path: &str, pool: &JobPool, loader: AsyncLoader<T>) {
+        let sender = self.sender.clone();
+        let path = path.to_string();
+        pool.spawn(move || {
+            let _ = sender.send((id, loader(&path)));
+        });
+    }
+
+    /// 完成已解码资源的创建
+    ///
+    /// 应在渲染循环中每帧调用一次，执行时长受 [`Assets::upload_budget`] 限制，未完成的资源顺延到下一帧
+    ///
+    /// # 返回值
+    /// 返回本次完成(包括失败)的资源数量
+    pub fn update(&mut self) -> usize {
+        let budget = Duration::from_secs_f64(self.upload_budget.max(0.0) / 1000.0);
+        let deadline = Instant::now() + budget;
+        let mut count = 0;
+        while let Ok((id, result)) = self.receiver.try_recv() {
+            if !self.entries.contains_key(&id) {
+                // 加载完成前句柄已全部释放并被卸载
+                continue;
+            }
+            let entry = self.entries.get_mut(&id).unwrap();
+            match result.and_then(|finalize| finalize()) {
+                Ok(asset) => {
+                    if entry.asset.is_some() {
+                        let path = entry.path.as_deref().unwrap_or("<unnamed>");
+                        info!(Self, "已重新加载 {}", path);
+                    }
+                    entry.state = LoadState::Ready;
+                    entry.asset = Some(asset);
+                }
+                // 重新加载失败时保留旧资源
+                Err(e) if entry.asset.is_some() => {
+                    let path = entry.path.as_deref().unwrap_or("<unnamed>");
+                    warn!(Self, "重新加载 {} 失败: {}", path, e);
+                }
+                Err(e) => self.fail_id(id, e.to_string()),
+            }
+            count += 1;
+            if Instant::now() >= deadline {
+                break;
+            }
+        }
+        count
+    }
+}
+
+impl Assets<Texture2D> {
+    /// 异步加载纹理，图像在工作线程中解码
+    ///
+    /// # 参数
+    /// + `path` - 图像文件路径
+    /// + `pool` - 线程池
+    /// + `srgb` - 是否为 sRGB 颜色空间
+    ///
+    /// # 示例
+    ///
+    /// ```no_run
+    /// use gle::*;
+    ///
+    /// let pool = JobPool::default();
+    /// let mut textures: Assets<Texture2D> = Assets::new();
+    /// let brick = textures.load_texture("assets/brick.png", &pool, true);
+    ///
+    /// // 在渲染循环中每帧
+    /// textures.update();
+    /// if let Some(texture) = textures.get(&brick) {
+    ///     texture.bind(0);
+    /// }
+    /// ```
+    pub fn load_texture(&mut self, path: &str, pool: &JobPool, srgb: bool) -> Handle<Texture2D> {
+        self.load_async(
+            path,
+            pool,
+            |path| {
+                image::open(path)
+                    .map(|image| image.flipv().into_rgba8())
+                    .map_err(|e| Error::Parse(format!("{}: {}", path, e)))
+            },
+            move |image| {
+                Ok(Texture2D::from_rgba8(
+                    image.width(),
+                    image.height(),
+                    image.as_raw(),
+                    srgb,
+                ))
+            },
+        )
+    }
+}
+
+impl Assets<GpuMesh> {
+    /// 异步加载 OBJ 模型并合并为一个网格，模型在工作线程中解析
+    pub fn load_obj(&mut self, path: &str, pool: &JobPool) -> Handle<GpuMesh> {
+        self.load_async(
+            path,
+            pool,
+            |path| ObjModel::load(path).map(|model| model.merged()),
+            |mesh| Ok(mesh.upload()),
+        )
+    }
+}
+
+impl Assets<Sound> {
+    /// 异步加载声音，声音在工作线程中读取并完整解码
+    pub fn load_sound(&mut self, path: &str, pool: &JobPool) -> Handle<Sound> {
+        self.load_async(path, pool, |path| Sound::load(path)?.decode(), Ok)
+    }
+}
+
+/// 将路径规范化以便比较，文件不存在时保留原路径
+fn normalize(path: &Path) -> PathBuf {
+    path.canonicalize().unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// 资源目录监视器
+///
+/// 在名为`AssetWatcher`的后台线程中定期扫描目录(包括子目录)，记录修改时间发生变化或新增的文件，
+/// 配合 [`Assets::reload`] 在运行时重新导入被修改的纹理、模型与材质
+///
+/// # 示例
+///
+/// ```no_run
+/// use gle::*;
+///
+/// let pool = JobPool::default();
+/// let watcher = AssetWatcher::new("assets");
+/// let mut textures: Assets<Texture2D> = Assets::new();
+/// let brick = textures.load_texture("assets/brick.png", &pool, true);
+///
+/// // 在渲染循环中每帧
+/// let changed = watcher.poll();
+/// textures.reload(&changed, &pool);
+/// textures.update();
+/// ```
+pub struct AssetWatcher {
+    receiver: Receiver<PathBuf>,
+    stop: Arc<AtomicBool>,
+}
+
+impl AssetWatcher {
+    /// 以 500 毫秒的间隔监视目录
+    pub fn new<P: AsRef<Path>>(dir: P) -> Self {
+        Self::with_interval(dir, Duration::from_millis(500))
+    }
+
+    /// 以指定的扫描间隔监视目录
+    pub fn with_interval<P: AsRef<Path>>(dir: P, interval: Duration) -> Self {
+        let dir = dir.as_ref().to_path_buf();
+        let (sender, receiver) = channel();
+        let stop = Arc::new(AtomicBool::new(false));
+        let stopped = stop.clone();
+        spawn(move || {
+            App::set_current_thread_name("AssetWatcher");
+            let mut times = HashMap::new();
+            scan(&dir, &mut times, &mut |_| {});
+            debug!("AssetWatcher", "正在监视 {} 中的 {} 个文件", dir.display(), times.len());
+            while !stopped.load(Ordering::Relaxed) {
+                sleep(interval);
+                let mut disconnected = false;
+                scan(&dir, &mut times, &mut |path| {
+                    disconnected |= sender.send(path).is_err();
+                });
+                if disconnected {
+                    break;
+                }
+            }
+        });
+        Self { receiver, stop }
+    }
+
+    /// 取出自上次调用以来发生变化的文件
+    ///
+    /// # 返回值
+    /// 返回去重后的文件路径
+    pub fn poll(&self) -> Vec<PathBuf> {
+        let mut changed: Vec<PathBuf> = self.receiver.try_iter().collect();
+        changed.sort();
+        changed.dedup();
+        changed
+    }
+}
+
+impl Drop for AssetWatcher {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+/// 递归扫描目录，对修改时间发生变化的文件调用`changed`
+fn scan(dir: &Path, times: &mut HashMap<PathBuf, SystemTime>, changed: &mut dyn FnMut(PathBuf)) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let Ok(metadata) = entry.metadata() else {
+            continue;
+        };
+        if metadata.is_dir() {
+            scan(&path, times, changed);
+        } else if let Ok(modified) = metadata.modified() {
+            if times.insert(path.clone(), modified) != Some(modified) {
+                changed(path);
+            }
+        }
+    }
+}
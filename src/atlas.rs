@@ -0,0 +1,417 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::error::{Error, Result};
+use crate::math::*;
+use crate::{Texture2D, TextureArray};
+
+struct AtlasEntry {
+    name: String,
+    width: u32,
+    height: u32,
+    pixels: Vec<u8>,
+}
+
+/// 图集中的一个区域
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AtlasRegion {
+    /// 所在的层，单页图集始终为`0`
+    pub layer: u32,
+    /// 左下角在层内的像素位置，不含填充
+    pub position: UVec2,
+    /// 像素尺寸，不含填充
+    pub size: UVec2,
+    /// 纹理区域，依次为左下角`u`、`v`与宽度、高度，可直接用于 [`Billboard::uv_rect`](crate::Billboard::uv_rect)
+    pub uv_rect: Vec4,
+}
+
+/// 图集构建器
+///
+/// 收集若干 RGBA8 图像，以货架算法打包到一张纹理或纹理数组的多个层中。
+/// 每个图像四周按 [`AtlasBuilder::padding`] 复制边缘像素作为填充，区域的起点按填充对齐，
+/// 使较低级别的多级渐远纹理在填充耗尽之前不会混入相邻图像的颜色
+///
+/// # 示例
+///
+/// ```
+/// use gle::*;
+///
+/// let mut builder = AtlasBuilder::new().with_padding(4);
+/// builder.add_file("coin", "assets/sprites/coin.png").unwrap();
+/// builder.add_file("heart", "assets/sprites/heart.png").unwrap();
+/// // 打包可以在工作线程中进行
+/// let atlas = builder.build().unwrap();
+///
+/// fn render_init(atlas: &Atlas) -> (Texture2D, Billboard) {
+///     let texture = atlas.upload(true);
+///     let coin = Billboard {
+///         texture: Some(texture.id()),
+///         uv_rect: atlas.region("coin").unwrap().uv_rect,
+///         ..Default::default()
+///     };
+///     (texture, coin)
+/// }
+/// ```
+pub struct AtlasBuilder {
+    /// 每个图像四周的填充像素数
+    pub padding: u32,
+    /// 单页图集的最大边长
+    pub max_size: u32,
+    entries: Vec<AtlasEntry>,
+}
+
+impl Default for AtlasBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AtlasBuilder {
+    /// 创建填充为 2 像素、最大边长为 4096 的图集构建器
+    pub fn new() -> Self {
+        Self {
+            padding: 2,
+            max_size: 4096,
+            entries: Vec::new(),
+        }
+    }
+
+    /// 设置填充像素数
+    pub fn with_padding(mut self, padding: u32) -> Self {
+        self.padding = padding;
+        self
+    }
+
+    /// 设置单页图集的最大边长
+    pub fn with_max_size(mut self, max_size: u32) -> Self {
+        self.max_size = max_size;
+        self
+    }
+
+    /// 已添加的图像数量
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// 是否没有添加任何图像
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// 添加图像
+    ///
+    /// # 参数
+    /// + `name` - 图像名称，同名图像将替换之前添加的图像
+    /// + `width` - 宽度
+    /// + `height` - 高度
+    /// + `pixels` - RGBA8 像素数据，自底向上逐行排列，长度应为`width * height * 4`
+    pub fn add(&mut self, name: &str, width: u32, height: u32, pixels: Vec<u8>) {
+        assert_eq!(pixels.len(), (width * height * 4) as usize, "像素数据长度不匹配");
+        assert!(width > 0 && height > 0, "图像尺寸不能为零");
+        let entry = AtlasEntry {
+            name: name.to_string(),
+            width,
+            height,
+            pixels,
+        };
+        match self.entries.iter_mut().find(|e| e.name == name) {
+            Some(existing) => *existing = entry,
+            None => self.entries.push(entry),
+        }
+    }
+
+    /// 从图像文件添加图像
+    ///
+    /// # 参数
+    /// + `name` - 图像名称
+    /// + `path` - 图像文件路径
+    pub fn add_file<P: AsRef<Path>>(&mut self, name: &str, path: P) -> Result<()> {
+        let path = path.as_ref();
+        let image = image::open(path)
+            .map_err(|e| Error::Parse(format!("{}: {}", path.display(), e)))?
+            .flipv()
+            .into_rgba8();
+        let (width, height) = image.dimensions();
+        self.add(name, width, height, image.into_raw());
+        Ok(())
+    }
+
+    /// 将所有图像打包到单页图集
+    ///
+    /// 从能容纳所有图像面积的最小 2 的幂尺寸开始尝试，宽高交替加倍直到 [`AtlasBuilder::max_size`]
+    ///
+    /// # 返回值
+    /// 成功时返回图集，最大尺寸下仍无法容纳所有图像时返回错误
+    pub fn build(&self) -> Result<Atlas> {
+        let area: u64 = self
+            .entries
+            .iter()
+            .map(|e| {
+                let (w, h) = self.slot_size(e);
+                w as u64 * h as u64
+            })
+            .sum();
+        let mut width = 1u32;
+        let mut height = 1u32;
+        while (width as u64 * height as u64) < area {
+            if width <= height {
+                width *= 2;
+            } else {
+                height *= 2;
+            }
+        }
+        while width <= self.max_size && height <= self.max_size {
+            if let Some(placements) = self.pack(width, height, Some(1)) {
+                return Ok(self.compose(width, height, &placements));
+            }
+            if width <= height {
+                width *= 2;
+            } else {
+                height *= 2;
+            }
+        }
+        Err(Error::Parse(format!(
+            "无法将 {} 个图像打包到 {}x{} 以内的图集",
+            self.entries.len(),
+            self.max_size,
+            self.max_size
+        )))
+    }
+
+    /// 将所有图像打包到纹理数组，每层尺寸固定，层数按需增加
+    ///
+    /// # 参数
+    /// + `width` - 每层的宽度
+    /// + `height` - 每层的高度
+    ///
+    /// # 返回值
+    /// 成功时返回图集，存在单层无法容纳的图像时返回错误
+    ///
+    /// # 注解
+    ///
+    /// 图像按高度降序、相同高度按添加顺序放置，因此尺寸均等于层尺寸且填充为 0 的图像依添加顺序各占一层
+    pub fn build_layers(&self, width: u32, height: u32) -> Result<Atlas> {
+        if let Some(entry) = self.entries.iter().find(|e| {
+            let (w, h) = self.slot_size(e);
+            w > width || h > height
+        }) {
+            return Err(Error::Parse(format!(
+                "图像 {} 的尺寸 {}x{} 超出图集层尺寸 {}x{}",
+                entry.name, entry.width, entry.height, width, height
+            )));
+        }
+        let placements = self.pack(width, height, None).unwrap_or_default();
+        Ok(self.compose(width, height, &placements))
+    }
+
+    /// 填充对齐单位，保证前`log2(padding) + 1`级多级渐远纹理不会跨越区域边界
+    fn alignment(&self) -> u32 {
+        if self.padding == 0 {
+            1
+        } else {
+            1 << self.padding.ilog2()
+        }
+    }
+
+    fn slot_size(&self, entry: &AtlasEntry) -> (u32, u32) {
+        let align = self.alignment();
+        let w = (entry.width + self.padding * 2).next_multiple_of(align);
+        let h = (entry.height + self.padding * 2).next_multiple_of(align);
+        (w, h)
+    }
+
+    /// 以货架算法计算每个图像所在的层与槽位左下角，按添加顺序返回
+    fn pack(
+        &self,
+        width: u32,
+        height: u32,
+        max_layers: Option<u32>,
+    ) -> Option<Vec<(u32, UVec2)>> {
+        let mut order: Vec<usize> = (0..self.entries.len()).collect();
+        order.sort_by_key(|&i| std::cmp::Reverse(self.slot_size(&self.entries[i]).1));
+        let mut placements = vec![(0, UVec2::ZERO); self.entries.len()];
+        let (mut layer, mut x, mut y, mut shelf) = (0u32, 0u32, 0u32, 0u32);
+        for i in order {
+            let (w, h) = self.slot_size(&self.entries[i]);
+            if w > width || h > height {
+                return None;
+            }
+            if x + w > width {
+                x = 0;
+                y += shelf;
+                shelf = 0;
+            }
+            if y + h > height {
+                layer += 1;
+                if max_layers.is_some_and(|max| layer >= max) {
+                    return None;
+                }
+                x = 0;
+                y = 0;
+                shelf = 0;
+            }
+            placements[i] = (layer, UVec2::new(x, y));
+            x += w;
+            shelf = shelf.max(h);
+        }
+        Some(placements)
+    }
+
+    fn compose(&self, width: u32, height: u32, placements: &[(u32, UVec2)]) -> Atlas {
+        let layers = placements.iter().map(|(layer, _)| layer + 1).max().unwrap_or(1);
+        let mut pixels = vec![0u8; (width * height * layers * 4) as usize];
+        let mut regions = Vec::with_capacity(self.entries.len());
+        let mut names = HashMap::new();
+        let size = Vec2::new(width as f32, height as f32);
+        let p = self.padding as i32;
+        for (entry, &(layer, slot)) in self.entries.iter().zip(placements) {
+            let origin = slot + UVec2::splat(self.padding);
+            let page = (layer * width * height) as usize;
+            // 以夹取方式读取源图像，填充区域即为边缘像素的延伸
+            for dy in -p..entry.height as i32 + p {
+                let sy = dy.clamp(0, entry.height as i32 - 1) as u32;
+                let ty = (origin.y as i32 + dy) as u32;
+                for dx in -p..entry.width as i32 + p {
+                    let sx = dx.clamp(0, entry.width as i32 - 1) as u32;
+                    let tx = (origin.x as i32 + dx) as u32;
+                    let src = ((sy * entry.width + sx) * 4) as usize;
+                    let dst = (page + (ty * width + tx) as usize) * 4;
+                    pixels[dst..dst + 4].copy_from_slice(&entry.pixels[src..src + 4]);
+                }
+            }
+            let entry_size = UVec2::new(entry.width, entry.height);
+            let uv = origin.as_vec2() / size;
+            let uv_size = entry_size.as_vec2() / size;
+            names.insert(entry.name.clone(), regions.len());
+            regions.push(AtlasRegion {
+                layer,
+                position: origin,
+                size: entry_size,
+                uv_rect: Vec4::new(uv.x, uv.y, uv_size.x, uv_size.y),
+            });
+        }
+        Atlas {
+            width,
+            height,
+            layers,
+            padding: self.padding,
+            pixels,
+            regions,
+            names,
+        }
+    }
+}
+
+/// 打包完成的图集
+///
+/// 由 [`AtlasBuilder`] 创建，保存在内存中的像素数据与各图像的区域，
+/// 可通过 [`Atlas::upload`] 或 [`Atlas::upload_array`] 创建纹理
+#[derive(Debug, Clone)]
+pub struct Atlas {
+    width: u32,
+    height: u32,
+    layers: u32,
+    padding: u32,
+    pixels: Vec<u8>,
+    regions: Vec<AtlasRegion>,
+    names: HashMap<String, usize>,
+}
+
+impl Atlas {
+    /// 获取每层的宽度
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// 获取每层的高度
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// 获取层数
+    pub fn layers(&self) -> u32 {
+        self.layers
+    }
+
+    /// 获取所有层的 RGBA8 像素数据，各层依次排列，每层自底向上逐行排列
+    pub fn pixels(&self) -> &[u8] {
+        &self.pixels
+    }
+
+    /// 由名称获取区域
+    pub fn region(&self, name: &str) -> Option<&AtlasRegion> {
+        self.names.get(name).map(|&i| &self.regions[i])
+    }
+
+    /// 遍历所有图像的名称与区域
+    pub fn regions(&self) -> impl Iterator<Item = (&str, &AtlasRegion)> {
+        self.names
+            .iter()
+            .map(|(name, &i)| (name.as_str(), &self.regions[i]))
+    }
+
+    /// 不会发生颜色渗透的多级渐远纹理级数
+    ///
+    /// 每个区域都占满整层时不受限制，返回完整的级数；否则由填充像素数决定
+    pub fn mip_levels(&self) -> u32 {
+        let full = self.width.max(self.height).ilog2() + 1;
+        if self.covers_layers() {
+            full
+        } else if self.padding == 0 {
+            1
+        } else {
+            (self.padding.ilog2() + 1).min(full)
+        }
+    }
+
+    /// 将单页图集上传为二维纹理
+    ///
+    /// # 参数
+    /// + `srgb` - 是否为 sRGB 颜色空间
+    ///
+    /// # 注解
+    ///
+    /// 只能在渲染线程中调用。图集只有一层时可用，多级渐远纹理的级数被限制为 [`Atlas::mip_levels`]，
+    /// 区域未占满整层时使用夹取环绕
+    pub fn upload(&self, srgb: bool) -> Texture2D {
+        assert_eq!(self.layers, 1, "多层图集应使用 upload_array 上传");
+        let texture = Texture2D::from_rgba8(self.width, self.height, &self.pixels, srgb);
+        self.apply_sampling(gl::TEXTURE_2D, texture.id());
+        texture
+    }
+
+    /// 将图集上传为纹理数组
+    ///
+    /// # 参数
+    /// + `srgb` - 是否为 sRGB 颜色空间
+    ///
+    /// # 注解
+    ///
+    /// 只能在渲染线程中调用，采样设置与 [`Atlas::upload`] 相同
+    pub fn upload_array(&self, srgb: bool) -> TextureArray {
+        let texture =
+            TextureArray::from_rgba8(self.width, self.height, self.layers, &self.pixels, srgb);
+        self.apply_sampling(gl::TEXTURE_2D_ARRAY, texture.id());
+        texture
+    }
+
+    /// 每个区域是否都恰好占满一层，此时区域可以重复平铺
+    fn covers_layers(&self) -> bool {
+        let size = UVec2::new(self.width, self.height);
+        self.regions
+            .iter()
+            .all(|r| r.position == UVec2::ZERO && r.size == size)
+    }
+
+    fn apply_sampling(&self, target: u32, id: u32) {
+        unsafe {
+            gl::BindTexture(target, id);
+            gl::TexParameteri(target, gl::TEXTURE_MAX_LEVEL, self.mip_levels() as i32 - 1);
+            if !self.covers_layers() {
+                gl::TexParameteri(target, gl::TEXTURE_WRAP_S, gl::CLAMP_TO_EDGE as i32);
+                gl::TexParameteri(target, gl::TEXTURE_WRAP_T, gl::CLAMP_TO_EDGE as i32);
+            }
+            gl::BindTexture(target, 0);
+        }
+    }
+}
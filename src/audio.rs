@@ -0,0 +1,908 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufReader, Cursor};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering};
+use std::sync::mpsc::{channel, Receiver, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread::spawn;
+use std::time::Duration;
+
+use lazy_static::lazy_static;
+use rodio::source::ChannelVolume;
+use rodio::{Decoder, OutputStream, Sink, Source};
+
+use crate::error::{Error, Result};
+use crate::math::*;
+use crate::{debug, error, warn, App, Camera, Entity, Scene};
+
+lazy_static! {
+    static ref AUDIO: Mutex<Option<Sender<AudioCommand>>> = Mutex::new(None);
+    static ref BUS_VOLUMES: Mutex<[f32; AudioBus::COUNT]> = Mutex::new([1.0; AudioBus::COUNT]);
+}
+
+static NEXT_SOUND_ID: AtomicU64 = AtomicU64::new(1);
+
+/// 音频线程检查播放结束的间隔
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+/// 混音线程读取空间化增益的间隔
+const SPATIAL_INTERVAL: Duration = Duration::from_millis(5);
+/// 音效库加载的文件扩展名
+const SUPPORTED_EXTENSIONS: [&str; 4] = ["wav", "ogg", "flac", "mp3"];
+/// 流式播放时每次从磁盘读取的数据块大小
+const STREAM_CHUNK: usize = 256 * 1024;
+
+/// 左右声道的增益，以位模式存储在原子变量中，供混音线程无锁读取
+type ChannelGains = Arc<[AtomicU32; 2]>;
+
+/// 混音总线
+///
+/// 每个声音属于一条总线，最终音量为声音音量、所属总线音量与主总线音量之积
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum AudioBus {
+    /// 主总线，影响所有声音
+    Master,
+    /// 音乐
+    Music,
+    /// 音效
+    #[default]
+    Sfx,
+}
+
+impl AudioBus {
+    const COUNT: usize = 3;
+}
+
+/// 解码后的 PCM 数据
+struct Pcm {
+    channels: u16,
+    sample_rate: u32,
+    samples: Arc<[i16]>,
+}
+
+#[derive(Clone)]
+enum SoundData {
+    Encoded(Arc<[u8]>),
+    Decoded(Arc<Pcm>),
+}
+
+/// 已加载到内存中的声音
+///
+/// 默认保存编码后的文件数据(WAV、OGG、FLAC、MP3)，每次播放时在音频线程中解码，可以廉价地克隆并多次同时播放。
+/// 频繁播放的短音效可以通过 [`Sound::decode`] 预先解码，较长的音乐应使用 [`Audio::play_music`] 从磁盘流式播放
+#[derive(Clone)]
+pub struct Sound {
+    data: SoundData,
+}
+
+impl std::fmt::Debug for Sound {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.data {
+            SoundData::Encoded(data) => write!(f, "Sound({} bytes encoded)", data.len()),
+            SoundData::Decoded(pcm) => write!(
+                f,
+                "Sound({} samples, {} channels, {} Hz)",
+                pcm.samples.len(),
+                pcm.channels,
+                pcm.sample_rate
+            ),
+        }
+    }
+}
+
+impl Sound {
+    /// 从文件加载声音
+    ///
+    /// # 参数
+    /// + `path` - 文件路径
+    ///
+    /// # 返回值
+    /// 成功时返回声音，文件无法读取或格式不受支持时返回错误
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        Self::from_bytes(std::fs::read(path)?)
+            .map_err(|e| Error::Parse(format!("{}: {}", path.display(), e)))
+    }
+
+    /// 从编码后的文件数据创建声音
+    pub fn from_bytes(bytes: Vec<u8>) -> Result<Self> {
+        let data: Arc<[u8]> = bytes.into();
+        Decoder::new(Cursor::new(data.clone())).map_err(|e| Error::Parse(e.to_string()))?;
+        Ok(Self {
+            data: SoundData::Encoded(data),
+        })
+    }
+
+    /// 将声音完整解码为 PCM 数据
+    ///
+    /// # 返回值
+    /// 返回解码后的声音，播放时无需再次解码，但占用的内存通常是编码数据的十倍左右
+    pub fn decode(&self) -> Result<Self> {
+        let SoundData::Encoded(data) = &self.data else {
+            return Ok(self.clone());
+        };
+        let decoder =
+            Decoder::new(Cursor::new(data.clone())).map_err(|e| Error::Parse(e.to_string()))?;
+        let channels = decoder.channels();
+        let sample_rate = decoder.sample_rate();
+        let samples: Arc<[i16]> = decoder.collect();
+        Ok(Self {
+            data: SoundData::Decoded(Arc::new(Pcm {
+                channels,
+                sample_rate,
+                samples,
+            })),
+        })
+    }
+
+    /// 获取声音时长
+    ///
+    /// # 返回值
+    /// 解码后的声音返回精确时长，编码的声音在格式支持时返回时长，否则返回`None`
+    pub fn duration(&self) -> Option<Duration> {
+        match &self.data {
+            SoundData::Encoded(data) => {
+                Decoder::new(Cursor::new(data.clone())).ok()?.total_duration()
+            }
+            SoundData::Decoded(pcm) => {
+                let frames = pcm.samples.len() as f64 / pcm.channels.max(1) as f64;
+                Some(Duration::from_secs_f64(frames / pcm.sample_rate.max(1) as f64))
+            }
+        }
+    }
+}
+
+/// 在解码后的 PCM 数据上播放的声源，多个声源共享同一份数据
+#[derive(Clone)]
+struct PcmSource {
+    pcm: Arc<Pcm>,
+    position: usize,
+}
+
+impl Iterator for PcmSource {
+    type Item = i16;
+
+    fn next(&mut self) -> Option<i16> {
+        let sample = self.pcm.samples.get(self.position).copied();
+        self.position += 1;
+        sample
+    }
+}
+
+impl Source for PcmSource {
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    fn channels(&self) -> u16 {
+        self.pcm.channels
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.pcm.sample_rate
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        None
+    }
+}
+
+/// 音效库
+///
+/// 将一组短音效预先解码并按名称保存，播放时无需读取文件或解码
+///
+/// # 示例
+///
+/// ```no_run
+/// use gle::*;
+///
+/// // 加载 assets/sfx 下的 jump.wav、hit.ogg 等文件
+/// let sfx = SoundBank::load_dir("assets/sfx").unwrap();
+/// sfx.play("jump");
+/// Audio::set_bus_volume(AudioBus::Sfx, 0.8);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct SoundBank {
+    /// 音效播放时所属的混音总线
+    pub bus: AudioBus,
+    sounds: HashMap<String, Sound>,
+}
+
+impl SoundBank {
+    /// 创建空的音效库
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 加载目录中的所有音频文件
+    ///
+    /// # 参数
+    /// + `dir` - 目录路径，扩展名为`wav`、`ogg`、`flac`或`mp3`的文件以去除扩展名后的文件名为名称加载
+    ///
+    /// # 返回值
+    /// 成功时返回音效库，任一文件加载失败时返回错误
+    pub fn load_dir<P: AsRef<Path>>(dir: P) -> Result<Self> {
+        let mut bank = Self::new();
+        for entry in std::fs::read_dir(dir)? {
+            let path = entry?.path();
+            let supported = path
+                .extension()
+                .and_then(|e| e.to_str())
+                .is_some_and(|e| SUPPORTED_EXTENSIONS.contains(&e.to_lowercase().as_str()));
+            let Some(name) = path.file_stem().and_then(|s| s.to_str()) else {
+                continue;
+            };
+            if supported && path.is_file() {
+                bank.insert(name, &Sound::load(&path)?)?;
+            }
+        }
+        debug!(Self, "已加载 {} 个音效", bank.len());
+        Ok(bank)
+    }
+
+    /// 解码并添加音效，已存在的同名音效将被替换
+    pub fn insert(&mut self, name: &str, sound: &Sound) -> Result<()> {
+        self.sounds.insert(name.to_string(), sound.decode()?);
+        Ok(())
+    }
+
+    /// 获取音效
+    pub fn get(&self, name: &str) -> Option<&Sound> {
+        self.sounds.get(name)
+    }
+
+    /// 获取音效数量
+    pub fn len(&self) -> usize {
+        self.sounds.len()
+    }
+
+    /// 音效库是否为空
+    pub fn is_empty(&self) -> bool {
+        self.sounds.is_empty()
+    }
+
+    /// 遍历所有音效的名称
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.sounds.keys().map(|k| k.as_str())
+    }
+
+    /// 在音效库的总线上播放音效
+    ///
+    /// # 返回值
+    /// 返回播放句柄，音效不存在时返回`None`
+    pub fn play(&self, name: &str) -> Option<SoundHandle> {
+        self.play_with(
+            name,
+            PlayParams {
+                bus: self.bus,
+                ..Default::default()
+            },
+        )
+    }
+
+    /// 以指定参数播放音效，参数中的总线优先于音效库的总线
+    pub fn play_with(&self, name: &str, params: PlayParams) -> Option<SoundHandle> {
+        match self.sounds.get(name) {
+            Some(sound) => Some(Audio::play_with(sound, params)),
+            None => {
+                warn!(Self, "音效 {} 不存在", name);
+                None
+            }
+        }
+    }
+}
+
+/// 播放参数
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PlayParams {
+    /// 音量，1 为原始音量
+    pub volume: f32,
+    /// 音调，同时改变播放速度，1 为原始音调
+    pub pitch: f32,
+    /// 是否循环播放
+    pub looping: bool,
+    /// 所属的混音总线
+    pub bus: AudioBus,
+}
+
+impl Default for PlayParams {
+    fn default() -> Self {
+        Self {
+            volume: 1.0,
+            pitch: 1.0,
+            looping: false,
+            bus: AudioBus::Sfx,
+        }
+    }
+}
+
+/// 正在播放的声音的句柄
+///
+/// 句柄被释放时声音不会停止，需要提前停止时调用 [`SoundHandle::stop`]
+#[derive(Debug, Clone)]
+pub struct SoundHandle {
+    id: u64,
+    finished: Arc<AtomicBool>,
+}
+
+impl SoundHandle {
+    /// 设置音量
+    pub fn set_volume(&self, volume: f32) {
+        Audio::send(AudioCommand::SetVolume(self.id, volume));
+    }
+
+    /// 设置音调，同时改变播放速度
+    pub fn set_pitch(&self, pitch: f32) {
+        Audio::send(AudioCommand::SetPitch(self.id, pitch));
+    }
+
+    /// 暂停播放
+    pub fn pause(&self) {
+        Audio::send(AudioCommand::Pause(self.id));
+    }
+
+    /// 继续播放
+    pub fn resume(&self) {
+        Audio::send(AudioCommand::Resume(self.id));
+    }
+
+    /// 停止播放，停止后无法继续
+    pub fn stop(&self) {
+        Audio::send(AudioCommand::Stop(self.id));
+    }
+
+    /// 是否已播放完毕或被停止
+    ///
+    /// # 注解
+    ///
+    /// 音频线程定期检查播放状态，结果可能滞后数十毫秒
+    pub fn is_finished(&self) -> bool {
+        self.finished.load(Ordering::Acquire)
+    }
+
+    fn set_spatial(&self, gains: [f32; 2], doppler: f32) {
+        Audio::send(AudioCommand::SetSpatial(self.id, gains, doppler));
+    }
+}
+
+enum SoundSource {
+    Memory(SoundData),
+    File(PathBuf),
+}
+
+enum AudioCommand {
+    Play {
+        id: u64,
+        source: SoundSource,
+        params: PlayParams,
+        spatial: Option<[f32; 2]>,
+        finished: Arc<AtomicBool>,
+    },
+    SetVolume(u64, f32),
+    SetPitch(u64, f32),
+    Pause(u64),
+    Resume(u64),
+    Stop(u64),
+    StopAll,
+    SetBusVolume(AudioBus, f32),
+    SetSpatial(u64, [f32; 2], f32),
+}
+
+/// 音频系统
+///
+/// 音频输出设备在名为`AudioThread`的独立线程中打开，其他线程通过消息控制播放，不会被解码或设备操作阻塞。
+/// 音频线程在首次播放时自动启动
+///
+/// # 示例
+///
+/// ```no_run
+/// use gle::*;
+///
+/// let click = Sound::load("assets/click.wav").unwrap();
+/// Audio::play(&click);
+///
+/// let music = Audio::play_music(
+///     "assets/theme.ogg",
+///     PlayParams { volume: 0.5, looping: true, ..Default::default() },
+/// )
+/// .unwrap();
+/// music.set_pitch(1.1);
+/// ```
+pub struct Audio;
+
+impl Audio {
+    /// 播放声音
+    ///
+    /// # 返回值
+    /// 返回播放句柄
+    pub fn play(sound: &Sound) -> SoundHandle {
+        Self::play_with(sound, PlayParams::default())
+    }
+
+    /// 以指定参数播放声音
+    pub fn play_with(sound: &Sound, params: PlayParams) -> SoundHandle {
+        Self::start(SoundSource::Memory(sound.data.clone()), params, None)
+    }
+
+    /// 从磁盘流式播放音乐
+    ///
+    /// 文件在播放过程中按数据块逐段读取和解码，不会整体载入内存，适合较长的 OGG 音轨。
+    /// 音乐通常应放在 [`AudioBus::Music`] 总线上
+    ///
+    /// # 参数
+    /// + `path` - 文件路径
+    /// + `params` - 播放参数
+    ///
+    /// # 返回值
+    /// 成功时返回播放句柄，文件无法打开时返回错误
+    pub fn play_music<P: AsRef<Path>>(path: P, params: PlayParams) -> Result<SoundHandle> {
+        let path = path.as_ref();
+        File::open(path)?;
+        Ok(Self::start(SoundSource::File(path.to_path_buf()), params, None))
+    }
+
+    /// 停止所有声音
+    pub fn stop_all() {
+        Self::send(AudioCommand::StopAll);
+    }
+
+    /// 设置主音量，等价于设置 [`AudioBus::Master`] 总线的音量
+    pub fn set_master_volume(volume: f32) {
+        Self::set_bus_volume(AudioBus::Master, volume);
+    }
+
+    /// 获取主音量
+    pub fn master_volume() -> f32 {
+        Self::bus_volume(AudioBus::Master)
+    }
+
+    /// 设置总线音量
+    ///
+    /// # 参数
+    /// + `bus` - 混音总线
+    /// + `volume` - 音量(默认值为1)，与总线上每个声音的音量相乘
+    pub fn set_bus_volume(bus: AudioBus, volume: f32) {
+        BUS_VOLUMES.lock().unwrap()[bus as usize] = volume;
+        Self::send(AudioCommand::SetBusVolume(bus, volume));
+    }
+
+    /// 获取总线音量
+    pub fn bus_volume(bus: AudioBus) -> f32 {
+        BUS_VOLUMES.lock().unwrap()[bus as usize]
+    }
+
+    fn start(source: SoundSource, params: PlayParams, spatial: Option<[f32; 2]>) -> SoundHandle {
+        let id = NEXT_SOUND_ID.fetch_add(1, Ordering::Relaxed);
+        let finished = Arc::new(AtomicBool::new(false));
+        Self::send(AudioCommand::Play {
+            id,
+            source,
+            params,
+            spatial,
+            finished: finished.clone(),
+        });
+        SoundHandle { id, finished }
+    }
+
+    fn send(command: AudioCommand) {
+        let mut audio = AUDIO.lock().unwrap();
+        let sender = audio.get_or_insert_with(|| {
+            let (sender, receiver) = channel();
+            let buses = *BUS_VOLUMES.lock().unwrap();
+            spawn(move || audio_thread(receiver, buses));
+            sender
+        });
+        if let Err(e) = sender.send(command) {
+            // 音频线程已退出(如无可用的输出设备)，直接结束声音
+            if let AudioCommand::Play { finished, .. } = e.0 {
+                finished.store(true, Ordering::Release);
+            }
+        }
+    }
+}
+
+/// 音频监听者组件
+///
+/// 附加在场景实体(通常是玩家或摄像机所在的实体)上，[`SpatialAudio`] 以其世界变换计算声音的方位。
+/// 场景中没有监听者时以主摄像机代替
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct AudioListener;
+
+/// 音频发射器组件
+///
+/// 附加在场景实体上，通过 [`AudioEmitter::play`] 播放的声音随实体的世界位置衰减和平移声像
+///
+/// # 示例
+///
+/// ```no_run
+/// use gle::{*, math::*};
+///
+/// let mut scene = Scene::new();
+/// let player = scene.spawn("player", Transform::IDENTITY);
+/// scene.insert(player, AudioListener);
+/// let torch = scene.spawn("torch", Transform::from_translation(Vec3::new(5.0, 0.0, 0.0)));
+///
+/// let crackle = Sound::load("assets/fire.ogg").unwrap();
+/// let mut emitter = AudioEmitter::new();
+/// emitter.play(&crackle, PlayParams { looping: true, ..Default::default() });
+/// scene.insert(torch, emitter);
+///
+/// let mut audio = SpatialAudio::new();
+/// // 在固定时间步中
+/// audio.update(&mut scene, 1.0 / 60.0);
+/// ```
+#[derive(Debug, Clone)]
+pub struct AudioEmitter {
+    /// 音量
+    pub volume: f32,
+    /// 参考距离，距离不超过该值时不衰减
+    pub min_distance: f32,
+    /// 最大距离，超出时静音
+    pub max_distance: f32,
+    /// 衰减系数，按反距离模型`min / (min + rolloff * (d - min))`衰减
+    pub rolloff: f32,
+    /// 是否应用多普勒效应
+    pub doppler: bool,
+    queued: Vec<(Sound, PlayParams)>,
+    voices: Vec<SoundHandle>,
+}
+
+impl Default for AudioEmitter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AudioEmitter {
+    /// 创建参考距离为 1、最大距离为 50 的发射器
+    pub fn new() -> Self {
+        Self {
+            volume: 1.0,
+            min_distance: 1.0,
+            max_distance: 50.0,
+            rolloff: 1.0,
+            doppler: true,
+            queued: Vec::new(),
+            voices: Vec::new(),
+        }
+    }
+
+    /// 在发射器的位置播放声音
+    ///
+    /// # 注解
+    ///
+    /// 声音在下一次调用 [`SpatialAudio::update`] 时以计算好的方位开始播放
+    pub fn play(&mut self, sound: &Sound, params: PlayParams) {
+        self.queued.push((sound.clone(), params));
+    }
+
+    /// 停止发射器的所有声音
+    pub fn stop(&mut self) {
+        self.queued.clear();
+        for voice in self.voices.drain(..) {
+            voice.stop();
+        }
+    }
+
+    /// 是否有正在播放或等待播放的声音
+    pub fn is_playing(&self) -> bool {
+        !self.queued.is_empty() || self.voices.iter().any(|v| !v.is_finished())
+    }
+
+    /// 计算左右声道增益
+    fn gains(&self, distance: f32, pan: f32) -> [f32; 2] {
+        if distance >= self.max_distance {
+            return [0.0; 2];
+        }
+        let min = self.min_distance.max(1e-3);
+        let gain = min / (min + self.rolloff * (distance.max(min) - min)) * self.volume;
+        // 等功率声像，正前方时两个声道均为满增益
+        let angle = (pan.clamp(-1.0, 1.0) + 1.0) * std::f32::consts::FRAC_PI_4;
+        let left = (angle.cos() * std::f32::consts::SQRT_2).min(1.0);
+        let right = (angle.sin() * std::f32::consts::SQRT_2).min(1.0);
+        [left * gain, right * gain]
+    }
+}
+
+struct TrackedEmitter {
+    position: Vec3,
+    voices: Vec<SoundHandle>,
+}
+
+/// 三维空间音频
+///
+/// 根据 [`AudioListener`] 与 [`AudioEmitter`] 所在实体的世界变换计算距离衰减、左右声像与多普勒效应。
+/// 速度由相邻两次更新间的位移求得，因此应在固定时间步中调用 [`SpatialAudio::update`]
+///
+/// # 注解
+///
+/// 发射器所在的实体被销毁或组件被移除后，其声音在下一次更新时停止
+pub struct SpatialAudio {
+    /// 多普勒效应的强度，为零时不应用
+    pub doppler_factor: f32,
+    /// 声速，单位为每秒
+    pub speed_of_sound: f32,
+    listener: Option<Vec3>,
+    emitters: HashMap<Entity, TrackedEmitter>,
+}
+
+impl Default for SpatialAudio {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SpatialAudio {
+    /// 创建声速为 343 的空间音频系统
+    pub fn new() -> Self {
+        Self {
+            doppler_factor: 1.0,
+            speed_of_sound: 343.0,
+            listener: None,
+            emitters: HashMap::new(),
+        }
+    }
+
+    /// 更新所有发射器的声音
+    ///
+    /// # 参数
+    /// + `scene` - 场景
+    /// + `dt` - 距上次更新的时间，单位为秒
+    pub fn update(&mut self, scene: &mut Scene, dt: f32) {
+        let (listener, right) = Self::listener(scene);
+        let listener_velocity = self.velocity(self.listener, listener, dt);
+        self.listener = Some(listener);
+
+        let entities: Vec<Entity> = scene.query::<AudioEmitter>().map(|(e, _)| e).collect();
+        let mut emitters = HashMap::with_capacity(entities.len());
+        for entity in entities {
+            let Some(position) = scene.world_matrix(entity).map(|m| m.w_axis.xyz()) else {
+                continue;
+            };
+            let previous = self.emitters.remove(&entity).map(|t| t.position);
+            let velocity = self.velocity(previous, position, dt);
+            let offset = position - listener;
+            let distance = offset.length();
+            let direction = offset.normalize_or_zero();
+            let emitter = scene.component_mut::<AudioEmitter>(entity).unwrap();
+            let gains = emitter.gains(distance, direction.dot(right));
+            let doppler = if emitter.doppler {
+                self.doppler(direction, listener_velocity, velocity)
+            } else {
+                1.0
+            };
+
+            emitter.voices.retain(|v| !v.is_finished());
+            for voice in &emitter.voices {
+                voice.set_spatial(gains, doppler);
+            }
+            for (sound, params) in emitter.queued.drain(..) {
+                let source = SoundSource::Memory(sound.data.clone());
+                let voice = Audio::start(source, params, Some(gains));
+                if doppler != 1.0 {
+                    voice.set_spatial(gains, doppler);
+                }
+                emitter.voices.push(voice);
+            }
+            let voices = emitter.voices.clone();
+            emitters.insert(entity, TrackedEmitter { position, voices });
+        }
+        for (_, removed) in self.emitters.drain() {
+            for voice in removed.voices {
+                voice.stop();
+            }
+        }
+        self.emitters = emitters;
+    }
+
+    /// 获取监听者的位置与右方向
+    fn listener(scene: &Scene) -> (Vec3, Vec3) {
+        let world = scene
+            .query::<AudioListener>()
+            .next()
+            .and_then(|(e, _)| scene.world_matrix(e));
+        if let Some(m) = world {
+            return (m.w_axis.xyz(), m.transform_vector3(Vec3::X).normalize_or_zero());
+        }
+        match Camera::main() {
+            Some(camera) => (camera.position, camera.right()),
+            None => (Vec3::ZERO, Vec3::X),
+        }
+    }
+
+    fn velocity(&self, previous: Option<Vec3>, current: Vec3, dt: f32) -> Vec3 {
+        match previous {
+            Some(previous) if dt > 0.0 => (current - previous) / dt,
+            _ => Vec3::ZERO,
+        }
+    }
+
+    /// 计算多普勒频移的播放速度倍率
+    fn doppler(&self, direction: Vec3, listener: Vec3, emitter: Vec3) -> f32 {
+        if self.doppler_factor <= 0.0 || direction == Vec3::ZERO {
+            return 1.0;
+        }
+        // 相对速度限制在声速以内，避免瞬移导致的极端音调
+        let limit = self.speed_of_sound * 0.5;
+        let toward_emitter = (listener.dot(direction) * self.doppler_factor).clamp(-limit, limit);
+        let away_from_listener =
+            (emitter.dot(direction) * self.doppler_factor).clamp(-limit, limit);
+        (self.speed_of_sound + toward_emitter) / (self.speed_of_sound + away_from_listener)
+    }
+}
+
+struct Voice {
+    sink: Sink,
+    bus: AudioBus,
+    volume: f32,
+    pitch: f32,
+    doppler: f32,
+    gains: Option<ChannelGains>,
+    finished: Arc<AtomicBool>,
+}
+
+impl Drop for Voice {
+    fn drop(&mut self) {
+        self.finished.store(true, Ordering::Release);
+    }
+}
+
+impl Voice {
+    fn apply_volume(&self, buses: &[f32; AudioBus::COUNT]) {
+        let mut volume = self.volume * buses[AudioBus::Master as usize];
+        if self.bus != AudioBus::Master {
+            volume *= buses[self.bus as usize];
+        }
+        self.sink.set_volume(volume);
+    }
+}
+
+fn audio_thread(receiver: Receiver<AudioCommand>, mut buses: [f32; AudioBus::COUNT]) {
+    App::set_current_thread_name("AudioThread");
+    debug!("Audio", "正在打开音频输出设备...");
+    let (_stream, handle) = match OutputStream::try_default() {
+        Ok(output) => output,
+        Err(e) => {
+            error!("Audio", "无法打开音频输出设备: {}", e);
+            return;
+        }
+    };
+    let mut voices: HashMap<u64, Voice> = HashMap::new();
+    loop {
+        match receiver.recv_timeout(POLL_INTERVAL) {
+            Ok(AudioCommand::Play {
+                id,
+                source,
+                params,
+                spatial,
+                finished,
+            }) => {
+                let sink = match Sink::try_new(&handle) {
+                    Ok(sink) => sink,
+                    Err(e) => {
+                        error!("Audio", "无法创建播放通道: {}", e);
+                        finished.store(true, Ordering::Release);
+                        continue;
+                    }
+                };
+                let gains = spatial.map(|[left, right]| {
+                    Arc::new([AtomicU32::new(left.to_bits()), AtomicU32::new(right.to_bits())])
+                });
+                if let Err(e) = append_source(&sink, source, params.looping, gains.clone()) {
+                    error!("Audio", "无法播放声音: {}", e);
+                    finished.store(true, Ordering::Release);
+                    continue;
+                }
+                sink.set_speed(params.pitch);
+                let voice = Voice {
+                    sink,
+                    bus: params.bus,
+                    volume: params.volume,
+                    pitch: params.pitch,
+                    doppler: 1.0,
+                    gains,
+                    finished,
+                };
+                voice.apply_volume(&buses);
+                voices.insert(id, voice);
+            }
+            Ok(AudioCommand::SetVolume(id, volume)) => {
+                if let Some(voice) = voices.get_mut(&id) {
+                    voice.volume = volume;
+                    voice.apply_volume(&buses);
+                }
+            }
+            Ok(AudioCommand::SetPitch(id, pitch)) => {
+                if let Some(voice) = voices.get_mut(&id) {
+                    voice.pitch = pitch;
+                    voice.sink.set_speed(pitch * voice.doppler);
+                }
+            }
+            Ok(AudioCommand::Pause(id)) => {
+                if let Some(voice) = voices.get(&id) {
+                    voice.sink.pause();
+                }
+            }
+            Ok(AudioCommand::Resume(id)) => {
+                if let Some(voice) = voices.get(&id) {
+                    voice.sink.play();
+                }
+            }
+            Ok(AudioCommand::Stop(id)) => {
+                voices.remove(&id);
+            }
+            Ok(AudioCommand::StopAll) => voices.clear(),
+            Ok(AudioCommand::SetBusVolume(bus, volume)) => {
+                buses[bus as usize] = volume;
+                for voice in voices.values() {
+                    voice.apply_volume(&buses);
+                }
+            }
+            Ok(AudioCommand::SetSpatial(id, [left, right], doppler)) => {
+                if let Some(voice) = voices.get_mut(&id) {
+                    if let Some(gains) = voice.gains.as_ref() {
+                        gains[0].store(left.to_bits(), Ordering::Relaxed);
+                        gains[1].store(right.to_bits(), Ordering::Relaxed);
+                    }
+                    voice.doppler = doppler;
+                    voice.sink.set_speed(voice.pitch * doppler);
+                }
+            }
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => break,
+        }
+        voices.retain(|_, voice| !voice.sink.empty());
+    }
+    debug!("Audio", "音频线程退出");
+}
+
+fn append_source(
+    sink: &Sink,
+    source: SoundSource,
+    looping: bool,
+    gains: Option<ChannelGains>,
+) -> Result<()> {
+    let parse = |e: rodio::decoder::DecoderError| Error::Parse(e.to_string());
+    let source: Box<dyn Source<Item = i16> + Send> = match source {
+        SoundSource::Memory(SoundData::Decoded(pcm)) => {
+            let source = PcmSource { pcm, position: 0 };
+            if looping {
+                Box::new(source.repeat_infinite())
+            } else {
+                Box::new(source)
+            }
+        }
+        SoundSource::Memory(SoundData::Encoded(data)) => {
+            let data = Cursor::new(data);
+            if looping {
+                Box::new(Decoder::new_looped(data).map_err(parse)?)
+            } else {
+                Box::new(Decoder::new(data).map_err(parse)?)
+            }
+        }
+        SoundSource::File(path) => {
+            let file = BufReader::with_capacity(STREAM_CHUNK, File::open(&path)?);
+            if looping {
+                Box::new(Decoder::new_looped(file).map_err(parse)?)
+            } else {
+                Box::new(Decoder::new(file).map_err(parse)?)
+            }
+        }
+    };
+    match gains {
+        Some(gains) => {
+            // 空间化的声音先混为单声道，再按增益分配到左右声道
+            let load = move |i: usize| f32::from_bits(gains[i].load(Ordering::Relaxed));
+            let initial = vec![load(0), load(1)];
+            sink.append(ChannelVolume::new(source, initial).periodic_access(
+                SPATIAL_INTERVAL,
+                move |source| {
+                    source.set_volume(0, load(0));
+                    source.set_volume(1, load(1));
+                },
+            ));
+        }
+        None => sink.append(source),
+    }
+    Ok(())
+}
@@ -0,0 +1,216 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use crate::math::*;
+use crate::{Aabb, Frustum, GpuMesh, Mesh, RenderStats};
+
+/// 合批网格中的子网格
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SubMesh {
+    /// 首个索引在合并索引缓冲中的位置
+    pub first_index: u32,
+    /// 索引数量
+    pub index_count: u32,
+    /// 世界空间包围盒
+    pub aabb: Aabb,
+}
+
+/// 静态合批结果
+///
+/// 包含同一材质下所有静态网格变换到世界空间后合并得到的网格，以及各子网格的范围与包围盒
+#[derive(Debug, Clone)]
+pub struct StaticBatch<K> {
+    /// 材质标识
+    pub material: K,
+    /// 合并后的网格
+    pub mesh: Mesh,
+    /// 子网格，顺序与添加顺序一致
+    pub submeshes: Vec<SubMesh>,
+}
+
+impl<K> StaticBatch<K> {
+    /// 获取整个合批的包围盒
+    pub fn aabb(&self) -> Aabb {
+        self.submeshes
+            .iter()
+            .fold(Aabb::EMPTY, |acc, s| acc.union(&s.aabb))
+    }
+
+    /// 将合批上传到 GPU
+    ///
+    /// # 注解
+    ///
+    /// 只能在渲染线程中调用
+    pub fn upload(self) -> GpuStaticBatch<K> {
+        GpuStaticBatch {
+            mesh: self.mesh.upload(),
+            aabb: self.aabb(),
+            material: self.material,
+            submeshes: self.submeshes,
+        }
+    }
+}
+
+/// 静态网格合批器
+///
+/// 在加载阶段将共享材质的静态网格合并为少量的大网格，以减少绘制调用
+///
+/// # 示例
+///
+/// ```
+/// use gle::{*, math::*};
+///
+/// let mut batcher = StaticBatcher::new();
+/// let rock = Mesh::icosphere(1.0, 1);
+/// for i in 0..100 {
+///     batcher.add("rock", &rock, &Mat4::from_translation(Vec3::new(i as f32 * 3.0, 0.0, 0.0)));
+/// }
+/// let batches = batcher.build();
+/// // 在渲染线程中
+/// let gpu: Vec<_> = batches.into_iter().map(StaticBatch::upload).collect();
+/// ```
+#[derive(Debug, Clone)]
+pub struct StaticBatcher<K> {
+    batches: Vec<StaticBatch<K>>,
+    lookup: HashMap<K, usize>,
+}
+
+impl<K: Eq + Hash + Clone> StaticBatcher<K> {
+    /// 创建合批器
+    pub fn new() -> Self {
+        Self {
+            batches: Vec::new(),
+            lookup: HashMap::new(),
+        }
+    }
+
+    /// 添加静态网格
+    ///
+    /// # 参数
+    /// + `material` - 材质标识，相同材质的网格将被合并
+    /// + `mesh` - 网格
+    /// + `transform` - 网格的世界变换矩阵
+    ///
+    /// # 返回值
+    /// 返回`(合批序号, 子网格序号)`，与 [`StaticBatcher::build`] 的结果对应
+    pub fn add(&mut self, material: K, mesh: &Mesh, transform: &Mat4) -> (usize, usize) {
+        let batch_index = *self.lookup.entry(material.clone()).or_insert_with(|| {
+            self.batches.push(StaticBatch {
+                material,
+                mesh: Mesh::new(),
+                submeshes: Vec::new(),
+            });
+            self.batches.len() - 1
+        });
+        let batch = &mut self.batches[batch_index];
+        let first_index = batch.mesh.indices.len() as u32;
+        batch.mesh.append(mesh, transform);
+        batch.submeshes.push(SubMesh {
+            first_index,
+            index_count: mesh.indices.len() as u32,
+            aabb: mesh.aabb().transformed(transform),
+        });
+        (batch_index, batch.submeshes.len() - 1)
+    }
+
+    /// 完成合批
+    ///
+    /// # 返回值
+    /// 返回所有合批，顺序与各材质首次添加的顺序一致
+    pub fn build(self) -> Vec<StaticBatch<K>> {
+        self.batches
+    }
+}
+
+impl<K: Eq + Hash + Clone> Default for StaticBatcher<K> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 已上传到 GPU 的静态合批
+///
+/// 在被释放时自动删除其 GPU 网格，因此只能在渲染线程中被释放
+#[derive(Debug)]
+pub struct GpuStaticBatch<K> {
+    /// 材质标识
+    pub material: K,
+    mesh: GpuMesh,
+    submeshes: Vec<SubMesh>,
+    aabb: Aabb,
+}
+
+impl<K> GpuStaticBatch<K> {
+    /// 获取合并后的 GPU 网格
+    pub fn mesh(&self) -> &GpuMesh {
+        &self.mesh
+    }
+
+    /// 获取子网格
+    pub fn submeshes(&self) -> &[SubMesh] {
+        &self.submeshes
+    }
+
+    /// 获取整个合批的包围盒
+    pub fn aabb(&self) -> Aabb {
+        self.aabb
+    }
+
+    /// 绘制整个合批
+    pub fn draw(&self) {
+        self.mesh.draw();
+    }
+
+    /// 仅绘制与视锥体相交的子网格
+    ///
+    /// # 参数
+    /// + `frustum` - 视锥体
+    ///
+    /// # 返回值
+    /// 返回实际绘制的子网格数量
+    ///
+    /// # 注解
+    ///
+    /// 索引连续的可见子网格会合并为一次绘制调用
+    pub fn draw_visible(&self, frustum: &Frustum) -> usize {
+        if !frustum.intersects_aabb(&self.aabb) {
+            return 0;
+        }
+        let mut visible = 0;
+        let mut run: Option<(u32, u32)> = None;
+        unsafe { gl::BindVertexArray(self.mesh.vao()) };
+        for s in &self.submeshes {
+            if !frustum.intersects_aabb(&s.aabb) {
+                continue;
+            }
+            visible += 1;
+            run = match run {
+                Some((first, count)) if first + count == s.first_index => {
+                    Some((first, count + s.index_count))
+                }
+                Some(prev) => {
+                    draw_range(prev);
+                    Some((s.first_index, s.index_count))
+                }
+                None => Some((s.first_index, s.index_count)),
+            };
+        }
+        if let Some(prev) = run {
+            draw_range(prev);
+        }
+        unsafe { gl::BindVertexArray(0) };
+        visible
+    }
+}
+
+fn draw_range((first, count): (u32, u32)) {
+    RenderStats::record_draw(count as u64 / 3);
+    unsafe {
+        gl::DrawElements(
+            gl::TRIANGLES,
+            count as i32,
+            gl::UNSIGNED_INT,
+            (first as usize * std::mem::size_of::<u32>()) as *const _,
+        );
+    }
+}
@@ -0,0 +1,251 @@
+use crate::error::Result;
+use crate::math::*;
+use crate::{Camera, GlObjectKind, GlObjects, Program, RenderStats, Scene};
+
+/// 公告板朝向约束
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BillboardMode {
+    /// 球面公告板，始终与摄像机平面平行，适用于精灵与血条
+    Spherical,
+    /// 柱面公告板，仅绕指定轴旋转以朝向摄像机，适用于远处树木等替身
+    Cylindrical(Vec3),
+}
+
+/// 公告板组件
+///
+/// 可以作为组件插入 [`Scene`]，由 [`BillboardRenderer::draw_scene`] 以节点的世界位置绘制
+///
+/// # 示例
+///
+/// ```
+/// use gle::{*, math::*};
+///
+/// let mut scene = Scene::new();
+/// let tree = scene.spawn("tree", Transform::from_translation(Vec3::new(10.0, 0.0, -20.0)));
+/// scene.insert(tree, Billboard {
+///     mode: BillboardMode::Cylindrical(Vec3::Y),
+///     size: Vec2::new(4.0, 6.0),
+///     pivot: Vec2::new(0.5, 0.0),
+///     ..Default::default()
+/// });
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Billboard {
+    /// 朝向约束
+    pub mode: BillboardMode,
+    /// 世界空间中的宽度与高度
+    pub size: Vec2,
+    /// 锚点在四边形内的归一化位置，`(0.5, 0.5)`为中心，`(0.5, 0.0)`为底边中点
+    pub pivot: Vec2,
+    /// 相对节点世界位置的偏移，如血条位于角色头顶
+    pub offset: Vec3,
+    /// 颜色，与纹理颜色相乘
+    pub color: Vec4,
+    /// 纹理，为`None`时仅使用颜色
+    pub texture: Option<u32>,
+    /// 纹理区域，依次为左下角`u`、`v`与宽度、高度，用于图集
+    pub uv_rect: Vec4,
+    /// 透明度裁剪阈值，透明度低于该值的片段将被丢弃
+    pub alpha_cutoff: f32,
+}
+
+impl Default for Billboard {
+    fn default() -> Self {
+        Self {
+            mode: BillboardMode::Spherical,
+            size: Vec2::ONE,
+            pivot: Vec2::splat(0.5),
+            offset: Vec3::ZERO,
+            color: Vec4::ONE,
+            texture: None,
+            uv_rect: Vec4::new(0.0, 0.0, 1.0, 1.0),
+            alpha_cutoff: 0.5,
+        }
+    }
+}
+
+impl Billboard {
+    /// 计算公告板的模型矩阵
+    ///
+    /// # 参数
+    /// + `position` - 锚点的世界位置(不含`offset`)
+    /// + `camera` - 摄像机
+    ///
+    /// # 返回值
+    /// 返回将`[0, 1]`范围的单位四边形变换到世界空间的矩阵
+    pub fn model_matrix(&self, position: Vec3, camera: &Camera) -> Mat4 {
+        let position = position + self.offset;
+        let (right, up) = match self.mode {
+            BillboardMode::Spherical => (camera.right(), camera.up()),
+            BillboardMode::Cylindrical(axis) => {
+                let axis = axis.normalize_or_zero();
+                let to_camera = camera.position - position;
+                let flat = to_camera - axis * to_camera.dot(axis);
+                let right = if flat.length_squared() > f32::EPSILON {
+                    axis.cross(flat).normalize()
+                } else {
+                    camera.right()
+                };
+                (right, axis)
+            }
+        };
+        let right = right * self.size.x;
+        let up = up * self.size.y;
+        let normal = right.cross(up).normalize_or_zero();
+        let origin = position - right * self.pivot.x - up * self.pivot.y;
+        Mat4::from_cols(
+            right.extend(0.0),
+            up.extend(0.0),
+            normal.extend(0.0),
+            origin.extend(1.0),
+        )
+    }
+}
+
+const VS: &str = r#"
+#version 330 core
+layout (location = 0) in vec2 aCorner;
+
+uniform mat4 uViewProj;
+uniform mat4 uModel;
+uniform vec4 uUvRect;
+
+out vec2 vUV;
+
+void main()
+{
+    gl_Position = uViewProj * uModel * vec4(aCorner, 0.0, 1.0);
+    vUV = uUvRect.xy + aCorner * uUvRect.zw;
+}
+"#;
+
+const FS: &str = r#"
+#version 330 core
+in vec2 vUV;
+out vec4 FragColor;
+
+uniform vec4 uColor;
+uniform bool uTextured;
+uniform sampler2D uTexture;
+uniform float uAlphaCutoff;
+
+void main()
+{
+    vec4 color = uColor;
+    if (uTextured) {
+        color *= texture(uTexture, vUV);
+    }
+    if (color.a < uAlphaCutoff) {
+        discard;
+    }
+    FragColor = color;
+}
+"#;
+
+const QUAD: [f32; 8] = [0.0, 0.0, 1.0, 0.0, 0.0, 1.0, 1.0, 1.0];
+
+/// 公告板渲染器
+///
+/// # 注解
+///
+/// 该类型只能在渲染线程中创建、使用与释放
+pub struct BillboardRenderer {
+    program: Program,
+    vao: u32,
+    vbo: u32,
+}
+
+impl BillboardRenderer {
+    /// 创建公告板渲染器
+    ///
+    /// # 返回值
+    /// 成功时返回公告板渲染器，内置着色器编译失败时返回错误
+    pub fn new() -> Result<Self> {
+        let program = Program::new(VS, FS)?;
+        let (mut vao, mut vbo) = (0, 0);
+        unsafe {
+            gl::GenVertexArrays(1, &mut vao);
+            gl::GenBuffers(1, &mut vbo);
+            gl::BindVertexArray(vao);
+            gl::BindBuffer(gl::ARRAY_BUFFER, vbo);
+            gl::BufferData(
+                gl::ARRAY_BUFFER,
+                std::mem::size_of_val(&QUAD) as isize,
+                QUAD.as_ptr() as *const _,
+                gl::STATIC_DRAW,
+            );
+            gl::EnableVertexAttribArray(0);
+            gl::VertexAttribPointer(0, 2, gl::FLOAT, gl::FALSE, 0, std::ptr::null());
+            gl::BindVertexArray(0);
+        }
+        GlObjects::track(GlObjectKind::VertexArray, vao);
+        GlObjects::track(GlObjectKind::Buffer, vbo);
+        Ok(Self { program, vao, vbo })
+    }
+
+    /// 绘制单个公告板
+    ///
+    /// # 参数
+    /// + `billboard` - 公告板
+    /// + `position` - 锚点的世界位置
+    /// + `camera` - 摄像机
+    pub fn draw(&self, billboard: &Billboard, position: Vec3, camera: &Camera) {
+        self.program.bind();
+        self.program.set("uViewProj", &camera.view_projection());
+        self.draw_one(billboard, position, camera);
+    }
+
+    /// 绘制场景中所有带有 [`Billboard`] 组件的节点
+    ///
+    /// # 参数
+    /// + `scene` - 场景，应已调用 [`Scene::update_world_transforms`]
+    /// + `camera` - 摄像机
+    ///
+    /// # 注解
+    ///
+    /// 公告板将按由远及近的顺序绘制，以便半透明边缘正确混合
+    pub fn draw_scene(&self, scene: &Scene, camera: &Camera) {
+        let mut items: Vec<(f32, Vec3, &Billboard)> = scene
+            .query::<Billboard>()
+            .filter_map(|(e, b)| {
+                let position = scene.get(e)?.world_matrix().w_axis.truncate();
+                Some((position.distance_squared(camera.position), position, b))
+            })
+            .collect();
+        items.sort_by(|a, b| b.0.total_cmp(&a.0));
+        self.program.bind();
+        self.program.set("uViewProj", &camera.view_projection());
+        for (_, position, billboard) in items {
+            self.draw_one(billboard, position, camera);
+        }
+    }
+
+    fn draw_one(&self, billboard: &Billboard, position: Vec3, camera: &Camera) {
+        self.program
+            .set("uModel", &billboard.model_matrix(position, camera));
+        self.program.set("uUvRect", &billboard.uv_rect);
+        self.program.set("uColor", &billboard.color);
+        self.program.set("uTextured", &billboard.texture.is_some());
+        self.program.set("uTexture", &0i32);
+        self.program.set("uAlphaCutoff", &billboard.alpha_cutoff);
+        unsafe {
+            gl::ActiveTexture(gl::TEXTURE0);
+            gl::BindTexture(gl::TEXTURE_2D, billboard.texture.unwrap_or(0));
+            gl::BindVertexArray(self.vao);
+            gl::DrawArrays(gl::TRIANGLE_STRIP, 0, 4);
+            RenderStats::record_draw(2);
+            gl::BindVertexArray(0);
+        }
+    }
+}
+
+impl Drop for BillboardRenderer {
+    fn drop(&mut self) {
+        GlObjects::untrack(GlObjectKind::VertexArray, self.vao);
+        GlObjects::untrack(GlObjectKind::Buffer, self.vbo);
+        unsafe {
+            gl::DeleteBuffers(1, &self.vbo);
+            gl::DeleteVertexArrays(1, &self.vao);
+        }
+    }
+}
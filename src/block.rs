@@ -0,0 +1,433 @@
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Error, Result};
+use crate::math::*;
+use crate::{Atlas, AtlasBuilder, BlockAppearance, BlockId, Face, TextureArray};
+
+/// 方块类型定义
+///
+/// 可以从 TOML 格式的方块定义文件加载，见 [`BlockRegistry::register_file`]。
+/// 面纹理按 [`BlockType::faces`]、[`BlockType::top`]/[`BlockType::bottom`]/[`BlockType::side`]、
+/// [`BlockType::texture`] 的顺序查找
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BlockType {
+    /// 方块名称，在注册表中唯一，用于在存档中保持方块ID稳定
+    pub name: String,
+    /// 所有面的默认纹理名称
+    #[serde(default)]
+    pub texture: String,
+    /// 顶面(`+Y`)纹理名称
+    #[serde(default)]
+    pub top: Option<String>,
+    /// 底面(`-Y`)纹理名称
+    #[serde(default)]
+    pub bottom: Option<String>,
+    /// 侧面纹理名称
+    #[serde(default)]
+    pub side: Option<String>,
+    /// 逐面指定的纹理名称，优先级最高
+    #[serde(default)]
+    pub faces: BTreeMap<Face, String>,
+    /// 是否透明，透明方块不遮挡相邻方块的面，纹理的 alpha 通道用于镂空
+    #[serde(default)]
+    pub transparent: bool,
+    /// 发光等级，范围为`[0, 15]`
+    #[serde(default)]
+    pub emission: u8,
+}
+
+impl BlockType {
+    /// 创建所有面使用同一纹理的不透明方块类型
+    ///
+    /// # 参数
+    /// + `name` - 方块名称
+    /// + `texture` - 纹理名称
+    pub fn new(name: &str, texture: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            texture: texture.to_string(),
+            top: None,
+            bottom: None,
+            side: None,
+            faces: BTreeMap::new(),
+            transparent: false,
+            emission: 0,
+        }
+    }
+
+    /// 设置顶面、侧面与底面的纹理
+    pub fn with_top_side_bottom(mut self, top: &str, side: &str, bottom: &str) -> Self {
+        self.top = Some(top.to_string());
+        self.side = Some(side.to_string());
+        self.bottom = Some(bottom.to_string());
+        self
+    }
+
+    /// 设置指定面的纹理
+    pub fn with_face(mut self, face: Face, texture: &str) -> Self {
+        self.faces.insert(face, texture.to_string());
+        self
+    }
+
+    /// 设置是否透明
+    pub fn with_transparent(mut self, transparent: bool) -> Self {
+        self.transparent = transparent;
+        self
+    }
+
+    /// 设置发光等级
+    pub fn with_emission(mut self, emission: u8) -> Self {
+        self.emission = emission;
+        self
+    }
+
+    /// 获取指定面的纹理名称
+    pub fn face_texture(&self, face: Face) -> &str {
+        if let Some(texture) = self.faces.get(&face) {
+            return texture;
+        }
+        let specific = match face {
+            Face::PosY => &self.top,
+            Face::NegY => &self.bottom,
+            _ => &self.side,
+        };
+        specific.as_deref().unwrap_or(&self.texture)
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct BlockFile {
+    #[serde(default, rename = "block")]
+    blocks: Vec<BlockType>,
+}
+
+#[derive(Debug, Clone)]
+struct RegisteredBlock {
+    desc: BlockType,
+    layers: [u32; 6],
+}
+
+/// 方块注册表
+///
+/// 为方块类型分配 [`BlockId`]，并把每个面的纹理名称映射为纹理数组的层序号。
+/// 注册表实现了 [`BlockAppearance`]，可直接交给 [`VoxelMesher`](crate::VoxelMesher) 或
+/// [`ChunkStreamer`](crate::ChunkStreamer) 生成网格，纹理数组由 [`BlockRegistry::load_textures`] 创建后交给
+/// [`VoxelRenderer`](crate::VoxelRenderer) 绘制
+///
+/// # 示例
+///
+/// ```
+/// use gle::*;
+///
+/// let mut registry = BlockRegistry::new();
+/// let stone = registry.register(BlockType::new("stone", "stone"));
+/// let grass = registry.register(
+///     BlockType::new("grass", "dirt").with_top_side_bottom("grass_top", "grass_side", "dirt"),
+/// );
+/// let lamp = registry.register(BlockType::new("lamp", "lamp").with_emission(15));
+/// assert_eq!(registry.id("grass"), Some(grass));
+/// assert_eq!(registry.texture_names(), ["stone", "grass_side", "grass_top", "dirt", "lamp"]);
+/// assert_eq!(registry.face_texture(grass, Face::NegY), 3);
+/// assert_eq!(registry.get(lamp).unwrap().emission, 15);
+///
+/// // 存档时保存名称到ID的映射，读档时先恢复映射再注册，已有方块的ID保持不变
+/// let ids = registry.id_map();
+/// let mut restored = BlockRegistry::with_id_map(&ids);
+/// restored.register(BlockType::new("glass", "glass").with_transparent(true));
+/// restored.register(BlockType::new("stone", "stone"));
+/// assert_eq!(restored.id("stone"), Some(stone));
+/// ```
+///
+/// # 注解
+///
+/// 名称为`air`的方块固定为 [`BlockId::AIR`]，不能注册。纹理层按纹理名称首次出现的顺序分配，
+/// 同名纹理共享同一层
+#[derive(Debug, Clone)]
+pub struct BlockRegistry {
+    blocks: Vec<Option<RegisteredBlock>>,
+    ids: HashMap<String, BlockId>,
+    reserved: HashMap<String, BlockId>,
+    textures: Vec<String>,
+    layers: HashMap<String, u32>,
+}
+
+impl Default for BlockRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BlockRegistry {
+    /// 创建只含空气的注册表
+    pub fn new() -> Self {
+        let mut ids = HashMap::new();
+        ids.insert("air".to_string(), BlockId::AIR);
+        Self {
+            blocks: vec![None],
+            ids,
+            reserved: HashMap::new(),
+            textures: Vec::new(),
+            layers: HashMap::new(),
+        }
+    }
+
+    /// 以存档中保存的名称到ID映射创建注册表
+    ///
+    /// # 参数
+    /// + `ids` - 由 [`BlockRegistry::id_map`] 获取的映射
+    ///
+    /// # 注解
+    ///
+    /// 映射中的名称在注册时使用原有ID，新名称使用未被占用的最小ID。
+    /// 映射中未再注册的方块保留其ID，[`BlockRegistry::get`] 对其返回`None`
+    pub fn with_id_map(ids: &BTreeMap<String, u16>) -> Self {
+        let mut registry = Self::new();
+        for (name, id) in ids {
+            if *id != 0 && name != "air" {
+                registry.reserved.insert(name.clone(), BlockId(*id));
+            }
+        }
+        registry
+    }
+
+    /// 注册方块类型
+    ///
+    /// # 参数
+    /// + `block` - 方块类型定义
+    ///
+    /// # 返回值
+    /// 方块ID，同名方块重复注册时替换原有定义并返回原有ID
+    pub fn register(&mut self, block: BlockType) -> BlockId {
+        assert!(block.name != "air", "不能注册名为 air 的方块");
+        let id = match self.ids.get(&block.name) {
+            Some(id) => *id,
+            None => {
+                let id = match self.reserved.get(&block.name) {
+                    Some(id) => *id,
+                    None => self.next_free_id(),
+                };
+                self.ids.insert(block.name.clone(), id);
+                id
+            }
+        };
+        let layers = Face::ALL.map(|face| self.texture_layer_or_insert(block.face_texture(face)));
+        let index = id.0 as usize;
+        if self.blocks.len() <= index {
+            self.blocks.resize(index + 1, None);
+        }
+        self.blocks[index] = Some(RegisteredBlock {
+            desc: block,
+            layers,
+        });
+        id
+    }
+
+    /// 从 TOML 源码注册方块类型
+    ///
+    /// 源码中以`[[block]]`数组列出方块类型，例如：
+    ///
+    /// ```toml
+    /// [[block]]
+    /// name = "grass"
+    /// texture = "dirt"
+    /// top = "grass_top"
+    /// side = "grass_side"
+    ///
+    /// [[block]]
+    /// name = "glass"
+    /// texture = "glass"
+    /// transparent = true
+    ///
+    /// [[block]]
+    /// name = "furnace"
+    /// texture = "furnace_side"
+    /// emission = 13
+    /// faces = { pos_z = "furnace_front" }
+    /// ```
+    ///
+    /// # 参数
+    /// + `src` - TOML 源码
+    ///
+    /// # 返回值
+    /// 成功时返回按定义顺序排列的方块ID
+    pub fn register_toml(&mut self, src: &str) -> Result<Vec<BlockId>> {
+        let file = toml::from_str::<BlockFile>(src).map_err(|e| Error::Parse(e.to_string()))?;
+        Ok(file
+            .blocks
+            .into_iter()
+            .map(|block| self.register(block))
+            .collect())
+    }
+
+    /// 从方块定义文件注册方块类型，格式见 [`BlockRegistry::register_toml`]
+    ///
+    /// # 参数
+    /// + `path` - 方块定义文件路径
+    pub fn register_file<P: AsRef<Path>>(&mut self, path: P) -> Result<Vec<BlockId>> {
+        let path = path.as_ref();
+        let src = std::fs::read_to_string(path)?;
+        let file = toml::from_str::<BlockFile>(&src)
+            .map_err(|e| Error::Parse(format!("{}: {}", path.display(), e)))?;
+        Ok(file
+            .blocks
+            .into_iter()
+            .map(|block| self.register(block))
+            .collect())
+    }
+
+    /// 由名称获取方块ID
+    pub fn id(&self, name: &str) -> Option<BlockId> {
+        self.ids.get(name).copied()
+    }
+
+    /// 获取方块类型定义，空气与未注册的ID返回`None`
+    pub fn get(&self, id: BlockId) -> Option<&BlockType> {
+        self.blocks
+            .get(id.0 as usize)
+            .and_then(|block| block.as_ref())
+            .map(|block| &block.desc)
+    }
+
+    /// 已注册的方块类型数量，不含空气
+    pub fn len(&self) -> usize {
+        self.blocks.iter().flatten().count()
+    }
+
+    /// 是否没有注册任何方块类型
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// 遍历已注册的方块ID与类型定义
+    pub fn iter(&self) -> impl Iterator<Item = (BlockId, &BlockType)> {
+        self.blocks
+            .iter()
+            .enumerate()
+            .filter_map(|(i, block)| block.as_ref().map(|block| (BlockId(i as u16), &block.desc)))
+    }
+
+    /// 获取名称到ID的映射，应随存档保存，读档时交给 [`BlockRegistry::with_id_map`]
+    ///
+    /// # 注解
+    ///
+    /// 映射包含本次未再注册但仍保留ID的方块，以免它们的ID被后续的新方块占用
+    pub fn id_map(&self) -> BTreeMap<String, u16> {
+        self.reserved
+            .iter()
+            .chain(self.ids.iter())
+            .map(|(name, id)| (name.clone(), id.0))
+            .collect()
+    }
+
+    /// 获取按层序号排列的纹理名称
+    pub fn texture_names(&self) -> &[String] {
+        &self.textures
+    }
+
+    /// 获取纹理名称对应的层序号
+    pub fn texture_layer(&self, name: &str) -> Option<u32> {
+        self.layers.get(name).copied()
+    }
+
+    /// 获取方块指定面的纹理层序号，空气与未注册的方块返回`0`
+    pub fn face_texture(&self, id: BlockId, face: Face) -> u32 {
+        self.registered(id)
+            .map_or(0, |block| block.layers[face as usize])
+    }
+
+    /// 从目录加载所有纹理并打包为图集，每个纹理占一层，层序号与 [`BlockRegistry::texture_layer`] 一致
+    ///
+    /// # 参数
+    /// + `dir` - 纹理目录，纹理名称为`name`的图像文件为`dir/name.png`
+    ///
+    /// # 返回值
+    /// 成功时返回图集，文件读取、解码失败或纹理尺寸不一致时返回错误
+    ///
+    /// # 注解
+    ///
+    /// 不需要 OpenGL 上下文，可以在工作线程中调用
+    pub fn build_atlas<P: AsRef<Path>>(&self, dir: P) -> Result<Atlas> {
+        let dir = dir.as_ref();
+        let mut builder = AtlasBuilder::new().with_padding(0);
+        for name in &self.textures {
+            builder.add_file(name, dir.join(format!("{}.png", name)))?;
+        }
+        let Some(first) = self.textures.first() else {
+            return builder.build_layers(1, 1);
+        };
+        let (width, height) = image::image_dimensions(dir.join(format!("{}.png", first)))
+            .map_err(|e| Error::Parse(e.to_string()))?;
+        let atlas = builder.build_layers(width, height)?;
+        let size = UVec2::new(width, height);
+        for (layer, name) in self.textures.iter().enumerate() {
+            let region = atlas.region(name);
+            if !region.is_some_and(|r| r.layer == layer as u32 && r.size == size) {
+                return Err(Error::Parse(format!(
+                    "方块纹理 {} 的尺寸与 {} 不一致",
+                    name, first
+                )));
+            }
+        }
+        Ok(atlas)
+    }
+
+    /// 从目录加载所有纹理并创建纹理数组，见 [`BlockRegistry::build_atlas`]
+    ///
+    /// # 参数
+    /// + `dir` - 纹理目录
+    /// + `srgb` - 是否为 sRGB 颜色空间
+    ///
+    /// # 注解
+    ///
+    /// 只能在渲染线程中调用
+    pub fn load_textures<P: AsRef<Path>>(&self, dir: P, srgb: bool) -> Result<TextureArray> {
+        Ok(self.build_atlas(dir)?.upload_array(srgb))
+    }
+
+    fn registered(&self, id: BlockId) -> Option<&RegisteredBlock> {
+        self.blocks
+            .get(id.0 as usize)
+            .and_then(|block| block.as_ref())
+    }
+
+    fn next_free_id(&self) -> BlockId {
+        let taken = |id: u16| {
+            self.blocks
+                .get(id as usize)
+                .is_some_and(|block| block.is_some())
+                || self.ids.values().any(|other| other.0 == id)
+                || self.reserved.values().any(|other| other.0 == id)
+        };
+        let id = (1..=u16::MAX).find(|id| !taken(*id)).expect("方块ID已耗尽");
+        BlockId(id)
+    }
+
+    fn texture_layer_or_insert(&mut self, name: &str) -> u32 {
+        if let Some(layer) = self.layers.get(name) {
+            return *layer;
+        }
+        let layer = self.textures.len() as u32;
+        self.textures.push(name.to_string());
+        self.layers.insert(name.to_string(), layer);
+        layer
+    }
+}
+
+impl BlockAppearance for BlockRegistry {
+    fn is_opaque(&self, block: BlockId) -> bool {
+        self.registered(block)
+            .is_some_and(|block| !block.desc.transparent)
+    }
+
+    fn face_texture(&self, block: BlockId, face: Face) -> u32 {
+        BlockRegistry::face_texture(self, block, face)
+    }
+
+    fn light_emission(&self, block: BlockId) -> u8 {
+        self.registered(block)
+            .map_or(0, |block| block.desc.emission.min(15))
+    }
+}
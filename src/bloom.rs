@@ -0,0 +1,271 @@
+use crate::error::Result;
+use crate::math::*;
+use crate::{
+    draw_fullscreen_triangle, AttachmentFormat, Framebuffer, PostContext, PostEffect, Program,
+    FULLSCREEN_VS,
+};
+
+const PREFILTER_FS: &str = r#"
+#version 330 core
+in vec2 vUV;
+out vec4 FragColor;
+
+uniform sampler2D uInput;
+uniform vec4 uThreshold; // (threshold, threshold - knee, 2 * knee, 0.25 / knee)
+uniform float uClamp;
+
+void main()
+{
+    vec3 color = min(texture(uInput, vUV).rgb, vec3(uClamp));
+    float brightness = max(color.r, max(color.g, color.b));
+    float soft = clamp(brightness - uThreshold.y, 0.0, uThreshold.z);
+    soft = soft * soft * uThreshold.w;
+    float contribution = max(soft, brightness - uThreshold.x) / max(brightness, 1e-4);
+    FragColor = vec4(color * contribution, 1.0);
+}
+"#;
+
+const DOWNSAMPLE_FS: &str = r#"
+#version 330 core
+in vec2 vUV;
+out vec4 FragColor;
+
+uniform sampler2D uInput;
+
+void main()
+{
+    vec2 t = 1.0 / vec2(textureSize(uInput, 0));
+    vec3 a = texture(uInput, vUV + t * vec2(-2.0, 2.0)).rgb;
+    vec3 b = texture(uInput, vUV + t * vec2(0.0, 2.0)).rgb;
+    vec3 c = texture(uInput, vUV + t * vec2(2.0, 2.0)).rgb;
+    vec3 d = texture(uInput, vUV + t * vec2(-2.0, 0.0)).rgb;
+    vec3 e = texture(uInput, vUV).rgb;
+    vec3 f = texture(uInput, vUV + t * vec2(2.0, 0.0)).rgb;
+    vec3 g = texture(uInput, vUV + t * vec2(-2.0, -2.0)).rgb;
+    vec3 h = texture(uInput, vUV + t * vec2(0.0, -2.0)).rgb;
+    vec3 i = texture(uInput, vUV + t * vec2(2.0, -2.0)).rgb;
+    vec3 j = texture(uInput, vUV + t * vec2(-1.0, 1.0)).rgb;
+    vec3 k = texture(uInput, vUV + t * vec2(1.0, 1.0)).rgb;
+    vec3 l = texture(uInput, vUV + t * vec2(-1.0, -1.0)).rgb;
+    vec3 m = texture(uInput, vUV + t * vec2(1.0, -1.0)).rgb;
+    vec3 color = e * 0.125;
+    color += (a + c + g + i) * 0.03125;
+    color += (b + d + f + h) * 0.0625;
+    color += (j + k + l + m) * 0.125;
+    FragColor = vec4(color, 1.0);
+}
+"#;
+
+const UPSAMPLE_FS: &str = r#"
+#version 330 core
+in vec2 vUV;
+out vec4 FragColor;
+
+uniform sampler2D uInput;
+uniform float uRadius;
+
+void main()
+{
+    vec2 t = uRadius / vec2(textureSize(uInput, 0));
+    vec3 color = texture(uInput, vUV).rgb * 4.0;
+    color += (texture(uInput, vUV + vec2(-t.x, 0.0)).rgb
+            + texture(uInput, vUV + vec2(t.x, 0.0)).rgb
+            + texture(uInput, vUV + vec2(0.0, -t.y)).rgb
+            + texture(uInput, vUV + vec2(0.0, t.y)).rgb) * 2.0;
+    color += texture(uInput, vUV + vec2(-t.x, -t.y)).rgb
+           + texture(uInput, vUV + vec2(t.x, -t.y)).rgb
+           + texture(uInput, vUV + vec2(-t.x, t.y)).rgb
+           + texture(uInput, vUV + vec2(t.x, t.y)).rgb;
+    FragColor = vec4(color / 16.0, 1.0);
+}
+"#;
+
+const COMPOSITE_FS: &str = r#"
+#version 330 core
+in vec2 vUV;
+out vec4 FragColor;
+
+uniform sampler2D uInput;
+uniform sampler2D uBloom;
+uniform float uIntensity;
+uniform vec3 uTint;
+
+void main()
+{
+    vec4 color = texture(uInput, vUV);
+    vec3 bloom = texture(uBloom, vUV).rgb * uTint;
+    FragColor = vec4(color.rgb + bloom * uIntensity, color.a);
+}
+"#;
+
+/// 泛光(Bloom)
+///
+/// 先以软阈值提取高亮部分，再逐级降采样生成模糊金字塔，随后逐级升采样叠加，最后与场景颜色相加。
+/// 应作用于未经色调映射的 HDR 颜色
+///
+/// # 示例
+///
+/// ```
+/// use gle::*;
+///
+/// fn render_init() {
+///     let mut bloom = Bloom::new(1280, 720).unwrap();
+///     bloom.threshold = 1.2;
+///     bloom.intensity = 0.8;
+///     let mut chain = PostProcessChain::new(1280, 720, AttachmentFormat::Rgba16F).unwrap();
+///     chain.push(bloom);
+/// }
+/// ```
+///
+/// # 注解
+///
+/// 该类型只能在渲染线程中创建、使用与释放
+pub struct Bloom {
+    /// 亮度阈值，亮度高于该值的部分产生泛光
+    pub threshold: f32,
+    /// 阈值软过渡宽度，为 0 时为硬阈值
+    pub knee: f32,
+    /// 泛光强度
+    pub intensity: f32,
+    /// 泛光颜色
+    pub tint: Vec3,
+    /// 升采样滤波半径(纹素)，越大越模糊
+    pub radius: f32,
+    /// 提取高亮时的亮度上限，用于抑制极亮像素产生的闪烁
+    pub clamp: f32,
+    prefilter: Program,
+    downsample: Program,
+    upsample: Program,
+    composite: Program,
+    mips: Vec<Framebuffer>,
+    max_levels: usize,
+}
+
+impl Bloom {
+    /// 使用最多 6 级金字塔创建泛光效果
+    ///
+    /// # 参数
+    /// + `width` - 输出宽度
+    /// + `height` - 输出高度
+    pub fn new(width: u32, height: u32) -> Result<Self> {
+        Self::with_levels(width, height, 6)
+    }
+
+    /// 使用指定的最大金字塔级数创建泛光效果
+    ///
+    /// # 参数
+    /// + `width` - 输出宽度
+    /// + `height` - 输出高度
+    /// + `levels` - 最大降采样级数，第一级为输出的一半大小，最小边长不足 2 像素时提前停止
+    pub fn with_levels(width: u32, height: u32, levels: usize) -> Result<Self> {
+        let mut bloom = Self {
+            threshold: 1.0,
+            knee: 0.5,
+            intensity: 0.6,
+            tint: Vec3::ONE,
+            radius: 1.0,
+            clamp: 65000.0,
+            prefilter: Program::new(FULLSCREEN_VS, PREFILTER_FS)?,
+            downsample: Program::new(FULLSCREEN_VS, DOWNSAMPLE_FS)?,
+            upsample: Program::new(FULLSCREEN_VS, UPSAMPLE_FS)?,
+            composite: Program::new(FULLSCREEN_VS, COMPOSITE_FS)?,
+            mips: Vec::new(),
+            max_levels: levels.max(1),
+        };
+        bloom.resize(width, height)?;
+        Ok(bloom)
+    }
+
+    /// 获取泛光金字塔的级数
+    pub fn levels(&self) -> usize {
+        self.mips.len()
+    }
+
+    /// 获取最近一次计算的泛光纹理(输出一半大小)
+    pub fn bloom_texture(&self) -> u32 {
+        self.mips[0].colors()[0].id()
+    }
+
+    /// 只计算泛光纹理而不合成到颜色，结果通过 [`Bloom::bloom_texture`] 获取
+    ///
+    /// # 参数
+    /// + `input` - HDR 场景颜色纹理
+    pub fn compute(&mut self, input: u32) {
+        let knee = (self.threshold * self.knee).max(1e-4);
+        let first = &self.mips[0];
+        first.bind();
+        self.prefilter.bind();
+        unsafe {
+            gl::ActiveTexture(gl::TEXTURE0);
+            gl::BindTexture(gl::TEXTURE_2D, input);
+        }
+        self.prefilter.set("uInput", &0);
+        self.prefilter.set(
+            "uThreshold",
+            &Vec4::new(self.threshold, self.threshold - knee, 2.0 * knee, 0.25 / knee),
+        );
+        self.prefilter.set("uClamp", &self.clamp);
+        draw_fullscreen_triangle();
+
+        self.downsample.bind();
+        self.downsample.set("uInput", &0);
+        for pair in self.mips.windows(2) {
+            pair[1].bind();
+            pair[0].colors()[0].bind(0);
+            draw_fullscreen_triangle();
+        }
+
+        self.upsample.bind();
+        self.upsample.set("uInput", &0);
+        self.upsample.set("uRadius", &self.radius);
+        unsafe {
+            gl::Enable(gl::BLEND);
+            gl::BlendFunc(gl::ONE, gl::ONE);
+        }
+        for pair in self.mips.windows(2).rev() {
+            pair[0].bind();
+            pair[1].colors()[0].bind(0);
+            draw_fullscreen_triangle();
+        }
+        unsafe { gl::Disable(gl::BLEND) };
+    }
+}
+
+impl PostEffect for Bloom {
+    fn name(&self) -> &str {
+        "bloom"
+    }
+
+    fn resize(&mut self, width: u32, height: u32) -> Result<()> {
+        self.mips.clear();
+        let (mut w, mut h) = (width / 2, height / 2);
+        while self.mips.len() < self.max_levels && w >= 2 && h >= 2 {
+            self.mips
+                .push(Framebuffer::new(w, h, &[AttachmentFormat::Rgba16F], None)?);
+            w /= 2;
+            h /= 2;
+        }
+        if self.mips.is_empty() {
+            self.mips
+                .push(Framebuffer::new(1, 1, &[AttachmentFormat::Rgba16F], None)?);
+        }
+        Ok(())
+    }
+
+    fn apply(&mut self, ctx: &PostContext, input: u32, output: Option<&Framebuffer>) {
+        self.compute(input);
+        ctx.bind_output(output);
+        self.composite.bind();
+        unsafe {
+            gl::ActiveTexture(gl::TEXTURE0);
+            gl::BindTexture(gl::TEXTURE_2D, input);
+            gl::ActiveTexture(gl::TEXTURE1);
+            gl::BindTexture(gl::TEXTURE_2D, self.bloom_texture());
+        }
+        self.composite.set("uInput", &0);
+        self.composite.set("uBloom", &1);
+        self.composite.set("uIntensity", &self.intensity);
+        self.composite.set("uTint", &self.tint);
+        draw_fullscreen_triangle();
+    }
+}
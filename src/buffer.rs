@@ -0,0 +1,105 @@
+use crate::{GlObjectKind, GlObjects};
+
+/// uniform 缓冲对象
+///
+/// 对 OpenGL uniform 缓冲对象的封装，在被释放时自动删除缓冲对象
+///
+/// # 示例
+///
+/// ```
+/// use gle::{*, math::*};
+///
+/// #[repr(C)]
+/// #[derive(Clone, Copy)]
+/// struct Globals {
+///     view_projection: Mat4,
+///     time: Vec4,
+/// }
+///
+/// fn render_init() {
+///     let ubo = UniformBuffer::new::<Globals>();
+///     ubo.bind_base(1);
+/// }
+/// ```
+///
+/// # 注解
+///
+/// 上传的结构体应使用`#[repr(C)]`并满足 std140 布局规则，最简单的做法是只使用`vec4`与`mat4`大小的成员。
+/// 该类型的所有方法只能在渲染线程中调用
+#[derive(Debug)]
+pub struct UniformBuffer {
+    id: u32,
+    size: usize,
+}
+
+impl UniformBuffer {
+    /// 创建大小与类型`T`相同的 uniform 缓冲
+    pub fn new<T: Copy>() -> Self {
+        Self::with_size(std::mem::size_of::<T>())
+    }
+
+    /// 创建指定字节大小的 uniform 缓冲
+    ///
+    /// # 参数
+    /// + `size` - 字节大小
+    pub fn with_size(size: usize) -> Self {
+        let mut id = 0;
+        unsafe {
+            gl::GenBuffers(1, &mut id);
+            gl::BindBuffer(gl::UNIFORM_BUFFER, id);
+            gl::BufferData(
+                gl::UNIFORM_BUFFER,
+                size as isize,
+                std::ptr::null(),
+                gl::DYNAMIC_DRAW,
+            );
+            gl::BindBuffer(gl::UNIFORM_BUFFER, 0);
+        }
+        GlObjects::track(GlObjectKind::Buffer, id);
+        Self { id, size }
+    }
+
+    /// 获取 OpenGL 缓冲对象ID
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    /// 获取字节大小
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// 更新缓冲内容
+    ///
+    /// # 参数
+    /// + `value` - 新的内容，大小不能超过缓冲大小
+    pub fn update<T: Copy>(&self, value: &T) {
+        let size = std::mem::size_of::<T>();
+        assert!(size <= self.size, "数据大小超出 uniform 缓冲大小");
+        unsafe {
+            gl::BindBuffer(gl::UNIFORM_BUFFER, self.id);
+            gl::BufferSubData(
+                gl::UNIFORM_BUFFER,
+                0,
+                size as isize,
+                value as *const T as *const _,
+            );
+            gl::BindBuffer(gl::UNIFORM_BUFFER, 0);
+        }
+    }
+
+    /// 将缓冲绑定到指定的 uniform 块绑定点
+    ///
+    /// # 参数
+    /// + `binding` - 绑定点，与 [`Program::set_block_binding`](crate::Program::set_block_binding) 一致
+    pub fn bind_base(&self, binding: u32) {
+        unsafe { gl::BindBufferBase(gl::UNIFORM_BUFFER, binding, self.id) };
+    }
+}
+
+impl Drop for UniformBuffer {
+    fn drop(&mut self) {
+        GlObjects::untrack(GlObjectKind::Buffer, self.id);
+        unsafe { gl::DeleteBuffers(1, &self.id) };
+    }
+}
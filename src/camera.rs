@@ -0,0 +1,321 @@
+use gom::*;
+
+use crate::math::*;
+use crate::{CameraTarget, Resources, MAX_CLIP_PLANES, WINDOW_KEY};
+
+const RENDER: &str = id!(RENDER);
+/// 主摄像机实例ID
+pub const MAIN_CAMERA: &str = id!(@RENDER.MAIN_CAMERA);
+
+/// 摄像机投影方式
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Projection {
+    /// 透视投影
+    Perspective {
+        /// 垂直视野角，单位为弧度
+        fov_y: f32,
+        /// 近裁剪面距离
+        near: f32,
+        /// 远裁剪面距离
+        far: f32,
+    },
+    /// 正交投影
+    Orthographic {
+        /// 可见区域的高度，宽度由宽高比决定
+        height: f32,
+        /// 近裁剪面距离
+        near: f32,
+        /// 远裁剪面距离
+        far: f32,
+    },
+}
+
+/// 渲染路径
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RenderPath {
+    /// 前向渲染，每个物体在绘制时计算全部光照
+    #[default]
+    Forward,
+    /// 延迟渲染，先写入 G-buffer 再统一计算光照，适用于光源较多的场景
+    Deferred,
+}
+
+/// 摄像机
+///
+/// 用于生成观察矩阵与投影矩阵，通过 [`Camera::register`] 注册为主摄像机后，
+/// 其宽高比会在窗口帧缓冲大小变化时自动更新
+///
+/// # 示例
+///
+/// ```
+/// use gle::{Camera, math::*};
+///
+/// let mut camera = Camera::perspective(45f32.to_radians(), 0.1, 100.0);
+/// camera.position = Vec3::new(0.0, 0.0, 3.0);
+/// camera.look_at(Vec3::ZERO);
+/// let view_projection = camera.view_projection();
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Camera {
+    /// 摄像机位置
+    pub position: Vec3,
+    /// 摄像机朝向，默认朝向 `-Z` 方向
+    pub rotation: Quat,
+    /// 投影方式
+    pub projection: Projection,
+    /// 渲染路径，由渲染循环据此选择前向或延迟管线
+    pub render_path: RenderPath,
+    /// 是否由 [`EditorGrid`](crate::EditorGrid) 绘制地面网格(默认值为`false`)
+    pub show_grid: bool,
+    /// 是否由 [`EditorGrid`](crate::EditorGrid) 在视口角落绘制坐标轴指示器(默认值为`false`)
+    pub show_gizmo: bool,
+    /// 斜近裁剪面，以世界空间平面`(法线, 距离)`替换近裁剪面，只保留`dot(plane.xyz, P) + plane.w >= 0`的部分，
+    /// 用于平面反射等需要裁掉平面一侧的场合，平面应位于摄像机前方(默认值为`None`)
+    pub oblique_plane: Option<Vec4>,
+    /// 用户裁剪平面，以世界空间平面`(法线, 距离)`只保留`dot(plane.xyz, P) + plane.w >= 0`的部分，
+    /// 见 [`ClipPlanes`](crate::ClipPlanes)(默认值均为`None`)
+    pub clip_planes: [Option<Vec4>; MAX_CLIP_PLANES],
+    /// 渲染目标，为 [`CameraTarget::Texture`] 时由 [`RenderTargets::bind`](crate::RenderTargets::bind)
+    /// 绑定离屏帧缓冲(默认值为 [`CameraTarget::Window`])
+    pub target: CameraTarget,
+    /// 视口在渲染目标中的归一化矩形`(x, y, 宽度, 高度)`，以左下角为原点，用于分屏与多视图
+    /// (默认值为`(0, 0, 1, 1)`)
+    pub viewport: Vec4,
+    aspect: f32,
+}
+
+impl Camera {
+    /// 创建一个透视投影摄像机
+    ///
+    /// # 参数
+    /// + `fov_y` - 垂直视野角，单位为弧度
+    /// + `near` - 近裁剪面距离
+    /// + `far` - 远裁剪面距离
+    ///
+    /// # 返回值
+    /// 返回一个位于原点、朝向 `-Z` 方向的摄像机
+    pub fn perspective(fov_y: f32, near: f32, far: f32) -> Self {
+        Self::new(Projection::Perspective { fov_y, near, far })
+    }
+
+    /// 创建一个正交投影摄像机
+    ///
+    /// # 参数
+    /// + `height` - 可见区域的高度
+    /// + `near` - 近裁剪面距离
+    /// + `far` - 远裁剪面距离
+    ///
+    /// # 返回值
+    /// 返回一个位于原点、朝向 `-Z` 方向的摄像机
+    pub fn orthographic(height: f32, near: f32, far: f32) -> Self {
+        Self::new(Projection::Orthographic { height, near, far })
+    }
+
+    /// 以指定投影方式创建摄像机
+    ///
+    /// # 参数
+    /// + `projection` - 投影方式
+    ///
+    /// # 返回值
+    /// 返回一个位于原点、朝向 `-Z` 方向的摄像机
+    pub fn new(projection: Projection) -> Self {
+        Self {
+            position: Vec3::ZERO,
+            rotation: Quat::IDENTITY,
+            projection,
+            render_path: RenderPath::default(),
+            show_grid: false,
+            show_gizmo: false,
+            oblique_plane: None,
+            clip_planes: [None; MAX_CLIP_PLANES],
+            target: CameraTarget::Window,
+            viewport: Vec4::new(0.0, 0.0, 1.0, 1.0),
+            aspect: 1.0,
+        }
+    }
+
+    /// 获取宽高比
+    pub fn aspect(&self) -> f32 {
+        self.aspect
+    }
+
+    /// 设置宽高比
+    ///
+    /// # 参数
+    /// + `aspect` - 宽高比，非正数或非有限值将被忽略
+    pub fn set_aspect(&mut self, aspect: f32) {
+        if aspect.is_finite() && aspect > 0.0 {
+            self.aspect = aspect;
+        }
+    }
+
+    /// 根据视口大小设置宽高比
+    ///
+    /// # 参数
+    /// + `width` - 视口宽度
+    /// + `height` - 视口高度
+    ///
+    /// # 注解
+    ///
+    /// 窗口最小化时视口高度为零，此时宽高比保持不变
+    pub fn set_viewport_size(&mut self, width: i32, height: i32) {
+        if width > 0 && height > 0 {
+            self.set_aspect(width as f32 / height as f32);
+        }
+    }
+
+    /// 根据渲染目标大小与 [`Camera::viewport`] 设置宽高比
+    ///
+    /// # 参数
+    /// + `width` - 渲染目标宽度
+    /// + `height` - 渲染目标高度
+    pub fn set_target_size(&mut self, width: i32, height: i32) {
+        let (_, _, w, h) = self.pixel_viewport(width, height);
+        self.set_viewport_size(w, h);
+    }
+
+    /// 计算视口在渲染目标中的像素矩形
+    ///
+    /// # 参数
+    /// + `width` - 渲染目标宽度
+    /// + `height` - 渲染目标高度
+    ///
+    /// # 返回值
+    /// 返回`(x, y, 宽度, 高度)`，以左下角为原点，可以直接传给`glViewport`与`glScissor`。
+    /// 相邻视口的边界取整到同一像素，因此分屏之间不会出现缝隙或重叠
+    pub fn pixel_viewport(&self, width: i32, height: i32) -> (i32, i32, i32, i32) {
+        let size = Vec2::new(width.max(0) as f32, height.max(0) as f32);
+        let min = (self.viewport.xy().clamp(Vec2::ZERO, Vec2::ONE) * size).round();
+        let max = ((self.viewport.xy() + self.viewport.zw()).clamp(Vec2::ZERO, Vec2::ONE) * size)
+            .round()
+            .max(min);
+        let (min, max) = (min.as_ivec2(), max.as_ivec2());
+        (min.x, min.y, max.x - min.x, max.y - min.y)
+    }
+
+    /// 获取近裁剪面与远裁剪面距离
+    pub fn clip_planes(&self) -> (f32, f32) {
+        match self.projection {
+            Projection::Perspective { near, far, .. } => (near, far),
+            Projection::Orthographic { near, far, .. } => (near, far),
+        }
+    }
+
+    /// 获取摄像机前方向
+    pub fn forward(&self) -> Vec3 {
+        self.rotation * Vec3::NEG_Z
+    }
+
+    /// 获取摄像机右方向
+    pub fn right(&self) -> Vec3 {
+        self.rotation * Vec3::X
+    }
+
+    /// 获取摄像机上方向
+    pub fn up(&self) -> Vec3 {
+        self.rotation * Vec3::Y
+    }
+
+    /// 使摄像机朝向指定位置，以世界 `+Y` 为上方向
+    ///
+    /// # 参数
+    /// + `target` - 目标位置
+    pub fn look_at(&mut self, target: Vec3) {
+        let dir = target - self.position;
+        if dir.length_squared() <= f32::EPSILON {
+            return;
+        }
+        let view = look_at(self.position, target, Vec3::Y);
+        self.rotation = Quat::from_mat4(&view.inverse()).normalize();
+    }
+
+    /// 获取观察矩阵
+    pub fn view_matrix(&self) -> Mat4 {
+        Mat4::from_rotation_translation(self.rotation, self.position).inverse()
+    }
+
+    /// 获取投影矩阵，设置了 [`Camera::oblique_plane`] 时近裁剪面被替换为该平面
+    pub fn projection_matrix(&self) -> Mat4 {
+        let projection = self.base_projection_matrix();
+        match self.oblique_plane {
+            Some(plane) => oblique_projection(projection, self.view_matrix(), plane),
+            None => projection,
+        }
+    }
+
+    fn base_projection_matrix(&self) -> Mat4 {
+        match self.projection {
+            Projection::Perspective { fov_y, near, far } => {
+                perspective(fov_y, self.aspect, near, far)
+            }
+            Projection::Orthographic { height, near, far } => {
+                let half_h = height * 0.5;
+                let half_w = half_h * self.aspect;
+                orthographic(-half_w, half_w, -half_h, half_h, near, far)
+            }
+        }
+    }
+
+    /// 获取投影矩阵与观察矩阵之积
+    pub fn view_projection(&self) -> Mat4 {
+        self.projection_matrix() * self.view_matrix()
+    }
+
+    /// 将摄像机注册为主摄像机
+    ///
+    /// # 参数
+    /// + `camera` - 摄像机，其宽高比将根据当前窗口帧缓冲大小初始化
+    ///
+    /// # 注解
+    ///
+    /// 主摄像机的宽高比会在窗口帧缓冲大小变化时按其视口自动更新，
+    /// 着色器所需的矩阵可以在渲染线程中通过 [`Camera::main`] 获取
+    pub fn register(mut camera: Camera) {
+        if let Some((w, h)) = Resources::with(WINDOW_KEY, |w| w.get_framebuffer_size()) {
+            camera.set_target_size(w, h);
+        }
+        Registry::register(MAIN_CAMERA, camera).unwrap();
+    }
+
+    /// 获取主摄像机的副本
+    ///
+    /// # 返回值
+    /// 若已注册主摄像机，返回其副本，否则返回`None`
+    pub fn main() -> Option<Camera> {
+        Registry::with(MAIN_CAMERA, |c: &Camera| *c)
+    }
+
+    /// 修改主摄像机
+    ///
+    /// # 参数
+    /// + `f` - 一个函数，它接受主摄像机的可变引用
+    ///
+    /// # 返回值
+    /// 若已注册主摄像机，返回`f`的返回值，否则返回`None`
+    pub fn apply_main<R, F: FnOnce(&mut Camera) -> R>(f: F) -> Option<R> {
+        Registry::apply(MAIN_CAMERA, f)
+    }
+}
+
+/// 以 Lengyel 的方法将投影矩阵的近裁剪面替换为任意平面
+///
+/// # 参数
+/// + `projection` - 原投影矩阵
+/// + `view` - 观察矩阵
+/// + `plane` - 世界空间平面
+fn oblique_projection(mut projection: Mat4, view: Mat4, plane: Vec4) -> Mat4 {
+    // 平面按逆转置矩阵变换到观察空间
+    let c = view.inverse().transpose() * plane;
+    if c.w >= 0.0 {
+        // 摄像机不在平面的裁剪一侧，斜裁剪面会错误地裁掉可见部分
+        return projection;
+    }
+    let corner = Vec4::new(c.x.signum(), c.y.signum(), 1.0, 1.0);
+    let q = projection.inverse() * corner;
+    let c = c * (2.0 / c.dot(q));
+    let row3 = projection.row(3);
+    for i in 0..4 {
+        projection.col_mut(i)[2] = c[i] - row3[i];
+    }
+    projection
+}
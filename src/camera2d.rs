@@ -0,0 +1,205 @@
+use crate::math::*;
+
+/// 虚拟分辨率到窗口的缩放方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ScaleMode {
+    /// 拉伸填满窗口，宽高比与虚拟分辨率不同时画面变形
+    Stretch,
+    /// 保持宽高比缩放到窗口内，多余部分留出黑边
+    #[default]
+    Letterbox,
+    /// 以不小于 1 的最大整数倍缩放并居中，像素风格的画面保持清晰
+    IntegerScale,
+}
+
+/// 二维正交摄像机
+///
+/// 以固定的虚拟分辨率描述可见区域，并按 [`ScaleMode`] 计算在窗口中的视口，
+/// 画面内容不随窗口大小改变，只改变缩放与黑边
+///
+/// # 示例
+///
+/// ```
+/// use gle::*;
+/// use gle::math::*;
+///
+/// fn render_loop(camera: &mut Camera2D, batch: &mut SpriteBatch, map: &mut Tilemap) {
+///     let (w, h) = App::window_size();
+///     camera.set_window_size(w, h);
+///     camera.apply_viewport();
+///     batch.begin(&camera.view_projection());
+///     let (min, max) = camera.visible_rect();
+///     map.draw(batch, min, max);
+///     batch.end();
+/// }
+///
+/// let mut camera = Camera2D::new(320, 180);
+/// camera.scale_mode = ScaleMode::IntegerScale;
+/// camera.set_window_size(1280, 720);
+/// assert_eq!(camera.viewport(), (0, 0, 1280, 720));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Camera2D {
+    /// 摄像机中心的世界坐标
+    pub position: Vec2,
+    /// 缩放倍数，大于 1 时放大
+    pub zoom: f32,
+    /// 旋转角，单位为弧度，逆时针为正
+    pub rotation: f32,
+    /// 缩放方式
+    pub scale_mode: ScaleMode,
+    /// 是否将摄像机位置对齐到虚拟像素，避免像素风格的画面在移动时抖动
+    pub pixel_snap: bool,
+    virtual_size: UVec2,
+    window_size: IVec2,
+}
+
+impl Camera2D {
+    /// 创建二维摄像机
+    ///
+    /// # 参数
+    /// + `width` - 虚拟分辨率的宽度，即缩放倍数为 1 时可见区域的宽度
+    /// + `height` - 虚拟分辨率的高度
+    pub fn new(width: u32, height: u32) -> Self {
+        let virtual_size = UVec2::new(width.max(1), height.max(1));
+        Self {
+            position: Vec2::ZERO,
+            zoom: 1.0,
+            rotation: 0.0,
+            scale_mode: ScaleMode::default(),
+            pixel_snap: false,
+            virtual_size,
+            window_size: virtual_size.as_ivec2(),
+        }
+    }
+
+    /// 获取虚拟分辨率
+    pub fn virtual_size(&self) -> UVec2 {
+        self.virtual_size
+    }
+
+    /// 设置虚拟分辨率
+    pub fn set_virtual_size(&mut self, width: u32, height: u32) {
+        self.virtual_size = UVec2::new(width.max(1), height.max(1));
+    }
+
+    /// 设置窗口帧缓冲大小
+    ///
+    /// # 注解
+    ///
+    /// 窗口最小化时大小为零，此时保持原有大小不变
+    pub fn set_window_size(&mut self, width: i32, height: i32) {
+        if width > 0 && height > 0 {
+            self.window_size = IVec2::new(width, height);
+        }
+    }
+
+    /// 获取虚拟像素到窗口像素的缩放倍数
+    ///
+    /// # 返回值
+    /// 返回水平与竖直方向的缩放倍数，仅 [`ScaleMode::Stretch`] 下两者可能不同
+    pub fn pixel_scale(&self) -> Vec2 {
+        let ratio = self.window_size.as_vec2() / self.virtual_size.as_vec2();
+        match self.scale_mode {
+            ScaleMode::Stretch => ratio,
+            ScaleMode::Letterbox => Vec2::splat(ratio.min_element()),
+            ScaleMode::IntegerScale => Vec2::splat(ratio.min_element().floor().max(1.0)),
+        }
+    }
+
+    /// 获取画面在窗口中的视口
+    ///
+    /// # 返回值
+    /// 返回`(x, y, 宽度, 高度)`，以窗口左下角为原点，可以直接传给`glViewport`
+    pub fn viewport(&self) -> (i32, i32, i32, i32) {
+        let size = (self.virtual_size.as_vec2() * self.pixel_scale()).round().as_ivec2();
+        let offset = (self.window_size - size) / 2;
+        (offset.x, offset.y, size.x, size.y)
+    }
+
+    fn viewport_rect(&self) -> (Vec2, Vec2) {
+        let (x, y, w, h) = self.viewport();
+        (Vec2::new(x as f32, y as f32), Vec2::new(w as f32, h as f32))
+    }
+
+    /// 清除整个窗口为黑色以绘制黑边，并将视口设置为画面区域
+    ///
+    /// # 注解
+    ///
+    /// 该函数只能在渲染线程中调用
+    pub fn apply_viewport(&self) {
+        let (x, y, w, h) = self.viewport();
+        unsafe {
+            gl::Viewport(0, 0, self.window_size.x, self.window_size.y);
+            gl::ClearColor(0.0, 0.0, 0.0, 1.0);
+            gl::Clear(gl::COLOR_BUFFER_BIT);
+            gl::Viewport(x, y, w, h);
+        }
+    }
+
+    /// 获取摄像机中心实际使用的位置，启用像素对齐时对齐到虚拟像素
+    fn center(&self) -> Vec2 {
+        if self.pixel_snap {
+            (self.position * self.zoom).round() / self.zoom
+        } else {
+            self.position
+        }
+    }
+
+    /// 获取观察矩阵
+    pub fn view_matrix(&self) -> Mat4 {
+        let center = self.center();
+        Mat4::from_rotation_z(-self.rotation) * Mat4::from_translation(-center.extend(0.0))
+    }
+
+    /// 获取投影矩阵
+    pub fn projection_matrix(&self) -> Mat4 {
+        let half = self.virtual_size.as_vec2() * 0.5 / self.zoom.max(f32::EPSILON);
+        orthographic(-half.x, half.x, -half.y, half.y, -1.0, 1.0)
+    }
+
+    /// 获取投影矩阵与观察矩阵之积
+    pub fn view_projection(&self) -> Mat4 {
+        self.projection_matrix() * self.view_matrix()
+    }
+
+    /// 获取可见区域在世界空间中的轴对齐包围矩形
+    ///
+    /// # 返回值
+    /// 返回左下角与右上角坐标，旋转时包含整个可见区域
+    pub fn visible_rect(&self) -> (Vec2, Vec2) {
+        let half = self.virtual_size.as_vec2() * 0.5 / self.zoom.max(f32::EPSILON);
+        let (sin, cos) = self.rotation.sin_cos();
+        let extent = Vec2::new(
+            half.x * cos.abs() + half.y * sin.abs(),
+            half.x * sin.abs() + half.y * cos.abs(),
+        );
+        let center = self.center();
+        (center - extent, center + extent)
+    }
+
+    /// 将屏幕坐标转换为世界坐标
+    ///
+    /// # 参数
+    /// + `screen` - 屏幕坐标，以窗口左上角为原点、向下为正，单位与 [`Camera2D::set_window_size`] 一致
+    ///
+    /// # 返回值
+    /// 返回对应的世界坐标，位于黑边中的点同样按画面延伸换算
+    pub fn screen_to_world(&self, screen: Vec2) -> Vec2 {
+        let (origin, size) = self.viewport_rect();
+        let flipped = Vec2::new(screen.x, self.window_size.y as f32 - screen.y);
+        let ndc = (flipped - origin) / size * 2.0 - 1.0;
+        self.view_projection().inverse().project_point3(ndc.extend(0.0)).truncate()
+    }
+
+    /// 将世界坐标转换为屏幕坐标
+    ///
+    /// # 返回值
+    /// 返回以窗口左上角为原点、向下为正的屏幕坐标
+    pub fn world_to_screen(&self, world: Vec2) -> Vec2 {
+        let (origin, size) = self.viewport_rect();
+        let ndc = self.view_projection().project_point3(world.extend(0.0)).truncate();
+        let pixel = (ndc + 1.0) * 0.5 * size + origin;
+        Vec2::new(pixel.x, self.window_size.y as f32 - pixel.y)
+    }
+}
@@ -0,0 +1,253 @@
+use crate::math::*;
+use crate::{move_and_slide, Aabb, Colliders, Input, Key, SlideResult, Transform};
+
+/// 角色移动的按键绑定
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CharacterBindings {
+    /// 前进
+    pub forward: Key,
+    /// 后退
+    pub back: Key,
+    /// 左移
+    pub left: Key,
+    /// 右移
+    pub right: Key,
+    /// 跳跃
+    pub jump: Key,
+    /// 疾跑
+    pub sprint: Key,
+}
+
+impl Default for CharacterBindings {
+    /// `WASD`移动，`Space`跳跃，`LeftControl`疾跑
+    fn default() -> Self {
+        Self {
+            forward: Key::W,
+            back: Key::S,
+            left: Key::A,
+            right: Key::D,
+            jump: Key::Space,
+            sprint: Key::LeftControl,
+        }
+    }
+}
+
+/// 角色控制器一帧的输入
+///
+/// 可以由 [`CharacterInput::from_keys`] 从键盘状态生成，也可以由手柄、网络或 AI 填写
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct CharacterInput {
+    /// 移动方向，`x`向右，`y`向前，长度超过 1 时被截断
+    pub movement: Vec2,
+    /// 角色的偏航角，单位为弧度，为零时朝向`-Z`方向
+    pub yaw: f32,
+    /// 是否跳跃，按住时落地后立即再次起跳
+    pub jump: bool,
+    /// 是否疾跑
+    pub sprint: bool,
+}
+
+impl CharacterInput {
+    /// 从本帧的按键状态生成输入
+    ///
+    /// # 参数
+    /// + `bindings` - 按键绑定
+    /// + `yaw` - 角色的偏航角，通常取自 [`FpsCameraController::yaw`](crate::FpsCameraController::yaw)
+    pub fn from_keys(bindings: &CharacterBindings, yaw: f32) -> Self {
+        let axis = |positive: Key, negative: Key| {
+            Input::key_down(positive) as i32 as f32 - Input::key_down(negative) as i32 as f32
+        };
+        Self {
+            movement: Vec2::new(
+                axis(bindings.right, bindings.left),
+                axis(bindings.forward, bindings.back),
+            ),
+            yaw,
+            jump: Input::key_down(bindings.jump),
+            sprint: Input::key_down(bindings.sprint),
+        }
+    }
+}
+
+/// 运动学角色控制器
+///
+/// 以轴对齐包围盒表示角色，在 [`move_and_slide`] 的基础上处理重力、跳跃、台阶攀爬与地面检测，
+/// 并将结果写入角色的 [`Transform`]。变换的平移为角色脚底中心，旋转为绕`Y`轴的偏航
+///
+/// # 示例
+///
+/// ```
+/// use gle::{*, math::*};
+///
+/// let mut world = VoxelWorld::new();
+/// world.set_block(IVec3::new(0, 0, 0), BlockId(1));
+/// let mut character = CharacterController::new();
+/// let mut transform = Transform::from_translation(Vec3::new(0.5, 1.5, 0.5));
+///
+/// // 在事件循环中每帧
+/// let input = CharacterInput::from_keys(&CharacterBindings::default(), 0.0);
+/// character.update(&world, &mut transform, &input, 1.0 / 60.0);
+/// ```
+///
+/// # 注解
+///
+/// 控制器不参与刚体模拟，不会被其他物体推动
+#[derive(Debug, Clone)]
+pub struct CharacterController {
+    /// 包围盒的半边长
+    pub half_extents: Vec3,
+    /// 行走速度，单位为每秒
+    pub walk_speed: f32,
+    /// 疾跑速度，单位为每秒
+    pub sprint_speed: f32,
+    /// 在地面上的水平加速度
+    pub acceleration: f32,
+    /// 在空中的水平加速度，决定空中转向的灵活程度
+    pub air_acceleration: f32,
+    /// 起跳时的竖直速度
+    pub jump_speed: f32,
+    /// 重力加速度
+    pub gravity: f32,
+    /// 最大下落速度
+    pub max_fall_speed: f32,
+    /// 无需跳跃即可走上的台阶高度，为零时不攀爬台阶
+    pub step_height: f32,
+    velocity: Vec3,
+    grounded: bool,
+}
+
+impl Default for CharacterController {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CharacterController {
+    /// 创建宽 0.6、高 1.8 的角色控制器，可跳上一格方块
+    pub fn new() -> Self {
+        Self {
+            half_extents: Vec3::new(0.3, 0.9, 0.3),
+            walk_speed: 4.3,
+            sprint_speed: 5.6,
+            acceleration: 40.0,
+            air_acceleration: 10.0,
+            jump_speed: 8.5,
+            gravity: 28.0,
+            max_fall_speed: 50.0,
+            step_height: 0.55,
+            velocity: Vec3::ZERO,
+            grounded: false,
+        }
+    }
+
+    /// 获取当前速度
+    pub fn velocity(&self) -> Vec3 {
+        self.velocity
+    }
+
+    /// 设置当前速度，可用于击退、弹射等效果
+    pub fn set_velocity(&mut self, velocity: Vec3) {
+        self.velocity = velocity;
+    }
+
+    /// 上一次更新后角色是否站在地面上
+    pub fn is_grounded(&self) -> bool {
+        self.grounded
+    }
+
+    /// 获取脚底位于指定位置时角色的包围盒
+    pub fn aabb(&self, feet: Vec3) -> Aabb {
+        Aabb::from_center_half_extents(feet + Vec3::Y * self.half_extents.y, self.half_extents)
+    }
+
+    /// 根据输入移动角色
+    ///
+    /// # 参数
+    /// + `colliders` - 静态几何体，如 [`VoxelWorld`](crate::VoxelWorld)
+    /// + `transform` - 角色的变换，平移为脚底中心
+    /// + `input` - 本帧输入
+    /// + `dt` - 时间间隔，单位为秒
+    ///
+    /// # 返回值
+    /// 返回本次移动的碰撞结果，可用于播放落地、撞墙音效等
+    pub fn update<C: Colliders + ?Sized>(
+        &mut self,
+        colliders: &C,
+        transform: &mut Transform,
+        input: &CharacterInput,
+        dt: f32,
+    ) -> SlideResult {
+        let yaw = Quat::from_rotation_y(input.yaw);
+        let movement = input.movement.clamp_length_max(1.0);
+        let wish = yaw * Vec3::new(movement.x, 0.0, -movement.y);
+        let speed = if input.sprint {
+            self.sprint_speed
+        } else {
+            self.walk_speed
+        };
+        let accel = if self.grounded {
+            self.acceleration
+        } else {
+            self.air_acceleration
+        };
+        let horizontal = self.velocity.xz();
+        let horizontal =
+            horizontal + (wish.xz() * speed - horizontal).clamp_length_max(accel * dt);
+        self.velocity.x = horizontal.x;
+        self.velocity.z = horizontal.y;
+        self.velocity.y = (self.velocity.y - self.gravity * dt).max(-self.max_fall_speed);
+        if self.grounded && input.jump {
+            self.velocity.y = self.jump_speed;
+        }
+
+        let aabb = self.aabb(transform.translation);
+        let motion = self.velocity * dt;
+        let mut result = move_and_slide(colliders, &aabb, motion);
+        if self.grounded && self.step_height > 0.0 && result.on_wall() {
+            if let Some(stepped) = self.step_up(colliders, &aabb, motion, &result) {
+                result = stepped;
+            }
+        }
+
+        for normal in &result.normals {
+            let into = self.velocity.dot(*normal);
+            if into < 0.0 {
+                self.velocity -= *normal * into;
+            }
+        }
+        self.grounded = result.on_floor();
+        transform.translation = Vec3::new(
+            result.aabb.center().x,
+            result.aabb.min.y,
+            result.aabb.center().z,
+        );
+        transform.rotation = yaw;
+        result
+    }
+
+    /// 尝试先抬高再水平移动最后落下，水平移动距离比直接移动更远且落在地面上时采用
+    fn step_up<C: Colliders + ?Sized>(
+        &self,
+        colliders: &C,
+        aabb: &Aabb,
+        motion: Vec3,
+        blocked: &SlideResult,
+    ) -> Option<SlideResult> {
+        let horizontal = Vec3::new(motion.x, 0.0, motion.z);
+        let up = move_and_slide(colliders, aabb, Vec3::Y * self.step_height);
+        let across = move_and_slide(colliders, &up.aabb, horizontal);
+        let drop = up.motion.y - motion.y.min(0.0);
+        let down = move_and_slide(colliders, &across.aabb, Vec3::NEG_Y * drop);
+        let progress = across.motion.xz().length_squared();
+        if !down.on_floor() || progress <= blocked.motion.xz().length_squared() + 1e-6 {
+            return None;
+        }
+        let mut normals = across.normals;
+        normals.extend(down.normals);
+        Some(SlideResult {
+            aabb: down.aabb,
+            motion: down.aabb.min - aabb.min,
+            normals,
+        })
+    }
+}
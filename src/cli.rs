@@ -0,0 +1,110 @@
+use std::path::PathBuf;
+
+use crate::error::{Error, Result};
+use crate::{EngineConfig, Level};
+
+/// 命令行设置
+///
+/// 解析以下选项，选项的值可以写作`--width 1280`或`--width=1280`：
+/// + `--config <path>` - 配置文件路径(默认值为`gle.toml`)
+/// + `--width <n>`、`--height <n>` - 窗口大小
+/// + `--fullscreen`、`--windowed` - 以全屏或窗口模式运行
+/// + `--log-level <debug|info|warn|error>` - 日志级别
+/// + `--headless` - 以无头模式运行
+///
+/// 命令行设置覆盖配置文件与构建器的默认值，无法识别的参数按原顺序保存在 [`CommandLine::rest`] 中，
+/// 留给程序自行解析
+///
+/// # 示例
+///
+/// ```
+/// use gle::*;
+///
+/// let args = ["--width=1280", "--height", "720", "--log-level", "debug", "save1"];
+/// let cli = CommandLine::parse(args.iter().map(|s| s.to_string())).unwrap();
+/// assert_eq!(cli.width, Some(1280));
+/// assert_eq!(cli.log_level, Some(Level::Debug));
+/// assert_eq!(cli.rest, vec!["save1".to_string()]);
+/// ```
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct CommandLine {
+    /// 配置文件路径
+    pub config: Option<PathBuf>,
+    /// 窗口宽度
+    pub width: Option<i32>,
+    /// 窗口高度
+    pub height: Option<i32>,
+    /// 是否全屏
+    pub fullscreen: Option<bool>,
+    /// 日志级别
+    pub log_level: Option<Level>,
+    /// 是否以无头模式运行
+    pub headless: bool,
+    /// 无法识别的参数
+    pub rest: Vec<String>,
+}
+
+impl CommandLine {
+    /// 解析当前进程的命令行参数，忽略程序路径
+    pub fn from_env() -> Result<Self> {
+        Self::parse(std::env::args().skip(1))
+    }
+
+    /// 解析命令行参数
+    ///
+    /// # 参数
+    /// + `args` - 不包含程序路径的参数
+    ///
+    /// # 返回值
+    /// 成功时返回命令行设置，选项缺少值或值无法解析时返回错误
+    pub fn parse<I: IntoIterator<Item = String>>(args: I) -> Result<Self> {
+        let mut cli = Self::default();
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let (name, inline) = match arg.split_once('=') {
+                Some((name, value)) if arg.starts_with("--") => (name, Some(value.to_string())),
+                _ => (arg.as_str(), None),
+            };
+            let mut value = || {
+                inline
+                    .clone()
+                    .or_else(|| args.next())
+                    .ok_or_else(|| Error::Parse(format!("命令行选项 {} 缺少值", name)))
+            };
+            match name {
+                "--config" => cli.config = Some(PathBuf::from(value()?)),
+                "--width" => cli.width = Some(parse_size(name, &value()?)?),
+                "--height" => cli.height = Some(parse_size(name, &value()?)?),
+                "--log-level" => cli.log_level = Some(value()?.parse()?),
+                "--fullscreen" => cli.fullscreen = Some(true),
+                "--windowed" => cli.fullscreen = Some(false),
+                "--headless" => cli.headless = true,
+                _ => cli.rest.push(arg.clone()),
+            }
+        }
+        Ok(cli)
+    }
+
+    /// 用命令行设置覆盖引擎配置
+    pub fn apply(&self, config: &mut EngineConfig) {
+        if let Some(width) = self.width {
+            config.window.width = width;
+        }
+        if let Some(height) = self.height {
+            config.window.height = height;
+        }
+        if let Some(fullscreen) = self.fullscreen {
+            config.window.fullscreen = fullscreen;
+        }
+        if let Some(level) = self.log_level {
+            config.log.level = level;
+        }
+    }
+}
+
+fn parse_size(name: &str, value: &str) -> Result<i32> {
+    match value.parse::<i32>() {
+        Ok(n) if n > 0 => Ok(n),
+        _ => Err(Error::Parse(format!("命令行选项 {} 的值 {} 不是正整数", name, value))),
+    }
+}
@@ -0,0 +1,102 @@
+use crate::math::*;
+use crate::{Camera, Program, Renderer};
+
+/// 用户裁剪平面的最大数量
+pub const MAX_CLIP_PLANES: usize = 4;
+
+/// 用户裁剪平面 uniform 及裁剪函数，可被自定义顶点着色器通过字符串拼接复用
+///
+/// 提供`void applyClipPlanes(vec3 P)`，以世界空间位置写入`gl_ClipDistance`。
+/// 平面由 [`ClipPlanes::apply`] 设置，未启用的平面不参与裁剪
+pub const CLIP_PLANES_GLSL: &str = r#"
+uniform vec4 uClipPlanes[4];
+out float gl_ClipDistance[4];
+
+void applyClipPlanes(vec3 P)
+{
+    for (int i = 0; i < 4; ++i) {
+        gl_ClipDistance[i] = dot(uClipPlanes[i].xyz, P) + uClipPlanes[i].w;
+    }
+}
+"#;
+
+/// 用户裁剪平面
+///
+/// 以 [`Camera::clip_planes`] 配置世界空间平面`(法线, 距离)`，只保留`dot(plane.xyz, P) + plane.w >= 0`的部分。
+/// [`RenderQueue`](crate::RenderQueue) 与内置的 PBR 着色器会自动应用摄像机的裁剪平面，
+/// 自定义着色器需拼接 [`CLIP_PLANES_GLSL`] 并在顶点着色器中调用`applyClipPlanes`
+///
+/// # 示例
+///
+/// ```
+/// use gle::{*, math::*};
+///
+/// // 绘制水面折射时只保留水面以下的部分
+/// let mut camera = Camera::perspective(60f32.to_radians(), 0.1, 100.0);
+/// camera.clip_planes[0] = Some(ClipPlanes::below(0.0, 0.05));
+///
+/// // 剖面视图，剖去 x > 1 的部分
+/// camera.clip_planes[1] = Some(ClipPlanes::section(Vec3::X, Vec3::NEG_X));
+/// ```
+pub struct ClipPlanes;
+
+impl ClipPlanes {
+    /// 由平面上一点与保留一侧的法线构造平面
+    ///
+    /// # 参数
+    /// + `point` - 平面上一点
+    /// + `normal` - 指向保留一侧的法线
+    pub fn section(point: Vec3, normal: Vec3) -> Vec4 {
+        let normal = normal.normalize_or_zero();
+        normal.extend(-normal.dot(point))
+    }
+
+    /// 保留水平面以上部分的平面，用于水面反射
+    ///
+    /// # 参数
+    /// + `height` - 水面高度
+    /// + `offset` - 平面向下偏移的距离，避免水面附近出现缝隙
+    pub fn above(height: f32, offset: f32) -> Vec4 {
+        Vec4::new(0.0, 1.0, 0.0, -(height - offset))
+    }
+
+    /// 保留水平面以下部分的平面，用于水面折射
+    ///
+    /// # 参数
+    /// + `height` - 水面高度
+    /// + `offset` - 平面向上偏移的距离，避免水面附近出现缝隙
+    pub fn below(height: f32, offset: f32) -> Vec4 {
+        Vec4::new(0.0, -1.0, 0.0, height + offset)
+    }
+
+    /// 启用摄像机的裁剪平面并将其设置到着色器程序
+    ///
+    /// # 参数
+    /// + `camera` - 摄像机
+    /// + `program` - 已绑定且拼接了 [`CLIP_PLANES_GLSL`] 的着色器程序，为`None`时只启用裁剪距离
+    ///
+    /// # 返回值
+    /// 返回启用的平面数量
+    ///
+    /// # 注解
+    ///
+    /// 该函数只能在渲染线程中调用，绘制结束后应调用 [`ClipPlanes::disable`]
+    pub fn apply(camera: &Camera, program: Option<&Program>) -> usize {
+        let mut mask = 0;
+        for (i, plane) in camera.clip_planes.iter().enumerate() {
+            if let Some(program) = program {
+                program.set(&format!("uClipPlanes[{}]", i), &plane.unwrap_or(Vec4::ZERO));
+            }
+            if plane.is_some() {
+                mask |= 1 << i;
+            }
+        }
+        Renderer::set_clip_distances(mask);
+        mask.count_ones() as usize
+    }
+
+    /// 关闭所有裁剪距离
+    pub fn disable() {
+        Renderer::set_clip_distances(0);
+    }
+}
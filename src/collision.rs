@@ -0,0 +1,263 @@
+use crate::math::*;
+use crate::{Aabb, VoxelWorld};
+
+/// 碰撞后与表面保持的间隙，避免浮点误差使包围盒嵌入表面
+const SKIN: f32 = 1e-3;
+
+/// 一次移动中最多沿表面滑动的次数
+const MAX_SLIDES: usize = 4;
+
+/// 扫掠检测的命中结果
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SweepHit {
+    /// 发生接触时已完成的位移比例，范围为`[0, 1]`
+    pub time: f32,
+    /// 被命中表面的朝外法线，沿某一坐标轴
+    pub normal: Vec3,
+}
+
+impl Aabb {
+    /// 获取平移后的包围盒
+    pub fn translated(&self, offset: Vec3) -> Aabb {
+        Aabb::new(self.min + offset, self.max + offset)
+    }
+
+    /// 获取两个包围盒的最小分离向量
+    ///
+    /// # 返回值
+    /// 两个包围盒重叠时，返回将`self`沿重叠最小的坐标轴推出`other`所需的位移，否则返回`None`
+    pub fn penetration(&self, other: &Aabb) -> Option<Vec3> {
+        let push_neg = self.max - other.min;
+        let push_pos = other.max - self.min;
+        if push_neg.min_element() <= 0.0 || push_pos.min_element() <= 0.0 {
+            return None;
+        }
+        let depth = push_neg.min(push_pos);
+        let axis = if depth.x < depth.y && depth.x < depth.z {
+            0
+        } else if depth.y < depth.z {
+            1
+        } else {
+            2
+        };
+        let mut offset = Vec3::ZERO;
+        offset[axis] = if push_neg[axis] < push_pos[axis] {
+            -push_neg[axis]
+        } else {
+            push_pos[axis]
+        };
+        Some(offset)
+    }
+
+    /// 扫掠检测：包围盒沿位移移动时是否与另一个包围盒发生接触
+    ///
+    /// # 参数
+    /// + `motion` - 本次移动的位移
+    /// + `other` - 静止的包围盒
+    ///
+    /// # 返回值
+    /// 返回最早的接触，位移过程中不接触、或开始时已经重叠时返回`None`
+    ///
+    /// # 注解
+    ///
+    /// 仅相切而不相互嵌入的包围盒不视为接触，因此贴着表面平行移动不会被阻挡
+    pub fn sweep(&self, motion: Vec3, other: &Aabb) -> Option<SweepHit> {
+        // 将问题转化为中心点射线与按自身半边长扩展后的包围盒求交
+        let half = self.half_extents();
+        let min = other.min - half;
+        let max = other.max + half;
+        let origin = self.center();
+        let mut entry = Vec3::splat(f32::NEG_INFINITY);
+        let mut exit = Vec3::splat(f32::INFINITY);
+        for axis in 0..3 {
+            if motion[axis] == 0.0 {
+                if origin[axis] <= min[axis] || origin[axis] >= max[axis] {
+                    return None;
+                }
+            } else {
+                let t1 = (min[axis] - origin[axis]) / motion[axis];
+                let t2 = (max[axis] - origin[axis]) / motion[axis];
+                entry[axis] = t1.min(t2);
+                exit[axis] = t1.max(t2);
+            }
+        }
+        let t_entry = entry.max_element();
+        let t_exit = exit.min_element();
+        if t_entry >= t_exit || !(-SKIN..=1.0).contains(&t_entry) || t_exit <= 0.0 {
+            return None;
+        }
+        let axis = if entry.x >= entry.y && entry.x >= entry.z {
+            0
+        } else if entry.y >= entry.z {
+            1
+        } else {
+            2
+        };
+        let mut normal = Vec3::ZERO;
+        normal[axis] = -motion[axis].signum();
+        Some(SweepHit {
+            time: t_entry.max(0.0),
+            normal,
+        })
+    }
+}
+
+/// 可供碰撞检测的静态几何体
+///
+/// 碰撞模块只需要知道某个区域内有哪些实心的轴对齐包围盒，体素世界与包围盒列表都实现了该特征，
+/// 也可以为高度图等自定义几何体实现
+pub trait Colliders {
+    /// 收集与区域重叠的所有实心包围盒
+    ///
+    /// # 参数
+    /// + `region` - 查询区域
+    /// + `out` - 输出列表，结果追加在末尾
+    fn overlapping(&self, region: &Aabb, out: &mut Vec<Aabb>);
+}
+
+impl Colliders for [Aabb] {
+    fn overlapping(&self, region: &Aabb, out: &mut Vec<Aabb>) {
+        out.extend(self.iter().filter(|aabb| aabb.intersects(region)));
+    }
+}
+
+impl Colliders for VoxelWorld {
+    /// 所有非空气方块均为边长为 1 的实心立方体
+    fn overlapping(&self, region: &Aabb, out: &mut Vec<Aabb>) {
+        let min = region.min.floor().as_ivec3();
+        let max = region.max.ceil().as_ivec3();
+        for y in min.y..max.y {
+            for z in min.z..max.z {
+                for x in min.x..max.x {
+                    let position = IVec3::new(x, y, z);
+                    if !self.block(position).is_air() {
+                        let min = position.as_vec3();
+                        out.push(Aabb::new(min, min + Vec3::ONE));
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// 扫掠检测：包围盒沿位移移动时最早接触的几何体
+///
+/// # 参数
+/// + `colliders` - 静态几何体
+/// + `aabb` - 移动的包围盒
+/// + `motion` - 位移
+///
+/// # 返回值
+/// 返回最早的接触，不接触时返回`None`
+pub fn sweep<C: Colliders + ?Sized>(
+    colliders: &C,
+    aabb: &Aabb,
+    motion: Vec3,
+) -> Option<SweepHit> {
+    let region = aabb.union(&aabb.translated(motion));
+    let mut candidates = Vec::new();
+    colliders.overlapping(&region, &mut candidates);
+    candidates
+        .iter()
+        .filter_map(|other| aabb.sweep(motion, other))
+        .min_by(|a, b| a.time.total_cmp(&b.time))
+}
+
+/// [`move_and_slide`] 的结果
+#[derive(Debug, Clone, PartialEq)]
+pub struct SlideResult {
+    /// 移动后的包围盒
+    pub aabb: Aabb,
+    /// 实际完成的位移
+    pub motion: Vec3,
+    /// 移动过程中接触的表面法线，按接触顺序排列
+    pub normals: Vec<Vec3>,
+}
+
+impl SlideResult {
+    /// 是否接触了地面(法线朝上的表面)
+    pub fn on_floor(&self) -> bool {
+        self.normals.iter().any(|n| n.y > 0.5)
+    }
+
+    /// 是否接触了天花板(法线朝下的表面)
+    pub fn on_ceiling(&self) -> bool {
+        self.normals.iter().any(|n| n.y < -0.5)
+    }
+
+    /// 是否接触了墙壁(法线水平的表面)
+    pub fn on_wall(&self) -> bool {
+        self.normals.iter().any(|n| n.y.abs() <= 0.5)
+    }
+
+    /// 是否发生了任何接触
+    pub fn collided(&self) -> bool {
+        !self.normals.is_empty()
+    }
+}
+
+/// 移动包围盒，遇到几何体时沿表面滑动
+///
+/// 每次接触后移动到接触位置，去掉剩余位移中朝向表面的分量并继续移动，最多滑动 4 次
+///
+/// # 参数
+/// + `colliders` - 静态几何体
+/// + `aabb` - 移动的包围盒
+/// + `motion` - 期望的位移
+///
+/// # 返回值
+/// 返回移动后的包围盒、实际位移与接触的表面
+///
+/// # 示例
+///
+/// ```
+/// use gle::{*, math::*};
+///
+/// let mut world = VoxelWorld::new();
+/// for x in -4..4 {
+///     for z in -4..4 {
+///         world.set_block(IVec3::new(x, 0, z), BlockId(1));
+///     }
+/// }
+/// let player = Aabb::from_center_half_extents(Vec3::new(0.0, 2.0, 0.0), Vec3::new(0.3, 0.9, 0.3));
+/// let result = move_and_slide(&world, &player, Vec3::new(0.5, -2.0, 0.0));
+/// assert!(result.on_floor());
+/// assert!(result.aabb.min.y >= 1.0);
+/// assert!((result.motion.x - 0.5).abs() < 1e-3);
+/// ```
+///
+/// # 注解
+///
+/// 开始时已与几何体重叠的包围盒不会被阻挡，以便从中移出；需要推出时可使用 [`Aabb::penetration`]
+pub fn move_and_slide<C: Colliders + ?Sized>(
+    colliders: &C,
+    aabb: &Aabb,
+    motion: Vec3,
+) -> SlideResult {
+    let mut result = SlideResult {
+        aabb: *aabb,
+        motion: Vec3::ZERO,
+        normals: Vec::new(),
+    };
+    let mut remaining = motion;
+    for _ in 0..MAX_SLIDES {
+        let length = remaining.length();
+        if length <= SKIN * 0.01 {
+            break;
+        }
+        let Some(hit) = sweep(colliders, &result.aabb, remaining) else {
+            result.aabb = result.aabb.translated(remaining);
+            result.motion += remaining;
+            break;
+        };
+        // 停在距离表面 SKIN 处
+        let time = (hit.time - SKIN / length).max(0.0);
+        let step = remaining * time;
+        result.aabb = result.aabb.translated(step);
+        result.motion += step;
+        result.normals.push(hit.normal);
+        remaining *= 1.0 - time;
+        remaining -= hit.normal * remaining.dot(hit.normal);
+    }
+    result
+}
@@ -0,0 +1,535 @@
+use std::path::Path;
+
+use crate::error::{Error, Result};
+use crate::{debug, warn, Texture2D};
+
+const DDS_MAGIC: &[u8; 4] = b"DDS ";
+const KTX2_MAGIC: [u8; 12] = [
+    0xAB, 0x4B, 0x54, 0x58, 0x20, 0x32, 0x30, 0xBB, 0x0D, 0x0A, 0x1A, 0x0A,
+];
+
+const COMPRESSED_RGB_S3TC_DXT1: u32 = 0x83F0;
+const COMPRESSED_RGBA_S3TC_DXT1: u32 = 0x83F1;
+const COMPRESSED_RGBA_S3TC_DXT3: u32 = 0x83F2;
+const COMPRESSED_RGBA_S3TC_DXT5: u32 = 0x83F3;
+const COMPRESSED_SRGB_S3TC_DXT1: u32 = 0x8C4C;
+const COMPRESSED_SRGB_ALPHA_S3TC_DXT1: u32 = 0x8C4D;
+const COMPRESSED_SRGB_ALPHA_S3TC_DXT3: u32 = 0x8C4E;
+const COMPRESSED_SRGB_ALPHA_S3TC_DXT5: u32 = 0x8C4F;
+const COMPRESSED_RED_RGTC1: u32 = 0x8DBB;
+const COMPRESSED_RG_RGTC2: u32 = 0x8DBD;
+const COMPRESSED_RGBA_BPTC_UNORM: u32 = 0x8E8C;
+const COMPRESSED_SRGB_ALPHA_BPTC_UNORM: u32 = 0x8E8D;
+const COMPRESSED_RGB_BPTC_SIGNED_FLOAT: u32 = 0x8E8E;
+const COMPRESSED_RGB_BPTC_UNSIGNED_FLOAT: u32 = 0x8E8F;
+
+/// 块压缩格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BcFormat {
+    /// BC1(DXT1)，RGB 与 1 位透明度
+    Bc1,
+    /// BC1(DXT1)，不含透明度
+    Bc1Rgb,
+    /// BC2(DXT3)，4 位显式透明度
+    Bc2,
+    /// BC3(DXT5)，插值透明度
+    Bc3,
+    /// BC4，单通道
+    Bc4,
+    /// BC5，双通道，常用于法线贴图
+    Bc5,
+    /// BC6H，无符号半精度浮点 HDR
+    Bc6hUfloat,
+    /// BC6H，有符号半精度浮点 HDR
+    Bc6hSfloat,
+    /// BC7，高质量 RGBA
+    Bc7,
+}
+
+impl BcFormat {
+    /// 每个 4x4 像素块的字节数
+    pub fn block_size(self) -> usize {
+        match self {
+            BcFormat::Bc1 | BcFormat::Bc1Rgb | BcFormat::Bc4 => 8,
+            _ => 16,
+        }
+    }
+
+    /// 获取尺寸为`width * height`的一级纹理的字节数
+    pub fn level_size(self, width: u32, height: u32) -> usize {
+        let blocks_x = width.div_ceil(4).max(1) as usize;
+        let blocks_y = height.div_ceil(4).max(1) as usize;
+        blocks_x * blocks_y * self.block_size()
+    }
+
+    /// 获取对应的 OpenGL 内部格式
+    ///
+    /// # 参数
+    /// + `srgb` - 是否为 sRGB 颜色空间，对没有 sRGB 变体的格式无效
+    pub fn gl_format(self, srgb: bool) -> u32 {
+        match (self, srgb) {
+            (BcFormat::Bc1, false) => COMPRESSED_RGBA_S3TC_DXT1,
+            (BcFormat::Bc1, true) => COMPRESSED_SRGB_ALPHA_S3TC_DXT1,
+            (BcFormat::Bc1Rgb, false) => COMPRESSED_RGB_S3TC_DXT1,
+            (BcFormat::Bc1Rgb, true) => COMPRESSED_SRGB_S3TC_DXT1,
+            (BcFormat::Bc2, false) => COMPRESSED_RGBA_S3TC_DXT3,
+            (BcFormat::Bc2, true) => COMPRESSED_SRGB_ALPHA_S3TC_DXT3,
+            (BcFormat::Bc3, false) => COMPRESSED_RGBA_S3TC_DXT5,
+            (BcFormat::Bc3, true) => COMPRESSED_SRGB_ALPHA_S3TC_DXT5,
+            (BcFormat::Bc4, _) => COMPRESSED_RED_RGTC1,
+            (BcFormat::Bc5, _) => COMPRESSED_RG_RGTC2,
+            (BcFormat::Bc6hUfloat, _) => COMPRESSED_RGB_BPTC_UNSIGNED_FLOAT,
+            (BcFormat::Bc6hSfloat, _) => COMPRESSED_RGB_BPTC_SIGNED_FLOAT,
+            (BcFormat::Bc7, false) => COMPRESSED_RGBA_BPTC_UNORM,
+            (BcFormat::Bc7, true) => COMPRESSED_SRGB_ALPHA_BPTC_UNORM,
+        }
+    }
+
+    /// 当前 OpenGL 上下文是否支持该格式，只能在渲染线程中调用
+    pub fn is_supported(self) -> bool {
+        match self {
+            BcFormat::Bc1 | BcFormat::Bc1Rgb | BcFormat::Bc2 | BcFormat::Bc3 => {
+                has_extension("GL_EXT_texture_compression_s3tc")
+            }
+            // RGTC 自 OpenGL 3.0 起为核心功能
+            BcFormat::Bc4 | BcFormat::Bc5 => true,
+            BcFormat::Bc6hUfloat | BcFormat::Bc6hSfloat | BcFormat::Bc7 => {
+                gl_version() >= (4, 2) || has_extension("GL_ARB_texture_compression_bptc")
+            }
+        }
+    }
+}
+
+fn gl_version() -> (i32, i32) {
+    let (mut major, mut minor) = (0, 0);
+    unsafe {
+        gl::GetIntegerv(gl::MAJOR_VERSION, &mut major);
+        gl::GetIntegerv(gl::MINOR_VERSION, &mut minor);
+    }
+    (major, minor)
+}
+
+pub(crate) fn has_extension(name: &str) -> bool {
+    let mut count = 0;
+    unsafe { gl::GetIntegerv(gl::NUM_EXTENSIONS, &mut count) };
+    (0..count.max(0) as u32).any(|i| unsafe {
+        let ptr = gl::GetStringi(gl::EXTENSIONS, i);
+        !ptr.is_null() && std::ffi::CStr::from_ptr(ptr as *const _).to_bytes() == name.as_bytes()
+    })
+}
+
+/// 预压缩的纹理图像
+///
+/// 从 DDS 或 KTX2 容器读取的块压缩数据，包含完整的多级渐远纹理链
+///
+/// # 注解
+///
+/// 仅支持二维纹理，不支持立方体贴图、纹理数组与 KTX2 的超压缩(如 Basis Universal、Zstandard)。
+/// 图像按容器中的顺序(首行为顶部)上传，不做翻转
+#[derive(Debug, Clone)]
+pub struct CompressedImage {
+    /// 压缩格式
+    pub format: BcFormat,
+    /// 容器是否声明了 sRGB 颜色空间
+    pub srgb: bool,
+    /// 宽度
+    pub width: u32,
+    /// 高度
+    pub height: u32,
+    /// 各级纹理的数据，第 0 级为原始尺寸
+    pub levels: Vec<Vec<u8>>,
+}
+
+impl CompressedImage {
+    /// 从文件加载，根据文件头识别 DDS 或 KTX2 格式
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let bytes = std::fs::read(path)?;
+        Self::parse(&bytes).map_err(|e| Error::Parse(format!("{}: {}", path.display(), e)))
+    }
+
+    /// 从内存中的 DDS 或 KTX2 数据解析
+    pub fn parse(bytes: &[u8]) -> Result<Self> {
+        if bytes.starts_with(DDS_MAGIC) {
+            Self::parse_dds(bytes)
+        } else if bytes.starts_with(&KTX2_MAGIC) {
+            Self::parse_ktx2(bytes)
+        } else {
+            Err(Error::Parse("不是 DDS 或 KTX2 文件".to_string()))
+        }
+    }
+
+    fn parse_dds(bytes: &[u8]) -> Result<Self> {
+        if bytes.len() < 128 {
+            return Err(Error::Parse("DDS 文件头不完整".to_string()));
+        }
+        let height = read_u32(bytes, 12);
+        let width = read_u32(bytes, 16);
+        let mip_count = read_u32(bytes, 28).max(1);
+        let four_cc = &bytes[84..88];
+        if read_u32(bytes, 112) & 0x200 != 0 {
+            return Err(Error::Parse("不支持立方体贴图".to_string()));
+        }
+        let (format, srgb, offset) = match four_cc {
+            b"DXT1" => (BcFormat::Bc1, false, 128),
+            b"DXT2" | b"DXT3" => (BcFormat::Bc2, false, 128),
+            b"DXT4" | b"DXT5" => (BcFormat::Bc3, false, 128),
+            b"ATI1" | b"BC4U" => (BcFormat::Bc4, false, 128),
+            b"ATI2" | b"BC5U" => (BcFormat::Bc5, false, 128),
+            b"DX10" => {
+                if bytes.len() < 148 {
+                    return Err(Error::Parse("DDS 扩展文件头不完整".to_string()));
+                }
+                if read_u32(bytes, 140) > 1 {
+                    return Err(Error::Parse("不支持纹理数组".to_string()));
+                }
+                let (format, srgb) = match read_u32(bytes, 128) {
+                    70 | 71 => (BcFormat::Bc1, false),
+                    72 => (BcFormat::Bc1, true),
+                    73 | 74 => (BcFormat::Bc2, false),
+                    75 => (BcFormat::Bc2, true),
+                    76 | 77 => (BcFormat::Bc3, false),
+                    78 => (BcFormat::Bc3, true),
+                    79 | 80 => (BcFormat::Bc4, false),
+                    82 | 83 => (BcFormat::Bc5, false),
+                    94 | 95 => (BcFormat::Bc6hUfloat, false),
+                    96 => (BcFormat::Bc6hSfloat, false),
+                    97 | 98 => (BcFormat::Bc7, false),
+                    99 => (BcFormat::Bc7, true),
+                    other => {
+                        return Err(Error::Parse(format!("不支持的 DXGI 格式 {}", other)));
+                    }
+                };
+                (format, srgb, 148)
+            }
+            other => {
+                return Err(Error::Parse(format!(
+                    "不支持的 DDS 格式 {}",
+                    String::from_utf8_lossy(other)
+                )));
+            }
+        };
+        let mut levels = Vec::new();
+        let mut offset = offset;
+        for level in 0..mip_count {
+            let (w, h) = level_extent(width, height, level);
+            let size = format.level_size(w, h);
+            let data = bytes
+                .get(offset..offset + size)
+                .ok_or_else(|| Error::Parse(format!("第 {} 级纹理数据不完整", level)))?;
+            levels.push(data.to_vec());
+            offset += size;
+        }
+        Ok(Self {
+            format,
+            srgb,
+            width,
+            height,
+            levels,
+        })
+    }
+
+    fn parse_ktx2(bytes: &[u8]) -> Result<Self> {
+        if bytes.len() < 80 {
+            return Err(Error::Parse("KTX2 文件头不完整".to_string()));
+        }
+        let vk_format = read_u32(bytes, 12);
+        let width = read_u32(bytes, 20);
+        let height = read_u32(bytes, 24);
+        let depth = read_u32(bytes, 28);
+        let layers = read_u32(bytes, 32);
+        let faces = read_u32(bytes, 36);
+        let level_count = read_u32(bytes, 40).max(1);
+        let supercompression = read_u32(bytes, 44);
+        if depth > 1 || layers > 1 || faces != 1 {
+            return Err(Error::Parse("仅支持二维纹理".to_string()));
+        }
+        if supercompression != 0 {
+            return Err(Error::Parse(format!("不支持超压缩方案 {}", supercompression)));
+        }
+        let (format, srgb) = match vk_format {
+            131 => (BcFormat::Bc1Rgb, false),
+            132 => (BcFormat::Bc1Rgb, true),
+            133 => (BcFormat::Bc1, false),
+            134 => (BcFormat::Bc1, true),
+            135 => (BcFormat::Bc2, false),
+            136 => (BcFormat::Bc2, true),
+            137 => (BcFormat::Bc3, false),
+            138 => (BcFormat::Bc3, true),
+            139 => (BcFormat::Bc4, false),
+            141 => (BcFormat::Bc5, false),
+            143 => (BcFormat::Bc6hUfloat, false),
+            144 => (BcFormat::Bc6hSfloat, false),
+            145 => (BcFormat::Bc7, false),
+            146 => (BcFormat::Bc7, true),
+            other => return Err(Error::Parse(format!("不支持的 Vulkan 格式 {}", other))),
+        };
+        let mut levels = Vec::new();
+        for level in 0..level_count {
+            let index = 80 + level as usize * 24;
+            if bytes.len() < index + 16 {
+                return Err(Error::Parse("KTX2 级别索引不完整".to_string()));
+            }
+            let offset = read_u64(bytes, index) as usize;
+            let length = read_u64(bytes, index + 8) as usize;
+            let data = bytes
+                .get(offset..offset.saturating_add(length))
+                .ok_or_else(|| Error::Parse(format!("第 {} 级纹理数据不完整", level)))?;
+            levels.push(data.to_vec());
+        }
+        Ok(Self {
+            format,
+            srgb,
+            width,
+            height,
+            levels,
+        })
+    }
+
+    /// 将各级纹理解压为 RGBA8 像素数据
+    ///
+    /// # 返回值
+    /// 返回各级的像素数据，BC6H 与 BC7 不支持软件解压，返回错误
+    pub fn decompress(&self) -> Result<Vec<Vec<u8>>> {
+        let decode_block: fn(&[u8], &mut [[u8; 4]; 16]) = match self.format {
+            BcFormat::Bc1 | BcFormat::Bc1Rgb => |b, out| decode_bc1(b, out, true),
+            BcFormat::Bc2 => decode_bc2,
+            BcFormat::Bc3 => decode_bc3,
+            BcFormat::Bc4 => decode_bc4,
+            BcFormat::Bc5 => decode_bc5,
+            other => {
+                return Err(Error::Parse(format!("{:?} 格式不支持软件解压", other)));
+            }
+        };
+        let block_size = self.format.block_size();
+        let mut result = Vec::with_capacity(self.levels.len());
+        for (level, data) in self.levels.iter().enumerate() {
+            let (w, h) = level_extent(self.width, self.height, level as u32);
+            let (w, h) = (w as usize, h as usize);
+            let blocks_x = w.div_ceil(4).max(1);
+            let mut pixels = vec![0u8; w * h * 4];
+            let mut texels = [[0u8; 4]; 16];
+            for (i, block) in data.chunks_exact(block_size).enumerate() {
+                let (bx, by) = (i % blocks_x * 4, i / blocks_x * 4);
+                if by >= h {
+                    break;
+                }
+                decode_block(block, &mut texels);
+                for (j, texel) in texels.iter().enumerate() {
+                    let (x, y) = (bx + j % 4, by + j / 4);
+                    if x < w && y < h {
+                        pixels[(y * w + x) * 4..][..4].copy_from_slice(texel);
+                    }
+                }
+            }
+            result.push(pixels);
+        }
+        Ok(result)
+    }
+
+    /// 上传为纹理
+    ///
+    /// # 参数
+    /// + `srgb` - 是否按 sRGB 颜色空间采样，容器声明为 sRGB 时总是按 sRGB 采样
+    ///
+    /// # 返回值
+    /// 成功时返回纹理，当前上下文不支持该格式且无法软件解压时返回错误
+    ///
+    /// # 注解
+    ///
+    /// 当前上下文不支持该格式时，先在 CPU 上解压为 RGBA8 再上传
+    pub fn upload(&self, srgb: bool) -> Result<Texture2D> {
+        let srgb = srgb || self.srgb;
+        let mut id = 0;
+        unsafe {
+            gl::GenTextures(1, &mut id);
+            gl::BindTexture(gl::TEXTURE_2D, id);
+        }
+        // 先创建纹理对象再构造包装，出错返回时纹理对象随之删除
+        let texture = Texture2D::from_raw(id, self.width, self.height);
+        if self.format.is_supported() {
+            for (level, data) in self.levels.iter().enumerate() {
+                let (w, h) = level_extent(self.width, self.height, level as u32);
+                unsafe {
+                    gl::CompressedTexImage2D(
+                        gl::TEXTURE_2D,
+                        level as i32,
+                        self.format.gl_format(srgb),
+                        w as i32,
+                        h as i32,
+                        0,
+                        data.len() as i32,
+                        data.as_ptr() as *const _,
+                    );
+                }
+            }
+        } else {
+            warn!(Self, "当前上下文不支持 {:?} 格式，将解压为 RGBA8 后上传", self.format);
+            let internal = if srgb { gl::SRGB8_ALPHA8 } else { gl::RGBA8 };
+            for (level, pixels) in self.decompress()?.iter().enumerate() {
+                let (w, h) = level_extent(self.width, self.height, level as u32);
+                unsafe {
+                    gl::PixelStorei(gl::UNPACK_ALIGNMENT, 1);
+                    gl::TexImage2D(
+                        gl::TEXTURE_2D,
+                        level as i32,
+                        internal as i32,
+                        w as i32,
+                        h as i32,
+                        0,
+                        gl::RGBA,
+                        gl::UNSIGNED_BYTE,
+                        pixels.as_ptr() as *const _,
+                    );
+                }
+            }
+        }
+        let min_filter = if self.levels.len() > 1 {
+            gl::LINEAR_MIPMAP_LINEAR
+        } else {
+            gl::LINEAR
+        };
+        unsafe {
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAX_LEVEL, self.levels.len() as i32 - 1);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, gl::REPEAT as i32);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, gl::REPEAT as i32);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, min_filter as i32);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::LINEAR as i32);
+            gl::BindTexture(gl::TEXTURE_2D, 0);
+        }
+        debug!(
+            Self,
+            "已上传 {}x{} {:?} 纹理，共 {} 级",
+            self.width,
+            self.height,
+            self.format,
+            self.levels.len()
+        );
+        Ok(texture)
+    }
+}
+
+impl Texture2D {
+    /// 从 DDS 或 KTX2 文件加载块压缩纹理
+    ///
+    /// # 参数
+    /// + `path` - 文件路径
+    /// + `srgb` - 是否为 sRGB 颜色空间
+    ///
+    /// # 返回值
+    /// 成功时返回纹理，文件读取失败、格式不受支持时返回错误
+    ///
+    /// # 示例
+    ///
+    /// ```
+    /// use gle::*;
+    ///
+    /// fn render_init() {
+    ///     let texture = Texture2D::load_compressed("assets/brick.ktx2", true).unwrap();
+    ///     texture.bind(0);
+    /// }
+    /// ```
+    pub fn load_compressed<P: AsRef<Path>>(path: P, srgb: bool) -> Result<Self> {
+        CompressedImage::load(path)?.upload(srgb)
+    }
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap())
+}
+
+fn read_u64(bytes: &[u8], offset: usize) -> u64 {
+    u64::from_le_bytes(bytes[offset..offset + 8].try_into().unwrap())
+}
+
+fn level_extent(width: u32, height: u32, level: u32) -> (u32, u32) {
+    ((width >> level).max(1), (height >> level).max(1))
+}
+
+fn rgb565(c: u16) -> [u8; 3] {
+    let r = (c >> 11) & 0x1F;
+    let g = (c >> 5) & 0x3F;
+    let b = c & 0x1F;
+    [
+        ((r << 3) | (r >> 2)) as u8,
+        ((g << 2) | (g >> 4)) as u8,
+        ((b << 3) | (b >> 2)) as u8,
+    ]
+}
+
+/// 解码 BC1 颜色块，`allow_alpha`为`false`时(BC2/BC3 的颜色部分)总是使用四色模式
+fn decode_bc1(block: &[u8], out: &mut [[u8; 4]; 16], allow_alpha: bool) {
+    let c0 = u16::from_le_bytes([block[0], block[1]]);
+    let c1 = u16::from_le_bytes([block[2], block[3]]);
+    let (a, b) = (rgb565(c0), rgb565(c1));
+    let mix = |wa: u16, wb: u16, d: u16| -> [u8; 4] {
+        let ch = |i: usize| ((a[i] as u16 * wa + b[i] as u16 * wb) / d) as u8;
+        [ch(0), ch(1), ch(2), 255]
+    };
+    let palette = if c0 > c1 || !allow_alpha {
+        [[a[0], a[1], a[2], 255], [b[0], b[1], b[2], 255], mix(2, 1, 3), mix(1, 2, 3)]
+    } else {
+        [[a[0], a[1], a[2], 255], [b[0], b[1], b[2], 255], mix(1, 1, 2), [0; 4]]
+    };
+    let indices = u32::from_le_bytes([block[4], block[5], block[6], block[7]]);
+    for (i, texel) in out.iter_mut().enumerate() {
+        *texel = palette[(indices >> (i * 2) & 3) as usize];
+    }
+}
+
+/// 解码 BC3/BC4/BC5 中使用的 8 字节单通道插值块
+fn decode_channel(block: &[u8]) -> [u8; 16] {
+    let (a0, a1) = (block[0] as u16, block[1] as u16);
+    let mut palette = [0u8; 8];
+    palette[0] = a0 as u8;
+    palette[1] = a1 as u8;
+    if a0 > a1 {
+        for i in 1..7 {
+            palette[i + 1] = ((a0 * (7 - i as u16) + a1 * i as u16) / 7) as u8;
+        }
+    } else {
+        for i in 1..5 {
+            palette[i + 1] = ((a0 * (5 - i as u16) + a1 * i as u16) / 5) as u8;
+        }
+        palette[6] = 0;
+        palette[7] = 255;
+    }
+    let mut bits = 0u64;
+    for (i, byte) in block[2..8].iter().enumerate() {
+        bits |= (*byte as u64) << (i * 8);
+    }
+    let mut out = [0u8; 16];
+    for (i, value) in out.iter_mut().enumerate() {
+        *value = palette[(bits >> (i * 3) & 7) as usize];
+    }
+    out
+}
+
+fn decode_bc2(block: &[u8], out: &mut [[u8; 4]; 16]) {
+    decode_bc1(&block[8..], out, false);
+    for (i, texel) in out.iter_mut().enumerate() {
+        let alpha = (block[i / 2] >> (i % 2 * 4)) & 0xF;
+        texel[3] = alpha * 17;
+    }
+}
+
+fn decode_bc3(block: &[u8], out: &mut [[u8; 4]; 16]) {
+    decode_bc1(&block[8..], out, false);
+    for (texel, alpha) in out.iter_mut().zip(decode_channel(&block[..8])) {
+        texel[3] = alpha;
+    }
+}
+
+fn decode_bc4(block: &[u8], out: &mut [[u8; 4]; 16]) {
+    for (texel, red) in out.iter_mut().zip(decode_channel(block)) {
+        *texel = [red, 0, 0, 255];
+    }
+}
+
+fn decode_bc5(block: &[u8], out: &mut [[u8; 4]; 16]) {
+    let red = decode_channel(&block[..8]);
+    let green = decode_channel(&block[8..]);
+    for (i, texel) in out.iter_mut().enumerate() {
+        *texel = [red[i], green[i], 0, 255];
+    }
+}
@@ -0,0 +1,146 @@
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Error, Result};
+use crate::Level;
+
+/// 窗口配置
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WindowConfig {
+    /// 窗口宽度(默认值为800)
+    pub width: i32,
+    /// 窗口高度(默认值为600)
+    pub height: i32,
+    /// 窗口标题
+    pub title: String,
+    /// 是否在主显示器上全屏显示，全屏时窗口大小取显示器当前的分辨率(默认值为`false`)
+    pub fullscreen: bool,
+    /// 是否开启垂直同步(默认值为`true`)
+    pub vsync: bool,
+    /// 多重采样抗锯齿的采样数，为 0 时关闭(默认值为0)
+    pub msaa: u32,
+}
+
+impl Default for WindowConfig {
+    fn default() -> Self {
+        Self {
+            width: 800,
+            height: 600,
+            title: "OpenGL Engine".to_string(),
+            fullscreen: false,
+            vsync: true,
+            msaa: 0,
+        }
+    }
+}
+
+/// 日志配置
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LogConfig {
+    /// 日志级别(默认值为`info`)
+    pub level: Level,
+    /// 日志输出文件，为空时输出到控制台
+    pub file: Option<PathBuf>,
+}
+
+impl Default for LogConfig {
+    fn default() -> Self {
+        Self {
+            level: Level::Info,
+            file: None,
+        }
+    }
+}
+
+/// 资源配置
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AssetConfig {
+    /// 资源根目录(默认值为`assets`)
+    pub root: PathBuf,
+}
+
+impl Default for AssetConfig {
+    fn default() -> Self {
+        Self {
+            root: PathBuf::from("assets"),
+        }
+    }
+}
+
+/// 引擎配置
+///
+/// 通常保存在程序目录下的`gle.toml`中，通过 [`AppBuilder::from_config`](crate::AppBuilder::from_config)
+/// 在启动时加载，使部署后的程序无需重新编译即可调整窗口、日志与资源目录。所有字段均可省略
+///
+/// # 示例
+///
+/// ```toml
+/// [window]
+/// width = 1280
+/// height = 720
+/// title = "RustCraft"
+/// fullscreen = false
+/// vsync = false
+/// msaa = 4
+///
+/// [log]
+/// level = "debug"
+/// file = "logs/game.log"
+///
+/// [assets]
+/// root = "data"
+/// ```
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EngineConfig {
+    /// 窗口配置
+    pub window: WindowConfig,
+    /// 日志配置
+    pub log: LogConfig,
+    /// 资源配置
+    pub assets: AssetConfig,
+}
+
+impl EngineConfig {
+    /// 从 TOML 源码解析配置
+    ///
+    /// # 参数
+    /// + `src` - TOML 源码
+    pub fn parse(src: &str) -> Result<Self> {
+        toml::from_str(src).map_err(|e| Error::Parse(e.to_string()))
+    }
+
+    /// 从配置文件加载配置
+    ///
+    /// # 参数
+    /// + `path` - 配置文件路径
+    ///
+    /// # 返回值
+    /// 成功时返回配置，其中的相对路径已转换为相对于当前工作目录的路径
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let src = std::fs::read_to_string(path)?;
+        let mut config = toml::from_str::<Self>(&src)
+            .map_err(|e| Error::Parse(format!("{}: {}", path.display(), e)))?;
+        let dir = path.parent().unwrap_or(Path::new(""));
+        config.assets.root = dir.join(&config.assets.root);
+        if let Some(file) = config.log.file.as_mut() {
+            *file = dir.join(&*file);
+        }
+        Ok(config)
+    }
+
+    /// 将配置保存到文件
+    ///
+    /// # 参数
+    /// + `path` - 配置文件路径
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let src = toml::to_string_pretty(self).map_err(|e| Error::Parse(e.to_string()))?;
+        std::fs::write(path, src)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,263 @@
+use crate::math::*;
+use crate::{App, Camera, CursorMode, Input, Key, MouseButton};
+
+/// 第一人称摄像机控制器
+///
+/// 使用`WASD`水平移动，`Space`/`LeftShift`垂直移动，按住`LeftControl`加速，
+/// 在光标被捕获时通过鼠标转动视角，按`Escape`切换光标捕获状态
+///
+/// # 示例
+///
+/// ```
+/// use gle::*;
+///
+/// let mut controller = FpsCameraController::new();
+/// let mut app = AppBuilder::new(800, 600, "OpenGL Engine")
+///     .set_event_init(|| Camera::register(Camera::perspective(1.0, 0.1, 100.0)))
+///     .set_event_loop(move || controller.update_main())
+///     .build();
+/// ```
+#[derive(Debug, Clone)]
+pub struct FpsCameraController {
+    /// 偏航角，单位为弧度，为零时朝向`-Z`方向
+    pub yaw: f32,
+    /// 俯仰角，单位为弧度
+    pub pitch: f32,
+    /// 移动速度，单位为每秒
+    pub move_speed: f32,
+    /// 加速时的速度倍率
+    pub sprint_multiplier: f32,
+    /// 鼠标灵敏度，单位为弧度每像素
+    pub mouse_sensitivity: f32,
+    /// 俯仰角的最大绝对值，单位为弧度
+    pub pitch_limit: f32,
+    /// 切换光标捕获状态的按键
+    pub grab_key: Key,
+    grabbed: bool,
+}
+
+impl Default for FpsCameraController {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FpsCameraController {
+    /// 创建一个新的第一人称摄像机控制器
+    ///
+    /// # 返回值
+    /// 返回一个光标未被捕获的控制器
+    pub fn new() -> Self {
+        Self {
+            yaw: 0.0,
+            pitch: 0.0,
+            move_speed: 5.0,
+            sprint_multiplier: 3.0,
+            mouse_sensitivity: 0.002,
+            pitch_limit: 89f32.to_radians(),
+            grab_key: Key::Escape,
+            grabbed: false,
+        }
+    }
+
+    /// 判断光标是否被捕获
+    pub fn is_grabbed(&self) -> bool {
+        self.grabbed
+    }
+
+    /// 设置光标捕获状态
+    ///
+    /// # 参数
+    /// + `grabbed` - 为`true`时隐藏并锁定光标，为`false`时恢复正常光标
+    pub fn set_grabbed(&mut self, grabbed: bool) {
+        self.grabbed = grabbed;
+        App::set_cursor_mode(if grabbed {
+            CursorMode::Disabled
+        } else {
+            CursorMode::Normal
+        });
+    }
+
+    /// 根据摄像机当前朝向同步偏航角与俯仰角
+    ///
+    /// # 参数
+    /// + `camera` - 摄像机
+    pub fn sync_from(&mut self, camera: &Camera) {
+        let forward = camera.forward();
+        self.yaw = (-forward.x).atan2(-forward.z);
+        self.pitch = forward.y.clamp(-1.0, 1.0).asin();
+    }
+
+    /// 根据本帧输入更新摄像机
+    ///
+    /// # 参数
+    /// + `camera` - 被控制的摄像机
+    /// + `dt` - 帧间隔时间，单位为秒
+    pub fn update(&mut self, camera: &mut Camera, dt: f32) {
+        if Input::key_pressed(self.grab_key) {
+            self.set_grabbed(!self.grabbed);
+        } else if self.grabbed {
+            let (dx, dy) = Input::cursor_delta();
+            self.yaw -= dx as f32 * self.mouse_sensitivity;
+            self.pitch -= dy as f32 * self.mouse_sensitivity;
+            self.pitch = self.pitch.clamp(-self.pitch_limit, self.pitch_limit);
+        }
+        camera.rotation = Quat::from_rotation_y(self.yaw) * Quat::from_rotation_x(self.pitch);
+
+        let forward = Quat::from_rotation_y(self.yaw) * Vec3::NEG_Z;
+        let right = Quat::from_rotation_y(self.yaw) * Vec3::X;
+        let mut dir = Vec3::ZERO;
+        if Input::key_down(Key::W) {
+            dir += forward;
+        }
+        if Input::key_down(Key::S) {
+            dir -= forward;
+        }
+        if Input::key_down(Key::D) {
+            dir += right;
+        }
+        if Input::key_down(Key::A) {
+            dir -= right;
+        }
+        if Input::key_down(Key::Space) {
+            dir += Vec3::Y;
+        }
+        if Input::key_down(Key::LeftShift) {
+            dir -= Vec3::Y;
+        }
+        let mut speed = self.move_speed;
+        if Input::key_down(Key::LeftControl) {
+            speed *= self.sprint_multiplier;
+        }
+        camera.position += dir.normalize_or_zero() * speed * dt;
+    }
+
+    /// 根据本帧输入更新主摄像机
+    ///
+    /// # 注解
+    ///
+    /// 应在事件循环函数中调用，帧间隔时间取自 [`App::event_ms`]，未注册主摄像机时不做任何事
+    pub fn update_main(&mut self) {
+        let dt = (App::event_ms() / 1000.0) as f32;
+        Camera::apply_main(|camera| self.update(camera, dt));
+    }
+}
+
+/// 轨道摄像机控制器
+///
+/// 摄像机始终朝向焦点，按住左键拖动以绕焦点旋转，滚动滚轮以缩放距离，按住中键拖动以平移焦点，
+/// 适用于模型查看器与编辑器
+///
+/// # 示例
+///
+/// ```
+/// use gle::{*, math::*};
+///
+/// let mut controller = OrbitCameraController::new(Vec3::ZERO, 5.0);
+/// let mut app = AppBuilder::new(800, 600, "OpenGL Engine")
+///     .set_event_init(|| Camera::register(Camera::perspective(1.0, 0.1, 100.0)))
+///     .set_event_loop(move || controller.update_main())
+///     .build();
+/// ```
+#[derive(Debug, Clone)]
+pub struct OrbitCameraController {
+    /// 焦点位置
+    pub focus: Vec3,
+    /// 摄像机到焦点的距离
+    pub distance: f32,
+    /// 偏航角，单位为弧度，为零时摄像机位于焦点的`+Z`方向
+    pub yaw: f32,
+    /// 俯仰角，单位为弧度，为正时摄像机位于焦点上方
+    pub pitch: f32,
+    /// 旋转灵敏度，单位为弧度每像素
+    pub rotate_sensitivity: f32,
+    /// 平移灵敏度，单位为距离倍率每像素
+    pub pan_sensitivity: f32,
+    /// 缩放速度，每单位滚轮增量使距离变为原来的`exp(-zoom_speed)`倍
+    pub zoom_speed: f32,
+    /// 最小距离
+    pub min_distance: f32,
+    /// 最大距离
+    pub max_distance: f32,
+    /// 俯仰角的最大绝对值，单位为弧度
+    pub pitch_limit: f32,
+    /// 用于旋转的鼠标按键
+    pub rotate_button: MouseButton,
+    /// 用于平移的鼠标按键
+    pub pan_button: MouseButton,
+}
+
+impl OrbitCameraController {
+    /// 创建一个新的轨道摄像机控制器
+    ///
+    /// # 参数
+    /// + `focus` - 焦点位置
+    /// + `distance` - 摄像机到焦点的距离
+    ///
+    /// # 返回值
+    /// 返回一个新的`OrbitCameraController`实例
+    pub fn new(focus: Vec3, distance: f32) -> Self {
+        Self {
+            focus,
+            distance,
+            yaw: 0.0,
+            pitch: 0.0,
+            rotate_sensitivity: 0.005,
+            pan_sensitivity: 0.001,
+            zoom_speed: 0.1,
+            min_distance: 0.1,
+            max_distance: 1000.0,
+            pitch_limit: 89f32.to_radians(),
+            rotate_button: MouseButton::Button1,
+            pan_button: MouseButton::Button3,
+        }
+    }
+
+    /// 获取摄像机朝向
+    pub fn rotation(&self) -> Quat {
+        Quat::from_rotation_y(self.yaw) * Quat::from_rotation_x(-self.pitch)
+    }
+
+    /// 将控制器状态应用到摄像机
+    ///
+    /// # 参数
+    /// + `camera` - 摄像机
+    pub fn apply(&self, camera: &mut Camera) {
+        let rotation = self.rotation();
+        camera.rotation = rotation;
+        camera.position = self.focus + rotation * Vec3::new(0.0, 0.0, self.distance);
+    }
+
+    /// 根据本帧输入更新摄像机
+    ///
+    /// # 参数
+    /// + `camera` - 被控制的摄像机
+    pub fn update(&mut self, camera: &mut Camera) {
+        let (dx, dy) = Input::cursor_delta();
+        let (dx, dy) = (dx as f32, dy as f32);
+        if Input::mouse_down(self.rotate_button) {
+            self.yaw -= dx * self.rotate_sensitivity;
+            self.pitch += dy * self.rotate_sensitivity;
+            self.pitch = self.pitch.clamp(-self.pitch_limit, self.pitch_limit);
+        } else if Input::mouse_down(self.pan_button) {
+            let rotation = self.rotation();
+            let scale = self.distance * self.pan_sensitivity;
+            self.focus += (rotation * Vec3::NEG_X * dx + rotation * Vec3::Y * dy) * scale;
+        }
+        let (_, scroll) = Input::scroll_delta();
+        if scroll != 0.0 {
+            self.distance *= (-(scroll as f32) * self.zoom_speed).exp();
+        }
+        self.distance = self.distance.clamp(self.min_distance, self.max_distance);
+        self.apply(camera);
+    }
+
+    /// 根据本帧输入更新主摄像机
+    ///
+    /// # 注解
+    ///
+    /// 应在事件循环函数中调用，未注册主摄像机时不做任何事
+    pub fn update_main(&mut self) {
+        Camera::apply_main(|camera| self.update(camera));
+    }
+}
@@ -0,0 +1,518 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use lazy_static::lazy_static;
+
+use crate::debug_overlay::system_font;
+use crate::error::Result;
+use crate::math::*;
+use crate::{
+    error, App, AppBuilder, Camera, EnginePlugin, GlObjectKind, GlObjects, Program, RenderStats,
+    SdfFont, Stage, StreamingBuffer, TextRenderer, TextStyle,
+};
+
+const VS: &str = r#"
+#version 330 core
+layout (location = 0) in vec3 aPos;
+layout (location = 1) in vec4 aColor;
+
+uniform mat4 uViewProjection;
+
+out vec4 vColor;
+
+void main()
+{
+    gl_Position = uViewProjection * vec4(aPos, 1.0);
+    vColor = aColor;
+}
+"#;
+
+const FS: &str = r#"
+#version 330 core
+in vec4 vColor;
+out vec4 FragColor;
+
+void main()
+{
+    FragColor = vColor;
+}
+"#;
+
+/// 每个顶点的浮点数：位置(3)、颜色(4)
+const VERTEX_FLOATS: usize = 7;
+/// 线段顶点流式缓冲每段的初始字节大小，约可容纳 4600 条线段
+const STREAM_SEGMENT_SIZE: usize = 256 * 1024;
+/// 队列中最多保存的线段数，超出后新的线段被丢弃
+const MAX_LINES: usize = 1 << 18;
+/// 队列中最多保存的文本数，超出后新的文本被丢弃
+const MAX_LABELS: usize = 4096;
+/// 球体每个圆的线段数
+const CIRCLE_SEGMENTS: usize = 32;
+/// 文本字号，单位为逻辑像素
+const TEXT_SIZE: f32 = 16.0;
+
+static ENABLED: AtomicBool = AtomicBool::new(true);
+
+#[derive(Debug, Clone, Copy)]
+struct Line {
+    a: Vec3,
+    b: Vec3,
+    color: Vec4,
+    depth_test: bool,
+    expires: Option<Instant>,
+}
+
+#[derive(Debug, Clone)]
+struct Label {
+    position: Vec3,
+    text: String,
+    color: Vec4,
+    expires: Option<Instant>,
+}
+
+#[derive(Default)]
+struct Queue {
+    lines: Vec<Line>,
+    labels: Vec<Label>,
+}
+
+lazy_static! {
+    static ref QUEUE: Mutex<Queue> = Mutex::new(Queue::default());
+}
+
+/// 调试绘制的样式
+///
+/// 通过 [`DebugDraw::pen`] 获取，默认开启深度测试且只显示一帧
+///
+/// # 示例
+///
+/// ```no_run
+/// use gle::*;
+/// use gle::math::*;
+///
+/// // 射线检测的结果保留两秒，并且不被场景遮挡
+/// let pen = DebugDraw::pen().with_depth_test(false).with_duration(2.0);
+/// pen.ray(Vec3::ZERO, Vec3::new(0.0, -10.0, 0.0), Vec4::new(1.0, 0.0, 0.0, 1.0));
+/// pen.text3d(Vec3::new(0.0, -10.0, 0.0), "hit", Vec4::ONE);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DebugPen {
+    /// 是否被场景遮挡，文本总是绘制在最上层
+    pub depth_test: bool,
+    /// 显示时间，单位为秒，为 0 时只在下一次提交时显示一帧
+    pub duration: f32,
+}
+
+impl Default for DebugPen {
+    fn default() -> Self {
+        Self {
+            depth_test: true,
+            duration: 0.0,
+        }
+    }
+}
+
+impl DebugPen {
+    /// 设置是否开启深度测试
+    pub fn with_depth_test(mut self, depth_test: bool) -> Self {
+        self.depth_test = depth_test;
+        self
+    }
+
+    /// 设置显示时间，单位为秒
+    pub fn with_duration(mut self, duration: f32) -> Self {
+        self.duration = duration;
+        self
+    }
+
+    /// 绘制线段
+    pub fn line(&self, a: Vec3, b: Vec3, color: Vec4) {
+        self.lines(&[(a, b)], color);
+    }
+
+    /// 绘制从`origin`出发、沿`direction`延伸的射线，`direction`的长度即射线的长度
+    pub fn ray(&self, origin: Vec3, direction: Vec3, color: Vec4) {
+        self.line(origin, origin + direction, color);
+    }
+
+    /// 绘制轴对齐包围盒的十二条棱
+    pub fn aabb(&self, min: Vec3, max: Vec3, color: Vec4) {
+        let corner = |i: usize| {
+            Vec3::new(
+                if i & 1 == 0 { min.x } else { max.x },
+                if i & 2 == 0 { min.y } else { max.y },
+                if i & 4 == 0 { min.z } else { max.z },
+            )
+        };
+        let edges: Vec<(Vec3, Vec3)> = (0..8)
+            .flat_map(|i| [1, 2, 4].into_iter().map(move |bit| (i, i | bit)))
+            .filter(|(i, j)| i != j)
+            .map(|(i, j)| (corner(i), corner(j)))
+            .collect();
+        self.lines(&edges, color);
+    }
+
+    /// 以三个相互垂直的大圆绘制球体
+    pub fn sphere(&self, center: Vec3, radius: f32, color: Vec4) {
+        let mut segments = Vec::with_capacity(CIRCLE_SEGMENTS * 3);
+        for (u, v) in [(Vec3::X, Vec3::Y), (Vec3::Y, Vec3::Z), (Vec3::Z, Vec3::X)] {
+            let point = |i: usize| {
+                let angle = i as f32 / CIRCLE_SEGMENTS as f32 * std::f32::consts::TAU;
+                center + (u * angle.cos() + v * angle.sin()) * radius
+            };
+            segments.extend((0..CIRCLE_SEGMENTS).map(|i| (point(i), point(i + 1))));
+        }
+        self.lines(&segments, color);
+    }
+
+    /// 以红、绿、蓝三色绘制变换的 X、Y、Z 轴
+    ///
+    /// # 参数
+    /// + `transform` - 世界变换矩阵
+    /// + `size` - 轴的长度
+    pub fn axis(&self, transform: &Mat4, size: f32) {
+        let origin = transform.transform_point3(Vec3::ZERO);
+        for (axis, color) in [
+            (Vec3::X, Vec4::new(1.0, 0.2, 0.2, 1.0)),
+            (Vec3::Y, Vec4::new(0.2, 1.0, 0.2, 1.0)),
+            (Vec3::Z, Vec4::new(0.3, 0.5, 1.0, 1.0)),
+        ] {
+            let end = origin + transform.transform_vector3(axis).normalize_or_zero() * size;
+            self.line(origin, end, color);
+        }
+    }
+
+    /// 在世界坐标处绘制一行文本，文本以固定的屏幕大小绘制在最上层，只支持 ASCII 字符
+    pub fn text3d(&self, position: Vec3, text: &str, color: Vec4) {
+        if !DebugDraw::is_enabled() {
+            return;
+        }
+        let mut queue = QUEUE.lock().unwrap();
+        if queue.labels.len() < MAX_LABELS {
+            let label = Label {
+                position,
+                text: text.to_string(),
+                color,
+                expires: self.expires(),
+            };
+            queue.labels.push(label);
+        }
+    }
+
+    fn lines(&self, segments: &[(Vec3, Vec3)], color: Vec4) {
+        if !DebugDraw::is_enabled() {
+            return;
+        }
+        let expires = self.expires();
+        let mut queue = QUEUE.lock().unwrap();
+        let room = MAX_LINES.saturating_sub(queue.lines.len());
+        queue.lines.extend(segments.iter().take(room).map(|&(a, b)| Line {
+            a,
+            b,
+            color,
+            depth_test: self.depth_test,
+            expires,
+        }));
+    }
+
+    fn expires(&self) -> Option<Instant> {
+        (self.duration > 0.0).then(|| Instant::now() + Duration::from_secs_f32(self.duration))
+    }
+}
+
+/// 即时模式调试绘制
+///
+/// 在任意线程中提交线段、包围盒、球体、坐标轴与文本，图元被缓存在全局队列中，
+/// 由渲染线程中的 [`DebugDrawRenderer`] 每帧统一绘制一次，用于可视化物理形状、射线检测与包围盒。
+/// 默认样式开启深度测试且只显示一帧，其他样式见 [`DebugPen`]
+///
+/// # 示例
+///
+/// ```no_run
+/// use gle::*;
+/// use gle::math::*;
+///
+/// AppBuilder::new(1280, 720, "Game")
+///     .add_plugin(DebugDrawPlugin)
+///     .set_event_loop(|| {
+///         DebugDraw::aabb(Vec3::splat(-1.0), Vec3::splat(1.0), Vec4::new(0.0, 1.0, 0.0, 1.0));
+///         DebugDraw::sphere(Vec3::new(3.0, 0.0, 0.0), 0.5, Vec4::ONE);
+///         DebugDraw::axis(&Mat4::IDENTITY, 2.0);
+///         DebugDraw::text3d(Vec3::new(0.0, 1.5, 0.0), "origin", Vec4::ONE);
+///     })
+///     .build()
+///     .exec();
+/// ```
+///
+/// # 注解
+///
+/// 每帧只显示的图元在下一次提交时被消费，事件循环与渲染循环帧率不同时可能闪烁或被跳过，
+/// 此时可为图元设置略长于一帧的显示时间
+pub struct DebugDraw;
+
+impl DebugDraw {
+    /// 获取默认样式，可在其上修改深度测试与显示时间
+    pub fn pen() -> DebugPen {
+        DebugPen::default()
+    }
+
+    /// 以默认样式绘制线段
+    pub fn line(a: Vec3, b: Vec3, color: Vec4) {
+        Self::pen().line(a, b, color);
+    }
+
+    /// 以默认样式绘制射线
+    pub fn ray(origin: Vec3, direction: Vec3, color: Vec4) {
+        Self::pen().ray(origin, direction, color);
+    }
+
+    /// 以默认样式绘制轴对齐包围盒
+    pub fn aabb(min: Vec3, max: Vec3, color: Vec4) {
+        Self::pen().aabb(min, max, color);
+    }
+
+    /// 以默认样式绘制球体
+    pub fn sphere(center: Vec3, radius: f32, color: Vec4) {
+        Self::pen().sphere(center, radius, color);
+    }
+
+    /// 以默认样式绘制坐标轴
+    pub fn axis(transform: &Mat4, size: f32) {
+        Self::pen().axis(transform, size);
+    }
+
+    /// 以默认样式绘制文本
+    pub fn text3d(position: Vec3, text: &str, color: Vec4) {
+        Self::pen().text3d(position, text, color);
+    }
+
+    /// 调试绘制是否开启
+    pub fn is_enabled() -> bool {
+        ENABLED.load(Ordering::Relaxed)
+    }
+
+    /// 开启或关闭调试绘制，关闭时提交的图元被忽略
+    pub fn set_enabled(enabled: bool) {
+        ENABLED.store(enabled, Ordering::Relaxed);
+    }
+
+    /// 清空队列中的所有图元，包括尚未到期的图元
+    pub fn clear() {
+        let mut queue = QUEUE.lock().unwrap();
+        queue.lines.clear();
+        queue.labels.clear();
+    }
+}
+
+/// 取出本帧要绘制的图元，并移除只显示一帧或已到期的图元
+fn take_frame() -> (Vec<Line>, Vec<Label>) {
+    let now = Instant::now();
+    let mut queue = QUEUE.lock().unwrap();
+    let alive = |expires: Option<Instant>| expires.is_some_and(|t| t > now);
+    let lines = queue.lines.clone();
+    let labels = queue.labels.clone();
+    queue.lines.retain(|l| alive(l.expires));
+    queue.labels.retain(|l| alive(l.expires));
+    (lines, labels)
+}
+
+/// 调试绘制渲染器
+///
+/// 每帧调用一次 [`DebugDrawRenderer::flush`] 绘制 [`DebugDraw`] 队列中的图元
+///
+/// # 注解
+///
+/// 该类型只能在渲染线程中创建、使用与释放
+pub struct DebugDrawRenderer {
+    program: Program,
+    vao: u32,
+    stream: StreamingBuffer,
+    vertices: Vec<f32>,
+    text: TextRenderer,
+    font: Option<SdfFont>,
+    font_failed: bool,
+}
+
+impl DebugDrawRenderer {
+    /// 创建调试绘制渲染器，文本在首次需要时使用系统自带的等宽字体
+    ///
+    /// # 返回值
+    /// 成功时返回渲染器，内置着色器编译失败时返回错误
+    pub fn new() -> Result<Self> {
+        let program = Program::new(VS, FS)?;
+        let mut vao = 0;
+        unsafe {
+            gl::GenVertexArrays(1, &mut vao);
+            gl::BindVertexArray(vao);
+            gl::EnableVertexAttribArray(0);
+            gl::EnableVertexAttribArray(1);
+            gl::BindVertexArray(0);
+        }
+        GlObjects::track(GlObjectKind::VertexArray, vao);
+        Ok(Self {
+            program,
+            vao,
+            stream: StreamingBuffer::new(STREAM_SEGMENT_SIZE),
+            vertices: Vec::new(),
+            text: TextRenderer::new()?,
+            font: None,
+            font_failed: false,
+        })
+    }
+
+    /// 以指定字体创建调试绘制渲染器
+    ///
+    /// # 参数
+    /// + `font` - 至少包含 ASCII 可打印字符的字体
+    pub fn with_font(font: SdfFont) -> Result<Self> {
+        let mut renderer = Self::new()?;
+        renderer.font = Some(font);
+        Ok(renderer)
+    }
+
+    /// 绘制队列中的图元，并移除只显示一帧或已到期的图元
+    ///
+    /// # 参数
+    /// + `view_projection` - 相机的视图投影矩阵
+    ///
+    /// # 注解
+    ///
+    /// 应在场景绘制完成之后调用，使开启深度测试的线段被场景正确遮挡
+    pub fn flush(&mut self, view_projection: &Mat4) {
+        let (lines, labels) = take_frame();
+        let depth_test = unsafe { gl::IsEnabled(gl::DEPTH_TEST) == gl::TRUE };
+        self.program.bind();
+        self.program.set("uViewProjection", view_projection);
+        unsafe {
+            gl::Enable(gl::BLEND);
+            gl::BlendFunc(gl::SRC_ALPHA, gl::ONE_MINUS_SRC_ALPHA);
+        }
+        for depth in [true, false] {
+            self.vertices.clear();
+            for line in lines.iter().filter(|l| l.depth_test == depth) {
+                for p in [line.a, line.b] {
+                    self.vertices.extend_from_slice(&p.to_array());
+                    self.vertices.extend_from_slice(&line.color.to_array());
+                }
+            }
+            if self.vertices.is_empty() {
+                continue;
+            }
+            let base = self.stream.write(&self.vertices);
+            let stride = VERTEX_FLOATS * std::mem::size_of::<f32>();
+            unsafe {
+                if depth {
+                    gl::Enable(gl::DEPTH_TEST);
+                } else {
+                    gl::Disable(gl::DEPTH_TEST);
+                }
+                gl::BindVertexArray(self.vao);
+                gl::BindBuffer(gl::ARRAY_BUFFER, self.stream.id());
+                for (index, (size, offset)) in [(3, 0), (4, 3)].into_iter().enumerate() {
+                    gl::VertexAttribPointer(
+                        index as u32,
+                        size,
+                        gl::FLOAT,
+                        gl::FALSE,
+                        stride as i32,
+                        (base + offset * std::mem::size_of::<f32>()) as *const _,
+                    );
+                }
+                gl::DrawArrays(gl::LINES, 0, (self.vertices.len() / VERTEX_FLOATS) as i32);
+                gl::BindVertexArray(0);
+            }
+            RenderStats::record_draw(0);
+        }
+        unsafe {
+            gl::Disable(gl::BLEND);
+            if depth_test {
+                gl::Enable(gl::DEPTH_TEST);
+            } else {
+                gl::Disable(gl::DEPTH_TEST);
+            }
+        }
+        if !labels.is_empty() {
+            self.draw_labels(&labels, view_projection);
+        }
+    }
+
+    fn draw_labels(&mut self, labels: &[Label], view_projection: &Mat4) {
+        if self.font.is_none() && !self.font_failed {
+            match system_font() {
+                Ok(font) => self.font = Some(font),
+                Err(e) => {
+                    error!(Self, "无法加载调试文本字体: {}", e);
+                    self.font_failed = true;
+                }
+            }
+        }
+        let Some(font) = &self.font else {
+            return;
+        };
+        let (w, h) = App::window_size();
+        let window = Vec2::new(w as f32, h as f32);
+        let (content_scale, _) = App::content_scale();
+        let style = TextStyle {
+            size: TEXT_SIZE * content_scale,
+            outline_width: content_scale,
+            ..Default::default()
+        };
+        self.text.begin(&orthographic(0.0, window.x, 0.0, window.y, -1.0, 1.0));
+        for label in labels {
+            let clip = *view_projection * label.position.extend(1.0);
+            if clip.w <= 0.0 {
+                continue;
+            }
+            let ndc = clip.truncate() / clip.w;
+            if ndc.x.abs() > 1.0 || ndc.y.abs() > 1.0 {
+                continue;
+            }
+            let screen = (Vec2::new(ndc.x, ndc.y) + 1.0) * 0.5 * window;
+            let width = font.data().measure(&label.text, style.size).x;
+            let position = screen - Vec2::new(width * 0.5, 0.0);
+            let style = TextStyle {
+                color: label.color,
+                ..style
+            };
+            self.text.draw(font, &label.text, position, &style);
+        }
+        self.text.end();
+    }
+}
+
+impl Drop for DebugDrawRenderer {
+    fn drop(&mut self) {
+        GlObjects::untrack(GlObjectKind::VertexArray, self.vao);
+        unsafe {
+            gl::DeleteVertexArrays(1, &self.vao);
+        }
+    }
+}
+
+/// 调试绘制插件
+///
+/// 在渲染线程中创建 [`DebugDrawRenderer`]，并在每帧渲染循环函数之后以主相机([`Camera::main`])
+/// 绘制 [`DebugDraw`] 队列中的图元。没有主相机时图元被丢弃
+pub struct DebugDrawPlugin;
+
+impl EnginePlugin for DebugDrawPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        let mut renderer = None;
+        app.add_system(Stage::PostRender, move || {
+            let renderer = renderer.get_or_insert_with(|| {
+                DebugDrawRenderer::new()
+                    .inspect_err(|e| {
+                        error!("DebugDrawPlugin", "无法创建调试绘制渲染器: {}", e);
+                    })
+                    .ok()
+            });
+            match (renderer, Camera::main()) {
+                (Some(renderer), Some(camera)) => renderer.flush(&camera.view_projection()),
+                _ => {
+                    take_frame();
+                }
+            }
+        });
+    }
+}
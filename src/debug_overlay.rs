@@ -0,0 +1,351 @@
+use std::collections::VecDeque;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+use lazy_static::lazy_static;
+
+use crate::error::{Error, Result};
+use crate::math::*;
+use crate::{
+    error, App, AppBuilder, EnginePlugin, Input, Key, Level, Log, RenderStats, SdfFont,
+    SdfFontBaker, Sprite, SpriteBatch, Stage, TextRenderer, TextStyle, Texture2D,
+};
+
+/// 帧时间曲线保存的帧数
+const HISTORY: usize = 240;
+/// 叠加层的宽度，单位为逻辑像素
+const WIDTH: f32 = 360.0;
+/// 帧时间曲线的高度，单位为逻辑像素
+const GRAPH_HEIGHT: f32 = 80.0;
+/// 字号，单位为逻辑像素
+const TEXT_SIZE: f32 = 16.0;
+/// 显示的最近警告与错误日志的条数
+const LOG_LINES: usize = 4;
+/// 日志行的最大字符数，超出部分被截断；叠加层字体只包含 ASCII 字符，其余字符显示为`?`
+const LOG_CHARS: usize = 40;
+/// 依次尝试的系统等宽字体
+const SYSTEM_FONTS: [&str; 4] = ["consola.ttf", "cour.ttf", "lucon.ttf", "arial.ttf"];
+
+static VISIBLE: AtomicBool = AtomicBool::new(false);
+
+lazy_static! {
+    static ref TOGGLE_KEY: Mutex<Option<Key>> = Mutex::new(Some(Key::F3));
+}
+
+/// 调试叠加层
+///
+/// 在窗口左上角显示事件循环与渲染循环的帧率和帧时间曲线、最近一帧的绘制调用与三角形数量
+/// (见 [`RenderStats`])、按资源类型统计的显存估计值与驱动报告的显存用量(见 [`App::gpu_memory_stats`])
+/// 以及最近的警告与错误日志(见 [`Log::recent`])。默认隐藏，按下切换键(默认为`F3`)显示或隐藏
+///
+/// # 示例
+///
+/// ```no_run
+/// use gle::*;
+///
+/// let mut overlay = None;
+/// AppBuilder::new(1280, 720, "Game")
+///     .set_render_loop(move || {
+///         // ... 绘制场景
+///         overlay.get_or_insert_with(|| DebugOverlay::new().unwrap()).draw();
+///     })
+///     .build()
+///     .exec();
+/// ```
+///
+/// # 注解
+///
+/// 该类型只能在渲染线程中创建、使用与释放。统计数据包含叠加层自身上一帧的绘制
+pub struct DebugOverlay {
+    /// 叠加层的缩放倍数，与窗口的内容缩放比例相乘
+    pub scale: f32,
+    batch: SpriteBatch,
+    text: TextRenderer,
+    font: SdfFont,
+    white: Texture2D,
+    event_history: VecDeque<f32>,
+    render_history: VecDeque<f32>,
+}
+
+impl DebugOverlay {
+    /// 创建调试叠加层，使用系统自带的等宽字体
+    ///
+    /// # 返回值
+    /// 成功时返回叠加层，找不到系统字体或着色器编译失败时返回错误
+    pub fn new() -> Result<Self> {
+        Self::with_font(system_font()?)
+    }
+
+    /// 以指定字体创建调试叠加层
+    ///
+    /// # 参数
+    /// + `font` - 至少包含 ASCII 可打印字符的字体
+    pub fn with_font(font: SdfFont) -> Result<Self> {
+        Ok(Self {
+            scale: 1.0,
+            batch: SpriteBatch::new()?,
+            text: TextRenderer::new()?,
+            font,
+            white: Texture2D::from_rgba8(1, 1, &[255; 4], false),
+            event_history: VecDeque::with_capacity(HISTORY),
+            render_history: VecDeque::with_capacity(HISTORY),
+        })
+    }
+
+    /// 叠加层是否可见
+    pub fn is_visible() -> bool {
+        VISIBLE.load(Ordering::Relaxed)
+    }
+
+    /// 设置叠加层是否可见
+    pub fn set_visible(visible: bool) {
+        VISIBLE.store(visible, Ordering::Relaxed);
+    }
+
+    /// 切换叠加层的可见性
+    pub fn toggle() {
+        VISIBLE.fetch_xor(true, Ordering::Relaxed);
+    }
+
+    /// 设置切换可见性的按键
+    ///
+    /// # 参数
+    /// + `key` - 按键，为`None`时只能通过 [`DebugOverlay::set_visible`] 切换
+    pub fn set_toggle_key(key: Option<Key>) {
+        *TOGGLE_KEY.lock().unwrap() = key;
+    }
+
+    pub(crate) fn _poll_toggle() {
+        let key = *TOGGLE_KEY.lock().unwrap();
+        if key.is_some_and(Input::key_pressed) {
+            Self::toggle();
+        }
+    }
+
+    /// 记录本帧的帧时间，可见时绘制叠加层
+    ///
+    /// # 注解
+    ///
+    /// 应在渲染循环的最后调用，使叠加层绘制在场景之上。隐藏时同样需要每帧调用以记录帧时间曲线
+    pub fn draw(&mut self) {
+        for (history, ms) in [
+            (&mut self.event_history, App::event_ms()),
+            (&mut self.render_history, App::render_ms()),
+        ] {
+            if history.len() == HISTORY {
+                history.pop_front();
+            }
+            history.push_back(ms as f32);
+        }
+        if !Self::is_visible() {
+            return;
+        }
+
+        let (w, h) = App::window_size();
+        let (content_scale, _) = App::content_scale();
+        let scale = self.scale * content_scale;
+        let projection = orthographic(0.0, w as f32, 0.0, h as f32, -1.0, 1.0);
+        let margin = 8.0 * scale;
+        let line = self.font.data().line_height(TEXT_SIZE * scale);
+        let width = WIDTH * scale;
+        let graph_height = GRAPH_HEIGHT * scale;
+        let memory = App::gpu_memory_stats();
+        let lines = [
+            format!(
+                "Event  {:>7.1} fps {:>7.2} ms",
+                App::event_fps(),
+                App::event_ms()
+            ),
+            format!(
+                "Render {:>7.1} fps {:>7.2} ms",
+                App::render_fps(),
+                App::render_ms()
+            ),
+            format!(
+                "Draws {:>6}   Tris {:>8}",
+                RenderStats::draw_calls(),
+                format_count(RenderStats::triangles())
+            ),
+            format!(
+                "Tex {:>10}  Buf {:>10}",
+                format_bytes(memory.textures),
+                format_bytes(memory.buffers)
+            ),
+            format!(
+                "RT  {:>10}  Str {:>10}",
+                format_bytes(memory.render_targets),
+                format_bytes(memory.streaming)
+            ),
+            match (memory.used(), memory.total, memory.available) {
+                (Some(used), Some(total), _) => {
+                    format!("VRAM {} / {}", format_bytes(used), format_bytes(total))
+                }
+                (_, _, Some(available)) => format!("VRAM {} free", format_bytes(available)),
+                _ => format!("VRAM ~{} (estimate)", format_bytes(memory.estimate())),
+            },
+        ];
+        let logs: Vec<(String, Vec4)> = Log::recent(LOG_LINES, Level::Warn)
+            .into_iter()
+            .map(|entry| {
+                let color = match entry.level {
+                    Level::Error => Vec4::new(1.0, 0.4, 0.4, 1.0),
+                    _ => Vec4::new(1.0, 0.85, 0.3, 1.0),
+                };
+                let text = format!("{} {}", entry.time.format("%H:%M:%S"), entry.message);
+                let text: String = text
+                    .chars()
+                    .map(|c| if c == ' ' || c.is_ascii_graphic() { c } else { '?' })
+                    .take(LOG_CHARS)
+                    .collect();
+                (text, color)
+            })
+            .collect();
+        let rows = lines.len() + logs.len();
+        let height = margin * 3.0 + line * rows as f32 + graph_height;
+        let top = h as f32 - margin;
+        let left = margin;
+
+        // 背景与帧时间曲线
+        self.batch.begin(&projection);
+        let white = self.white.id();
+        let mut rect = |min: Vec2, size: Vec2, color: Vec4| {
+            let sprite = Sprite {
+                color,
+                ..Sprite::new(min, min + size)
+            };
+            self.batch.draw(white, &sprite);
+        };
+        rect(
+            Vec2::new(left, top - height),
+            Vec2::new(width, height),
+            Vec4::new(0.0, 0.0, 0.0, 0.7),
+        );
+        let graph_min = Vec2::new(left + margin, top - height + margin);
+        let graph_size = Vec2::new(width - 2.0 * margin, graph_height);
+        let peak = self
+            .event_history
+            .iter()
+            .chain(&self.render_history)
+            .fold(1000.0_f32 / 30.0, |a, b| a.max(*b));
+        let bar = graph_size.x / HISTORY as f32;
+        let colors = [
+            (&self.render_history, Vec4::new(0.3, 0.85, 0.4, 0.9)),
+            (&self.event_history, Vec4::new(1.0, 0.6, 0.2, 0.9)),
+        ];
+        for (history, color) in colors {
+            let offset = HISTORY - history.len();
+            for (i, ms) in history.iter().enumerate() {
+                let thickness = scale.max(1.0);
+                let y = ((ms / peak).min(1.0) * graph_size.y).max(thickness) - thickness;
+                let x = graph_min.x + (offset + i) as f32 * bar;
+                rect(Vec2::new(x, graph_min.y + y), Vec2::new(bar, thickness), color);
+            }
+        }
+        for target in [1000.0 / 60.0, 1000.0 / 30.0] {
+            let y = graph_min.y + target / peak * graph_size.y;
+            let color = Vec4::new(1.0, 1.0, 1.0, 0.25);
+            rect(Vec2::new(graph_min.x, y), Vec2::new(graph_size.x, 1.0), color);
+        }
+        self.batch.end();
+
+        self.text.begin(&projection);
+        let style = TextStyle {
+            size: TEXT_SIZE * scale,
+            ..Default::default()
+        };
+        let (ascent, _) = self.font.data().vertical_metrics(style.size);
+        let mut baseline = top - margin - ascent;
+        for text in &lines {
+            self.text.draw(&self.font, text, Vec2::new(left + margin, baseline), &style);
+            baseline -= line;
+        }
+        for (text, color) in &logs {
+            let style = TextStyle {
+                color: *color,
+                ..style
+            };
+            self.text.draw(&self.font, text, Vec2::new(left + margin, baseline), &style);
+            baseline -= line;
+        }
+        self.text.end();
+    }
+}
+
+/// 调试叠加层插件
+///
+/// 在渲染线程中创建 [`DebugOverlay`]，并在每帧渲染循环函数之后绘制，使叠加层显示在场景之上
+///
+/// # 示例
+///
+/// ```no_run
+/// use gle::*;
+///
+/// AppBuilder::new(1280, 720, "Game")
+///     .add_plugin(DebugOverlayPlugin)
+///     .build()
+///     .exec();
+/// ```
+pub struct DebugOverlayPlugin;
+
+impl EnginePlugin for DebugOverlayPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        let mut overlay = None;
+        app.add_system(Stage::PostRender, move || {
+            let overlay = overlay.get_or_insert_with(|| {
+                DebugOverlay::new()
+                    .inspect_err(|e| {
+                        error!("DebugOverlayPlugin", "无法创建调试叠加层: {}", e);
+                    })
+                    .ok()
+            });
+            if let Some(overlay) = overlay {
+                overlay.draw();
+            }
+        });
+    }
+}
+
+/// 烘焙系统自带等宽字体的 ASCII 可打印字符
+///
+/// # 注解
+///
+/// 只能在渲染线程中调用
+pub(crate) fn system_font() -> Result<SdfFont> {
+    let windir = std::env::var("WINDIR").unwrap_or_else(|_| "C:\\Windows".to_string());
+    let font = SYSTEM_FONTS
+        .iter()
+        .map(|name| PathBuf::from(&windir).join("Fonts").join(name))
+        .find_map(|path| std::fs::read(path).ok())
+        .ok_or_else(|| Error::Parse("未找到可用的系统字体".to_string()))?;
+    let baker = SdfFontBaker {
+        size: 32.0,
+        spread: 4.0,
+        ..Default::default()
+    };
+    Ok(baker.bake(&font, ' '..='~')?.upload())
+}
+
+/// 以 K、M 为单位格式化数量
+fn format_count(n: u64) -> String {
+    if n < 1_000 {
+        n.to_string()
+    } else if n < 1_000_000 {
+        format!("{:.1}K", n as f64 / 1e3)
+    } else {
+        format!("{:.2}M", n as f64 / 1e6)
+    }
+}
+
+/// 以 KiB、MiB、GiB 为单位格式化字节数
+fn format_bytes(bytes: u64) -> String {
+    const KIB: f64 = 1024.0;
+    let b = bytes as f64;
+    if b < KIB * KIB {
+        format!("{:.1} KiB", b / KIB)
+    } else if b < KIB * KIB * KIB {
+        format!("{:.1} MiB", b / (KIB * KIB))
+    } else {
+        format!("{:.2} GiB", b / (KIB * KIB * KIB))
+    }
+}
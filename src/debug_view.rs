@@ -0,0 +1,174 @@
+use std::sync::Mutex;
+
+use lazy_static::lazy_static;
+
+use crate::error::Result;
+use crate::{Program, CLIP_PLANES_GLSL};
+
+lazy_static! {
+    static ref DEBUG_VIEW: Mutex<DebugView> = Mutex::new(DebugView::Shaded);
+}
+
+const VS_HEAD: &str = r#"
+#version 330 core
+layout (location = 0) in vec3 aPosition;
+layout (location = 1) in vec3 aNormal;
+layout (location = 2) in vec2 aUV;
+"#;
+
+const VS_MAIN: &str = r#"
+uniform mat4 uViewProj;
+uniform mat4 uModel;
+
+out vec3 vNormal;
+out vec2 vUV;
+
+void main()
+{
+    vNormal = mat3(transpose(inverse(uModel))) * aNormal;
+    vUV = aUV;
+    vec4 world = uModel * vec4(aPosition, 1.0);
+    applyClipPlanes(world.xyz);
+    gl_Position = uViewProj * world;
+}
+"#;
+
+const FS: &str = r#"
+#version 330 core
+in vec3 vNormal;
+in vec2 vUV;
+out vec4 FragColor;
+
+uniform sampler2D uTexture;
+uniform int uMode;
+
+const vec3 MIP_COLORS[6] = vec3[](
+    vec3(0.0, 0.0, 1.0),
+    vec3(0.0, 0.5, 1.0),
+    vec3(0.0, 1.0, 0.0),
+    vec3(1.0, 1.0, 0.0),
+    vec3(1.0, 0.5, 0.0),
+    vec3(1.0, 0.0, 0.0)
+);
+
+void main()
+{
+    if (uMode == 0) {
+        FragColor = vec4(normalize(vNormal) * 0.5 + 0.5, 1.0);
+    } else if (uMode == 1) {
+        // 棋盘格便于观察 UV 的拉伸与接缝
+        vec2 cell = floor(vUV * 8.0);
+        float checker = mod(cell.x + cell.y, 2.0) * 0.25 + 0.75;
+        FragColor = vec4(fract(vUV) * checker, 0.0, 1.0);
+    } else if (uMode == 2) {
+        // 以加法混合叠加，约 10 层达到红色、20 层达到黄色
+        FragColor = vec4(0.1, 0.05, 0.025, 1.0);
+    } else {
+        vec2 texel = vUV * vec2(textureSize(uTexture, 0));
+        float rate = max(length(dFdx(texel)), length(dFdy(texel)));
+        float lod = clamp(log2(max(rate, 1e-6)), 0.0, 5.0);
+        int level = int(lod);
+        vec3 color = mix(MIP_COLORS[level], MIP_COLORS[min(level + 1, 5)], fract(lod));
+        FragColor = vec4(color, 1.0);
+    }
+}
+"#;
+
+/// 调试视图
+///
+/// 以诊断用的方式绘制 [`RenderQueue`](crate::RenderQueue) 中的物体，用于检查模型法线、UV 展开、
+/// 过度绘制与纹理分辨率等内容问题。全局视图通过 [`DebugView::set`] 在运行时切换，
+/// 单个材质可通过 [`Material::debug_view`](crate::Material::debug_view) 覆盖全局视图
+///
+/// # 示例
+///
+/// ```no_run
+/// use gle::*;
+///
+/// // 按 F4 依次切换调试视图
+/// if Input::key_pressed(Key::F4) {
+///     DebugView::set(DebugView::current().next());
+/// }
+/// ```
+///
+/// # 注解
+///
+/// 除 [`DebugView::Shaded`] 与 [`DebugView::Wireframe`] 外，其余视图以内置着色器替换物体的着色器程序，
+/// 不调用 [`DrawItem::uniforms`](crate::DrawItem::uniforms)。全局视图不为 [`DebugView::Shaded`] 时，
+/// 渲染队列不使用顺序无关透明度目标
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum DebugView {
+    /// 正常着色
+    #[default]
+    Shaded,
+    /// 以物体自身的着色器绘制线框
+    Wireframe,
+    /// 世界空间法线，映射到`[0, 1]`
+    Normals,
+    /// 纹理坐标，叠加棋盘格
+    Uvs,
+    /// 过度绘制热度图，关闭深度测试并以加法混合叠加，越亮表示绘制次数越多
+    Overdraw,
+    /// 主纹理的 mip 级别，蓝色为0级，红色为5级及以上
+    MipLevel,
+}
+
+impl DebugView {
+    /// 所有调试视图，按 [`DebugView::next`] 的切换顺序排列
+    pub const ALL: [DebugView; 6] = [
+        DebugView::Shaded,
+        DebugView::Wireframe,
+        DebugView::Normals,
+        DebugView::Uvs,
+        DebugView::Overdraw,
+        DebugView::MipLevel,
+    ];
+
+    /// 获取全局调试视图
+    pub fn current() -> DebugView {
+        *DEBUG_VIEW.lock().unwrap()
+    }
+
+    /// 设置全局调试视图，可在任意线程中调用，从下一次执行渲染队列起生效
+    pub fn set(view: DebugView) {
+        *DEBUG_VIEW.lock().unwrap() = view;
+    }
+
+    /// 获取切换顺序中的下一个视图
+    pub fn next(self) -> DebugView {
+        let index = Self::ALL.iter().position(|v| *v == self).unwrap_or(0);
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+
+    /// 获取视图名称
+    pub fn name(self) -> &'static str {
+        match self {
+            DebugView::Shaded => "shaded",
+            DebugView::Wireframe => "wireframe",
+            DebugView::Normals => "normals",
+            DebugView::Uvs => "uvs",
+            DebugView::Overdraw => "overdraw",
+            DebugView::MipLevel => "mip level",
+        }
+    }
+
+    /// 判断该视图是否以内置着色器替换物体的着色器程序
+    pub fn replaces_program(self) -> bool {
+        !matches!(self, DebugView::Shaded | DebugView::Wireframe)
+    }
+
+    /// 内置着色器中的`uMode`取值
+    pub(crate) fn mode(self) -> i32 {
+        match self {
+            DebugView::Normals => 0,
+            DebugView::Uvs => 1,
+            DebugView::Overdraw => 2,
+            _ => 3,
+        }
+    }
+}
+
+/// 创建调试视图的内置着色器程序
+pub(crate) fn debug_view_program() -> Result<Program> {
+    Program::new(&[VS_HEAD, CLIP_PLANES_GLSL, VS_MAIN].concat(), FS)
+}
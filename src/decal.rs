@@ -0,0 +1,339 @@
+use crate::error::Result;
+use crate::math::*;
+use crate::{Camera, GlObjectKind, GlObjects, Program, RenderStats, Renderer, Scene, StencilState};
+
+/// 模板缓冲中标记"不接收贴花"的位
+///
+/// [`RenderQueue`](crate::RenderQueue) 绘制不透明物体时，为 [`DrawItem::receive_decals`](crate::DrawItem::receive_decals)
+/// 为`false`的表面写入该位，其余表面清除该位；[`DecalRenderer`] 只在该位为 0 的像素上绘制贴花。
+/// 渲染目标需使用带模板的深度附件([`AttachmentFormat::Depth24Stencil8`](crate::AttachmentFormat::Depth24Stencil8))，
+/// 否则所有表面都接收贴花
+pub const DECAL_STENCIL_BIT: u32 = 0x80;
+
+const VS: &str = r#"
+#version 330 core
+layout (location = 0) in vec3 aPos;
+
+uniform mat4 uViewProj;
+uniform mat4 uModel;
+
+void main()
+{
+    gl_Position = uViewProj * uModel * vec4(aPos, 1.0);
+}
+"#;
+
+const FS: &str = r#"
+#version 330 core
+out vec4 FragColor;
+
+uniform sampler2D uDepth;
+uniform sampler2D uTexture;
+uniform bool uTextured;
+uniform mat4 uInvViewProj;
+uniform mat4 uInvModel;
+uniform vec3 uProjectDir;
+uniform vec4 uColor;
+uniform vec4 uUvRect;
+uniform float uNormalThreshold;
+
+void main()
+{
+    vec2 screen = gl_FragCoord.xy / vec2(textureSize(uDepth, 0));
+    float depth = texture(uDepth, screen).r;
+    vec4 world = uInvViewProj * vec4(vec3(screen, depth) * 2.0 - 1.0, 1.0);
+    world /= world.w;
+    // 由重建的位置求表面法线，需在任何 discard 之前计算导数
+    vec3 normal = normalize(cross(dFdx(world.xyz), dFdy(world.xyz)));
+
+    vec3 local = (uInvModel * world).xyz;
+    if (depth >= 1.0 || any(greaterThan(abs(local), vec3(0.5)))) {
+        discard;
+    }
+    float facing = dot(normal, -uProjectDir);
+    if (facing < uNormalThreshold) {
+        discard;
+    }
+
+    vec2 uv = uUvRect.xy + (local.xz + 0.5) * uUvRect.zw;
+    vec4 color = uColor;
+    if (uTextured) {
+        color *= texture(uTexture, uv);
+    }
+    // 在掠射角附近与投影盒的上下边缘柔和过渡
+    color.a *= smoothstep(uNormalThreshold, min(uNormalThreshold + 0.2, 1.0), facing);
+    color.a *= 1.0 - smoothstep(0.4, 0.5, abs(local.y));
+    FragColor = color;
+}
+"#;
+
+/// 单位立方体的 36 个顶点
+#[rustfmt::skip]
+const CUBE: [f32; 108] = [
+    -0.5, -0.5, -0.5,  0.5,  0.5, -0.5,  0.5, -0.5, -0.5,
+     0.5,  0.5, -0.5, -0.5, -0.5, -0.5, -0.5,  0.5, -0.5,
+    -0.5, -0.5,  0.5,  0.5, -0.5,  0.5,  0.5,  0.5,  0.5,
+     0.5,  0.5,  0.5, -0.5,  0.5,  0.5, -0.5, -0.5,  0.5,
+    -0.5,  0.5,  0.5, -0.5,  0.5, -0.5, -0.5, -0.5, -0.5,
+    -0.5, -0.5, -0.5, -0.5, -0.5,  0.5, -0.5,  0.5,  0.5,
+     0.5,  0.5,  0.5,  0.5, -0.5, -0.5,  0.5,  0.5, -0.5,
+     0.5, -0.5, -0.5,  0.5,  0.5,  0.5,  0.5, -0.5,  0.5,
+    -0.5, -0.5, -0.5,  0.5, -0.5, -0.5,  0.5, -0.5,  0.5,
+     0.5, -0.5,  0.5, -0.5, -0.5,  0.5, -0.5, -0.5, -0.5,
+    -0.5,  0.5, -0.5,  0.5,  0.5,  0.5,  0.5,  0.5, -0.5,
+     0.5,  0.5,  0.5, -0.5,  0.5, -0.5, -0.5,  0.5,  0.5,
+];
+
+/// 贴花组件
+///
+/// 贴花是沿节点局部`-Y`方向投影的盒子，节点的世界变换决定盒子的位置、朝向与大小
+/// (单位立方体，缩放即为盒子的尺寸)，盒子内的表面按局部`XZ`坐标映射纹理，适用于弹孔、血迹与路面标线等。
+/// 可以作为组件插入 [`Scene`]，由 [`DecalRenderer::draw_scene`] 绘制
+///
+/// # 示例
+///
+/// ```
+/// use gle::{*, math::*};
+///
+/// fn spawn_bullet_hole(scene: &mut Scene, point: Vec3, normal: Vec3, texture: u32) {
+///     let transform = Transform {
+///         translation: point,
+///         rotation: Quat::from_rotation_arc(Vec3::Y, normal),
+///         scale: Vec3::new(0.2, 0.2, 0.2),
+///     };
+///     let hole = scene.spawn("bullet_hole", transform);
+///     scene.insert(hole, Decal {
+///         texture: Some(texture),
+///         lifetime: Some(30.0),
+///         ..Default::default()
+///     });
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Decal {
+    /// 纹理，为`None`时仅使用颜色
+    pub texture: Option<u32>,
+    /// 颜色，与纹理颜色相乘
+    pub color: Vec4,
+    /// 纹理区域，依次为左下角`u`、`v`与宽度、高度，用于图集
+    pub uv_rect: Vec4,
+    /// 绘制顺序，较大的贴花绘制在较小的贴花之上，相同时较新的贴花在上
+    pub order: i32,
+    /// 表面法线与投影反方向夹角余弦的下限，低于该值的表面不接收贴花，避免在侧面上拉伸(默认值为0.2)
+    pub normal_threshold: f32,
+    /// 存在时间，单位为秒，为`None`时永久存在
+    pub lifetime: Option<f32>,
+    /// 到期前淡出的时长，单位为秒(默认值为1)
+    pub fade_duration: f32,
+    /// 已存在的时间，单位为秒，由 [`DecalRenderer::update`] 推进
+    pub age: f32,
+}
+
+impl Default for Decal {
+    fn default() -> Self {
+        Self {
+            texture: None,
+            color: Vec4::ONE,
+            uv_rect: Vec4::new(0.0, 0.0, 1.0, 1.0),
+            order: 0,
+            normal_threshold: 0.2,
+            lifetime: None,
+            fade_duration: 1.0,
+            age: 0.0,
+        }
+    }
+}
+
+impl Decal {
+    /// 计算淡出系数
+    ///
+    /// # 返回值
+    /// 返回`[0, 1]`范围的系数，未进入淡出阶段时为 1，到期时为 0
+    pub fn fade(&self) -> f32 {
+        match self.lifetime {
+            Some(lifetime) if self.fade_duration > 0.0 => {
+                ((lifetime - self.age) / self.fade_duration).clamp(0.0, 1.0)
+            }
+            Some(lifetime) if self.age >= lifetime => 0.0,
+            _ => 1.0,
+        }
+    }
+
+    /// 判断是否已到期
+    pub fn is_expired(&self) -> bool {
+        self.lifetime.is_some_and(|lifetime| self.age >= lifetime)
+    }
+}
+
+/// 贴花渲染器
+///
+/// 以场景深度重建每个像素的世界位置，将其变换到贴花的盒子空间后采样贴花纹理并混合到当前渲染目标
+///
+/// # 示例
+///
+/// ```
+/// use gle::*;
+///
+/// fn render_loop(
+///     decals: &DecalRenderer,
+///     scene: &mut Scene,
+///     camera: &Camera,
+///     depth: u32,
+///     dt: f32,
+/// ) {
+///     DecalRenderer::update(scene, dt);
+///     // ... 绘制不透明物体
+///     decals.draw_scene(scene, camera, depth);
+///     // ... 绘制半透明物体
+/// }
+/// ```
+///
+/// # 注解
+///
+/// 应在不透明物体之后、半透明物体之前绘制。绘制期间关闭深度写入，深度纹理可以是当前渲染目标的深度附件。
+/// 表面是否接收贴花由模板缓冲中的 [`DECAL_STENCIL_BIT`] 决定。
+/// 该类型只能在渲染线程中创建、使用与释放
+pub struct DecalRenderer {
+    program: Program,
+    vao: u32,
+    vbo: u32,
+}
+
+impl DecalRenderer {
+    /// 创建贴花渲染器
+    ///
+    /// # 返回值
+    /// 成功时返回贴花渲染器，内置着色器编译失败时返回错误
+    pub fn new() -> Result<Self> {
+        let program = Program::new(VS, FS)?;
+        let (mut vao, mut vbo) = (0, 0);
+        unsafe {
+            gl::GenVertexArrays(1, &mut vao);
+            gl::GenBuffers(1, &mut vbo);
+            gl::BindVertexArray(vao);
+            gl::BindBuffer(gl::ARRAY_BUFFER, vbo);
+            gl::BufferData(
+                gl::ARRAY_BUFFER,
+                std::mem::size_of_val(&CUBE) as isize,
+                CUBE.as_ptr() as *const _,
+                gl::STATIC_DRAW,
+            );
+            gl::EnableVertexAttribArray(0);
+            gl::VertexAttribPointer(0, 3, gl::FLOAT, gl::FALSE, 0, std::ptr::null());
+            gl::BindVertexArray(0);
+        }
+        GlObjects::track(GlObjectKind::VertexArray, vao);
+        GlObjects::track(GlObjectKind::Buffer, vbo);
+        Ok(Self { program, vao, vbo })
+    }
+
+    /// 推进场景中所有贴花的存在时间，并销毁已到期的贴花节点
+    ///
+    /// # 参数
+    /// + `scene` - 场景
+    /// + `dt` - 距上次更新的时间，单位为秒
+    ///
+    /// # 返回值
+    /// 返回销毁的贴花数量
+    pub fn update(scene: &mut Scene, dt: f32) -> usize {
+        let mut expired = Vec::new();
+        for (entity, decal) in scene.query_mut::<Decal>() {
+            decal.age += dt;
+            if decal.is_expired() {
+                expired.push(entity);
+            }
+        }
+        for &entity in &expired {
+            scene.despawn(entity);
+        }
+        expired.len()
+    }
+
+    /// 绘制场景中所有带有 [`Decal`] 组件的节点
+    ///
+    /// # 参数
+    /// + `scene` - 场景，应已调用 [`Scene::update_world_transforms`]
+    /// + `camera` - 摄像机，应与绘制深度时相同
+    /// + `depth` - 场景深度纹理，大小应与当前视口一致
+    ///
+    /// # 注解
+    ///
+    /// 贴花按 [`Decal::order`] 排序，相同时按存在时间由长到短绘制
+    pub fn draw_scene(&self, scene: &Scene, camera: &Camera, depth: u32) {
+        let mut items: Vec<(Mat4, &Decal)> = scene
+            .query::<Decal>()
+            .filter(|(_, decal)| decal.fade() > 0.0)
+            .filter_map(|(e, decal)| Some((scene.get(e)?.world_matrix(), decal)))
+            .collect();
+        if items.is_empty() {
+            return;
+        }
+        items.sort_by(|a, b| a.1.order.cmp(&b.1.order).then(b.1.age.total_cmp(&a.1.age)));
+
+        let view_proj = camera.view_projection();
+        let program = &self.program;
+        program.bind();
+        program.set("uViewProj", &view_proj);
+        program.set("uInvViewProj", &view_proj.inverse());
+        program.set("uDepth", &0i32);
+        program.set("uTexture", &1i32);
+        unsafe {
+            let cull_face = gl::IsEnabled(gl::CULL_FACE) == gl::TRUE;
+            let depth_test = gl::IsEnabled(gl::DEPTH_TEST) == gl::TRUE;
+            let mut depth_mask = gl::FALSE;
+            gl::GetBooleanv(gl::DEPTH_WRITEMASK, &mut depth_mask);
+            // 只绘制背面且不做深度测试，摄像机位于盒子内部时贴花依然可见
+            gl::Enable(gl::CULL_FACE);
+            gl::CullFace(gl::FRONT);
+            gl::Disable(gl::DEPTH_TEST);
+            gl::DepthMask(gl::FALSE);
+            gl::Enable(gl::BLEND);
+            gl::BlendFunc(gl::SRC_ALPHA, gl::ONE_MINUS_SRC_ALPHA);
+            Renderer::set_stencil(Some(StencilState {
+                read_mask: DECAL_STENCIL_BIT,
+                ..StencilState::equal(0)
+            }));
+            gl::ActiveTexture(gl::TEXTURE0);
+            gl::BindTexture(gl::TEXTURE_2D, depth);
+            gl::BindVertexArray(self.vao);
+
+            for (model, decal) in items {
+                let color = decal.color * Vec4::new(1.0, 1.0, 1.0, decal.fade());
+                program.set("uModel", &model);
+                program.set("uInvModel", &model.inverse());
+                program.set("uProjectDir", &-model.y_axis.truncate().normalize_or_zero());
+                program.set("uColor", &color);
+                program.set("uUvRect", &decal.uv_rect);
+                program.set("uNormalThreshold", &decal.normal_threshold);
+                program.set("uTextured", &decal.texture.is_some());
+                gl::ActiveTexture(gl::TEXTURE1);
+                gl::BindTexture(gl::TEXTURE_2D, decal.texture.unwrap_or(0));
+                gl::DrawArrays(gl::TRIANGLES, 0, 36);
+                RenderStats::record_draw(12);
+            }
+
+            gl::BindVertexArray(0);
+            Renderer::set_stencil(None);
+            gl::Disable(gl::BLEND);
+            gl::DepthMask(depth_mask);
+            if depth_test {
+                gl::Enable(gl::DEPTH_TEST);
+            }
+            gl::CullFace(gl::BACK);
+            if !cull_face {
+                gl::Disable(gl::CULL_FACE);
+            }
+        }
+    }
+}
+
+impl Drop for DecalRenderer {
+    fn drop(&mut self) {
+        GlObjects::untrack(GlObjectKind::VertexArray, self.vao);
+        GlObjects::untrack(GlObjectKind::Buffer, self.vbo);
+        unsafe {
+            gl::DeleteBuffers(1, &self.vbo);
+            gl::DeleteVertexArrays(1, &self.vao);
+        }
+    }
+}
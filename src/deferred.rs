@@ -0,0 +1,228 @@
+use crate::error::Result;
+use crate::{
+    draw_fullscreen_triangle, AttachmentFormat, Camera, Framebuffer, Ibl, Program, FOG_BINDING,
+    FOG_GLSL, FULLSCREEN_VS, LIGHTING_GLSL, LIGHTS_BINDING, PBR_AMBIENT_GLSL, PBR_BRDF_GLSL,
+    SHADOWS_BINDING, SHADOW_TEXTURE_UNIT,
+};
+
+const RESOLVE_FS_HEAD: &str = r#"
+#version 330 core
+in vec2 vUV;
+out vec4 FragColor;
+
+uniform sampler2D gPosition;
+uniform sampler2D gNormal;
+uniform sampler2D gAlbedo;
+uniform sampler2D gMaterial;
+uniform sampler2D uOcclusion;
+uniform bool uHasOcclusion;
+uniform vec3 uCameraPos;
+"#;
+
+const RESOLVE_FS_MAIN: &str = r#"
+void main()
+{
+    vec4 position = texture(gPosition, vUV);
+    if (position.w == 0.0) {
+        discard;
+    }
+    vec3 P = position.xyz;
+    vec4 normal = texture(gNormal, vUV);
+    vec4 albedo = texture(gAlbedo, vUV);
+    vec4 material = texture(gMaterial, vUV);
+    vec3 N = normalize(normal.xyz);
+    float roughness = normal.w;
+    float metallic = albedo.a;
+    float occlusion = material.a;
+    if (uHasOcclusion) {
+        occlusion *= texture(uOcclusion, vUV).r;
+    }
+
+    vec3 V = normalize(uCameraPos - P);
+    vec3 F0 = mix(vec3(0.04), albedo.rgb, metallic);
+    vec3 Lo = evaluateLights(N, V, P, albedo.rgb, metallic, roughness, F0);
+    vec3 ambient = ambientLightAt(P, N, V, albedo.rgb, metallic, roughness, F0) * occlusion;
+    FragColor = vec4(applyFog(ambient + Lo + material.rgb, P, uCameraPos), 1.0);
+}
+"#;
+
+/// G-buffer 颜色附件的格式
+///
+/// 依次为：世界空间位置(w 为 1 表示有几何体)、世界空间法线与粗糙度、基础颜色与金属度、自发光与环境光遮蔽
+pub const GBUFFER_FORMATS: [AttachmentFormat; 4] = [
+    AttachmentFormat::Rgba32F,
+    AttachmentFormat::Rgba16F,
+    AttachmentFormat::Rgba8,
+    AttachmentFormat::Rgba16F,
+];
+
+/// 延迟渲染器
+///
+/// 几何通道使用 [`PbrShaderCache::deferred`](crate::PbrShaderCache::deferred) 构建的材质将表面属性写入 G-buffer，
+/// 光照解析通道对每个像素统一计算光源、阴影与环境光，着色开销与物体数量无关，适用于光源较多的场景。
+/// 可根据 [`Camera::render_path`] 为每个摄像机选择前向或延迟路径
+///
+/// # 示例
+///
+/// ```
+/// use gle::*;
+///
+/// fn render_frame(
+///     deferred: &mut DeferredRenderer,
+///     hdr: &mut HdrPipeline,
+///     lighting: &LightingSystem,
+///     camera: &Camera,
+/// ) {
+///     if camera.render_path == RenderPath::Deferred {
+///         deferred.begin();
+///         // 使用 G-buffer 材质绘制不透明物体……
+///         hdr.begin();
+///         deferred.resolve(camera, None, None, Some(hdr.scene()));
+///         // 以前向方式绘制透明物体……
+///         hdr.end(camera, 1.0 / 60.0);
+///     }
+/// }
+/// ```
+///
+/// # 注解
+///
+/// 解析通道使用当前绑定的光源与阴影缓冲(见 [`LightingSystem`](crate::LightingSystem) 与 [`ShadowSystem`](crate::ShadowSystem))。
+/// 解析结束后 G-buffer 的深度被复制到输出目标，以便继续前向绘制透明物体。
+/// 该类型只能在渲染线程中创建、使用与释放
+pub struct DeferredRenderer {
+    gbuffer: Framebuffer,
+    resolve: Program,
+    resolve_ibl: Program,
+}
+
+impl DeferredRenderer {
+    /// 创建延迟渲染器
+    ///
+    /// # 参数
+    /// + `width` - G-buffer 宽度，应与输出目标一致
+    /// + `height` - G-buffer 高度，应与输出目标一致
+    pub fn new(width: u32, height: u32) -> Result<Self> {
+        let gbuffer = Framebuffer::new(
+            width,
+            height,
+            &GBUFFER_FORMATS,
+            Some(AttachmentFormat::Depth32F),
+        )?;
+        let fs = format!(
+            "{}{}{}{}{}{}",
+            RESOLVE_FS_HEAD,
+            PBR_BRDF_GLSL,
+            LIGHTING_GLSL,
+            FOG_GLSL,
+            PBR_AMBIENT_GLSL,
+            RESOLVE_FS_MAIN
+        );
+        let resolve = Program::new(FULLSCREEN_VS, &fs)?;
+        let resolve_ibl = Program::with_defines(FULLSCREEN_VS, &fs, &["HAS_IBL"])?;
+        for program in [&resolve, &resolve_ibl] {
+            program.set_block_binding("Lights", LIGHTS_BINDING);
+            program.set_block_binding("Shadows", SHADOWS_BINDING);
+            program.set_block_binding("Fog", FOG_BINDING);
+            program.bind();
+            program.set("uShadowMap", &(SHADOW_TEXTURE_UNIT as i32));
+            program.set("gPosition", &0);
+            program.set("gNormal", &1);
+            program.set("gAlbedo", &2);
+            program.set("gMaterial", &3);
+            program.set("uOcclusion", &4);
+        }
+        Ok(Self {
+            gbuffer,
+            resolve,
+            resolve_ibl,
+        })
+    }
+
+    /// 获取 G-buffer，颜色附件格式见 [`GBUFFER_FORMATS`]
+    pub fn gbuffer(&self) -> &Framebuffer {
+        &self.gbuffer
+    }
+
+    /// 修改 G-buffer 大小
+    pub fn resize(&mut self, width: u32, height: u32) -> Result<()> {
+        self.gbuffer.resize(width, height)
+    }
+
+    /// 开始几何通道：绑定 G-buffer 并清除所有附件
+    pub fn begin(&self) {
+        self.gbuffer.bind();
+        unsafe {
+            gl::ClearColor(0.0, 0.0, 0.0, 0.0);
+            gl::Clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT);
+        }
+    }
+
+    /// 执行光照解析通道
+    ///
+    /// # 参数
+    /// + `camera` - 几何通道使用的摄像机
+    /// + `ibl` - 基于图像的环境光，为`None`时使用光照块中的常量环境光
+    /// + `occlusion` - 额外的环境光遮蔽纹理(如 [`Ssao::occlusion_texture`](crate::Ssao::occlusion_texture))，只作用于环境光
+    /// + `target` - 输出目标，为`None`时输出到默认帧缓冲(大小与 G-buffer 相同)
+    ///
+    /// # 注解
+    ///
+    /// 输出目标需包含与 G-buffer 相同格式([`AttachmentFormat::Depth32F`])的深度附件才能复制深度。
+    /// 没有几何体的像素保持输出目标原有内容
+    pub fn resolve(
+        &self,
+        camera: &Camera,
+        ibl: Option<&Ibl>,
+        occlusion: Option<u32>,
+        target: Option<&Framebuffer>,
+    ) {
+        match target {
+            Some(framebuffer) => framebuffer.bind(),
+            None => Framebuffer::bind_default(self.gbuffer.width(), self.gbuffer.height()),
+        }
+        let program = if ibl.is_some() {
+            &self.resolve_ibl
+        } else {
+            &self.resolve
+        };
+        program.bind();
+        for (unit, attachment) in self.gbuffer.colors().iter().enumerate() {
+            attachment.bind(unit as u32);
+        }
+        unsafe {
+            gl::ActiveTexture(gl::TEXTURE4);
+            gl::BindTexture(gl::TEXTURE_2D, occlusion.unwrap_or(0));
+            gl::Disable(gl::DEPTH_TEST);
+            gl::Disable(gl::BLEND);
+        }
+        program.set("uHasOcclusion", &occlusion.is_some());
+        program.set("uCameraPos", &camera.position);
+        if let Some(ibl) = ibl {
+            ibl.bind(program);
+        }
+        draw_fullscreen_triangle();
+
+        let (width, height) = match target {
+            Some(framebuffer) => (framebuffer.width(), framebuffer.height()),
+            None => (self.gbuffer.width(), self.gbuffer.height()),
+        };
+        unsafe {
+            gl::BindFramebuffer(gl::READ_FRAMEBUFFER, self.gbuffer.id());
+            gl::BindFramebuffer(gl::DRAW_FRAMEBUFFER, target.map_or(0, |t| t.id()));
+            gl::BlitFramebuffer(
+                0,
+                0,
+                self.gbuffer.width() as i32,
+                self.gbuffer.height() as i32,
+                0,
+                0,
+                width as i32,
+                height as i32,
+                gl::DEPTH_BUFFER_BIT,
+                gl::NEAREST,
+            );
+            gl::BindFramebuffer(gl::FRAMEBUFFER, target.map_or(0, |t| t.id()));
+            gl::Enable(gl::DEPTH_TEST);
+        }
+    }
+}
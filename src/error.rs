@@ -0,0 +1,51 @@
+//! 错误类型
+
+use std::fmt::{self, Display, Formatter};
+
+/// 引擎错误
+#[derive(Debug)]
+pub enum Error {
+    /// 着色器编译或程序链接失败，包含 OpenGL 输出的日志
+    Shader(String),
+    /// 文件读写失败
+    Io(std::io::Error),
+    /// 资源文件格式错误，包含错误描述
+    Parse(String),
+    /// OpenGL 对象创建失败(如帧缓冲不完整)，包含错误描述
+    Gl(String),
+    /// 渲染图声明错误(如循环依赖)，包含错误描述
+    Graph(String),
+    /// 脚本编译或执行失败，包含错误描述
+    Script(String),
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Shader(log) => write!(f, "着色器错误: {}", log),
+            Error::Io(e) => write!(f, "IO错误: {}", e),
+            Error::Parse(msg) => write!(f, "解析错误: {}", msg),
+            Error::Gl(msg) => write!(f, "OpenGL错误: {}", msg),
+            Error::Graph(msg) => write!(f, "渲染图错误: {}", msg),
+            Error::Script(msg) => write!(f, "脚本错误: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(e: std::io::Error) -> Self {
+        Error::Io(e)
+    }
+}
+
+/// 引擎结果类型
+pub type Result<T> = std::result::Result<T, Error>;
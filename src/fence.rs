@@ -0,0 +1,100 @@
+use std::time::Duration;
+
+use gl::types::GLsync;
+
+/// GPU 栅栏
+///
+/// 对 OpenGL 同步对象的封装，在命令流中插入后，GPU 执行完此前提交的所有命令时变为已触发状态，
+/// 用于判断异步回读、上传与流式写入的数据何时可以安全访问。被释放时自动删除同步对象
+///
+/// # 示例
+///
+/// ```no_run
+/// use gle::*;
+///
+/// fn render_loop(readback: &mut Option<(u32, GpuFence)>) {
+///     if let Some((buffer, fence)) = readback {
+///         if fence.is_signaled() {
+///             // 映射 buffer 读取数据，不会阻塞
+///             *readback = None;
+///         }
+///     }
+/// }
+/// ```
+///
+/// # 注解
+///
+/// 同步对象在共享上下文之间共享，因此栅栏可以在上传线程中插入后交给渲染线程查询。
+/// 除此之外，该类型的所有方法只能在拥有 OpenGL 上下文的线程中调用
+#[derive(Debug)]
+pub struct GpuFence {
+    sync: GLsync,
+}
+
+unsafe impl Send for GpuFence {}
+
+impl GpuFence {
+    /// 在当前命令流中插入栅栏
+    ///
+    /// # 注解
+    ///
+    /// 插入栅栏的线程在此后不再提交命令时(如上传线程)，应调用`gl::Flush`确保栅栏被提交给 GPU，
+    /// 否则其他线程对它的查询可能永远不会触发
+    pub fn insert() -> Self {
+        let sync = unsafe { gl::FenceSync(gl::SYNC_GPU_COMMANDS_COMPLETE, 0) };
+        Self { sync }
+    }
+
+    /// 判断 GPU 是否已执行完栅栏之前的命令，不阻塞
+    pub fn is_signaled(&self) -> bool {
+        let status = unsafe { gl::ClientWaitSync(self.sync, 0, 0) };
+        status == gl::ALREADY_SIGNALED || status == gl::CONDITION_SATISFIED
+    }
+
+    /// 阻塞当前线程直到栅栏触发
+    ///
+    /// # 返回值
+    /// 栅栏触发时返回`true`，等待失败(如上下文丢失)时返回`false`
+    pub fn wait(&self) -> bool {
+        loop {
+            match self.wait_status(Duration::from_secs(1)) {
+                gl::TIMEOUT_EXPIRED => continue,
+                status => return status != gl::WAIT_FAILED,
+            }
+        }
+    }
+
+    /// 阻塞当前线程直到栅栏触发或超时
+    ///
+    /// # 参数
+    /// + `timeout` - 最长等待时间
+    ///
+    /// # 返回值
+    /// 栅栏在超时前触发时返回`true`
+    pub fn wait_timeout(&self, timeout: Duration) -> bool {
+        matches!(
+            self.wait_status(timeout),
+            gl::ALREADY_SIGNALED | gl::CONDITION_SATISFIED
+        )
+    }
+
+    /// 使 GPU 在执行后续命令前等待栅栏触发，不阻塞当前线程
+    ///
+    /// # 注解
+    ///
+    /// 用于在一个上下文中等待另一个共享上下文提交的命令，同一上下文内的命令本就按顺序执行
+    pub fn gpu_wait(&self) {
+        unsafe { gl::WaitSync(self.sync, 0, gl::TIMEOUT_IGNORED) };
+    }
+
+    fn wait_status(&self, timeout: Duration) -> u32 {
+        let nanos = timeout.as_nanos().min(u64::MAX as u128) as u64;
+        unsafe { gl::ClientWaitSync(self.sync, gl::SYNC_FLUSH_COMMANDS_BIT, nanos) }
+    }
+}
+
+impl Drop for GpuFence {
+    fn drop(&mut self) {
+        unsafe { gl::DeleteSync(self.sync) };
+    }
+}
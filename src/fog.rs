@@ -0,0 +1,237 @@
+use crate::math::*;
+use crate::{Scene, UniformBuffer};
+
+/// 雾 uniform 块`Fog`的绑定点
+pub const FOG_BINDING: u32 = 2;
+
+/// 雾 uniform 块及雾效函数，可被自定义着色器通过字符串拼接复用
+///
+/// 提供`vec3 applyFog(vec3 color, vec3 P, vec3 cameraPos)`与`vec3 applySkyFog(vec3 color, vec3 direction, vec3 cameraPos)`。
+/// 使用该代码的着色器程序需将`Fog`块绑定到 [`FOG_BINDING`]
+pub const FOG_GLSL: &str = r#"
+layout (std140) uniform Fog {
+    vec4 uFogColor;   // rgb 颜色，a 为 1 时启用
+    vec4 uFogParams;  // 模式(0 线性、1 指数、2 平方指数)、线性起点、线性终点、密度
+    vec4 uFogHeight;  // 高度衰减率、基准高度、最大不透明度、天空距离
+};
+
+float fogFactor(vec3 P, vec3 cameraPos)
+{
+    if (uFogColor.a < 0.5) {
+        return 0.0;
+    }
+    vec3 ray = P - cameraPos;
+    float dist = length(ray);
+    int mode = int(uFogParams.x);
+    float f;
+    if (mode == 0) {
+        f = clamp((dist - uFogParams.y) / max(uFogParams.z - uFogParams.y, 1e-4), 0.0, 1.0);
+    } else if (mode == 1) {
+        f = 1.0 - exp(-uFogParams.w * dist);
+    } else {
+        float d = uFogParams.w * dist;
+        f = 1.0 - exp(-d * d);
+    }
+    float falloff = uFogHeight.x;
+    if (falloff > 0.0) {
+        // 密度随高度指数衰减，沿视线积分得到平均密度系数
+        float k = falloff * ray.y;
+        float average = exp(-falloff * (cameraPos.y - uFogHeight.y));
+        average *= abs(k) > 1e-4 ? (1.0 - exp(-k)) / k : 1.0;
+        f = 1.0 - pow(1.0 - f, min(average, 1e4));
+    }
+    return min(f, uFogHeight.z);
+}
+
+vec3 applyFog(vec3 color, vec3 P, vec3 cameraPos)
+{
+    return mix(color, uFogColor.rgb, fogFactor(P, cameraPos));
+}
+
+vec3 applySkyFog(vec3 color, vec3 direction, vec3 cameraPos)
+{
+    return mix(color, uFogColor.rgb, fogFactor(cameraPos + normalize(direction) * uFogHeight.w, cameraPos));
+}
+"#;
+
+/// 雾的距离衰减方式
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FogMode {
+    /// 线性雾，在`start`与`end`之间由无雾过渡到完全被雾覆盖
+    Linear {
+        /// 起始距离
+        start: f32,
+        /// 完全覆盖距离
+        end: f32,
+    },
+    /// 指数雾，浓度为`1 - exp(-density * d)`
+    Exponential {
+        /// 密度
+        density: f32,
+    },
+    /// 平方指数雾，浓度为`1 - exp(-(density * d)²)`，近处更清晰
+    ExponentialSquared {
+        /// 密度
+        density: f32,
+    },
+}
+
+/// 雾组件
+///
+/// 附加到场景中任意实体上即可为该场景启用雾效，由 [`FogSystem`] 收集并上传，
+/// 作用于 PBR 材质、延迟光照解析与天空
+///
+/// # 示例
+///
+/// ```
+/// use gle::{*, math::*};
+///
+/// let mut scene = Scene::new();
+/// let environment = scene.spawn("environment", Transform::default());
+/// scene.insert(environment, Fog {
+///     mode: FogMode::Exponential { density: 0.02 },
+///     color: Vec3::new(0.6, 0.7, 0.8),
+///     height_falloff: 0.15,
+///     ..Default::default()
+/// });
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Fog {
+    /// 距离衰减方式
+    pub mode: FogMode,
+    /// 雾的颜色(线性空间)
+    pub color: Vec3,
+    /// 高度衰减率，大于 0 时雾的密度随高度指数降低，为 0 时不随高度变化
+    pub height_falloff: f32,
+    /// 高度雾的基准高度，该高度处密度为设定值
+    pub base_height: f32,
+    /// 最大不透明度，范围`[0, 1]`，小于 1 时远处物体仍可见
+    pub max_opacity: f32,
+    /// 计算天空雾效时使用的距离
+    pub sky_distance: f32,
+    /// 是否启用
+    pub enabled: bool,
+}
+
+impl Default for Fog {
+    fn default() -> Self {
+        Self {
+            mode: FogMode::Exponential { density: 0.01 },
+            color: Vec3::new(0.5, 0.6, 0.7),
+            height_falloff: 0.0,
+            base_height: 0.0,
+            max_opacity: 1.0,
+            sky_distance: 1000.0,
+            enabled: true,
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+struct FogBlock {
+    color: Vec4,
+    params: Vec4,
+    height: Vec4,
+}
+
+impl From<Option<&Fog>> for FogBlock {
+    fn from(fog: Option<&Fog>) -> Self {
+        let Some(fog) = fog.filter(|f| f.enabled) else {
+            return Self::default();
+        };
+        let params = match fog.mode {
+            FogMode::Linear { start, end } => Vec4::new(0.0, start, end, 0.0),
+            FogMode::Exponential { density } => Vec4::new(1.0, 0.0, 0.0, density),
+            FogMode::ExponentialSquared { density } => Vec4::new(2.0, 0.0, 0.0, density),
+        };
+        Self {
+            color: fog.color.extend(1.0),
+            params,
+            height: Vec4::new(
+                fog.height_falloff.max(0.0),
+                fog.base_height,
+                fog.max_opacity.clamp(0.0, 1.0),
+                fog.sky_distance,
+            ),
+        }
+    }
+}
+
+/// 雾效系统
+///
+/// 每帧从场景中查找第一个启用的 [`Fog`] 组件，写入绑定点为 [`FOG_BINDING`] 的 uniform 缓冲；
+/// 场景中没有雾组件时关闭雾效
+///
+/// # 示例
+///
+/// ```
+/// use gle::*;
+///
+/// // 在渲染线程中
+/// let mut fog = FogSystem::new();
+/// fog.update(&scene);
+/// queue.execute(&camera);
+/// ```
+///
+/// # 注解
+///
+/// 使用 [`FOG_GLSL`] 的着色器在未创建雾效系统时读取到的 uniform 块内容未定义，因此即使不需要雾效也应创建该系统。
+/// 该类型只能在渲染线程中创建、使用与释放
+#[derive(Debug)]
+pub struct FogSystem {
+    ubo: UniformBuffer,
+    current: Option<Fog>,
+}
+
+impl FogSystem {
+    /// 创建雾效系统，初始时不启用雾效
+    pub fn new() -> Self {
+        let system = Self {
+            ubo: UniformBuffer::new::<FogBlock>(),
+            current: None,
+        };
+        system.upload();
+        system
+    }
+
+    /// 获取当前生效的雾
+    pub fn current(&self) -> Option<&Fog> {
+        self.current.as_ref()
+    }
+
+    /// 从场景中收集雾组件并更新 uniform 缓冲
+    pub fn update(&mut self, scene: &Scene) {
+        let fog = scene
+            .query::<Fog>()
+            .map(|(_, fog)| *fog)
+            .find(|fog| fog.enabled);
+        self.set(fog);
+    }
+
+    /// 直接设置当前生效的雾，`None`表示关闭雾效
+    pub fn set(&mut self, fog: Option<Fog>) {
+        if self.current != fog {
+            self.current = fog;
+            self.upload();
+        } else {
+            self.bind();
+        }
+    }
+
+    fn upload(&self) {
+        self.ubo.update(&FogBlock::from(self.current.as_ref()));
+        self.bind();
+    }
+
+    /// 将雾 uniform 缓冲重新绑定到 [`FOG_BINDING`]
+    pub fn bind(&self) {
+        self.ubo.bind_base(FOG_BINDING);
+    }
+}
+
+impl Default for FogSystem {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,395 @@
+use crate::error::{Error, Result};
+use crate::{GlObjectKind, GlObjects, RenderStats};
+
+/// 帧缓冲附件格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AttachmentFormat {
+    /// 8位 RGBA
+    Rgba8,
+    /// 8位 sRGB 颜色与线性透明度
+    Srgb8Alpha8,
+    /// 16位浮点 RGBA，用于 HDR 颜色
+    Rgba16F,
+    /// 32位浮点 RGBA
+    Rgba32F,
+    /// 16位浮点 RG，用于速度或法线编码
+    Rg16F,
+    /// 8位单通道
+    R8,
+    /// 16位浮点单通道
+    R16F,
+    /// 32位浮点单通道
+    R32F,
+    /// 32位无符号整数单通道，用于物体ID等不可插值的数据
+    R32UI,
+    /// 24位深度与8位模板
+    Depth24Stencil8,
+    /// 32位浮点深度
+    Depth32F,
+}
+
+impl AttachmentFormat {
+    /// 获取`(内部格式, 像素格式, 数据类型)`
+    pub fn gl_formats(self) -> (u32, u32, u32) {
+        match self {
+            AttachmentFormat::Rgba8 => (gl::RGBA8, gl::RGBA, gl::UNSIGNED_BYTE),
+            AttachmentFormat::Srgb8Alpha8 => (gl::SRGB8_ALPHA8, gl::RGBA, gl::UNSIGNED_BYTE),
+            AttachmentFormat::Rgba16F => (gl::RGBA16F, gl::RGBA, gl::FLOAT),
+            AttachmentFormat::Rgba32F => (gl::RGBA32F, gl::RGBA, gl::FLOAT),
+            AttachmentFormat::Rg16F => (gl::RG16F, gl::RG, gl::FLOAT),
+            AttachmentFormat::R8 => (gl::R8, gl::RED, gl::UNSIGNED_BYTE),
+            AttachmentFormat::R16F => (gl::R16F, gl::RED, gl::FLOAT),
+            AttachmentFormat::R32F => (gl::R32F, gl::RED, gl::FLOAT),
+            AttachmentFormat::R32UI => (gl::R32UI, gl::RED_INTEGER, gl::UNSIGNED_INT),
+            AttachmentFormat::Depth24Stencil8 => (
+                gl::DEPTH24_STENCIL8,
+                gl::DEPTH_STENCIL,
+                gl::UNSIGNED_INT_24_8,
+            ),
+            AttachmentFormat::Depth32F => (gl::DEPTH_COMPONENT32F, gl::DEPTH_COMPONENT, gl::FLOAT),
+        }
+    }
+
+    /// 判断是否为深度格式
+    pub fn is_depth(self) -> bool {
+        matches!(
+            self,
+            AttachmentFormat::Depth24Stencil8 | AttachmentFormat::Depth32F
+        )
+    }
+
+    /// 判断是否为整数格式，整数格式只能以`usampler2D`采样且不能线性过滤
+    pub fn is_integer(self) -> bool {
+        self == AttachmentFormat::R32UI
+    }
+
+    /// 判断是否包含模板
+    pub fn has_stencil(self) -> bool {
+        self == AttachmentFormat::Depth24Stencil8
+    }
+
+    /// 获取每个像素的字节数
+    pub fn bytes_per_pixel(self) -> u32 {
+        match self {
+            AttachmentFormat::R8 => 1,
+            AttachmentFormat::R16F => 2,
+            AttachmentFormat::Rgba8
+            | AttachmentFormat::Srgb8Alpha8
+            | AttachmentFormat::Rg16F
+            | AttachmentFormat::R32F
+            | AttachmentFormat::R32UI
+            | AttachmentFormat::Depth24Stencil8
+            | AttachmentFormat::Depth32F => 4,
+            AttachmentFormat::Rgba16F => 8,
+            AttachmentFormat::Rgba32F => 16,
+        }
+    }
+}
+
+/// 可作为帧缓冲附件的二维纹理
+///
+/// 使用线性过滤与边缘截取环绕，在被释放时自动删除纹理对象，因此只能在渲染线程中被释放
+#[derive(Debug)]
+pub struct RenderTexture {
+    id: u32,
+    width: u32,
+    height: u32,
+    format: AttachmentFormat,
+}
+
+impl RenderTexture {
+    /// 创建渲染纹理
+    ///
+    /// # 参数
+    /// + `width` - 宽度
+    /// + `height` - 高度
+    /// + `format` - 格式
+    pub fn new(width: u32, height: u32, format: AttachmentFormat) -> Self {
+        let (internal, pixel, ty) = format.gl_formats();
+        let filter = if format.is_depth() || format.is_integer() {
+            gl::NEAREST
+        } else {
+            gl::LINEAR
+        };
+        let mut id = 0;
+        unsafe {
+            gl::GenTextures(1, &mut id);
+            gl::BindTexture(gl::TEXTURE_2D, id);
+            gl::TexImage2D(
+                gl::TEXTURE_2D,
+                0,
+                internal as i32,
+                width.max(1) as i32,
+                height.max(1) as i32,
+                0,
+                pixel,
+                ty,
+                std::ptr::null(),
+            );
+            for (name, value) in [
+                (gl::TEXTURE_MIN_FILTER, filter),
+                (gl::TEXTURE_MAG_FILTER, filter),
+                (gl::TEXTURE_WRAP_S, gl::CLAMP_TO_EDGE),
+                (gl::TEXTURE_WRAP_T, gl::CLAMP_TO_EDGE),
+            ] {
+                gl::TexParameteri(gl::TEXTURE_2D, name, value as i32);
+            }
+            gl::BindTexture(gl::TEXTURE_2D, 0);
+        }
+        let texture = Self {
+            id,
+            width: width.max(1),
+            height: height.max(1),
+            format,
+        };
+        RenderStats::_track_render_target(texture.bytes());
+        GlObjects::track(GlObjectKind::Texture, id);
+        texture
+    }
+
+    /// 获取 OpenGL 纹理对象ID
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    /// 获取宽度
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// 获取高度
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// 获取格式
+    pub fn format(&self) -> AttachmentFormat {
+        self.format
+    }
+
+    /// 将纹理绑定到指定纹理单元
+    ///
+    /// # 参数
+    /// + `unit` - 纹理单元序号，从0开始
+    pub fn bind(&self, unit: u32) {
+        unsafe {
+            gl::ActiveTexture(gl::TEXTURE0 + unit);
+            gl::BindTexture(gl::TEXTURE_2D, self.id);
+        }
+    }
+
+    fn bytes(&self) -> i64 {
+        self.width as i64 * self.height as i64 * self.format.bytes_per_pixel() as i64
+    }
+}
+
+impl Drop for RenderTexture {
+    fn drop(&mut self) {
+        unsafe { gl::DeleteTextures(1, &self.id) };
+        RenderStats::_track_render_target(-self.bytes());
+        GlObjects::untrack(GlObjectKind::Texture, self.id);
+    }
+}
+
+/// 帧缓冲
+///
+/// 对 OpenGL 帧缓冲对象的封装，所有附件均为 [`RenderTexture`]，可直接作为后续通道的输入
+///
+/// # 示例
+///
+/// ```
+/// use gle::*;
+///
+/// fn render_init() {
+///     let scene = Framebuffer::new(
+///         1280,
+///         720,
+///         &[AttachmentFormat::Rgba16F],
+///         Some(AttachmentFormat::Depth32F),
+///     )
+///     .unwrap();
+///     scene.bind();
+///     // 绘制场景……
+///     Framebuffer::bind_default(1280, 720);
+/// }
+/// ```
+///
+/// # 注解
+///
+/// 该类型只能在渲染线程中创建、使用与释放
+#[derive(Debug)]
+pub struct Framebuffer {
+    id: u32,
+    width: u32,
+    height: u32,
+    colors: Vec<RenderTexture>,
+    depth: Option<RenderTexture>,
+}
+
+impl Framebuffer {
+    /// 创建帧缓冲
+    ///
+    /// # 参数
+    /// + `width` - 宽度
+    /// + `height` - 高度
+    /// + `colors` - 颜色附件格式，依次附加到`COLOR_ATTACHMENT0`、`COLOR_ATTACHMENT1`……
+    /// + `depth` - 深度附件格式，为`None`时不附加深度
+    ///
+    /// # 返回值
+    /// 成功时返回帧缓冲，帧缓冲不完整时返回错误
+    pub fn new(
+        width: u32,
+        height: u32,
+        colors: &[AttachmentFormat],
+        depth: Option<AttachmentFormat>,
+    ) -> Result<Self> {
+        let colors: Vec<RenderTexture> = colors
+            .iter()
+            .map(|&format| RenderTexture::new(width, height, format))
+            .collect();
+        let depth = depth.map(|format| RenderTexture::new(width, height, format));
+        let mut id = 0;
+        unsafe {
+            let mut saved = 0;
+            gl::GetIntegerv(gl::FRAMEBUFFER_BINDING, &mut saved);
+            gl::GenFramebuffers(1, &mut id);
+            gl::BindFramebuffer(gl::FRAMEBUFFER, id);
+            let mut draw_buffers = Vec::with_capacity(colors.len());
+            for (i, color) in colors.iter().enumerate() {
+                let attachment = gl::COLOR_ATTACHMENT0 + i as u32;
+                gl::FramebufferTexture2D(gl::FRAMEBUFFER, attachment, gl::TEXTURE_2D, color.id, 0);
+                draw_buffers.push(attachment);
+            }
+            if draw_buffers.is_empty() {
+                gl::DrawBuffer(gl::NONE);
+                gl::ReadBuffer(gl::NONE);
+            } else {
+                gl::DrawBuffers(draw_buffers.len() as i32, draw_buffers.as_ptr());
+            }
+            if let Some(depth) = &depth {
+                let attachment = if depth.format.has_stencil() {
+                    gl::DEPTH_STENCIL_ATTACHMENT
+                } else {
+                    gl::DEPTH_ATTACHMENT
+                };
+                gl::FramebufferTexture2D(gl::FRAMEBUFFER, attachment, gl::TEXTURE_2D, depth.id, 0);
+            }
+            let status = gl::CheckFramebufferStatus(gl::FRAMEBUFFER);
+            gl::BindFramebuffer(gl::FRAMEBUFFER, saved as u32);
+            if status != gl::FRAMEBUFFER_COMPLETE {
+                gl::DeleteFramebuffers(1, &id);
+                return Err(Error::Gl(format!("帧缓冲不完整: 0x{:X}", status)));
+            }
+        }
+        GlObjects::track(GlObjectKind::Framebuffer, id);
+        Ok(Self {
+            id,
+            width: width.max(1),
+            height: height.max(1),
+            colors,
+            depth,
+        })
+    }
+
+    /// 获取 OpenGL 帧缓冲对象ID
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    /// 获取宽度
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// 获取高度
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// 获取指定序号的颜色附件
+    pub fn color(&self, index: usize) -> Option<&RenderTexture> {
+        self.colors.get(index)
+    }
+
+    /// 获取所有颜色附件
+    pub fn colors(&self) -> &[RenderTexture] {
+        &self.colors
+    }
+
+    /// 获取深度附件
+    pub fn depth(&self) -> Option<&RenderTexture> {
+        self.depth.as_ref()
+    }
+
+    /// 以相同的附件格式重新创建指定大小的帧缓冲
+    ///
+    /// # 参数
+    /// + `width` - 新的宽度
+    /// + `height` - 新的高度
+    ///
+    /// # 注解
+    ///
+    /// 大小不变时不做任何操作，原有附件内容在重新创建后丢失
+    pub fn resize(&mut self, width: u32, height: u32) -> Result<()> {
+        if (width.max(1), height.max(1)) == (self.width, self.height) {
+            return Ok(());
+        }
+        let colors: Vec<AttachmentFormat> = self.colors.iter().map(|c| c.format).collect();
+        let depth = self.depth.as_ref().map(|d| d.format);
+        *self = Self::new(width, height, &colors, depth)?;
+        Ok(())
+    }
+
+    /// 绑定帧缓冲并将视口设置为其大小
+    pub fn bind(&self) {
+        unsafe {
+            gl::BindFramebuffer(gl::FRAMEBUFFER, self.id);
+            gl::Viewport(0, 0, self.width as i32, self.height as i32);
+        }
+    }
+
+    /// 绑定默认帧缓冲并设置视口
+    ///
+    /// # 参数
+    /// + `width` - 视口宽度，通常为窗口帧缓冲宽度
+    /// + `height` - 视口高度，通常为窗口帧缓冲高度
+    pub fn bind_default(width: u32, height: u32) {
+        unsafe {
+            gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
+            gl::Viewport(0, 0, width as i32, height as i32);
+        }
+    }
+
+    /// 将颜色附件0复制到另一个帧缓冲或默认帧缓冲
+    ///
+    /// # 参数
+    /// + `target` - 目标帧缓冲，为`None`时复制到默认帧缓冲
+    /// + `width` - 目标宽度
+    /// + `height` - 目标高度
+    pub fn blit_to(&self, target: Option<&Framebuffer>, width: u32, height: u32) {
+        unsafe {
+            gl::BindFramebuffer(gl::READ_FRAMEBUFFER, self.id);
+            gl::BindFramebuffer(gl::DRAW_FRAMEBUFFER, target.map_or(0, |t| t.id));
+            gl::BlitFramebuffer(
+                0,
+                0,
+                self.width as i32,
+                self.height as i32,
+                0,
+                0,
+                width as i32,
+                height as i32,
+                gl::COLOR_BUFFER_BIT,
+                gl::LINEAR,
+            );
+            gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
+        }
+    }
+}
+
+impl Drop for Framebuffer {
+    fn drop(&mut self) {
+        GlObjects::untrack(GlObjectKind::Framebuffer, self.id);
+        unsafe { gl::DeleteFramebuffers(1, &self.id) };
+    }
+}
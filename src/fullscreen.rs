@@ -0,0 +1,53 @@
+use std::cell::Cell;
+
+use crate::{GlObjectKind, GlObjects, RenderStats};
+
+/// 全屏三角形顶点着色器，向片段着色器输出`vUV`(范围`[0, 1]`)
+///
+/// 与 [`draw_fullscreen_triangle`] 配合使用，无需任何顶点属性
+pub const FULLSCREEN_VS: &str = r#"
+#version 330 core
+out vec2 vUV;
+
+void main()
+{
+    vec2 p = vec2(float((gl_VertexID << 1) & 2), float(gl_VertexID & 2));
+    vUV = p;
+    gl_Position = vec4(p * 2.0 - 1.0, 0.0, 1.0);
+}
+"#;
+
+thread_local! {
+    static EMPTY_VAO: Cell<u32> = const { Cell::new(0) };
+}
+
+/// 绘制覆盖整个视口的三角形
+///
+/// # 注解
+///
+/// 应先绑定使用 [`FULLSCREEN_VS`] 作为顶点着色器的着色器程序。只能在渲染线程中调用
+pub fn draw_fullscreen_triangle() {
+    EMPTY_VAO.with(|vao| unsafe {
+        if vao.get() == 0 {
+            let mut id = 0;
+            gl::GenVertexArrays(1, &mut id);
+            GlObjects::track(GlObjectKind::VertexArray, id);
+            vao.set(id);
+        }
+        gl::BindVertexArray(vao.get());
+        gl::DrawArrays(gl::TRIANGLES, 0, 3);
+        gl::BindVertexArray(0);
+    });
+    RenderStats::record_draw(1);
+}
+
+/// 删除当前线程绘制全屏三角形所用的顶点数组对象，渲染线程退出前调用
+pub(crate) fn _release_fullscreen_vao() {
+    EMPTY_VAO.with(|vao| {
+        let id = vao.replace(0);
+        if id != 0 {
+            GlObjects::untrack(GlObjectKind::VertexArray, id);
+            unsafe { gl::DeleteVertexArrays(1, &id) };
+        }
+    });
+}
@@ -0,0 +1,201 @@
+use crate::error::Result;
+use crate::{draw_fullscreen_triangle, Framebuffer, PostContext, PostEffect, Program, FULLSCREEN_VS};
+
+const FXAA_FS: &str = r#"
+#version 330 core
+in vec2 vUV;
+out vec4 FragColor;
+
+uniform sampler2D uInput;
+uniform float uEdgeThreshold;
+uniform float uEdgeThresholdMin;
+uniform float uSubpixel;
+uniform int uSearchSteps;
+
+float luma(vec2 uv)
+{
+    return dot(texture(uInput, uv).rgb, vec3(0.299, 0.587, 0.114));
+}
+
+void main()
+{
+    vec2 texel = 1.0 / vec2(textureSize(uInput, 0));
+    vec4 center = texture(uInput, vUV);
+    float lumaM = dot(center.rgb, vec3(0.299, 0.587, 0.114));
+    float lumaN = luma(vUV + vec2(0.0, texel.y));
+    float lumaS = luma(vUV - vec2(0.0, texel.y));
+    float lumaE = luma(vUV + vec2(texel.x, 0.0));
+    float lumaW = luma(vUV - vec2(texel.x, 0.0));
+
+    float lumaMax = max(lumaM, max(max(lumaN, lumaS), max(lumaE, lumaW)));
+    float lumaMin = min(lumaM, min(min(lumaN, lumaS), min(lumaE, lumaW)));
+    float range = lumaMax - lumaMin;
+    if (range < max(uEdgeThresholdMin, lumaMax * uEdgeThreshold)) {
+        FragColor = center;
+        return;
+    }
+
+    float lumaNE = luma(vUV + texel);
+    float lumaSW = luma(vUV - texel);
+    float lumaNW = luma(vUV + vec2(-texel.x, texel.y));
+    float lumaSE = luma(vUV + vec2(texel.x, -texel.y));
+
+    // 子像素混合系数
+    float average = (2.0 * (lumaN + lumaS + lumaE + lumaW) + lumaNE + lumaSW + lumaNW + lumaSE) / 12.0;
+    float subpixel = clamp(abs(average - lumaM) / range, 0.0, 1.0);
+    subpixel = smoothstep(0.0, 1.0, subpixel);
+    subpixel = subpixel * subpixel * uSubpixel;
+
+    // 判断边缘方向
+    float horizontal = abs(lumaNW + lumaNE - 2.0 * lumaN) + 2.0 * abs(lumaW + lumaE - 2.0 * lumaM) + abs(lumaSW + lumaSE - 2.0 * lumaS);
+    float vertical = abs(lumaNW + lumaSW - 2.0 * lumaW) + 2.0 * abs(lumaN + lumaS - 2.0 * lumaM) + abs(lumaNE + lumaSE - 2.0 * lumaE);
+    bool isHorizontal = horizontal >= vertical;
+
+    float luma1 = isHorizontal ? lumaS : lumaW;
+    float luma2 = isHorizontal ? lumaN : lumaE;
+    float gradient1 = abs(luma1 - lumaM);
+    float gradient2 = abs(luma2 - lumaM);
+    bool steepest1 = gradient1 >= gradient2;
+    float gradientScaled = 0.25 * max(gradient1, gradient2);
+    float stepLength = isHorizontal ? texel.y : texel.x;
+    float lumaLocal;
+    if (steepest1) {
+        stepLength = -stepLength;
+        lumaLocal = 0.5 * (luma1 + lumaM);
+    } else {
+        lumaLocal = 0.5 * (luma2 + lumaM);
+    }
+
+    vec2 uv = vUV;
+    if (isHorizontal) {
+        uv.y += stepLength * 0.5;
+    } else {
+        uv.x += stepLength * 0.5;
+    }
+
+    // 沿边缘双向搜索端点
+    vec2 offset = isHorizontal ? vec2(texel.x, 0.0) : vec2(0.0, texel.y);
+    vec2 uv1 = uv - offset;
+    vec2 uv2 = uv + offset;
+    float end1 = luma(uv1) - lumaLocal;
+    float end2 = luma(uv2) - lumaLocal;
+    bool reached1 = abs(end1) >= gradientScaled;
+    bool reached2 = abs(end2) >= gradientScaled;
+    for (int i = 1; i < uSearchSteps && !(reached1 && reached2); ++i) {
+        float stride = i < 4 ? 1.0 : (i < 8 ? 2.0 : 4.0);
+        if (!reached1) {
+            uv1 -= offset * stride;
+            end1 = luma(uv1) - lumaLocal;
+            reached1 = abs(end1) >= gradientScaled;
+        }
+        if (!reached2) {
+            uv2 += offset * stride;
+            end2 = luma(uv2) - lumaLocal;
+            reached2 = abs(end2) >= gradientScaled;
+        }
+    }
+
+    float distance1 = isHorizontal ? vUV.x - uv1.x : vUV.y - uv1.y;
+    float distance2 = isHorizontal ? uv2.x - vUV.x : uv2.y - vUV.y;
+    bool closer1 = distance1 < distance2;
+    float closest = min(distance1, distance2);
+    float edgeLength = distance1 + distance2;
+    float pixelOffset = -closest / edgeLength + 0.5;
+    bool centerSmaller = lumaM < lumaLocal;
+    bool correctVariation = ((closer1 ? end1 : end2) < 0.0) != centerSmaller;
+    float finalOffset = max(correctVariation ? pixelOffset : 0.0, subpixel);
+
+    vec2 finalUV = vUV;
+    if (isHorizontal) {
+        finalUV.y += finalOffset * stepLength;
+    } else {
+        finalUV.x += finalOffset * stepLength;
+    }
+    FragColor = vec4(texture(uInput, finalUV).rgb, center.a);
+}
+"#;
+
+/// FXAA 质量预设
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FxaaQuality {
+    /// 边缘搜索 4 步，开销最低
+    Low,
+    /// 边缘搜索 8 步
+    #[default]
+    Medium,
+    /// 边缘搜索 12 步，长边缘质量最好
+    High,
+}
+
+/// 快速近似抗锯齿(FXAA)
+///
+/// 根据亮度检测边缘并沿边缘方向混合像素，适用于渲染到离屏目标而无法使用多重采样的场合。
+/// 应作用于色调映射后的 LDR 颜色，因此通常添加在后处理链的末尾，可通过
+/// [`PostProcessChain::set_enabled`](crate::PostProcessChain::set_enabled) 以名称`"fxaa"`在运行时开关
+///
+/// # 示例
+///
+/// ```
+/// use gle::*;
+///
+/// fn render_init() {
+///     let mut hdr = HdrPipeline::new(1280, 720, false).unwrap();
+///     hdr.chain_mut().push(Fxaa::new().unwrap());
+///     // 运行时关闭抗锯齿
+///     hdr.chain_mut().set_enabled("fxaa", false);
+/// }
+/// ```
+///
+/// # 注解
+///
+/// 该类型只能在渲染线程中创建、使用与释放
+pub struct Fxaa {
+    /// 质量预设
+    pub quality: FxaaQuality,
+    /// 相对边缘阈值，局部亮度对比低于最大亮度乘以该值时不处理
+    pub edge_threshold: f32,
+    /// 绝对边缘阈值，用于跳过暗部
+    pub edge_threshold_min: f32,
+    /// 子像素混合强度，范围`[0, 1]`
+    pub subpixel: f32,
+    program: Program,
+}
+
+impl Fxaa {
+    /// 使用默认参数创建 FXAA 效果
+    pub fn new() -> Result<Self> {
+        Ok(Self {
+            quality: FxaaQuality::default(),
+            edge_threshold: 0.125,
+            edge_threshold_min: 0.0312,
+            subpixel: 0.75,
+            program: Program::new(FULLSCREEN_VS, FXAA_FS)?,
+        })
+    }
+}
+
+impl PostEffect for Fxaa {
+    fn name(&self) -> &str {
+        "fxaa"
+    }
+
+    fn apply(&mut self, ctx: &PostContext, input: u32, output: Option<&Framebuffer>) {
+        ctx.bind_output(output);
+        self.program.bind();
+        unsafe {
+            gl::ActiveTexture(gl::TEXTURE0);
+            gl::BindTexture(gl::TEXTURE_2D, input);
+        }
+        let steps = match self.quality {
+            FxaaQuality::Low => 4,
+            FxaaQuality::Medium => 8,
+            FxaaQuality::High => 12,
+        };
+        self.program.set("uInput", &0);
+        self.program.set("uEdgeThreshold", &self.edge_threshold);
+        self.program.set("uEdgeThresholdMin", &self.edge_threshold_min);
+        self.program.set("uSubpixel", &self.subpixel.clamp(0.0, 1.0));
+        self.program.set("uSearchSteps", &steps);
+        draw_fullscreen_triangle();
+    }
+}
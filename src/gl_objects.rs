@@ -0,0 +1,200 @@
+use std::backtrace::Backtrace;
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use lazy_static::lazy_static;
+
+use crate::{info, warn};
+
+lazy_static! {
+    static ref OBJECTS: Mutex<HashMap<(GlObjectKind, u32), TrackedObject>> =
+        Mutex::new(HashMap::new());
+}
+
+/// 被跟踪的 OpenGL 对象类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GlObjectKind {
+    /// 纹理
+    Texture,
+    /// 缓冲
+    Buffer,
+    /// 帧缓冲
+    Framebuffer,
+    /// 渲染缓冲
+    Renderbuffer,
+    /// 顶点数组
+    VertexArray,
+    /// 着色器程序
+    Program,
+    /// 查询
+    Query,
+    /// 采样器
+    Sampler,
+}
+
+impl GlObjectKind {
+    /// 获取类型名称
+    pub fn name(self) -> &'static str {
+        match self {
+            GlObjectKind::Texture => "texture",
+            GlObjectKind::Buffer => "buffer",
+            GlObjectKind::Framebuffer => "framebuffer",
+            GlObjectKind::Renderbuffer => "renderbuffer",
+            GlObjectKind::VertexArray => "vertex array",
+            GlObjectKind::Program => "program",
+            GlObjectKind::Query => "query",
+            GlObjectKind::Sampler => "sampler",
+        }
+    }
+
+    fn identifier(self) -> u32 {
+        match self {
+            GlObjectKind::Texture => gl::TEXTURE,
+            GlObjectKind::Buffer => gl::BUFFER,
+            GlObjectKind::Framebuffer => gl::FRAMEBUFFER,
+            GlObjectKind::Renderbuffer => gl::RENDERBUFFER,
+            GlObjectKind::VertexArray => gl::VERTEX_ARRAY,
+            GlObjectKind::Program => gl::PROGRAM,
+            GlObjectKind::Query => gl::QUERY,
+            GlObjectKind::Sampler => gl::SAMPLER,
+        }
+    }
+}
+
+struct TrackedObject {
+    label: Option<String>,
+    backtrace: Option<Backtrace>,
+}
+
+/// OpenGL 对象跟踪
+///
+/// 引擎的资源包装类型([`Texture2D`](crate::Texture2D)、[`GpuMesh`](crate::GpuMesh)、
+/// [`Program`](crate::Program)、[`Framebuffer`](crate::Framebuffer) 等)在创建与释放对象时自动登记，
+/// 渲染线程退出时通过 [`GlObjects::report_leaks`] 在日志中列出从未释放的对象及其调试标签，
+/// 调试构建下还会附带创建时的调用栈，用于在开发期间发现资源泄漏
+///
+/// # 示例
+///
+/// ```no_run
+/// use gle::*;
+///
+/// let texture = Texture2D::from_rgba8(1, 1, &[255; 4], false);
+/// GlObjects::set_label(GlObjectKind::Texture, texture.id(), "white");
+/// // 忘记释放的纹理会在退出时报告为 texture 1 "white"
+/// std::mem::forget(texture);
+/// ```
+///
+/// # 注解
+///
+/// 直接调用 OpenGL 创建的对象可以通过 [`GlObjects::track`] 与 [`GlObjects::untrack`] 手动登记。
+/// 调试构建下每次登记都会捕获调用栈，频繁创建对象时有一定开销
+pub struct GlObjects;
+
+impl GlObjects {
+    /// 登记新创建的对象
+    ///
+    /// # 参数
+    /// + `kind` - 对象类型
+    /// + `id` - 对象ID
+    pub fn track(kind: GlObjectKind, id: u32) {
+        let backtrace = cfg!(debug_assertions).then(Backtrace::force_capture);
+        let object = TrackedObject {
+            label: None,
+            backtrace,
+        };
+        OBJECTS.lock().unwrap().insert((kind, id), object);
+    }
+
+    /// 注销已删除的对象
+    ///
+    /// # 参数
+    /// + `kind` - 对象类型
+    /// + `id` - 对象ID
+    pub fn untrack(kind: GlObjectKind, id: u32) {
+        OBJECTS.lock().unwrap().remove(&(kind, id));
+    }
+
+    /// 设置对象的调试标签
+    ///
+    /// 标签出现在泄漏报告中；支持`glObjectLabel`(OpenGL 4.3 或`GL_KHR_debug`)时同时设置到驱动，
+    /// 可在 RenderDoc 等图形调试工具中看到
+    ///
+    /// # 参数
+    /// + `kind` - 对象类型
+    /// + `id` - 对象ID
+    /// + `label` - 标签
+    ///
+    /// # 注解
+    ///
+    /// 该函数只能在渲染线程中调用
+    pub fn set_label(kind: GlObjectKind, id: u32, label: &str) {
+        if let Some(object) = OBJECTS.lock().unwrap().get_mut(&(kind, id)) {
+            object.label = Some(label.to_string());
+        }
+        if gl::ObjectLabel::is_loaded() {
+            unsafe {
+                gl::ObjectLabel(
+                    kind.identifier(),
+                    id,
+                    label.len() as i32,
+                    label.as_ptr() as *const _,
+                )
+            };
+        }
+    }
+
+    /// 获取对象的调试标签
+    pub fn label(kind: GlObjectKind, id: u32) -> Option<String> {
+        OBJECTS.lock().unwrap().get(&(kind, id))?.label.clone()
+    }
+
+    /// 获取当前存活的对象数量
+    ///
+    /// # 参数
+    /// + `kind` - 对象类型，为`None`时统计所有类型
+    pub fn live_count(kind: Option<GlObjectKind>) -> usize {
+        let objects = OBJECTS.lock().unwrap();
+        match kind {
+            Some(kind) => objects.keys().filter(|(k, _)| *k == kind).count(),
+            None => objects.len(),
+        }
+    }
+
+    /// 在日志中报告所有尚未释放的对象
+    ///
+    /// # 返回值
+    /// 返回未释放的对象数量
+    ///
+    /// # 注解
+    ///
+    /// 渲染线程退出前会自动调用，此时仍被全局变量持有的对象同样会被报告
+    pub fn report_leaks() -> usize {
+        let objects = OBJECTS.lock().unwrap();
+        if objects.is_empty() {
+            info!(Self, "所有 OpenGL 对象均已释放");
+            return 0;
+        }
+        let mut leaks: Vec<_> = objects.iter().collect();
+        leaks.sort_by_key(|((kind, id), _)| (kind.name(), *id));
+        warn!(Self, "{} 个 OpenGL 对象未被释放", leaks.len());
+        for ((kind, id), object) in leaks {
+            let label = object.label.as_deref().map(|l| format!(" \"{}\"", l));
+            match &object.backtrace {
+                Some(backtrace) => {
+                    warn!(
+                        Self,
+                        "{} {}{} 创建于:\n{}",
+                        kind.name(),
+                        id,
+                        label.unwrap_or_default(),
+                        backtrace
+                    );
+                }
+                None => {
+                    warn!(Self, "{} {}{}", kind.name(), id, label.unwrap_or_default());
+                }
+            }
+        }
+        objects.len()
+    }
+}
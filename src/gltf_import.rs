@@ -0,0 +1,498 @@
+use std::{collections::HashMap, path::Path};
+
+use crate::error::{Error, Result};
+use crate::math::*;
+use crate::{debug, warn, Entity, Mesh, Scene, Transform};
+
+/// glTF 网格图元
+#[derive(Debug, Clone)]
+pub struct GltfPrimitive {
+    /// 网格数据
+    pub mesh: Mesh,
+    /// 材质在 [`GltfImport::materials`] 中的序号
+    pub material: Option<usize>,
+}
+
+/// glTF 网格，由一个或多个图元组成
+#[derive(Debug, Clone)]
+pub struct GltfMesh {
+    /// 网格名称
+    pub name: String,
+    /// 图元
+    pub primitives: Vec<GltfPrimitive>,
+}
+
+/// glTF 材质的透明模式
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GltfAlphaMode {
+    /// 不透明
+    Opaque,
+    /// 透明度测试，低于阈值的片段被丢弃
+    Mask(f32),
+    /// 透明度混合
+    Blend,
+}
+
+/// glTF 金属度-粗糙度材质
+///
+/// 纹理字段为图像在 [`GltfImport::images`] 中的序号
+#[derive(Debug, Clone, PartialEq)]
+pub struct GltfMaterial {
+    /// 材质名称
+    pub name: String,
+    /// 基础颜色系数
+    pub base_color: Vec4,
+    /// 基础颜色纹理
+    pub base_color_texture: Option<usize>,
+    /// 金属度系数
+    pub metallic: f32,
+    /// 粗糙度系数
+    pub roughness: f32,
+    /// 金属度-粗糙度纹理(B 通道为金属度，G 通道为粗糙度)
+    pub metallic_roughness_texture: Option<usize>,
+    /// 法线纹理
+    pub normal_texture: Option<usize>,
+    /// 法线缩放系数
+    pub normal_scale: f32,
+    /// 环境光遮蔽纹理(R 通道)
+    pub occlusion_texture: Option<usize>,
+    /// 环境光遮蔽强度
+    pub occlusion_strength: f32,
+    /// 自发光系数
+    pub emissive: Vec3,
+    /// 自发光纹理
+    pub emissive_texture: Option<usize>,
+    /// 透明模式
+    pub alpha_mode: GltfAlphaMode,
+    /// 是否双面渲染
+    pub double_sided: bool,
+}
+
+/// glTF 图像，已统一转换为 RGBA8 格式
+#[derive(Debug, Clone, PartialEq)]
+pub struct GltfImage {
+    /// 宽度
+    pub width: u32,
+    /// 高度
+    pub height: u32,
+    /// 按行排列的 RGBA8 像素数据
+    pub pixels: Vec<u8>,
+}
+
+/// 附加在场景实体上的 glTF 网格引用组件
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GltfMeshInstance {
+    /// 网格在 [`GltfImport::meshes`] 中的序号
+    pub mesh: usize,
+}
+
+/// 动画通道作用的属性
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GltfAnimationProperty {
+    /// 平移
+    Translation,
+    /// 旋转
+    Rotation,
+    /// 缩放
+    Scale,
+}
+
+/// 关键帧插值方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GltfInterpolation {
+    /// 阶梯插值
+    Step,
+    /// 线性插值(旋转使用球面线性插值)
+    Linear,
+    /// 三次样条插值，每个关键帧依次存储入切线、值、出切线
+    CubicSpline,
+}
+
+/// glTF 动画通道
+#[derive(Debug, Clone)]
+pub struct GltfChannel {
+    /// 目标实体
+    pub target: Entity,
+    /// 作用的属性
+    pub property: GltfAnimationProperty,
+    /// 插值方式
+    pub interpolation: GltfInterpolation,
+    /// 关键帧时间，单位为秒
+    pub times: Vec<f32>,
+    /// 关键帧值，平移与缩放仅使用前三个分量，旋转为`(x, y, z, w)`四元数
+    pub values: Vec<Vec4>,
+}
+
+/// glTF 动画
+#[derive(Debug, Clone)]
+pub struct GltfAnimation {
+    /// 动画名称
+    pub name: String,
+    /// 动画通道
+    pub channels: Vec<GltfChannel>,
+}
+
+/// glTF 导入结果
+///
+/// # 示例
+///
+/// ```
+/// use gle::*;
+///
+/// let mut import = GltfImport::load("assets/scene.gltf").unwrap();
+/// let animation = &import.animations[0];
+/// animation.apply(&mut import.scene, 0.5);
+/// import.scene.update_world_transforms();
+/// for (entity, instance) in import.scene.query::<GltfMeshInstance>() {
+///     let world = import.scene.get(entity).unwrap().world_matrix();
+///     let mesh = &import.meshes[instance.mesh];
+/// }
+/// ```
+pub struct GltfImport {
+    /// 节点层级，包含网格的节点附加有 [`GltfMeshInstance`] 组件
+    pub scene: Scene,
+    /// 网格
+    pub meshes: Vec<GltfMesh>,
+    /// 材质
+    pub materials: Vec<GltfMaterial>,
+    /// 图像
+    pub images: Vec<GltfImage>,
+    /// 动画
+    pub animations: Vec<GltfAnimation>,
+}
+
+impl GltfImport {
+    /// 从文件导入 glTF 2.0 资源(`.gltf`或`.glb`)
+    ///
+    /// # 参数
+    /// + `path` - 文件路径
+    ///
+    /// # 返回值
+    /// 成功时返回导入结果，文件读取失败或格式错误时返回错误
+    ///
+    /// # 注解
+    ///
+    /// 仅导入默认场景(不存在时为第一个场景)中的节点，仅支持三角形图元，
+    /// 不作用于已导入节点的动画通道以及变形目标权重通道将被忽略
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        debug!(Self, "正在导入 glTF 资源 {}", path.display());
+        let (document, buffers, images) =
+            gltf::import(path).map_err(|e| Error::Parse(format!("{}: {}", path.display(), e)))?;
+
+        let meshes = document
+            .meshes()
+            .map(|m| import_mesh(&m, &buffers))
+            .collect::<Vec<_>>();
+        let materials = document.materials().map(|m| import_material(&m)).collect();
+        let images = images.into_iter().map(import_image).collect();
+
+        let mut scene = Scene::new();
+        let mut node_map = HashMap::new();
+        if let Some(gltf_scene) = document.default_scene().or_else(|| document.scenes().next()) {
+            for node in gltf_scene.nodes() {
+                import_node(&node, None, &mut scene, &mut node_map);
+            }
+        }
+
+        let animations = document
+            .animations()
+            .map(|a| import_animation(&a, &buffers, &node_map))
+            .collect();
+
+        Ok(Self {
+            scene,
+            meshes,
+            materials,
+            images,
+            animations,
+        })
+    }
+}
+
+fn texture_index(texture: gltf::Texture) -> usize {
+    texture.source().index()
+}
+
+fn import_mesh(mesh: &gltf::Mesh, buffers: &[gltf::buffer::Data]) -> GltfMesh {
+    let mut primitives = Vec::new();
+    for primitive in mesh.primitives() {
+        if primitive.mode() != gltf::mesh::Mode::Triangles {
+            warn!(
+                "gle::GltfImport",
+                "网格 {:?} 包含不支持的图元类型 {:?}，已忽略",
+                mesh.name(),
+                primitive.mode()
+            );
+            continue;
+        }
+        let reader = primitive.reader(|b| Some(&buffers[b.index()]));
+        let positions: Vec<Vec3> = reader
+            .read_positions()
+            .map(|p| p.map(Vec3::from).collect())
+            .unwrap_or_default();
+        let normals: Vec<Vec3> = reader
+            .read_normals()
+            .map(|n| n.map(Vec3::from).collect())
+            .unwrap_or_default();
+        let uvs: Vec<Vec2> = reader
+            .read_tex_coords(0)
+            .map(|t| t.into_f32().map(Vec2::from).collect())
+            .unwrap_or_default();
+        let indices: Vec<u32> = reader
+            .read_indices()
+            .map(|i| i.into_u32().collect())
+            .unwrap_or_else(|| (0..positions.len() as u32).collect());
+        let mut data = Mesh {
+            positions,
+            normals,
+            uvs,
+            indices,
+        };
+        if data.normals.is_empty() {
+            data.compute_normals();
+        }
+        primitives.push(GltfPrimitive {
+            mesh: data,
+            material: primitive.material().index(),
+        });
+    }
+    GltfMesh {
+        name: mesh.name().unwrap_or_default().to_string(),
+        primitives,
+    }
+}
+
+fn import_material(material: &gltf::Material) -> GltfMaterial {
+    let pbr = material.pbr_metallic_roughness();
+    GltfMaterial {
+        name: material.name().unwrap_or_default().to_string(),
+        base_color: Vec4::from(pbr.base_color_factor()),
+        base_color_texture: pbr.base_color_texture().map(|i| texture_index(i.texture())),
+        metallic: pbr.metallic_factor(),
+        roughness: pbr.roughness_factor(),
+        metallic_roughness_texture: pbr
+            .metallic_roughness_texture()
+            .map(|i| texture_index(i.texture())),
+        normal_texture: material.normal_texture().map(|n| texture_index(n.texture())),
+        normal_scale: material.normal_texture().map_or(1.0, |n| n.scale()),
+        occlusion_texture: material
+            .occlusion_texture()
+            .map(|o| texture_index(o.texture())),
+        occlusion_strength: material.occlusion_texture().map_or(1.0, |o| o.strength()),
+        emissive: Vec3::from(material.emissive_factor()),
+        emissive_texture: material.emissive_texture().map(|i| texture_index(i.texture())),
+        alpha_mode: match material.alpha_mode() {
+            gltf::material::AlphaMode::Opaque => GltfAlphaMode::Opaque,
+            gltf::material::AlphaMode::Mask => {
+                GltfAlphaMode::Mask(material.alpha_cutoff().unwrap_or(0.5))
+            }
+            gltf::material::AlphaMode::Blend => GltfAlphaMode::Blend,
+        },
+        double_sided: material.double_sided(),
+    }
+}
+
+fn import_image(image: gltf::image::Data) -> GltfImage {
+    use gltf::image::Format;
+    let channels = match image.format {
+        Format::R8 => 1,
+        Format::R8G8 => 2,
+        Format::R8G8B8 => 3,
+        Format::R8G8B8A8 => 4,
+        format => {
+            warn!(
+                "gle::GltfImport",
+                "不支持的图像格式 {:?}，已替换为白色图像", format
+            );
+            return GltfImage {
+                width: 1,
+                height: 1,
+                pixels: vec![255; 4],
+            };
+        }
+    };
+    let pixels = if channels == 4 {
+        image.pixels
+    } else {
+        image
+            .pixels
+            .chunks_exact(channels)
+            .flat_map(|p| match channels {
+                1 => [p[0], p[0], p[0], 255],
+                2 => [p[0], p[1], 0, 255],
+                _ => [p[0], p[1], p[2], 255],
+            })
+            .collect()
+    };
+    GltfImage {
+        width: image.width,
+        height: image.height,
+        pixels,
+    }
+}
+
+fn import_node(
+    node: &gltf::Node,
+    parent: Option<Entity>,
+    scene: &mut Scene,
+    node_map: &mut HashMap<usize, Entity>,
+) {
+    let (t, r, s) = node.transform().decomposed();
+    let transform = Transform {
+        translation: Vec3::from(t),
+        rotation: Quat::from_array(r),
+        scale: Vec3::from(s),
+    };
+    let name = node.name().unwrap_or_default();
+    let entity = match parent {
+        Some(p) => scene.spawn_child(p, name, transform),
+        None => scene.spawn(name, transform),
+    };
+    if let Some(mesh) = node.mesh() {
+        scene.insert(entity, GltfMeshInstance { mesh: mesh.index() });
+    }
+    node_map.insert(node.index(), entity);
+    for child in node.children() {
+        import_node(&child, Some(entity), scene, node_map);
+    }
+}
+
+fn import_animation(
+    animation: &gltf::Animation,
+    buffers: &[gltf::buffer::Data],
+    node_map: &HashMap<usize, Entity>,
+) -> GltfAnimation {
+    use gltf::animation::{util::ReadOutputs, Interpolation, Property};
+    let mut channels = Vec::new();
+    for channel in animation.channels() {
+        let Some(&target) = node_map.get(&channel.target().node().index()) else {
+            continue;
+        };
+        let property = match channel.target().property() {
+            Property::Translation => GltfAnimationProperty::Translation,
+            Property::Rotation => GltfAnimationProperty::Rotation,
+            Property::Scale => GltfAnimationProperty::Scale,
+            Property::MorphTargetWeights => continue,
+        };
+        let interpolation = match channel.sampler().interpolation() {
+            Interpolation::Step => GltfInterpolation::Step,
+            Interpolation::Linear => GltfInterpolation::Linear,
+            Interpolation::CubicSpline => GltfInterpolation::CubicSpline,
+        };
+        let reader = channel.reader(|b| Some(&buffers[b.index()]));
+        let times: Vec<f32> = match reader.read_inputs() {
+            Some(inputs) => inputs.collect(),
+            None => continue,
+        };
+        let values: Vec<Vec4> = match reader.read_outputs() {
+            Some(ReadOutputs::Translations(v)) | Some(ReadOutputs::Scales(v)) => {
+                v.map(|v| Vec3::from(v).extend(0.0)).collect()
+            }
+            Some(ReadOutputs::Rotations(v)) => v.into_f32().map(Vec4::from).collect(),
+            _ => continue,
+        };
+        channels.push(GltfChannel {
+            target,
+            property,
+            interpolation,
+            times,
+            values,
+        });
+    }
+    GltfAnimation {
+        name: animation.name().unwrap_or_default().to_string(),
+        channels,
+    }
+}
+
+impl GltfChannel {
+    /// 获取指定时间的属性值
+    ///
+    /// # 参数
+    /// + `time` - 时间，单位为秒，超出关键帧范围时取端点值
+    ///
+    /// # 返回值
+    /// 返回插值后的属性值，没有关键帧时返回`None`
+    pub fn sample(&self, time: f32) -> Option<Vec4> {
+        let stride = if self.interpolation == GltfInterpolation::CubicSpline {
+            3
+        } else {
+            1
+        };
+        let value = |i: usize| self.values.get(i * stride + stride / 2).copied();
+        let last = self.times.len().checked_sub(1)?;
+        if time <= self.times[0] {
+            return value(0);
+        }
+        if time >= self.times[last] {
+            return value(last);
+        }
+        let next = self.times.partition_point(|&t| t <= time);
+        let prev = next - 1;
+        let dt = self.times[next] - self.times[prev];
+        let t = if dt > 0.0 {
+            (time - self.times[prev]) / dt
+        } else {
+            0.0
+        };
+        let (a, b) = (value(prev)?, value(next)?);
+        let is_rotation = self.property == GltfAnimationProperty::Rotation;
+        let result = match self.interpolation {
+            GltfInterpolation::Step => a,
+            GltfInterpolation::Linear if is_rotation => {
+                Vec4::from(Quat::from_vec4(a).slerp(Quat::from_vec4(b), t))
+            }
+            GltfInterpolation::Linear => a.lerp(b, t),
+            GltfInterpolation::CubicSpline => {
+                let out_tangent = self.values.get(prev * 3 + 2).copied()? * dt;
+                let in_tangent = self.values.get(next * 3).copied()? * dt;
+                let (t2, t3) = (t * t, t * t * t);
+                let v = a * (2.0 * t3 - 3.0 * t2 + 1.0)
+                    + out_tangent * (t3 - 2.0 * t2 + t)
+                    + b * (-2.0 * t3 + 3.0 * t2)
+                    + in_tangent * (t3 - t2);
+                if is_rotation {
+                    v.normalize()
+                } else {
+                    v
+                }
+            }
+        };
+        Some(result)
+    }
+}
+
+impl GltfAnimation {
+    /// 获取动画时长
+    ///
+    /// # 返回值
+    /// 返回所有通道中最后一个关键帧的时间，单位为秒
+    pub fn duration(&self) -> f32 {
+        self.channels
+            .iter()
+            .filter_map(|c| c.times.last().copied())
+            .fold(0.0, f32::max)
+    }
+
+    /// 将指定时间的动画姿态应用到场景中
+    ///
+    /// # 参数
+    /// + `scene` - 导入时生成的场景
+    /// + `time` - 时间，单位为秒
+    pub fn apply(&self, scene: &mut Scene, time: f32) {
+        for channel in &self.channels {
+            let (Some(value), Some(node)) = (channel.sample(time), scene.get_mut(channel.target))
+            else {
+                continue;
+            };
+            match channel.property {
+                GltfAnimationProperty::Translation => node.transform.translation = value.truncate(),
+                GltfAnimationProperty::Rotation => {
+                    node.transform.rotation = Quat::from_vec4(value).normalize()
+                }
+                GltfAnimationProperty::Scale => node.transform.scale = value.truncate(),
+            }
+        }
+    }
+}
@@ -0,0 +1,333 @@
+use crate::error::Result;
+use crate::math::*;
+use crate::{
+    draw_fullscreen_triangle, error, App, AppBuilder, Camera, EnginePlugin, GlObjectKind, GlObjects,
+    Program, RenderStats, Stage,
+};
+
+const GRID_VS: &str = r#"
+#version 330 core
+uniform mat4 uInvViewProj;
+
+out vec3 vNear;
+out vec3 vFar;
+
+void main()
+{
+    vec2 p = vec2(float((gl_VertexID << 1) & 2), float(gl_VertexID & 2)) * 2.0 - 1.0;
+    vec4 near = uInvViewProj * vec4(p, -1.0, 1.0);
+    vec4 far = uInvViewProj * vec4(p, 1.0, 1.0);
+    vNear = near.xyz / near.w;
+    vFar = far.xyz / far.w;
+    gl_Position = vec4(p, 0.0, 1.0);
+}
+"#;
+
+const GRID_FS: &str = r#"
+#version 330 core
+in vec3 vNear;
+in vec3 vFar;
+out vec4 FragColor;
+
+uniform mat4 uViewProj;
+uniform vec3 uCameraPos;
+uniform float uCellSize;
+uniform float uMajorEvery;
+uniform float uFadeDistance;
+uniform vec4 uColor;
+uniform vec4 uMajorColor;
+uniform vec4 uAxisXColor;
+uniform vec4 uAxisZColor;
+
+// 像素到最近网格线的覆盖率，线宽为一个像素
+float gridLine(vec2 coord)
+{
+    vec2 width = max(fwidth(coord), vec2(1e-6));
+    vec2 g = abs(fract(coord - 0.5) - 0.5) / width;
+    return 1.0 - min(min(g.x, g.y), 1.0);
+}
+
+void main()
+{
+    float dy = vFar.y - vNear.y;
+    float t = abs(dy) < 1e-6 ? -1.0 : -vNear.y / dy;
+    if (t <= 0.0 || t > 1.0) {
+        discard;
+    }
+    vec3 p = vNear + t * (vFar - vNear);
+    vec4 clip = uViewProj * vec4(p, 1.0);
+    gl_FragDepth = clip.z / clip.w * 0.5 + 0.5;
+
+    vec2 coord = p.xz / uCellSize;
+    float minor = gridLine(coord);
+    float major = gridLine(coord / uMajorEvery);
+    vec4 color = vec4(uColor.rgb, uColor.a * minor);
+    if (uMajorColor.a * major > color.a) {
+        color = vec4(uMajorColor.rgb, uMajorColor.a * major);
+    }
+    vec2 axis = fwidth(p.xz);
+    if (abs(p.z) < axis.y) {
+        color = uAxisXColor;
+    }
+    if (abs(p.x) < axis.x) {
+        color = uAxisZColor;
+    }
+    color.a *= 1.0 - smoothstep(uFadeDistance * 0.5, uFadeDistance, distance(p, uCameraPos));
+    if (color.a <= 0.001) {
+        discard;
+    }
+    FragColor = color;
+}
+"#;
+
+const GIZMO_VS: &str = r#"
+#version 330 core
+layout (location = 0) in vec3 aPos;
+layout (location = 1) in vec4 aColor;
+
+uniform mat4 uRotation;
+
+out vec4 vColor;
+
+void main()
+{
+    gl_Position = uRotation * vec4(aPos, 1.0);
+    vColor = aColor;
+}
+"#;
+
+const GIZMO_FS: &str = r#"
+#version 330 core
+in vec4 vColor;
+out vec4 FragColor;
+
+void main()
+{
+    FragColor = vColor;
+}
+"#;
+
+/// 坐标轴指示器的顶点数：正负三个半轴各两个顶点
+const GIZMO_VERTICES: usize = 12;
+/// 坐标轴指示器与窗口边缘的距离，单位为逻辑像素
+const GIZMO_MARGIN: f32 = 12.0;
+
+/// 编辑器网格与坐标轴指示器
+///
+/// 网格由全屏着色器在`y = 0`平面上绘制，覆盖到视野尽头，随距离淡出，并以红色与蓝色标出 X 轴与 Z 轴；
+/// 网格写入深度，因此被场景中的物体正确遮挡。坐标轴指示器绘制在视口左下角，显示摄像机的朝向，
+/// 正半轴为亮色，负半轴为暗色。两者分别由 [`Camera::show_grid`] 与 [`Camera::show_gizmo`] 开启
+///
+/// # 示例
+///
+/// ```no_run
+/// use gle::*;
+///
+/// let mut camera = Camera::perspective(60f32.to_radians(), 0.1, 1000.0);
+/// camera.show_grid = true;
+/// camera.show_gizmo = true;
+///
+/// let mut grid = None;
+/// AppBuilder::new(1280, 720, "Editor")
+///     .set_render_loop(move || {
+///         // ... 绘制场景
+///         grid.get_or_insert_with(|| EditorGrid::new().unwrap()).draw(&camera);
+///     })
+///     .build()
+///     .exec();
+/// ```
+///
+/// # 注解
+///
+/// 该类型只能在渲染线程中创建、使用与释放
+pub struct EditorGrid {
+    /// 网格单元的边长(默认值为1)
+    pub cell_size: f32,
+    /// 每隔多少个单元绘制一条主网格线(默认值为10)
+    pub major_every: u32,
+    /// 网格完全淡出的距离，从该距离的一半处开始淡出(默认值为150)
+    pub fade_distance: f32,
+    /// 次网格线颜色
+    pub color: Vec4,
+    /// 主网格线颜色
+    pub major_color: Vec4,
+    /// 坐标轴指示器的边长，单位为逻辑像素(默认值为80)
+    pub gizmo_size: f32,
+    grid_program: Program,
+    gizmo_program: Program,
+    vao: u32,
+    vbo: u32,
+}
+
+impl EditorGrid {
+    /// 创建编辑器网格
+    ///
+    /// # 返回值
+    /// 成功时返回网格，内置着色器编译失败时返回错误
+    pub fn new() -> Result<Self> {
+        let grid_program = Program::new(GRID_VS, GRID_FS)?;
+        let gizmo_program = Program::new(GIZMO_VS, GIZMO_FS)?;
+        let mut vertices = Vec::with_capacity(GIZMO_VERTICES * 7);
+        for (axis, color) in [
+            (Vec3::X, Vec3::new(0.95, 0.3, 0.3)),
+            (Vec3::Y, Vec3::new(0.4, 0.9, 0.3)),
+            (Vec3::Z, Vec3::new(0.3, 0.5, 1.0)),
+        ] {
+            for (sign, brightness) in [(1.0, 1.0), (-1.0, 0.4)] {
+                let color = (color * brightness).extend(1.0);
+                for p in [Vec3::ZERO, axis * sign] {
+                    vertices.extend_from_slice(&p.to_array());
+                    vertices.extend_from_slice(&color.to_array());
+                }
+            }
+        }
+        let (mut vao, mut vbo) = (0, 0);
+        let stride = (7 * std::mem::size_of::<f32>()) as i32;
+        unsafe {
+            gl::GenVertexArrays(1, &mut vao);
+            gl::GenBuffers(1, &mut vbo);
+            gl::BindVertexArray(vao);
+            gl::BindBuffer(gl::ARRAY_BUFFER, vbo);
+            gl::BufferData(
+                gl::ARRAY_BUFFER,
+                std::mem::size_of_val(vertices.as_slice()) as isize,
+                vertices.as_ptr() as *const _,
+                gl::STATIC_DRAW,
+            );
+            for (index, (size, offset)) in [(3, 0), (4, 3)].into_iter().enumerate() {
+                gl::EnableVertexAttribArray(index as u32);
+                gl::VertexAttribPointer(
+                    index as u32,
+                    size,
+                    gl::FLOAT,
+                    gl::FALSE,
+                    stride,
+                    (offset * std::mem::size_of::<f32>()) as *const _,
+                );
+            }
+            gl::BindVertexArray(0);
+        }
+        GlObjects::track(GlObjectKind::VertexArray, vao);
+        GlObjects::track(GlObjectKind::Buffer, vbo);
+        Ok(Self {
+            cell_size: 1.0,
+            major_every: 10,
+            fade_distance: 150.0,
+            color: Vec4::new(0.5, 0.5, 0.5, 0.35),
+            major_color: Vec4::new(0.65, 0.65, 0.65, 0.6),
+            gizmo_size: 80.0,
+            grid_program,
+            gizmo_program,
+            vao,
+            vbo,
+        })
+    }
+
+    /// 按摄像机的设置绘制网格与坐标轴指示器
+    ///
+    /// # 参数
+    /// + `camera` - 摄像机，[`Camera::show_grid`] 与 [`Camera::show_gizmo`] 均为`false`时不绘制
+    ///
+    /// # 注解
+    ///
+    /// 应在不透明物体之后、半透明物体之前调用，使网格被物体遮挡并与半透明物体正确混合
+    pub fn draw(&mut self, camera: &Camera) {
+        if camera.show_grid {
+            self.draw_grid(camera);
+        }
+        if camera.show_gizmo {
+            self.draw_gizmo(camera);
+        }
+    }
+
+    fn draw_grid(&mut self, camera: &Camera) {
+        let view_proj = camera.view_projection();
+        let program = &self.grid_program;
+        program.bind();
+        program.set("uInvViewProj", &view_proj.inverse());
+        program.set("uViewProj", &view_proj);
+        program.set("uCameraPos", &camera.position);
+        program.set("uCellSize", &self.cell_size.max(f32::EPSILON));
+        program.set("uMajorEvery", &(self.major_every.max(1) as f32));
+        program.set("uFadeDistance", &self.fade_distance.max(f32::EPSILON));
+        program.set("uColor", &self.color);
+        program.set("uMajorColor", &self.major_color);
+        program.set("uAxisXColor", &Vec4::new(0.95, 0.3, 0.3, 1.0));
+        program.set("uAxisZColor", &Vec4::new(0.3, 0.5, 1.0, 1.0));
+        unsafe {
+            let depth_test = gl::IsEnabled(gl::DEPTH_TEST) == gl::TRUE;
+            let mut depth_mask = gl::FALSE;
+            gl::GetBooleanv(gl::DEPTH_WRITEMASK, &mut depth_mask);
+            gl::Enable(gl::DEPTH_TEST);
+            gl::DepthMask(gl::FALSE);
+            gl::Enable(gl::BLEND);
+            gl::BlendFunc(gl::SRC_ALPHA, gl::ONE_MINUS_SRC_ALPHA);
+            draw_fullscreen_triangle();
+            gl::Disable(gl::BLEND);
+            gl::DepthMask(depth_mask);
+            if !depth_test {
+                gl::Disable(gl::DEPTH_TEST);
+            }
+        }
+    }
+
+    fn draw_gizmo(&mut self, camera: &Camera) {
+        let (content_scale, _) = App::content_scale();
+        let size = (self.gizmo_size * content_scale) as i32;
+        let margin = (GIZMO_MARGIN * content_scale) as i32;
+        // 只保留摄像机的旋转，并略微缩小使轴端不被裁剪
+        let rotation = Mat4::from_scale(Vec3::new(0.8, 0.8, -0.1))
+            * Mat4::from_quat(camera.rotation.inverse());
+        self.gizmo_program.bind();
+        self.gizmo_program.set("uRotation", &rotation);
+        unsafe {
+            let mut viewport = [0; 4];
+            gl::GetIntegerv(gl::VIEWPORT, viewport.as_mut_ptr());
+            let depth_test = gl::IsEnabled(gl::DEPTH_TEST) == gl::TRUE;
+            gl::Disable(gl::DEPTH_TEST);
+            gl::Viewport(viewport[0] + margin, viewport[1] + margin, size, size);
+            gl::BindVertexArray(self.vao);
+            gl::DrawArrays(gl::LINES, 0, GIZMO_VERTICES as i32);
+            gl::BindVertexArray(0);
+            gl::Viewport(viewport[0], viewport[1], viewport[2], viewport[3]);
+            if depth_test {
+                gl::Enable(gl::DEPTH_TEST);
+            }
+        }
+        RenderStats::record_draw(0);
+    }
+}
+
+impl Drop for EditorGrid {
+    fn drop(&mut self) {
+        GlObjects::untrack(GlObjectKind::VertexArray, self.vao);
+        GlObjects::untrack(GlObjectKind::Buffer, self.vbo);
+        unsafe {
+            gl::DeleteBuffers(1, &self.vbo);
+            gl::DeleteVertexArrays(1, &self.vao);
+        }
+    }
+}
+
+/// 编辑器网格插件
+///
+/// 在渲染线程中创建 [`EditorGrid`]，并在每帧渲染循环函数之后按主摄像机([`Camera::main`])的设置绘制
+pub struct EditorGridPlugin;
+
+impl EnginePlugin for EditorGridPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        let mut grid = None;
+        app.add_system(Stage::PostRender, move || {
+            let grid = grid.get_or_insert_with(|| {
+                EditorGrid::new()
+                    .inspect_err(|e| {
+                        error!("EditorGridPlugin", "无法创建编辑器网格: {}", e);
+                    })
+                    .ok()
+            });
+            if let (Some(grid), Some(camera)) = (grid, Camera::main()) {
+                grid.draw(&camera);
+            }
+        });
+    }
+}
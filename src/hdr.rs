@@ -0,0 +1,123 @@
+use crate::error::Result;
+use crate::{AttachmentFormat, Camera, Framebuffer, PostContext, PostProcessChain, ToneMapping};
+
+/// HDR 渲染管线
+///
+/// 场景被渲染到 RGBA16F 颜色与 32 位浮点深度的离屏帧缓冲中，随后经过后处理链输出到默认帧缓冲。
+/// 后处理链默认只包含 [`ToneMapping`]，其它 HDR 效果应通过 [`PostProcessChain::insert_before`] 插入到`"tonemap"`之前
+///
+/// # 示例
+///
+/// ```
+/// use gle::*;
+///
+/// fn render_init() {
+///     let mut hdr = HdrPipeline::new(1280, 720, true).unwrap();
+///     hdr.chain_mut().insert_before("tonemap", Bloom::new(1280, 720).unwrap());
+///     hdr.tone_mapping_mut().exposure = Exposure::Auto(AutoExposure::default());
+/// }
+///
+/// fn render_loop(hdr: &mut HdrPipeline, camera: &Camera) {
+///     hdr.begin();
+///     // 绘制场景……
+///     hdr.end(camera, 1.0 / 60.0);
+/// }
+/// ```
+///
+/// # 注解
+///
+/// 该类型只能在渲染线程中创建、使用与释放
+pub struct HdrPipeline {
+    scene: Framebuffer,
+    chain: PostProcessChain,
+    width: u32,
+    height: u32,
+}
+
+impl HdrPipeline {
+    /// 创建 HDR 渲染管线
+    ///
+    /// # 参数
+    /// + `width` - 输出宽度，通常为窗口帧缓冲宽度
+    /// + `height` - 输出高度，通常为窗口帧缓冲高度
+    /// + `normals` - 是否为场景帧缓冲添加法线附件(颜色附件1)，
+    ///   配合 [`PbrShaderCache::with_normal_output`](crate::PbrShaderCache::with_normal_output) 使用
+    pub fn new(width: u32, height: u32, normals: bool) -> Result<Self> {
+        let colors: &[AttachmentFormat] = if normals {
+            &[AttachmentFormat::Rgba16F, AttachmentFormat::Rgba16F]
+        } else {
+            &[AttachmentFormat::Rgba16F]
+        };
+        let scene = Framebuffer::new(width, height, colors, Some(AttachmentFormat::Depth32F))?;
+        let mut chain = PostProcessChain::new(width, height, AttachmentFormat::Rgba16F)?;
+        chain.push(ToneMapping::new()?);
+        Ok(Self {
+            scene,
+            chain,
+            width,
+            height,
+        })
+    }
+
+    /// 获取场景帧缓冲
+    pub fn scene(&self) -> &Framebuffer {
+        &self.scene
+    }
+
+    /// 获取后处理链
+    pub fn chain(&self) -> &PostProcessChain {
+        &self.chain
+    }
+
+    /// 获取后处理链的可变引用
+    pub fn chain_mut(&mut self) -> &mut PostProcessChain {
+        &mut self.chain
+    }
+
+    /// 获取色调映射效果的可变引用
+    ///
+    /// # 注解
+    ///
+    /// 色调映射效果被从后处理链中移除时会导致 panic
+    pub fn tone_mapping_mut(&mut self) -> &mut ToneMapping {
+        self.chain
+            .get_mut::<ToneMapping>()
+            .expect("色调映射效果已从后处理链中移除")
+    }
+
+    /// 修改输出大小
+    pub fn resize(&mut self, width: u32, height: u32) -> Result<()> {
+        self.scene.resize(width, height)?;
+        self.chain.resize(width, height)?;
+        self.width = width;
+        self.height = height;
+        Ok(())
+    }
+
+    /// 开始渲染场景：绑定场景帧缓冲并清除颜色、法线与深度
+    pub fn begin(&self) {
+        self.scene.bind();
+        unsafe {
+            gl::ClearColor(0.0, 0.0, 0.0, 1.0);
+            gl::Clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT);
+        }
+    }
+
+    /// 结束渲染场景：执行后处理链并输出到默认帧缓冲
+    ///
+    /// # 参数
+    /// + `camera` - 渲染场景时使用的相机
+    /// + `dt` - 距上一帧的时间(秒)，用于自动曝光等随时间变化的效果
+    pub fn end(&mut self, camera: &Camera, dt: f32) {
+        let ctx = PostContext {
+            camera,
+            depth: self.scene.depth().map_or(0, |d| d.id()),
+            normal: self.scene.color(1).map(|n| n.id()),
+            width: self.width,
+            height: self.height,
+            dt,
+        };
+        let input = self.scene.colors()[0].id();
+        self.chain.run(&ctx, input, None);
+    }
+}
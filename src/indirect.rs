@@ -0,0 +1,272 @@
+use crate::{GlObjectKind, GlObjects, GpuMesh, Mesh, RenderStats};
+
+/// 间接绘制命令，内存布局与`DrawElementsIndirectCommand`一致
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DrawElementsIndirectCommand {
+    /// 索引数量
+    pub count: u32,
+    /// 实例数量
+    pub instance_count: u32,
+    /// 首个索引在索引缓冲中的位置
+    pub first_index: u32,
+    /// 加到每个索引上的顶点偏移
+    pub base_vertex: i32,
+    /// 首个实例的编号，用于偏移实例化顶点属性
+    pub base_instance: u32,
+}
+
+/// 网格在 [`MeshPool`] 中的位置
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MeshRange {
+    /// 首个索引在合并索引缓冲中的位置
+    pub first_index: u32,
+    /// 索引数量
+    pub index_count: u32,
+    /// 首个顶点在合并顶点缓冲中的位置
+    pub base_vertex: i32,
+    /// 顶点数量
+    pub vertex_count: u32,
+}
+
+impl MeshRange {
+    /// 生成绘制该网格的间接绘制命令
+    ///
+    /// # 参数
+    /// + `instance_count` - 实例数量
+    /// + `base_instance` - 首个实例的编号
+    pub fn command(&self, instance_count: u32, base_instance: u32) -> DrawElementsIndirectCommand {
+        DrawElementsIndirectCommand {
+            count: self.index_count,
+            instance_count,
+            first_index: self.first_index,
+            base_vertex: self.base_vertex,
+            base_instance,
+        }
+    }
+}
+
+/// 网格池
+///
+/// 将顶点格式相同的多个网格合并到同一组顶点与索引缓冲中，以便通过一次多重间接绘制提交
+///
+/// # 示例
+///
+/// ```
+/// use gle::{*, math::*};
+///
+/// let mut pool = MeshPool::new();
+/// let cube = pool.add(&Mesh::cube(Vec3::ONE));
+/// let sphere = pool.add(&Mesh::uv_sphere(0.5, 16, 8));
+/// // 在渲染线程中
+/// let gpu = pool.upload();
+/// let mut commands = IndirectBuffer::new();
+/// commands.push(cube.command(100, 0));
+/// commands.push(sphere.command(50, 100));
+/// commands.draw(&gpu);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct MeshPool {
+    mesh: Mesh,
+    ranges: Vec<MeshRange>,
+}
+
+impl MeshPool {
+    /// 创建空的网格池
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 向网格池中添加网格
+    ///
+    /// # 参数
+    /// + `mesh` - 网格
+    ///
+    /// # 返回值
+    /// 返回网格在池中的位置，索引保持相对于网格自身，由`base_vertex`偏移
+    pub fn add(&mut self, mesh: &Mesh) -> MeshRange {
+        let range = MeshRange {
+            first_index: self.mesh.indices.len() as u32,
+            index_count: mesh.indices.len() as u32,
+            base_vertex: self.mesh.positions.len() as i32,
+            vertex_count: mesh.positions.len() as u32,
+        };
+        self.mesh.append(mesh, &crate::math::Mat4::IDENTITY);
+        // `append`会对索引加上顶点偏移，此处还原为局部索引，由 base_vertex 负责偏移
+        let base = range.base_vertex as u32;
+        for index in &mut self.mesh.indices[range.first_index as usize..] {
+            *index -= base;
+        }
+        self.ranges.push(range);
+        range
+    }
+
+    /// 获取所有网格的位置，顺序与添加顺序一致
+    pub fn ranges(&self) -> &[MeshRange] {
+        &self.ranges
+    }
+
+    /// 将网格池上传到 GPU
+    ///
+    /// # 返回值
+    /// 返回包含所有网格的 GPU 网格，顶点属性布局与 [`Mesh::upload`] 相同
+    ///
+    /// # 注解
+    ///
+    /// 只能在渲染线程中调用，返回的 GPU 网格不应直接通过 [`GpuMesh::draw`] 绘制
+    pub fn upload(&self) -> GpuMesh {
+        self.mesh.upload()
+    }
+}
+
+/// 判断当前上下文是否支持多重间接绘制(OpenGL 4.3+)
+///
+/// # 注解
+///
+/// 只能在渲染线程中调用
+pub fn supports_multi_draw_indirect() -> bool {
+    let (mut major, mut minor) = (0, 0);
+    unsafe {
+        gl::GetIntegerv(gl::MAJOR_VERSION, &mut major);
+        gl::GetIntegerv(gl::MINOR_VERSION, &mut minor);
+    }
+    (major, minor) >= (4, 3)
+}
+
+/// 间接绘制命令缓冲
+///
+/// 在 OpenGL 4.3+ 上通过`glMultiDrawElementsIndirect`一次提交全部命令，
+/// 否则逐条调用`glDrawElementsInstancedBaseVertex`作为后备
+///
+/// # 注解
+///
+/// + 后备路径无法应用`base_instance`，依赖`base_instance`偏移实例属性的着色器在后备路径下将读取错误的实例数据
+/// + 该类型只能在渲染线程中创建、使用与释放
+#[derive(Debug)]
+pub struct IndirectBuffer {
+    buffer: u32,
+    capacity: usize,
+    commands: Vec<DrawElementsIndirectCommand>,
+    supported: bool,
+}
+
+impl IndirectBuffer {
+    /// 创建间接绘制命令缓冲
+    pub fn new() -> Self {
+        let supported = supports_multi_draw_indirect();
+        let mut buffer = 0;
+        if supported {
+            unsafe { gl::GenBuffers(1, &mut buffer) };
+            GlObjects::track(GlObjectKind::Buffer, buffer);
+        }
+        Self {
+            buffer,
+            capacity: 0,
+            commands: Vec::new(),
+            supported,
+        }
+    }
+
+    /// 判断是否使用多重间接绘制
+    pub fn is_indirect(&self) -> bool {
+        self.supported
+    }
+
+    /// 添加绘制命令
+    pub fn push(&mut self, command: DrawElementsIndirectCommand) {
+        self.commands.push(command);
+    }
+
+    /// 清空绘制命令
+    pub fn clear(&mut self) {
+        self.commands.clear();
+    }
+
+    /// 获取绘制命令
+    pub fn commands(&self) -> &[DrawElementsIndirectCommand] {
+        &self.commands
+    }
+
+    /// 获取绘制命令数量
+    pub fn len(&self) -> usize {
+        self.commands.len()
+    }
+
+    /// 判断是否没有绘制命令
+    pub fn is_empty(&self) -> bool {
+        self.commands.is_empty()
+    }
+
+    /// 提交所有绘制命令
+    ///
+    /// # 参数
+    /// + `mesh` - 命令所引用的 GPU 网格，通常由 [`MeshPool::upload`] 生成
+    pub fn draw(&mut self, mesh: &GpuMesh) {
+        if self.commands.is_empty() {
+            return;
+        }
+        let stride = std::mem::size_of::<DrawElementsIndirectCommand>();
+        unsafe {
+            gl::BindVertexArray(mesh.vao());
+            if self.supported {
+                gl::BindBuffer(gl::DRAW_INDIRECT_BUFFER, self.buffer);
+                let size = (self.commands.len() * stride) as isize;
+                if self.commands.len() > self.capacity {
+                    self.capacity = self.commands.len().next_power_of_two();
+                    gl::BufferData(
+                        gl::DRAW_INDIRECT_BUFFER,
+                        (self.capacity * stride) as isize,
+                        std::ptr::null(),
+                        gl::DYNAMIC_DRAW,
+                    );
+                }
+                gl::BufferSubData(
+                    gl::DRAW_INDIRECT_BUFFER,
+                    0,
+                    size,
+                    self.commands.as_ptr() as *const _,
+                );
+                let triangles = self.commands.iter();
+                RenderStats::record_draw(
+                    triangles.map(|c| c.count as u64 / 3 * c.instance_count as u64).sum(),
+                );
+                gl::MultiDrawElementsIndirect(
+                    gl::TRIANGLES,
+                    gl::UNSIGNED_INT,
+                    std::ptr::null(),
+                    self.commands.len() as i32,
+                    stride as i32,
+                );
+                gl::BindBuffer(gl::DRAW_INDIRECT_BUFFER, 0);
+            } else {
+                for c in &self.commands {
+                    RenderStats::record_draw(c.count as u64 / 3 * c.instance_count as u64);
+                    gl::DrawElementsInstancedBaseVertex(
+                        gl::TRIANGLES,
+                        c.count as i32,
+                        gl::UNSIGNED_INT,
+                        (c.first_index as usize * std::mem::size_of::<u32>()) as *const _,
+                        c.instance_count as i32,
+                        c.base_vertex,
+                    );
+                }
+            }
+            gl::BindVertexArray(0);
+        }
+    }
+}
+
+impl Default for IndirectBuffer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for IndirectBuffer {
+    fn drop(&mut self) {
+        if self.buffer != 0 {
+            GlObjects::untrack(GlObjectKind::Buffer, self.buffer);
+            unsafe { gl::DeleteBuffers(1, &self.buffer) };
+        }
+    }
+}
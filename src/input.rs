@@ -0,0 +1,225 @@
+use std::collections::HashSet;
+
+use gom::*;
+
+use crate::{Action, Key, MouseButton};
+
+const INPUT: &str = id!(INPUT);
+const INPUT_STATE: &str = id!(@INPUT.STATE);
+
+#[derive(Default)]
+struct InputState {
+    keys_down: HashSet<i32>,
+    keys_pressed: HashSet<i32>,
+    keys_released: HashSet<i32>,
+    keys_repeated: HashSet<i32>,
+    chars: String,
+    buttons_down: HashSet<i32>,
+    buttons_pressed: HashSet<i32>,
+    buttons_released: HashSet<i32>,
+    cursor: Option<(f64, f64)>,
+    cursor_delta: (f64, f64),
+    scroll_delta: (f64, f64),
+}
+
+/// 输入管理器
+///
+/// 汇总窗口的键盘与鼠标事件，以便在事件循环中按帧查询输入状态
+///
+/// # 示例
+///
+/// ```
+/// use gle::*;
+///
+/// fn event_loop() {
+///     if Input::key_pressed(Key::Space) {
+///         println!("Space key pressed this frame");
+///     }
+///     let (dx, dy) = Input::cursor_delta();
+/// }
+/// ```
+///
+/// # 注解
+///
+/// "本帧"指事件循环的一次迭代，帧内的按下、松开、光标位移与滚轮增量会在事件循环函数返回后被清空
+pub struct Input;
+
+impl Input {
+    fn _lazy_init() {
+        if !Registry::<InputState>::exists(INPUT_STATE) {
+            Registry::register(INPUT_STATE, InputState::default()).unwrap();
+        }
+    }
+
+    fn _with<R, F: FnOnce(&InputState) -> R>(f: F) -> R
+    where
+        R: Default,
+    {
+        Self::_lazy_init();
+        Registry::with(INPUT_STATE, f).unwrap_or_default()
+    }
+
+    fn _apply<F: FnOnce(&mut InputState)>(f: F) {
+        Self::_lazy_init();
+        Registry::apply(INPUT_STATE, f);
+    }
+
+    pub(crate) fn _on_key(key: Key, action: Action) {
+        let key = key as i32;
+        Self::_apply(|s| match action {
+            Action::Press => {
+                s.keys_down.insert(key);
+                s.keys_pressed.insert(key);
+                s.keys_repeated.insert(key);
+            }
+            Action::Release => {
+                s.keys_down.remove(&key);
+                s.keys_released.insert(key);
+            }
+            Action::Repeat => {
+                s.keys_repeated.insert(key);
+            }
+        });
+    }
+
+    pub(crate) fn _on_char(ch: char) {
+        Self::_apply(|s| s.chars.push(ch));
+    }
+
+    pub(crate) fn _on_mouse_button(button: MouseButton, action: Action) {
+        let button = button as i32;
+        Self::_apply(|s| match action {
+            Action::Press => {
+                s.buttons_down.insert(button);
+                s.buttons_pressed.insert(button);
+            }
+            Action::Release => {
+                s.buttons_down.remove(&button);
+                s.buttons_released.insert(button);
+            }
+            Action::Repeat => {}
+        });
+    }
+
+    pub(crate) fn _on_cursor_pos(x: f64, y: f64) {
+        Self::_apply(|s| {
+            if let Some((lx, ly)) = s.cursor {
+                s.cursor_delta.0 += x - lx;
+                s.cursor_delta.1 += y - ly;
+            }
+            s.cursor = Some((x, y));
+        });
+    }
+
+    pub(crate) fn _on_scroll(x: f64, y: f64) {
+        Self::_apply(|s| {
+            s.scroll_delta.0 += x;
+            s.scroll_delta.1 += y;
+        });
+    }
+
+    pub(crate) fn _end_frame() {
+        Self::_apply(|s| {
+            s.keys_pressed.clear();
+            s.keys_released.clear();
+            s.keys_repeated.clear();
+            s.chars.clear();
+            s.buttons_pressed.clear();
+            s.buttons_released.clear();
+            s.cursor_delta = (0.0, 0.0);
+            s.scroll_delta = (0.0, 0.0);
+        });
+    }
+
+    /// 判断按键是否处于按下状态
+    ///
+    /// # 参数
+    /// + `key` - 按键
+    pub fn key_down(key: Key) -> bool {
+        Self::_with(|s| s.keys_down.contains(&(key as i32)))
+    }
+
+    /// 判断按键是否在本帧被按下
+    ///
+    /// # 参数
+    /// + `key` - 按键
+    pub fn key_pressed(key: Key) -> bool {
+        Self::_with(|s| s.keys_pressed.contains(&(key as i32)))
+    }
+
+    /// 判断按键是否在本帧被松开
+    ///
+    /// # 参数
+    /// + `key` - 按键
+    pub fn key_released(key: Key) -> bool {
+        Self::_with(|s| s.keys_released.contains(&(key as i32)))
+    }
+
+    /// 判断按键是否在本帧被按下或因长按而自动重复
+    ///
+    /// # 参数
+    /// + `key` - 按键
+    ///
+    /// # 注解
+    ///
+    /// 适用于文本编辑与菜单导航等需要按住连续触发的操作，重复间隔由操作系统决定
+    pub fn key_repeated(key: Key) -> bool {
+        Self::_with(|s| s.keys_repeated.contains(&(key as i32)))
+    }
+
+    /// 获取本帧输入的文本
+    ///
+    /// # 返回值
+    /// 返回本帧内按输入顺序排列的字符，已按键盘布局与输入法转换，不含控制字符
+    pub fn text_input() -> String {
+        Self::_with(|s| s.chars.clone())
+    }
+
+    /// 判断鼠标按键是否处于按下状态
+    ///
+    /// # 参数
+    /// + `button` - 鼠标按键
+    pub fn mouse_down(button: MouseButton) -> bool {
+        Self::_with(|s| s.buttons_down.contains(&(button as i32)))
+    }
+
+    /// 判断鼠标按键是否在本帧被按下
+    ///
+    /// # 参数
+    /// + `button` - 鼠标按键
+    pub fn mouse_pressed(button: MouseButton) -> bool {
+        Self::_with(|s| s.buttons_pressed.contains(&(button as i32)))
+    }
+
+    /// 判断鼠标按键是否在本帧被松开
+    ///
+    /// # 参数
+    /// + `button` - 鼠标按键
+    pub fn mouse_released(button: MouseButton) -> bool {
+        Self::_with(|s| s.buttons_released.contains(&(button as i32)))
+    }
+
+    /// 获取鼠标光标位置
+    ///
+    /// # 返回值
+    /// 返回光标相对于窗口客户区左上角的坐标，尚未收到光标事件时返回`(0.0, 0.0)`
+    pub fn cursor_pos() -> (f64, f64) {
+        Self::_with(|s| s.cursor.unwrap_or_default())
+    }
+
+    /// 获取本帧的鼠标光标位移
+    ///
+    /// # 返回值
+    /// 返回光标在本帧内的横向与纵向位移
+    pub fn cursor_delta() -> (f64, f64) {
+        Self::_with(|s| s.cursor_delta)
+    }
+
+    /// 获取本帧的滚轮增量
+    ///
+    /// # 返回值
+    /// 返回滚轮在本帧内的横向与纵向滚动距离
+    pub fn scroll_delta() -> (f64, f64) {
+        Self::_with(|s| s.scroll_delta)
+    }
+}
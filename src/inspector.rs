@@ -0,0 +1,491 @@
+//! 运行时场景检查器
+//!
+//! 以 [`Ui`](crate::Ui) 控件构建实体列表与组件面板，与游戏界面共用同一个 [`UiRenderer`](crate::UiRenderer)
+
+use crate::error::{Error, Result};
+use crate::math::*;
+use crate::{
+    warn, Anchor, App, ComponentRegistry, Container, Entity, FlexLayout, Rect, Scene, Sprite,
+    SpriteBatch, Texture2D, Ui, UiEvent, Widget, WidgetId,
+};
+
+/// 检查器面板的宽度，单位为逻辑像素
+const WIDTH: f32 = 360.0;
+/// 行高，单位为逻辑像素
+const ROW: f32 = 28.0;
+/// 实体列表显示的最大实体数
+const MAX_ENTITIES: usize = 512;
+/// 选中框的最小边长，单位为物理像素
+const MIN_HIGHLIGHT: f32 = 24.0;
+
+/// 检查器中可编辑的字段
+#[derive(Debug, Clone, PartialEq)]
+enum Field {
+    Name,
+    Translation,
+    Rotation,
+    Scale,
+    Component(String),
+}
+
+impl Field {
+    fn label(&self) -> &str {
+        match self {
+            Field::Name => "name",
+            Field::Translation => "translation",
+            Field::Rotation => "rotation (deg)",
+            Field::Scale => "scale",
+            Field::Component(name) => name,
+        }
+    }
+}
+
+/// 运行时场景检查器
+///
+/// 在 [`Ui`] 中添加一个靠右的面板：上半部分按层级列出场景中的实体，点击选中；
+/// 下半部分显示选中实体的名称、局部变换以及 [`ComponentRegistry`] 中注册过的组件(以单行 JSON 显示)。
+/// 字段在未获得焦点时每帧刷新为实体的当前值，编辑后按回车写回实体，格式错误时输出警告并恢复原值。
+/// 选中的实体可以在渲染线程中由 [`InspectorHighlight`] 在视口中标出
+///
+/// # 示例
+///
+/// ```no_run
+/// use std::sync::{Arc, Mutex};
+/// use gle::*;
+///
+/// let scene = Arc::new(Mutex::new(Scene::new()));
+/// let ui = Arc::new(Mutex::new(Ui::new()));
+/// let inspector = {
+///     let mut ui = ui.lock().unwrap();
+///     Arc::new(Mutex::new(SceneInspector::new(&mut ui, ComponentRegistry::new())))
+/// };
+///
+/// let (event_scene, event_ui, event_inspector) = (scene.clone(), ui.clone(), inspector.clone());
+/// let mut renderer = None;
+/// AppBuilder::new(1280, 720, "Game")
+///     .set_event_loop(move || {
+///         let mut ui = event_ui.lock().unwrap();
+///         let mut inspector = event_inspector.lock().unwrap();
+///         if Input::key_pressed(Key::F4) {
+///             inspector.toggle(&mut ui);
+///         }
+///         ui.update();
+///         let events = ui.events();
+///         inspector.update(&mut ui, &mut event_scene.lock().unwrap(), &events);
+///     })
+///     .set_render_loop(move || {
+///         let (renderer, highlight, font) = renderer.get_or_insert_with(|| {
+///             let font = SdfFont::load("assets/noto_sans.toml").unwrap();
+///             (UiRenderer::new().unwrap(), InspectorHighlight::new().unwrap(), font)
+///         });
+///         // ... 绘制场景
+///         let scene = scene.lock().unwrap();
+///         let selected = inspector.lock().unwrap().selected();
+///         if let (Some(entity), Some(camera)) = (selected, Camera::main()) {
+///             highlight.draw(&scene, entity, &camera.view_projection());
+///         }
+///         renderer.draw(&ui.lock().unwrap(), font);
+///     })
+///     .build()
+///     .exec();
+/// ```
+pub struct SceneInspector {
+    registry: ComponentRegistry,
+    panel: WidgetId,
+    list: WidgetId,
+    details: WidgetId,
+    rows: Vec<WidgetId>,
+    entities: Vec<Entity>,
+    outline: Vec<(Entity, String, usize)>,
+    listed_selection: Option<Entity>,
+    fields: Vec<(WidgetId, Field)>,
+    selected: Option<Entity>,
+}
+
+impl SceneInspector {
+    /// 创建检查器并将面板添加到界面的根控件中
+    ///
+    /// # 参数
+    /// + `ui` - 界面
+    /// + `registry` - 组件注册表，只有注册过的组件会被显示
+    pub fn new(ui: &mut Ui, registry: ComponentRegistry) -> Self {
+        let root = ui.root();
+        let panel = Widget::panel(Rect::new(-8.0, 8.0, WIDTH, -16.0))
+            .with_anchor(Anchor::new(
+                Vec2::new(1.0, 0.0),
+                Vec2::ONE,
+                Vec2::new(1.0, 0.0),
+            ))
+            .with_container(Container::Flex(FlexLayout::column(8.0, 8.0)));
+        let panel = ui.add(root, panel);
+        ui.add(panel, Widget::label("Scene", Rect::new(0.0, 0.0, 0.0, ROW)));
+        let area = || {
+            Widget::scroll_area(Rect::default())
+                .with_grow(1.0)
+                .with_container(Container::Flex(FlexLayout::column(2.0, 4.0)))
+        };
+        let list = ui.add(panel, area());
+        ui.add(
+            panel,
+            Widget::label("Inspector", Rect::new(0.0, 0.0, 0.0, ROW)),
+        );
+        let details = ui.add(panel, area());
+        Self {
+            registry,
+            panel,
+            list,
+            details,
+            rows: Vec::new(),
+            entities: Vec::new(),
+            outline: Vec::new(),
+            listed_selection: None,
+            fields: Vec::new(),
+            selected: None,
+        }
+    }
+
+    /// 组件注册表的可变引用
+    pub fn registry_mut(&mut self) -> &mut ComponentRegistry {
+        &mut self.registry
+    }
+
+    /// 面板是否可见
+    pub fn is_visible(&self, ui: &Ui) -> bool {
+        ui.widget(self.panel).is_some_and(|w| w.visible)
+    }
+
+    /// 设置面板是否可见
+    pub fn set_visible(&self, ui: &mut Ui, visible: bool) {
+        if let Some(panel) = ui.widget_mut(self.panel) {
+            panel.visible = visible;
+        }
+    }
+
+    /// 切换面板的可见性
+    pub fn toggle(&self, ui: &mut Ui) {
+        self.set_visible(ui, !self.is_visible(ui));
+    }
+
+    /// 获取选中的实体
+    pub fn selected(&self) -> Option<Entity> {
+        self.selected
+    }
+
+    /// 选中实体
+    ///
+    /// # 参数
+    /// + `entity` - 要选中的实体，为`None`时取消选中
+    pub fn select(&mut self, entity: Option<Entity>) {
+        self.selected = entity;
+    }
+
+    /// 处理界面事件并同步面板与场景
+    ///
+    /// # 参数
+    /// + `ui` - 界面
+    /// + `scene` - 场景
+    /// + `events` - 本帧 [`Ui::events`] 取出的事件，检查器以外的事件被忽略
+    ///
+    /// # 注解
+    ///
+    /// 应在事件循环中每帧于 [`Ui::update`] 之后调用。面板隐藏时只处理选中状态
+    pub fn update(&mut self, ui: &mut Ui, scene: &mut Scene, events: &[UiEvent]) {
+        if self.selected.is_some_and(|e| !scene.contains(e)) {
+            self.selected = None;
+        }
+        if !self.is_visible(ui) {
+            return;
+        }
+        for event in events {
+            match *event {
+                UiEvent::Clicked(id) => {
+                    if let Some(index) = self.rows.iter().position(|row| *row == id) {
+                        self.selected = self.entities.get(index).copied();
+                    }
+                }
+                UiEvent::Submitted(id) => {
+                    if let Some((_, field)) = self.fields.iter().find(|(w, _)| *w == id) {
+                        let field = field.clone();
+                        self.apply(ui, scene, id, &field);
+                    }
+                }
+                _ => {}
+            }
+        }
+        self.refresh_list(ui, scene);
+        self.refresh_fields(ui, scene);
+    }
+
+    /// 实体层级改变或选中的实体改变时重建实体列表
+    fn refresh_list(&mut self, ui: &mut Ui, scene: &Scene) {
+        let mut outline = Vec::new();
+        let mut stack: Vec<(Entity, usize)> = scene.roots().iter().rev().map(|&e| (e, 0)).collect();
+        while let Some((entity, depth)) = stack.pop() {
+            if outline.len() == MAX_ENTITIES {
+                break;
+            }
+            let Some(node) = scene.get(entity) else {
+                continue;
+            };
+            outline.push((entity, node.name.clone(), depth));
+            stack.extend(node.children().iter().rev().map(|&c| (c, depth + 1)));
+        }
+        if outline == self.outline && self.listed_selection == self.selected {
+            return;
+        }
+        for row in self.rows.drain(..) {
+            ui.remove(row);
+        }
+        self.entities.clear();
+        for (entity, name, depth) in &outline {
+            let marker = if Some(*entity) == self.selected {
+                "> "
+            } else {
+                ""
+            };
+            let name = if name.is_empty() {
+                format!("#{}", entity.index())
+            } else {
+                name.clone()
+            };
+            let text = format!("{}{}{}", "  ".repeat(*depth), marker, name);
+            let row = ui.add(
+                self.list,
+                Widget::button(&text, Rect::new(0.0, 0.0, 0.0, ROW)),
+            );
+            self.rows.push(row);
+            self.entities.push(*entity);
+        }
+        if outline.len() == MAX_ENTITIES {
+            let text = format!("... ({} entities)", scene.len());
+            let row = ui.add(
+                self.list,
+                Widget::label(&text, Rect::new(0.0, 0.0, 0.0, ROW)),
+            );
+            self.rows.push(row);
+        }
+        self.outline = outline;
+        self.listed_selection = self.selected;
+    }
+
+    /// 选中实体的组件改变时重建字段，并刷新未获得焦点的字段的值
+    fn refresh_fields(&mut self, ui: &mut Ui, scene: &Scene) {
+        let values: Vec<(Field, String)> = match self.selected {
+            Some(entity) => self.values(scene, entity),
+            None => Vec::new(),
+        };
+        let changed = values.len() != self.fields.len()
+            || values
+                .iter()
+                .zip(&self.fields)
+                .any(|((a, _), (_, b))| a != b);
+        if changed {
+            let children = ui.widget(self.details).map(|w| w.children().to_vec());
+            for child in children.unwrap_or_default() {
+                ui.remove(child);
+            }
+            self.fields.clear();
+            if values.is_empty() {
+                let label = Widget::label("No entity selected", Rect::new(0.0, 0.0, 0.0, ROW));
+                ui.add(self.details, label);
+            }
+            for (field, _) in &values {
+                ui.add(
+                    self.details,
+                    Widget::label(field.label(), Rect::new(0.0, 0.0, 0.0, ROW)),
+                );
+                let input = Widget::text_input("", Rect::new(0.0, 0.0, 0.0, ROW));
+                let input = ui.add(self.details, input);
+                self.fields.push((input, field.clone()));
+            }
+        }
+        for ((input, _), (_, value)) in self.fields.iter().zip(&values) {
+            if ui.focus() != Some(*input) && ui.text(*input) != Some(value.as_str()) {
+                ui.set_text(*input, value);
+            }
+        }
+    }
+
+    /// 选中实体的所有字段及其当前值
+    fn values(&self, scene: &Scene, entity: Entity) -> Vec<(Field, String)> {
+        let Some(node) = scene.get(entity) else {
+            return Vec::new();
+        };
+        let t = &node.transform;
+        let (y, x, z) = t.rotation.to_euler(EulerRot::YXZ);
+        let rotation = Vec3::new(x, y, z) * (180.0 / std::f32::consts::PI);
+        let mut values = vec![
+            (Field::Name, node.name.clone()),
+            (Field::Translation, format_vec3(t.translation)),
+            (Field::Rotation, format_vec3(rotation)),
+            (Field::Scale, format_vec3(t.scale)),
+        ];
+        for (name, value) in self.registry.save_all(scene, entity) {
+            let text = value
+                .and_then(|v| serde_json::to_string(&v).map_err(|e| Error::Parse(e.to_string())))
+                .unwrap_or_else(|e| format!("<{}>", e));
+            values.push((Field::Component(name.to_string()), text));
+        }
+        values
+    }
+
+    /// 将字段的文本写回选中的实体
+    fn apply(&mut self, ui: &mut Ui, scene: &mut Scene, input: WidgetId, field: &Field) {
+        let (Some(entity), Some(text)) = (self.selected, ui.text(input).map(str::to_string)) else {
+            return;
+        };
+        let result = match field {
+            Field::Name => {
+                if let Some(node) = scene.get_mut(entity) {
+                    node.name = text.clone();
+                }
+                Ok(())
+            }
+            Field::Translation | Field::Rotation | Field::Scale => match parse_vec3(&text) {
+                Some(v) => {
+                    if let Some(node) = scene.get_mut(entity) {
+                        let t = &mut node.transform;
+                        match field {
+                            Field::Translation => t.translation = v,
+                            Field::Rotation => {
+                                let r = v * (std::f32::consts::PI / 180.0);
+                                t.rotation = Quat::from_euler(EulerRot::YXZ, r.y, r.x, r.z);
+                            }
+                            _ => t.scale = v,
+                        }
+                    }
+                    Ok(())
+                }
+                None => Err(format!("{:?} 不是三个数", text)),
+            },
+            Field::Component(name) => serde_json::from_str(&text)
+                .map_err(|e| Error::Parse(e.to_string()))
+                .and_then(|v| self.registry.load(name, scene, entity, &v))
+                .map_err(|e| e.to_string()),
+        };
+        if let Err(e) = result {
+            warn!(Self, "无法设置 {}: {}", field.label(), e);
+        }
+        // 释放焦点，使字段恢复为实体的当前值
+        ui.set_focus(None);
+    }
+}
+
+/// 以空格分隔的三个数格式化向量
+fn format_vec3(v: Vec3) -> String {
+    format!("{:.3} {:.3} {:.3}", v.x, v.y, v.z)
+}
+
+/// 解析以空格或逗号分隔的三个数
+fn parse_vec3(text: &str) -> Option<Vec3> {
+    let values: Vec<f32> = text
+        .split(|c: char| c.is_whitespace() || c == ',')
+        .filter(|s| !s.is_empty())
+        .map(|s| s.parse().ok())
+        .collect::<Option<_>>()?;
+    match values[..] {
+        [x, y, z] => Some(Vec3::new(x, y, z)),
+        _ => None,
+    }
+}
+
+/// 检查器选中实体的视口高亮
+///
+/// 将实体及其子孙实体的原点投影到屏幕，在包围它们的矩形四角绘制括号形标记，并在实体原点处绘制一个方点
+///
+/// # 注解
+///
+/// 该类型只能在渲染线程中创建、使用与释放
+pub struct InspectorHighlight {
+    /// 标记颜色
+    pub color: Vec4,
+    batch: SpriteBatch,
+    white: Texture2D,
+}
+
+impl InspectorHighlight {
+    /// 创建视口高亮
+    ///
+    /// # 返回值
+    /// 成功时返回视口高亮，内置着色器编译失败时返回错误
+    pub fn new() -> Result<Self> {
+        Ok(Self {
+            color: Vec4::new(1.0, 0.6, 0.1, 1.0),
+            batch: SpriteBatch::new()?,
+            white: Texture2D::from_rgba8(1, 1, &[255; 4], false),
+        })
+    }
+
+    /// 标出实体
+    ///
+    /// # 参数
+    /// + `scene` - 场景
+    /// + `entity` - 实体，不存在或位于相机之后时不绘制
+    /// + `view_projection` - 相机的视图投影矩阵
+    pub fn draw(&mut self, scene: &Scene, entity: Entity, view_projection: &Mat4) {
+        let (w, h) = App::window_size();
+        let window = Vec2::new(w as f32, h as f32);
+        let project = |e: Entity| {
+            let clip = *view_projection * scene.world_matrix(e)?.w_axis;
+            if clip.w <= 0.0 {
+                return None;
+            }
+            let ndc = clip.truncate() / clip.w;
+            Some((Vec2::new(ndc.x, ndc.y) + 1.0) * 0.5 * window)
+        };
+        let Some(origin) = project(entity) else {
+            return;
+        };
+        let mut min = origin;
+        let mut max = origin;
+        let mut stack = scene
+            .get(entity)
+            .map(|n| n.children().to_vec())
+            .unwrap_or_default();
+        while let Some(e) = stack.pop() {
+            if let Some(p) = project(e) {
+                min = min.min(p);
+                max = max.max(p);
+            }
+            stack.extend(
+                scene
+                    .get(e)
+                    .map(|n| n.children().to_vec())
+                    .unwrap_or_default(),
+            );
+        }
+        let (content_scale, _) = App::content_scale();
+        let pad = Vec2::splat((MIN_HIGHLIGHT * content_scale - (max - min).min_element()) * 0.5);
+        min -= pad.max(Vec2::splat(4.0 * content_scale));
+        max += pad.max(Vec2::splat(4.0 * content_scale));
+
+        let thickness = (2.0 * content_scale).round().max(1.0);
+        let arm = ((max - min).min_element() * 0.25).max(thickness);
+        let projection = orthographic(0.0, window.x, 0.0, window.y, -1.0, 1.0);
+        self.batch.begin(&projection);
+        let white = self.white.id();
+        let color = self.color;
+        let mut rect = |min: Vec2, size: Vec2| {
+            let sprite = Sprite {
+                color,
+                ..Sprite::new(min, min + size)
+            };
+            self.batch.draw(white, &sprite);
+        };
+        for (corner, dir) in [
+            (min, Vec2::new(1.0, 1.0)),
+            (Vec2::new(max.x, min.y), Vec2::new(-1.0, 1.0)),
+            (Vec2::new(min.x, max.y), Vec2::new(1.0, -1.0)),
+            (max, Vec2::new(-1.0, -1.0)),
+        ] {
+            // 水平与竖直两条边，从角向内延伸
+            let h = Vec2::new(arm * dir.x, thickness * dir.y);
+            let v = Vec2::new(thickness * dir.x, arm * dir.y);
+            rect(corner.min(corner + h), h.abs());
+            rect(corner.min(corner + v), v.abs());
+        }
+        let dot = Vec2::splat(thickness * 2.0);
+        rect(origin - dot * 0.5, dot);
+        self.batch.end();
+    }
+}
@@ -0,0 +1,111 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread::{spawn, JoinHandle};
+
+use crate::{debug, App};
+
+type Job = Box<dyn FnOnce() + Send>;
+
+/// 后台任务线程池
+///
+/// 由固定数量的工作线程按提交顺序执行任务，用于地形生成、网格构建、资源解码等不需要 OpenGL 上下文的耗时工作，
+/// 避免阻塞渲染循环。任务结果可通过通道传回，需要上传到 GPU 的数据再经 [`App::run_on_render_thread`] 交给渲染线程
+///
+/// # 示例
+///
+/// ```
+/// use std::sync::mpsc::channel;
+/// use gle::*;
+///
+/// let pool = JobPool::new(2);
+/// let (sender, receiver) = channel();
+/// for i in 0..4 {
+///     let sender = sender.clone();
+///     pool.spawn(move || sender.send(i * i).unwrap());
+/// }
+/// let sum: i32 = receiver.iter().take(4).sum();
+/// assert_eq!(sum, 14);
+/// ```
+///
+/// # 注解
+///
+/// 线程池被释放时等待已提交的任务全部执行完毕
+pub struct JobPool {
+    sender: Option<Sender<Job>>,
+    workers: Vec<JoinHandle<()>>,
+    pending: Arc<AtomicUsize>,
+}
+
+impl JobPool {
+    /// 创建线程池
+    ///
+    /// # 参数
+    /// + `threads` - 工作线程数量，至少为 1
+    pub fn new(threads: usize) -> Self {
+        let (sender, receiver) = channel::<Job>();
+        let receiver = Arc::new(Mutex::new(receiver));
+        let pending = Arc::new(AtomicUsize::new(0));
+        let workers = (0..threads.max(1))
+            .map(|i| {
+                let receiver: Arc<Mutex<Receiver<Job>>> = receiver.clone();
+                let pending = pending.clone();
+                spawn(move || {
+                    App::set_current_thread_name(&format!("Worker-{}", i));
+                    loop {
+                        let job = receiver.lock().unwrap().recv();
+                        let Ok(job) = job else {
+                            break;
+                        };
+                        job();
+                        pending.fetch_sub(1, Ordering::AcqRel);
+                    }
+                })
+            })
+            .collect();
+        debug!(Self, "已启动 {} 个工作线程", threads.max(1));
+        Self {
+            sender: Some(sender),
+            workers,
+            pending,
+        }
+    }
+
+    /// 获取工作线程数量
+    pub fn threads(&self) -> usize {
+        self.workers.len()
+    }
+
+    /// 获取已提交但尚未执行完毕的任务数量
+    pub fn pending(&self) -> usize {
+        self.pending.load(Ordering::Acquire)
+    }
+
+    /// 提交任务
+    ///
+    /// # 参数
+    /// + `job` - 任务，将在某个工作线程中执行
+    pub fn spawn<F: 'static + FnOnce() + Send>(&self, job: F) {
+        self.pending.fetch_add(1, Ordering::AcqRel);
+        if let Some(sender) = &self.sender {
+            sender.send(Box::new(job)).unwrap();
+        }
+    }
+}
+
+impl Default for JobPool {
+    /// 创建工作线程数量为 CPU 核心数减 1 的线程池，为渲染线程与事件线程留出余量
+    fn default() -> Self {
+        let cores = std::thread::available_parallelism().map_or(2, |n| n.get());
+        Self::new(cores.saturating_sub(1))
+    }
+}
+
+impl Drop for JobPool {
+    fn drop(&mut self) {
+        self.sender.take();
+        for worker in self.workers.drain(..) {
+            let _ = worker.join();
+        }
+    }
+}
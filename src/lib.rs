@@ -1,10 +1,216 @@
 
+mod animation;
 mod app;
+mod assets;
+mod atlas;
+mod audio;
+mod batching;
+mod billboard;
+mod block;
+mod bloom;
+mod buffer;
+mod camera;
+mod camera2d;
+mod character;
+mod cli;
+mod clip_plane;
+mod collision;
+mod compressed;
+mod config;
+mod controller;
+mod debug_draw;
+mod debug_overlay;
+mod debug_view;
+mod decal;
+mod deferred;
+pub mod error;
+mod fence;
+mod fog;
+mod framebuffer;
+mod fullscreen;
+mod fxaa;
+mod gl_objects;
+mod gltf_import;
+mod grid;
+mod hdr;
+mod indirect;
+mod input;
+mod inspector;
+mod jobs;
+mod lighting;
+mod locale;
+mod lod;
+mod material;
 pub mod log;
+pub mod math;
+mod mesh;
+mod netsync;
+mod noise;
+mod obj;
+mod occlusion;
+mod oit;
+mod outline;
+mod packet;
+mod particles;
+mod pbo;
+mod pbr;
+#[cfg(feature = "rapier")]
+mod physics;
+mod picking;
+mod planar_reflection;
+mod plugin;
+mod postprocess;
+mod prefab;
+mod primitives;
+mod query;
+mod reflection_probe;
+mod region;
+mod render_graph;
+mod render_queue;
+mod render_scale;
+mod render_target;
+mod renderer;
+mod resources;
+mod sampler;
+mod save;
+mod scene;
+mod scene_file;
+#[cfg(feature = "rhai")]
+mod script;
+mod shader;
+mod shadow;
+mod shaping;
+mod sky;
+mod spatial;
+mod sprite;
+mod ssao;
+mod stats;
+mod stream_buffer;
+mod streaming;
+mod text;
+mod texture;
+mod tilemap;
+mod time_of_day;
+mod tonemap;
+mod transform_feedback;
+mod ui;
+mod ui_layout;
+mod upload;
+mod viewport;
+mod voxel;
+mod voxel_mesh;
+mod voxel_render;
+mod voxel_stream;
+mod water;
+mod window_state;
 
+pub use animation::*;
 pub use app::*;
+pub use assets::*;
+pub use atlas::*;
+pub use audio::*;
+pub use batching::*;
+pub use billboard::*;
+pub use block::*;
+pub use bloom::*;
+pub use buffer::*;
+pub use camera::*;
+pub use camera2d::*;
+pub use character::*;
+pub use cli::*;
+pub use clip_plane::*;
+pub use collision::*;
+pub use compressed::*;
+pub use config::*;
+pub use controller::*;
+pub use debug_draw::*;
+pub use debug_overlay::*;
+pub use debug_view::*;
+pub use decal::*;
+pub use deferred::*;
+pub use error::Error;
+pub use fence::*;
+pub use fog::*;
+pub use framebuffer::*;
+pub use fullscreen::*;
+pub use fxaa::*;
+pub use gl_objects::*;
+pub use gltf_import::*;
+pub use grid::*;
+pub use hdr::*;
+pub use indirect::*;
+pub use input::*;
+pub use inspector::*;
+pub use jobs::*;
+pub use lighting::*;
+pub use locale::*;
+pub use lod::*;
+pub use material::*;
 pub use log::*;
+pub use mesh::*;
+pub use netsync::*;
+pub use noise::*;
+pub use obj::*;
+pub use occlusion::*;
+pub use oit::*;
+pub use outline::*;
+pub use packet::*;
+pub use particles::*;
+pub use pbo::*;
+pub use pbr::*;
+#[cfg(feature = "rapier")]
+pub use physics::*;
+pub use picking::*;
+pub use planar_reflection::*;
+pub use plugin::*;
+pub use postprocess::*;
+pub use prefab::*;
+pub use query::*;
+pub use reflection_probe::*;
+pub use region::*;
+pub use render_graph::*;
+pub use render_queue::*;
+pub use render_scale::*;
+pub use render_target::*;
+pub use renderer::*;
+pub use resources::*;
+pub use sampler::*;
+pub use save::*;
+pub use scene::*;
+pub use scene_file::*;
+#[cfg(feature = "rhai")]
+pub use script::*;
+pub use shader::*;
+pub use shadow::*;
+pub use shaping::*;
+pub use sky::*;
+pub use spatial::*;
+pub use sprite::*;
+pub use ssao::*;
+pub use stats::*;
+pub use stream_buffer::*;
+pub use streaming::*;
+pub use text::*;
+pub use texture::*;
+pub use tilemap::*;
+pub use time_of_day::*;
+pub use tonemap::*;
+pub use transform_feedback::*;
+pub use ui::*;
+pub use ui_layout::*;
+pub use upload::*;
+pub use viewport::*;
+pub use voxel::*;
+pub use voxel_mesh::*;
+pub use voxel_render::*;
+pub use voxel_stream::*;
+pub use water::*;
+pub use window_state::*;
 
 pub use gom::{id, Registry};
+#[cfg(feature = "rapier")]
+pub use rapier3d;
+#[cfg(feature = "rhai")]
+pub use rhai;
 /// 窗口实例类型
 pub type Window = glfw::PWindow;
\ No newline at end of file
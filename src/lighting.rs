@@ -0,0 +1,434 @@
+use crate::math::*;
+use crate::{Camera, Scene, ShadowSettings, UniformBuffer, MAX_SHADOWS};
+
+/// 光源 uniform 块可容纳的最大光源数量
+pub const MAX_LIGHTS: usize = 16;
+
+/// 光源 uniform 块`Lights`的绑定点
+pub const LIGHTS_BINDING: u32 = 0;
+
+/// 光源 uniform 块及光照求和函数，可被自定义着色器通过字符串拼接复用
+///
+/// 依赖 [`PBR_BRDF_GLSL`](crate::PBR_BRDF_GLSL) 中的`evaluateBRDF`，
+/// 提供`vec3 evaluateLights(vec3 N, vec3 V, vec3 P, vec3 albedo, float metallic, float roughness, vec3 F0)`。
+/// 使用该代码的着色器程序需将`Shadows`块绑定到 [`SHADOWS_BINDING`](crate::SHADOWS_BINDING)，
+/// 并将`uShadowMap`设置为 [`SHADOW_TEXTURE_UNIT`](crate::SHADOW_TEXTURE_UNIT)
+pub const LIGHTING_GLSL: &str = r#"
+#define MAX_LIGHTS 16
+#define LIGHT_DIRECTIONAL 0
+#define LIGHT_POINT 1
+#define LIGHT_SPOT 2
+#define MAX_SHADOWS 8
+
+struct Light {
+    vec4 positionRange;
+    vec4 directionType;
+    vec4 colorIntensity;
+    vec4 spot;
+};
+
+layout (std140) uniform Lights {
+    Light uLights[MAX_LIGHTS];
+    ivec4 uLightCount;
+    vec4 uAmbient;
+};
+
+layout (std140) uniform Shadows {
+    mat4 uShadowMatrices[MAX_SHADOWS];
+    vec4 uShadowParams[MAX_SHADOWS];
+    vec4 uCascadeSplits[MAX_SHADOWS];
+    mat4 uCameraView;
+    vec4 uShadowDebug;
+};
+
+uniform sampler2DArrayShadow uShadowMap;
+
+vec3 gCascadeTint = vec3(1.0);
+
+// 以 PCF 过滤计算阴影系数，1 为完全照亮；index 为光源的首个阴影贴图层，级联时按视深选择层
+float shadowFactor(int index, vec3 P, vec3 N, vec3 L)
+{
+    if (index < 0) {
+        return 1.0;
+    }
+    int cascades = int(uShadowParams[index].w);
+    if (cascades > 1) {
+        float depth = -(uCameraView * vec4(P, 1.0)).z;
+        int cascade = cascades - 1;
+        for (int c = 0; c < cascades; ++c) {
+            if (depth < uCascadeSplits[index + c].x) {
+                cascade = c;
+                break;
+            }
+        }
+        if (depth >= uCascadeSplits[index + cascades - 1].x) {
+            return 1.0;
+        }
+        if (uShadowDebug.x > 0.5) {
+            const vec3 tints[4] = vec3[4](vec3(1.0, 0.4, 0.4), vec3(0.4, 1.0, 0.4), vec3(0.4, 0.4, 1.0), vec3(1.0, 1.0, 0.4));
+            gCascadeTint = tints[cascade];
+        }
+        index += cascade;
+    }
+    vec4 params = uShadowParams[index];
+    float NdotL = clamp(dot(N, L), 0.0, 1.0);
+    vec3 offsetP = P + N * params.y * (1.0 - NdotL);
+    vec4 clip = uShadowMatrices[index] * vec4(offsetP, 1.0);
+    vec3 proj = clip.xyz / clip.w * 0.5 + 0.5;
+    if (proj.z > 1.0) {
+        return 1.0;
+    }
+    float bias = params.x * mix(2.0, 0.5, NdotL);
+    vec2 texel = 1.0 / vec2(textureSize(uShadowMap, 0).xy);
+    int radius = int(params.z);
+    float sum = 0.0;
+    for (int x = -radius; x <= radius; ++x) {
+        for (int y = -radius; y <= radius; ++y) {
+            vec2 uv = proj.xy + vec2(x, y) * texel;
+            sum += texture(uShadowMap, vec4(uv, float(index), proj.z - bias));
+        }
+    }
+    float side = float(2 * radius + 1);
+    return sum / (side * side);
+}
+
+float lightAttenuation(float distance, float range)
+{
+    float ratio = distance / range;
+    float window = clamp(1.0 - ratio * ratio * ratio * ratio, 0.0, 1.0);
+    return window * window / (distance * distance + 1.0);
+}
+
+// 第 i 个光源在 P 处的入射方向与辐照度
+vec3 lightRadiance(int i, vec3 P, out vec3 L)
+{
+    Light light = uLights[i];
+    int type = int(light.directionType.w);
+    vec3 radiance = light.colorIntensity.rgb * light.colorIntensity.a;
+    if (type == LIGHT_DIRECTIONAL) {
+        L = -normalize(light.directionType.xyz);
+        return radiance;
+    }
+    vec3 toLight = light.positionRange.xyz - P;
+    float distance = length(toLight);
+    L = toLight / max(distance, 1e-4);
+    radiance *= lightAttenuation(distance, light.positionRange.w);
+    if (type == LIGHT_SPOT) {
+        float cosAngle = dot(-L, normalize(light.directionType.xyz));
+        radiance *= smoothstep(light.spot.y, light.spot.x, cosAngle);
+    }
+    return radiance;
+}
+
+vec3 evaluateLights(vec3 N, vec3 V, vec3 P, vec3 albedo, float metallic, float roughness, vec3 F0)
+{
+    vec3 Lo = vec3(0.0);
+    for (int i = 0; i < uLightCount.x; ++i) {
+        vec3 L;
+        vec3 radiance = lightRadiance(i, P, L);
+        radiance *= shadowFactor(int(uLights[i].spot.z), P, N, L);
+        Lo += evaluateBRDF(N, V, L, albedo, metallic, roughness, F0) * radiance;
+    }
+    return Lo * gCascadeTint;
+}
+"#;
+
+/// 光源类型
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LightKind {
+    /// 平行光，方向为节点的前方向(`-Z`)
+    Directional,
+    /// 点光源
+    Point {
+        /// 影响范围，超出该距离的辐照度为零
+        range: f32,
+    },
+    /// 聚光灯，方向为节点的前方向(`-Z`)
+    Spot {
+        /// 影响范围
+        range: f32,
+        /// 内锥角，单位为弧度，锥角内为全亮度
+        inner_angle: f32,
+        /// 外锥角，单位为弧度，锥角外亮度为零
+        outer_angle: f32,
+    },
+}
+
+/// 光源组件
+///
+/// 插入到 [`Scene`] 的节点上，位置与方向取自节点的世界变换
+///
+/// # 示例
+///
+/// ```
+/// use gle::{*, math::*};
+///
+/// let mut scene = Scene::new();
+/// let sun = scene.spawn("sun", Transform::from_rotation(Quat::from_rotation_x(-1.0)));
+/// scene.insert(sun, Light::directional(Vec3::ONE, 3.0));
+/// let lamp = scene.spawn("lamp", Transform::from_translation(Vec3::new(0.0, 2.0, 0.0)));
+/// scene.insert(lamp, Light::point(Vec3::new(1.0, 0.8, 0.6), 10.0, 8.0));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Light {
+    /// 光源类型
+    pub kind: LightKind,
+    /// 颜色(线性空间)
+    pub color: Vec3,
+    /// 强度
+    pub intensity: f32,
+    /// 优先级，光源数量超出上限时优先保留优先级高的光源
+    pub priority: i32,
+    /// 是否启用
+    pub enabled: bool,
+    /// 阴影设置，为`None`时不投射阴影，点光源的阴影暂不支持
+    pub shadow: Option<ShadowSettings>,
+}
+
+impl Light {
+    /// 创建平行光
+    pub fn directional(color: Vec3, intensity: f32) -> Self {
+        Self::new(LightKind::Directional, color, intensity)
+    }
+
+    /// 创建点光源
+    ///
+    /// # 参数
+    /// + `color` - 颜色
+    /// + `intensity` - 强度
+    /// + `range` - 影响范围
+    pub fn point(color: Vec3, intensity: f32, range: f32) -> Self {
+        Self::new(LightKind::Point { range }, color, intensity)
+    }
+
+    /// 创建聚光灯
+    ///
+    /// # 参数
+    /// + `color` - 颜色
+    /// + `intensity` - 强度
+    /// + `range` - 影响范围
+    /// + `inner_angle` - 内锥角，单位为弧度
+    /// + `outer_angle` - 外锥角，单位为弧度
+    pub fn spot(color: Vec3, intensity: f32, range: f32, inner_angle: f32, outer_angle: f32) -> Self {
+        Self::new(
+            LightKind::Spot {
+                range,
+                inner_angle,
+                outer_angle,
+            },
+            color,
+            intensity,
+        )
+    }
+
+    fn new(kind: LightKind, color: Vec3, intensity: f32) -> Self {
+        Self {
+            kind,
+            color,
+            intensity,
+            priority: 0,
+            enabled: true,
+            shadow: None,
+        }
+    }
+
+    /// 启用阴影
+    ///
+    /// # 参数
+    /// + `settings` - 阴影设置
+    pub fn with_shadow(mut self, settings: ShadowSettings) -> Self {
+        self.shadow = Some(settings);
+        self
+    }
+
+    /// 获取影响范围，平行光返回正无穷
+    pub fn range(&self) -> f32 {
+        match self.kind {
+            LightKind::Directional => f32::INFINITY,
+            LightKind::Point { range } | LightKind::Spot { range, .. } => range,
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+struct GpuLight {
+    position_range: Vec4,
+    direction_type: Vec4,
+    color_intensity: Vec4,
+    spot: Vec4,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+struct LightBlock {
+    lights: [GpuLight; MAX_LIGHTS],
+    count: IVec4,
+    ambient: Vec4,
+}
+
+/// 每帧选出的有效光源
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ActiveLight {
+    /// 光源组件
+    pub light: Light,
+    /// 世界空间位置
+    pub position: Vec3,
+    /// 世界空间方向
+    pub direction: Vec3,
+    /// 首个阴影贴图层序号，使用级联的平行光占用从该序号开始的连续多层，不投射阴影时为`None`
+    pub shadow_index: Option<usize>,
+}
+
+/// 光照系统
+///
+/// 每帧从场景中收集光源，按优先级与对摄像机的影响程度选出不超过上限的光源，
+/// 写入绑定点为 [`LIGHTS_BINDING`] 的 uniform 缓冲，供 PBR 等有光照材质使用
+///
+/// # 示例
+///
+/// ```
+/// use gle::*;
+///
+/// // 在渲染线程中
+/// let mut lighting = LightingSystem::new();
+/// lighting.update(&scene, &camera);
+/// queue.execute(&camera);
+/// ```
+///
+/// # 注解
+///
+/// 该类型只能在渲染线程中创建、使用与释放
+#[derive(Debug)]
+pub struct LightingSystem {
+    ubo: UniformBuffer,
+    active: Vec<ActiveLight>,
+    /// 环境光颜色，仅在未使用 IBL 的材质中生效(默认值为`(0.03, 0.03, 0.03)`)
+    pub ambient: Vec3,
+    /// 每帧最多使用的光源数量，不超过 [`MAX_LIGHTS`]
+    pub max_lights: usize,
+}
+
+impl LightingSystem {
+    /// 创建光照系统
+    pub fn new() -> Self {
+        Self {
+            ubo: UniformBuffer::new::<LightBlock>(),
+            active: Vec::new(),
+            ambient: Vec3::splat(0.03),
+            max_lights: MAX_LIGHTS,
+        }
+    }
+
+    /// 获取本帧选出的光源，顺序与 uniform 块中的顺序一致
+    pub fn active_lights(&self) -> &[ActiveLight] {
+        &self.active
+    }
+
+    /// 收集场景中的光源并更新 uniform 缓冲
+    ///
+    /// # 参数
+    /// + `scene` - 场景，应已调用 [`Scene::update_world_transforms`]
+    /// + `camera` - 摄像机，用于估计光源的重要程度
+    ///
+    /// # 注解
+    ///
+    /// 光源按优先级从高到低排序，优先级相同时平行光在前，其余光源按其在摄像机处的估计辐照度从高到低排序。
+    /// 投射阴影的光源按该顺序分配阴影贴图层，直到 [`MAX_SHADOWS`] 层用尽
+    pub fn update(&mut self, scene: &Scene, camera: &Camera) {
+        let mut candidates: Vec<(i32, f32, ActiveLight)> = scene
+            .query::<Light>()
+            .filter(|(_, light)| light.enabled)
+            .filter_map(|(entity, light)| {
+                let world = scene.get(entity)?.world_matrix();
+                let position = world.w_axis.truncate();
+                let direction = world.transform_vector3(Vec3::NEG_Z).normalize_or_zero();
+                let importance = match light.kind {
+                    LightKind::Directional => f32::INFINITY,
+                    _ => {
+                        let distance = position.distance(camera.position);
+                        light.intensity * light.color.max_element() / (1.0 + distance * distance)
+                    }
+                };
+                Some((
+                    light.priority,
+                    importance,
+                    ActiveLight {
+                        light: *light,
+                        position,
+                        direction,
+                        shadow_index: None,
+                    },
+                ))
+            })
+            .collect();
+        candidates.sort_by(|a, b| b.0.cmp(&a.0).then(b.1.total_cmp(&a.1)));
+        self.active.clear();
+        self.active.extend(
+            candidates
+                .into_iter()
+                .take(self.max_lights.min(MAX_LIGHTS))
+                .map(|(_, _, light)| light),
+        );
+        let mut layers = 0;
+        for active in &mut self.active {
+            let Some(settings) = active.light.shadow else {
+                continue;
+            };
+            let count = settings.layer_count(active.light.kind);
+            if count > 0 && layers + count <= MAX_SHADOWS {
+                active.shadow_index = Some(layers);
+                layers += count;
+            }
+        }
+        self.upload();
+    }
+
+    fn upload(&self) {
+        let mut block = LightBlock {
+            lights: [GpuLight::default(); MAX_LIGHTS],
+            count: IVec4::new(self.active.len() as i32, 0, 0, 0),
+            ambient: self.ambient.extend(1.0),
+        };
+        for (gpu, active) in block.lights.iter_mut().zip(&self.active) {
+            let light = &active.light;
+            let (kind, spot) = match light.kind {
+                LightKind::Directional => (0.0, Vec4::ZERO),
+                LightKind::Point { .. } => (1.0, Vec4::ZERO),
+                LightKind::Spot {
+                    inner_angle,
+                    outer_angle,
+                    ..
+                } => (
+                    2.0,
+                    Vec4::new(inner_angle.cos(), outer_angle.cos(), 0.0, 0.0),
+                ),
+            };
+            let shadow = active.shadow_index.map_or(-1.0, |i| i as f32);
+            let spot = Vec4::new(spot.x, spot.y, shadow, 0.0);
+            *gpu = GpuLight {
+                position_range: active.position.extend(light.range().min(f32::MAX)),
+                direction_type: active.direction.extend(kind),
+                color_intensity: light.color.extend(light.intensity),
+                spot,
+            };
+        }
+        self.ubo.update(&block);
+        self.ubo.bind_base(LIGHTS_BINDING);
+    }
+
+    /// 将光源 uniform 缓冲重新绑定到 [`LIGHTS_BINDING`]
+    ///
+    /// # 注解
+    ///
+    /// [`LightingSystem::update`] 已自动绑定，仅在其他代码占用了该绑定点后需要调用
+    pub fn bind(&self) {
+        self.ubo.bind_base(LIGHTS_BINDING);
+    }
+}
+
+impl Default for LightingSystem {
+    fn default() -> Self {
+        Self::new()
+    }
+}
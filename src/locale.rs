@@ -0,0 +1,477 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+
+use lazy_static::lazy_static;
+
+use crate::error::{Error, Result};
+use crate::info;
+
+lazy_static! {
+    static ref LOCALE: RwLock<LocaleState> = RwLock::new(LocaleState::default());
+}
+
+#[derive(Default)]
+struct LocaleState {
+    current: Option<StringTable>,
+    fallback: Option<StringTable>,
+    generation: u64,
+}
+
+/// 复数类别，与 Unicode CLDR 的定义一致
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PluralCategory {
+    /// 零
+    Zero,
+    /// 单数
+    One,
+    /// 双数
+    Two,
+    /// 少数
+    Few,
+    /// 多数
+    Many,
+    /// 其他，所有语言都必须提供
+    Other,
+}
+
+impl PluralCategory {
+    fn from_key(key: &str) -> Option<Self> {
+        Some(match key {
+            "zero" => Self::Zero,
+            "one" => Self::One,
+            "two" => Self::Two,
+            "few" => Self::Few,
+            "many" => Self::Many,
+            "other" => Self::Other,
+            _ => return None,
+        })
+    }
+
+    /// 按语言的复数规则获取整数对应的复数类别
+    ///
+    /// # 参数
+    /// + `language` - 语言标签，如`en`、`zh-CN`，只使用主语言部分
+    /// + `n` - 数量
+    ///
+    /// # 注解
+    ///
+    /// 内置中日韩、英德等日耳曼与罗曼语族、法语、俄语等东斯拉夫语、波兰语、捷克语与阿拉伯语的整数规则，
+    /// 其他语言按英语规则处理
+    pub fn of(language: &str, n: i64) -> Self {
+        let primary = language.split(['-', '_']).next().unwrap_or("").to_ascii_lowercase();
+        let n = n.unsigned_abs();
+        let (n10, n100) = (n % 10, n % 100);
+        match primary.as_str() {
+            "zh" | "ja" | "ko" | "vi" | "th" | "id" | "ms" => Self::Other,
+            "fr" | "pt" if n <= 1 => Self::One,
+            "fr" | "pt" => Self::Other,
+            "ru" | "uk" | "be" => {
+                if n10 == 1 && n100 != 11 {
+                    Self::One
+                } else if (2..=4).contains(&n10) && !(12..=14).contains(&n100) {
+                    Self::Few
+                } else {
+                    Self::Many
+                }
+            }
+            "pl" => {
+                if n == 1 {
+                    Self::One
+                } else if (2..=4).contains(&n10) && !(12..=14).contains(&n100) {
+                    Self::Few
+                } else {
+                    Self::Many
+                }
+            }
+            "cs" | "sk" => match n {
+                1 => Self::One,
+                2..=4 => Self::Few,
+                _ => Self::Other,
+            },
+            "ar" => match (n, n100) {
+                (0, _) => Self::Zero,
+                (1, _) => Self::One,
+                (2, _) => Self::Two,
+                (_, 3..=10) => Self::Few,
+                (_, 11..=99) => Self::Many,
+                _ => Self::Other,
+            },
+            _ if n == 1 => Self::One,
+            _ => Self::Other,
+        }
+    }
+}
+
+/// 字符串表中的一条消息
+#[derive(Debug, Clone, PartialEq)]
+pub enum Message {
+    /// 普通文本
+    Text(String),
+    /// 按数量选择的复数形式，必须包含`Other`
+    Plural(HashMap<PluralCategory, String>),
+}
+
+/// 一种语言的字符串表
+///
+/// 由 TOML 文件描述，键为消息ID，值为可以包含`{参数}`占位符的文本；`{{`与`}}`表示花括号本身。
+/// 键只包含复数类别(`zero`、`one`、`two`、`few`、`many`、`other`)的表表示复数消息，
+/// 以`count`参数选择形式；其他表作为命名空间，其中的键以`.`连接
+///
+/// ```toml
+/// title = "My Game"
+///
+/// [menu]
+/// start = "Start"
+/// greeting = "Welcome back, {name}!"
+///
+/// [coins]
+/// one = "{count} coin"
+/// other = "{count} coins"
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct StringTable {
+    language: String,
+    path: Option<PathBuf>,
+    messages: HashMap<String, Message>,
+}
+
+impl StringTable {
+    /// 解析字符串表
+    ///
+    /// # 参数
+    /// + `language` - 语言标签，用于选择复数规则
+    /// + `src` - TOML 文本
+    pub fn parse(language: &str, src: &str) -> Result<Self> {
+        let table = toml::from_str::<toml::Table>(src).map_err(|e| Error::Parse(e.to_string()))?;
+        let mut messages = HashMap::new();
+        Self::collect("", &table, &mut messages)?;
+        Ok(Self {
+            language: language.to_string(),
+            path: None,
+            messages,
+        })
+    }
+
+    /// 加载字符串表
+    ///
+    /// # 参数
+    /// + `path` - TOML 文件路径，文件名(不含扩展名)作为语言标签，如`locales/zh-CN.toml`
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let language = path.file_stem().unwrap_or_default().to_string_lossy().to_string();
+        let src = fs::read_to_string(path)?;
+        let mut table = Self::parse(&language, &src)
+            .map_err(|e| Error::Parse(format!("{}: {}", path.display(), e)))?;
+        table.path = Some(path.to_path_buf());
+        Ok(table)
+    }
+
+    fn collect(
+        prefix: &str,
+        table: &toml::Table,
+        out: &mut HashMap<String, Message>,
+    ) -> Result<()> {
+        for (key, value) in table {
+            let key = if prefix.is_empty() {
+                key.clone()
+            } else {
+                format!("{}.{}", prefix, key)
+            };
+            match value {
+                toml::Value::String(text) => {
+                    out.insert(key, Message::Text(text.clone()));
+                }
+                toml::Value::Table(inner)
+                    if !inner.is_empty()
+                        && inner.keys().all(|k| PluralCategory::from_key(k).is_some()) =>
+                {
+                    let mut forms = HashMap::new();
+                    for (category, text) in inner {
+                        let text = text.as_str().ok_or_else(|| {
+                            Error::Parse(format!("{}.{} 应为字符串", key, category))
+                        })?;
+                        forms.insert(PluralCategory::from_key(category).unwrap(), text.to_string());
+                    }
+                    if !forms.contains_key(&PluralCategory::Other) {
+                        return Err(Error::Parse(format!("复数消息 {} 缺少 other", key)));
+                    }
+                    out.insert(key, Message::Plural(forms));
+                }
+                toml::Value::Table(inner) => Self::collect(&key, inner, out)?,
+                _ => return Err(Error::Parse(format!("{} 应为字符串或表", key))),
+            }
+        }
+        Ok(())
+    }
+
+    /// 获取语言标签
+    pub fn language(&self) -> &str {
+        &self.language
+    }
+
+    /// 获取加载时的文件路径，由文本解析时返回`None`
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    /// 获取消息数量
+    pub fn len(&self) -> usize {
+        self.messages.len()
+    }
+
+    /// 是否不含任何消息
+    pub fn is_empty(&self) -> bool {
+        self.messages.is_empty()
+    }
+
+    /// 获取消息
+    pub fn get(&self, key: &str) -> Option<&Message> {
+        self.messages.get(key)
+    }
+
+    /// 格式化消息
+    ///
+    /// # 参数
+    /// + `key` - 消息ID
+    /// + `args` - 参数名与参数值，复数消息以`count`参数的整数部分选择形式
+    ///
+    /// # 返回值
+    /// 消息不存在时返回`None`，缺少的参数保留占位符原样输出
+    pub fn format(&self, key: &str, args: &[(&str, String)]) -> Option<String> {
+        let pattern = match self.messages.get(key)? {
+            Message::Text(text) => text,
+            Message::Plural(forms) => {
+                let count = args
+                    .iter()
+                    .find(|(name, _)| *name == "count")
+                    .and_then(|(_, value)| value.trim().parse::<f64>().ok())
+                    .unwrap_or(0.0);
+                let category = PluralCategory::of(&self.language, count as i64);
+                forms.get(&category).unwrap_or(&forms[&PluralCategory::Other])
+            }
+        };
+        Some(substitute(pattern, args))
+    }
+}
+
+/// 替换`{参数}`占位符
+fn substitute(pattern: &str, args: &[(&str, String)]) -> String {
+    let mut out = String::with_capacity(pattern.len());
+    let mut rest = pattern;
+    while let Some(i) = rest.find(['{', '}']) {
+        out.push_str(&rest[..i]);
+        let tail = &rest[i..];
+        if tail.starts_with("{{") || tail.starts_with("}}") {
+            out.push_str(&tail[..1]);
+            rest = &tail[2..];
+            continue;
+        }
+        match tail.find('}').filter(|_| tail.starts_with('{')) {
+            Some(end) => {
+                let name = tail[1..end].trim();
+                match args.iter().find(|(arg, _)| *arg == name) {
+                    Some((_, value)) => out.push_str(value),
+                    None => out.push_str(&tail[..=end]),
+                }
+                rest = &tail[end + 1..];
+            }
+            None => {
+                out.push_str(&tail[..1]);
+                rest = &tail[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// 全局本地化
+///
+/// 持有当前语言与后备语言的字符串表，可以在运行时随时切换，切换后 [`Locale::generation`] 递增，
+/// [`LocalizedText`] 据此在下次使用时重新格式化
+///
+/// # 示例
+///
+/// ```no_run
+/// use gle::*;
+///
+/// Locale::set_fallback(StringTable::load("assets/locales/en.toml").unwrap());
+/// Locale::load("assets/locales/zh-CN.toml").unwrap();
+///
+/// let title = tr!("title");
+/// let greeting = tr!("menu.greeting", name = "Alice");
+/// let coins = tr!("coins", count = 3);
+///
+/// // 在设置菜单中切换语言
+/// Locale::load("assets/locales/de.toml").unwrap();
+/// ```
+pub struct Locale;
+
+impl Locale {
+    /// 设置当前语言的字符串表
+    pub fn set(table: StringTable) {
+        let mut state = LOCALE.write().unwrap();
+        info!("Locale", "切换语言为 {}", table.language);
+        state.current = Some(table);
+        state.generation += 1;
+    }
+
+    /// 设置后备语言的字符串表，当前语言缺少的消息从中查找
+    pub fn set_fallback(table: StringTable) {
+        let mut state = LOCALE.write().unwrap();
+        state.fallback = Some(table);
+        state.generation += 1;
+    }
+
+    /// 加载字符串表并设为当前语言
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<()> {
+        Self::set(StringTable::load(path)?);
+        Ok(())
+    }
+
+    /// 重新加载当前语言与后备语言的字符串表文件，用于编辑翻译时的热重载
+    ///
+    /// # 返回值
+    /// 读取或解析失败时返回错误，此时保留原有的字符串表
+    pub fn reload() -> Result<()> {
+        let (current, fallback) = {
+            let state = LOCALE.read().unwrap();
+            let path = |t: &Option<StringTable>| t.as_ref().and_then(|t| t.path.clone());
+            (path(&state.current), path(&state.fallback))
+        };
+        let current = current.map(StringTable::load).transpose()?;
+        let fallback = fallback.map(StringTable::load).transpose()?;
+        let mut state = LOCALE.write().unwrap();
+        if current.is_some() {
+            state.current = current;
+        }
+        if fallback.is_some() {
+            state.fallback = fallback;
+        }
+        state.generation += 1;
+        Ok(())
+    }
+
+    /// 获取当前语言标签，未设置时返回`None`
+    pub fn language() -> Option<String> {
+        LOCALE.read().unwrap().current.as_ref().map(|t| t.language.clone())
+    }
+
+    /// 当前语言是否从右到左书写，此时文本应经 [`TextShaper`](crate::TextShaper) 整形
+    pub fn is_rtl() -> bool {
+        Self::language().is_some_and(|language| {
+            let primary = language.split(['-', '_']).next().unwrap_or("").to_ascii_lowercase();
+            matches!(primary.as_str(), "ar" | "he" | "fa" | "ur" | "yi" | "ps")
+        })
+    }
+
+    /// 获取字符串表的版本号，每次切换或重新加载后递增
+    pub fn generation() -> u64 {
+        LOCALE.read().unwrap().generation
+    }
+
+    /// 翻译消息
+    ///
+    /// # 参数
+    /// + `key` - 消息ID
+    /// + `args` - 参数名与参数值
+    ///
+    /// # 返回值
+    /// 依次在当前语言与后备语言中查找，都不存在时返回消息ID本身
+    pub fn translate(key: &str, args: &[(&str, String)]) -> String {
+        let state = LOCALE.read().unwrap();
+        state
+            .current
+            .iter()
+            .chain(state.fallback.iter())
+            .find_map(|table| table.format(key, args))
+            .unwrap_or_else(|| key.to_string())
+    }
+}
+
+/// 翻译消息
+///
+/// `tr!("key")`翻译无参数的消息，`tr!("key", name = value, ...)`以`Display`格式化参数值后代入，
+/// 复数消息以`count`参数选择形式，见 [`Locale::translate`]
+#[macro_export]
+macro_rules! tr {
+    ($key:expr) => {
+        $crate::Locale::translate($key, &[])
+    };
+    ($key:expr, $($name:ident = $value:expr),+ $(,)?) => {
+        $crate::Locale::translate($key, &[$((stringify!($name), ($value).to_string())),+])
+    };
+}
+
+/// 随语言切换自动更新的文本
+///
+/// 保存消息ID与参数，在语言切换或参数改变后的首次访问时重新格式化，适合界面中长期存在的标签
+///
+/// # 示例
+///
+/// ```no_run
+/// use gle::*;
+/// use gle::math::*;
+///
+/// let mut score = LocalizedText::new("hud.score").with_arg("points", 0);
+///
+/// fn render_loop(text: &mut TextRenderer, font: &SdfFont, score: &mut LocalizedText) {
+///     score.set_arg("points", 1200);
+///     text.begin(&orthographic(0.0, 1280.0, 0.0, 720.0, -1.0, 1.0));
+///     text.draw_localized(font, score, Vec2::new(20.0, 690.0), &TextStyle::default());
+///     text.end();
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct LocalizedText {
+    key: String,
+    args: Vec<(&'static str, String)>,
+    cache: String,
+    generation: Option<u64>,
+}
+
+impl LocalizedText {
+    /// 由消息ID创建
+    pub fn new(key: &str) -> Self {
+        Self {
+            key: key.to_string(),
+            args: Vec::new(),
+            cache: String::new(),
+            generation: None,
+        }
+    }
+
+    /// 添加参数
+    pub fn with_arg(mut self, name: &'static str, value: impl ToString) -> Self {
+        self.set_arg(name, value);
+        self
+    }
+
+    /// 设置参数，值改变时下次访问重新格式化
+    pub fn set_arg(&mut self, name: &'static str, value: impl ToString) {
+        let value = value.to_string();
+        match self.args.iter_mut().find(|(arg, _)| *arg == name) {
+            Some((_, old)) if *old == value => return,
+            Some((_, old)) => *old = value,
+            None => self.args.push((name, value)),
+        }
+        self.generation = None;
+    }
+
+    /// 获取消息ID
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// 获取当前语言下的文本
+    pub fn text(&mut self) -> &str {
+        let generation = Locale::generation();
+        if self.generation != Some(generation) {
+            self.cache = Locale::translate(&self.key, &self.args);
+            self.generation = Some(generation);
+        }
+        &self.cache
+    }
+}
@@ -0,0 +1,184 @@
+use crate::math::*;
+use crate::{Camera, Projection};
+
+/// 细节层次的选择依据
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LodMetric {
+    /// 依据对象中心到摄像机的距离，各层级的阈值为该层级适用的最大距离，应按升序排列
+    Distance,
+    /// 依据对象包围球在屏幕上覆盖的高度比例(`0.0`~`1.0`)，各层级的阈值为该层级适用的最小覆盖比例，应按降序排列
+    ScreenCoverage,
+}
+
+/// 细节层次
+#[derive(Debug, Clone)]
+pub struct LodLevel<M> {
+    /// 该层级使用的网格
+    pub mesh: M,
+    /// 该层级的切换阈值，含义取决于 [`LodMetric`]，最后一个层级的阈值将被忽略
+    pub threshold: f32,
+}
+
+/// 细节层次组
+///
+/// 为同一对象注册多个精细程度不同的网格，并根据摄像机自动选择合适的层级，
+/// 层级切换带有滞后区间，以避免对象在阈值附近来回切换。
+/// 以 [`RenderQueue::submit_lod`](crate::RenderQueue::submit_lod) 提交时，层级在提交过程中自动选择
+///
+/// # 示例
+///
+/// ```
+/// use gle::{*, math::*};
+///
+/// let mut lod = LodGroup::new(LodMetric::Distance)
+///     .with_level("high", 10.0)
+///     .with_level("medium", 50.0)
+///     .with_level("low", 0.0);
+/// let camera = Camera::perspective(1.0, 0.1, 100.0);
+/// assert_eq!(lod.select(&camera, Vec3::new(0.0, 0.0, -5.0), 1.0), Some(&"high"));
+/// ```
+#[derive(Debug, Clone)]
+pub struct LodGroup<M> {
+    levels: Vec<LodLevel<M>>,
+    metric: LodMetric,
+    current: usize,
+    /// 滞后比例，切换到其他层级时需要越过阈值的比例(默认值为0.1)
+    pub hysteresis: f32,
+    /// 阈值缩放系数，大于`1.0`时更倾向于使用精细层级(默认值为1.0)
+    pub bias: f32,
+}
+
+impl<M> LodGroup<M> {
+    /// 创建一个空的细节层次组
+    ///
+    /// # 参数
+    /// + `metric` - 层级的选择依据
+    pub fn new(metric: LodMetric) -> Self {
+        Self {
+            levels: Vec::new(),
+            metric,
+            current: 0,
+            hysteresis: 0.1,
+            bias: 1.0,
+        }
+    }
+
+    /// 添加一个层级
+    ///
+    /// # 参数
+    /// + `mesh` - 网格，应按从精细到粗糙的顺序添加
+    /// + `threshold` - 切换阈值
+    ///
+    /// # 返回值
+    /// 返回`LodGroup`实例本身
+    pub fn with_level(mut self, mesh: M, threshold: f32) -> Self {
+        self.add_level(mesh, threshold);
+        self
+    }
+
+    /// 添加一个层级
+    ///
+    /// # 参数
+    /// + `mesh` - 网格，应按从精细到粗糙的顺序添加
+    /// + `threshold` - 切换阈值
+    pub fn add_level(&mut self, mesh: M, threshold: f32) {
+        self.levels.push(LodLevel { mesh, threshold });
+    }
+
+    /// 获取所有层级
+    pub fn levels(&self) -> &[LodLevel<M>] {
+        &self.levels
+    }
+
+    /// 获取选择依据
+    pub fn metric(&self) -> LodMetric {
+        self.metric
+    }
+
+    /// 获取当前层级的序号
+    pub fn current_level(&self) -> usize {
+        self.current
+    }
+
+    /// 获取当前层级的网格
+    pub fn current(&self) -> Option<&M> {
+        self.levels.get(self.current).map(|l| &l.mesh)
+    }
+
+    /// 计算对象的度量值
+    ///
+    /// # 参数
+    /// + `camera` - 摄像机
+    /// + `center` - 对象包围球的世界空间中心
+    /// + `radius` - 对象包围球的半径
+    pub fn measure(&self, camera: &Camera, center: Vec3, radius: f32) -> f32 {
+        let distance = camera.position.distance(center);
+        match self.metric {
+            LodMetric::Distance => distance,
+            LodMetric::ScreenCoverage => match camera.projection {
+                Projection::Perspective { fov_y, .. } => {
+                    if distance <= radius {
+                        1.0
+                    } else {
+                        (radius / (distance * (fov_y * 0.5).tan())).min(1.0)
+                    }
+                }
+                Projection::Orthographic { height, .. } => (radius * 2.0 / height).min(1.0),
+            },
+        }
+    }
+
+    /// 根据度量值计算层级
+    fn level_for(&self, value: f32, scale: f32) -> usize {
+        let boundaries = &self.levels[..self.levels.len().saturating_sub(1)];
+        match self.metric {
+            LodMetric::Distance => boundaries
+                .iter()
+                .filter(|l| value > l.threshold * self.bias * scale)
+                .count(),
+            LodMetric::ScreenCoverage => boundaries
+                .iter()
+                .filter(|l| value < l.threshold / self.bias * scale)
+                .count(),
+        }
+    }
+
+    /// 根据度量值更新当前层级
+    ///
+    /// # 参数
+    /// + `value` - 由 [`LodGroup::measure`] 计算的度量值
+    ///
+    /// # 返回值
+    /// 返回当前层级的网格，没有任何层级时返回`None`
+    pub fn select_by_value(&mut self, value: f32) -> Option<&M> {
+        if self.levels.is_empty() {
+            return None;
+        }
+        let (coarser_scale, finer_scale) = match self.metric {
+            LodMetric::Distance => (1.0 + self.hysteresis, 1.0 - self.hysteresis),
+            LodMetric::ScreenCoverage => (1.0 - self.hysteresis, 1.0 + self.hysteresis),
+        };
+        let coarser = self.level_for(value, coarser_scale);
+        let finer = self.level_for(value, finer_scale);
+        if coarser > self.current {
+            self.current = coarser;
+        } else if finer < self.current {
+            self.current = finer;
+        }
+        self.current()
+    }
+
+    /// 根据摄像机更新当前层级
+    ///
+    /// # 参数
+    /// + `camera` - 摄像机
+    /// + `center` - 对象包围球的世界空间中心
+    /// + `radius` - 对象包围球的半径
+    ///
+    /// # 返回值
+    /// 返回当前层级的网格，没有任何层级时返回`None`
+    pub fn select(&mut self, camera: &Camera, center: Vec3, radius: f32) -> Option<&M> {
+        let value = self.measure(camera, center, radius);
+        self.select_by_value(value)
+    }
+}
@@ -1,8 +1,14 @@
 use colored::*;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
 use std::sync::Mutex;
 
+/// 内存日志缓冲默认保存的条目数
+const DEFAULT_CAPACITY: usize = 1024;
+
 /// 日志级别标志
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum Level {
     Debug,
     Info,
@@ -10,10 +16,45 @@ pub enum Level {
     Error,
 }
 
+impl std::str::FromStr for Level {
+    type Err = crate::error::Error;
+
+    /// 按名称解析日志级别，不区分大小写
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "debug" => Ok(Level::Debug),
+            "info" => Ok(Level::Info),
+            "warn" | "warning" => Ok(Level::Warn),
+            "error" => Ok(Level::Error),
+            _ => Err(crate::error::Error::Parse(format!("未知的日志级别 {}", s))),
+        }
+    }
+}
+
+/// 内存日志缓冲中的一条日志
+#[derive(Debug, Clone, PartialEq)]
+pub struct LogEntry {
+    /// 序号，从 1 开始按记录顺序递增
+    pub sequence: u64,
+    /// 日志级别
+    pub level: Level,
+    /// 记录时间
+    pub time: chrono::DateTime<chrono::Local>,
+    /// 日志来源
+    pub owner: String,
+    /// 记录日志的线程名称
+    pub thread: String,
+    /// 日志内容
+    pub message: String,
+}
+
 /// 日志记录器
 struct Logger {
     level: Level,
     file: Option<String>,
+    buffer: VecDeque<LogEntry>,
+    capacity: usize,
+    sequence: u64,
 }
 
 impl Logger {
@@ -21,7 +62,35 @@ impl Logger {
         Self {
             level: Level::Info,
             file: None,
+            buffer: VecDeque::new(),
+            capacity: DEFAULT_CAPACITY,
+            sequence: 0,
+        }
+    }
+
+    fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity;
+        while self.buffer.len() > capacity {
+            self.buffer.pop_front();
+        }
+    }
+
+    fn push(&mut self, level: Level, owner: &str, thread: String, message: &str) {
+        self.sequence += 1;
+        if self.capacity == 0 {
+            return;
+        }
+        if self.buffer.len() == self.capacity {
+            self.buffer.pop_front();
         }
+        self.buffer.push_back(LogEntry {
+            sequence: self.sequence,
+            level,
+            time: chrono::Local::now(),
+            owner: owner.to_string(),
+            thread,
+            message: message.to_string(),
+        });
     }
 
     fn set_level(&mut self, level: Level) {
@@ -104,6 +173,21 @@ lazy_static! {
     static ref LOGGER_INIT: Mutex<Logger> = Mutex::new(Logger::new());
 }
 
+/// 日志
+///
+/// 日志同时写入控制台(或文件)与内存中的环形缓冲，界面可以通过 [`Log::recent`] 与 [`Log::since`]
+/// 查询最近的日志，使其在程序内可见
+///
+/// # 示例
+///
+/// ```
+/// use gle::*;
+///
+/// warn!("Example", "纹理 {} 未找到", "grass.png");
+/// for entry in Log::recent(10, Level::Warn) {
+///     println!("[{:?}] {}: {}", entry.level, entry.owner, entry.message);
+/// }
+/// ```
 pub struct Log;
 
 impl Log {
@@ -120,13 +204,76 @@ impl Log {
         let mut logger = LOGGER_INIT.lock().unwrap();
         logger.set_file(file);
     }
+
+    /// 设置内存日志缓冲保存的条目数
+    /// 默认情况下保存最近 1024 条，为 0 时不保存；缓冲只保存达到日志级别的条目
+    ///
+    /// # 参数
+    /// + `capacity` - 最多保存的条目数
+    pub fn set_capacity(capacity: usize) {
+        LOGGER_INIT.lock().unwrap().set_capacity(capacity);
+    }
+
+    /// 获取内存日志缓冲中最近的若干条日志
+    ///
+    /// # 参数
+    /// + `count` - 最多返回的条数
+    /// + `level` - 最低日志级别
+    ///
+    /// # 返回值
+    /// 返回级别不低于`level`的最近`count`条日志，按记录顺序排列
+    pub fn recent(count: usize, level: Level) -> Vec<LogEntry> {
+        let logger = LOGGER_INIT.lock().unwrap();
+        let mut entries: Vec<LogEntry> = logger
+            .buffer
+            .iter()
+            .rev()
+            .filter(|e| e.level >= level)
+            .take(count)
+            .cloned()
+            .collect();
+        entries.reverse();
+        entries
+    }
+
+    /// 获取序号大于指定值的日志，用于增量地刷新控制台
+    ///
+    /// # 参数
+    /// + `sequence` - 上次获取的最后一条日志的序号，首次获取时为 0
+    /// + `level` - 最低日志级别
+    ///
+    /// # 返回值
+    /// 返回仍在缓冲中的新日志，按记录顺序排列
+    pub fn since(sequence: u64, level: Level) -> Vec<LogEntry> {
+        let logger = LOGGER_INIT.lock().unwrap();
+        logger
+            .buffer
+            .iter()
+            .filter(|e| e.sequence > sequence && e.level >= level)
+            .cloned()
+            .collect()
+    }
+
+    /// 获取最后一条日志的序号，尚未记录日志时为 0
+    pub fn last_sequence() -> u64 {
+        LOGGER_INIT.lock().unwrap().sequence
+    }
+
+    /// 清空内存日志缓冲
+    pub fn clear_buffer() {
+        LOGGER_INIT.lock().unwrap().buffer.clear();
+    }
 }
 
 /// 日志输出函数
 pub fn log(level: Level, owner: &str, message: &str) {
-    let logger = LOGGER_INIT.lock().unwrap();
-    let owner = format!("{} @{:<20}", owner, App::current_thread_name());
-    logger.log(level, &owner, message);
+    let mut logger = LOGGER_INIT.lock().unwrap();
+    if level < logger.level {
+        return;
+    }
+    let thread = App::current_thread_name();
+    logger.log(level, &format!("{} @{:<20}", owner, thread), message);
+    logger.push(level, owner, thread, message);
 }
 
 /// 调试日志输出宏
@@ -0,0 +1,323 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Error, Result};
+use crate::math::*;
+use crate::{DebugView, DrawItem, GpuMesh, Program, RenderPass, Texture2D};
+
+/// 材质参数值
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum MaterialValue {
+    /// 布尔值
+    Bool(bool),
+    /// 整数
+    Int(i32),
+    /// 浮点数
+    Float(f32),
+    /// 二维向量
+    Vec2([f32; 2]),
+    /// 三维向量
+    Vec3([f32; 3]),
+    /// 四维向量
+    Vec4([f32; 4]),
+    /// 4x4 矩阵，按列主序排列
+    Mat4([f32; 16]),
+}
+
+impl MaterialValue {
+    /// 将参数值设置到当前着色器程序的指定 uniform
+    ///
+    /// # 参数
+    /// + `program` - 着色器程序
+    /// + `name` - uniform 名称
+    pub fn apply(&self, program: &Program, name: &str) {
+        match *self {
+            MaterialValue::Bool(v) => program.set(name, &v),
+            MaterialValue::Int(v) => program.set(name, &v),
+            MaterialValue::Float(v) => program.set(name, &v),
+            MaterialValue::Vec2(v) => program.set(name, &Vec2::from_array(v)),
+            MaterialValue::Vec3(v) => program.set(name, &Vec3::from_array(v)),
+            MaterialValue::Vec4(v) => program.set(name, &Vec4::from_array(v)),
+            MaterialValue::Mat4(v) => program.set(name, &Mat4::from_cols_array(&v)),
+        }
+    }
+}
+
+macro_rules! material_value_from {
+    ($($t:ty => $variant:ident),* $(,)?) => {
+        $(
+            impl From<$t> for MaterialValue {
+                fn from(v: $t) -> Self {
+                    MaterialValue::$variant(v.into())
+                }
+            }
+        )*
+    };
+}
+
+material_value_from! {
+    bool => Bool,
+    i32 => Int,
+    f32 => Float,
+    Vec2 => Vec2,
+    Vec3 => Vec3,
+    Vec4 => Vec4,
+}
+
+impl From<Mat4> for MaterialValue {
+    fn from(v: Mat4) -> Self {
+        MaterialValue::Mat4(v.to_cols_array())
+    }
+}
+
+/// 材质定义文件中的着色器描述
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ShaderDesc {
+    /// 顶点着色器文件路径，相对于材质文件所在目录
+    pub vertex: PathBuf,
+    /// 片段着色器文件路径，相对于材质文件所在目录
+    pub fragment: PathBuf,
+    /// 着色器变体的宏定义
+    #[serde(default)]
+    pub defines: Vec<String>,
+}
+
+/// 材质定义文件中的纹理描述
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TextureDesc {
+    /// 图像文件路径，相对于材质文件所在目录
+    pub path: PathBuf,
+    /// 是否为 sRGB 颜色空间
+    #[serde(default)]
+    pub srgb: bool,
+}
+
+/// 材质定义
+///
+/// 可以从 TOML 格式的材质文件加载，例如：
+///
+/// ```toml
+/// pass = "Opaque"
+/// receive_decals = true
+///
+/// [shader]
+/// vertex = "lit.vert"
+/// fragment = "lit.frag"
+/// defines = ["USE_ALBEDO_MAP"]
+///
+/// [parameters]
+/// uBaseColor = [1.0, 0.8, 0.6, 1.0]
+/// uRoughness = 0.5
+///
+/// [textures]
+/// uAlbedoMap = { path = "brick.png", srgb = true }
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MaterialDesc {
+    /// 渲染通道
+    #[serde(default = "default_pass")]
+    pub pass: RenderPass,
+    /// 着色器
+    pub shader: ShaderDesc,
+    /// 参数，键为 uniform 名称
+    #[serde(default)]
+    pub parameters: BTreeMap<String, MaterialValue>,
+    /// 纹理，键为采样器 uniform 名称
+    #[serde(default)]
+    pub textures: BTreeMap<String, TextureDesc>,
+    /// 是否接收贴花
+    #[serde(default = "default_receive_decals")]
+    pub receive_decals: bool,
+}
+
+fn default_pass() -> RenderPass {
+    RenderPass::Opaque
+}
+
+fn default_receive_decals() -> bool {
+    true
+}
+
+impl MaterialDesc {
+    /// 从 TOML 源码解析材质定义
+    ///
+    /// # 参数
+    /// + `src` - TOML 源码
+    pub fn parse(src: &str) -> Result<Self> {
+        toml::from_str(src).map_err(|e| Error::Parse(e.to_string()))
+    }
+
+    /// 从材质文件加载材质定义
+    ///
+    /// # 参数
+    /// + `path` - 材质文件路径
+    ///
+    /// # 返回值
+    /// 成功时返回材质定义，其中的相对路径已转换为相对于当前工作目录的路径
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let src = std::fs::read_to_string(path)?;
+        let mut desc = toml::from_str::<Self>(&src)
+            .map_err(|e| Error::Parse(format!("{}: {}", path.display(), e)))?;
+        let dir = path.parent().unwrap_or(Path::new(""));
+        desc.shader.vertex = dir.join(&desc.shader.vertex);
+        desc.shader.fragment = dir.join(&desc.shader.fragment);
+        for texture in desc.textures.values_mut() {
+            texture.path = dir.join(&texture.path);
+        }
+        Ok(desc)
+    }
+}
+
+/// 材质
+///
+/// 由着色器变体、命名参数与纹理组成，绑定时自动设置所有 uniform 并将纹理依次绑定到纹理单元
+///
+/// # 示例
+///
+/// ```
+/// use gle::{*, math::*};
+///
+/// fn render_init() {
+///     let mut material = Material::load("assets/brick.material.toml").unwrap();
+///     material.set("uRoughness", 0.8);
+///     material.bind();
+/// }
+/// ```
+///
+/// # 注解
+///
+/// 该类型只能在渲染线程中创建、使用与释放
+#[derive(Clone)]
+pub struct Material {
+    program: Arc<Program>,
+    /// 渲染通道
+    pub pass: RenderPass,
+    /// 是否接收贴花(默认值为`true`)，用于角色、植被等不应被贴花覆盖的表面
+    pub receive_decals: bool,
+    /// 覆盖全局调试视图的调试视图(默认值为`None`)，见 [`DebugView`]
+    pub debug_view: Option<DebugView>,
+    parameters: BTreeMap<String, MaterialValue>,
+    textures: BTreeMap<String, Arc<Texture2D>>,
+}
+
+impl Material {
+    /// 以指定着色器程序创建不含参数的材质
+    ///
+    /// # 参数
+    /// + `program` - 着色器程序，可在多个材质之间共享
+    pub fn new(program: Arc<Program>) -> Self {
+        Self {
+            program,
+            pass: RenderPass::Opaque,
+            receive_decals: true,
+            debug_view: None,
+            parameters: BTreeMap::new(),
+            textures: BTreeMap::new(),
+        }
+    }
+
+    /// 由材质定义创建材质，编译着色器并加载纹理
+    ///
+    /// # 参数
+    /// + `desc` - 材质定义
+    pub fn from_desc(desc: &MaterialDesc) -> Result<Self> {
+        let vs = std::fs::read_to_string(&desc.shader.vertex)?;
+        let fs = std::fs::read_to_string(&desc.shader.fragment)?;
+        let defines: Vec<&str> = desc.shader.defines.iter().map(String::as_str).collect();
+        let mut material = Self::new(Arc::new(Program::with_defines(&vs, &fs, &defines)?));
+        material.pass = desc.pass;
+        material.receive_decals = desc.receive_decals;
+        material.parameters = desc.parameters.clone();
+        for (name, texture) in &desc.textures {
+            let texture = Texture2D::load(&texture.path, texture.srgb)?;
+            material.textures.insert(name.clone(), Arc::new(texture));
+        }
+        Ok(material)
+    }
+
+    /// 从材质文件加载材质
+    ///
+    /// # 参数
+    /// + `path` - 材质文件路径
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::from_desc(&MaterialDesc::load(path)?)
+    }
+
+    /// 获取着色器程序
+    pub fn program(&self) -> &Arc<Program> {
+        &self.program
+    }
+
+    /// 设置参数
+    ///
+    /// # 参数
+    /// + `name` - uniform 名称
+    /// + `value` - 参数值
+    pub fn set<V: Into<MaterialValue>>(&mut self, name: &str, value: V) {
+        self.parameters.insert(name.to_string(), value.into());
+    }
+
+    /// 获取参数
+    pub fn parameter(&self, name: &str) -> Option<&MaterialValue> {
+        self.parameters.get(name)
+    }
+
+    /// 设置纹理
+    ///
+    /// # 参数
+    /// + `name` - 采样器 uniform 名称
+    /// + `texture` - 纹理，可在多个材质之间共享
+    pub fn set_texture(&mut self, name: &str, texture: Arc<Texture2D>) {
+        self.textures.insert(name.to_string(), texture);
+    }
+
+    /// 获取纹理
+    pub fn texture(&self, name: &str) -> Option<&Arc<Texture2D>> {
+        self.textures.get(name)
+    }
+
+    /// 绑定着色器程序并应用所有参数与纹理
+    pub fn bind(&self) {
+        self.program.bind();
+        self.apply();
+    }
+
+    /// 在已绑定的着色器程序上应用所有参数与纹理
+    ///
+    /// # 注解
+    ///
+    /// 纹理按名称顺序绑定到纹理单元`1`、`2`……，纹理单元`0`保留给 [`RenderQueue`](crate::RenderQueue) 的主纹理
+    pub fn apply(&self) {
+        for (name, value) in &self.parameters {
+            value.apply(&self.program, name);
+        }
+        for (unit, (name, texture)) in self.textures.iter().enumerate() {
+            let unit = unit as u32 + 1;
+            texture.bind(unit);
+            self.program.set(name, &(unit as i32));
+        }
+    }
+
+    /// 生成使用该材质的绘制，可提交到 [`RenderQueue`](crate::RenderQueue)
+    ///
+    /// # 参数
+    /// + `mesh` - 网格
+    /// + `model` - 模型矩阵
+    pub fn draw_item<'a>(&'a self, mesh: &'a GpuMesh, model: Mat4) -> DrawItem<'a> {
+        DrawItem {
+            pass: self.pass,
+            program: &self.program,
+            texture: self.textures.values().next().map_or(0, |t| t.id()),
+            mesh,
+            model,
+            uniforms: Some(Box::new(move |_| self.apply())),
+            receive_decals: self.receive_decals,
+            debug_view: self.debug_view,
+        }
+    }
+}
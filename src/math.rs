@@ -0,0 +1,179 @@
+//! 数学模块
+//!
+//! 重导出 [`glam`] 的全部类型，并提供符合 OpenGL 裁剪空间约定(右手坐标系，NDC 深度范围为 `[-1, 1]`)的矩阵构造函数，
+//! 以及将数学类型上传为着色器 uniform 的辅助 trait
+//!
+//! # 示例
+//!
+//! ```
+//! use gle::math::*;
+//!
+//! let proj = perspective(45f32.to_radians(), 800.0 / 600.0, 0.1, 100.0);
+//! let view = look_at(Vec3::new(0.0, 0.0, 3.0), Vec3::ZERO, Vec3::Y);
+//! let mvp = proj * view;
+//! assert_eq!(mvp.as_uniform().len(), 16);
+//! ```
+pub use glam::*;
+
+/// 构造 OpenGL 约定的透视投影矩阵
+///
+/// # 参数
+/// + `fov_y` - 垂直视野角，单位为弧度
+/// + `aspect` - 宽高比
+/// + `near` - 近裁剪面距离
+/// + `far` - 远裁剪面距离
+///
+/// # 返回值
+/// 返回透视投影矩阵
+pub fn perspective(fov_y: f32, aspect: f32, near: f32, far: f32) -> Mat4 {
+    Mat4::perspective_rh_gl(fov_y, aspect, near, far)
+}
+
+/// 构造 OpenGL 约定的正交投影矩阵
+///
+/// # 参数
+/// + `left` - 左边界
+/// + `right` - 右边界
+/// + `bottom` - 下边界
+/// + `top` - 上边界
+/// + `near` - 近裁剪面距离
+/// + `far` - 远裁剪面距离
+///
+/// # 返回值
+/// 返回正交投影矩阵
+pub fn orthographic(left: f32, right: f32, bottom: f32, top: f32, near: f32, far: f32) -> Mat4 {
+    Mat4::orthographic_rh_gl(left, right, bottom, top, near, far)
+}
+
+/// 构造右手坐标系的观察矩阵
+///
+/// # 参数
+/// + `eye` - 观察者位置
+/// + `target` - 观察目标位置
+/// + `up` - 上方向
+///
+/// # 返回值
+/// 返回观察矩阵
+pub fn look_at(eye: Vec3, target: Vec3, up: Vec3) -> Mat4 {
+    Mat4::look_at_rh(eye, target, up)
+}
+
+/// 将浮点数学类型视为连续的 `f32` 数组(矩阵为列主序)，可直接传递给 `glUniform*fv`
+pub trait AsUniform {
+    /// 获取 uniform 数据
+    ///
+    /// # 返回值
+    /// 返回按列主序排列的浮点数组
+    fn as_uniform(&self) -> &[f32];
+}
+
+macro_rules! impl_as_uniform {
+    ($($t:ty => $n:expr),* $(,)?) => {
+        $(
+            impl AsUniform for $t {
+                fn as_uniform(&self) -> &[f32] {
+                    AsRef::<[f32; $n]>::as_ref(self)
+                }
+            }
+        )*
+    };
+}
+
+impl AsUniform for f32 {
+    fn as_uniform(&self) -> &[f32] {
+        std::slice::from_ref(self)
+    }
+}
+
+impl_as_uniform!(Vec2 => 2, Vec3 => 3, Vec4 => 4, Mat2 => 4, Mat3 => 9, Mat4 => 16);
+
+/// 可上传为着色器 uniform 的类型
+///
+/// # 注解
+///
+/// 该 trait 的方法只能在持有 OpenGL 上下文的线程(即渲染线程)中调用，且作用于当前使用的着色器程序
+pub trait Uniform {
+    /// 将值上传到当前着色器程序的指定位置
+    ///
+    /// # 参数
+    /// + `location` - uniform 位置，为 `-1` 时 OpenGL 会忽略该调用
+    fn set_uniform(&self, location: i32);
+}
+
+impl Uniform for f32 {
+    fn set_uniform(&self, location: i32) {
+        unsafe { gl::Uniform1f(location, *self) };
+    }
+}
+
+impl Uniform for i32 {
+    fn set_uniform(&self, location: i32) {
+        unsafe { gl::Uniform1i(location, *self) };
+    }
+}
+
+impl Uniform for u32 {
+    fn set_uniform(&self, location: i32) {
+        unsafe { gl::Uniform1ui(location, *self) };
+    }
+}
+
+impl Uniform for bool {
+    fn set_uniform(&self, location: i32) {
+        unsafe { gl::Uniform1i(location, *self as i32) };
+    }
+}
+
+impl Uniform for Vec2 {
+    fn set_uniform(&self, location: i32) {
+        unsafe { gl::Uniform2fv(location, 1, self.as_uniform().as_ptr()) };
+    }
+}
+
+impl Uniform for Vec3 {
+    fn set_uniform(&self, location: i32) {
+        unsafe { gl::Uniform3fv(location, 1, self.as_uniform().as_ptr()) };
+    }
+}
+
+impl Uniform for Vec4 {
+    fn set_uniform(&self, location: i32) {
+        unsafe { gl::Uniform4fv(location, 1, self.as_uniform().as_ptr()) };
+    }
+}
+
+impl Uniform for IVec2 {
+    fn set_uniform(&self, location: i32) {
+        unsafe { gl::Uniform2i(location, self.x, self.y) };
+    }
+}
+
+impl Uniform for IVec3 {
+    fn set_uniform(&self, location: i32) {
+        unsafe { gl::Uniform3i(location, self.x, self.y, self.z) };
+    }
+}
+
+impl Uniform for IVec4 {
+    fn set_uniform(&self, location: i32) {
+        unsafe { gl::Uniform4i(location, self.x, self.y, self.z, self.w) };
+    }
+}
+
+impl Uniform for Mat2 {
+    fn set_uniform(&self, location: i32) {
+        unsafe { gl::UniformMatrix2fv(location, 1, gl::FALSE, self.as_uniform().as_ptr()) };
+    }
+}
+
+impl Uniform for Mat3 {
+    fn set_uniform(&self, location: i32) {
+        unsafe { gl::UniformMatrix3fv(location, 1, gl::FALSE, self.as_uniform().as_ptr()) };
+    }
+}
+
+impl Uniform for Mat4 {
+    fn set_uniform(&self, location: i32) {
+        unsafe { gl::UniformMatrix4fv(location, 1, gl::FALSE, self.as_uniform().as_ptr()) };
+    }
+}
@@ -0,0 +1,269 @@
+use crate::math::*;
+use crate::{Aabb, GlObjectKind, GlObjects, RenderStats};
+
+/// 网格数据
+///
+/// 以独立数组存储顶点属性，`normals`与`uvs`可以为空，否则其长度必须与`positions`相同
+///
+/// # 示例
+///
+/// ```
+/// use gle::{*, math::*};
+///
+/// let mesh = Mesh {
+///     positions: vec![Vec3::ZERO, Vec3::X, Vec3::Y],
+///     indices: vec![0, 1, 2],
+///     ..Default::default()
+/// };
+/// // 在渲染线程中上传到 GPU
+/// let gpu_mesh = mesh.upload();
+/// gpu_mesh.draw();
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Mesh {
+    /// 顶点位置
+    pub positions: Vec<Vec3>,
+    /// 顶点法线
+    pub normals: Vec<Vec3>,
+    /// 顶点纹理坐标
+    pub uvs: Vec<Vec2>,
+    /// 三角形索引
+    pub indices: Vec<u32>,
+}
+
+/// [`GpuMesh`] 的顶点属性位置：位置
+pub const ATTRIB_POSITION: u32 = 0;
+/// [`GpuMesh`] 的顶点属性位置：法线
+pub const ATTRIB_NORMAL: u32 = 1;
+/// [`GpuMesh`] 的顶点属性位置：纹理坐标
+pub const ATTRIB_UV: u32 = 2;
+
+impl Mesh {
+    /// 创建一个空网格
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 获取顶点数量
+    pub fn vertex_count(&self) -> usize {
+        self.positions.len()
+    }
+
+    /// 获取三角形数量
+    pub fn triangle_count(&self) -> usize {
+        self.indices.len() / 3
+    }
+
+    /// 获取网格的包围盒
+    pub fn aabb(&self) -> Aabb {
+        Aabb::from_points(self.positions.iter().copied())
+    }
+
+    /// 根据三角形计算平滑顶点法线，覆盖已有的法线
+    pub fn compute_normals(&mut self) {
+        let mut normals = vec![Vec3::ZERO; self.positions.len()];
+        for tri in self.indices.chunks_exact(3) {
+            let [a, b, c] = [tri[0] as usize, tri[1] as usize, tri[2] as usize];
+            let n = (self.positions[b] - self.positions[a]).cross(self.positions[c] - self.positions[a]);
+            normals[a] += n;
+            normals[b] += n;
+            normals[c] += n;
+        }
+        self.normals = normals.into_iter().map(|n| n.normalize_or_zero()).collect();
+    }
+
+    /// 将另一个网格追加到该网格中
+    ///
+    /// # 参数
+    /// + `other` - 被追加的网格
+    /// + `transform` - 应用于被追加网格的变换
+    pub fn append(&mut self, other: &Mesh, transform: &Mat4) {
+        let base = self.positions.len() as u32;
+        let normal_matrix = Mat3::from_mat4(*transform).inverse().transpose();
+        if self.normals.len() < self.positions.len() {
+            self.normals.resize(self.positions.len(), Vec3::ZERO);
+        }
+        if self.uvs.len() < self.positions.len() {
+            self.uvs.resize(self.positions.len(), Vec2::ZERO);
+        }
+        self.positions
+            .extend(other.positions.iter().map(|&p| transform.transform_point3(p)));
+        self.normals.extend((0..other.positions.len()).map(|i| {
+            other
+                .normals
+                .get(i)
+                .map_or(Vec3::ZERO, |&n| (normal_matrix * n).normalize_or_zero())
+        }));
+        self.uvs
+            .extend((0..other.positions.len()).map(|i| other.uvs.get(i).copied().unwrap_or(Vec2::ZERO)));
+        self.indices.extend(other.indices.iter().map(|&i| i + base));
+    }
+
+    /// 生成交错排列的顶点数据，每个顶点依次为位置(3)、法线(3)、纹理坐标(2)
+    pub fn interleaved(&self) -> Vec<f32> {
+        let mut data = Vec::with_capacity(self.positions.len() * 8);
+        for (i, p) in self.positions.iter().enumerate() {
+            let n = self.normals.get(i).copied().unwrap_or(Vec3::ZERO);
+            let uv = self.uvs.get(i).copied().unwrap_or(Vec2::ZERO);
+            data.extend_from_slice(&[p.x, p.y, p.z, n.x, n.y, n.z, uv.x, uv.y]);
+        }
+        data
+    }
+
+    /// 将网格上传到 GPU
+    ///
+    /// # 返回值
+    /// 返回 GPU 网格，其顶点属性位置为：
+    /// + `0` - 位置`vec3`
+    /// + `1` - 法线`vec3`
+    /// + `2` - 纹理坐标`vec2`
+    ///
+    /// # 注解
+    ///
+    /// 只能在渲染线程中调用
+    pub fn upload(&self) -> GpuMesh {
+        let data = self.interleaved();
+        let (mut vao, mut vbo, mut ebo) = (0, 0, 0);
+        let stride = 8 * std::mem::size_of::<f32>() as i32;
+        unsafe {
+            gl::GenVertexArrays(1, &mut vao);
+            gl::GenBuffers(1, &mut vbo);
+            gl::GenBuffers(1, &mut ebo);
+            gl::BindVertexArray(vao);
+            gl::BindBuffer(gl::ARRAY_BUFFER, vbo);
+            gl::BufferData(
+                gl::ARRAY_BUFFER,
+                (data.len() * std::mem::size_of::<f32>()) as isize,
+                data.as_ptr() as *const _,
+                gl::STATIC_DRAW,
+            );
+            gl::BindBuffer(gl::ELEMENT_ARRAY_BUFFER, ebo);
+            gl::BufferData(
+                gl::ELEMENT_ARRAY_BUFFER,
+                (self.indices.len() * std::mem::size_of::<u32>()) as isize,
+                self.indices.as_ptr() as *const _,
+                gl::STATIC_DRAW,
+            );
+            gl::EnableVertexAttribArray(ATTRIB_POSITION);
+            gl::VertexAttribPointer(ATTRIB_POSITION, 3, gl::FLOAT, gl::FALSE, stride, std::ptr::null());
+            gl::EnableVertexAttribArray(ATTRIB_NORMAL);
+            gl::VertexAttribPointer(
+                ATTRIB_NORMAL,
+                3,
+                gl::FLOAT,
+                gl::FALSE,
+                stride,
+                (3 * std::mem::size_of::<f32>()) as *const _,
+            );
+            gl::EnableVertexAttribArray(ATTRIB_UV);
+            gl::VertexAttribPointer(
+                ATTRIB_UV,
+                2,
+                gl::FLOAT,
+                gl::FALSE,
+                stride,
+                (6 * std::mem::size_of::<f32>()) as *const _,
+            );
+            gl::BindVertexArray(0);
+        }
+        let mesh = GpuMesh {
+            vao,
+            vbo,
+            ebo,
+            index_count: self.indices.len() as i32,
+            vertex_count: self.positions.len() as i32,
+        };
+        RenderStats::_track_buffer(mesh.buffer_bytes());
+        GlObjects::track(GlObjectKind::VertexArray, vao);
+        GlObjects::track(GlObjectKind::Buffer, vbo);
+        GlObjects::track(GlObjectKind::Buffer, ebo);
+        mesh
+    }
+}
+
+/// 已上传到 GPU 的网格
+///
+/// 在被释放时自动删除其顶点数组对象与缓冲对象，因此只能在渲染线程中被释放
+#[derive(Debug)]
+pub struct GpuMesh {
+    vao: u32,
+    vbo: u32,
+    ebo: u32,
+    index_count: i32,
+    vertex_count: i32,
+}
+
+impl GpuMesh {
+    /// 获取顶点数组对象ID
+    pub fn vao(&self) -> u32 {
+        self.vao
+    }
+
+    /// 获取顶点缓冲对象ID
+    pub fn vbo(&self) -> u32 {
+        self.vbo
+    }
+
+    /// 获取索引缓冲对象ID
+    pub fn ebo(&self) -> u32 {
+        self.ebo
+    }
+
+    /// 获取索引数量
+    pub fn index_count(&self) -> i32 {
+        self.index_count
+    }
+
+    /// 获取顶点数量
+    pub fn vertex_count(&self) -> i32 {
+        self.vertex_count
+    }
+
+    /// 缓冲占用的字节数：每个顶点 8 个浮点数，每个索引 4 字节
+    fn buffer_bytes(&self) -> i64 {
+        self.vertex_count as i64 * 32 + self.index_count as i64 * 4
+    }
+
+    /// 绘制网格
+    pub fn draw(&self) {
+        RenderStats::record_draw(self.index_count as u64 / 3);
+        unsafe {
+            gl::BindVertexArray(self.vao);
+            gl::DrawElements(gl::TRIANGLES, self.index_count, gl::UNSIGNED_INT, std::ptr::null());
+            gl::BindVertexArray(0);
+        }
+    }
+
+    /// 以实例化方式绘制网格
+    ///
+    /// # 参数
+    /// + `instances` - 实例数量
+    pub fn draw_instanced(&self, instances: i32) {
+        RenderStats::record_draw(self.index_count as u64 / 3 * instances.max(0) as u64);
+        unsafe {
+            gl::BindVertexArray(self.vao);
+            gl::DrawElementsInstanced(
+                gl::TRIANGLES,
+                self.index_count,
+                gl::UNSIGNED_INT,
+                std::ptr::null(),
+                instances,
+            );
+            gl::BindVertexArray(0);
+        }
+    }
+}
+
+impl Drop for GpuMesh {
+    fn drop(&mut self) {
+        RenderStats::_track_buffer(-self.buffer_bytes());
+        GlObjects::untrack(GlObjectKind::VertexArray, self.vao);
+        GlObjects::untrack(GlObjectKind::Buffer, self.vbo);
+        GlObjects::untrack(GlObjectKind::Buffer, self.ebo);
+        unsafe {
+            gl::DeleteBuffers(1, &self.ebo);
+            gl::DeleteBuffers(1, &self.vbo);
+            gl::DeleteVertexArrays(1, &self.vao);
+        }
+    }
+}
@@ -0,0 +1,247 @@
+use std::collections::VecDeque;
+
+use crate::Tweenable;
+
+type StepFn<S, I> = Box<dyn FnMut(&mut S, &I) + Send>;
+
+/// 远程实体的快照缓冲区
+///
+/// 缓存服务器发来的带时间戳的状态，并在两个快照之间插值。
+/// 客户端应以比估计的服务器时间晚 [`SnapshotBuffer::delay`] 的时间采样，
+/// 使采样点总是落在两个已收到的快照之间，从而平滑网络抖动
+///
+/// # 示例
+///
+/// ```
+/// use gle::{*, math::*};
+///
+/// let mut buffer = SnapshotBuffer::new();
+/// buffer.push(1.0, Vec3::ZERO);
+/// buffer.push(1.1, Vec3::X);
+///
+/// // 估计的服务器时间为 1.15 秒，延迟 0.1 秒采样
+/// let position = buffer.sample_delayed(1.15).unwrap();
+/// assert!((position.x - 0.5).abs() < 1e-4);
+/// ```
+#[derive(Debug, Clone)]
+pub struct SnapshotBuffer<T> {
+    /// 插值延迟，单位为秒，通常取服务器快照间隔的两倍
+    pub delay: f64,
+    /// 最多缓存的快照数量
+    pub capacity: usize,
+    snapshots: VecDeque<(f64, T)>,
+}
+
+impl<T: Tweenable> Default for SnapshotBuffer<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Tweenable> SnapshotBuffer<T> {
+    /// 创建延迟为 0.1 秒、最多缓存 32 个快照的缓冲区
+    pub fn new() -> Self {
+        Self {
+            delay: 0.1,
+            capacity: 32,
+            snapshots: VecDeque::new(),
+        }
+    }
+
+    /// 添加快照
+    ///
+    /// # 参数
+    /// + `time` - 快照在服务器上的时间，单位为秒
+    /// + `state` - 快照状态
+    ///
+    /// # 注解
+    ///
+    /// 乱序到达的快照按时间插入，与已有快照时间相同时替换之
+    pub fn push(&mut self, time: f64, state: T) {
+        let index = self.snapshots.partition_point(|(t, _)| *t < time);
+        match self.snapshots.get_mut(index) {
+            Some(snapshot) if snapshot.0 == time => snapshot.1 = state,
+            _ => self.snapshots.insert(index, (time, state)),
+        }
+        while self.snapshots.len() > self.capacity.max(2) {
+            self.snapshots.pop_front();
+        }
+    }
+
+    /// 在指定时间采样
+    ///
+    /// # 参数
+    /// + `time` - 服务器时间，单位为秒
+    ///
+    /// # 返回值
+    /// 返回插值后的状态，时间超出已有快照的范围时返回最早或最新的快照，缓冲区为空时返回`None`
+    ///
+    /// # 注解
+    ///
+    /// 早于采样时间且不再需要的快照会被丢弃
+    pub fn sample(&mut self, time: f64) -> Option<T> {
+        while self.snapshots.len() > 2 && self.snapshots[1].0 <= time {
+            self.snapshots.pop_front();
+        }
+        let (t0, a) = *self.snapshots.front()?;
+        if time <= t0 {
+            return Some(a);
+        }
+        match self.snapshots.get(1) {
+            Some(&(t1, b)) if time < t1 => {
+                Some(T::interpolate(a, b, ((time - t0) / (t1 - t0)) as f32))
+            }
+            _ => self.latest(),
+        }
+    }
+
+    /// 以估计的服务器时间减去插值延迟后的时间采样
+    ///
+    /// # 参数
+    /// + `server_time` - 估计的当前服务器时间，单位为秒
+    pub fn sample_delayed(&mut self, server_time: f64) -> Option<T> {
+        self.sample(server_time - self.delay)
+    }
+
+    /// 获取最新的快照
+    pub fn latest(&self) -> Option<T> {
+        self.snapshots.back().map(|(_, state)| *state)
+    }
+
+    /// 获取最新快照的时间
+    pub fn latest_time(&self) -> Option<f64> {
+        self.snapshots.back().map(|(time, _)| *time)
+    }
+
+    /// 获取缓存的快照数量
+    pub fn len(&self) -> usize {
+        self.snapshots.len()
+    }
+
+    /// 缓冲区是否为空
+    pub fn is_empty(&self) -> bool {
+        self.snapshots.is_empty()
+    }
+
+    /// 清空缓冲区，如实体传送后避免从旧位置插值
+    pub fn clear(&mut self) {
+        self.snapshots.clear();
+    }
+}
+
+/// 本地输入预测
+///
+/// 客户端发送输入的同时立即在本地模拟，服务器确认某个输入后，以服务器的权威状态为起点重放尚未确认的输入。
+/// 模拟函数应与服务器使用的逻辑一致，如 [`CharacterController::update`](crate::CharacterController::update)
+///
+/// # 示例
+///
+/// ```
+/// use gle::{*, math::*};
+///
+/// let mut predictor = Predictor::new(Vec3::ZERO, |position: &mut Vec3, input: &Vec3| {
+///     *position += *input;
+/// });
+///
+/// // 每个时间步
+/// let sequence = predictor.predict(Vec3::X);
+/// // 将 (sequence, 输入) 发送给服务器 ...
+/// predictor.predict(Vec3::X);
+///
+/// // 服务器确认了第一个输入，但权威位置略有不同
+/// let correction = predictor.reconcile(sequence, Vec3::new(0.9, 0.0, 0.0));
+/// assert!((predictor.state().x - 1.9).abs() < 1e-5);
+/// assert!((correction.x + 0.1).abs() < 1e-5);
+/// ```
+pub struct Predictor<S, I> {
+    state: S,
+    step: StepFn<S, I>,
+    pending: VecDeque<(u32, I)>,
+    next_sequence: u32,
+    /// 最多保留的未确认输入数量，超出时丢弃最早的输入
+    pub max_pending: usize,
+}
+
+impl<S, I> Predictor<S, I> {
+    /// 创建预测器
+    ///
+    /// # 参数
+    /// + `state` - 初始状态
+    /// + `step` - 模拟函数，将一个输入应用到状态上
+    pub fn new<F: FnMut(&mut S, &I) + Send + 'static>(state: S, step: F) -> Self {
+        Self {
+            state,
+            step: Box::new(step),
+            pending: VecDeque::new(),
+            next_sequence: 0,
+            max_pending: 256,
+        }
+    }
+
+    /// 获取预测的当前状态
+    pub fn state(&self) -> &S {
+        &self.state
+    }
+
+    /// 获取尚未被服务器确认的输入
+    pub fn pending(&self) -> impl Iterator<Item = &(u32, I)> {
+        self.pending.iter()
+    }
+
+    /// 在本地应用输入并记录
+    ///
+    /// # 参数
+    /// + `input` - 本时间步的输入
+    ///
+    /// # 返回值
+    /// 返回输入的序号，应随输入一起发送给服务器，服务器在状态中回传已处理的最大序号
+    pub fn predict(&mut self, input: I) -> u32 {
+        let sequence = self.next_sequence;
+        self.next_sequence = self.next_sequence.wrapping_add(1);
+        (self.step)(&mut self.state, &input);
+        self.pending.push_back((sequence, input));
+        while self.pending.len() > self.max_pending {
+            self.pending.pop_front();
+        }
+        sequence
+    }
+
+    /// 重置状态并清空未确认的输入，如重生或传送时
+    pub fn reset(&mut self, state: S) {
+        self.state = state;
+        self.pending.clear();
+    }
+
+    /// 以服务器的权威状态校正预测，不计算校正量
+    ///
+    /// 适用于无法相减的状态类型
+    pub fn reconcile_with(&mut self, acknowledged: u32, state: S) {
+        // 序号按回绕比较，只要未确认的输入少于 2^31 个即可正确区分先后
+        while let Some((sequence, _)) = self.pending.front() {
+            if (acknowledged.wrapping_sub(*sequence) as i32) < 0 {
+                break;
+            }
+            self.pending.pop_front();
+        }
+        self.state = state;
+        for (_, input) in &self.pending {
+            (self.step)(&mut self.state, input);
+        }
+    }
+}
+
+impl<S: Clone + std::ops::Sub<Output = S>, I> Predictor<S, I> {
+    /// 以服务器的权威状态校正预测
+    ///
+    /// # 参数
+    /// + `acknowledged` - 服务器已处理的最大输入序号
+    /// + `state` - 服务器处理该输入后的权威状态
+    ///
+    /// # 返回值
+    /// 返回校正量，即校正后与校正前预测状态之差，可用于在若干帧内平滑地消除视觉上的跳变
+    pub fn reconcile(&mut self, acknowledged: u32, state: S) -> S {
+        let previous = self.state.clone();
+        self.reconcile_with(acknowledged, state);
+        self.state.clone() - previous
+    }
+}
@@ -0,0 +1,466 @@
+use std::f32::consts::FRAC_1_SQRT_2;
+
+use crate::math::*;
+
+const GRAD2: [Vec2; 8] = [
+    Vec2::new(1.0, 0.0),
+    Vec2::new(-1.0, 0.0),
+    Vec2::new(0.0, 1.0),
+    Vec2::new(0.0, -1.0),
+    Vec2::new(FRAC_1_SQRT_2, FRAC_1_SQRT_2),
+    Vec2::new(-FRAC_1_SQRT_2, FRAC_1_SQRT_2),
+    Vec2::new(FRAC_1_SQRT_2, -FRAC_1_SQRT_2),
+    Vec2::new(-FRAC_1_SQRT_2, -FRAC_1_SQRT_2),
+];
+
+const GRAD3: [Vec3; 12] = [
+    Vec3::new(1.0, 1.0, 0.0),
+    Vec3::new(-1.0, 1.0, 0.0),
+    Vec3::new(1.0, -1.0, 0.0),
+    Vec3::new(-1.0, -1.0, 0.0),
+    Vec3::new(1.0, 0.0, 1.0),
+    Vec3::new(-1.0, 0.0, 1.0),
+    Vec3::new(1.0, 0.0, -1.0),
+    Vec3::new(-1.0, 0.0, -1.0),
+    Vec3::new(0.0, 1.0, 1.0),
+    Vec3::new(0.0, -1.0, 1.0),
+    Vec3::new(0.0, 1.0, -1.0),
+    Vec3::new(0.0, -1.0, -1.0),
+];
+
+/// 各倍频程采样位置的偏移，避免原点附近各层噪声相关
+const OCTAVE_OFFSET: Vec3 = Vec3::new(19.19, 47.77, 73.31);
+
+/// 噪声函数
+///
+/// 二维与三维版本的输出范围均约为`[-1, 1]`(分形噪声见各类型说明)，相同种子与输入总是得到相同的结果。
+/// 批量填充方法按行主序写入连续的切片，适合一次生成整块地形或体素数据
+pub trait Noise {
+    /// 采样二维噪声
+    fn noise2(&self, p: Vec2) -> f32;
+
+    /// 采样三维噪声
+    fn noise3(&self, p: Vec3) -> f32;
+
+    /// 在规则网格上批量采样二维噪声
+    ///
+    /// # 参数
+    /// + `out` - 输出，长度应为`width`的整数倍，第`i`项对应网格点`(i % width, i / width)`
+    /// + `width` - 每行的采样数
+    /// + `origin` - 首个采样点的坐标
+    /// + `step` - 相邻采样点的间距
+    fn fill2(&self, out: &mut [f32], width: usize, origin: Vec2, step: f32) {
+        for (y, row) in out.chunks_mut(width.max(1)).enumerate() {
+            let py = origin.y + y as f32 * step;
+            for (x, value) in row.iter_mut().enumerate() {
+                *value = self.noise2(Vec2::new(origin.x + x as f32 * step, py));
+            }
+        }
+    }
+
+    /// 在规则网格上批量采样三维噪声
+    ///
+    /// # 参数
+    /// + `out` - 输出，第`i`项对应网格点`(i % width, i / width % height, i / (width * height))`
+    /// + `width` - X 方向的采样数
+    /// + `height` - Y 方向的采样数
+    /// + `origin` - 首个采样点的坐标
+    /// + `step` - 相邻采样点的间距
+    fn fill3(&self, out: &mut [f32], width: usize, height: usize, origin: Vec3, step: f32) {
+        let width = width.max(1);
+        for (i, row) in out.chunks_mut(width).enumerate() {
+            let (y, z) = (i % height.max(1), i / height.max(1));
+            let (py, pz) = (origin.y + y as f32 * step, origin.z + z as f32 * step);
+            for (x, value) in row.iter_mut().enumerate() {
+                *value = self.noise3(Vec3::new(origin.x + x as f32 * step, py, pz));
+            }
+        }
+    }
+}
+
+/// 由种子打乱的置换表，长度加倍以省去索引回绕
+#[derive(Debug, Clone)]
+struct Permutation([u8; 512]);
+
+impl Permutation {
+    fn new(seed: u32) -> Self {
+        let mut table = [0u8; 512];
+        for (i, v) in table.iter_mut().take(256).enumerate() {
+            *v = i as u8;
+        }
+        // 种子为 0 时 xorshift 会停留在 0
+        let mut state = seed ^ 0x9e37_79b9;
+        for i in (1..256).rev() {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            table.swap(i, state as usize % (i + 1));
+        }
+        let (low, high) = table.split_at_mut(256);
+        high.copy_from_slice(low);
+        Self(table)
+    }
+
+    #[inline]
+    fn get(&self, i: i32) -> usize {
+        self.0[(i & 511) as usize] as usize
+    }
+}
+
+#[inline]
+fn fade(t: f32) -> f32 {
+    t * t * t * (t * (t * 6.0 - 15.0) + 10.0)
+}
+
+#[inline]
+fn lerp(a: f32, b: f32, t: f32) -> f32 {
+    a + (b - a) * t
+}
+
+#[inline]
+fn floor_i32(v: f32) -> i32 {
+    v.floor() as i32
+}
+
+/// 改进的 Perlin 梯度噪声
+///
+/// # 示例
+///
+/// ```
+/// use gle::{*, math::*};
+///
+/// let perlin = Perlin::new(42);
+/// let height = perlin.noise2(Vec2::new(12.3, 4.5));
+/// assert!((-1.0..=1.0).contains(&height));
+/// ```
+#[derive(Debug, Clone)]
+pub struct Perlin {
+    perm: Permutation,
+}
+
+impl Perlin {
+    /// 以指定种子创建 Perlin 噪声
+    pub fn new(seed: u32) -> Self {
+        Self {
+            perm: Permutation::new(seed),
+        }
+    }
+
+    #[inline]
+    fn grad2(&self, hash: usize, x: f32, y: f32) -> f32 {
+        GRAD2[hash & 7].dot(Vec2::new(x, y))
+    }
+
+    #[inline]
+    fn grad3(&self, hash: usize, p: Vec3) -> f32 {
+        GRAD3[hash % 12].dot(p)
+    }
+}
+
+impl Noise for Perlin {
+    fn noise2(&self, p: Vec2) -> f32 {
+        let (xi, yi) = (floor_i32(p.x), floor_i32(p.y));
+        let (x, y) = (p.x - xi as f32, p.y - yi as f32);
+        let (u, v) = (fade(x), fade(y));
+        let perm = &self.perm;
+        let a = perm.get(xi) as i32 + yi;
+        let b = perm.get(xi + 1) as i32 + yi;
+        let n00 = self.grad2(perm.get(a), x, y);
+        let n10 = self.grad2(perm.get(b), x - 1.0, y);
+        let n01 = self.grad2(perm.get(a + 1), x, y - 1.0);
+        let n11 = self.grad2(perm.get(b + 1), x - 1.0, y - 1.0);
+        lerp(lerp(n00, n10, u), lerp(n01, n11, u), v)
+    }
+
+    fn noise3(&self, p: Vec3) -> f32 {
+        let (xi, yi, zi) = (floor_i32(p.x), floor_i32(p.y), floor_i32(p.z));
+        let f = p - Vec3::new(xi as f32, yi as f32, zi as f32);
+        let (u, v, w) = (fade(f.x), fade(f.y), fade(f.z));
+        let perm = &self.perm;
+        let a = perm.get(xi) as i32 + yi;
+        let aa = perm.get(a) as i32 + zi;
+        let ab = perm.get(a + 1) as i32 + zi;
+        let b = perm.get(xi + 1) as i32 + yi;
+        let ba = perm.get(b) as i32 + zi;
+        let bb = perm.get(b + 1) as i32 + zi;
+        let g = |hash: i32, dx: f32, dy: f32, dz: f32| {
+            self.grad3(perm.get(hash), f - Vec3::new(dx, dy, dz))
+        };
+        lerp(
+            lerp(
+                lerp(g(aa, 0.0, 0.0, 0.0), g(ba, 1.0, 0.0, 0.0), u),
+                lerp(g(ab, 0.0, 1.0, 0.0), g(bb, 1.0, 1.0, 0.0), u),
+                v,
+            ),
+            lerp(
+                lerp(g(aa + 1, 0.0, 0.0, 1.0), g(ba + 1, 1.0, 0.0, 1.0), u),
+                lerp(g(ab + 1, 0.0, 1.0, 1.0), g(bb + 1, 1.0, 1.0, 1.0), u),
+                v,
+            ),
+            w,
+        )
+    }
+}
+
+/// 单纯形(Simplex)噪声
+///
+/// 与 Perlin 噪声相比没有明显的轴向痕迹，且三维版本的计算量更小
+///
+/// # 示例
+///
+/// ```
+/// use gle::{*, math::*};
+///
+/// let simplex = Simplex::new(7);
+/// let mut density = vec![0.0; 16 * 16 * 16];
+/// simplex.fill3(&mut density, 16, 16, Vec3::ZERO, 0.05);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Simplex {
+    perm: Permutation,
+}
+
+impl Simplex {
+    /// 以指定种子创建单纯形噪声
+    pub fn new(seed: u32) -> Self {
+        Self {
+            perm: Permutation::new(seed),
+        }
+    }
+}
+
+impl Noise for Simplex {
+    fn noise2(&self, p: Vec2) -> f32 {
+        const F2: f32 = 0.366_025_4; // (√3 - 1) / 2
+        const G2: f32 = 0.211_324_87; // (3 - √3) / 6
+        let s = (p.x + p.y) * F2;
+        let (i, j) = (floor_i32(p.x + s), floor_i32(p.y + s));
+        let t = (i + j) as f32 * G2;
+        let p0 = p - Vec2::new(i as f32 - t, j as f32 - t);
+        let (i1, j1) = if p0.x > p0.y { (1, 0) } else { (0, 1) };
+        let p1 = p0 - Vec2::new(i1 as f32, j1 as f32) + G2;
+        let p2 = p0 - 1.0 + 2.0 * G2;
+        let perm = &self.perm;
+        let corner = |d: Vec2, hash: usize| {
+            let t = 0.5 - d.length_squared();
+            if t <= 0.0 {
+                return 0.0;
+            }
+            let g = GRAD3[hash % 12];
+            let t2 = t * t;
+            t2 * t2 * (g.x * d.x + g.y * d.y)
+        };
+        let n0 = corner(p0, perm.get(i + perm.get(j) as i32));
+        let n1 = corner(p1, perm.get(i + i1 + perm.get(j + j1) as i32));
+        let n2 = corner(p2, perm.get(i + 1 + perm.get(j + 1) as i32));
+        70.0 * (n0 + n1 + n2)
+    }
+
+    fn noise3(&self, p: Vec3) -> f32 {
+        const F3: f32 = 1.0 / 3.0;
+        const G3: f32 = 1.0 / 6.0;
+        let s = (p.x + p.y + p.z) * F3;
+        let (i, j, k) = (floor_i32(p.x + s), floor_i32(p.y + s), floor_i32(p.z + s));
+        let t = (i + j + k) as f32 * G3;
+        let p0 = p - Vec3::new(i as f32 - t, j as f32 - t, k as f32 - t);
+        // 按坐标分量的大小顺序确定所在的单纯形
+        let (o1, o2) = if p0.x >= p0.y {
+            if p0.y >= p0.z {
+                (IVec3::new(1, 0, 0), IVec3::new(1, 1, 0))
+            } else if p0.x >= p0.z {
+                (IVec3::new(1, 0, 0), IVec3::new(1, 0, 1))
+            } else {
+                (IVec3::new(0, 0, 1), IVec3::new(1, 0, 1))
+            }
+        } else if p0.y < p0.z {
+            (IVec3::new(0, 0, 1), IVec3::new(0, 1, 1))
+        } else if p0.x < p0.z {
+            (IVec3::new(0, 1, 0), IVec3::new(0, 1, 1))
+        } else {
+            (IVec3::new(0, 1, 0), IVec3::new(1, 1, 0))
+        };
+        let perm = &self.perm;
+        let corner = |o: IVec3, offset: f32| {
+            let d = p0 - o.as_vec3() + offset;
+            let t = 0.6 - d.length_squared();
+            if t <= 0.0 {
+                return 0.0;
+            }
+            let hash = perm.get(i + o.x + perm.get(j + o.y + perm.get(k + o.z) as i32) as i32);
+            let t2 = t * t;
+            t2 * t2 * GRAD3[hash % 12].dot(d)
+        };
+        let n = corner(IVec3::ZERO, 0.0)
+            + corner(o1, G3)
+            + corner(o2, 2.0 * G3)
+            + corner(IVec3::ONE, 3.0 * G3);
+        32.0 * n
+    }
+}
+
+/// 分形布朗运动(fBm)
+///
+/// 将基础噪声以递增的频率与递减的振幅叠加多个倍频程，输出按振幅之和归一化到约`[-1, 1]`，常用于地形高度与云层密度
+///
+/// # 示例
+///
+/// ```
+/// use gle::{*, math::*};
+///
+/// let terrain = Fbm::new(Simplex::new(1), 6).with_frequency(0.01);
+/// let mut heights = vec![0.0; 64 * 64];
+/// terrain.fill2(&mut heights, 64, Vec2::ZERO, 1.0);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Fbm<N: Noise> {
+    /// 基础噪声
+    pub noise: N,
+    /// 倍频程数
+    pub octaves: u32,
+    /// 第一个倍频程的频率
+    pub frequency: f32,
+    /// 相邻倍频程的频率倍数
+    pub lacunarity: f32,
+    /// 相邻倍频程的振幅倍数
+    pub gain: f32,
+}
+
+impl<N: Noise> Fbm<N> {
+    /// 创建分形布朗运动，频率为 1，频率倍数为 2，振幅倍数为 0.5
+    ///
+    /// # 参数
+    /// + `noise` - 基础噪声
+    /// + `octaves` - 倍频程数
+    pub fn new(noise: N, octaves: u32) -> Self {
+        Self {
+            noise,
+            octaves,
+            frequency: 1.0,
+            lacunarity: 2.0,
+            gain: 0.5,
+        }
+    }
+
+    /// 设置第一个倍频程的频率
+    pub fn with_frequency(mut self, frequency: f32) -> Self {
+        self.frequency = frequency;
+        self
+    }
+
+    /// 设置相邻倍频程的频率倍数与振幅倍数
+    pub fn with_lacunarity_gain(mut self, lacunarity: f32, gain: f32) -> Self {
+        self.lacunarity = lacunarity;
+        self.gain = gain;
+        self
+    }
+
+    fn accumulate(&self, mut sample: impl FnMut(u32, f32) -> f32) -> f32 {
+        let (mut sum, mut norm) = (0.0, 0.0);
+        let (mut frequency, mut amplitude) = (self.frequency, 1.0);
+        for octave in 0..self.octaves.max(1) {
+            sum += sample(octave, frequency) * amplitude;
+            norm += amplitude;
+            frequency *= self.lacunarity;
+            amplitude *= self.gain;
+        }
+        sum / norm
+    }
+}
+
+impl<N: Noise> Noise for Fbm<N> {
+    fn noise2(&self, p: Vec2) -> f32 {
+        self.accumulate(|octave, frequency| {
+            self.noise
+                .noise2(p * frequency + OCTAVE_OFFSET.truncate() * octave as f32)
+        })
+    }
+
+    fn noise3(&self, p: Vec3) -> f32 {
+        self.accumulate(|octave, frequency| {
+            self.noise.noise3(p * frequency + OCTAVE_OFFSET * octave as f32)
+        })
+    }
+}
+
+/// 脊状多重分形噪声
+///
+/// 对基础噪声取绝对值后反转，在零值处形成尖锐的山脊，并以上一倍频程的结果加权后续倍频程，
+/// 使细节集中在山脊附近。输出范围约为`[0, 1]`，适用于山脉与峡谷
+///
+/// # 示例
+///
+/// ```
+/// use gle::{*, math::*};
+///
+/// let mountains = Ridged::new(Perlin::new(3), 5).with_frequency(0.005);
+/// let height = mountains.noise2(Vec2::new(100.0, 250.0)) * 300.0;
+/// ```
+#[derive(Debug, Clone)]
+pub struct Ridged<N: Noise> {
+    /// 基础噪声
+    pub noise: N,
+    /// 倍频程数
+    pub octaves: u32,
+    /// 第一个倍频程的频率
+    pub frequency: f32,
+    /// 相邻倍频程的频率倍数
+    pub lacunarity: f32,
+    /// 相邻倍频程的振幅倍数
+    pub gain: f32,
+    /// 上一倍频程对后续倍频程的加权强度，越大山脊越尖锐
+    pub sharpness: f32,
+}
+
+impl<N: Noise> Ridged<N> {
+    /// 创建脊状多重分形噪声，频率为 1，频率倍数为 2，振幅倍数为 0.5，加权强度为 2
+    ///
+    /// # 参数
+    /// + `noise` - 基础噪声
+    /// + `octaves` - 倍频程数
+    pub fn new(noise: N, octaves: u32) -> Self {
+        Self {
+            noise,
+            octaves,
+            frequency: 1.0,
+            lacunarity: 2.0,
+            gain: 0.5,
+            sharpness: 2.0,
+        }
+    }
+
+    /// 设置第一个倍频程的频率
+    pub fn with_frequency(mut self, frequency: f32) -> Self {
+        self.frequency = frequency;
+        self
+    }
+
+    fn accumulate(&self, mut sample: impl FnMut(u32, f32) -> f32) -> f32 {
+        let (mut sum, mut norm) = (0.0, 0.0);
+        let (mut frequency, mut amplitude, mut weight) = (self.frequency, 1.0, 1.0);
+        for octave in 0..self.octaves.max(1) {
+            let ridge = 1.0 - sample(octave, frequency).abs();
+            let signal = ridge * ridge * weight;
+            weight = (signal * self.sharpness).clamp(0.0, 1.0);
+            sum += signal * amplitude;
+            norm += amplitude;
+            frequency *= self.lacunarity;
+            amplitude *= self.gain;
+        }
+        sum / norm
+    }
+}
+
+impl<N: Noise> Noise for Ridged<N> {
+    fn noise2(&self, p: Vec2) -> f32 {
+        self.accumulate(|octave, frequency| {
+            self.noise
+                .noise2(p * frequency + OCTAVE_OFFSET.truncate() * octave as f32)
+        })
+    }
+
+    fn noise3(&self, p: Vec3) -> f32 {
+        self.accumulate(|octave, frequency| {
+            self.noise.noise3(p * frequency + OCTAVE_OFFSET * octave as f32)
+        })
+    }
+}
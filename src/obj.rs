@@ -0,0 +1,325 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::error::{Error, Result};
+use crate::math::*;
+use crate::{debug, warn, Mesh};
+
+/// OBJ 模型中的材质(来自 MTL 文件)
+#[derive(Debug, Clone, PartialEq)]
+pub struct ObjMaterial {
+    /// 材质名
+    pub name: String,
+    /// 环境光颜色(`Ka`)
+    pub ambient: Vec3,
+    /// 漫反射颜色(`Kd`)
+    pub diffuse: Vec3,
+    /// 镜面反射颜色(`Ks`)
+    pub specular: Vec3,
+    /// 镜面反射指数(`Ns`)
+    pub shininess: f32,
+    /// 不透明度(`d`，或`1 - Tr`)
+    pub dissolve: f32,
+    /// 漫反射贴图路径(`map_Kd`)
+    pub diffuse_map: Option<PathBuf>,
+    /// 镜面反射贴图路径(`map_Ks`)
+    pub specular_map: Option<PathBuf>,
+    /// 法线贴图路径(`norm`或`map_Bump`)
+    pub normal_map: Option<PathBuf>,
+}
+
+impl ObjMaterial {
+    fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            ambient: Vec3::ZERO,
+            diffuse: Vec3::ONE,
+            specular: Vec3::ZERO,
+            shininess: 0.0,
+            dissolve: 1.0,
+            diffuse_map: None,
+            specular_map: None,
+            normal_map: None,
+        }
+    }
+}
+
+/// OBJ 模型中使用同一材质的一部分网格
+#[derive(Debug, Clone, PartialEq)]
+pub struct ObjMesh {
+    /// 对象或组名
+    pub name: String,
+    /// 网格数据
+    pub mesh: Mesh,
+    /// 材质在 [`ObjModel::materials`] 中的序号
+    pub material: Option<usize>,
+}
+
+/// OBJ 模型
+///
+/// # 示例
+///
+/// ```
+/// use gle::*;
+///
+/// let model = ObjModel::load("assets/teapot.obj").unwrap();
+/// for part in &model.meshes {
+///     let gpu_mesh = part.mesh.upload();
+/// }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ObjModel {
+    /// 按对象、组与材质划分的网格
+    pub meshes: Vec<ObjMesh>,
+    /// 材质
+    pub materials: Vec<ObjMaterial>,
+}
+
+#[derive(Default)]
+struct Builder {
+    positions: Vec<Vec3>,
+    normals: Vec<Vec3>,
+    uvs: Vec<Vec2>,
+    name: String,
+    material: Option<usize>,
+    mesh: Mesh,
+    has_normals: bool,
+    cache: HashMap<(usize, Option<usize>, Option<usize>), u32>,
+    meshes: Vec<ObjMesh>,
+}
+
+impl Builder {
+    fn flush(&mut self) {
+        if self.mesh.indices.is_empty() {
+            self.mesh = Mesh::default();
+            self.cache.clear();
+            return;
+        }
+        let mut mesh = std::mem::take(&mut self.mesh);
+        if !self.has_normals {
+            mesh.compute_normals();
+        }
+        self.meshes.push(ObjMesh {
+            name: self.name.clone(),
+            mesh,
+            material: self.material,
+        });
+        self.cache.clear();
+        self.has_normals = false;
+    }
+
+    fn resolve(index: &str, len: usize, line: usize) -> Result<usize> {
+        let i: i64 = index
+            .parse()
+            .map_err(|_| Error::Parse(format!("OBJ 第 {} 行: 无效的索引 `{}`", line, index)))?;
+        let resolved = if i < 0 { len as i64 + i } else { i - 1 };
+        if resolved < 0 || resolved >= len as i64 {
+            return Err(Error::Parse(format!("OBJ 第 {} 行: 索引 {} 越界", line, i)));
+        }
+        Ok(resolved as usize)
+    }
+
+    fn vertex(&mut self, token: &str, line: usize) -> Result<u32> {
+        let mut parts = token.split('/');
+        let v = Self::resolve(parts.next().unwrap_or(""), self.positions.len(), line)?;
+        let vt = match parts.next() {
+            Some(s) if !s.is_empty() => Some(Self::resolve(s, self.uvs.len(), line)?),
+            _ => None,
+        };
+        let vn = match parts.next() {
+            Some(s) if !s.is_empty() => Some(Self::resolve(s, self.normals.len(), line)?),
+            _ => None,
+        };
+        let key = (v, vt, vn);
+        if let Some(&index) = self.cache.get(&key) {
+            return Ok(index);
+        }
+        let index = self.mesh.positions.len() as u32;
+        self.mesh.positions.push(self.positions[v]);
+        self.mesh.uvs.push(vt.map_or(Vec2::ZERO, |i| self.uvs[i]));
+        self.mesh.normals.push(vn.map_or(Vec3::ZERO, |i| self.normals[i]));
+        self.has_normals |= vn.is_some();
+        self.cache.insert(key, index);
+        Ok(index)
+    }
+}
+
+fn floats<const N: usize>(parts: &[&str], line: usize) -> Result<[f32; N]> {
+    let mut out = [0.0; N];
+    for (i, v) in out.iter_mut().enumerate() {
+        *v = match parts.get(i) {
+            Some(s) => s
+                .parse()
+                .map_err(|_| Error::Parse(format!("第 {} 行: 无效的数值 `{}`", line, s)))?,
+            None if i > 0 => 0.0,
+            None => return Err(Error::Parse(format!("第 {} 行: 缺少数值", line))),
+        };
+    }
+    Ok(out)
+}
+
+impl ObjModel {
+    /// 从文件加载 OBJ 模型，并加载其引用的 MTL 材质库
+    ///
+    /// # 参数
+    /// + `path` - OBJ 文件路径，MTL 文件与贴图路径相对于该文件所在目录解析
+    ///
+    /// # 返回值
+    /// 成功时返回模型，文件读取失败或格式错误时返回错误
+    ///
+    /// # 注解
+    ///
+    /// 找不到 MTL 文件时仅记录警告，模型仍可正常加载
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let source = fs::read_to_string(path)?;
+        let dir = path.parent().unwrap_or(Path::new(""));
+        debug!(Self, "正在加载 OBJ 模型 {}", path.display());
+        Self::parse(&source, |name| {
+            let mtl_path = dir.join(name);
+            match fs::read_to_string(&mtl_path) {
+                Ok(src) => ObjMaterial::parse_mtl(&src, dir).map(Some),
+                Err(e) => {
+                    warn!(Self, "无法读取材质库 {}: {}", mtl_path.display(), e);
+                    Ok(None)
+                }
+            }
+        })
+    }
+
+    /// 从字符串解析 OBJ 模型
+    ///
+    /// # 参数
+    /// + `source` - OBJ 文本
+    /// + `load_mtl` - 一个函数，它接受`mtllib`指令引用的文件名，返回解析后的材质列表，返回`None`表示忽略该材质库
+    ///
+    /// # 返回值
+    /// 成功时返回模型，格式错误时返回错误
+    ///
+    /// # 注解
+    ///
+    /// 多边形面会以扇形方式三角化，没有法线的网格部分会自动计算平滑法线
+    pub fn parse<F>(source: &str, mut load_mtl: F) -> Result<Self>
+    where
+        F: FnMut(&str) -> Result<Option<Vec<ObjMaterial>>>,
+    {
+        let mut b = Builder::default();
+        let mut materials: Vec<ObjMaterial> = Vec::new();
+        for (n, line) in source.lines().enumerate() {
+            let n = n + 1;
+            let line = line.split('#').next().unwrap_or("").trim();
+            let mut parts = line.split_whitespace();
+            let Some(keyword) = parts.next() else {
+                continue;
+            };
+            let args: Vec<&str> = parts.collect();
+            match keyword {
+                "v" => {
+                    let [x, y, z] = floats(&args, n)?;
+                    b.positions.push(Vec3::new(x, y, z));
+                }
+                "vn" => {
+                    let [x, y, z] = floats(&args, n)?;
+                    b.normals.push(Vec3::new(x, y, z));
+                }
+                "vt" => {
+                    let [u, v] = floats(&args, n)?;
+                    b.uvs.push(Vec2::new(u, v));
+                }
+                "f" => {
+                    if args.len() < 3 {
+                        return Err(Error::Parse(format!("OBJ 第 {} 行: 面至少需要三个顶点", n)));
+                    }
+                    let mut indices = Vec::with_capacity(args.len());
+                    for token in &args {
+                        indices.push(b.vertex(token, n)?);
+                    }
+                    for i in 1..indices.len() - 1 {
+                        b.mesh
+                            .indices
+                            .extend_from_slice(&[indices[0], indices[i], indices[i + 1]]);
+                    }
+                }
+                "o" | "g" => {
+                    b.flush();
+                    b.name = args.join(" ");
+                }
+                "usemtl" => {
+                    b.flush();
+                    let name = args.join(" ");
+                    b.material = materials.iter().position(|m| m.name == name);
+                }
+                "mtllib" => {
+                    for name in &args {
+                        if let Some(mut list) = load_mtl(name)? {
+                            materials.append(&mut list);
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+        b.flush();
+        Ok(Self {
+            meshes: b.meshes,
+            materials,
+        })
+    }
+
+    /// 将所有部分合并为一个网格，忽略材质
+    pub fn merged(&self) -> Mesh {
+        let mut mesh = Mesh::new();
+        for part in &self.meshes {
+            mesh.append(&part.mesh, &Mat4::IDENTITY);
+        }
+        mesh
+    }
+}
+
+impl ObjMaterial {
+    /// 从字符串解析 MTL 材质库
+    ///
+    /// # 参数
+    /// + `source` - MTL 文本
+    /// + `dir` - 贴图路径的基准目录
+    ///
+    /// # 返回值
+    /// 成功时返回材质列表，格式错误时返回错误
+    pub fn parse_mtl(source: &str, dir: &Path) -> Result<Vec<ObjMaterial>> {
+        let mut materials: Vec<ObjMaterial> = Vec::new();
+        for (n, line) in source.lines().enumerate() {
+            let n = n + 1;
+            let line = line.split('#').next().unwrap_or("").trim();
+            let mut parts = line.split_whitespace();
+            let Some(keyword) = parts.next() else {
+                continue;
+            };
+            let args: Vec<&str> = parts.collect();
+            if keyword == "newmtl" {
+                materials.push(ObjMaterial::new(&args.join(" ")));
+                continue;
+            }
+            let Some(m) = materials.last_mut() else {
+                continue;
+            };
+            // 贴图指令的选项(如 -bm 1.0)位于文件名之前，取最后一个参数作为文件名
+            let map = || args.last().map(|f| dir.join(f));
+            match keyword {
+                "Ka" => m.ambient = Vec3::from(floats::<3>(&args, n)?),
+                "Kd" => m.diffuse = Vec3::from(floats::<3>(&args, n)?),
+                "Ks" => m.specular = Vec3::from(floats::<3>(&args, n)?),
+                "Ns" => m.shininess = floats::<1>(&args, n)?[0],
+                "d" => m.dissolve = floats::<1>(&args, n)?[0],
+                "Tr" => m.dissolve = 1.0 - floats::<1>(&args, n)?[0],
+                "map_Kd" => m.diffuse_map = map(),
+                "map_Ks" => m.specular_map = map(),
+                "norm" | "map_Bump" | "map_bump" | "bump" => m.normal_map = map(),
+                _ => {}
+            }
+        }
+        Ok(materials)
+    }
+}
@@ -0,0 +1,296 @@
+use std::collections::HashMap;
+
+use crate::error::Result;
+use crate::math::*;
+use crate::{
+    Aabb, ConditionalMode, GlObjectKind, GlObjects, OcclusionQuery, Program, QueryKind, RenderStats,
+};
+
+const VS: &str = r#"
+#version 330 core
+layout (location = 0) in vec3 aPos;
+uniform mat4 uMvp;
+void main()
+{
+    gl_Position = uMvp * vec4(aPos, 1.0);
+}
+"#;
+
+const FS: &str = r#"
+#version 330 core
+out vec4 FragColor;
+void main()
+{
+    FragColor = vec4(1.0);
+}
+"#;
+
+const CUBE_VERTICES: [f32; 24] = [
+    0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 1.0, 1.0, 0.0, //
+    0.0, 0.0, 1.0, 1.0, 0.0, 1.0, 0.0, 1.0, 1.0, 1.0, 1.0, 1.0, //
+];
+
+const CUBE_INDICES: [u8; 36] = [
+    0, 2, 6, 0, 6, 4, // -X
+    1, 5, 7, 1, 7, 3, // +X
+    0, 4, 5, 0, 5, 1, // -Y
+    2, 3, 7, 2, 7, 6, // +Y
+    0, 1, 3, 0, 3, 2, // -Z
+    4, 6, 7, 4, 7, 5, // +Z
+];
+
+struct QueryState {
+    query: OcclusionQuery,
+    issued_frame: u64,
+}
+
+/// 基于硬件遮挡查询的剔除器
+///
+/// 将对象的包围盒绘制到当前深度缓冲上(不写入颜色与深度)，并通过 [`OcclusionQuery`] 判断其是否可见，
+/// 适用于遮挡严重的室内场景
+///
+/// # 示例
+///
+/// ```
+/// use gle::*;
+///
+/// fn render_loop(culler: &mut OcclusionCuller, camera: &Camera, objects: &[(u64, Aabb)]) {
+///     // 1. 先绘制主要遮挡物以填充深度缓冲
+///     // 2. 对所有对象发起遮挡查询
+///     culler.begin(&camera.view_projection(), camera.position);
+///     for (key, aabb) in objects {
+///         culler.query(*key, aabb);
+///     }
+///     culler.end();
+///     // 3. 仅绘制可见对象
+///     for (key, _) in objects {
+///         if culler.is_visible(*key) {
+///             // draw object
+///         }
+///     }
+/// }
+/// ```
+///
+/// # 注解
+///
+/// 查询结果以异步方式读取，不会阻塞渲染线程，因此对象的可见性存在至少一帧的延迟。
+/// 为避免对象错误地消失，剔除器采用保守策略：
+/// + 尚无查询结果的对象视为可见
+/// + 摄像机位于包围盒内的对象视为可见
+/// + 查询结果超过 [`OcclusionCuller::max_latency`] 帧仍未返回的对象视为可见
+pub struct OcclusionCuller {
+    program: Program,
+    vao: u32,
+    vbo: u32,
+    ebo: u32,
+    states: HashMap<u64, QueryState>,
+    frame: u64,
+    view_projection: Mat4,
+    camera_pos: Vec3,
+    saved_cull_face: bool,
+    saved_depth_mask: u8,
+    saved_color_mask: [u8; 4],
+    /// 等待查询结果的最大帧数，超过后对象视为可见(默认值为3)
+    pub max_latency: u64,
+    /// 判断摄像机是否位于包围盒内时对包围盒的扩展距离，应不小于摄像机近裁剪面距离(默认值为0.1)
+    pub near_margin: f32,
+}
+
+impl OcclusionCuller {
+    /// 创建遮挡剔除器
+    ///
+    /// # 返回值
+    /// 成功时返回遮挡剔除器，内置着色器编译失败时返回错误
+    pub fn new() -> Result<Self> {
+        let program = Program::new(VS, FS)?;
+        let (mut vao, mut vbo, mut ebo) = (0, 0, 0);
+        unsafe {
+            gl::GenVertexArrays(1, &mut vao);
+            gl::GenBuffers(1, &mut vbo);
+            gl::GenBuffers(1, &mut ebo);
+            gl::BindVertexArray(vao);
+            gl::BindBuffer(gl::ARRAY_BUFFER, vbo);
+            gl::BufferData(
+                gl::ARRAY_BUFFER,
+                std::mem::size_of_val(&CUBE_VERTICES) as isize,
+                CUBE_VERTICES.as_ptr() as *const _,
+                gl::STATIC_DRAW,
+            );
+            gl::BindBuffer(gl::ELEMENT_ARRAY_BUFFER, ebo);
+            gl::BufferData(
+                gl::ELEMENT_ARRAY_BUFFER,
+                std::mem::size_of_val(&CUBE_INDICES) as isize,
+                CUBE_INDICES.as_ptr() as *const _,
+                gl::STATIC_DRAW,
+            );
+            gl::EnableVertexAttribArray(0);
+            gl::VertexAttribPointer(0, 3, gl::FLOAT, gl::FALSE, 0, std::ptr::null());
+            gl::BindVertexArray(0);
+        }
+        GlObjects::track(GlObjectKind::VertexArray, vao);
+        GlObjects::track(GlObjectKind::Buffer, vbo);
+        GlObjects::track(GlObjectKind::Buffer, ebo);
+        Ok(Self {
+            program,
+            vao,
+            vbo,
+            ebo,
+            states: HashMap::new(),
+            frame: 0,
+            view_projection: Mat4::IDENTITY,
+            camera_pos: Vec3::ZERO,
+            saved_cull_face: false,
+            saved_depth_mask: gl::TRUE,
+            saved_color_mask: [gl::TRUE; 4],
+            max_latency: 3,
+            near_margin: 0.1,
+        })
+    }
+
+    fn poll(&mut self) {
+        for state in self.states.values_mut().filter(|s| s.query.is_pending()) {
+            state.query.poll();
+        }
+    }
+
+    /// 开始本帧的遮挡查询
+    ///
+    /// # 参数
+    /// + `view_projection` - 投影矩阵与观察矩阵之积
+    /// + `camera_pos` - 摄像机位置
+    ///
+    /// # 注解
+    ///
+    /// 调用前应已将遮挡物绘制到深度缓冲中，该函数会禁用颜色与深度写入以及面剔除，直到调用 [`OcclusionCuller::end`]
+    pub fn begin(&mut self, view_projection: &Mat4, camera_pos: Vec3) {
+        self.frame += 1;
+        self.view_projection = *view_projection;
+        self.camera_pos = camera_pos;
+        self.poll();
+        unsafe {
+            self.saved_cull_face = gl::IsEnabled(gl::CULL_FACE) == gl::TRUE;
+            gl::GetBooleanv(gl::DEPTH_WRITEMASK, &mut self.saved_depth_mask);
+            gl::GetBooleanv(gl::COLOR_WRITEMASK, self.saved_color_mask.as_mut_ptr());
+            gl::Disable(gl::CULL_FACE);
+            gl::DepthMask(gl::FALSE);
+            gl::ColorMask(gl::FALSE, gl::FALSE, gl::FALSE, gl::FALSE);
+            gl::BindVertexArray(self.vao);
+        }
+        self.program.bind();
+    }
+
+    /// 对对象发起遮挡查询
+    ///
+    /// # 参数
+    /// + `key` - 对象的唯一标识
+    /// + `aabb` - 对象的世界空间包围盒
+    ///
+    /// # 注解
+    ///
+    /// 必须在 [`OcclusionCuller::begin`] 与 [`OcclusionCuller::end`] 之间调用，若该对象上一次的查询结果尚未返回，则不会发起新的查询
+    pub fn query(&mut self, key: u64, aabb: &Aabb) {
+        let state = self.states.entry(key).or_insert_with(|| QueryState {
+            query: OcclusionQuery::new(QueryKind::AnySamplesPassed),
+            issued_frame: 0,
+        });
+        if state.query.is_pending() {
+            return;
+        }
+        let model = Mat4::from_translation(aabb.min) * Mat4::from_scale(aabb.max - aabb.min);
+        self.program.set("uMvp", &(self.view_projection * model));
+        state.query.scope(|| unsafe {
+            gl::DrawElements(
+                gl::TRIANGLES,
+                CUBE_INDICES.len() as i32,
+                gl::UNSIGNED_BYTE,
+                std::ptr::null(),
+            );
+            RenderStats::record_draw(CUBE_INDICES.len() as u64 / 3);
+        });
+        state.issued_frame = self.frame;
+    }
+
+    /// 结束本帧的遮挡查询，恢复调用 [`OcclusionCuller::begin`] 前的渲染状态
+    pub fn end(&mut self) {
+        unsafe {
+            gl::BindVertexArray(0);
+            let [r, g, b, a] = self.saved_color_mask;
+            gl::ColorMask(r, g, b, a);
+            gl::DepthMask(self.saved_depth_mask);
+            if self.saved_cull_face {
+                gl::Enable(gl::CULL_FACE);
+            }
+        }
+    }
+
+    /// 判断对象是否可能可见
+    ///
+    /// # 参数
+    /// + `key` - 对象的唯一标识
+    ///
+    /// # 返回值
+    /// 对象被确认遮挡时返回`false`，否则返回`true`
+    pub fn is_visible(&self, key: u64) -> bool {
+        match self.states.get(&key) {
+            None => true,
+            Some(state)
+                if state.query.is_pending() && self.frame - state.issued_frame > self.max_latency =>
+            {
+                true
+            }
+            Some(state) => state.query.is_visible().unwrap_or(true),
+        }
+    }
+
+    /// 判断对象是否可能可见，摄像机位于包围盒内时总是视为可见
+    ///
+    /// # 参数
+    /// + `key` - 对象的唯一标识
+    /// + `aabb` - 对象的世界空间包围盒
+    pub fn is_visible_with(&self, key: u64, aabb: &Aabb) -> bool {
+        let margin = Vec3::splat(self.near_margin);
+        Aabb::new(aabb.min - margin, aabb.max + margin).contains_point(self.camera_pos)
+            || self.is_visible(key)
+    }
+
+    /// 以对象最近一次的遮挡查询为条件执行绘制，被遮挡时由GPU跳过绘制而无需读回结果
+    ///
+    /// # 参数
+    /// + `key` - 对象的唯一标识
+    /// + `mode` - 等待方式
+    /// + `f` - 绘制函数
+    ///
+    /// # 返回值
+    /// 返回`f`的返回值
+    ///
+    /// # 注解
+    ///
+    /// 应在 [`OcclusionCuller::end`] 之后调用，对象尚未发起过查询时不加条件地执行`f`
+    pub fn draw_conditional<R>(&self, key: u64, mode: ConditionalMode, f: impl FnOnce() -> R) -> R {
+        match self.states.get(&key) {
+            Some(state) => state.query.conditional(mode, f),
+            None => f(),
+        }
+    }
+
+    /// 移除对象的查询状态
+    ///
+    /// # 参数
+    /// + `key` - 对象的唯一标识
+    pub fn remove(&mut self, key: u64) {
+        self.states.remove(&key);
+    }
+}
+
+impl Drop for OcclusionCuller {
+    fn drop(&mut self) {
+        GlObjects::untrack(GlObjectKind::VertexArray, self.vao);
+        GlObjects::untrack(GlObjectKind::Buffer, self.vbo);
+        GlObjects::untrack(GlObjectKind::Buffer, self.ebo);
+        unsafe {
+            gl::DeleteBuffers(1, &self.ebo);
+            gl::DeleteBuffers(1, &self.vbo);
+            gl::DeleteVertexArrays(1, &self.vao);
+        }
+    }
+}
@@ -0,0 +1,209 @@
+use std::cell::Cell;
+
+use crate::error::{Error, Result};
+use crate::{
+    draw_fullscreen_triangle, warn, AttachmentFormat, GlObjectKind, GlObjects, Program,
+    RenderTexture, FULLSCREEN_VS,
+};
+
+/// 加权混合顺序无关透明度的片段输出，提供`void writeOit(vec4 color)`
+///
+/// 仅在定义`OIT`时声明输出，自定义半透明着色器可拼接该源码，并在定义`OIT`时以`writeOit`代替直接写入颜色。
+/// `color`为非预乘透明度的线性颜色
+pub const OIT_GLSL: &str = r#"
+#ifdef OIT
+layout(location = 0) out vec4 oitAccum;
+layout(location = 1) out float oitReveal;
+
+void writeOit(vec4 color)
+{
+    // McGuire & Bavoil 2013，式(10)
+    float a = clamp(color.a, 0.0, 1.0);
+    float z = gl_FragCoord.z;
+    float w = clamp(pow(min(1.0, a * 10.0) + 0.01, 3.0) * 1e8 * pow(1.0 - z * 0.9, 3.0), 1e-2, 3e3);
+    oitAccum = vec4(color.rgb * a, a) * w;
+    oitReveal = a;
+}
+#endif
+"#;
+
+const COMPOSITE_FS: &str = r#"
+#version 330 core
+in vec2 vUV;
+out vec4 FragColor;
+
+uniform sampler2D uAccum;
+uniform sampler2D uReveal;
+
+void main()
+{
+    float reveal = texture(uReveal, vUV).r;
+    if (reveal >= 1.0) {
+        discard;
+    }
+    vec4 accum = texture(uAccum, vUV);
+    if (isinf(max(max(abs(accum.r), abs(accum.g)), abs(accum.b)))) {
+        accum.rgb = vec3(accum.a);
+    }
+    vec3 average = accum.rgb / max(accum.a, 1e-5);
+    FragColor = vec4(average, 1.0 - reveal);
+}
+"#;
+
+/// 加权混合顺序无关透明度(Weighted Blended OIT)渲染目标
+///
+/// 半透明物体以任意顺序绘制到累积与透射率两个附件中，随后一次性合成到场景颜色上，
+/// 不再需要对重叠的玻璃、粒子等进行排序。通过 [`RenderQueue::set_oit`](crate::RenderQueue::set_oit) 启用后，
+/// 渲染队列的半透明通道将自动绘制到该目标并在通道结束时合成
+///
+/// # 示例
+///
+/// ```
+/// use gle::*;
+///
+/// fn render_frame(oit: &OitTarget, hdr: &HdrPipeline, queue: &mut RenderQueue, camera: &Camera) {
+///     let depth = hdr.scene().depth().unwrap().id();
+///     queue.set_oit(oit, depth);
+///     hdr.begin();
+///     queue.execute(camera);
+/// }
+/// ```
+///
+/// # 注解
+///
+/// 半透明材质的着色器需使用 [`OIT_GLSL`] 输出(PBR 材质可使用 [`PbrShaderCache::with_oit`](crate::PbrShaderCache::with_oit))。
+/// 需要 OpenGL 4.0 的逐附件混合函数，不支持时 [`OitTarget::new`] 返回错误。
+/// 该类型只能在渲染线程中创建、使用与释放
+pub struct OitTarget {
+    fbo: u32,
+    accum: RenderTexture,
+    reveal: RenderTexture,
+    composite: Program,
+    previous: Cell<u32>,
+}
+
+impl OitTarget {
+    /// 判断当前上下文是否支持加权混合顺序无关透明度
+    pub fn is_supported() -> bool {
+        gl::BlendFunci::is_loaded()
+    }
+
+    /// 创建顺序无关透明度渲染目标
+    ///
+    /// # 参数
+    /// + `width` - 宽度，应与场景颜色目标一致
+    /// + `height` - 高度，应与场景颜色目标一致
+    pub fn new(width: u32, height: u32) -> Result<Self> {
+        if !Self::is_supported() {
+            warn!(Self, "当前上下文不支持逐附件混合函数，无法使用顺序无关透明度");
+            return Err(Error::Gl("不支持 glBlendFunci".to_string()));
+        }
+        let accum = RenderTexture::new(width, height, AttachmentFormat::Rgba16F);
+        let reveal = RenderTexture::new(width, height, AttachmentFormat::R16F);
+        let mut fbo = 0;
+        unsafe {
+            gl::GenFramebuffers(1, &mut fbo);
+            gl::BindFramebuffer(gl::FRAMEBUFFER, fbo);
+            gl::FramebufferTexture2D(
+                gl::FRAMEBUFFER,
+                gl::COLOR_ATTACHMENT0,
+                gl::TEXTURE_2D,
+                accum.id(),
+                0,
+            );
+            gl::FramebufferTexture2D(
+                gl::FRAMEBUFFER,
+                gl::COLOR_ATTACHMENT1,
+                gl::TEXTURE_2D,
+                reveal.id(),
+                0,
+            );
+            let buffers = [gl::COLOR_ATTACHMENT0, gl::COLOR_ATTACHMENT1];
+            gl::DrawBuffers(2, buffers.as_ptr());
+            gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
+        }
+        GlObjects::track(GlObjectKind::Framebuffer, fbo);
+        let composite = Program::new(FULLSCREEN_VS, COMPOSITE_FS)?;
+        composite.bind();
+        composite.set("uAccum", &0);
+        composite.set("uReveal", &1);
+        Ok(Self {
+            fbo,
+            accum,
+            reveal,
+            composite,
+            previous: Cell::new(0),
+        })
+    }
+
+    /// 获取宽度
+    pub fn width(&self) -> u32 {
+        self.accum.width()
+    }
+
+    /// 获取高度
+    pub fn height(&self) -> u32 {
+        self.accum.height()
+    }
+
+    /// 开始绘制半透明物体
+    ///
+    /// # 参数
+    /// + `depth_texture` - 已绘制不透明物体的场景深度纹理，半透明物体将与其进行深度测试(不写入)
+    ///
+    /// # 注解
+    ///
+    /// 记录当前绑定的帧缓冲，以便 [`OitTarget::composite`] 合成回去
+    pub fn begin(&self, depth_texture: u32) {
+        unsafe {
+            let mut previous = 0;
+            gl::GetIntegerv(gl::FRAMEBUFFER_BINDING, &mut previous);
+            self.previous.set(previous as u32);
+            gl::BindFramebuffer(gl::FRAMEBUFFER, self.fbo);
+            gl::FramebufferTexture2D(
+                gl::FRAMEBUFFER,
+                gl::DEPTH_ATTACHMENT,
+                gl::TEXTURE_2D,
+                depth_texture,
+                0,
+            );
+            gl::Viewport(0, 0, self.width() as i32, self.height() as i32);
+            gl::ClearBufferfv(gl::COLOR, 0, [0.0f32; 4].as_ptr());
+            gl::ClearBufferfv(gl::COLOR, 1, [1.0f32; 4].as_ptr());
+            gl::Enable(gl::DEPTH_TEST);
+            gl::DepthMask(gl::FALSE);
+            gl::DepthFunc(gl::LESS);
+            gl::Enable(gl::BLEND);
+            gl::BlendEquation(gl::FUNC_ADD);
+            gl::BlendFunci(0, gl::ONE, gl::ONE);
+            gl::BlendFunci(1, gl::ZERO, gl::ONE_MINUS_SRC_COLOR);
+        }
+    }
+
+    /// 结束绘制并将半透明结果合成到 [`OitTarget::begin`] 时绑定的帧缓冲
+    ///
+    /// # 注解
+    ///
+    /// 合成后混合函数为`(SRC_ALPHA, ONE_MINUS_SRC_ALPHA)`，深度写入保持关闭
+    pub fn composite(&self) {
+        unsafe {
+            gl::BindFramebuffer(gl::FRAMEBUFFER, self.fbo);
+            gl::FramebufferTexture2D(gl::FRAMEBUFFER, gl::DEPTH_ATTACHMENT, gl::TEXTURE_2D, 0, 0);
+            gl::BindFramebuffer(gl::FRAMEBUFFER, self.previous.get());
+            gl::Disable(gl::DEPTH_TEST);
+            gl::BlendFunc(gl::SRC_ALPHA, gl::ONE_MINUS_SRC_ALPHA);
+        }
+        self.composite.bind();
+        self.accum.bind(0);
+        self.reveal.bind(1);
+        draw_fullscreen_triangle();
+        unsafe { gl::Enable(gl::DEPTH_TEST) };
+    }
+}
+
+impl Drop for OitTarget {
+    fn drop(&mut self) {
+        GlObjects::untrack(GlObjectKind::Framebuffer, self.fbo);
+        unsafe { gl::DeleteFramebuffers(1, &self.fbo) };
+    }
+}
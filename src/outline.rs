@@ -0,0 +1,328 @@
+use crate::error::Result;
+use crate::math::*;
+use crate::{
+    draw_fullscreen_triangle, error, AttachmentFormat, Entity, Framebuffer, GlObjectKind, GlObjects,
+    PickingPass, PostContext, PostEffect, Program, RenderGraph, ResourceId, FULLSCREEN_VS,
+};
+
+const MASK_FS: &str = r#"
+#version 330 core
+out vec4 FragColor;
+
+uniform usampler2D uIds;
+uniform sampler2D uSelected;
+
+void main()
+{
+    uint id = texelFetch(uIds, ivec2(gl_FragCoord.xy), 0).r;
+    ivec2 size = textureSize(uSelected, 0);
+    int index = int(id);
+    bool selected = id != 0u && index < size.x * size.y
+        && texelFetch(uSelected, ivec2(index % size.x, index / size.x), 0).r > 0.5;
+    // 选中的像素作为种子，记录自身的像素坐标
+    FragColor = selected ? vec4(gl_FragCoord.xy, 0.0, 1.0) : vec4(-1.0);
+}
+"#;
+
+const JFA_FS: &str = r#"
+#version 330 core
+out vec4 FragColor;
+
+uniform sampler2D uSeeds;
+uniform int uStep;
+
+void main()
+{
+    ivec2 p = ivec2(gl_FragCoord.xy);
+    ivec2 size = textureSize(uSeeds, 0);
+    vec4 best = vec4(-1.0);
+    float bestDist = 1e20;
+    for (int y = -1; y <= 1; ++y) {
+        for (int x = -1; x <= 1; ++x) {
+            ivec2 q = p + ivec2(x, y) * uStep;
+            if (any(lessThan(q, ivec2(0))) || any(greaterThanEqual(q, size))) {
+                continue;
+            }
+            vec4 seed = texelFetch(uSeeds, q, 0);
+            if (seed.w <= 0.0) {
+                continue;
+            }
+            float d = distance(seed.xy, gl_FragCoord.xy);
+            if (d < bestDist) {
+                bestDist = d;
+                best = seed;
+            }
+        }
+    }
+    FragColor = best;
+}
+"#;
+
+const COMPOSITE_FS: &str = r#"
+#version 330 core
+in vec2 vUV;
+out vec4 FragColor;
+
+uniform sampler2D uInput;
+uniform sampler2D uSeeds;
+uniform bool uActive;
+uniform vec4 uColor;
+uniform float uWidth;
+uniform float uFill;
+
+void main()
+{
+    vec4 color = texture(uInput, vUV);
+    if (uActive) {
+        ivec2 size = textureSize(uSeeds, 0);
+        ivec2 p = clamp(ivec2(vUV * vec2(size)), ivec2(0), size - 1);
+        vec4 seed = texelFetch(uSeeds, p, 0);
+        if (seed.w > 0.0) {
+            float d = distance(seed.xy, vec2(p) + 0.5);
+            // 距离为 0 的像素属于选中物体本身
+            float a = d < 0.5 ? uFill : 1.0 - smoothstep(uWidth - 1.0, uWidth, d);
+            color.rgb = mix(color.rgb, uColor.rgb, uColor.a * a);
+        }
+    }
+    FragColor = color;
+}
+"#;
+
+/// 选中标记查找表的宽度，ID 按行优先排列
+const LOOKUP_WIDTH: usize = 1024;
+
+/// 选中物体的轮廓高亮
+///
+/// 以 [`PickingPass`] 的实体ID附件为输入，先将选中实体覆盖的像素标记为种子，
+/// 再用跳跃泛洪(JFA)求出每个像素到最近选中像素的距离，最后在距离不超过 [`SelectionOutline::width`]
+/// 的像素上叠加轮廓颜色。轮廓不受物体遮挡，被遮挡的选中物体同样会显示轮廓。
+/// 可作为 [`PostEffect`] 加入后处理链(名称为`"outline"`)，也可通过
+/// [`SelectionOutline::add_to_graph`] 加入渲染图
+///
+/// # 示例
+///
+/// ```no_run
+/// use gle::*;
+///
+/// fn render_loop(
+///     pass: &mut PickingPass,
+///     outline: &mut SelectionOutline,
+///     camera: &Camera,
+///     selected: &[Entity],
+/// ) {
+///     pass.begin(&camera.view_projection());
+///     // ... 以实体ID绘制场景
+///     pass.end();
+///     outline.select(pass, selected);
+///     // 随后在后处理链中应用 outline
+/// }
+/// ```
+///
+/// # 注解
+///
+/// 选中实体的ID随每次绘制拾取通道而变化，因此每帧结束拾取通道后都应调用 [`SelectionOutline::select`]。
+/// 该类型只能在渲染线程中创建、使用与释放
+pub struct SelectionOutline {
+    /// 轮廓颜色，透明度控制叠加强度
+    pub color: Vec4,
+    /// 轮廓宽度，单位为ID附件的像素(默认值为3)
+    pub width: f32,
+    /// 选中物体本身叠加轮廓颜色的比例，范围`[0, 1]`(默认值为0)
+    pub fill: f32,
+    mask_program: Program,
+    jfa_program: Program,
+    composite_program: Program,
+    seeds: [Framebuffer; 2],
+    lookup: u32,
+    ids: u32,
+    active: bool,
+}
+
+impl SelectionOutline {
+    /// 创建轮廓效果
+    ///
+    /// # 返回值
+    /// 成功时返回轮廓效果，着色器编译失败或帧缓冲不完整时返回错误
+    pub fn new() -> Result<Self> {
+        let seed = || Framebuffer::new(1, 1, &[AttachmentFormat::Rgba32F], None);
+        let mut lookup = 0;
+        unsafe {
+            gl::GenTextures(1, &mut lookup);
+            gl::BindTexture(gl::TEXTURE_2D, lookup);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::NEAREST as i32);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::NEAREST as i32);
+            gl::BindTexture(gl::TEXTURE_2D, 0);
+        }
+        GlObjects::track(GlObjectKind::Texture, lookup);
+        Ok(Self {
+            color: Vec4::new(1.0, 0.6, 0.1, 1.0),
+            width: 3.0,
+            fill: 0.0,
+            mask_program: Program::new(FULLSCREEN_VS, MASK_FS)?,
+            jfa_program: Program::new(FULLSCREEN_VS, JFA_FS)?,
+            composite_program: Program::new(FULLSCREEN_VS, COMPOSITE_FS)?,
+            seeds: [seed()?, seed()?],
+            lookup,
+            ids: 0,
+            active: false,
+        })
+    }
+
+    /// 设置本帧选中的实体
+    ///
+    /// # 参数
+    /// + `pass` - 本帧已绘制完成的拾取通道
+    /// + `selected` - 选中的实体，为空时不绘制轮廓
+    pub fn select(&mut self, pass: &PickingPass, selected: &[Entity]) {
+        let ids = pass.ids_of(selected);
+        self.active = !ids.is_empty();
+        if !self.active {
+            return;
+        }
+        let framebuffer = pass.framebuffer();
+        let (w, h) = (framebuffer.width(), framebuffer.height());
+        for seeds in &mut self.seeds {
+            if let Err(e) = seeds.resize(w, h) {
+                error!(Self, "无法调整轮廓缓冲的大小: {}", e);
+                self.active = false;
+                return;
+            }
+        }
+        self.ids = framebuffer.color(0).map_or(0, |texture| texture.id());
+
+        let max = ids.iter().copied().max().unwrap_or(0) as usize;
+        let rows = max / LOOKUP_WIDTH + 1;
+        let mut data = vec![0u8; LOOKUP_WIDTH * rows];
+        for id in ids {
+            data[id as usize] = 255;
+        }
+        unsafe {
+            gl::BindTexture(gl::TEXTURE_2D, self.lookup);
+            gl::PixelStorei(gl::UNPACK_ALIGNMENT, 1);
+            gl::TexImage2D(
+                gl::TEXTURE_2D,
+                0,
+                gl::R8 as i32,
+                LOOKUP_WIDTH as i32,
+                rows as i32,
+                0,
+                gl::RED,
+                gl::UNSIGNED_BYTE,
+                data.as_ptr() as *const _,
+            );
+            gl::PixelStorei(gl::UNPACK_ALIGNMENT, 4);
+            gl::BindTexture(gl::TEXTURE_2D, 0);
+        }
+    }
+
+    /// 将轮廓效果加入渲染图
+    ///
+    /// # 参数
+    /// + `graph` - 渲染图
+    /// + `input` - 场景颜色
+    /// + `output` - 输出目标，为`None`时输出到默认帧缓冲
+    pub fn add_to_graph<'a>(
+        &'a mut self,
+        graph: &mut RenderGraph<'a>,
+        input: ResourceId,
+        output: Option<ResourceId>,
+    ) {
+        let pass = graph.add_pass("outline").read(input);
+        let pass = match output {
+            Some(output) => pass.write(output),
+            None => pass.write_backbuffer(),
+        };
+        pass.execute(move |ctx| self.render(ctx.texture(input)));
+    }
+
+    /// 计算距离场并合成到当前绑定的帧缓冲与视口
+    fn render(&mut self, input: u32) {
+        if self.active {
+            self.flood();
+        }
+        let program = &self.composite_program;
+        program.bind();
+        unsafe {
+            gl::ActiveTexture(gl::TEXTURE0);
+            gl::BindTexture(gl::TEXTURE_2D, input);
+            gl::ActiveTexture(gl::TEXTURE1);
+            gl::BindTexture(gl::TEXTURE_2D, self.seeds[0].color(0).map_or(0, |t| t.id()));
+        }
+        program.set("uInput", &0);
+        program.set("uSeeds", &1);
+        program.set("uActive", &self.active);
+        program.set("uColor", &self.color);
+        program.set("uWidth", &self.width.max(1.0));
+        program.set("uFill", &self.fill.clamp(0.0, 1.0));
+        draw_fullscreen_triangle();
+    }
+
+    /// 生成种子并执行跳跃泛洪，结果位于`seeds[0]`，完成后恢复之前的帧缓冲与视口
+    fn flood(&mut self) {
+        let mut saved_framebuffer = 0;
+        let mut saved_viewport = [0; 4];
+        unsafe {
+            gl::GetIntegerv(gl::FRAMEBUFFER_BINDING, &mut saved_framebuffer);
+            gl::GetIntegerv(gl::VIEWPORT, saved_viewport.as_mut_ptr());
+        }
+
+        self.seeds[0].bind();
+        self.mask_program.bind();
+        unsafe {
+            gl::ActiveTexture(gl::TEXTURE0);
+            gl::BindTexture(gl::TEXTURE_2D, self.ids);
+            gl::ActiveTexture(gl::TEXTURE1);
+            gl::BindTexture(gl::TEXTURE_2D, self.lookup);
+        }
+        self.mask_program.set("uIds", &0);
+        self.mask_program.set("uSelected", &1);
+        draw_fullscreen_triangle();
+
+        // 步长从不小于轮廓宽度的 2 的幂开始逐次减半，最后再以步长 1 修正一次
+        let mut steps = Vec::new();
+        let mut step = (self.width.max(1.0).ceil() as u32).next_power_of_two();
+        while step >= 1 {
+            steps.push(step as i32);
+            step /= 2;
+        }
+        steps.push(1);
+        self.jfa_program.bind();
+        self.jfa_program.set("uSeeds", &0);
+        for step in steps {
+            self.seeds[1].bind();
+            unsafe {
+                gl::ActiveTexture(gl::TEXTURE0);
+                gl::BindTexture(gl::TEXTURE_2D, self.seeds[0].color(0).map_or(0, |t| t.id()));
+            }
+            self.jfa_program.set("uStep", &step);
+            draw_fullscreen_triangle();
+            self.seeds.swap(0, 1);
+        }
+
+        unsafe {
+            gl::BindFramebuffer(gl::FRAMEBUFFER, saved_framebuffer as u32);
+            let [x, y, w, h] = saved_viewport;
+            gl::Viewport(x, y, w, h);
+        }
+    }
+}
+
+impl PostEffect for SelectionOutline {
+    fn name(&self) -> &str {
+        "outline"
+    }
+
+    fn apply(&mut self, ctx: &PostContext, input: u32, output: Option<&Framebuffer>) {
+        ctx.bind_output(output);
+        self.render(input);
+    }
+}
+
+impl Drop for SelectionOutline {
+    fn drop(&mut self) {
+        GlObjects::untrack(GlObjectKind::Texture, self.lookup);
+        unsafe {
+            gl::DeleteTextures(1, &self.lookup);
+        }
+    }
+}
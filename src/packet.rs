@@ -0,0 +1,293 @@
+use std::io::{Read, Write};
+
+use flate2::read::ZlibDecoder;
+use flate2::write::ZlibEncoder;
+use flate2::Compression;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Error, Result};
+
+/// 网络协议版本，线上格式或引擎内置数据包发生不兼容变化时递增
+pub const PROTOCOL_VERSION: u32 = 1;
+
+/// 引擎保留的数据包ID上限，游戏自定义的数据包ID应不小于该值
+pub const RESERVED_PACKET_IDS: u16 = 16;
+
+const FLAG_COMPRESSED: u8 = 1;
+/// 帧头：4 字节长度、1 字节标志与 2 字节数据包ID
+const FRAME_HEADER: usize = 7;
+
+/// 网络数据包
+///
+/// 数据包以 serde 序列化为 bincode，每种数据包由唯一的 [`Packet::ID`] 区分。
+/// 通常配合 serde 的派生宏与 [`packet!`] 宏实现
+///
+/// # 示例
+///
+/// ```
+/// use gle::*;
+/// use serde::{Deserialize, Serialize};
+///
+/// #[derive(Debug, PartialEq, Serialize, Deserialize)]
+/// struct Chat {
+///     text: String,
+/// }
+///
+/// #[derive(Debug, PartialEq, Serialize, Deserialize)]
+/// struct Move {
+///     position: [f32; 3],
+/// }
+///
+/// packet!(Chat = 16, Move = 17);
+///
+/// let codec = PacketCodec::new();
+/// let mut stream = codec.encode(&Chat { text: "hello".into() }).unwrap();
+/// stream.extend(codec.encode(&Move { position: [1.0, 2.0, 3.0] }).unwrap());
+///
+/// let first = codec.decode_frame(&mut stream).unwrap().unwrap();
+/// assert_eq!(first.decode::<Chat>().unwrap().text, "hello");
+/// let second = codec.decode_frame(&mut stream).unwrap().unwrap();
+/// assert!(second.is::<Move>());
+/// assert!(stream.is_empty());
+/// ```
+pub trait Packet: Serialize + DeserializeOwned {
+    /// 数据包ID
+    const ID: u16;
+}
+
+/// 为类型实现 [`Packet`]
+///
+/// # 示例
+///
+/// ```
+/// use gle::*;
+/// use serde::{Deserialize, Serialize};
+///
+/// #[derive(Serialize, Deserialize)]
+/// struct Ping(u64);
+///
+/// packet!(Ping = 20);
+/// assert_eq!(Ping::ID, 20);
+/// ```
+#[macro_export]
+macro_rules! packet {
+    ($($t:ty = $id:expr),* $(,)?) => {
+        $(
+            impl $crate::Packet for $t {
+                const ID: u16 = $id;
+            }
+        )*
+    };
+}
+
+/// 握手请求，连接建立后客户端发送的第一个数据包
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Handshake {
+    /// 客户端的协议版本
+    pub protocol: u32,
+    /// 游戏自定义的版本标识，如游戏版本号或模组列表的摘要
+    pub game: String,
+}
+
+/// 握手响应
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HandshakeResponse {
+    /// 服务器的协议版本
+    pub protocol: u32,
+    /// 拒绝连接的原因，为`None`时表示接受
+    pub rejection: Option<String>,
+    /// 服务器要求的压缩阈值，双方此后均应以该阈值编码数据包
+    pub compression_threshold: Option<u32>,
+}
+
+packet!(Handshake = 0, HandshakeResponse = 1);
+
+impl Handshake {
+    /// 以当前协议版本创建握手请求
+    ///
+    /// # 参数
+    /// + `game` - 游戏自定义的版本标识
+    pub fn new(game: &str) -> Self {
+        Self {
+            protocol: PROTOCOL_VERSION,
+            game: game.to_string(),
+        }
+    }
+
+    /// 在服务器端检查握手请求
+    ///
+    /// # 参数
+    /// + `game` - 服务器的游戏版本标识，须与客户端一致
+    /// + `codec` - 服务器的编解码器，其压缩阈值随响应告知客户端
+    ///
+    /// # 返回值
+    /// 返回应发送给客户端的响应
+    pub fn check(&self, game: &str, codec: &PacketCodec) -> HandshakeResponse {
+        let rejection = if self.protocol != PROTOCOL_VERSION {
+            Some(format!(
+                "协议版本不匹配: 客户端为 {}，服务器为 {}",
+                self.protocol, PROTOCOL_VERSION
+            ))
+        } else if self.game != game {
+            Some(format!("游戏版本不匹配: 客户端为 {}，服务器为 {}", self.game, game))
+        } else {
+            None
+        };
+        HandshakeResponse {
+            protocol: PROTOCOL_VERSION,
+            rejection,
+            compression_threshold: codec.compression_threshold.map(|t| t as u32),
+        }
+    }
+}
+
+impl HandshakeResponse {
+    /// 服务器是否接受了连接
+    pub fn accepted(&self) -> bool {
+        self.rejection.is_none()
+    }
+}
+
+/// 已分帧但尚未反序列化的数据包
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RawPacket {
+    /// 数据包ID
+    pub id: u16,
+    /// 解压后的 bincode 数据
+    pub payload: Vec<u8>,
+}
+
+impl RawPacket {
+    /// 是否为指定类型的数据包
+    pub fn is<P: Packet>(&self) -> bool {
+        self.id == P::ID
+    }
+
+    /// 反序列化为指定类型的数据包
+    ///
+    /// # 返回值
+    /// ID 不匹配或数据无效时返回错误
+    pub fn decode<P: Packet>(&self) -> Result<P> {
+        if self.id != P::ID {
+            return Err(Error::Parse(format!(
+                "数据包ID不匹配: 期望 {}，实际为 {}",
+                P::ID,
+                self.id
+            )));
+        }
+        bincode::deserialize(&self.payload).map_err(|e| Error::Parse(e.to_string()))
+    }
+}
+
+/// 数据包编解码器
+///
+/// 每个数据包编码为一帧：4 字节小端序的帧长度(不含自身)、1 字节标志、2 字节小端序的数据包ID与数据。
+/// 数据不小于压缩阈值时以 zlib 压缩并设置标志位，小数据包不压缩以节省 CPU
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PacketCodec {
+    /// 压缩阈值，单位为字节，为`None`时不压缩
+    pub compression_threshold: Option<usize>,
+    /// 允许接收的最大帧长度，超出时视为协议错误，防止恶意对端耗尽内存
+    pub max_frame_size: usize,
+}
+
+impl Default for PacketCodec {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PacketCodec {
+    /// 创建压缩阈值为 256 字节、最大帧长度为 2MB 的编解码器
+    pub fn new() -> Self {
+        Self {
+            compression_threshold: Some(256),
+            max_frame_size: 2 * 1024 * 1024,
+        }
+    }
+
+    /// 编码数据包
+    ///
+    /// # 返回值
+    /// 返回完整的一帧
+    pub fn encode<P: Packet>(&self, packet: &P) -> Result<Vec<u8>> {
+        let payload = bincode::serialize(packet).map_err(|e| Error::Parse(e.to_string()))?;
+        let compress = self
+            .compression_threshold
+            .is_some_and(|threshold| payload.len() >= threshold);
+        let mut frame = vec![0u8; FRAME_HEADER];
+        if compress {
+            let mut encoder = ZlibEncoder::new(frame, Compression::fast());
+            encoder.write_all(&payload)?;
+            frame = encoder.finish()?;
+        } else {
+            frame.extend_from_slice(&payload);
+        }
+        let length = (frame.len() - 4) as u32;
+        frame[0..4].copy_from_slice(&length.to_le_bytes());
+        frame[4] = if compress { FLAG_COMPRESSED } else { 0 };
+        frame[5..7].copy_from_slice(&P::ID.to_le_bytes());
+        Ok(frame)
+    }
+
+    /// 从接收缓冲区中取出一个完整的帧并解码
+    ///
+    /// # 参数
+    /// + `buffer` - 接收缓冲区，成功解码的帧会从开头移除
+    ///
+    /// # 返回值
+    /// 返回数据包，缓冲区中的数据不足一帧时返回`None`，帧无效时返回错误
+    pub fn decode_frame(&self, buffer: &mut Vec<u8>) -> Result<Option<RawPacket>> {
+        if buffer.len() < 4 {
+            return Ok(None);
+        }
+        let length = u32::from_le_bytes([buffer[0], buffer[1], buffer[2], buffer[3]]) as usize;
+        self.check_length(length)?;
+        if buffer.len() < length + 4 {
+            return Ok(None);
+        }
+        let frame: Vec<u8> = buffer.drain(..length + 4).collect();
+        self.decode_body(&frame[4..]).map(Some)
+    }
+
+    /// 将数据包写入字节流
+    pub fn write<W: Write, P: Packet>(&self, writer: &mut W, packet: &P) -> Result<()> {
+        writer.write_all(&self.encode(packet)?)?;
+        Ok(())
+    }
+
+    /// 从字节流中读取一个数据包，阻塞直到读取完整
+    pub fn read<R: Read>(&self, reader: &mut R) -> Result<RawPacket> {
+        let mut length = [0u8; 4];
+        reader.read_exact(&mut length)?;
+        let length = u32::from_le_bytes(length) as usize;
+        self.check_length(length)?;
+        let mut body = vec![0u8; length];
+        reader.read_exact(&mut body)?;
+        self.decode_body(&body)
+    }
+
+    fn check_length(&self, length: usize) -> Result<()> {
+        if length < FRAME_HEADER - 4 || length > self.max_frame_size {
+            return Err(Error::Parse(format!("数据包帧长度 {} 无效", length)));
+        }
+        Ok(())
+    }
+
+    fn decode_body(&self, body: &[u8]) -> Result<RawPacket> {
+        let flags = body[0];
+        let id = u16::from_le_bytes([body[1], body[2]]);
+        let data = &body[3..];
+        let payload = if flags & FLAG_COMPRESSED != 0 {
+            let mut payload = Vec::new();
+            ZlibDecoder::new(data)
+                .take(self.max_frame_size as u64 * 4)
+                .read_to_end(&mut payload)?;
+            payload
+        } else {
+            data.to_vec()
+        };
+        Ok(RawPacket { id, payload })
+    }
+}
@@ -0,0 +1,504 @@
+use std::f32::consts::TAU;
+
+use crate::error::Result;
+use crate::math::*;
+use crate::{
+    Camera, GlObjectKind, GlObjects, Program, Projection, RenderStats, StreamingBuffer, Tweenable,
+};
+
+/// 随生命周期变化的曲线
+///
+/// 由若干`(归一化时间, 值)`关键帧组成，关键帧之间线性插值
+#[derive(Debug, Clone, PartialEq)]
+pub struct Curve<T: Tweenable> {
+    keys: Vec<(f32, T)>,
+}
+
+impl<T: Tweenable> Curve<T> {
+    /// 创建常量曲线
+    pub fn constant(value: T) -> Self {
+        Self {
+            keys: vec![(0.0, value)],
+        }
+    }
+
+    /// 创建从起始值线性变化到结束值的曲线
+    pub fn linear(from: T, to: T) -> Self {
+        Self {
+            keys: vec![(0.0, from), (1.0, to)],
+        }
+    }
+
+    /// 由关键帧创建曲线
+    ///
+    /// # 参数
+    /// + `keys` - 关键帧列表，每项为`(归一化时间, 值)`，不能为空
+    pub fn from_keys(mut keys: Vec<(f32, T)>) -> Self {
+        assert!(!keys.is_empty(), "关键帧列表不能为空");
+        keys.sort_by(|a, b| a.0.total_cmp(&b.0));
+        Self { keys }
+    }
+
+    /// 获取曲线在指定时间的值
+    ///
+    /// # 参数
+    /// + `t` - 归一化时间，范围为`[0, 1]`
+    pub fn sample(&self, t: f32) -> T {
+        let next = self.keys.partition_point(|k| k.0 <= t);
+        if next == 0 {
+            return self.keys[0].1;
+        }
+        if next >= self.keys.len() {
+            return self.keys[self.keys.len() - 1].1;
+        }
+        let (t0, a) = self.keys[next - 1];
+        let (t1, b) = self.keys[next];
+        T::interpolate(a, b, (t - t0) / (t1 - t0))
+    }
+}
+
+/// 粒子发射形状
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EmitterShape {
+    /// 从发射器位置发射
+    Point,
+    /// 从指定半径的球体内随机位置发射
+    Sphere(f32),
+    /// 从指定半边长的长方体内随机位置发射
+    Box(Vec3),
+}
+
+/// 粒子发射器配置
+#[derive(Debug, Clone, PartialEq)]
+pub struct EmitterConfig {
+    /// 每秒发射的粒子数量
+    pub spawn_rate: f32,
+    /// 粒子寿命范围，单位为秒
+    pub lifetime: (f32, f32),
+    /// 发射形状
+    pub shape: EmitterShape,
+    /// 初始速度
+    pub velocity: Vec3,
+    /// 初始速度的随机扰动幅度
+    pub velocity_spread: f32,
+    /// 加速度(如重力)
+    pub acceleration: Vec3,
+    /// 空气阻力系数，每秒速度衰减的比例
+    pub drag: f32,
+    /// 随生命周期变化的尺寸
+    pub size_over_life: Curve<f32>,
+    /// 随生命周期变化的颜色(RGBA)
+    pub color_over_life: Curve<Vec4>,
+    /// 随生命周期变化的速度缩放
+    pub speed_over_life: Curve<f32>,
+    /// 最大粒子数量
+    pub max_particles: usize,
+}
+
+impl Default for EmitterConfig {
+    fn default() -> Self {
+        Self {
+            spawn_rate: 20.0,
+            lifetime: (1.0, 2.0),
+            shape: EmitterShape::Point,
+            velocity: Vec3::Y,
+            velocity_spread: 0.5,
+            acceleration: Vec3::ZERO,
+            drag: 0.0,
+            size_over_life: Curve::constant(0.1),
+            color_over_life: Curve::linear(Vec4::ONE, Vec4::new(1.0, 1.0, 1.0, 0.0)),
+            speed_over_life: Curve::constant(1.0),
+            max_particles: 1000,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Particle {
+    position: Vec3,
+    velocity: Vec3,
+    age: f32,
+    lifetime: f32,
+}
+
+/// 粒子发射器
+///
+/// 在 CPU 上模拟粒子，并生成供 [`ParticleRenderer`] 使用的实例数据
+///
+/// # 示例
+///
+/// ```
+/// use gle::{*, math::*};
+///
+/// let mut emitter = ParticleEmitter::new(EmitterConfig {
+///     spawn_rate: 100.0,
+///     acceleration: Vec3::new(0.0, -9.8, 0.0),
+///     size_over_life: Curve::linear(0.2, 0.0),
+///     ..Default::default()
+/// });
+/// emitter.update(1.0 / 60.0);
+/// ```
+#[derive(Debug, Clone)]
+pub struct ParticleEmitter {
+    /// 发射器配置
+    pub config: EmitterConfig,
+    /// 发射器位置
+    pub position: Vec3,
+    /// 是否持续发射，为`false`时已有粒子仍会继续模拟
+    pub emitting: bool,
+    particles: Vec<Particle>,
+    spawn_accumulator: f32,
+    seed: u32,
+}
+
+impl ParticleEmitter {
+    /// 创建粒子发射器
+    ///
+    /// # 参数
+    /// + `config` - 发射器配置
+    pub fn new(config: EmitterConfig) -> Self {
+        Self {
+            config,
+            position: Vec3::ZERO,
+            emitting: true,
+            particles: Vec::new(),
+            spawn_accumulator: 0.0,
+            seed: 0x9E37_79B9,
+        }
+    }
+
+    /// 获取存活粒子数量
+    pub fn len(&self) -> usize {
+        self.particles.len()
+    }
+
+    /// 判断是否没有存活粒子
+    pub fn is_empty(&self) -> bool {
+        self.particles.is_empty()
+    }
+
+    /// 清除所有粒子
+    pub fn clear(&mut self) {
+        self.particles.clear();
+        self.spawn_accumulator = 0.0;
+    }
+
+    fn random(&mut self) -> f32 {
+        // xorshift32
+        self.seed ^= self.seed << 13;
+        self.seed ^= self.seed >> 17;
+        self.seed ^= self.seed << 5;
+        (self.seed >> 8) as f32 / (1u32 << 24) as f32
+    }
+
+    fn random_unit(&mut self) -> Vec3 {
+        let z = self.random() * 2.0 - 1.0;
+        let a = self.random() * TAU;
+        let r = (1.0 - z * z).sqrt();
+        Vec3::new(r * a.cos(), r * a.sin(), z)
+    }
+
+    /// 立即发射指定数量的粒子
+    ///
+    /// # 参数
+    /// + `count` - 粒子数量，超出最大粒子数量的部分将被忽略
+    pub fn burst(&mut self, count: usize) {
+        for _ in 0..count {
+            if self.particles.len() >= self.config.max_particles {
+                break;
+            }
+            let offset = match self.config.shape {
+                EmitterShape::Point => Vec3::ZERO,
+                EmitterShape::Sphere(r) => self.random_unit() * r * self.random().cbrt(),
+                EmitterShape::Box(h) => {
+                    Vec3::new(self.random(), self.random(), self.random()) * 2.0 * h - h
+                }
+            };
+            let velocity =
+                self.config.velocity + self.random_unit() * self.config.velocity_spread * self.random();
+            let (min, max) = self.config.lifetime;
+            let lifetime = min + (max - min) * self.random();
+            self.particles.push(Particle {
+                position: self.position + offset,
+                velocity,
+                age: 0.0,
+                lifetime: lifetime.max(f32::EPSILON),
+            });
+        }
+    }
+
+    /// 推进粒子模拟
+    ///
+    /// # 参数
+    /// + `dt` - 时间增量，单位为秒
+    pub fn update(&mut self, dt: f32) {
+        let config = &self.config;
+        let damping = (1.0 - config.drag * dt).max(0.0);
+        self.particles.retain_mut(|p| {
+            p.age += dt;
+            if p.age >= p.lifetime {
+                return false;
+            }
+            p.velocity = (p.velocity + config.acceleration * dt) * damping;
+            let speed = config.speed_over_life.sample(p.age / p.lifetime);
+            p.position += p.velocity * speed * dt;
+            true
+        });
+        if self.emitting {
+            self.spawn_accumulator += self.config.spawn_rate * dt;
+            let count = self.spawn_accumulator.floor();
+            self.spawn_accumulator -= count;
+            self.burst(count as usize);
+        }
+    }
+
+    /// 生成实例数据
+    ///
+    /// # 参数
+    /// + `data` - 输出缓冲，每个粒子依次写入位置(3)、尺寸(1)、颜色(4)共8个浮点数
+    pub fn write_instances(&self, data: &mut Vec<f32>) {
+        data.clear();
+        data.reserve(self.particles.len() * 8);
+        for p in &self.particles {
+            let t = p.age / p.lifetime;
+            let size = self.config.size_over_life.sample(t);
+            let color = self.config.color_over_life.sample(t);
+            data.extend_from_slice(&[
+                p.position.x,
+                p.position.y,
+                p.position.z,
+                size,
+                color.x,
+                color.y,
+                color.z,
+                color.w,
+            ]);
+        }
+    }
+}
+
+const VS: &str = r#"
+#version 330 core
+layout (location = 0) in vec2 aCorner;
+layout (location = 1) in vec4 aPosSize;
+layout (location = 2) in vec4 aColor;
+
+uniform mat4 uView;
+uniform mat4 uProjection;
+
+out vec2 vUV;
+out vec4 vColor;
+out float vDepth;
+
+void main()
+{
+    vec4 viewPos = uView * vec4(aPosSize.xyz, 1.0);
+    viewPos.xy += aCorner * aPosSize.w;
+    gl_Position = uProjection * viewPos;
+    vUV = aCorner + 0.5;
+    vColor = aColor;
+    vDepth = -viewPos.z;
+}
+"#;
+
+const FS: &str = r#"
+#version 330 core
+in vec2 vUV;
+in vec4 vColor;
+in float vDepth;
+out vec4 FragColor;
+
+uniform sampler2D uDepth;
+uniform bool uSoft;
+uniform bool uOrthographic;
+uniform float uSoftness;
+uniform vec2 uClip;
+uniform vec2 uViewport;
+uniform sampler2D uTexture;
+uniform bool uTextured;
+
+float linearDepth(float d)
+{
+    float n = uClip.x;
+    float f = uClip.y;
+    if (uOrthographic) {
+        return n + d * (f - n);
+    }
+    float z = d * 2.0 - 1.0;
+    return 2.0 * n * f / (f + n - z * (f - n));
+}
+
+void main()
+{
+    vec4 color = vColor;
+    if (uTextured) {
+        color *= texture(uTexture, vUV);
+    } else {
+        float r = length(vUV - 0.5) * 2.0;
+        color.a *= clamp(1.0 - r * r, 0.0, 1.0);
+    }
+    if (uSoft) {
+        float scene = linearDepth(texture(uDepth, gl_FragCoord.xy / uViewport).r);
+        color.a *= clamp((scene - vDepth) / uSoftness, 0.0, 1.0);
+    }
+    if (color.a <= 0.0) {
+        discard;
+    }
+    FragColor = color;
+}
+"#;
+
+const QUAD: [f32; 8] = [-0.5, -0.5, 0.5, -0.5, -0.5, 0.5, 0.5, 0.5];
+
+/// 粒子实例流式缓冲每段的初始字节大小，可容纳 8192 个粒子
+const STREAM_SEGMENT_SIZE: usize = 8192 * 8 * std::mem::size_of::<f32>();
+
+/// 粒子渲染器
+///
+/// 以实例化方式将粒子绘制为面向摄像机的四边形，并支持基于场景深度纹理的软粒子淡出
+///
+/// # 注解
+///
+/// 该类型只能在渲染线程中创建、使用与释放
+pub struct ParticleRenderer {
+    program: Program,
+    vao: u32,
+    quad_vbo: u32,
+    stream: StreamingBuffer,
+    instances: Vec<f32>,
+    /// 软粒子淡出距离，为零时禁用软粒子(默认值为0.5)
+    pub softness: f32,
+    /// 是否使用叠加混合，为`false`时使用透明度混合(默认值为false)
+    pub additive: bool,
+}
+
+impl ParticleRenderer {
+    /// 创建粒子渲染器
+    ///
+    /// # 返回值
+    /// 成功时返回粒子渲染器，内置着色器编译失败时返回错误
+    pub fn new() -> Result<Self> {
+        let program = Program::new(VS, FS)?;
+        let (mut vao, mut quad_vbo) = (0, 0);
+        unsafe {
+            gl::GenVertexArrays(1, &mut vao);
+            gl::GenBuffers(1, &mut quad_vbo);
+            gl::BindVertexArray(vao);
+            gl::BindBuffer(gl::ARRAY_BUFFER, quad_vbo);
+            gl::BufferData(
+                gl::ARRAY_BUFFER,
+                std::mem::size_of_val(&QUAD) as isize,
+                QUAD.as_ptr() as *const _,
+                gl::STATIC_DRAW,
+            );
+            gl::EnableVertexAttribArray(0);
+            gl::VertexAttribPointer(0, 2, gl::FLOAT, gl::FALSE, 0, std::ptr::null());
+            for index in [1, 2] {
+                gl::EnableVertexAttribArray(index);
+                gl::VertexAttribDivisor(index, 1);
+            }
+            gl::BindVertexArray(0);
+        }
+        GlObjects::track(GlObjectKind::VertexArray, vao);
+        GlObjects::track(GlObjectKind::Buffer, quad_vbo);
+        Ok(Self {
+            program,
+            vao,
+            quad_vbo,
+            stream: StreamingBuffer::new(STREAM_SEGMENT_SIZE),
+            instances: Vec::new(),
+            softness: 0.5,
+            additive: false,
+        })
+    }
+
+    /// 绘制发射器中的所有粒子
+    ///
+    /// # 参数
+    /// + `emitter` - 粒子发射器
+    /// + `camera` - 摄像机
+    /// + `depth_texture` - 场景深度纹理，为`None`时禁用软粒子
+    /// + `texture` - 粒子纹理，为`None`时绘制圆形光斑
+    ///
+    /// # 注解
+    ///
+    /// 应在不透明物体绘制完成后调用，绘制期间会启用混合并禁用深度写入，结束后恢复默认状态(禁用混合、启用深度写入)
+    pub fn draw(
+        &mut self,
+        emitter: &ParticleEmitter,
+        camera: &Camera,
+        depth_texture: Option<u32>,
+        texture: Option<u32>,
+    ) {
+        emitter.write_instances(&mut self.instances);
+        if self.instances.is_empty() {
+            return;
+        }
+        let count = (self.instances.len() / 8) as i32;
+        let mut viewport = [0i32; 4];
+        unsafe { gl::GetIntegerv(gl::VIEWPORT, viewport.as_mut_ptr()) };
+        let soft = depth_texture.is_some() && self.softness > 0.0;
+        let (near, far) = camera.clip_planes();
+
+        self.program.bind();
+        self.program.set("uView", &camera.view_matrix());
+        self.program.set("uProjection", &camera.projection_matrix());
+        self.program.set("uSoft", &soft);
+        self.program.set("uSoftness", &self.softness.max(f32::EPSILON));
+        self.program.set(
+            "uOrthographic",
+            &matches!(camera.projection, Projection::Orthographic { .. }),
+        );
+        self.program.set("uClip", &Vec2::new(near, far));
+        self.program
+            .set("uViewport", &Vec2::new(viewport[2] as f32, viewport[3] as f32));
+        self.program.set("uDepth", &0i32);
+        self.program.set("uTexture", &1i32);
+        self.program.set("uTextured", &texture.is_some());
+        let base = self.stream.write(&self.instances);
+        let stride = 8 * std::mem::size_of::<f32>();
+        unsafe {
+            gl::ActiveTexture(gl::TEXTURE0);
+            gl::BindTexture(gl::TEXTURE_2D, depth_texture.unwrap_or(0));
+            gl::ActiveTexture(gl::TEXTURE1);
+            gl::BindTexture(gl::TEXTURE_2D, texture.unwrap_or(0));
+            gl::ActiveTexture(gl::TEXTURE0);
+
+            gl::BindVertexArray(self.vao);
+            gl::BindBuffer(gl::ARRAY_BUFFER, self.stream.id());
+            for (index, offset) in [(1, 0), (2, 4)] {
+                gl::VertexAttribPointer(
+                    index,
+                    4,
+                    gl::FLOAT,
+                    gl::FALSE,
+                    stride as i32,
+                    (base + offset * std::mem::size_of::<f32>()) as *const _,
+                );
+            }
+            gl::Enable(gl::BLEND);
+            if self.additive {
+                gl::BlendFunc(gl::SRC_ALPHA, gl::ONE);
+            } else {
+                gl::BlendFunc(gl::SRC_ALPHA, gl::ONE_MINUS_SRC_ALPHA);
+            }
+            gl::DepthMask(gl::FALSE);
+            gl::DrawArraysInstanced(gl::TRIANGLE_STRIP, 0, 4, count);
+            RenderStats::record_draw(count.max(0) as u64 * 2);
+            gl::BindVertexArray(0);
+            gl::DepthMask(gl::TRUE);
+            gl::Disable(gl::BLEND);
+        }
+    }
+}
+
+impl Drop for ParticleRenderer {
+    fn drop(&mut self) {
+        GlObjects::untrack(GlObjectKind::VertexArray, self.vao);
+        GlObjects::untrack(GlObjectKind::Buffer, self.quad_vbo);
+        unsafe {
+            gl::DeleteBuffers(1, &self.quad_vbo);
+            gl::DeleteVertexArrays(1, &self.vao);
+        }
+    }
+}
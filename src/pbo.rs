@@ -0,0 +1,119 @@
+use std::ptr::null;
+
+use crate::{GlObjectKind, GlObjects, GpuFence};
+
+/// 像素缓冲对象环
+///
+/// 由若干个像素解包缓冲(PBO)轮流承载待上传的像素数据，纹理更新命令从缓冲中异步读取，
+/// CPU 写入下一帧数据时无需等待 GPU 完成上一次上传。常用于视频帧、动态图集等每帧更新的纹理，
+/// 配合 [`Texture2D::update_async`](crate::Texture2D::update_async) 使用
+///
+/// # 示例
+///
+/// ```
+/// use gle::*;
+///
+/// fn render_init() -> (Texture2D, PixelBufferRing) {
+///     let texture = Texture2D::from_rgba8(640, 360, &vec![0; 640 * 360 * 4], true);
+///     (texture, PixelBufferRing::new(3))
+/// }
+///
+/// fn render_loop(texture: &Texture2D, ring: &mut PixelBufferRing, frame: &[u8]) {
+///     texture.update_async(ring, 0, 0, 640, 360, frame);
+///     texture.bind(0);
+/// }
+/// ```
+///
+/// # 注解
+///
+/// 轮到的缓冲仍在被 GPU 读取时(由栅栏判断)，以分配新存储的方式"孤立"旧存储，驱动在上传完成后回收旧存储，
+/// 因此写入永远不会阻塞。该类型的所有方法只能在渲染线程中调用
+#[derive(Debug)]
+pub struct PixelBufferRing {
+    buffers: Vec<u32>,
+    capacities: Vec<usize>,
+    fences: Vec<Option<GpuFence>>,
+    current: usize,
+}
+
+impl PixelBufferRing {
+    /// 创建像素缓冲对象环
+    ///
+    /// # 参数
+    /// + `count` - 缓冲数量，至少为 1，通常取 2 或 3
+    pub fn new(count: usize) -> Self {
+        let count = count.max(1);
+        let mut buffers = vec![0; count];
+        unsafe { gl::GenBuffers(count as i32, buffers.as_mut_ptr()) };
+        for &buffer in &buffers {
+            GlObjects::track(GlObjectKind::Buffer, buffer);
+        }
+        Self {
+            buffers,
+            capacities: vec![0; count],
+            fences: (0..count).map(|_| None).collect(),
+            current: 0,
+        }
+    }
+
+    /// 获取缓冲数量
+    pub fn len(&self) -> usize {
+        self.buffers.len()
+    }
+
+    /// 缓冲环是否为空，由于至少包含一个缓冲，总是返回`false`
+    pub fn is_empty(&self) -> bool {
+        self.buffers.is_empty()
+    }
+
+    /// 将数据写入下一个缓冲，并将其绑定到`GL_PIXEL_UNPACK_BUFFER`
+    ///
+    /// 此后以偏移量`0`作为像素指针调用的纹理上传命令将从该缓冲读取数据，命令提交后应调用 [`PixelBufferRing::end`]
+    ///
+    /// # 参数
+    /// + `data` - 像素数据
+    pub fn begin(&mut self, data: &[u8]) {
+        self.current = (self.current + 1) % self.buffers.len();
+        let i = self.current;
+        let busy = self.fences[i].take().is_some_and(|fence| !fence.is_signaled());
+        unsafe {
+            gl::BindBuffer(gl::PIXEL_UNPACK_BUFFER, self.buffers[i]);
+            if busy || self.capacities[i] < data.len() {
+                // 孤立旧存储：驱动分配新存储，旧存储在 GPU 读取完成后释放
+                let capacity = data.len().max(self.capacities[i]);
+                gl::BufferData(
+                    gl::PIXEL_UNPACK_BUFFER,
+                    capacity as isize,
+                    null(),
+                    gl::STREAM_DRAW,
+                );
+                self.capacities[i] = capacity;
+            }
+            let ptr = gl::MapBufferRange(
+                gl::PIXEL_UNPACK_BUFFER,
+                0,
+                data.len() as isize,
+                gl::MAP_WRITE_BIT | gl::MAP_INVALIDATE_RANGE_BIT | gl::MAP_UNSYNCHRONIZED_BIT,
+            );
+            if !ptr.is_null() {
+                std::ptr::copy_nonoverlapping(data.as_ptr(), ptr as *mut u8, data.len());
+                gl::UnmapBuffer(gl::PIXEL_UNPACK_BUFFER);
+            }
+        }
+    }
+
+    /// 在当前缓冲的读取命令之后插入栅栏，并解除`GL_PIXEL_UNPACK_BUFFER`的绑定
+    pub fn end(&mut self) {
+        self.fences[self.current] = Some(GpuFence::insert());
+        unsafe { gl::BindBuffer(gl::PIXEL_UNPACK_BUFFER, 0) };
+    }
+}
+
+impl Drop for PixelBufferRing {
+    fn drop(&mut self) {
+        for &buffer in &self.buffers {
+            GlObjects::untrack(GlObjectKind::Buffer, buffer);
+        }
+        unsafe { gl::DeleteBuffers(self.buffers.len() as i32, self.buffers.as_ptr()) };
+    }
+}
@@ -0,0 +1,1134 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::error::Result;
+use crate::math::*;
+use crate::{
+    draw_fullscreen_triangle, GlObjectKind, GlObjects, GltfAlphaMode, GltfImport, Material, Mesh,
+    Program, RenderPass, RenderStats, Texture2D, CLIP_PLANES_GLSL, FOG_BINDING, FOG_GLSL,
+    FULLSCREEN_VS, LIGHTING_GLSL, LIGHTS_BINDING, MAX_REFLECTION_PROBES, OIT_GLSL,
+    REFLECTION_PROBE_TEXTURE_UNIT, SHADOWS_BINDING, SHADOW_TEXTURE_UNIT,
+};
+
+/// PBR 着色所用的 BRDF 函数，可被自定义着色器通过字符串拼接复用
+pub const PBR_BRDF_GLSL: &str = r#"
+const float PI = 3.14159265359;
+
+float distributionGGX(float NdotH, float roughness)
+{
+    float a = roughness * roughness;
+    float a2 = a * a;
+    float d = NdotH * NdotH * (a2 - 1.0) + 1.0;
+    return a2 / (PI * d * d);
+}
+
+float geometrySchlickGGX(float NdotX, float k)
+{
+    return NdotX / (NdotX * (1.0 - k) + k);
+}
+
+float geometrySmith(float NdotV, float NdotL, float roughness)
+{
+    float r = roughness + 1.0;
+    float k = r * r / 8.0;
+    return geometrySchlickGGX(NdotV, k) * geometrySchlickGGX(NdotL, k);
+}
+
+vec3 fresnelSchlick(float cosTheta, vec3 F0)
+{
+    return F0 + (1.0 - F0) * pow(clamp(1.0 - cosTheta, 0.0, 1.0), 5.0);
+}
+
+vec3 fresnelSchlickRoughness(float cosTheta, vec3 F0, float roughness)
+{
+    return F0 + (max(vec3(1.0 - roughness), F0) - F0) * pow(clamp(1.0 - cosTheta, 0.0, 1.0), 5.0);
+}
+
+// 返回单个光源的出射辐射度(未乘以光源颜色)
+vec3 evaluateBRDF(vec3 N, vec3 V, vec3 L, vec3 albedo, float metallic, float roughness, vec3 F0)
+{
+    vec3 H = normalize(V + L);
+    float NdotL = max(dot(N, L), 0.0);
+    float NdotV = max(dot(N, V), 1e-4);
+    float NdotH = max(dot(N, H), 0.0);
+    float D = distributionGGX(NdotH, roughness);
+    float G = geometrySmith(NdotV, NdotL, roughness);
+    vec3 F = fresnelSchlick(max(dot(H, V), 0.0), F0);
+    vec3 specular = D * G * F / (4.0 * NdotV * NdotL + 1e-4);
+    vec3 kD = (1.0 - F) * (1.0 - metallic);
+    return (kD * albedo / PI + specular) * NdotL;
+}
+"#;
+
+const PBR_VS_HEAD: &str = r#"
+#version 330 core
+layout (location = 0) in vec3 aPosition;
+layout (location = 1) in vec3 aNormal;
+layout (location = 2) in vec2 aUV;
+"#;
+
+const PBR_VS_MAIN: &str = r#"
+uniform mat4 uModel;
+uniform mat4 uViewProj;
+
+out vec3 vWorldPos;
+out vec3 vNormal;
+out vec2 vUV;
+
+void main()
+{
+    vec4 world = uModel * vec4(aPosition, 1.0);
+    vWorldPos = world.xyz;
+    vNormal = mat3(transpose(inverse(uModel))) * aNormal;
+    vUV = aUV;
+    applyClipPlanes(vWorldPos);
+    gl_Position = uViewProj * world;
+}
+"#;
+
+const PBR_FS_HEAD: &str = r#"
+#version 330 core
+in vec3 vWorldPos;
+in vec3 vNormal;
+in vec2 vUV;
+#ifdef GBUFFER
+layout(location = 0) out vec4 gPosition;
+layout(location = 1) out vec4 gNormal;
+layout(location = 2) out vec4 gAlbedo;
+layout(location = 3) out vec4 gMaterial;
+#elif !defined(OIT)
+layout(location = 0) out vec4 FragColor;
+#endif
+#ifdef WRITE_NORMALS
+layout(location = 1) out vec4 NormalOut;
+#endif
+
+uniform vec3 uCameraPos;
+uniform vec4 uBaseColor;
+uniform float uMetallic;
+uniform float uRoughness;
+uniform vec3 uEmissive;
+uniform float uNormalScale;
+uniform float uOcclusionStrength;
+uniform float uAlphaCutoff;
+
+uniform sampler2D uAlbedoMap;
+uniform sampler2D uNormalMap;
+uniform sampler2D uMetallicRoughnessMap;
+uniform sampler2D uOcclusionMap;
+uniform sampler2D uEmissiveMap;
+"#;
+
+/// 环境光计算，提供`vec3 ambientLight(vec3 N, vec3 V, vec3 albedo, float metallic, float roughness, vec3 F0)`
+/// 与考虑反射探针的`vec3 ambientLightAt(vec3 P, vec3 N, ...)`。
+/// 定义`HAS_IBL`时使用 [`Ibl`] 的贴图，否则使用光照块中的常量环境光，因此需拼接在 [`LIGHTING_GLSL`] 之后；
+/// 同时定义`HAS_REFLECTION_PROBES`时镜面反射混合 [`ReflectionProbes`](crate::ReflectionProbes) 绑定的探针
+pub const PBR_AMBIENT_GLSL: &str = r#"
+uniform samplerCube uIrradianceMap;
+uniform samplerCube uPrefilterMap;
+uniform sampler2D uBrdfLut;
+uniform float uPrefilterLod;
+uniform float uIblIntensity;
+
+vec3 ambientLight(vec3 N, vec3 V, vec3 albedo, float metallic, float roughness, vec3 F0)
+{
+#ifdef HAS_IBL
+    float NdotV = max(dot(N, V), 0.0);
+    vec3 F = fresnelSchlickRoughness(NdotV, F0, roughness);
+    vec3 kD = (1.0 - F) * (1.0 - metallic);
+    vec3 diffuse = texture(uIrradianceMap, N).rgb * albedo;
+    vec3 R = reflect(-V, N);
+    vec3 prefiltered = textureLod(uPrefilterMap, R, roughness * uPrefilterLod).rgb;
+    vec2 brdf = texture(uBrdfLut, vec2(NdotV, roughness)).rg;
+    vec3 specular = prefiltered * (F * brdf.x + brdf.y);
+    return (kD * diffuse + specular) * uIblIntensity;
+#else
+    return uAmbient.rgb * albedo;
+#endif
+}
+
+#if defined(HAS_IBL) && defined(HAS_REFLECTION_PROBES)
+#define MAX_REFLECTION_PROBES 4
+uniform samplerCube uProbeMap0;
+uniform samplerCube uProbeMap1;
+uniform samplerCube uProbeMap2;
+uniform samplerCube uProbeMap3;
+uniform int uProbeCount;
+uniform float uProbeLod;
+uniform vec3 uProbeCenter[MAX_REFLECTION_PROBES];
+uniform vec3 uProbeBoxMin[MAX_REFLECTION_PROBES];
+uniform vec3 uProbeBoxMax[MAX_REFLECTION_PROBES];
+uniform float uProbeBlend[MAX_REFLECTION_PROBES];
+uniform float uProbeIntensity[MAX_REFLECTION_PROBES];
+uniform bool uProbeBoxProjection[MAX_REFLECTION_PROBES];
+
+vec3 sampleProbe(int i, vec3 dir, float lod)
+{
+    if (i == 0) {
+        return textureLod(uProbeMap0, dir, lod).rgb;
+    } else if (i == 1) {
+        return textureLod(uProbeMap1, dir, lod).rgb;
+    } else if (i == 2) {
+        return textureLod(uProbeMap2, dir, lod).rgb;
+    }
+    return textureLod(uProbeMap3, dir, lod).rgb;
+}
+
+// 影响范围内的权重，在盒子边缘的混合距离内由 1 过渡到 0
+float probeWeight(int i, vec3 P)
+{
+    vec3 d = min(P - uProbeBoxMin[i], uProbeBoxMax[i] - P);
+    float inside = min(min(d.x, d.y), d.z);
+    return clamp(inside / max(uProbeBlend[i], 1e-4), 0.0, 1.0);
+}
+
+// 盒投影修正：求反射光线与盒子的交点，以交点相对捕获位置的方向采样
+vec3 probeDirection(int i, vec3 P, vec3 R)
+{
+    if (!uProbeBoxProjection[i]) {
+        return R;
+    }
+    vec3 first = (uProbeBoxMax[i] - P) / R;
+    vec3 second = (uProbeBoxMin[i] - P) / R;
+    vec3 furthest = max(first, second);
+    float dist = min(min(furthest.x, furthest.y), furthest.z);
+    return P + R * dist - uProbeCenter[i];
+}
+#endif
+
+vec3 ambientLightAt(vec3 P, vec3 N, vec3 V, vec3 albedo, float metallic, float roughness, vec3 F0)
+{
+#if defined(HAS_IBL) && defined(HAS_REFLECTION_PROBES)
+    float NdotV = max(dot(N, V), 0.0);
+    vec3 F = fresnelSchlickRoughness(NdotV, F0, roughness);
+    vec3 kD = (1.0 - F) * (1.0 - metallic);
+    vec3 diffuse = texture(uIrradianceMap, N).rgb * albedo * uIblIntensity;
+    vec3 R = reflect(-V, N);
+    // 探针按优先级由高到低叠加，剩余的权重由全局环境图补足
+    vec3 prefiltered = vec3(0.0);
+    float total = 0.0;
+    for (int i = 0; i < MAX_REFLECTION_PROBES; ++i) {
+        if (i >= uProbeCount || total >= 1.0) {
+            break;
+        }
+        float w = probeWeight(i, P) * (1.0 - total);
+        if (w > 0.0) {
+            vec3 dir = probeDirection(i, P, R);
+            prefiltered += sampleProbe(i, dir, roughness * uProbeLod) * uProbeIntensity[i] * w;
+            total += w;
+        }
+    }
+    prefiltered += textureLod(uPrefilterMap, R, roughness * uPrefilterLod).rgb
+        * uIblIntensity * (1.0 - total);
+    vec2 brdf = texture(uBrdfLut, vec2(NdotV, roughness)).rg;
+    vec3 specular = prefiltered * (F * brdf.x + brdf.y);
+    return kD * diffuse + specular;
+#else
+    return ambientLight(N, V, albedo, metallic, roughness, F0);
+#endif
+}
+"#;
+
+const PBR_FS_MAIN: &str = r#"
+// 由屏幕空间导数构造切线空间，无需顶点切线
+vec3 perturbNormal(vec3 N, vec3 p, vec2 uv, vec3 mapN)
+{
+    vec3 dp1 = dFdx(p);
+    vec3 dp2 = dFdy(p);
+    vec2 duv1 = dFdx(uv);
+    vec2 duv2 = dFdy(uv);
+    vec3 dp2perp = cross(dp2, N);
+    vec3 dp1perp = cross(N, dp1);
+    vec3 T = dp2perp * duv1.x + dp1perp * duv2.x;
+    vec3 B = dp2perp * duv1.y + dp1perp * duv2.y;
+    float invmax = inversesqrt(max(max(dot(T, T), dot(B, B)), 1e-12));
+    return normalize(mat3(T * invmax, B * invmax, N) * mapN);
+}
+
+void main()
+{
+    vec4 base = uBaseColor;
+#ifdef HAS_ALBEDO_MAP
+    base *= texture(uAlbedoMap, vUV);
+#endif
+#ifdef ALPHA_MASK
+    if (base.a < uAlphaCutoff) {
+        discard;
+    }
+#endif
+    float metallic = uMetallic;
+    float roughness = uRoughness;
+#ifdef HAS_METALLIC_ROUGHNESS_MAP
+    vec4 mr = texture(uMetallicRoughnessMap, vUV);
+    roughness *= mr.g;
+    metallic *= mr.b;
+#endif
+    roughness = clamp(roughness, 0.04, 1.0);
+
+    vec3 N = normalize(vNormal);
+    if (!gl_FrontFacing) {
+        N = -N;
+    }
+#ifdef HAS_NORMAL_MAP
+    vec3 m = texture(uNormalMap, vUV).xyz * 2.0 - 1.0;
+    m.xy *= uNormalScale;
+    N = perturbNormal(N, vWorldPos, vUV, normalize(m));
+#endif
+    float occlusion = 1.0;
+#ifdef HAS_OCCLUSION_MAP
+    occlusion = mix(1.0, texture(uOcclusionMap, vUV).r, uOcclusionStrength);
+#endif
+    vec3 emissive = uEmissive;
+#ifdef HAS_EMISSIVE_MAP
+    emissive *= texture(uEmissiveMap, vUV).rgb;
+#endif
+#ifdef GBUFFER
+    gPosition = vec4(vWorldPos, 1.0);
+    gNormal = vec4(N, roughness);
+    gAlbedo = vec4(base.rgb, metallic);
+    gMaterial = vec4(emissive, occlusion);
+#else
+    vec3 V = normalize(uCameraPos - vWorldPos);
+    vec3 F0 = mix(vec3(0.04), base.rgb, metallic);
+    vec3 Lo = evaluateLights(N, V, vWorldPos, base.rgb, metallic, roughness, F0);
+    vec3 ambient = ambientLightAt(vWorldPos, N, V, base.rgb, metallic, roughness, F0) * occlusion;
+    vec3 color = applyFog(ambient + Lo + emissive, vWorldPos, uCameraPos);
+#ifdef OIT
+    writeOit(vec4(color, base.a));
+#else
+    FragColor = vec4(color, base.a);
+#endif
+#endif
+#ifdef WRITE_NORMALS
+    NormalOut = vec4(N, 1.0);
+#endif
+}
+"#;
+
+/// 金属度-粗糙度工作流的 PBR 材质
+///
+/// 参数含义与 glTF 2.0 材质一致，默认的有光照材质
+///
+/// # 示例
+///
+/// ```
+/// use gle::{*, math::*};
+///
+/// fn render_init() {
+///     let import = GltfImport::load("assets/helmet.glb").unwrap();
+///     let mut shaders = PbrShaderCache::new(false);
+///     let materials: Vec<Material> = PbrMaterial::from_gltf(&import)
+///         .iter()
+///         .map(|m| m.build(&mut shaders).unwrap())
+///         .collect();
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct PbrMaterial {
+    /// 基础颜色系数(线性空间)
+    pub base_color: Vec4,
+    /// 金属度系数
+    pub metallic: f32,
+    /// 粗糙度系数
+    pub roughness: f32,
+    /// 自发光系数(线性空间)
+    pub emissive: Vec3,
+    /// 法线缩放系数
+    pub normal_scale: f32,
+    /// 环境光遮蔽强度
+    pub occlusion_strength: f32,
+    /// 透明模式
+    pub alpha_mode: GltfAlphaMode,
+    /// 是否双面渲染
+    pub double_sided: bool,
+    /// 基础颜色贴图(sRGB)
+    pub albedo_map: Option<Arc<Texture2D>>,
+    /// 切线空间法线贴图
+    pub normal_map: Option<Arc<Texture2D>>,
+    /// 金属度-粗糙度贴图(B 通道为金属度，G 通道为粗糙度)
+    pub metallic_roughness_map: Option<Arc<Texture2D>>,
+    /// 环境光遮蔽贴图(R 通道)
+    pub occlusion_map: Option<Arc<Texture2D>>,
+    /// 自发光贴图(sRGB)
+    pub emissive_map: Option<Arc<Texture2D>>,
+}
+
+impl Default for PbrMaterial {
+    fn default() -> Self {
+        Self {
+            base_color: Vec4::ONE,
+            metallic: 0.0,
+            roughness: 0.5,
+            emissive: Vec3::ZERO,
+            normal_scale: 1.0,
+            occlusion_strength: 1.0,
+            alpha_mode: GltfAlphaMode::Opaque,
+            double_sided: false,
+            albedo_map: None,
+            normal_map: None,
+            metallic_roughness_map: None,
+            occlusion_map: None,
+            emissive_map: None,
+        }
+    }
+}
+
+impl PbrMaterial {
+    /// 由 glTF 导入结果创建所有材质，并上传其引用的纹理
+    ///
+    /// # 参数
+    /// + `import` - glTF 导入结果
+    ///
+    /// # 返回值
+    /// 返回与`import.materials`一一对应的材质，被多个材质引用的纹理只上传一次
+    ///
+    /// # 注解
+    ///
+    /// 只能在渲染线程中调用
+    pub fn from_gltf(import: &GltfImport) -> Vec<PbrMaterial> {
+        let mut cache: HashMap<(usize, bool), Arc<Texture2D>> = HashMap::new();
+        let mut texture = |index: Option<usize>, srgb: bool| {
+            let index = index?;
+            let image = import.images.get(index)?;
+            Some(
+                cache
+                    .entry((index, srgb))
+                    .or_insert_with(|| {
+                        // glTF 图像按自顶向下存储，与其纹理坐标约定一致，无需翻转
+                        Arc::new(Texture2D::from_rgba8(
+                            image.width,
+                            image.height,
+                            &image.pixels,
+                            srgb,
+                        ))
+                    })
+                    .clone(),
+            )
+        };
+        import
+            .materials
+            .iter()
+            .map(|m| PbrMaterial {
+                base_color: m.base_color,
+                metallic: m.metallic,
+                roughness: m.roughness,
+                emissive: m.emissive,
+                normal_scale: m.normal_scale,
+                occlusion_strength: m.occlusion_strength,
+                alpha_mode: m.alpha_mode,
+                double_sided: m.double_sided,
+                albedo_map: texture(m.base_color_texture, true),
+                normal_map: texture(m.normal_texture, false),
+                metallic_roughness_map: texture(m.metallic_roughness_texture, false),
+                occlusion_map: texture(m.occlusion_texture, false),
+                emissive_map: texture(m.emissive_texture, true),
+            })
+            .collect()
+    }
+
+    /// 获取该材质所需的着色器变体宏定义
+    pub fn defines(&self) -> Vec<&'static str> {
+        let mut defines = Vec::new();
+        if self.albedo_map.is_some() {
+            defines.push("HAS_ALBEDO_MAP");
+        }
+        if self.normal_map.is_some() {
+            defines.push("HAS_NORMAL_MAP");
+        }
+        if self.metallic_roughness_map.is_some() {
+            defines.push("HAS_METALLIC_ROUGHNESS_MAP");
+        }
+        if self.occlusion_map.is_some() {
+            defines.push("HAS_OCCLUSION_MAP");
+        }
+        if self.emissive_map.is_some() {
+            defines.push("HAS_EMISSIVE_MAP");
+        }
+        match self.alpha_mode {
+            GltfAlphaMode::Mask(_) => defines.push("ALPHA_MASK"),
+            GltfAlphaMode::Blend => defines.push("ALPHA_BLEND"),
+            GltfAlphaMode::Opaque => {}
+        }
+        defines
+    }
+
+    /// 生成通用材质
+    ///
+    /// # 参数
+    /// + `shaders` - 着色器变体缓存
+    ///
+    /// # 返回值
+    /// 成功时返回设置好所有参数与贴图的材质，着色器编译失败时返回错误
+    ///
+    /// # 注解
+    ///
+    /// `uViewProj`、`uModel`、`uCameraPos`与 IBL 相关 uniform 需由调用者设置，
+    /// 可分别通过 [`RenderQueue`](crate::RenderQueue) 与 [`Ibl::bind`] 完成；
+    /// 光源由 [`LightingSystem`](crate::LightingSystem) 自动提供
+    pub fn build(&self, shaders: &mut PbrShaderCache) -> Result<Material> {
+        let mut material = Material::new(shaders.get(&self.defines())?);
+        material.pass = match self.alpha_mode {
+            GltfAlphaMode::Opaque => RenderPass::Opaque,
+            GltfAlphaMode::Mask(_) => RenderPass::AlphaTest,
+            GltfAlphaMode::Blend => RenderPass::Transparent,
+        };
+        material.set("uBaseColor", self.base_color);
+        material.set("uMetallic", self.metallic);
+        material.set("uRoughness", self.roughness);
+        material.set("uEmissive", self.emissive);
+        material.set("uNormalScale", self.normal_scale);
+        material.set("uOcclusionStrength", self.occlusion_strength);
+        if let GltfAlphaMode::Mask(cutoff) = self.alpha_mode {
+            material.set("uAlphaCutoff", cutoff);
+        }
+        let maps = [
+            ("uAlbedoMap", &self.albedo_map),
+            ("uNormalMap", &self.normal_map),
+            ("uMetallicRoughnessMap", &self.metallic_roughness_map),
+            ("uOcclusionMap", &self.occlusion_map),
+            ("uEmissiveMap", &self.emissive_map),
+        ];
+        for (name, map) in maps {
+            if let Some(texture) = map {
+                material.set_texture(name, texture.clone());
+            }
+        }
+        Ok(material)
+    }
+}
+
+/// PBR 着色器变体缓存
+///
+/// 相同宏定义组合的材质共享同一着色器程序
+pub struct PbrShaderCache {
+    programs: HashMap<Vec<&'static str>, Arc<Program>>,
+    ibl: bool,
+    probes: bool,
+    normals: bool,
+    deferred: bool,
+    oit: bool,
+}
+
+impl PbrShaderCache {
+    /// 创建空的着色器变体缓存
+    ///
+    /// # 参数
+    /// + `ibl` - 是否启用基于图像的环境光，启用时需在绘制前调用 [`Ibl::bind`]，否则使用常量环境光
+    pub fn new(ibl: bool) -> Self {
+        Self {
+            programs: HashMap::new(),
+            ibl,
+            probes: false,
+            normals: false,
+            deferred: false,
+            oit: false,
+        }
+    }
+
+    /// 创建输出 G-buffer 的着色器变体缓存，用于 [`DeferredRenderer`](crate::DeferredRenderer) 的几何通道
+    ///
+    /// # 注解
+    ///
+    /// 该缓存生成的程序不计算光照，环境光与光源在光照解析通道中计算。
+    /// 透明混合材质无法写入 G-buffer，应使用 [`PbrShaderCache::new`] 构建并在解析后以前向方式绘制
+    pub fn deferred() -> Self {
+        Self {
+            programs: HashMap::new(),
+            ibl: false,
+            probes: false,
+            normals: false,
+            deferred: true,
+            oit: false,
+        }
+    }
+
+    /// 设置是否向颜色附件1输出世界空间法线
+    ///
+    /// 启用后渲染目标需要第二个颜色附件(建议使用 [`AttachmentFormat::Rgba16F`](crate::AttachmentFormat::Rgba16F))，
+    /// 输出的法线可作为 [`PostContext::normal`](crate::PostContext::normal) 供屏幕空间环境光遮蔽等后处理使用
+    ///
+    /// # 参数
+    /// + `enabled` - 是否输出法线，修改后已缓存的着色器程序将被丢弃。G-buffer 变体已包含法线，忽略该设置
+    pub fn with_normal_output(mut self, enabled: bool) -> Self {
+        if self.normals != enabled {
+            self.programs.clear();
+        }
+        self.normals = enabled;
+        self
+    }
+
+    /// 设置镜面环境光是否混合反射探针
+    ///
+    /// 启用后需在绘制前调用 [`ReflectionProbes::bind`](crate::ReflectionProbes::bind)，
+    /// 探针的 BRDF 查找表来自 [`Ibl`]，因此只在启用基于图像的环境光时生效
+    ///
+    /// # 参数
+    /// + `enabled` - 是否启用，修改后已缓存的着色器程序将被丢弃
+    pub fn with_reflection_probes(mut self, enabled: bool) -> Self {
+        if self.probes != enabled {
+            self.programs.clear();
+        }
+        self.probes = enabled;
+        self
+    }
+
+    /// 设置半透明混合材质是否输出到顺序无关透明度目标
+    ///
+    /// 启用后包含`ALPHA_BLEND`的变体使用 [`OIT_GLSL`] 输出，需配合 [`RenderQueue::set_oit`](crate::RenderQueue::set_oit) 绘制
+    ///
+    /// # 参数
+    /// + `enabled` - 是否启用，修改后已缓存的着色器程序将被丢弃
+    pub fn with_oit(mut self, enabled: bool) -> Self {
+        if self.oit != enabled {
+            self.programs.clear();
+        }
+        self.oit = enabled;
+        self
+    }
+
+    /// 获取指定宏定义组合的着色器程序，不存在时编译
+    ///
+    /// # 参数
+    /// + `defines` - 宏定义列表，`HAS_IBL`、`HAS_REFLECTION_PROBES`、`WRITE_NORMALS`、`GBUFFER`与`OIT`
+    ///   由缓存根据创建参数自动添加
+    pub fn get(&mut self, defines: &[&'static str]) -> Result<Arc<Program>> {
+        let mut key = defines.to_vec();
+        key.sort_unstable();
+        if let Some(program) = self.programs.get(&key) {
+            return Ok(program.clone());
+        }
+        let fs = format!(
+            "{}{}{}{}{}{}{}",
+            PBR_FS_HEAD,
+            OIT_GLSL,
+            PBR_BRDF_GLSL,
+            LIGHTING_GLSL,
+            FOG_GLSL,
+            PBR_AMBIENT_GLSL,
+            PBR_FS_MAIN
+        );
+        let mut all = key.clone();
+        if self.ibl {
+            all.push("HAS_IBL");
+        }
+        let probes = self.ibl && self.probes && !self.deferred;
+        if probes {
+            all.push("HAS_REFLECTION_PROBES");
+        }
+        let oit = self.oit && key.contains(&"ALPHA_BLEND");
+        if self.deferred {
+            all.push("GBUFFER");
+        } else if oit {
+            all.push("OIT");
+        } else if self.normals {
+            all.push("WRITE_NORMALS");
+        }
+        let vs = [PBR_VS_HEAD, CLIP_PLANES_GLSL, PBR_VS_MAIN].concat();
+        let program = Arc::new(Program::with_defines(&vs, &fs, &all)?);
+        program.set_block_binding("Lights", LIGHTS_BINDING);
+        program.set_block_binding("Shadows", SHADOWS_BINDING);
+        program.set_block_binding("Fog", FOG_BINDING);
+        program.bind();
+        program.set("uShadowMap", &(SHADOW_TEXTURE_UNIT as i32));
+        if probes {
+            for i in 0..MAX_REFLECTION_PROBES {
+                let unit = (REFLECTION_PROBE_TEXTURE_UNIT as usize + i) as i32;
+                program.set(&format!("uProbeMap{}", i), &unit);
+            }
+        }
+        self.programs.insert(key, program.clone());
+        Ok(program)
+    }
+}
+
+const CUBE_VS: &str = r#"
+#version 330 core
+layout (location = 0) in vec3 aPosition;
+
+uniform mat4 uViewProj;
+
+out vec3 vDirection;
+
+void main()
+{
+    vDirection = aPosition;
+    gl_Position = uViewProj * vec4(aPosition, 1.0);
+}
+"#;
+
+const EQUIRECT_FS: &str = r#"
+#version 330 core
+in vec3 vDirection;
+out vec4 FragColor;
+
+uniform sampler2D uEquirect;
+
+void main()
+{
+    vec3 d = normalize(vDirection);
+    vec2 uv = vec2(atan(d.z, d.x) / 6.28318530718 + 0.5, asin(clamp(d.y, -1.0, 1.0)) / 3.14159265359 + 0.5);
+    FragColor = vec4(texture(uEquirect, uv).rgb, 1.0);
+}
+"#;
+
+const IRRADIANCE_FS: &str = r#"
+#version 330 core
+in vec3 vDirection;
+out vec4 FragColor;
+
+uniform samplerCube uEnvironment;
+
+const float PI = 3.14159265359;
+
+void main()
+{
+    vec3 N = normalize(vDirection);
+    vec3 up = abs(N.y) < 0.999 ? vec3(0.0, 1.0, 0.0) : vec3(1.0, 0.0, 0.0);
+    vec3 right = normalize(cross(up, N));
+    up = cross(N, right);
+    vec3 irradiance = vec3(0.0);
+    float samples = 0.0;
+    const float delta = 0.025;
+    for (float phi = 0.0; phi < 2.0 * PI; phi += delta) {
+        for (float theta = 0.0; theta < 0.5 * PI; theta += delta) {
+            vec3 t = vec3(sin(theta) * cos(phi), sin(theta) * sin(phi), cos(theta));
+            vec3 dir = t.x * right + t.y * up + t.z * N;
+            irradiance += texture(uEnvironment, dir).rgb * cos(theta) * sin(theta);
+            samples += 1.0;
+        }
+    }
+    FragColor = vec4(PI * irradiance / samples, 1.0);
+}
+"#;
+
+const IMPORTANCE_SAMPLE_GLSL: &str = r#"
+const float PI = 3.14159265359;
+
+vec2 hammersley(uint i, uint n)
+{
+    uint bits = i;
+    bits = (bits << 16u) | (bits >> 16u);
+    bits = ((bits & 0x55555555u) << 1u) | ((bits & 0xAAAAAAAAu) >> 1u);
+    bits = ((bits & 0x33333333u) << 2u) | ((bits & 0xCCCCCCCCu) >> 2u);
+    bits = ((bits & 0x0F0F0F0Fu) << 4u) | ((bits & 0xF0F0F0F0u) >> 4u);
+    bits = ((bits & 0x00FF00FFu) << 8u) | ((bits & 0xFF00FF00u) >> 8u);
+    return vec2(float(i) / float(n), float(bits) * 2.3283064365386963e-10);
+}
+
+vec3 importanceSampleGGX(vec2 xi, vec3 N, float roughness)
+{
+    float a = roughness * roughness;
+    float phi = 2.0 * PI * xi.x;
+    float cosTheta = sqrt((1.0 - xi.y) / (1.0 + (a * a - 1.0) * xi.y));
+    float sinTheta = sqrt(1.0 - cosTheta * cosTheta);
+    vec3 H = vec3(cos(phi) * sinTheta, sin(phi) * sinTheta, cosTheta);
+    vec3 up = abs(N.z) < 0.999 ? vec3(0.0, 0.0, 1.0) : vec3(1.0, 0.0, 0.0);
+    vec3 tangent = normalize(cross(up, N));
+    vec3 bitangent = cross(N, tangent);
+    return normalize(tangent * H.x + bitangent * H.y + N * H.z);
+}
+"#;
+
+const PREFILTER_FS: &str = r#"
+in vec3 vDirection;
+out vec4 FragColor;
+
+uniform samplerCube uEnvironment;
+uniform float uRoughness;
+uniform float uResolution;
+
+float distributionGGX(float NdotH, float roughness)
+{
+    float a = roughness * roughness;
+    float a2 = a * a;
+    float d = NdotH * NdotH * (a2 - 1.0) + 1.0;
+    return a2 / (PI * d * d);
+}
+
+void main()
+{
+    vec3 N = normalize(vDirection);
+    vec3 V = N;
+    const uint SAMPLES = 1024u;
+    vec3 color = vec3(0.0);
+    float weight = 0.0;
+    for (uint i = 0u; i < SAMPLES; ++i) {
+        vec3 H = importanceSampleGGX(hammersley(i, SAMPLES), N, uRoughness);
+        vec3 L = normalize(2.0 * dot(V, H) * H - V);
+        float NdotL = dot(N, L);
+        if (NdotL > 0.0) {
+            // 按采样概率选择源纹理级别，减少高亮区域的噪点
+            float NdotH = max(dot(N, H), 0.0);
+            float pdf = distributionGGX(NdotH, uRoughness) * 0.25 + 1e-4;
+            float saTexel = 4.0 * PI / (6.0 * uResolution * uResolution);
+            float saSample = 1.0 / (float(SAMPLES) * pdf + 1e-4);
+            float lod = uRoughness == 0.0 ? 0.0 : 0.5 * log2(saSample / saTexel);
+            color += textureLod(uEnvironment, L, lod).rgb * NdotL;
+            weight += NdotL;
+        }
+    }
+    FragColor = vec4(color / max(weight, 1e-4), 1.0);
+}
+"#;
+
+const BRDF_FS: &str = r#"
+in vec2 vUV;
+out vec2 FragColor;
+
+float geometrySchlickGGX(float NdotX, float roughness)
+{
+    float k = roughness * roughness / 2.0;
+    return NdotX / (NdotX * (1.0 - k) + k);
+}
+
+void main()
+{
+    float NdotV = max(vUV.x, 1e-4);
+    float roughness = vUV.y;
+    vec3 V = vec3(sqrt(1.0 - NdotV * NdotV), 0.0, NdotV);
+    vec3 N = vec3(0.0, 0.0, 1.0);
+    float A = 0.0;
+    float B = 0.0;
+    const uint SAMPLES = 1024u;
+    for (uint i = 0u; i < SAMPLES; ++i) {
+        vec3 H = importanceSampleGGX(hammersley(i, SAMPLES), N, roughness);
+        vec3 L = normalize(2.0 * dot(V, H) * H - V);
+        float NdotL = max(L.z, 0.0);
+        float NdotH = max(H.z, 0.0);
+        float VdotH = max(dot(V, H), 0.0);
+        if (NdotL > 0.0) {
+            float G = geometrySchlickGGX(NdotV, roughness) * geometrySchlickGGX(NdotL, roughness);
+            float Gvis = G * VdotH / (NdotH * NdotV);
+            float Fc = pow(1.0 - VdotH, 5.0);
+            A += (1.0 - Fc) * Gvis;
+            B += Fc * Gvis;
+        }
+    }
+    FragColor = vec2(A, B) / float(SAMPLES);
+}
+"#;
+
+/// IBL 贴图所绑定的首个纹理单元，依次为辐照度图、预过滤环境图与 BRDF 查找表
+pub const IBL_TEXTURE_UNIT: u32 = 13;
+
+/// BRDF 查找表的字节数，512×512 的 RG16F 纹理
+const BRDF_LUT_BYTES: i64 = 512 * 512 * 4;
+
+/// 基于图像的光照
+///
+/// 由等距柱状投影的环境贴图生成环境立方体贴图、漫反射辐照度图、镜面预过滤环境图与 BRDF 查找表
+///
+/// # 示例
+///
+/// ```
+/// use gle::*;
+///
+/// fn render_init() {
+///     let sky = Texture2D::load_hdr("assets/sky.hdr", HdrFormat::Rgba16F).unwrap();
+///     let ibl = Ibl::from_equirectangular(sky.id(), 512).unwrap();
+/// }
+/// ```
+///
+/// # 注解
+///
+/// 该类型只能在渲染线程中创建、使用与释放
+#[derive(Debug)]
+pub struct Ibl {
+    environment: u32,
+    irradiance: u32,
+    prefilter: u32,
+    brdf_lut: u32,
+    prefilter_levels: u32,
+    bytes: i64,
+    /// 环境光强度(默认值为1.0)
+    pub intensity: f32,
+}
+
+impl Ibl {
+    /// 由等距柱状投影的环境贴图生成 IBL 贴图
+    ///
+    /// # 参数
+    /// + `equirect` - 等距柱状投影的二维纹理ID，最好为 HDR 格式
+    /// + `size` - 环境立方体贴图的边长
+    ///
+    /// # 返回值
+    /// 成功时返回 IBL，内置着色器编译失败时返回错误
+    ///
+    /// # 注解
+    ///
+    /// 生成过程较为耗时，应在加载阶段调用。调用后帧缓冲绑定与视口将被恢复
+    pub fn from_equirectangular(equirect: u32, size: u32) -> Result<Self> {
+        let equirect_program = Program::new(CUBE_VS, EQUIRECT_FS)?;
+        let irradiance_program = Program::new(CUBE_VS, IRRADIANCE_FS)?;
+        let prefilter_program = prefilter_program()?;
+        let brdf_fs = format!("#version 330 core\n{}{}", IMPORTANCE_SAMPLE_GLSL, BRDF_FS);
+        let brdf_program = Program::new(FULLSCREEN_VS, &brdf_fs)?;
+
+        let cube = Mesh::cube(Vec3::splat(2.0)).upload();
+        let prefilter_size = (size / 4).max(16);
+        let prefilter_levels = prefilter_size.ilog2().min(5) + 1;
+        let mut saved_fbo = 0;
+        let mut saved_viewport = [0i32; 4];
+        let mut fbo = 0;
+        unsafe {
+            gl::GetIntegerv(gl::FRAMEBUFFER_BINDING, &mut saved_fbo);
+            gl::GetIntegerv(gl::VIEWPORT, saved_viewport.as_mut_ptr());
+            gl::GenFramebuffers(1, &mut fbo);
+            gl::BindFramebuffer(gl::FRAMEBUFFER, fbo);
+            gl::Disable(gl::DEPTH_TEST);
+            gl::Disable(gl::CULL_FACE);
+            gl::Enable(gl::TEXTURE_CUBE_MAP_SEAMLESS);
+        }
+        GlObjects::track(GlObjectKind::Framebuffer, fbo);
+
+        // 环境立方体贴图
+        let environment = create_cubemap(size, true);
+        equirect_program.bind();
+        equirect_program.set("uEquirect", &0i32);
+        unsafe {
+            gl::ActiveTexture(gl::TEXTURE0);
+            gl::BindTexture(gl::TEXTURE_2D, equirect);
+        }
+        render_cube_faces(&equirect_program, &cube, environment, size, 0);
+        unsafe {
+            gl::BindTexture(gl::TEXTURE_CUBE_MAP, environment);
+            gl::GenerateMipmap(gl::TEXTURE_CUBE_MAP);
+        }
+
+        // 漫反射辐照度图
+        let irradiance = create_cubemap(32, false);
+        irradiance_program.bind();
+        irradiance_program.set("uEnvironment", &0i32);
+        unsafe { gl::BindTexture(gl::TEXTURE_CUBE_MAP, environment) };
+        render_cube_faces(&irradiance_program, &cube, irradiance, 32, 0);
+
+        // 镜面预过滤环境图
+        let prefilter = create_cubemap(prefilter_size, true);
+        prefilter_program.bind();
+        prefilter_program.set("uEnvironment", &0i32);
+        prefilter_program.set("uResolution", &(size as f32));
+        for level in 0..prefilter_levels {
+            let roughness = level as f32 / (prefilter_levels - 1) as f32;
+            prefilter_program.set("uRoughness", &roughness);
+            render_cube_faces(
+                &prefilter_program,
+                &cube,
+                prefilter,
+                (prefilter_size >> level).max(1),
+                level as i32,
+            );
+        }
+
+        // BRDF 查找表
+        let mut brdf_lut = 0;
+        unsafe {
+            gl::GenTextures(1, &mut brdf_lut);
+            gl::BindTexture(gl::TEXTURE_2D, brdf_lut);
+            gl::TexImage2D(
+                gl::TEXTURE_2D,
+                0,
+                gl::RG16F as i32,
+                512,
+                512,
+                0,
+                gl::RG,
+                gl::FLOAT,
+                std::ptr::null(),
+            );
+            for (name, value) in [
+                (gl::TEXTURE_WRAP_S, gl::CLAMP_TO_EDGE),
+                (gl::TEXTURE_WRAP_T, gl::CLAMP_TO_EDGE),
+                (gl::TEXTURE_MIN_FILTER, gl::LINEAR),
+                (gl::TEXTURE_MAG_FILTER, gl::LINEAR),
+            ] {
+                gl::TexParameteri(gl::TEXTURE_2D, name, value as i32);
+            }
+            gl::FramebufferTexture2D(
+                gl::FRAMEBUFFER,
+                gl::COLOR_ATTACHMENT0,
+                gl::TEXTURE_2D,
+                brdf_lut,
+                0,
+            );
+            gl::Viewport(0, 0, 512, 512);
+        }
+        RenderStats::_track_texture(BRDF_LUT_BYTES);
+        GlObjects::track(GlObjectKind::Texture, brdf_lut);
+        brdf_program.bind();
+        draw_fullscreen_triangle();
+
+        unsafe {
+            gl::BindFramebuffer(gl::FRAMEBUFFER, saved_fbo as u32);
+            gl::DeleteFramebuffers(1, &fbo);
+            gl::Viewport(
+                saved_viewport[0],
+                saved_viewport[1],
+                saved_viewport[2],
+                saved_viewport[3],
+            );
+            gl::Enable(gl::DEPTH_TEST);
+        }
+        GlObjects::untrack(GlObjectKind::Framebuffer, fbo);
+        Ok(Self {
+            environment,
+            irradiance,
+            prefilter,
+            brdf_lut,
+            prefilter_levels,
+            bytes: cubemap_bytes(size, true)
+                + cubemap_bytes(32, false)
+                + cubemap_bytes(prefilter_size, true)
+                + BRDF_LUT_BYTES,
+            intensity: 1.0,
+        })
+    }
+
+    /// 获取环境立方体贴图ID，可用于绘制天空盒
+    pub fn environment(&self) -> u32 {
+        self.environment
+    }
+
+    /// 获取漫反射辐照度立方体贴图ID
+    pub fn irradiance(&self) -> u32 {
+        self.irradiance
+    }
+
+    /// 获取镜面预过滤立方体贴图ID
+    pub fn prefilter(&self) -> u32 {
+        self.prefilter
+    }
+
+    /// 获取 BRDF 查找表纹理ID
+    pub fn brdf_lut(&self) -> u32 {
+        self.brdf_lut
+    }
+
+    /// 将 IBL 贴图绑定到纹理单元并设置 PBR 着色器的相关 uniform
+    ///
+    /// # 参数
+    /// + `program` - 已绑定的 PBR 着色器程序
+    ///
+    /// # 注解
+    ///
+    /// 贴图绑定到从 [`IBL_TEXTURE_UNIT`] 开始的三个纹理单元
+    pub fn bind(&self, program: &Program) {
+        unsafe {
+            gl::ActiveTexture(gl::TEXTURE0 + IBL_TEXTURE_UNIT);
+            gl::BindTexture(gl::TEXTURE_CUBE_MAP, self.irradiance);
+            gl::ActiveTexture(gl::TEXTURE0 + IBL_TEXTURE_UNIT + 1);
+            gl::BindTexture(gl::TEXTURE_CUBE_MAP, self.prefilter);
+            gl::ActiveTexture(gl::TEXTURE0 + IBL_TEXTURE_UNIT + 2);
+            gl::BindTexture(gl::TEXTURE_2D, self.brdf_lut);
+            gl::ActiveTexture(gl::TEXTURE0);
+        }
+        program.set("uIrradianceMap", &(IBL_TEXTURE_UNIT as i32));
+        program.set("uPrefilterMap", &(IBL_TEXTURE_UNIT as i32 + 1));
+        program.set("uBrdfLut", &(IBL_TEXTURE_UNIT as i32 + 2));
+        program.set("uPrefilterLod", &((self.prefilter_levels - 1) as f32));
+        program.set("uIblIntensity", &self.intensity);
+    }
+}
+
+impl Drop for Ibl {
+    fn drop(&mut self) {
+        let textures = [self.environment, self.irradiance, self.prefilter, self.brdf_lut];
+        RenderStats::_track_texture(-self.bytes);
+        for &texture in &textures {
+            GlObjects::untrack(GlObjectKind::Texture, texture);
+        }
+        unsafe { gl::DeleteTextures(textures.len() as i32, textures.as_ptr()) };
+    }
+}
+
+/// 创建镜面预过滤着色器，顶点着色器读取`uViewProj`，片段着色器读取`uEnvironment`、`uRoughness`与`uResolution`
+pub(crate) fn prefilter_program() -> Result<Program> {
+    let fs = format!("#version 330 core\n{}{}", IMPORTANCE_SAMPLE_GLSL, PREFILTER_FS);
+    Program::new(CUBE_VS, &fs)
+}
+
+/// 创建 RGB16F 立方体贴图，返回的纹理已登记到 [`GlObjects`] 与显存统计，删除时应注销
+pub(crate) fn create_cubemap(size: u32, mipmap: bool) -> u32 {
+    let mut id = 0;
+    unsafe {
+        gl::GenTextures(1, &mut id);
+        gl::BindTexture(gl::TEXTURE_CUBE_MAP, id);
+        for face in 0..6 {
+            gl::TexImage2D(
+                gl::TEXTURE_CUBE_MAP_POSITIVE_X + face,
+                0,
+                gl::RGB16F as i32,
+                size as i32,
+                size as i32,
+                0,
+                gl::RGB,
+                gl::FLOAT,
+                std::ptr::null(),
+            );
+        }
+        let min_filter = if mipmap {
+            gl::LINEAR_MIPMAP_LINEAR
+        } else {
+            gl::LINEAR
+        };
+        for (name, value) in [
+            (gl::TEXTURE_WRAP_S, gl::CLAMP_TO_EDGE),
+            (gl::TEXTURE_WRAP_T, gl::CLAMP_TO_EDGE),
+            (gl::TEXTURE_WRAP_R, gl::CLAMP_TO_EDGE),
+            (gl::TEXTURE_MIN_FILTER, min_filter),
+            (gl::TEXTURE_MAG_FILTER, gl::LINEAR),
+        ] {
+            gl::TexParameteri(gl::TEXTURE_CUBE_MAP, name, value as i32);
+        }
+        if mipmap {
+            gl::GenerateMipmap(gl::TEXTURE_CUBE_MAP);
+        }
+    }
+    RenderStats::_track_texture(cubemap_bytes(size, mipmap));
+    GlObjects::track(GlObjectKind::Texture, id);
+    id
+}
+
+/// 估计 [`create_cubemap`] 创建的立方体贴图的字节数，每像素 6 字节，含多级纹理时增加三分之一
+pub(crate) fn cubemap_bytes(size: u32, mipmap: bool) -> i64 {
+    let bytes = size as i64 * size as i64 * 6 * 6;
+    if mipmap {
+        bytes * 4 / 3
+    } else {
+        bytes
+    }
+}
+
+/// 立方体贴图六个面的观察方向与上方向
+pub(crate) const CUBE_FACE_VIEWS: [(Vec3, Vec3); 6] = [
+    (Vec3::X, Vec3::NEG_Y),
+    (Vec3::NEG_X, Vec3::NEG_Y),
+    (Vec3::Y, Vec3::Z),
+    (Vec3::NEG_Y, Vec3::NEG_Z),
+    (Vec3::Z, Vec3::NEG_Y),
+    (Vec3::NEG_Z, Vec3::NEG_Y),
+];
+
+/// 将立方体绘制到立方体贴图的六个面
+pub(crate) fn render_cube_faces(
+    program: &Program,
+    cube: &crate::GpuMesh,
+    target: u32,
+    size: u32,
+    level: i32,
+) {
+    let projection = perspective(90f32.to_radians(), 1.0, 0.1, 10.0);
+    unsafe { gl::Viewport(0, 0, size as i32, size as i32) };
+    for (face, (dir, up)) in CUBE_FACE_VIEWS.into_iter().enumerate() {
+        program.set("uViewProj", &(projection * look_at(Vec3::ZERO, dir, up)));
+        unsafe {
+            gl::FramebufferTexture2D(
+                gl::FRAMEBUFFER,
+                gl::COLOR_ATTACHMENT0,
+                gl::TEXTURE_CUBE_MAP_POSITIVE_X + face as u32,
+                target,
+                level,
+            );
+        }
+        cube.draw();
+    }
+}
@@ -0,0 +1,488 @@
+use rapier3d::na::{Point3, Quaternion, Translation3, UnitQuaternion, Vector3};
+use rapier3d::prelude::{
+    CCDSolver, Collider, ColliderHandle, ColliderSet, DebugRenderBackend, DebugRenderObject,
+    DebugRenderPipeline, DefaultBroadPhase, ImpulseJointSet, IntegrationParameters, IslandManager,
+    Isometry, MultibodyJointSet, NarrowPhase, PhysicsPipeline, QueryFilter, QueryPipeline,
+    RigidBody, RigidBodyHandle, RigidBodySet,
+};
+
+use crate::error::Result;
+use crate::math::*;
+use crate::{Camera, Entity, GlObjectKind, GlObjects, Program, RenderStats, Scene, Transform};
+
+fn to_isometry(translation: Vec3, rotation: Quat) -> Isometry<f32> {
+    Isometry::from_parts(
+        Translation3::new(translation.x, translation.y, translation.z),
+        UnitQuaternion::new_normalize(Quaternion::new(
+            rotation.w, rotation.x, rotation.y, rotation.z,
+        )),
+    )
+}
+
+fn from_isometry(isometry: &Isometry<f32>) -> (Vec3, Quat) {
+    let t = isometry.translation.vector;
+    let q = isometry.rotation;
+    (Vec3::new(t.x, t.y, t.z), Quat::from_xyzw(q.i, q.j, q.k, q.w))
+}
+
+/// 物理刚体组件
+///
+/// 由 [`PhysicsWorld::add_body`] 插入到实体上，记录实体对应的刚体
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PhysicsBody {
+    /// 刚体句柄
+    pub handle: RigidBodyHandle,
+}
+
+/// 物理调试绘制的线段
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PhysicsDebugLine {
+    /// 起点
+    pub start: Vec3,
+    /// 终点
+    pub end: Vec3,
+    /// 颜色(线性空间 RGBA)
+    pub color: Vec4,
+}
+
+struct LineCollector<'a>(&'a mut Vec<PhysicsDebugLine>);
+
+impl DebugRenderBackend for LineCollector<'_> {
+    fn draw_line(
+        &mut self,
+        _object: DebugRenderObject,
+        a: Point3<f32>,
+        b: Point3<f32>,
+        color: [f32; 4],
+    ) {
+        self.0.push(PhysicsDebugLine {
+            start: Vec3::new(a.x, a.y, a.z),
+            end: Vec3::new(b.x, b.y, b.z),
+            color: hsla_to_rgba(color),
+        });
+    }
+}
+
+/// rapier 的调试颜色以 HSLA 表示
+fn hsla_to_rgba([h, s, l, a]: [f32; 4]) -> Vec4 {
+    let c = (1.0 - (2.0 * l - 1.0).abs()) * s;
+    let h = (h / 60.0).rem_euclid(6.0);
+    let x = c * (1.0 - (h % 2.0 - 1.0).abs());
+    let (r, g, b) = match h as u32 {
+        0 => (c, x, 0.0),
+        1 => (x, c, 0.0),
+        2 => (0.0, c, x),
+        3 => (0.0, x, c),
+        4 => (x, 0.0, c),
+        _ => (c, 0.0, x),
+    };
+    let m = l - c * 0.5;
+    Vec4::new(r + m, g + m, b + m, a)
+}
+
+/// rapier3d 物理世界适配器
+///
+/// 持有 rapier 的全部模拟状态，以固定时间步长推进模拟，并在场景图与刚体之间同步位姿：
+/// 每步之前将运动学刚体移动到对应节点的位置，每步之后将动态刚体的位姿写回对应节点的变换。
+/// 需要启用`rapier`特性
+///
+/// # 示例
+///
+/// ```
+/// use gle::{*, math::*};
+/// use gle::rapier3d::prelude::{ColliderBuilder, RigidBodyBuilder};
+///
+/// let mut scene = Scene::new();
+/// let mut physics = PhysicsWorld::new();
+///
+/// let ground = scene.spawn("ground", Transform::IDENTITY);
+/// physics.add_body(
+///     &mut scene,
+///     ground,
+///     RigidBodyBuilder::fixed().build(),
+///     vec![ColliderBuilder::cuboid(50.0, 0.1, 50.0).build()],
+/// );
+/// let crate_box = scene.spawn("crate", Transform::from_translation(Vec3::new(0.0, 5.0, 0.0)));
+/// physics.add_body(
+///     &mut scene,
+///     crate_box,
+///     RigidBodyBuilder::dynamic().build(),
+///     vec![ColliderBuilder::cuboid(0.5, 0.5, 0.5).build()],
+/// );
+///
+/// // 在事件循环中每帧
+/// physics.update(&mut scene, 1.0 / 60.0);
+/// ```
+///
+/// # 注解
+///
+/// 刚体位姿为世界空间，写回时会换算为相对父节点的局部变换，因此父节点的世界变换应已更新。
+/// 节点的缩放不影响碰撞体，碰撞体尺寸需在创建时指定
+pub struct PhysicsWorld {
+    /// 重力加速度
+    pub gravity: Vec3,
+    /// 固定时间步长，单位为秒
+    pub fixed_dt: f32,
+    /// 每次 [`PhysicsWorld::update`] 最多推进的步数，超出的时间被丢弃以免卡顿时雪崩
+    pub max_steps: u32,
+    /// 刚体集合
+    pub bodies: RigidBodySet,
+    /// 碰撞体集合
+    pub colliders: ColliderSet,
+    /// 冲量关节集合
+    pub impulse_joints: ImpulseJointSet,
+    /// 多体关节集合
+    pub multibody_joints: MultibodyJointSet,
+    integration: IntegrationParameters,
+    pipeline: PhysicsPipeline,
+    islands: IslandManager,
+    broad_phase: DefaultBroadPhase,
+    narrow_phase: NarrowPhase,
+    ccd: CCDSolver,
+    queries: QueryPipeline,
+    debug: DebugRenderPipeline,
+    accumulator: f32,
+}
+
+impl Default for PhysicsWorld {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PhysicsWorld {
+    /// 创建重力为`(0, -9.81, 0)`、步长为 1/60 秒的物理世界
+    pub fn new() -> Self {
+        Self {
+            gravity: Vec3::new(0.0, -9.81, 0.0),
+            fixed_dt: 1.0 / 60.0,
+            max_steps: 5,
+            bodies: RigidBodySet::new(),
+            colliders: ColliderSet::new(),
+            impulse_joints: ImpulseJointSet::new(),
+            multibody_joints: MultibodyJointSet::new(),
+            integration: IntegrationParameters::default(),
+            pipeline: PhysicsPipeline::new(),
+            islands: IslandManager::new(),
+            broad_phase: DefaultBroadPhase::new(),
+            narrow_phase: NarrowPhase::new(),
+            ccd: CCDSolver::new(),
+            queries: QueryPipeline::new(),
+            debug: DebugRenderPipeline::default(),
+            accumulator: 0.0,
+        }
+    }
+
+    /// 为实体添加刚体，刚体的初始位姿取自实体节点的世界变换
+    ///
+    /// # 参数
+    /// + `scene` - 场景
+    /// + `entity` - 实体，将被插入 [`PhysicsBody`] 组件
+    /// + `body` - 刚体
+    /// + `colliders` - 附加到刚体上的碰撞体
+    ///
+    /// # 返回值
+    /// 返回刚体句柄
+    pub fn add_body(
+        &mut self,
+        scene: &mut Scene,
+        entity: Entity,
+        mut body: RigidBody,
+        colliders: Vec<Collider>,
+    ) -> RigidBodyHandle {
+        if let Some(world) = scene.world_matrix(entity) {
+            let transform = Transform::from_matrix(&world);
+            body.set_position(to_isometry(transform.translation, transform.rotation), true);
+        }
+        let handle = self.bodies.insert(body);
+        for collider in colliders {
+            self.colliders
+                .insert_with_parent(collider, handle, &mut self.bodies);
+        }
+        scene.insert(entity, PhysicsBody { handle });
+        handle
+    }
+
+    /// 移除实体的刚体及其碰撞体与关节
+    pub fn remove_body(&mut self, scene: &mut Scene, entity: Entity) {
+        if let Some(body) = scene.remove::<PhysicsBody>(entity) {
+            self.bodies.remove(
+                body.handle,
+                &mut self.islands,
+                &mut self.colliders,
+                &mut self.impulse_joints,
+                &mut self.multibody_joints,
+                true,
+            );
+        }
+    }
+
+    /// 获取实体的刚体
+    pub fn body(&self, scene: &Scene, entity: Entity) -> Option<&RigidBody> {
+        let body = scene.component::<PhysicsBody>(entity)?;
+        self.bodies.get(body.handle)
+    }
+
+    /// 获取实体的刚体的可变引用，可用于施加力与冲量
+    pub fn body_mut(&mut self, scene: &Scene, entity: Entity) -> Option<&mut RigidBody> {
+        let body = scene.component::<PhysicsBody>(entity)?;
+        self.bodies.get_mut(body.handle)
+    }
+
+    /// 推进模拟并同步场景，应在每帧调用
+    ///
+    /// # 参数
+    /// + `scene` - 场景
+    /// + `dt` - 帧间隔时间，单位为秒
+    ///
+    /// # 返回值
+    /// 返回本次推进的固定步数
+    pub fn update(&mut self, scene: &mut Scene, dt: f32) -> u32 {
+        self.accumulator += dt;
+        let mut steps = 0;
+        while self.accumulator >= self.fixed_dt && steps < self.max_steps {
+            self.accumulator -= self.fixed_dt;
+            self.sync_kinematic(scene);
+            self.step();
+            steps += 1;
+        }
+        if steps == self.max_steps {
+            self.accumulator = self.accumulator.min(self.fixed_dt);
+        }
+        if steps > 0 {
+            self.sync_to_scene(scene);
+        }
+        steps
+    }
+
+    /// 推进一个固定步长，不与场景同步
+    pub fn step(&mut self) {
+        self.integration.dt = self.fixed_dt;
+        let gravity = Vector3::new(self.gravity.x, self.gravity.y, self.gravity.z);
+        self.pipeline.step(
+            &gravity,
+            &self.integration,
+            &mut self.islands,
+            &mut self.broad_phase,
+            &mut self.narrow_phase,
+            &mut self.bodies,
+            &mut self.colliders,
+            &mut self.impulse_joints,
+            &mut self.multibody_joints,
+            &mut self.ccd,
+            Some(&mut self.queries),
+            &(),
+            &(),
+        );
+    }
+
+    /// 将运动学刚体移动到对应节点的世界位置
+    pub fn sync_kinematic(&mut self, scene: &Scene) {
+        for (entity, body) in scene.query::<PhysicsBody>() {
+            let Some(rigid) = self.bodies.get_mut(body.handle) else {
+                continue;
+            };
+            if !rigid.is_kinematic() {
+                continue;
+            }
+            if let Some(world) = scene.world_matrix(entity) {
+                let transform = Transform::from_matrix(&world);
+                let isometry = to_isometry(transform.translation, transform.rotation);
+                rigid.set_next_kinematic_position(isometry);
+            }
+        }
+    }
+
+    /// 将动态刚体的位姿写回对应节点的局部变换
+    pub fn sync_to_scene(&self, scene: &mut Scene) {
+        let updates: Vec<(Entity, Vec3, Quat)> = scene
+            .query::<PhysicsBody>()
+            .filter_map(|(entity, body)| {
+                let rigid = self.bodies.get(body.handle)?;
+                if !rigid.is_dynamic() {
+                    return None;
+                }
+                let (translation, rotation) = from_isometry(rigid.position());
+                Some((entity, translation, rotation))
+            })
+            .collect();
+        for (entity, translation, rotation) in updates {
+            let parent = scene.get(entity).and_then(|node| node.parent());
+            let parent_world = parent.and_then(|parent| scene.world_matrix(parent));
+            let Some(node) = scene.get_mut(entity) else {
+                continue;
+            };
+            match parent_world {
+                Some(parent_world) => {
+                    let world = Mat4::from_rotation_translation(rotation, translation);
+                    let local = Transform::from_matrix(&(parent_world.inverse() * world));
+                    node.transform.translation = local.translation;
+                    node.transform.rotation = local.rotation;
+                }
+                None => {
+                    node.transform.translation = translation;
+                    node.transform.rotation = rotation;
+                }
+            }
+        }
+    }
+
+    /// 沿射线查找最先命中的碰撞体
+    ///
+    /// # 参数
+    /// + `origin` - 起点
+    /// + `direction` - 方向，无需归一化
+    /// + `max_distance` - 最大检测距离
+    ///
+    /// # 返回值
+    /// 返回命中的碰撞体与命中距离，未命中时返回`None`
+    pub fn raycast(
+        &self,
+        origin: Vec3,
+        direction: Vec3,
+        max_distance: f32,
+    ) -> Option<(ColliderHandle, f32)> {
+        let direction = direction.normalize_or_zero();
+        let ray = rapier3d::prelude::Ray::new(
+            Point3::new(origin.x, origin.y, origin.z),
+            Vector3::new(direction.x, direction.y, direction.z),
+        );
+        self.queries.cast_ray(
+            &self.bodies,
+            &self.colliders,
+            &ray,
+            max_distance,
+            true,
+            QueryFilter::default(),
+        )
+    }
+
+    /// 生成碰撞体、关节与接触点的调试线段
+    pub fn debug_lines(&mut self) -> Vec<PhysicsDebugLine> {
+        let mut lines = Vec::new();
+        self.debug.render(
+            &mut LineCollector(&mut lines),
+            &self.bodies,
+            &self.colliders,
+            &self.impulse_joints,
+            &self.multibody_joints,
+            &self.narrow_phase,
+        );
+        lines
+    }
+}
+
+const DEBUG_VS: &str = r#"
+#version 330 core
+layout (location = 0) in vec3 aPosition;
+layout (location = 1) in vec4 aColor;
+
+uniform mat4 uViewProj;
+
+out vec4 vColor;
+
+void main()
+{
+    vColor = aColor;
+    gl_Position = uViewProj * vec4(aPosition, 1.0);
+}
+"#;
+
+const DEBUG_FS: &str = r#"
+#version 330 core
+in vec4 vColor;
+out vec4 FragColor;
+
+void main()
+{
+    FragColor = vColor;
+}
+"#;
+
+/// 物理调试渲染器，以线框绘制 [`PhysicsWorld::debug_lines`] 生成的线段
+///
+/// # 示例
+///
+/// ```
+/// use gle::*;
+///
+/// fn render_loop(renderer: &PhysicsDebugRenderer, physics: &mut PhysicsWorld, camera: &Camera) {
+///     renderer.draw(&physics.debug_lines(), camera);
+/// }
+/// ```
+///
+/// # 注解
+///
+/// 该类型只能在渲染线程中创建、使用与释放
+pub struct PhysicsDebugRenderer {
+    program: Program,
+    vao: u32,
+    vbo: u32,
+}
+
+impl PhysicsDebugRenderer {
+    /// 创建物理调试渲染器
+    pub fn new() -> Result<Self> {
+        let program = Program::new(DEBUG_VS, DEBUG_FS)?;
+        let (mut vao, mut vbo) = (0, 0);
+        let stride = 7 * std::mem::size_of::<f32>() as i32;
+        unsafe {
+            gl::GenVertexArrays(1, &mut vao);
+            gl::GenBuffers(1, &mut vbo);
+            gl::BindVertexArray(vao);
+            gl::BindBuffer(gl::ARRAY_BUFFER, vbo);
+            gl::EnableVertexAttribArray(0);
+            gl::VertexAttribPointer(0, 3, gl::FLOAT, gl::FALSE, stride, std::ptr::null());
+            gl::EnableVertexAttribArray(1);
+            gl::VertexAttribPointer(1, 4, gl::FLOAT, gl::FALSE, stride, (3 * 4) as *const _);
+            gl::BindVertexArray(0);
+        }
+        GlObjects::track(GlObjectKind::VertexArray, vao);
+        GlObjects::track(GlObjectKind::Buffer, vbo);
+        Ok(Self { program, vao, vbo })
+    }
+
+    /// 绘制线段，深度测试保持开启
+    ///
+    /// # 参数
+    /// + `lines` - 线段
+    /// + `camera` - 摄像机
+    pub fn draw(&self, lines: &[PhysicsDebugLine], camera: &Camera) {
+        if lines.is_empty() {
+            return;
+        }
+        let mut vertices = Vec::with_capacity(lines.len() * 14);
+        for line in lines {
+            for p in [line.start, line.end] {
+                vertices.extend_from_slice(&p.to_array());
+                vertices.extend_from_slice(&line.color.to_array());
+            }
+        }
+        self.program.bind();
+        self.program.set("uViewProj", &camera.view_projection());
+        unsafe {
+            gl::BindBuffer(gl::ARRAY_BUFFER, self.vbo);
+            gl::BufferData(
+                gl::ARRAY_BUFFER,
+                std::mem::size_of_val(vertices.as_slice()) as isize,
+                vertices.as_ptr() as *const _,
+                gl::STREAM_DRAW,
+            );
+            gl::BindVertexArray(self.vao);
+            gl::DrawArrays(gl::LINES, 0, (lines.len() * 2) as i32);
+            RenderStats::record_draw(0);
+            gl::BindVertexArray(0);
+        }
+    }
+}
+
+impl Drop for PhysicsDebugRenderer {
+    fn drop(&mut self) {
+        GlObjects::untrack(GlObjectKind::VertexArray, self.vao);
+        GlObjects::untrack(GlObjectKind::Buffer, self.vbo);
+        unsafe {
+            gl::DeleteBuffers(1, &self.vbo);
+            gl::DeleteVertexArrays(1, &self.vao);
+        }
+    }
+}
@@ -0,0 +1,378 @@
+use std::collections::{HashSet, VecDeque};
+use std::ptr::null;
+use std::rc::Rc;
+use std::sync::Mutex;
+
+use lazy_static::lazy_static;
+
+use crate::error::Result;
+use crate::math::*;
+use crate::{
+    error, App, AttachmentFormat, Entity, Framebuffer, GlObjectKind, GlObjects, GpuFence, GpuMesh,
+    Program,
+};
+
+const VS: &str = r#"
+#version 330 core
+layout (location = 0) in vec3 aPos;
+
+uniform mat4 uModel;
+uniform mat4 uViewProjection;
+
+void main()
+{
+    gl_Position = uViewProjection * uModel * vec4(aPos, 1.0);
+}
+"#;
+
+const FS: &str = r#"
+#version 330 core
+out uint FragId;
+
+uniform uint uId;
+
+void main()
+{
+    FragId = uId;
+}
+"#;
+
+lazy_static! {
+    static ref REQUESTS: Mutex<Vec<(f64, f64)>> = Mutex::new(Vec::new());
+    static ref RESULTS: Mutex<VecDeque<PickResult>> = Mutex::new(VecDeque::new());
+}
+
+/// 拾取结果
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PickResult {
+    /// 请求的横坐标，相对于窗口客户区左上角
+    pub x: f64,
+    /// 请求的纵坐标，相对于窗口客户区左上角
+    pub y: f64,
+    /// 该像素上最近的实体，没有实体时为`None`
+    pub entity: Option<Entity>,
+}
+
+/// 跨线程的异步拾取
+///
+/// 在任意线程(通常是事件线程)中通过 [`Picking::request`] 提交拾取请求，
+/// 渲染线程中的 [`PickingPass`] 在下一次 [`PickingPass::end`] 时发起异步回读，
+/// GPU 完成后的某一帧中结果可由 [`Picking::poll`] 取出，整个过程不会阻塞渲染线程
+///
+/// # 示例
+///
+/// ```no_run
+/// use gle::*;
+///
+/// fn event_loop(selected: &mut Option<Entity>) {
+///     if Input::mouse_pressed(MouseButton::Button1) {
+///         let (x, y) = Input::cursor_pos();
+///         Picking::request(x, y);
+///     }
+///     while let Some(result) = Picking::poll() {
+///         *selected = result.entity;
+///     }
+/// }
+/// ```
+pub struct Picking;
+
+impl Picking {
+    /// 提交拾取请求
+    ///
+    /// # 参数
+    /// + `x` - 横坐标，相对于窗口客户区左上角，与 [`Input::cursor_pos`](crate::Input::cursor_pos) 相同
+    /// + `y` - 纵坐标
+    pub fn request(x: f64, y: f64) {
+        REQUESTS.lock().unwrap().push((x, y));
+    }
+
+    /// 取出最早完成的拾取结果
+    ///
+    /// # 返回值
+    /// 按完成顺序返回结果，没有已完成的结果时返回`None`
+    pub fn poll() -> Option<PickResult> {
+        RESULTS.lock().unwrap().pop_front()
+    }
+
+    /// 丢弃尚未处理的请求与尚未取出的结果
+    pub fn clear() {
+        REQUESTS.lock().unwrap().clear();
+        RESULTS.lock().unwrap().clear();
+    }
+}
+
+/// 正在进行的异步回读
+struct Readback {
+    x: f64,
+    y: f64,
+    buffer: u32,
+    fence: GpuFence,
+    entities: Rc<Vec<Entity>>,
+}
+
+/// 物体拾取渲染通道
+///
+/// 将实体按绘制顺序编号后写入`R32UI`颜色附件(0 表示没有实体)，并带有深度附件，
+/// 因此每个像素记录的是离摄像机最近的实体，实现像素级精确的选择。
+/// 通道的大小在每次 [`PickingPass::begin`] 时与窗口同步
+///
+/// # 示例
+///
+/// ```no_run
+/// use gle::*;
+/// use gle::math::*;
+///
+/// fn render_loop(pass: &mut PickingPass, camera: &Camera, objects: &[(Entity, Mat4, GpuMesh)]) {
+///     pass.begin(&camera.view_projection());
+///     for (entity, model, mesh) in objects {
+///         pass.draw(*entity, model, mesh);
+///     }
+///     pass.end();
+///
+///     // 同步拾取会等待 GPU 完成绘制，只适合偶尔调用
+///     let (x, y) = Input::cursor_pos();
+///     if let Some(entity) = pass.pick(x, y) {
+///         println!("hovering {:?}", entity);
+///     }
+/// }
+/// ```
+///
+/// # 注解
+///
+/// 该类型只能在渲染线程中创建、使用与释放。只需在有拾取请求的帧中绘制通道时，
+/// 可先检查 [`PickingPass::has_requests`]
+pub struct PickingPass {
+    framebuffer: Framebuffer,
+    program: Program,
+    drawn: Vec<Entity>,
+    last_drawn: Rc<Vec<Entity>>,
+    readbacks: Vec<Readback>,
+    saved_framebuffer: i32,
+    saved_viewport: [i32; 4],
+    saved_depth_test: bool,
+}
+
+impl PickingPass {
+    /// 创建拾取通道
+    ///
+    /// # 返回值
+    /// 成功时返回拾取通道，着色器编译失败或帧缓冲不完整时返回错误
+    pub fn new() -> Result<Self> {
+        let (w, h) = App::window_size();
+        let framebuffer = Framebuffer::new(
+            w.max(1) as u32,
+            h.max(1) as u32,
+            &[AttachmentFormat::R32UI],
+            Some(AttachmentFormat::Depth32F),
+        )?;
+        Ok(Self {
+            framebuffer,
+            program: Program::new(VS, FS)?,
+            drawn: Vec::new(),
+            last_drawn: Rc::new(Vec::new()),
+            readbacks: Vec::new(),
+            saved_framebuffer: 0,
+            saved_viewport: [0; 4],
+            saved_depth_test: false,
+        })
+    }
+
+    /// 是否有尚未发起回读的拾取请求
+    pub fn has_requests() -> bool {
+        !REQUESTS.lock().unwrap().is_empty()
+    }
+
+    /// 获取实体ID附件
+    pub fn framebuffer(&self) -> &Framebuffer {
+        &self.framebuffer
+    }
+
+    /// 开始绘制拾取通道：绑定并清空ID附件与深度附件
+    ///
+    /// # 参数
+    /// + `view_projection` - 摄像机的视图投影矩阵
+    pub fn begin(&mut self, view_projection: &Mat4) {
+        unsafe {
+            gl::GetIntegerv(gl::FRAMEBUFFER_BINDING, &mut self.saved_framebuffer);
+            gl::GetIntegerv(gl::VIEWPORT, self.saved_viewport.as_mut_ptr());
+            self.saved_depth_test = gl::IsEnabled(gl::DEPTH_TEST) == gl::TRUE;
+        }
+        let (w, h) = App::window_size();
+        if w > 0 && h > 0 {
+            if let Err(e) = self.framebuffer.resize(w as u32, h as u32) {
+                error!(Self, "无法调整拾取通道的大小: {}", e);
+            }
+        }
+        self.framebuffer.bind();
+        unsafe {
+            gl::ClearBufferuiv(gl::COLOR, 0, [0u32; 4].as_ptr());
+            gl::DepthMask(gl::TRUE);
+            gl::Clear(gl::DEPTH_BUFFER_BIT);
+            gl::Enable(gl::DEPTH_TEST);
+        }
+        self.drawn.clear();
+        self.program.bind();
+        self.program.set("uViewProjection", view_projection);
+    }
+
+    /// 以实体的ID绘制网格
+    ///
+    /// # 参数
+    /// + `entity` - 实体
+    /// + `model` - 模型矩阵
+    /// + `mesh` - 网格
+    pub fn draw(&mut self, entity: Entity, model: &Mat4, mesh: &GpuMesh) {
+        if self.drawn.len() >= u32::MAX as usize - 1 {
+            return;
+        }
+        self.drawn.push(entity);
+        self.program.set("uModel", model);
+        self.program.set("uId", &(self.drawn.len() as u32));
+        mesh.draw();
+    }
+
+    /// 结束绘制：为待处理的拾取请求发起异步回读，收集已完成的回读，并恢复之前的帧缓冲与视口
+    pub fn end(&mut self) {
+        let entities = Rc::new(std::mem::take(&mut self.drawn));
+        let requests = std::mem::take(&mut *REQUESTS.lock().unwrap());
+        for (x, y) in requests {
+            let Some((px, py)) = self.to_pixel(x, y) else {
+                RESULTS.lock().unwrap().push_back(PickResult { x, y, entity: None });
+                continue;
+            };
+            let mut buffer = 0;
+            unsafe {
+                gl::GenBuffers(1, &mut buffer);
+                gl::BindBuffer(gl::PIXEL_PACK_BUFFER, buffer);
+                gl::BufferData(gl::PIXEL_PACK_BUFFER, 4, null(), gl::STREAM_READ);
+                gl::ReadBuffer(gl::COLOR_ATTACHMENT0);
+                gl::ReadPixels(px, py, 1, 1, gl::RED_INTEGER, gl::UNSIGNED_INT, null::<u8>() as _);
+                gl::BindBuffer(gl::PIXEL_PACK_BUFFER, 0);
+            }
+            GlObjects::track(GlObjectKind::Buffer, buffer);
+            let fence = GpuFence::insert();
+            self.readbacks.push(Readback {
+                x,
+                y,
+                buffer,
+                fence,
+                entities: entities.clone(),
+            });
+        }
+        self.last_drawn = entities;
+        unsafe {
+            gl::BindFramebuffer(gl::FRAMEBUFFER, self.saved_framebuffer as u32);
+            let [x, y, w, h] = self.saved_viewport;
+            gl::Viewport(x, y, w, h);
+            if !self.saved_depth_test {
+                gl::Disable(gl::DEPTH_TEST);
+            }
+        }
+        self.collect();
+    }
+
+    /// 同步拾取上一次绘制的通道
+    ///
+    /// # 参数
+    /// + `x` - 横坐标，相对于窗口客户区左上角
+    /// + `y` - 纵坐标
+    ///
+    /// # 返回值
+    /// 返回该像素上最近的实体，坐标在窗口外或没有实体时返回`None`
+    ///
+    /// # 注解
+    ///
+    /// 会等待 GPU 完成之前的所有命令，频繁调用时应使用 [`Picking::request`]
+    pub fn pick(&self, x: f64, y: f64) -> Option<Entity> {
+        let (px, py) = self.to_pixel(x, y)?;
+        let mut id = 0u32;
+        unsafe {
+            let mut saved = 0;
+            gl::GetIntegerv(gl::READ_FRAMEBUFFER_BINDING, &mut saved);
+            gl::BindFramebuffer(gl::READ_FRAMEBUFFER, self.framebuffer.id());
+            gl::ReadBuffer(gl::COLOR_ATTACHMENT0);
+            let ptr = &mut id as *mut u32;
+            gl::ReadPixels(px, py, 1, 1, gl::RED_INTEGER, gl::UNSIGNED_INT, ptr as _);
+            gl::BindFramebuffer(gl::READ_FRAMEBUFFER, saved as u32);
+        }
+        resolve(&self.last_drawn, id)
+    }
+
+    /// 获取实体在上一次绘制的通道中的ID
+    ///
+    /// # 参数
+    /// + `entities` - 实体列表
+    ///
+    /// # 返回值
+    /// 返回这些实体在ID附件中的所有ID，同一实体绘制了多次时有多个ID，未绘制的实体被忽略
+    pub fn ids_of(&self, entities: &[Entity]) -> Vec<u32> {
+        let entities: HashSet<_> = entities.iter().collect();
+        self.last_drawn
+            .iter()
+            .enumerate()
+            .filter(|(_, entity)| entities.contains(entity))
+            .map(|(index, _)| index as u32 + 1)
+            .collect()
+    }
+
+    /// 将窗口坐标转换为通道中的像素坐标
+    fn to_pixel(&self, x: f64, y: f64) -> Option<(i32, i32)> {
+        let (w, h) = App::window_size();
+        let fw = self.framebuffer.width() as f64;
+        let fh = self.framebuffer.height() as f64;
+        let (sx, sy) = if w > 0 && h > 0 {
+            (fw / w as f64, fh / h as f64)
+        } else {
+            (1.0, 1.0)
+        };
+        let px = (x * sx).floor();
+        let py = fh - 1.0 - (y * sy).floor();
+        if px < 0.0 || py < 0.0 || px >= fw || py >= fh {
+            return None;
+        }
+        Some((px as i32, py as i32))
+    }
+
+    /// 收集 GPU 已完成的回读
+    fn collect(&mut self) {
+        let mut results = Vec::new();
+        self.readbacks.retain(|readback| unsafe {
+            if !readback.fence.is_signaled() {
+                return true;
+            }
+            let mut id = 0u32;
+            gl::BindBuffer(gl::PIXEL_PACK_BUFFER, readback.buffer);
+            let ptr = gl::MapBufferRange(gl::PIXEL_PACK_BUFFER, 0, 4, gl::MAP_READ_BIT);
+            if !ptr.is_null() {
+                id = *(ptr as *const u32);
+                gl::UnmapBuffer(gl::PIXEL_PACK_BUFFER);
+            }
+            gl::BindBuffer(gl::PIXEL_PACK_BUFFER, 0);
+            gl::DeleteBuffers(1, &readback.buffer);
+            GlObjects::untrack(GlObjectKind::Buffer, readback.buffer);
+            results.push(PickResult {
+                x: readback.x,
+                y: readback.y,
+                entity: resolve(&readback.entities, id),
+            });
+            false
+        });
+        if !results.is_empty() {
+            RESULTS.lock().unwrap().extend(results);
+        }
+    }
+}
+
+impl Drop for PickingPass {
+    fn drop(&mut self) {
+        for readback in self.readbacks.drain(..) {
+            GlObjects::untrack(GlObjectKind::Buffer, readback.buffer);
+            unsafe { gl::DeleteBuffers(1, &readback.buffer) };
+        }
+    }
+}
+
+/// 将ID转换为实体，0 表示没有实体
+fn resolve(entities: &[Entity], id: u32) -> Option<Entity> {
+    id.checked_sub(1).and_then(|i| entities.get(i as usize)).copied()
+}
@@ -0,0 +1,225 @@
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+
+use crate::error::Result;
+use crate::math::*;
+use crate::{error, App, AttachmentFormat, Camera, Entity, Framebuffer, Scene};
+
+/// 平面反射组件
+///
+/// 反射平面穿过节点的世界位置，法线为节点局部`+Y`方向，适用于镜子与平静的水面。
+/// 可以作为组件插入 [`Scene`]，由 [`PlanarReflections`] 为每个组件渲染一张反射纹理
+///
+/// # 示例
+///
+/// ```
+/// use gle::{*, math::*};
+///
+/// let mut scene = Scene::new();
+/// // 立在 -Z 方向的墙上、朝向 +Z 的镜子
+/// let mirror = scene.spawn("mirror", Transform {
+///     translation: Vec3::new(0.0, 1.5, -3.0),
+///     rotation: Quat::from_rotation_x(90f32.to_radians()),
+///     scale: Vec3::ONE,
+/// });
+/// scene.insert(mirror, PlanarReflector::default());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PlanarReflector {
+    /// 反射纹理相对窗口帧缓冲的分辨率比例(默认值为0.5)
+    pub resolution_scale: f32,
+    /// 裁剪平面沿法线反方向的偏移，避免与平面接触的物体在反射中出现缝隙(默认值为0.02)
+    pub clip_offset: f32,
+    /// 是否渲染反射(默认值为`true`)
+    pub enabled: bool,
+}
+
+impl Default for PlanarReflector {
+    fn default() -> Self {
+        Self {
+            resolution_scale: 0.5,
+            clip_offset: 0.02,
+            enabled: true,
+        }
+    }
+}
+
+/// 平面反射渲染器
+///
+/// 为场景中的每个 [`PlanarReflector`] 以关于反射平面镜像的摄像机绘制场景，并以斜近裁剪面
+/// ([`Camera::oblique_plane`])裁掉平面背后的物体，无需着色器配合`gl_ClipDistance`。
+/// 反射纹理与屏幕对齐，接收反射的着色器以`vec2(screen.x, 1.0 - screen.y)`采样，其中`screen`为片段的屏幕纹理坐标
+///
+/// # 示例
+///
+/// ```no_run
+/// use gle::*;
+///
+/// fn render_frame(
+///     reflections: &mut PlanarReflections,
+///     scene: &Scene,
+///     camera: &Camera,
+///     queue: &mut RenderQueue,
+/// ) {
+///     reflections.render(scene, camera, |camera, _mirror| {
+///         // 绘制场景，但不绘制镜子本身
+///         queue.execute(camera);
+///     });
+///     // 绘制镜子时绑定 reflections.texture(mirror)
+/// }
+/// ```
+///
+/// # 注解
+///
+/// 镜像摄像机的图像上下颠倒且三角形环绕方向相反，回调执行期间正面已设为顺时针。
+/// 摄像机位于平面背面时不渲染该反射。该类型只能在渲染线程中创建、使用与释放
+#[derive(Default)]
+pub struct PlanarReflections {
+    targets: HashMap<Entity, Framebuffer>,
+}
+
+impl PlanarReflections {
+    /// 创建平面反射渲染器
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 计算关于任意平面镜像后的摄像机
+    ///
+    /// # 参数
+    /// + `camera` - 原摄像机
+    /// + `plane` - 世界空间平面`(法线, 距离)`，法线应指向原摄像机一侧
+    /// + `clip_offset` - 斜近裁剪面沿法线反方向的偏移
+    ///
+    /// # 返回值
+    /// 返回镜像后的摄像机，其斜近裁剪面裁掉平面背后的物体，且不显示编辑器网格与坐标轴指示器
+    pub fn reflection_camera(camera: &Camera, plane: Vec4, clip_offset: f32) -> Camera {
+        let n = plane.truncate().normalize_or_zero();
+        let d = plane.w / plane.truncate().length().max(f32::EPSILON);
+        // 世界空间中的镜像，再翻转摄像机局部 Y 轴使其仍为旋转
+        let mirror = Mat3::IDENTITY - 2.0 * Mat3::from_cols(n * n.x, n * n.y, n * n.z);
+        let flip = Mat3::from_diagonal(Vec3::new(1.0, -1.0, 1.0));
+        let rotation = mirror * Mat3::from_quat(camera.rotation) * flip;
+        let mut reflected = *camera;
+        reflected.position = camera.position - 2.0 * (n.dot(camera.position) + d) * n;
+        reflected.rotation = Quat::from_mat3(&rotation).normalize();
+        reflected.oblique_plane = Some(n.extend(d + clip_offset));
+        reflected.show_grid = false;
+        reflected.show_gizmo = false;
+        reflected
+    }
+
+    /// 获取节点上反射平面的世界空间表示`(法线, 距离)`
+    ///
+    /// # 参数
+    /// + `scene` - 场景，应已调用 [`Scene::update_world_transforms`]
+    /// + `entity` - 带有 [`PlanarReflector`] 的节点
+    pub fn plane(scene: &Scene, entity: Entity) -> Option<Vec4> {
+        let world = scene.get(entity)?.world_matrix();
+        let normal = world.y_axis.truncate().normalize_or_zero();
+        if normal == Vec3::ZERO {
+            return None;
+        }
+        let point = world.w_axis.truncate();
+        Some(normal.extend(-normal.dot(point)))
+    }
+
+    /// 获取反射纹理ID
+    ///
+    /// # 返回值
+    /// 该节点尚未渲染过反射时返回`None`
+    pub fn texture(&self, entity: Entity) -> Option<u32> {
+        self.targets.get(&entity)?.color(0).map(|texture| texture.id())
+    }
+
+    /// 获取反射渲染目标
+    pub fn framebuffer(&self, entity: Entity) -> Option<&Framebuffer> {
+        self.targets.get(&entity)
+    }
+
+    /// 渲染场景中所有启用的反射
+    ///
+    /// # 参数
+    /// + `scene` - 场景，应已调用 [`Scene::update_world_transforms`]
+    /// + `camera` - 主摄像机
+    /// + `draw` - 绘制场景的回调，以镜像摄像机与反射节点调用，不应绘制该反射节点本身
+    ///
+    /// # 注解
+    ///
+    /// 回调调用前已绑定并清除对应的渲染目标，结束后恢复调用前绑定的帧缓冲、视口与正面方向。
+    /// 已被移除的反射节点的渲染目标在此时释放
+    pub fn render(
+        &mut self,
+        scene: &Scene,
+        camera: &Camera,
+        mut draw: impl FnMut(&Camera, Entity),
+    ) {
+        let reflectors: Vec<(Entity, PlanarReflector)> = scene
+            .query::<PlanarReflector>()
+            .map(|(entity, reflector)| (entity, *reflector))
+            .collect();
+        self.targets.retain(|entity, _| reflectors.iter().any(|(e, r)| e == entity && r.enabled));
+        if reflectors.is_empty() {
+            return;
+        }
+
+        let mut previous = 0;
+        let mut viewport = [0; 4];
+        unsafe {
+            gl::GetIntegerv(gl::FRAMEBUFFER_BINDING, &mut previous);
+            gl::GetIntegerv(gl::VIEWPORT, viewport.as_mut_ptr());
+        }
+        let (w, h) = App::window_size();
+        for (entity, reflector) in reflectors {
+            let Some(plane) = Self::plane(scene, entity).filter(|_| reflector.enabled) else {
+                continue;
+            };
+            if plane.truncate().dot(camera.position) + plane.w <= 0.0 {
+                continue;
+            }
+            let scale = reflector.resolution_scale.clamp(0.05, 1.0);
+            let width = ((w.max(1) as f32 * scale) as u32).max(1);
+            let height = ((h.max(1) as f32 * scale) as u32).max(1);
+            let target = match self.targets.entry(entity) {
+                Entry::Occupied(entry) => {
+                    let target = entry.into_mut();
+                    if let Err(e) = target.resize(width, height) {
+                        error!(Self, "无法调整反射目标的大小: {}", e);
+                        continue;
+                    }
+                    target
+                }
+                Entry::Vacant(entry) => match create_target(width, height) {
+                    Ok(target) => entry.insert(target),
+                    Err(e) => {
+                        error!(Self, "无法创建反射目标: {}", e);
+                        continue;
+                    }
+                },
+            };
+            target.bind();
+            unsafe {
+                gl::ClearColor(0.0, 0.0, 0.0, 1.0);
+                gl::Clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT);
+                gl::FrontFace(gl::CW);
+            }
+            let mut reflected = Self::reflection_camera(camera, plane, reflector.clip_offset);
+            reflected.set_viewport_size(width as i32, height as i32);
+            draw(&reflected, entity);
+            unsafe { gl::FrontFace(gl::CCW) };
+        }
+        unsafe {
+            gl::BindFramebuffer(gl::FRAMEBUFFER, previous as u32);
+            gl::Viewport(viewport[0], viewport[1], viewport[2], viewport[3]);
+        }
+    }
+}
+
+fn create_target(width: u32, height: u32) -> Result<Framebuffer> {
+    Framebuffer::new(
+        width,
+        height,
+        &[AttachmentFormat::Rgba16F],
+        Some(AttachmentFormat::Depth32F),
+    )
+}
@@ -0,0 +1,67 @@
+use crate::AppBuilder;
+
+/// 系统运行的阶段
+///
+/// 同一阶段的系统按添加顺序运行
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Stage {
+    /// 在事件线程中、事件初始化函数之前运行一次
+    EventStartup,
+    /// 在事件线程中，每帧在事件循环函数之前运行
+    PreEvent,
+    /// 在事件线程中，每帧在事件循环函数之后、本帧的输入状态清空之前运行
+    PostEvent,
+    /// 在渲染线程中、OpenGL 上下文初始化后、渲染初始化函数之前运行一次
+    RenderStartup,
+    /// 在渲染线程中，每帧在渲染循环函数之前运行
+    PreRender,
+    /// 在渲染线程中，每帧在渲染循环函数之后、交换缓冲之前运行
+    PostRender,
+}
+
+impl Stage {
+    /// 是否在渲染线程中运行
+    pub fn is_render(&self) -> bool {
+        matches!(self, Stage::RenderStartup | Stage::PreRender | Stage::PostRender)
+    }
+}
+
+/// 引擎插件
+///
+/// 插件在 [`AppBuilder::add_plugin`] 时向构建器注册自己的系统
+/// ([`AppBuilder::add_system`])、资源([`AppBuilder::insert_resource`])与窗口事件处理函数
+/// ([`AppBuilder::add_event_handler`])，多个插件的注册互不覆盖，
+/// 渲染器、物理、音频等子系统因此可以独立地接入应用
+///
+/// # 示例
+///
+/// ```no_run
+/// use gle::*;
+///
+/// /// 在标题栏显示帧率
+/// struct FpsTitle;
+///
+/// impl EnginePlugin for FpsTitle {
+///     fn build(&self, app: &mut AppBuilder) {
+///         app.add_system(Stage::PostEvent, || {
+///             let title = format!("Game - {:.0} fps", App::render_fps());
+///             Resources::apply(WINDOW_KEY, |w| w.set_title(&title));
+///         });
+///     }
+/// }
+///
+/// AppBuilder::new(1280, 720, "Game")
+///     .add_plugin(FpsTitle)
+///     .add_plugin(DebugOverlayPlugin)
+///     .build()
+///     .exec();
+/// ```
+pub trait EnginePlugin {
+    /// 插件名称，用于避免重复添加，默认为类型名
+    fn name(&self) -> &str {
+        std::any::type_name::<Self>()
+    }
+
+    /// 向构建器注册插件的系统、资源与回调函数
+    fn build(&self, app: &mut AppBuilder);
+}
@@ -0,0 +1,282 @@
+use std::any::Any;
+
+use crate::error::Result;
+use crate::{draw_fullscreen_triangle, AttachmentFormat, Camera, Framebuffer, Program, FULLSCREEN_VS};
+
+const COPY_FS: &str = r#"
+#version 330 core
+in vec2 vUV;
+out vec4 FragColor;
+
+uniform sampler2D uInput;
+
+void main()
+{
+    FragColor = texture(uInput, vUV);
+}
+"#;
+
+/// 后处理通道的输入信息
+///
+/// 颜色之外的场景信息(深度、法线)由渲染场景的帧缓冲提供，在整条后处理链中保持不变
+#[derive(Debug, Clone, Copy)]
+pub struct PostContext<'a> {
+    /// 渲染场景时使用的相机
+    pub camera: &'a Camera,
+    /// 场景深度纹理
+    pub depth: u32,
+    /// 场景世界空间法线纹理(RGB 分量为未编码的单位法线)，为`None`时由需要法线的通道从深度重建
+    pub normal: Option<u32>,
+    /// 最终输出的宽度
+    pub width: u32,
+    /// 最终输出的高度
+    pub height: u32,
+    /// 距上一帧的时间(秒)
+    pub dt: f32,
+}
+
+impl PostContext<'_> {
+    /// 绑定输出目标并设置视口
+    ///
+    /// # 参数
+    /// + `output` - 输出帧缓冲，为`None`时绑定默认帧缓冲并使用上下文中的输出大小
+    pub fn bind_output(&self, output: Option<&Framebuffer>) {
+        match output {
+            Some(framebuffer) => framebuffer.bind(),
+            None => Framebuffer::bind_default(self.width, self.height),
+        }
+    }
+}
+
+/// 后处理效果
+///
+/// # 示例
+///
+/// ```
+/// use gle::*;
+///
+/// struct Invert {
+///     program: Program,
+/// }
+///
+/// impl PostEffect for Invert {
+///     fn name(&self) -> &str {
+///         "invert"
+///     }
+///
+///     fn apply(&mut self, ctx: &PostContext, input: u32, output: Option<&Framebuffer>) {
+///         ctx.bind_output(output);
+///         self.program.bind();
+///         unsafe {
+///             gl::ActiveTexture(gl::TEXTURE0);
+///             gl::BindTexture(gl::TEXTURE_2D, input);
+///         }
+///         self.program.set("uInput", &0);
+///         draw_fullscreen_triangle();
+///     }
+/// }
+/// ```
+pub trait PostEffect: Any {
+    /// 效果名称，在同一条后处理链中应唯一
+    fn name(&self) -> &str;
+
+    /// 输出大小改变时调用，用于重建内部的中间帧缓冲
+    fn resize(&mut self, _width: u32, _height: u32) -> Result<()> {
+        Ok(())
+    }
+
+    /// 执行后处理
+    ///
+    /// # 参数
+    /// + `ctx` - 场景信息
+    /// + `input` - 上一个通道输出的颜色纹理
+    /// + `output` - 输出目标，为`None`时输出到默认帧缓冲，实现应通过 [`PostContext::bind_output`] 绑定
+    fn apply(&mut self, ctx: &PostContext, input: u32, output: Option<&Framebuffer>);
+}
+
+struct Entry {
+    effect: Box<dyn PostEffect>,
+    enabled: bool,
+}
+
+/// 后处理链
+///
+/// 按添加顺序依次执行已启用的后处理效果，中间结果在两个帧缓冲之间交替，最后一个效果直接写入输出目标
+///
+/// # 示例
+///
+/// ```
+/// use gle::*;
+///
+/// fn render_init() {
+///     let mut chain = PostProcessChain::new(1280, 720, AttachmentFormat::Rgba16F).unwrap();
+///     chain.push(Ssao::new(1280, 720).unwrap());
+///     chain.set_enabled("ssao", false);
+/// }
+/// ```
+///
+/// # 注解
+///
+/// 该类型只能在渲染线程中创建、使用与释放
+pub struct PostProcessChain {
+    entries: Vec<Entry>,
+    targets: [Framebuffer; 2],
+    copy: Program,
+}
+
+impl PostProcessChain {
+    /// 创建空的后处理链
+    ///
+    /// # 参数
+    /// + `width` - 输出宽度
+    /// + `height` - 输出高度
+    /// + `format` - 中间结果的颜色格式
+    pub fn new(width: u32, height: u32, format: AttachmentFormat) -> Result<Self> {
+        let copy = Program::new(FULLSCREEN_VS, COPY_FS)?;
+        Ok(Self {
+            entries: Vec::new(),
+            targets: [
+                Framebuffer::new(width, height, &[format], None)?,
+                Framebuffer::new(width, height, &[format], None)?,
+            ],
+            copy,
+        })
+    }
+
+    /// 在链尾添加效果，添加的效果默认启用
+    pub fn push<E: PostEffect>(&mut self, effect: E) -> &mut Self {
+        self.entries.push(Entry {
+            effect: Box::new(effect),
+            enabled: true,
+        });
+        self
+    }
+
+    /// 在指定名称的效果之前插入效果，不存在该名称时添加到链尾
+    pub fn insert_before<E: PostEffect>(&mut self, name: &str, effect: E) -> &mut Self {
+        let index = self.position(name).unwrap_or(self.entries.len());
+        self.entries.insert(
+            index,
+            Entry {
+                effect: Box::new(effect),
+                enabled: true,
+            },
+        );
+        self
+    }
+
+    /// 移除指定名称的效果
+    ///
+    /// # 返回值
+    /// 存在该效果时返回`true`
+    pub fn remove(&mut self, name: &str) -> bool {
+        match self.position(name) {
+            Some(index) => {
+                self.entries.remove(index);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// 启用或禁用指定名称的效果
+    ///
+    /// # 返回值
+    /// 存在该效果时返回`true`
+    pub fn set_enabled(&mut self, name: &str, enabled: bool) -> bool {
+        match self.position(name) {
+            Some(index) => {
+                self.entries[index].enabled = enabled;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// 判断指定名称的效果是否已启用，不存在时返回`false`
+    pub fn is_enabled(&self, name: &str) -> bool {
+        self.position(name)
+            .is_some_and(|index| self.entries[index].enabled)
+    }
+
+    /// 获取链中第一个类型为`E`的效果
+    pub fn get<E: PostEffect>(&self) -> Option<&E> {
+        self.entries.iter().find_map(|entry| {
+            let effect: &dyn Any = entry.effect.as_ref();
+            effect.downcast_ref::<E>()
+        })
+    }
+
+    /// 获取链中第一个类型为`E`的效果的可变引用，用于在运行时调整参数
+    pub fn get_mut<E: PostEffect>(&mut self) -> Option<&mut E> {
+        self.entries.iter_mut().find_map(|entry| {
+            let effect: &mut dyn Any = entry.effect.as_mut();
+            effect.downcast_mut::<E>()
+        })
+    }
+
+    /// 获取效果名称列表(按执行顺序)
+    pub fn names(&self) -> Vec<&str> {
+        self.entries.iter().map(|entry| entry.effect.name()).collect()
+    }
+
+    /// 修改输出大小，同时通知所有效果
+    pub fn resize(&mut self, width: u32, height: u32) -> Result<()> {
+        for target in &mut self.targets {
+            target.resize(width, height)?;
+        }
+        for entry in &mut self.entries {
+            entry.effect.resize(width, height)?;
+        }
+        Ok(())
+    }
+
+    /// 执行后处理链
+    ///
+    /// # 参数
+    /// + `ctx` - 场景信息
+    /// + `input` - 场景颜色纹理
+    /// + `output` - 最终输出目标，为`None`时输出到默认帧缓冲
+    ///
+    /// # 注解
+    ///
+    /// 执行期间关闭深度测试与混合，结束后恢复深度测试。没有启用的效果时直接复制输入
+    pub fn run(&mut self, ctx: &PostContext, input: u32, output: Option<&Framebuffer>) {
+        unsafe {
+            gl::Disable(gl::DEPTH_TEST);
+            gl::Disable(gl::BLEND);
+        }
+        let enabled: Vec<usize> = (0..self.entries.len())
+            .filter(|&i| self.entries[i].enabled)
+            .collect();
+        if enabled.is_empty() {
+            ctx.bind_output(output);
+            self.copy.bind();
+            unsafe {
+                gl::ActiveTexture(gl::TEXTURE0);
+                gl::BindTexture(gl::TEXTURE_2D, input);
+            }
+            self.copy.set("uInput", &0);
+            draw_fullscreen_triangle();
+        } else {
+            let mut source = input;
+            for (n, &index) in enabled.iter().enumerate() {
+                let effect = &mut self.entries[index].effect;
+                if n + 1 == enabled.len() {
+                    effect.apply(ctx, source, output);
+                } else {
+                    let target = &self.targets[n % 2];
+                    effect.apply(ctx, source, Some(target));
+                    source = target.colors()[0].id();
+                }
+            }
+        }
+        unsafe { gl::Enable(gl::DEPTH_TEST) };
+    }
+
+    fn position(&self, name: &str) -> Option<usize> {
+        self.entries
+            .iter()
+            .position(|entry| entry.effect.name() == name)
+    }
+}
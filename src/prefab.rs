@@ -0,0 +1,424 @@
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::error::{Error, Result};
+use crate::scene_file::{instantiate_contents, instantiate_entity};
+use crate::{error, info, ComponentRegistry, Entity, EntityDesc, Scene, SceneFile, Transform};
+
+/// 预制体嵌套的最大深度，超过时视为循环引用
+const MAX_DEPTH: usize = 16;
+
+/// 预制体实例的参数覆盖
+///
+/// 键为实体在预制体中的路径，根实体为空字符串，子实体为以`/`分隔的名称(如`door/handle`)；
+/// 值为组件名称到 JSON 值的映射，以 JSON Merge Patch 的方式合并到预制体的组件上：
+/// 对象逐字段合并，其他值直接替换，`null`删除对应的字段或组件
+pub type PrefabOverrides = BTreeMap<String, BTreeMap<String, Value>>;
+
+/// 预制体实例组件
+///
+/// 由 [`PrefabLibrary`] 添加到实例的根实体上，记录实例引用的预制体与参数覆盖。
+/// 保存场景时带有该组件的实体只记录预制体引用、名称、变换与覆盖参数，
+/// 运行时对实例内部的修改不会被保存
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PrefabInstance {
+    /// 预制体名称
+    pub prefab: String,
+    /// 参数覆盖
+    pub overrides: PrefabOverrides,
+}
+
+struct Prefab {
+    root: EntityDesc,
+    path: Option<PathBuf>,
+}
+
+/// 预制体库
+///
+/// 预制体是可复用的实体模板，以 JSON 保存单个根实体，格式与 [`SceneFile`] 中的实体相同。
+/// 预制体的子实体可以通过`prefab`字段引用其他预制体，根实体引用其他预制体时该预制体是基础预制体的变体。
+/// 预制体文件被修改后调用 [`PrefabLibrary::reload`]，场景中所有实例将按新的模板重建，
+/// 重建时保留实例根实体的句柄、名称、变换与参数覆盖
+///
+/// # 示例
+///
+/// ```no_run
+/// use gle::*;
+/// use serde_json::json;
+///
+/// let mut prefabs = PrefabLibrary::new(ComponentRegistry::new());
+/// prefabs.load_dir("prefabs").unwrap();
+///
+/// let mut scene = prefabs.load_scene("levels/forest.scene.json").unwrap();
+/// let mut overrides = PrefabOverrides::new();
+/// overrides
+///     .entry(String::new())
+///     .or_default()
+///     .insert("assets".to_string(), json!({ "mesh": "models/pine.obj" }));
+/// let pine = prefabs.instantiate(&mut scene, "tree", None, &overrides).unwrap();
+///
+/// // 文件监视器报告 prefabs/tree.json 被修改
+/// let changed = [std::path::PathBuf::from("prefabs/tree.json")];
+/// prefabs.reload(&changed, &mut scene);
+/// assert!(scene.contains(pine));
+/// ```
+pub struct PrefabLibrary {
+    registry: ComponentRegistry,
+    prefabs: HashMap<String, Prefab>,
+}
+
+impl PrefabLibrary {
+    /// 创建空的预制体库
+    ///
+    /// # 参数
+    /// + `registry` - 创建实例时使用的组件注册表
+    pub fn new(registry: ComponentRegistry) -> Self {
+        Self {
+            registry,
+            prefabs: HashMap::new(),
+        }
+    }
+
+    /// 组件注册表
+    pub fn registry(&self) -> &ComponentRegistry {
+        &self.registry
+    }
+
+    /// 组件注册表的可变引用
+    pub fn registry_mut(&mut self) -> &mut ComponentRegistry {
+        &mut self.registry
+    }
+
+    /// 预制体数量
+    pub fn len(&self) -> usize {
+        self.prefabs.len()
+    }
+
+    /// 是否没有预制体
+    pub fn is_empty(&self) -> bool {
+        self.prefabs.is_empty()
+    }
+
+    /// 判断预制体是否存在
+    pub fn contains(&self, name: &str) -> bool {
+        self.prefabs.contains_key(name)
+    }
+
+    /// 获取预制体的模板
+    pub fn get(&self, name: &str) -> Option<&EntityDesc> {
+        self.prefabs.get(name).map(|p| &p.root)
+    }
+
+    /// 所有预制体的名称
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.prefabs.keys().map(String::as_str)
+    }
+
+    /// 添加或替换预制体
+    ///
+    /// # 参数
+    /// + `name` - 预制体名称
+    /// + `root` - 预制体的根实体
+    ///
+    /// # 注解
+    ///
+    /// 替换预制体不会影响已创建的实例，需要时调用 [`PrefabLibrary::refresh`]
+    pub fn insert(&mut self, name: &str, root: EntityDesc) {
+        self.prefabs.insert(name.to_string(), Prefab { root, path: None });
+    }
+
+    /// 从文件加载预制体
+    ///
+    /// # 参数
+    /// + `path` - 预制体文件路径，文件名中第一个`.`之前的部分作为预制体名称，
+    ///   如`prefabs/tree.prefab.json`的名称为`tree`
+    ///
+    /// # 返回值
+    /// 成功时返回预制体名称
+    pub fn load<P: AsRef<Path>>(&mut self, path: P) -> Result<String> {
+        let path = path.as_ref();
+        let name = path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .and_then(|n| n.split('.').next())
+            .filter(|n| !n.is_empty())
+            .ok_or_else(|| Error::Parse(format!("无效的预制体路径 {}", path.display())))?
+            .to_string();
+        let root = load_desc(path)?;
+        let path = Some(path.canonicalize().unwrap_or_else(|_| path.to_path_buf()));
+        self.prefabs.insert(name.clone(), Prefab { root, path });
+        Ok(name)
+    }
+
+    /// 加载目录下所有的`.json`预制体文件，不包含子目录
+    ///
+    /// # 返回值
+    /// 成功时返回加载的预制体数量
+    pub fn load_dir<P: AsRef<Path>>(&mut self, dir: P) -> Result<usize> {
+        let mut count = 0;
+        for entry in std::fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.is_file() && path.extension().is_some_and(|e| e == "json") {
+                self.load(&path)?;
+                count += 1;
+            }
+        }
+        Ok(count)
+    }
+
+    /// 在场景中创建预制体的实例
+    ///
+    /// # 参数
+    /// + `scene` - 场景
+    /// + `name` - 预制体名称
+    /// + `parent` - 实例的父实体，为`None`时创建为场景的根实体
+    /// + `overrides` - 参数覆盖
+    ///
+    /// # 返回值
+    /// 成功时返回实例的根实体，其名称与变换取自预制体的根实体；
+    /// 预制体不存在、循环引用或组件格式错误时返回错误
+    pub fn instantiate(
+        &self,
+        scene: &mut Scene,
+        name: &str,
+        parent: Option<Entity>,
+        overrides: &PrefabOverrides,
+    ) -> Result<Entity> {
+        self.spawn(scene, name, parent, overrides, None, 0)
+    }
+
+    /// 在场景中创建场景文件中的实体，实体可以引用库中的预制体
+    ///
+    /// 参数与返回值同 [`SceneFile::instantiate`]
+    pub fn instantiate_file(
+        &self,
+        file: &SceneFile,
+        scene: &mut Scene,
+        parent: Option<Entity>,
+    ) -> Result<Vec<Entity>> {
+        file.entities
+            .iter()
+            .map(|desc| instantiate_entity(desc, scene, parent, &self.registry, Some(self), 0))
+            .collect()
+    }
+
+    /// 从引用了预制体的场景文件加载场景
+    ///
+    /// # 返回值
+    /// 成功时返回世界变换已更新的场景
+    pub fn load_scene<P: AsRef<Path>>(&self, path: P) -> Result<Scene> {
+        let mut scene = Scene::new();
+        self.instantiate_file(&SceneFile::load(path)?, &mut scene, None)?;
+        scene.update_world_transforms();
+        Ok(scene)
+    }
+
+    /// 重新加载被修改的预制体文件，并重建场景中这些预制体的实例
+    ///
+    /// # 参数
+    /// + `changed` - 被修改的文件路径，不属于库中预制体的路径被忽略
+    /// + `scene` - 场景
+    ///
+    /// # 返回值
+    /// 返回重建的实例数量，加载失败的预制体输出错误日志并保留原模板
+    pub fn reload(&mut self, changed: &[PathBuf], scene: &mut Scene) -> usize {
+        let changed: Vec<PathBuf> = changed
+            .iter()
+            .map(|p| p.canonicalize().unwrap_or_else(|_| p.clone()))
+            .collect();
+        let mut reloaded = Vec::new();
+        for (name, prefab) in &mut self.prefabs {
+            let Some(path) = prefab.path.as_ref().filter(|p| changed.contains(p)) else {
+                continue;
+            };
+            match load_desc(path) {
+                Ok(root) => {
+                    prefab.root = root;
+                    reloaded.push(name.clone());
+                }
+                Err(e) => {
+                    error!(Self, "重新加载预制体 {} 失败: {}", name, e);
+                }
+            }
+        }
+        let count = reloaded.iter().map(|name| self.refresh(scene, name)).sum();
+        if !reloaded.is_empty() {
+            info!(Self, "重新加载预制体 {:?}，重建了 {} 个实例", reloaded, count);
+        }
+        count
+    }
+
+    /// 按预制体当前的模板重建场景中它的所有实例
+    ///
+    /// 实例根实体上注册过的组件与所有子实体被移除后重新创建，根实体的句柄、名称与变换保持不变。
+    /// 直接或通过基础预制体间接引用该预制体的嵌套实例同样会被重建
+    ///
+    /// # 返回值
+    /// 返回重建的实例数量
+    pub fn refresh(&self, scene: &mut Scene, name: &str) -> usize {
+        let instances: Vec<(Entity, PrefabOverrides)> = scene
+            .query::<PrefabInstance>()
+            .filter(|(_, instance)| self.depends_on(&instance.prefab, name, 0))
+            .map(|(entity, instance)| (entity, instance.overrides.clone()))
+            .collect();
+        let mut count = 0;
+        for (entity, overrides) in instances {
+            // 外层实例重建时内层实例可能已被移除
+            if !scene.contains(entity) {
+                continue;
+            }
+            let prefab = scene.component::<PrefabInstance>(entity).unwrap().prefab.clone();
+            match self.rebuild(scene, entity, &prefab, &overrides) {
+                Ok(()) => count += 1,
+                Err(e) => {
+                    error!(Self, "重建预制体 {} 的实例失败: {}", prefab, e);
+                }
+            }
+        }
+        count
+    }
+
+    pub(crate) fn spawn(
+        &self,
+        scene: &mut Scene,
+        name: &str,
+        parent: Option<Entity>,
+        overrides: &PrefabOverrides,
+        placement: Option<(&str, Transform)>,
+        depth: usize,
+    ) -> Result<Entity> {
+        let mut desc = self.resolve(name, overrides, depth)?;
+        if let Some((instance_name, transform)) = placement {
+            if !instance_name.is_empty() {
+                desc.name = instance_name.to_string();
+            }
+            desc.transform = transform;
+        }
+        let entity = instantiate_entity(&desc, scene, parent, &self.registry, Some(self), depth)?;
+        let instance = PrefabInstance {
+            prefab: name.to_string(),
+            overrides: overrides.clone(),
+        };
+        scene.insert(entity, instance);
+        Ok(entity)
+    }
+
+    fn rebuild(
+        &self,
+        scene: &mut Scene,
+        entity: Entity,
+        name: &str,
+        overrides: &PrefabOverrides,
+    ) -> Result<()> {
+        let desc = self.resolve(name, overrides, 0)?;
+        let children = scene.get(entity).unwrap().children().to_vec();
+        for child in children {
+            scene.despawn(child);
+        }
+        self.registry.remove_all(scene, entity);
+        instantiate_contents(&desc, scene, entity, &self.registry, Some(self), 0)
+    }
+
+    /// 展开预制体的模板：应用参数覆盖，并将变体展开为基础预制体
+    fn resolve(&self, name: &str, overrides: &PrefabOverrides, depth: usize) -> Result<EntityDesc> {
+        if depth > MAX_DEPTH {
+            let message = format!("预制体 {} 的嵌套层数过多，可能存在循环引用", name);
+            return Err(Error::Parse(message));
+        }
+        let mut desc = self
+            .get(name)
+            .ok_or_else(|| Error::Parse(format!("预制体 {} 不存在", name)))?
+            .clone();
+        apply_overrides(&mut desc, overrides, "");
+        match desc.prefab.take() {
+            Some(base) => {
+                let mut resolved = self.resolve(&base, &desc.overrides, depth + 1)?;
+                resolved.name = desc.name;
+                resolved.transform = desc.transform;
+                Ok(resolved)
+            }
+            None => Ok(desc),
+        }
+    }
+
+    /// 判断预制体`name`是否直接或间接引用了预制体`target`
+    fn depends_on(&self, name: &str, target: &str, depth: usize) -> bool {
+        if name == target {
+            return true;
+        }
+        if depth > MAX_DEPTH {
+            return false;
+        }
+        self.get(name)
+            .is_some_and(|desc| references(desc, &mut |n| self.depends_on(n, target, depth + 1)))
+    }
+}
+
+fn load_desc(path: &Path) -> Result<EntityDesc> {
+    let src = std::fs::read_to_string(path)?;
+    serde_json::from_str(&src).map_err(|e| Error::Parse(format!("{}: {}", path.display(), e)))
+}
+
+/// 判断实体描述中是否有预制体引用满足条件
+fn references(desc: &EntityDesc, f: &mut impl FnMut(&str) -> bool) -> bool {
+    desc.prefab.as_deref().is_some_and(&mut *f) || desc.children.iter().any(|c| references(c, f))
+}
+
+/// 将参数覆盖应用到路径为`path`的实体描述及其子实体上
+fn apply_overrides(desc: &mut EntityDesc, overrides: &PrefabOverrides, path: &str) {
+    if desc.prefab.is_some() {
+        // 嵌套的预制体实例：将其路径下的覆盖转交给实例自己
+        for (key, patch) in overrides {
+            let rebased = if key == path {
+                Some(String::new())
+            } else if path.is_empty() {
+                Some(key.clone())
+            } else {
+                key.strip_prefix(path).and_then(|k| k.strip_prefix('/')).map(str::to_string)
+            };
+            if let Some(rebased) = rebased {
+                let target = desc.overrides.entry(rebased).or_default();
+                for (component, value) in patch {
+                    merge(target.entry(component.clone()).or_insert(Value::Null), value);
+                }
+            }
+        }
+        return;
+    }
+    if let Some(patch) = overrides.get(path) {
+        for (component, value) in patch {
+            if value.is_null() {
+                desc.components.remove(component);
+            } else {
+                merge(desc.components.entry(component.clone()).or_insert(Value::Null), value);
+            }
+        }
+    }
+    for child in &mut desc.children {
+        let child_path = if path.is_empty() {
+            child.name.clone()
+        } else {
+            format!("{}/{}", path, child.name)
+        };
+        apply_overrides(child, overrides, &child_path);
+    }
+}
+
+/// 按 JSON Merge Patch 合并
+fn merge(target: &mut Value, patch: &Value) {
+    match (target, patch) {
+        (Value::Object(target), Value::Object(patch)) => {
+            for (key, value) in patch {
+                if value.is_null() {
+                    target.remove(key);
+                } else {
+                    merge(target.entry(key.clone()).or_insert(Value::Null), value);
+                }
+            }
+        }
+        (target, patch) => *target = patch.clone(),
+    }
+}
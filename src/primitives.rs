@@ -0,0 +1,266 @@
+use std::collections::HashMap;
+use std::f32::consts::{FRAC_PI_2, PI, TAU};
+
+use crate::math::*;
+use crate::Mesh;
+
+/// 程序化几何体生成
+///
+/// 所有几何体以原点为中心，三角形以逆时针方向为正面，法线朝外
+impl Mesh {
+    /// 生成立方体
+    ///
+    /// # 参数
+    /// + `size` - 各轴方向上的边长
+    ///
+    /// # 返回值
+    /// 返回每个面拥有独立顶点的立方体，每个面的纹理坐标覆盖`[0, 1]`
+    pub fn cube(size: Vec3) -> Mesh {
+        let h = size * 0.5;
+        let faces = [
+            (Vec3::X, Vec3::NEG_Z, Vec3::Y),
+            (Vec3::NEG_X, Vec3::Z, Vec3::Y),
+            (Vec3::Y, Vec3::X, Vec3::NEG_Z),
+            (Vec3::NEG_Y, Vec3::X, Vec3::Z),
+            (Vec3::Z, Vec3::X, Vec3::Y),
+            (Vec3::NEG_Z, Vec3::NEG_X, Vec3::Y),
+        ];
+        let mut mesh = Mesh::new();
+        for (n, u, v) in faces {
+            let base = mesh.positions.len() as u32;
+            for (su, sv) in [(-1.0, -1.0), (1.0, -1.0), (1.0, 1.0), (-1.0, 1.0)] {
+                mesh.positions.push((n + u * su + v * sv) * h);
+                mesh.normals.push(n);
+                mesh.uvs.push(Vec2::new((su + 1.0) * 0.5, (sv + 1.0) * 0.5));
+            }
+            mesh.indices
+                .extend_from_slice(&[base, base + 1, base + 2, base, base + 2, base + 3]);
+        }
+        mesh
+    }
+
+    /// 生成经纬球
+    ///
+    /// # 参数
+    /// + `radius` - 半径
+    /// + `segments` - 经线方向的分段数，至少为3
+    /// + `rings` - 纬线方向的分段数，至少为2
+    pub fn uv_sphere(radius: f32, segments: u32, rings: u32) -> Mesh {
+        let rings = rings.max(2);
+        let rows: Vec<(f32, f32)> = (0..=rings)
+            .map(|i| (PI * i as f32 / rings as f32, 0.0))
+            .collect();
+        lat_long(&rows, radius, segments.max(3))
+    }
+
+    /// 生成正二十面体细分球
+    ///
+    /// # 参数
+    /// + `radius` - 半径
+    /// + `subdivisions` - 细分次数，每次细分使三角形数量变为原来的4倍
+    ///
+    /// # 注解
+    ///
+    /// 纹理坐标由球面坐标计算，在经线接缝处会出现插值瑕疵
+    pub fn icosphere(radius: f32, subdivisions: u32) -> Mesh {
+        let t = (1.0 + 5f32.sqrt()) / 2.0;
+        let mut positions: Vec<Vec3> = [
+            (-1.0, t, 0.0),
+            (1.0, t, 0.0),
+            (-1.0, -t, 0.0),
+            (1.0, -t, 0.0),
+            (0.0, -1.0, t),
+            (0.0, 1.0, t),
+            (0.0, -1.0, -t),
+            (0.0, 1.0, -t),
+            (t, 0.0, -1.0),
+            (t, 0.0, 1.0),
+            (-t, 0.0, -1.0),
+            (-t, 0.0, 1.0),
+        ]
+        .iter()
+        .map(|&(x, y, z)| Vec3::new(x, y, z).normalize())
+        .collect();
+        #[rustfmt::skip]
+        let mut faces: Vec<[u32; 3]> = vec![
+            [0, 11, 5], [0, 5, 1], [0, 1, 7], [0, 7, 10], [0, 10, 11],
+            [1, 5, 9], [5, 11, 4], [11, 10, 2], [10, 7, 6], [7, 1, 8],
+            [3, 9, 4], [3, 4, 2], [3, 2, 6], [3, 6, 8], [3, 8, 9],
+            [4, 9, 5], [2, 4, 11], [6, 2, 10], [8, 6, 7], [9, 8, 1],
+        ];
+        for _ in 0..subdivisions {
+            let mut cache: HashMap<(u32, u32), u32> = HashMap::new();
+            let mut midpoint = |a: u32, b: u32, positions: &mut Vec<Vec3>| {
+                *cache.entry((a.min(b), a.max(b))).or_insert_with(|| {
+                    let p = (positions[a as usize] + positions[b as usize]).normalize();
+                    positions.push(p);
+                    positions.len() as u32 - 1
+                })
+            };
+            let mut next = Vec::with_capacity(faces.len() * 4);
+            for [a, b, c] in faces {
+                let ab = midpoint(a, b, &mut positions);
+                let bc = midpoint(b, c, &mut positions);
+                let ca = midpoint(c, a, &mut positions);
+                next.extend_from_slice(&[[a, ab, ca], [b, bc, ab], [c, ca, bc], [ab, bc, ca]]);
+            }
+            faces = next;
+        }
+        let mut mesh = Mesh::new();
+        for &p in &positions {
+            mesh.positions.push(p * radius);
+            mesh.normals.push(p);
+            mesh.uvs.push(Vec2::new(
+                0.5 + p.z.atan2(p.x) / TAU,
+                0.5 + p.y.clamp(-1.0, 1.0).asin() / PI,
+            ));
+        }
+        for [a, b, c] in faces {
+            let (pa, pb, pc) = (positions[a as usize], positions[b as usize], positions[c as usize]);
+            // 保证三角形朝外
+            if (pb - pa).cross(pc - pa).dot(pa + pb + pc) >= 0.0 {
+                mesh.indices.extend_from_slice(&[a, b, c]);
+            } else {
+                mesh.indices.extend_from_slice(&[a, c, b]);
+            }
+        }
+        mesh
+    }
+
+    /// 生成位于 XZ 平面、朝向`+Y`的平面网格
+    ///
+    /// # 参数
+    /// + `width` - X 方向的尺寸
+    /// + `depth` - Z 方向的尺寸
+    /// + `subdivisions_x` - X 方向的分段数，至少为1
+    /// + `subdivisions_z` - Z 方向的分段数，至少为1
+    pub fn plane(width: f32, depth: f32, subdivisions_x: u32, subdivisions_z: u32) -> Mesh {
+        let (nx, nz) = (subdivisions_x.max(1), subdivisions_z.max(1));
+        let mut mesh = Mesh::new();
+        for j in 0..=nz {
+            for i in 0..=nx {
+                let (u, v) = (i as f32 / nx as f32, j as f32 / nz as f32);
+                mesh.positions
+                    .push(Vec3::new((u - 0.5) * width, 0.0, (v - 0.5) * depth));
+                mesh.normals.push(Vec3::Y);
+                mesh.uvs.push(Vec2::new(u, 1.0 - v));
+            }
+        }
+        for j in 0..nz {
+            for i in 0..nx {
+                let a = j * (nx + 1) + i;
+                let c = a + nx + 1;
+                mesh.indices
+                    .extend_from_slice(&[a, c, a + 1, a + 1, c, c + 1]);
+            }
+        }
+        mesh
+    }
+
+    /// 生成沿 Y 轴的圆柱体
+    ///
+    /// # 参数
+    /// + `radius` - 半径
+    /// + `height` - 高度
+    /// + `segments` - 圆周方向的分段数，至少为3
+    pub fn cylinder(radius: f32, height: f32, segments: u32) -> Mesh {
+        let segments = segments.max(3);
+        let half = height * 0.5;
+        let mut mesh = Mesh::new();
+        // 侧面
+        for (y, v) in [(half, 1.0), (-half, 0.0)] {
+            for s in 0..=segments {
+                let u = s as f32 / segments as f32;
+                let (sin, cos) = (u * TAU).sin_cos();
+                mesh.positions.push(Vec3::new(cos * radius, y, sin * radius));
+                mesh.normals.push(Vec3::new(cos, 0.0, sin));
+                mesh.uvs.push(Vec2::new(u, v));
+            }
+        }
+        for s in 0..segments {
+            let a = s;
+            let b = s + segments + 1;
+            mesh.indices
+                .extend_from_slice(&[a, a + 1, b, a + 1, b + 1, b]);
+        }
+        // 顶面与底面
+        for (y, n) in [(half, Vec3::Y), (-half, Vec3::NEG_Y)] {
+            let center = mesh.positions.len() as u32;
+            mesh.positions.push(Vec3::new(0.0, y, 0.0));
+            mesh.normals.push(n);
+            mesh.uvs.push(Vec2::splat(0.5));
+            for s in 0..=segments {
+                let (sin, cos) = (s as f32 / segments as f32 * TAU).sin_cos();
+                mesh.positions.push(Vec3::new(cos * radius, y, sin * radius));
+                mesh.normals.push(n);
+                mesh.uvs.push(Vec2::new(0.5 + cos * 0.5, 0.5 + sin * 0.5));
+            }
+            for s in 0..segments {
+                let (p0, p1) = (center + 1 + s, center + 2 + s);
+                if n.y > 0.0 {
+                    mesh.indices.extend_from_slice(&[center, p1, p0]);
+                } else {
+                    mesh.indices.extend_from_slice(&[center, p0, p1]);
+                }
+            }
+        }
+        mesh
+    }
+
+    /// 生成沿 Y 轴的胶囊体
+    ///
+    /// # 参数
+    /// + `radius` - 半球半径
+    /// + `height` - 中间圆柱部分的高度，总高度为`height + 2 * radius`
+    /// + `segments` - 圆周方向的分段数，至少为3
+    /// + `rings` - 每个半球在纬线方向的分段数，至少为1
+    pub fn capsule(radius: f32, height: f32, segments: u32, rings: u32) -> Mesh {
+        let rings = rings.max(1);
+        let half = height * 0.5;
+        let mut rows = Vec::with_capacity(2 * (rings as usize + 1));
+        for i in 0..=rings {
+            rows.push((FRAC_PI_2 * i as f32 / rings as f32, half));
+        }
+        for i in 0..=rings {
+            rows.push((FRAC_PI_2 + FRAC_PI_2 * i as f32 / rings as f32, -half));
+        }
+        lat_long(&rows, radius, segments.max(3))
+    }
+}
+
+/// 按行生成经纬网格，每行为`(极角, Y 方向偏移)`，极角为零时位于`+Y`极点
+fn lat_long(rows: &[(f32, f32)], radius: f32, segments: u32) -> Mesh {
+    let total_height = {
+        let (first, last) = (rows[0], rows[rows.len() - 1]);
+        (first.0.cos() * radius + first.1) - (last.0.cos() * radius + last.1)
+    };
+    let bottom = rows[rows.len() - 1].0.cos() * radius + rows[rows.len() - 1].1;
+    let mut mesh = Mesh::new();
+    for &(theta, offset) in rows {
+        let (sin_t, cos_t) = theta.sin_cos();
+        for s in 0..=segments {
+            let u = s as f32 / segments as f32;
+            let (sin_p, cos_p) = (u * TAU).sin_cos();
+            let n = Vec3::new(sin_t * cos_p, cos_t, sin_t * sin_p);
+            let p = n * radius + Vec3::new(0.0, offset, 0.0);
+            mesh.positions.push(p);
+            mesh.normals.push(n);
+            mesh.uvs.push(Vec2::new(u, (p.y - bottom) / total_height));
+        }
+    }
+    let stride = segments + 1;
+    let last_row = rows.len() as u32 - 2;
+    for r in 0..rows.len() as u32 - 1 {
+        for s in 0..segments {
+            let a = r * stride + s;
+            let b = a + stride;
+            if r != 0 {
+                mesh.indices.extend_from_slice(&[a, a + 1, b]);
+            }
+            if r != last_row {
+                mesh.indices.extend_from_slice(&[a + 1, b + 1, b]);
+            }
+        }
+    }
+    mesh
+}
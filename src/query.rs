@@ -0,0 +1,236 @@
+use crate::{GlObjectKind, GlObjects};
+
+/// 遮挡查询类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum QueryKind {
+    /// 统计通过深度与模板测试的样本数量
+    SamplesPassed,
+    /// 只判断是否有样本通过，驱动可以提前结束计数，通常比 [`QueryKind::SamplesPassed`] 更快
+    #[default]
+    AnySamplesPassed,
+}
+
+impl QueryKind {
+    fn target(self) -> u32 {
+        match self {
+            QueryKind::SamplesPassed => gl::SAMPLES_PASSED,
+            QueryKind::AnySamplesPassed => gl::ANY_SAMPLES_PASSED,
+        }
+    }
+}
+
+/// 条件渲染的等待方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum ConditionalMode {
+    /// GPU 等待查询结果后再决定是否绘制
+    #[default]
+    Wait,
+    /// 查询结果尚未就绪时直接绘制，不产生等待
+    NoWait,
+    /// 同 [`ConditionalMode::Wait`]，但允许驱动按屏幕区域分别判断
+    ByRegionWait,
+    /// 同 [`ConditionalMode::NoWait`]，但允许驱动按屏幕区域分别判断
+    ByRegionNoWait,
+}
+
+impl ConditionalMode {
+    fn to_gl(self) -> u32 {
+        match self {
+            ConditionalMode::Wait => gl::QUERY_WAIT,
+            ConditionalMode::NoWait => gl::QUERY_NO_WAIT,
+            ConditionalMode::ByRegionWait => gl::QUERY_BY_REGION_WAIT,
+            ConditionalMode::ByRegionNoWait => gl::QUERY_BY_REGION_NO_WAIT,
+        }
+    }
+}
+
+/// 遮挡查询对象
+///
+/// 统计 [`OcclusionQuery::begin`] 与 [`OcclusionQuery::end`] 之间的绘制通过深度测试的样本，
+/// 结果以异步方式通过 [`OcclusionQuery::poll`] 读取而不阻塞渲染线程，
+/// 可用于自定义可见性判断、镜头光晕的遮挡测试，或作为条件渲染的条件
+///
+/// # 示例
+///
+/// ```no_run
+/// use gle::*;
+///
+/// fn render_loop(query: &mut OcclusionQuery, flare_visibility: &mut f32) {
+///     // 上一帧的结果
+///     if let Some(samples) = query.poll() {
+///         *flare_visibility = (samples as f32 / 64.0).min(1.0);
+///     }
+///     if !query.is_pending() {
+///         query.scope(|| {
+///             // 在光源位置绘制一个 8x8 像素的小方块，不写入颜色与深度
+///         });
+///     }
+/// }
+/// ```
+///
+/// # 注解
+///
+/// 同一类型的查询不能嵌套。该类型只能在渲染线程中创建、使用与释放
+pub struct OcclusionQuery {
+    id: u32,
+    kind: QueryKind,
+    active: bool,
+    pending: bool,
+    result: Option<u64>,
+}
+
+impl OcclusionQuery {
+    /// 创建遮挡查询
+    ///
+    /// # 参数
+    /// + `kind` - 查询类型
+    pub fn new(kind: QueryKind) -> Self {
+        let mut id = 0;
+        unsafe { gl::GenQueries(1, &mut id) };
+        GlObjects::track(GlObjectKind::Query, id);
+        Self {
+            id,
+            kind,
+            active: false,
+            pending: false,
+            result: None,
+        }
+    }
+
+    /// 获取查询对象ID
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    /// 获取查询类型
+    pub fn kind(&self) -> QueryKind {
+        self.kind
+    }
+
+    /// 开始查询
+    ///
+    /// # 注解
+    ///
+    /// 上一次查询的结果尚未返回时重新开始查询会丢弃该结果，可先通过 [`OcclusionQuery::is_pending`] 判断
+    pub fn begin(&mut self) {
+        if self.active {
+            return;
+        }
+        unsafe { gl::BeginQuery(self.kind.target(), self.id) };
+        self.active = true;
+    }
+
+    /// 结束查询
+    pub fn end(&mut self) {
+        if !self.active {
+            return;
+        }
+        unsafe { gl::EndQuery(self.kind.target()) };
+        self.active = false;
+        self.pending = true;
+    }
+
+    /// 在查询中执行绘制
+    ///
+    /// # 参数
+    /// + `f` - 绘制函数
+    ///
+    /// # 返回值
+    /// 返回`f`的返回值
+    pub fn scope<R>(&mut self, f: impl FnOnce() -> R) -> R {
+        self.begin();
+        let result = f();
+        self.end();
+        result
+    }
+
+    /// 判断是否有已结束但结果尚未读取的查询
+    pub fn is_pending(&self) -> bool {
+        self.pending
+    }
+
+    /// 以查询结果为条件执行绘制
+    ///
+    /// `f`中的绘制只在最近一次查询有样本通过时由GPU执行，不需要将结果读回CPU，
+    /// 适合在同一帧中先以包围盒发起查询，再以条件渲染绘制开销较大的物体
+    ///
+    /// # 参数
+    /// + `mode` - 等待方式
+    /// + `f` - 绘制函数，其中的清除、计算与非绘制命令不受条件影响
+    ///
+    /// # 返回值
+    /// 返回`f`的返回值
+    ///
+    /// # 注解
+    ///
+    /// 从未发起过查询或查询仍在进行中时不加条件地执行`f`
+    pub fn conditional<R>(&self, mode: ConditionalMode, f: impl FnOnce() -> R) -> R {
+        let issued = self.pending || self.result.is_some();
+        if self.active || !issued {
+            return f();
+        }
+        unsafe { gl::BeginConditionalRender(self.id, mode.to_gl()) };
+        let result = f();
+        unsafe { gl::EndConditionalRender() };
+        result
+    }
+
+    /// 读取已返回的查询结果，不阻塞
+    ///
+    /// # 返回值
+    /// 返回最近一次已返回的结果：[`QueryKind::SamplesPassed`] 为样本数量，
+    /// [`QueryKind::AnySamplesPassed`] 为0或1。尚无任何结果时返回`None`
+    pub fn poll(&mut self) -> Option<u64> {
+        if self.pending {
+            let mut available = 0;
+            unsafe { gl::GetQueryObjectuiv(self.id, gl::QUERY_RESULT_AVAILABLE, &mut available) };
+            if available != 0 {
+                self.fetch();
+            }
+        }
+        self.result
+    }
+
+    /// 等待并读取查询结果，会阻塞渲染线程直到GPU完成查询
+    ///
+    /// # 返回值
+    /// 从未开始过查询时返回`None`
+    pub fn wait(&mut self) -> Option<u64> {
+        if self.pending {
+            self.fetch();
+        }
+        self.result
+    }
+
+    /// 获取最近一次已读取的结果
+    pub fn result(&self) -> Option<u64> {
+        self.result
+    }
+
+    /// 根据最近一次已读取的结果判断是否可见
+    ///
+    /// # 返回值
+    /// 尚无结果时返回`None`
+    pub fn is_visible(&self) -> Option<bool> {
+        self.result.map(|samples| samples != 0)
+    }
+
+    fn fetch(&mut self) {
+        let mut samples = 0u64;
+        unsafe { gl::GetQueryObjectui64v(self.id, gl::QUERY_RESULT, &mut samples) };
+        self.pending = false;
+        self.result = Some(samples);
+    }
+}
+
+impl Drop for OcclusionQuery {
+    fn drop(&mut self) {
+        unsafe {
+            if self.active {
+                gl::EndQuery(self.kind.target());
+            }
+            gl::DeleteQueries(1, &self.id);
+        }
+        GlObjects::untrack(GlObjectKind::Query, self.id);
+    }
+}
@@ -0,0 +1,459 @@
+use std::collections::HashMap;
+
+use crate::error::Result;
+use crate::math::*;
+use crate::pbr::{
+    create_cubemap, cubemap_bytes, prefilter_program, render_cube_faces, CUBE_FACE_VIEWS,
+};
+use crate::{Camera, Entity, GlObjectKind, GlObjects, GpuMesh, Mesh, Program, RenderStats, Scene};
+
+/// 着色器中同时混合的反射探针数量上限
+pub const MAX_REFLECTION_PROBES: usize = 4;
+
+/// 反射探针立方体贴图所绑定的首个纹理单元，依次占用 [`MAX_REFLECTION_PROBES`] 个纹理单元
+pub const REFLECTION_PROBE_TEXTURE_UNIT: u32 = 8;
+
+/// 反射探针的更新方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ProbeMode {
+    /// 烘焙一次，之后只在调用 [`ReflectionProbes::invalidate`] 时重新捕获
+    #[default]
+    Baked,
+    /// 运行时持续更新，每帧只捕获 [`ReflectionProbes::faces_per_frame`] 个面以分摊开销
+    Realtime,
+}
+
+/// 反射探针组件
+///
+/// 在节点的世界位置捕获周围环境的立方体贴图，为影响范围内的表面提供局部镜面反射。
+/// 影响范围是以节点位置为中心、与世界坐标轴对齐的盒子，多个探针重叠时较小的探针优先，
+/// 在盒子边缘的 [`ReflectionProbe::blend_distance`] 内与其它探针或全局环境图平滑过渡。
+/// 可以作为组件插入 [`Scene`]，由 [`ReflectionProbes`] 捕获并绑定
+///
+/// # 示例
+///
+/// ```
+/// use gle::{*, math::*};
+///
+/// let mut scene = Scene::new();
+/// let probe = scene.spawn("room_probe", Transform::from_translation(Vec3::new(0.0, 1.5, 0.0)));
+/// scene.insert(probe, ReflectionProbe {
+///     extents: Vec3::new(5.0, 1.5, 4.0),
+///     box_projection: true,
+///     ..Default::default()
+/// });
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ReflectionProbe {
+    /// 影响范围的半边长
+    pub extents: Vec3,
+    /// 盒子边缘向内的混合距离(默认值为1)
+    pub blend_distance: f32,
+    /// 是否进行盒投影修正，适用于室内等近似长方体的空间(默认值为`false`)
+    pub box_projection: bool,
+    /// 反射强度(默认值为1)
+    pub intensity: f32,
+    /// 更新方式
+    pub mode: ProbeMode,
+    /// 捕获时的近裁剪面距离(默认值为0.1)
+    pub near: f32,
+    /// 捕获时的远裁剪面距离(默认值为100)
+    pub far: f32,
+}
+
+impl Default for ReflectionProbe {
+    fn default() -> Self {
+        Self {
+            extents: Vec3::splat(5.0),
+            blend_distance: 1.0,
+            box_projection: false,
+            intensity: 1.0,
+            mode: ProbeMode::default(),
+            near: 0.1,
+            far: 100.0,
+        }
+    }
+}
+
+/// 单个探针的捕获结果
+struct Capture {
+    environment: u32,
+    prefilter: u32,
+    size: u32,
+    /// 是否已完成过至少一次完整捕获
+    ready: bool,
+    /// 下一个待捕获的面，为 6 时表示捕获完成
+    next_face: u32,
+}
+
+impl Drop for Capture {
+    fn drop(&mut self) {
+        let textures = [self.environment, self.prefilter];
+        RenderStats::_track_texture(-2 * cubemap_bytes(self.size, true));
+        for &texture in &textures {
+            GlObjects::untrack(GlObjectKind::Texture, texture);
+        }
+        unsafe { gl::DeleteTextures(textures.len() as i32, textures.as_ptr()) };
+    }
+}
+
+/// 本帧绑定的探针
+#[derive(Debug, Clone, Copy)]
+struct Active {
+    entity: Entity,
+    center: Vec3,
+    probe: ReflectionProbe,
+}
+
+/// 反射探针系统
+///
+/// 为场景中的每个 [`ReflectionProbe`] 维护环境立方体贴图与镜面预过滤贴图：烘焙探针在首次出现或失效时完整捕获，
+/// 实时探针轮流每帧捕获若干个面，六个面完成后重新预过滤。捕获时通过回调以 90° 视野的摄像机绘制场景。
+/// 每帧选取离摄像机最近的至多 [`MAX_REFLECTION_PROBES`] 个探针绑定到 PBR 着色器，
+/// 着色器按片段位置混合，需由 [`PbrShaderCache::with_reflection_probes`](crate::PbrShaderCache::with_reflection_probes)
+/// 启用的着色器变体
+///
+/// # 示例
+///
+/// ```no_run
+/// use gle::*;
+///
+/// fn render_loop(probes: &mut ReflectionProbes, scene: &Scene, camera: &Camera, ibl: &Ibl) {
+///     probes.update(scene, camera, |capture| {
+///         // 以 capture 摄像机绘制场景(不含半透明物体与界面)
+///     });
+///     // 绘制场景时，绑定 PBR 着色器后
+///     // ibl.bind(&program);
+///     // probes.bind(&program);
+/// }
+/// ```
+///
+/// # 注解
+///
+/// 捕获回调中绘制的物体也会采样探针贴图，为避免读写同一纹理，捕获期间不应调用 [`ReflectionProbes::bind`]。
+/// 该类型只能在渲染线程中创建、使用与释放
+pub struct ReflectionProbes {
+    /// 实时探针每帧捕获的面数(默认值为1)
+    pub faces_per_frame: u32,
+    size: u32,
+    levels: u32,
+    captures: HashMap<Entity, Capture>,
+    active: Vec<Active>,
+    realtime_cursor: usize,
+    prefilter_program: Program,
+    cube: GpuMesh,
+    fbo: u32,
+    depth: u32,
+}
+
+impl ReflectionProbes {
+    /// 创建反射探针系统
+    ///
+    /// # 参数
+    /// + `size` - 捕获立方体贴图的边长，通常为 128 或 256
+    ///
+    /// # 返回值
+    /// 成功时返回反射探针系统，内置着色器编译失败时返回错误
+    pub fn new(size: u32) -> Result<Self> {
+        let size = size.max(16);
+        let (mut fbo, mut depth) = (0, 0);
+        unsafe {
+            gl::GenFramebuffers(1, &mut fbo);
+            gl::GenRenderbuffers(1, &mut depth);
+            gl::BindRenderbuffer(gl::RENDERBUFFER, depth);
+            gl::RenderbufferStorage(
+                gl::RENDERBUFFER,
+                gl::DEPTH_COMPONENT24,
+                size as i32,
+                size as i32,
+            );
+            gl::BindRenderbuffer(gl::RENDERBUFFER, 0);
+        }
+        GlObjects::track(GlObjectKind::Framebuffer, fbo);
+        GlObjects::track(GlObjectKind::Renderbuffer, depth);
+        Ok(Self {
+            faces_per_frame: 1,
+            size,
+            levels: size.ilog2().min(5) + 1,
+            captures: HashMap::new(),
+            active: Vec::new(),
+            realtime_cursor: 0,
+            prefilter_program: prefilter_program()?,
+            cube: Mesh::cube(Vec3::splat(2.0)).upload(),
+            fbo,
+            depth,
+        })
+    }
+
+    /// 获取捕获立方体贴图的边长
+    pub fn size(&self) -> u32 {
+        self.size
+    }
+
+    /// 获取探针的预过滤立方体贴图ID
+    ///
+    /// # 返回值
+    /// 探针尚未完成首次捕获时返回`None`
+    pub fn prefilter(&self, entity: Entity) -> Option<u32> {
+        self.captures
+            .get(&entity)
+            .filter(|capture| capture.ready)
+            .map(|capture| capture.prefilter)
+    }
+
+    /// 使探针失效，下一次 [`ReflectionProbes::update`] 时重新捕获
+    ///
+    /// # 参数
+    /// + `entity` - 探针所在的节点
+    pub fn invalidate(&mut self, entity: Entity) {
+        if let Some(capture) = self.captures.get_mut(&entity) {
+            capture.next_face = 0;
+        }
+    }
+
+    /// 使所有探针失效，用于场景光照发生较大变化后重新烘焙
+    pub fn invalidate_all(&mut self) {
+        for capture in self.captures.values_mut() {
+            capture.next_face = 0;
+        }
+    }
+
+    /// 立即完整捕获场景中的所有探针
+    ///
+    /// # 参数
+    /// + `scene` - 场景，应已调用 [`Scene::update_world_transforms`]
+    /// + `render` - 以给定摄像机绘制场景的回调，绘制到当前绑定的帧缓冲
+    ///
+    /// # 注解
+    ///
+    /// 开销较大，应在加载阶段调用
+    pub fn bake_all<F: FnMut(&Camera)>(&mut self, scene: &Scene, mut render: F) {
+        for (entity, center, probe) in probes(scene) {
+            self.capture_faces(entity, center, &probe, 6, &mut render);
+        }
+    }
+
+    /// 更新探针并选取本帧绑定的探针
+    ///
+    /// # 参数
+    /// + `scene` - 场景，应已调用 [`Scene::update_world_transforms`]
+    /// + `camera` - 本帧的主摄像机，用于选取探针
+    /// + `render` - 以给定摄像机绘制场景的回调，绘制到当前绑定的帧缓冲
+    ///
+    /// # 注解
+    ///
+    /// 新出现或失效的烘焙探针在本次调用中完整捕获；实时探针轮流捕获，共 [`ReflectionProbes::faces_per_frame`] 个面。
+    /// 已被移除的探针的贴图在此时释放
+    pub fn update<F: FnMut(&Camera)>(&mut self, scene: &Scene, camera: &Camera, mut render: F) {
+        let probes = probes(scene);
+        self.captures.retain(|entity, _| probes.iter().any(|(e, _, _)| e == entity));
+
+        let mut realtime = Vec::new();
+        for (entity, center, probe) in &probes {
+            let pending = self.captures.get(entity).is_none_or(|c| c.next_face < 6);
+            match probe.mode {
+                ProbeMode::Baked if pending => {
+                    self.capture_faces(*entity, *center, probe, 6, &mut render);
+                }
+                ProbeMode::Realtime => realtime.push((*entity, *center, *probe)),
+                ProbeMode::Baked => {}
+            }
+        }
+        let mut budget = self.faces_per_frame;
+        for _ in 0..realtime.len() {
+            if budget == 0 {
+                break;
+            }
+            let (entity, center, probe) = realtime[self.realtime_cursor % realtime.len()];
+            let capture = self.captures.get_mut(&entity);
+            if let Some(capture) = capture.filter(|c| c.next_face >= 6) {
+                capture.next_face = 0;
+            }
+            budget -= self.capture_faces(entity, center, &probe, budget, &mut render);
+            if self.captures.get(&entity).is_some_and(|c| c.next_face >= 6) {
+                self.realtime_cursor = self.realtime_cursor.wrapping_add(1);
+            }
+        }
+
+        self.select(&probes, camera.position);
+    }
+
+    /// 将本帧选取的探针绑定到纹理单元并设置 PBR 着色器的相关 uniform
+    ///
+    /// # 参数
+    /// + `program` - 已绑定的 PBR 着色器程序
+    ///
+    /// # 注解
+    ///
+    /// 贴图绑定到从 [`REFLECTION_PROBE_TEXTURE_UNIT`] 开始的纹理单元
+    pub fn bind(&self, program: &Program) {
+        for (i, active) in self.active.iter().enumerate() {
+            let Some(capture) = self.captures.get(&active.entity) else {
+                continue;
+            };
+            let probe = &active.probe;
+            unsafe {
+                gl::ActiveTexture(gl::TEXTURE0 + REFLECTION_PROBE_TEXTURE_UNIT + i as u32);
+                gl::BindTexture(gl::TEXTURE_CUBE_MAP, capture.prefilter);
+            }
+            program.set(&format!("uProbeCenter[{}]", i), &active.center);
+            program.set(&format!("uProbeBoxMin[{}]", i), &(active.center - probe.extents));
+            program.set(&format!("uProbeBoxMax[{}]", i), &(active.center + probe.extents));
+            program.set(&format!("uProbeBlend[{}]", i), &probe.blend_distance);
+            program.set(&format!("uProbeIntensity[{}]", i), &probe.intensity);
+            program.set(&format!("uProbeBoxProjection[{}]", i), &probe.box_projection);
+        }
+        unsafe { gl::ActiveTexture(gl::TEXTURE0) };
+        program.set("uProbeCount", &(self.active.len() as i32));
+        program.set("uProbeLod", &((self.levels - 1) as f32));
+    }
+
+    /// 选取离摄像机最近的探针，按体积由小到大排列作为混合优先级
+    fn select(&mut self, probes: &[(Entity, Vec3, ReflectionProbe)], eye: Vec3) {
+        let mut candidates: Vec<(f32, Active)> = probes
+            .iter()
+            .filter(|(entity, _, _)| self.captures.get(entity).is_some_and(|c| c.ready))
+            .map(|&(entity, center, probe)| {
+                let outside = ((eye - center).abs() - probe.extents).max(Vec3::ZERO);
+                let active = Active {
+                    entity,
+                    center,
+                    probe,
+                };
+                (outside.length(), active)
+            })
+            .collect();
+        candidates.sort_by(|a, b| a.0.total_cmp(&b.0));
+        candidates.truncate(MAX_REFLECTION_PROBES);
+        self.active = candidates.into_iter().map(|(_, active)| active).collect();
+        self.active.sort_by(|a, b| {
+            let volume = |p: &ReflectionProbe| p.extents.x * p.extents.y * p.extents.z;
+            volume(&a.probe).total_cmp(&volume(&b.probe))
+        });
+    }
+
+    /// 捕获探针的若干个面，六个面完成后重新预过滤
+    ///
+    /// # 返回值
+    /// 返回实际捕获的面数
+    fn capture_faces<F: FnMut(&Camera)>(
+        &mut self,
+        entity: Entity,
+        center: Vec3,
+        probe: &ReflectionProbe,
+        max_faces: u32,
+        render: &mut F,
+    ) -> u32 {
+        let size = self.size;
+        let capture = self.captures.entry(entity).or_insert_with(|| Capture {
+            environment: create_cubemap(size, true),
+            prefilter: create_cubemap(size, true),
+            size,
+            ready: false,
+            next_face: 0,
+        });
+        if capture.next_face >= 6 {
+            return 0;
+        }
+
+        let mut saved_fbo = 0;
+        let mut saved_viewport = [0i32; 4];
+        unsafe {
+            gl::GetIntegerv(gl::FRAMEBUFFER_BINDING, &mut saved_fbo);
+            gl::GetIntegerv(gl::VIEWPORT, saved_viewport.as_mut_ptr());
+            gl::BindFramebuffer(gl::FRAMEBUFFER, self.fbo);
+            gl::FramebufferRenderbuffer(
+                gl::FRAMEBUFFER,
+                gl::DEPTH_ATTACHMENT,
+                gl::RENDERBUFFER,
+                self.depth,
+            );
+            gl::Viewport(0, 0, size as i32, size as i32);
+        }
+        let mut camera = Camera::perspective(90f32.to_radians(), probe.near, probe.far);
+        camera.position = center;
+        let mut captured = 0;
+        while capture.next_face < 6 && captured < max_faces {
+            let face = capture.next_face;
+            let (dir, up) = CUBE_FACE_VIEWS[face as usize];
+            let view = look_at(Vec3::ZERO, dir, up);
+            camera.rotation = Quat::from_mat4(&view.inverse()).normalize();
+            unsafe {
+                gl::BindFramebuffer(gl::FRAMEBUFFER, self.fbo);
+                gl::Viewport(0, 0, size as i32, size as i32);
+                gl::FramebufferTexture2D(
+                    gl::FRAMEBUFFER,
+                    gl::COLOR_ATTACHMENT0,
+                    gl::TEXTURE_CUBE_MAP_POSITIVE_X + face,
+                    capture.environment,
+                    0,
+                );
+                gl::ClearColor(0.0, 0.0, 0.0, 1.0);
+                gl::Clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT);
+                gl::Enable(gl::DEPTH_TEST);
+            }
+            render(&camera);
+            capture.next_face += 1;
+            captured += 1;
+        }
+
+        if capture.next_face >= 6 {
+            unsafe {
+                gl::FramebufferRenderbuffer(
+                    gl::FRAMEBUFFER,
+                    gl::DEPTH_ATTACHMENT,
+                    gl::RENDERBUFFER,
+                    0,
+                );
+                gl::Disable(gl::DEPTH_TEST);
+                gl::Disable(gl::CULL_FACE);
+                gl::Enable(gl::TEXTURE_CUBE_MAP_SEAMLESS);
+                gl::ActiveTexture(gl::TEXTURE0);
+                gl::BindTexture(gl::TEXTURE_CUBE_MAP, capture.environment);
+                gl::GenerateMipmap(gl::TEXTURE_CUBE_MAP);
+            }
+            let program = &self.prefilter_program;
+            program.bind();
+            program.set("uEnvironment", &0i32);
+            program.set("uResolution", &(size as f32));
+            for level in 0..self.levels {
+                let roughness = level as f32 / (self.levels - 1) as f32;
+                program.set("uRoughness", &roughness);
+                let level_size = (size >> level).max(1);
+                render_cube_faces(program, &self.cube, capture.prefilter, level_size, level as i32);
+            }
+            unsafe { gl::Enable(gl::DEPTH_TEST) };
+            capture.ready = true;
+        }
+
+        unsafe {
+            gl::BindFramebuffer(gl::FRAMEBUFFER, saved_fbo as u32);
+            let [x, y, w, h] = saved_viewport;
+            gl::Viewport(x, y, w, h);
+        }
+        captured
+    }
+}
+
+impl Drop for ReflectionProbes {
+    fn drop(&mut self) {
+        self.captures.clear();
+        GlObjects::untrack(GlObjectKind::Framebuffer, self.fbo);
+        GlObjects::untrack(GlObjectKind::Renderbuffer, self.depth);
+        unsafe {
+            gl::DeleteRenderbuffers(1, &self.depth);
+            gl::DeleteFramebuffers(1, &self.fbo);
+        }
+    }
+}
+
+/// 收集场景中的探针及其世界位置
+fn probes(scene: &Scene) -> Vec<(Entity, Vec3, ReflectionProbe)> {
+    scene
+        .query::<ReflectionProbe>()
+        .filter_map(|(entity, probe)| {
+            let center = scene.get(entity)?.world_matrix().w_axis.truncate();
+            Some((entity, center, *probe))
+        })
+        .collect()
+}
@@ -0,0 +1,389 @@
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use flate2::read::ZlibDecoder;
+use flate2::write::ZlibEncoder;
+use flate2::Compression;
+
+use crate::error::{Error, Result};
+use crate::math::*;
+use crate::{debug, Chunk, VoxelWorld};
+
+/// 区域文件在每个坐标轴上包含的区块数
+pub const REGION_SIZE: i32 = 8;
+
+const REGION_CHUNKS: usize = (REGION_SIZE * REGION_SIZE * REGION_SIZE) as usize;
+const SECTOR_SIZE: u64 = 4096;
+/// 文件头占用的扇区数：区块位置表与时间戳表各占半个扇区
+const HEADER_SECTORS: u32 = 1;
+const MAX_CHUNK_SECTORS: u32 = 255;
+
+/// 区块数据的压缩方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RegionCompression {
+    /// 不压缩
+    None,
+    /// zlib
+    Zlib,
+}
+
+impl RegionCompression {
+    fn tag(self) -> u8 {
+        match self {
+            RegionCompression::Zlib => 2,
+            RegionCompression::None => 3,
+        }
+    }
+
+    fn from_tag(tag: u8) -> Option<Self> {
+        match tag {
+            2 => Some(RegionCompression::Zlib),
+            3 => Some(RegionCompression::None),
+            _ => None,
+        }
+    }
+}
+
+/// 区域文件
+///
+/// 仿照 Anvil 格式，将 8³ 个相邻区块保存在同一个文件中。文件以 4096 字节的扇区为单位分配：
+/// 首个扇区为文件头，依次保存每个区块的位置(大端序，高 24 位为起始扇区、低 8 位为扇区数)与最后写入时间；
+/// 其余扇区保存区块数据，每段数据以 4 字节大端序长度与 1 字节压缩方式开头。
+/// 区块变大而原位置放不下时重新分配扇区，释放的扇区可被之后写入的区块复用
+///
+/// # 注解
+///
+/// 同一文件不应同时被多个 [`RegionFile`] 打开
+pub struct RegionFile {
+    file: File,
+    locations: [u32; REGION_CHUNKS],
+    timestamps: [u32; REGION_CHUNKS],
+    used: Vec<bool>,
+    compression: RegionCompression,
+}
+
+impl RegionFile {
+    /// 打开区域文件，文件不存在时创建
+    ///
+    /// # 参数
+    /// + `path` - 文件路径
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path)?;
+        let length = file.metadata()?.len();
+        let mut header = vec![0u8; (HEADER_SECTORS as u64 * SECTOR_SIZE) as usize];
+        if length < header.len() as u64 {
+            file.set_len(0)?;
+            file.write_all(&header)?;
+        } else {
+            file.read_exact(&mut header)?;
+        }
+        let sectors = length.div_ceil(SECTOR_SIZE).max(HEADER_SECTORS as u64) as usize;
+        let mut region = Self {
+            file,
+            locations: [0; REGION_CHUNKS],
+            timestamps: [0; REGION_CHUNKS],
+            used: vec![false; sectors],
+            compression: RegionCompression::Zlib,
+        };
+        region.used[..HEADER_SECTORS as usize].fill(true);
+        for i in 0..REGION_CHUNKS {
+            let word = |offset: usize| {
+                u32::from_be_bytes(header[offset + i * 4..offset + i * 4 + 4].try_into().unwrap())
+            };
+            region.locations[i] = word(0);
+            region.timestamps[i] = word(REGION_CHUNKS * 4);
+            let (start, count) = split_location(region.locations[i]);
+            if start < HEADER_SECTORS || (start + count) as usize > sectors {
+                // 损坏的位置表项，当作区块不存在
+                region.locations[i] = 0;
+                continue;
+            }
+            region.used[start as usize..(start + count) as usize].fill(true);
+        }
+        debug!(Self, "已打开区域文件 {}", path.display());
+        Ok(region)
+    }
+
+    /// 设置之后写入区块时使用的压缩方式，默认为 zlib
+    pub fn set_compression(&mut self, compression: RegionCompression) {
+        self.compression = compression;
+    }
+
+    /// 区域内是否保存了指定区块
+    ///
+    /// # 参数
+    /// + `local` - 区块在区域内的坐标，各分量须小于 [`REGION_SIZE`]
+    pub fn contains(&self, local: UVec3) -> bool {
+        self.locations[region_index(local)] != 0
+    }
+
+    /// 获取区块最后写入的时间，单位为 Unix 秒，区块不存在时返回`None`
+    pub fn timestamp(&self, local: UVec3) -> Option<u32> {
+        let i = region_index(local);
+        (self.locations[i] != 0).then_some(self.timestamps[i])
+    }
+
+    /// 读取区块
+    ///
+    /// # 参数
+    /// + `local` - 区块在区域内的坐标
+    ///
+    /// # 返回值
+    /// 返回区块，区块不存在时返回`None`，数据损坏时返回错误
+    pub fn read(&mut self, local: UVec3) -> Result<Option<Chunk>> {
+        let (start, count) = split_location(self.locations[region_index(local)]);
+        if count == 0 {
+            return Ok(None);
+        }
+        self.file.seek(SeekFrom::Start(start as u64 * SECTOR_SIZE))?;
+        let mut head = [0u8; 5];
+        self.file.read_exact(&mut head)?;
+        let length = u32::from_be_bytes([head[0], head[1], head[2], head[3]]) as u64;
+        if length == 0 || length + 4 > count as u64 * SECTOR_SIZE {
+            return Err(Error::Parse(format!("区块 {} 的数据长度无效", local)));
+        }
+        let compression = RegionCompression::from_tag(head[4])
+            .ok_or_else(|| Error::Parse(format!("区块 {} 的压缩方式 {} 未知", local, head[4])))?;
+        let mut data = vec![0u8; length as usize - 1];
+        self.file.read_exact(&mut data)?;
+        if compression == RegionCompression::Zlib {
+            let mut inflated = Vec::new();
+            ZlibDecoder::new(data.as_slice()).read_to_end(&mut inflated)?;
+            data = inflated;
+        }
+        bincode::deserialize(&data)
+            .map(Some)
+            .map_err(|e| Error::Parse(format!("区块 {}: {}", local, e)))
+    }
+
+    /// 写入区块
+    ///
+    /// # 参数
+    /// + `local` - 区块在区域内的坐标
+    /// + `chunk` - 区块
+    pub fn write(&mut self, local: UVec3, chunk: &Chunk) -> Result<()> {
+        let encoded = bincode::serialize(chunk).map_err(|e| Error::Parse(e.to_string()))?;
+        let data = match self.compression {
+            RegionCompression::Zlib => {
+                let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+                encoder.write_all(&encoded)?;
+                encoder.finish()?
+            }
+            RegionCompression::None => encoded,
+        };
+        let length = data.len() as u64 + 1;
+        let count = (length + 4).div_ceil(SECTOR_SIZE) as u32;
+        if count > MAX_CHUNK_SECTORS {
+            return Err(Error::Parse(format!("区块 {} 的数据超过 1MB", local)));
+        }
+
+        let i = region_index(local);
+        let (old_start, old_count) = split_location(self.locations[i]);
+        self.free(old_start, old_count);
+        let start = self.allocate(count);
+        let mut sector = Vec::with_capacity((count as u64 * SECTOR_SIZE) as usize);
+        sector.extend_from_slice(&(length as u32).to_be_bytes());
+        sector.push(self.compression.tag());
+        sector.extend_from_slice(&data);
+        sector.resize((count as u64 * SECTOR_SIZE) as usize, 0);
+        self.file.seek(SeekFrom::Start(start as u64 * SECTOR_SIZE))?;
+        self.file.write_all(&sector)?;
+
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs() as u32);
+        self.write_header(i, (start << 8) | count, timestamp)
+    }
+
+    /// 删除区块，其扇区可被之后写入的区块复用
+    pub fn remove(&mut self, local: UVec3) -> Result<()> {
+        let i = region_index(local);
+        let (start, count) = split_location(self.locations[i]);
+        if count == 0 {
+            return Ok(());
+        }
+        self.free(start, count);
+        self.write_header(i, 0, 0)
+    }
+
+    /// 将缓冲的写入同步到磁盘
+    pub fn flush(&mut self) -> Result<()> {
+        self.file.sync_data()?;
+        Ok(())
+    }
+
+    fn write_header(&mut self, i: usize, location: u32, timestamp: u32) -> Result<()> {
+        self.locations[i] = location;
+        self.timestamps[i] = timestamp;
+        self.file.seek(SeekFrom::Start(i as u64 * 4))?;
+        self.file.write_all(&location.to_be_bytes())?;
+        self.file.seek(SeekFrom::Start((REGION_CHUNKS + i) as u64 * 4))?;
+        self.file.write_all(&timestamp.to_be_bytes())?;
+        Ok(())
+    }
+
+    fn free(&mut self, start: u32, count: u32) {
+        if count > 0 {
+            self.used[start as usize..(start + count) as usize].fill(false);
+        }
+    }
+
+    /// 查找第一段足够长的空闲扇区，找不到时在文件末尾追加
+    fn allocate(&mut self, count: u32) -> u32 {
+        let count = count as usize;
+        let mut run = 0;
+        let mut found = None;
+        for (sector, used) in self.used.iter().enumerate() {
+            run = if *used { 0 } else { run + 1 };
+            if run == count {
+                found = Some(sector + 1 - count);
+                break;
+            }
+        }
+        let start = found.unwrap_or_else(|| {
+            // 末尾的空闲扇区也可以与新扇区拼接
+            let tail = self.used.iter().rev().take_while(|used| !**used).count();
+            self.used.len() - tail
+        });
+        if self.used.len() < start + count {
+            self.used.resize(start + count, false);
+        }
+        self.used[start..start + count].fill(true);
+        start as u32
+    }
+}
+
+fn split_location(location: u32) -> (u32, u32) {
+    (location >> 8, location & 0xFF)
+}
+
+fn region_index(local: UVec3) -> usize {
+    debug_assert!(local.max_element() < REGION_SIZE as u32, "区域内坐标越界");
+    let s = REGION_SIZE as usize;
+    (local.y as usize * s + local.z as usize) * s + local.x as usize
+}
+
+/// 基于区域文件的区块存储
+///
+/// 将世界的区块分组保存到目录下的区域文件`r.<x>.<y>.<z>.region`中，避免为每个区块创建一个小文件。
+/// 最近使用的区域文件保持打开，超过 [`RegionStorage::max_open`] 时关闭最久未使用的文件
+///
+/// # 示例
+///
+/// ```no_run
+/// use gle::{*, math::*};
+///
+/// let mut storage = RegionStorage::new("saves/world/regions").unwrap();
+/// let mut chunk = Chunk::new();
+/// chunk.set(UVec3::new(1, 2, 3), BlockId(4));
+/// storage.save_chunk(IVec3::new(-3, 0, 17), &chunk).unwrap();
+/// let loaded = storage.load_chunk(IVec3::new(-3, 0, 17)).unwrap().unwrap();
+/// assert_eq!(loaded.get(UVec3::new(1, 2, 3)), BlockId(4));
+/// ```
+///
+/// # 注解
+///
+/// 存储不是线程安全的，在 [`ChunkStreamer`](crate::ChunkStreamer) 的生成函数中使用时应以互斥锁包装
+pub struct RegionStorage {
+    /// 同时打开的区域文件数上限
+    pub max_open: usize,
+    dir: PathBuf,
+    regions: HashMap<IVec3, (RegionFile, u64)>,
+    clock: u64,
+}
+
+impl RegionStorage {
+    /// 创建区块存储，目录不存在时创建
+    ///
+    /// # 参数
+    /// + `dir` - 区域文件所在目录
+    pub fn new<P: AsRef<Path>>(dir: P) -> Result<Self> {
+        std::fs::create_dir_all(dir.as_ref())?;
+        Ok(Self {
+            max_open: 32,
+            dir: dir.as_ref().to_path_buf(),
+            regions: HashMap::new(),
+            clock: 0,
+        })
+    }
+
+    /// 将区块坐标拆分为区域坐标与区域内坐标
+    pub fn split(coord: IVec3) -> (IVec3, UVec3) {
+        let region = coord.div_euclid(IVec3::splat(REGION_SIZE));
+        let local = coord.rem_euclid(IVec3::splat(REGION_SIZE));
+        (region, local.as_uvec3())
+    }
+
+    /// 获取区域文件的路径
+    pub fn region_path(&self, region: IVec3) -> PathBuf {
+        self.dir
+            .join(format!("r.{}.{}.{}.region", region.x, region.y, region.z))
+    }
+
+    /// 读取区块，区块从未保存过时返回`None`
+    pub fn load_chunk(&mut self, coord: IVec3) -> Result<Option<Chunk>> {
+        let (region, local) = Self::split(coord);
+        if !self.regions.contains_key(&region) && !self.region_path(region).exists() {
+            return Ok(None);
+        }
+        self.region(region)?.read(local)
+    }
+
+    /// 保存区块，空区块会从文件中删除
+    pub fn save_chunk(&mut self, coord: IVec3, chunk: &Chunk) -> Result<()> {
+        let (region, local) = Self::split(coord);
+        if chunk.is_empty() {
+            if self.regions.contains_key(&region) || self.region_path(region).exists() {
+                self.region(region)?.remove(local)?;
+            }
+            return Ok(());
+        }
+        self.region(region)?.write(local, chunk)
+    }
+
+    /// 保存世界中的所有区块
+    pub fn save_world(&mut self, world: &VoxelWorld) -> Result<()> {
+        for (coord, chunk) in world.chunks() {
+            self.save_chunk(coord, chunk)?;
+        }
+        self.flush()
+    }
+
+    /// 将所有打开的区域文件同步到磁盘
+    pub fn flush(&mut self) -> Result<()> {
+        for (region, _) in self.regions.values_mut() {
+            region.flush()?;
+        }
+        Ok(())
+    }
+
+    fn region(&mut self, region: IVec3) -> Result<&mut RegionFile> {
+        self.clock += 1;
+        if !self.regions.contains_key(&region) {
+            if self.regions.len() >= self.max_open.max(1) {
+                let oldest = self
+                    .regions
+                    .iter()
+                    .min_by_key(|(_, (_, used))| *used)
+                    .map(|(coord, _)| *coord);
+                if let Some(mut file) = oldest.and_then(|coord| self.regions.remove(&coord)) {
+                    file.0.flush()?;
+                }
+            }
+            let file = RegionFile::open(self.region_path(region))?;
+            self.regions.insert(region, (file, 0));
+        }
+        let entry = self.regions.get_mut(&region).unwrap();
+        entry.1 = self.clock;
+        Ok(&mut entry.0)
+    }
+}
@@ -0,0 +1,691 @@
+use std::collections::HashMap;
+
+use crate::error::{Error, Result};
+use crate::{debug, AttachmentFormat, GlObjectKind, GlObjects, RenderTexture};
+
+type PassCallback<'a> = Box<dyn FnMut(&PassContext) + 'a>;
+
+/// 渲染图资源标识
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ResourceId(usize);
+
+/// 渲染图中间资源的大小
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GraphSize {
+    /// 与渲染图输出大小相同
+    Full,
+    /// 渲染图输出大小乘以比例
+    Scaled(f32),
+    /// 固定大小
+    Fixed(u32, u32),
+}
+
+impl GraphSize {
+    fn resolve(self, width: u32, height: u32) -> (u32, u32) {
+        match self {
+            GraphSize::Full => (width.max(1), height.max(1)),
+            GraphSize::Scaled(scale) => (
+                ((width as f32 * scale) as u32).max(1),
+                ((height as f32 * scale) as u32).max(1),
+            ),
+            GraphSize::Fixed(w, h) => (w.max(1), h.max(1)),
+        }
+    }
+}
+
+enum ResourceKind {
+    Transient {
+        format: AttachmentFormat,
+        size: GraphSize,
+    },
+    Imported {
+        texture: u32,
+        width: u32,
+        height: u32,
+        depth: bool,
+    },
+}
+
+struct Resource {
+    name: String,
+    kind: ResourceKind,
+}
+
+struct Pass<'a> {
+    name: String,
+    reads: Vec<ResourceId>,
+    writes: Vec<ResourceId>,
+    depth: Option<ResourceId>,
+    backbuffer: bool,
+    side_effect: bool,
+    storage: bool,
+    execute: Option<PassCallback<'a>>,
+}
+
+/// 通道执行时的上下文
+///
+/// 执行回调被调用前，通道的输出附件已绑定为当前帧缓冲，视口已设置为输出大小
+#[derive(Debug)]
+pub struct PassContext<'r> {
+    textures: &'r [u32],
+    width: u32,
+    height: u32,
+}
+
+impl PassContext<'_> {
+    /// 获取资源对应的 OpenGL 纹理对象ID
+    ///
+    /// # 注解
+    ///
+    /// 只能获取通道声明为输入或输出的资源，其它资源可能尚未分配或已被回收复用
+    pub fn texture(&self, id: ResourceId) -> u32 {
+        self.textures[id.0]
+    }
+
+    /// 获取输出大小
+    pub fn size(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+
+    /// 将资源纹理绑定到指定纹理单元
+    pub fn bind_texture(&self, id: ResourceId, unit: u32) {
+        unsafe {
+            gl::ActiveTexture(gl::TEXTURE0 + unit);
+            gl::BindTexture(gl::TEXTURE_2D, self.texture(id));
+        }
+    }
+}
+
+/// 渲染通道声明构建器
+///
+/// 由 [`RenderGraph::add_pass`] 返回，通过链式调用声明通道的输入与输出
+pub struct PassBuilder<'g, 'a> {
+    graph: &'g mut RenderGraph<'a>,
+    index: usize,
+}
+
+impl<'a> PassBuilder<'_, 'a> {
+    fn pass(&mut self) -> &mut Pass<'a> {
+        &mut self.graph.passes[self.index]
+    }
+
+    /// 声明通道读取的资源
+    pub fn read(mut self, id: ResourceId) -> Self {
+        self.pass().reads.push(id);
+        self
+    }
+
+    /// 声明通道写入的颜色附件，按声明顺序附加到`COLOR_ATTACHMENT0`、`COLOR_ATTACHMENT1`……
+    pub fn write(mut self, id: ResourceId) -> Self {
+        self.pass().writes.push(id);
+        self
+    }
+
+    /// 声明通道使用的深度附件，深度附件同时被读取与写入
+    pub fn depth(mut self, id: ResourceId) -> Self {
+        self.pass().depth = Some(id);
+        self
+    }
+
+    /// 声明通道输出到默认帧缓冲，此类通道不会被剔除
+    pub fn write_backbuffer(mut self) -> Self {
+        self.pass().backbuffer = true;
+        self
+    }
+
+    /// 声明通道具有资源之外的副作用(如回读数据)，此类通道不会被剔除
+    pub fn side_effect(mut self) -> Self {
+        self.pass().side_effect = true;
+        self
+    }
+
+    /// 声明通道通过图像存储或着色器存储缓冲写入资源，读取其输出前将插入内存屏障
+    pub fn storage(mut self) -> Self {
+        self.pass().storage = true;
+        self
+    }
+
+    /// 设置通道的执行回调
+    pub fn execute<F: FnMut(&PassContext) + 'a>(mut self, f: F) {
+        self.pass().execute = Some(Box::new(f));
+    }
+}
+
+/// 渲染图
+///
+/// 以资源依赖描述一帧内的渲染通道。执行时根据读写关系对通道排序，剔除输出未被使用的通道，
+/// 按生命周期从 [`RenderTargetPool`] 中分配与回收中间纹理(生命周期不重叠的资源复用同一纹理)，
+/// 并在需要时插入内存屏障。后处理效果、SSAO、泛光与色调映射等均可作为通道组合
+///
+/// # 示例
+///
+/// ```
+/// use gle::*;
+///
+/// fn render_frame(pool: &mut RenderTargetPool) {
+///     let mut graph = RenderGraph::new(1280, 720);
+///     let color = graph.create("scene.color", AttachmentFormat::Rgba16F, GraphSize::Full);
+///     let depth = graph.create("scene.depth", AttachmentFormat::Depth32F, GraphSize::Full);
+///     let half = graph.create("half", AttachmentFormat::Rgba16F, GraphSize::Scaled(0.5));
+///     graph
+///         .add_pass("present")
+///         .read(half)
+///         .write_backbuffer()
+///         .execute(move |ctx| {
+///             ctx.bind_texture(half, 0);
+///             // 绘制全屏三角形……
+///         });
+///     graph.add_pass("scene").write(color).depth(depth).execute(|_| {
+///         // 绘制场景……
+///     });
+///     graph.add_pass("downsample").read(color).write(half).execute(move |ctx| {
+///         ctx.bind_texture(color, 0);
+///         // 绘制全屏三角形……
+///     });
+///     // 通道按依赖关系以 scene、downsample、present 的顺序执行
+///     graph.execute(pool).unwrap();
+/// }
+/// ```
+///
+/// # 注解
+///
+/// 渲染图通常每帧重新构建，其执行回调可借用当帧的数据。中间纹理由跨帧保留的 [`RenderTargetPool`] 持有。
+/// 该类型只能在渲染线程中执行
+pub struct RenderGraph<'a> {
+    width: u32,
+    height: u32,
+    resources: Vec<Resource>,
+    passes: Vec<Pass<'a>>,
+}
+
+impl<'a> RenderGraph<'a> {
+    /// 创建空的渲染图
+    ///
+    /// # 参数
+    /// + `width` - 输出宽度，用于计算 [`GraphSize::Full`] 与默认帧缓冲视口
+    /// + `height` - 输出高度
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            resources: Vec::new(),
+            passes: Vec::new(),
+        }
+    }
+
+    /// 声明由渲染图分配的中间资源
+    ///
+    /// # 参数
+    /// + `name` - 资源名称，用于调试输出
+    /// + `format` - 格式
+    /// + `size` - 大小
+    pub fn create(&mut self, name: &str, format: AttachmentFormat, size: GraphSize) -> ResourceId {
+        self.resources.push(Resource {
+            name: name.to_string(),
+            kind: ResourceKind::Transient { format, size },
+        });
+        ResourceId(self.resources.len() - 1)
+    }
+
+    /// 导入外部纹理作为资源，导入的纹理不会被分配或回收，写入导入资源的通道不会被剔除
+    ///
+    /// # 参数
+    /// + `name` - 资源名称
+    /// + `texture` - OpenGL 纹理对象ID
+    /// + `width` - 纹理宽度
+    /// + `height` - 纹理高度
+    /// + `depth` - 是否为深度纹理
+    pub fn import(
+        &mut self,
+        name: &str,
+        texture: u32,
+        width: u32,
+        height: u32,
+        depth: bool,
+    ) -> ResourceId {
+        self.resources.push(Resource {
+            name: name.to_string(),
+            kind: ResourceKind::Imported {
+                texture,
+                width,
+                height,
+                depth,
+            },
+        });
+        ResourceId(self.resources.len() - 1)
+    }
+
+    /// 添加渲染通道
+    ///
+    /// # 参数
+    /// + `name` - 通道名称，用于调试输出
+    ///
+    /// # 返回值
+    /// 返回用于声明输入输出的构建器
+    pub fn add_pass<'g>(&'g mut self, name: &str) -> PassBuilder<'g, 'a> {
+        self.passes.push(Pass {
+            name: name.to_string(),
+            reads: Vec::new(),
+            writes: Vec::new(),
+            depth: None,
+            backbuffer: false,
+            side_effect: false,
+            storage: false,
+            execute: None,
+        });
+        let index = self.passes.len() - 1;
+        PassBuilder { graph: self, index }
+    }
+
+    /// 计算通道执行顺序，不包含被剔除的通道
+    ///
+    /// # 返回值
+    /// 返回按执行顺序排列的通道名称，存在循环依赖时返回错误
+    pub fn order(&self) -> Result<Vec<&str>> {
+        let order = self.schedule()?;
+        Ok(order.iter().map(|&i| self.passes[i].name.as_str()).collect())
+    }
+
+    /// 执行渲染图
+    ///
+    /// # 参数
+    /// + `pool` - 中间纹理池
+    ///
+    /// # 返回值
+    /// 存在循环依赖或资源声明错误时返回错误，此时不执行任何通道
+    pub fn execute(mut self, pool: &mut RenderTargetPool) -> Result<()> {
+        let order = self.schedule()?;
+        let resource_count = self.resources.len();
+
+        // 计算中间资源的生命周期(在执行顺序中的首末位置)
+        let mut first = vec![usize::MAX; resource_count];
+        let mut last = vec![0; resource_count];
+        for (step, &index) in order.iter().enumerate() {
+            for id in self.passes[index].resources() {
+                first[id.0] = first[id.0].min(step);
+                last[id.0] = last[id.0].max(step);
+            }
+        }
+
+        let mut textures = vec![0u32; resource_count];
+        let mut sizes = vec![(0u32, 0u32); resource_count];
+        let mut allocated: Vec<Option<RenderTexture>> = (0..resource_count).map(|_| None).collect();
+        let mut storage_written = vec![false; resource_count];
+        for (i, resource) in self.resources.iter().enumerate() {
+            if let ResourceKind::Imported {
+                texture,
+                width,
+                height,
+                ..
+            } = resource.kind
+            {
+                textures[i] = texture;
+                sizes[i] = (width, height);
+            }
+        }
+
+        for (step, &index) in order.iter().enumerate() {
+            let pass = &mut self.passes[index];
+            for id in pass.resources() {
+                if first[id.0] != step {
+                    continue;
+                }
+                if let ResourceKind::Transient { format, size } = self.resources[id.0].kind {
+                    let (w, h) = size.resolve(self.width, self.height);
+                    let texture = pool.acquire(w, h, format);
+                    textures[id.0] = texture.id();
+                    sizes[id.0] = (w, h);
+                    allocated[id.0] = Some(texture);
+                }
+            }
+
+            if pass.reads.iter().any(|id| storage_written[id.0]) {
+                memory_barrier();
+            }
+
+            let (width, height) = if pass.backbuffer {
+                unsafe { gl::BindFramebuffer(gl::FRAMEBUFFER, 0) };
+                (self.width, self.height)
+            } else if pass.writes.is_empty() && pass.depth.is_none() {
+                (self.width, self.height)
+            } else {
+                let colors: Vec<u32> = pass.writes.iter().map(|id| textures[id.0]).collect();
+                let imported = pass.writes.iter().chain(pass.depth.iter()).any(|id| {
+                    matches!(self.resources[id.0].kind, ResourceKind::Imported { .. })
+                });
+                let depth = pass.depth.map(|id| {
+                    let stencil = match self.resources[id.0].kind {
+                        ResourceKind::Transient { format, .. } => format.has_stencil(),
+                        ResourceKind::Imported { .. } => false,
+                    };
+                    (textures[id.0], stencil)
+                });
+                let fbo = pool.framebuffer(&colors, depth, !imported)?;
+                unsafe { gl::BindFramebuffer(gl::FRAMEBUFFER, fbo) };
+                pass.writes
+                    .first()
+                    .or(pass.depth.as_ref())
+                    .map(|id| sizes[id.0])
+                    .unwrap_or((self.width, self.height))
+            };
+            unsafe { gl::Viewport(0, 0, width as i32, height as i32) };
+
+            if let Some(execute) = pass.execute.as_mut() {
+                execute(&PassContext {
+                    textures: &textures,
+                    width,
+                    height,
+                });
+            }
+
+            if pass.storage {
+                for id in &pass.writes {
+                    storage_written[id.0] = true;
+                }
+            }
+            for id in pass.resources() {
+                if last[id.0] == step {
+                    if let Some(texture) = allocated[id.0].take() {
+                        pool.release(texture);
+                    }
+                }
+            }
+        }
+        unsafe {
+            gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
+            gl::Viewport(0, 0, self.width as i32, self.height as i32);
+        }
+        pool.end_frame();
+        Ok(())
+    }
+
+    /// 计算执行顺序：写入者先于读取者，同一资源的多个写入者按声明顺序执行
+    fn schedule(&self) -> Result<Vec<usize>> {
+        for pass in &self.passes {
+            for id in pass.resources() {
+                if id.0 >= self.resources.len() {
+                    return Err(Error::Graph(format!(
+                        "通道 {} 引用了不存在的资源",
+                        pass.name
+                    )));
+                }
+            }
+            for id in pass.writes.iter().chain(pass.depth.iter()) {
+                let is_depth = match self.resources[id.0].kind {
+                    ResourceKind::Transient { format, .. } => format.is_depth(),
+                    ResourceKind::Imported { depth, .. } => depth,
+                };
+                if is_depth != (pass.depth == Some(*id)) {
+                    return Err(Error::Graph(format!(
+                        "通道 {} 的附件 {} 格式与用途不符",
+                        pass.name, self.resources[id.0].name
+                    )));
+                }
+            }
+        }
+
+        let count = self.passes.len();
+        let mut writers: Vec<Vec<usize>> = vec![Vec::new(); self.resources.len()];
+        for (i, pass) in self.passes.iter().enumerate() {
+            for id in pass.writes.iter().chain(pass.depth.iter()) {
+                writers[id.0].push(i);
+            }
+        }
+        let mut dependencies: Vec<Vec<usize>> = vec![Vec::new(); count];
+        for (i, pass) in self.passes.iter().enumerate() {
+            for id in &pass.reads {
+                dependencies[i].extend(writers[id.0].iter().filter(|&&w| w != i));
+            }
+            for id in pass.writes.iter().chain(pass.depth.iter()) {
+                let list = &writers[id.0];
+                let position = list.iter().position(|&w| w == i).unwrap_or(0);
+                if position > 0 {
+                    dependencies[i].push(list[position - 1]);
+                }
+            }
+        }
+
+        // 从有外部可见结果的通道出发标记存活通道
+        let mut alive = vec![false; count];
+        let mut stack: Vec<usize> = (0..count)
+            .filter(|&i| {
+                let pass = &self.passes[i];
+                pass.backbuffer
+                    || pass.side_effect
+                    || pass.writes.iter().chain(pass.depth.iter()).any(|id| {
+                        matches!(self.resources[id.0].kind, ResourceKind::Imported { .. })
+                    })
+            })
+            .collect();
+        while let Some(i) = stack.pop() {
+            if !alive[i] {
+                alive[i] = true;
+                stack.extend(dependencies[i].iter().copied());
+            }
+        }
+
+        // 拓扑排序，同层按声明顺序
+        let mut remaining: Vec<usize> = vec![0; count];
+        let mut dependents: Vec<Vec<usize>> = vec![Vec::new(); count];
+        for i in (0..count).filter(|&i| alive[i]) {
+            for &d in &dependencies[i] {
+                remaining[i] += 1;
+                dependents[d].push(i);
+            }
+        }
+        let mut ready: Vec<usize> = (0..count)
+            .filter(|&i| alive[i] && remaining[i] == 0)
+            .collect();
+        let mut order = Vec::with_capacity(count);
+        while !ready.is_empty() {
+            ready.sort_unstable_by(|a, b| b.cmp(a));
+            let i = ready.pop().unwrap();
+            order.push(i);
+            for &next in &dependents[i] {
+                remaining[next] -= 1;
+                if remaining[next] == 0 {
+                    ready.push(next);
+                }
+            }
+        }
+        let alive_count = alive.iter().filter(|&&a| a).count();
+        if order.len() != alive_count {
+            let cycle: Vec<&str> = (0..count)
+                .filter(|&i| alive[i] && !order.contains(&i))
+                .map(|i| self.passes[i].name.as_str())
+                .collect();
+            return Err(Error::Graph(format!("通道存在循环依赖: {}", cycle.join(", "))));
+        }
+        if alive_count < count {
+            debug!(
+                Self,
+                "剔除 {} 个输出未被使用的通道",
+                count - alive_count
+            );
+        }
+        Ok(order)
+    }
+}
+
+impl Pass<'_> {
+    fn resources(&self) -> impl Iterator<Item = ResourceId> + '_ {
+        self.reads
+            .iter()
+            .chain(self.writes.iter())
+            .chain(self.depth.iter())
+            .copied()
+    }
+}
+
+fn memory_barrier() {
+    if gl::MemoryBarrier::is_loaded() {
+        unsafe {
+            gl::MemoryBarrier(
+                gl::TEXTURE_FETCH_BARRIER_BIT
+                    | gl::SHADER_IMAGE_ACCESS_BARRIER_BIT
+                    | gl::FRAMEBUFFER_BARRIER_BIT,
+            )
+        };
+    }
+}
+
+/// 渲染图中间纹理池
+///
+/// 跨帧保留渲染图分配的纹理与帧缓冲对象，连续若干帧未被使用的纹理将被释放
+///
+/// # 注解
+///
+/// 该类型只能在渲染线程中创建、使用与释放
+#[derive(Debug)]
+pub struct RenderTargetPool {
+    free: Vec<(RenderTexture, u64)>,
+    framebuffers: HashMap<(Vec<u32>, Option<u32>), u32>,
+    temporary: Vec<u32>,
+    frame: u64,
+    max_idle_frames: u64,
+}
+
+impl Default for RenderTargetPool {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RenderTargetPool {
+    /// 创建空的纹理池，纹理连续 3 帧未被使用时释放
+    pub fn new() -> Self {
+        Self {
+            free: Vec::new(),
+            framebuffers: HashMap::new(),
+            temporary: Vec::new(),
+            frame: 0,
+            max_idle_frames: 3,
+        }
+    }
+
+    /// 获取池中空闲纹理数量
+    pub fn len(&self) -> usize {
+        self.free.len()
+    }
+
+    /// 判断池中是否没有空闲纹理
+    pub fn is_empty(&self) -> bool {
+        self.free.is_empty()
+    }
+
+    /// 释放池中所有纹理与帧缓冲对象
+    pub fn clear(&mut self) {
+        for &fbo in self.framebuffers.values() {
+            GlObjects::untrack(GlObjectKind::Framebuffer, fbo);
+            unsafe { gl::DeleteFramebuffers(1, &fbo) };
+        }
+        self.framebuffers.clear();
+        for fbo in self.temporary.drain(..) {
+            GlObjects::untrack(GlObjectKind::Framebuffer, fbo);
+            unsafe { gl::DeleteFramebuffers(1, &fbo) };
+        }
+        self.free.clear();
+    }
+
+    fn acquire(&mut self, width: u32, height: u32, format: AttachmentFormat) -> RenderTexture {
+        let found = self.free.iter().position(|(t, _)| {
+            t.width() == width && t.height() == height && t.format() == format
+        });
+        match found {
+            Some(index) => self.free.swap_remove(index).0,
+            None => RenderTexture::new(width, height, format),
+        }
+    }
+
+    fn release(&mut self, texture: RenderTexture) {
+        self.free.push((texture, self.frame));
+    }
+
+    /// 获取附加了指定纹理的帧缓冲对象
+    ///
+    /// 引用外部导入纹理的帧缓冲不被缓存(外部纹理可能被释放而ID被复用)，在帧结束时删除
+    fn framebuffer(
+        &mut self,
+        colors: &[u32],
+        depth: Option<(u32, bool)>,
+        cache: bool,
+    ) -> Result<u32> {
+        let key = (colors.to_vec(), depth.map(|(id, _)| id));
+        if let Some(&fbo) = self.framebuffers.get(&key).filter(|_| cache) {
+            return Ok(fbo);
+        }
+        let mut fbo = 0;
+        unsafe {
+            gl::GenFramebuffers(1, &mut fbo);
+            gl::BindFramebuffer(gl::FRAMEBUFFER, fbo);
+            let mut draw_buffers = Vec::with_capacity(colors.len());
+            for (i, &texture) in colors.iter().enumerate() {
+                let attachment = gl::COLOR_ATTACHMENT0 + i as u32;
+                gl::FramebufferTexture2D(gl::FRAMEBUFFER, attachment, gl::TEXTURE_2D, texture, 0);
+                draw_buffers.push(attachment);
+            }
+            if draw_buffers.is_empty() {
+                gl::DrawBuffer(gl::NONE);
+                gl::ReadBuffer(gl::NONE);
+            } else {
+                gl::DrawBuffers(draw_buffers.len() as i32, draw_buffers.as_ptr());
+            }
+            if let Some((texture, stencil)) = depth {
+                let attachment = if stencil {
+                    gl::DEPTH_STENCIL_ATTACHMENT
+                } else {
+                    gl::DEPTH_ATTACHMENT
+                };
+                gl::FramebufferTexture2D(gl::FRAMEBUFFER, attachment, gl::TEXTURE_2D, texture, 0);
+            }
+            let status = gl::CheckFramebufferStatus(gl::FRAMEBUFFER);
+            if status != gl::FRAMEBUFFER_COMPLETE {
+                gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
+                gl::DeleteFramebuffers(1, &fbo);
+                return Err(Error::Gl(format!("帧缓冲不完整: 0x{:X}", status)));
+            }
+        }
+        GlObjects::track(GlObjectKind::Framebuffer, fbo);
+        if cache {
+            self.framebuffers.insert(key, fbo);
+        } else {
+            self.temporary.push(fbo);
+        }
+        Ok(fbo)
+    }
+
+    fn end_frame(&mut self) {
+        for fbo in self.temporary.drain(..) {
+            GlObjects::untrack(GlObjectKind::Framebuffer, fbo);
+            unsafe { gl::DeleteFramebuffers(1, &fbo) };
+        }
+        let frame = self.frame;
+        let max_idle = self.max_idle_frames;
+        let mut expired = Vec::new();
+        self.free.retain(|(texture, used)| {
+            let keep = frame - used < max_idle;
+            if !keep {
+                expired.push(texture.id());
+            }
+            keep
+        });
+        if !expired.is_empty() {
+            // 纹理ID可能被重新分配，引用已释放纹理的帧缓冲必须一并删除
+            self.framebuffers.retain(|(colors, depth), fbo| {
+                let stale = colors.iter().chain(depth.iter()).any(|id| expired.contains(id));
+                if stale {
+                    GlObjects::untrack(GlObjectKind::Framebuffer, *fbo);
+                    unsafe { gl::DeleteFramebuffers(1, fbo) };
+                }
+                !stale
+            });
+        }
+        self.frame += 1;
+    }
+}
+
+impl Drop for RenderTargetPool {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
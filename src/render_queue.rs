@@ -0,0 +1,411 @@
+use serde::{Deserialize, Serialize};
+
+use crate::debug_view::debug_view_program;
+use crate::math::*;
+use crate::{
+    error, Camera, ClipPlanes, DebugView, GpuMesh, LodGroup, OitTarget, Program, Renderer,
+    StencilState, DECAL_STENCIL_BIT,
+};
+
+type UniformSetter<'a> = Box<dyn Fn(&Program) + 'a>;
+
+/// 渲染通道，决定绘制的先后顺序与深度排序方向
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum RenderPass {
+    /// 不透明物体，由近及远绘制以减少过度绘制
+    Opaque = 0,
+    /// 透明度裁剪物体，由近及远绘制
+    AlphaTest = 1,
+    /// 天空等背景，在不透明物体之后绘制
+    Background = 2,
+    /// 半透明物体，由远及近绘制以保证混合正确
+    Transparent = 3,
+    /// 界面等叠加层，按提交顺序绘制
+    Overlay = 4,
+}
+
+/// 绘制排序键
+///
+/// 由高位到低位依次为：
+/// + 不透明类通道 - 通道(4位)、着色器程序(12位)、纹理(16位)、深度(32位)
+/// + 半透明通道 - 通道(4位)、反转深度(32位)、着色器程序(12位)、纹理(16位)
+/// + 叠加通道 - 通道(4位)、提交序号(60位)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SortKey(pub u64);
+
+impl SortKey {
+    /// 生成排序键
+    ///
+    /// # 参数
+    /// + `pass` - 渲染通道
+    /// + `program` - 着色器程序ID
+    /// + `texture` - 主纹理ID
+    /// + `depth` - 到摄像机的距离
+    /// + `sequence` - 提交序号，仅用于叠加通道
+    pub fn new(pass: RenderPass, program: u32, texture: u32, depth: f32, sequence: u32) -> Self {
+        let pass_bits = (pass as u64) << 60;
+        let program = (program & 0xFFF) as u64;
+        let texture = (texture & 0xFFFF) as u64;
+        // 非负浮点数的位模式与其大小单调一致
+        let depth = depth.max(0.0).to_bits() as u64;
+        SortKey(match pass {
+            RenderPass::Opaque | RenderPass::AlphaTest | RenderPass::Background => {
+                pass_bits | program << 48 | texture << 32 | depth
+            }
+            RenderPass::Transparent => {
+                pass_bits | (!depth & 0xFFFF_FFFF) << 28 | program << 16 | texture
+            }
+            RenderPass::Overlay => pass_bits | sequence as u64,
+        })
+    }
+}
+
+/// 一次绘制
+pub struct DrawItem<'a> {
+    /// 渲染通道
+    pub pass: RenderPass,
+    /// 着色器程序
+    pub program: &'a Program,
+    /// 绑定到纹理单元0的纹理，为0时不绑定
+    pub texture: u32,
+    /// 网格
+    pub mesh: &'a GpuMesh,
+    /// 模型矩阵，以`uModel`传入着色器
+    pub model: Mat4,
+    /// 用于设置其余 uniform 的回调，在绘制前调用
+    pub uniforms: Option<UniformSetter<'a>>,
+    /// 是否接收贴花，仅对不透明类通道有效，见 [`DECAL_STENCIL_BIT`]
+    pub receive_decals: bool,
+    /// 覆盖全局调试视图的调试视图，为`None`时使用 [`DebugView::current`]
+    pub debug_view: Option<DebugView>,
+}
+
+/// 渲染队列
+///
+/// 每帧将绘制记录到队列中，执行前按 [`SortKey`] 排序，以减少状态切换并自动处理不透明与半透明物体的绘制顺序
+///
+/// # 示例
+///
+/// ```
+/// use gle::{*, math::*};
+///
+/// // 在渲染线程中
+/// let mut queue = RenderQueue::new();
+/// queue.submit(DrawItem {
+///     pass: RenderPass::Opaque,
+///     program: &program,
+///     texture: 0,
+///     mesh: &mesh,
+///     model: Mat4::IDENTITY,
+///     uniforms: None,
+///     receive_decals: true,
+///     debug_view: None,
+/// });
+/// queue.execute(&camera);
+/// ```
+///
+/// # 注解
+///
+/// 着色器程序通过`uViewProj`接收观察投影矩阵，通过`uModel`接收模型矩阵，
+/// 通过`uTexture`接收纹理单元0。不透明类通道会在模板缓冲中写入 [`DECAL_STENCIL_BIT`]
+#[derive(Default)]
+pub struct RenderQueue<'a> {
+    items: Vec<(Option<f32>, DrawItem<'a>)>,
+    oit: Option<(&'a OitTarget, u32)>,
+    debug_program: Option<Program>,
+}
+
+impl<'a> RenderQueue<'a> {
+    /// 创建空的渲染队列
+    pub fn new() -> Self {
+        Self {
+            items: Vec::new(),
+            oit: None,
+            debug_program: None,
+        }
+    }
+
+    /// 为半透明通道启用顺序无关透明度
+    ///
+    /// # 参数
+    /// + `oit` - 顺序无关透明度渲染目标
+    /// + `depth_texture` - 当前渲染目标的深度纹理
+    ///
+    /// # 注解
+    ///
+    /// 启用后半透明通道绘制到`oit`中，通道结束时合成到当前渲染目标，半透明材质需输出 [`OIT_GLSL`](crate::OIT_GLSL) 的结果
+    pub fn set_oit(&mut self, oit: &'a OitTarget, depth_texture: u32) {
+        self.oit = Some((oit, depth_texture));
+    }
+
+    /// 为半透明通道关闭顺序无关透明度，恢复由远及近排序混合
+    pub fn clear_oit(&mut self) {
+        self.oit = None;
+    }
+
+    /// 获取队列中的绘制数量
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    /// 判断队列是否为空
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// 清空队列
+    pub fn clear(&mut self) {
+        self.items.clear();
+    }
+
+    /// 提交一次绘制
+    ///
+    /// # 参数
+    /// + `item` - 绘制，执行时以模型矩阵的平移部分到摄像机的距离作为深度
+    pub fn submit(&mut self, item: DrawItem<'a>) {
+        self.items.push((None, item));
+    }
+
+    /// 以指定深度提交一次绘制
+    ///
+    /// # 参数
+    /// + `item` - 绘制
+    /// + `depth` - 到摄像机的距离，适用于包围盒中心与模型原点相距较远的网格
+    pub fn submit_with_depth(&mut self, item: DrawItem<'a>, depth: f32) {
+        self.items.push((Some(depth), item));
+    }
+
+    /// 以细节层次组提交一次绘制，提交时根据摄像机为该对象选择层级
+    ///
+    /// # 参数
+    /// + `lod` - 对象的细节层次组
+    /// + `camera` - 摄像机，应与执行队列时所用的摄像机一致
+    /// + `model` - 模型矩阵，其平移部分作为包围球中心
+    /// + `radius` - 局部空间的包围球半径，按模型矩阵的最大缩放换算到世界空间
+    /// + `item` - 一个函数，它接受选中层级的网格并返回绘制
+    ///
+    /// # 返回值
+    /// 返回选中层级的序号，细节层次组没有任何层级时不提交并返回`None`
+    ///
+    /// # 示例
+    ///
+    /// ```no_run
+    /// use gle::{*, math::*};
+    ///
+    /// struct Tree {
+    ///     model: Mat4,
+    ///     lod: LodGroup<GpuMesh>,
+    /// }
+    ///
+    /// fn render<'a>(
+    ///     queue: &mut RenderQueue<'a>,
+    ///     trees: &'a mut [Tree],
+    ///     material: &'a Material,
+    ///     camera: &Camera,
+    /// ) {
+    ///     for tree in trees.iter_mut() {
+    ///         let model = tree.model;
+    ///         queue.submit_lod(&mut tree.lod, camera, model, 2.0, |mesh| {
+    ///             material.draw_item(mesh, model)
+    ///         });
+    ///     }
+    ///     queue.execute(camera);
+    /// }
+    /// ```
+    pub fn submit_lod(
+        &mut self,
+        lod: &'a mut LodGroup<GpuMesh>,
+        camera: &Camera,
+        model: Mat4,
+        radius: f32,
+        item: impl FnOnce(&'a GpuMesh) -> DrawItem<'a>,
+    ) -> Option<usize> {
+        let scale = model
+            .x_axis
+            .truncate()
+            .length()
+            .max(model.y_axis.truncate().length())
+            .max(model.z_axis.truncate().length());
+        lod.select(camera, model.w_axis.truncate(), radius * scale)?;
+        let lod: &'a LodGroup<GpuMesh> = lod;
+        self.submit(item(lod.current()?));
+        Some(lod.current_level())
+    }
+
+    /// 排序并执行队列中的所有绘制，执行后队列被清空
+    ///
+    /// # 参数
+    /// + `camera` - 摄像机
+    ///
+    /// # 返回值
+    /// 返回着色器程序切换次数
+    ///
+    /// # 注解
+    ///
+    /// 半透明通道会启用混合并禁用深度写入(启用顺序无关透明度时改为绘制到其渲染目标)，执行结束后恢复默认状态。
+    /// 绘制按 [`DebugView`] 的设置以调试方式进行，摄像机的 [`Camera::clip_planes`] 在执行期间启用
+    pub fn execute(&mut self, camera: &Camera) -> usize {
+        let mut items: Vec<(SortKey, DrawItem<'a>)> = self
+            .items
+            .drain(..)
+            .enumerate()
+            .map(|(i, (depth, item))| {
+                let depth = depth.unwrap_or_else(|| {
+                    item.model.w_axis.truncate().distance(camera.position)
+                });
+                let key = SortKey::new(item.pass, item.program.id(), item.texture, depth, i as u32);
+                (key, item)
+            })
+            .collect();
+        items.sort_by_key(|(key, _)| *key);
+        let global_view = DebugView::current();
+        let oit = self.oit.filter(|_| global_view == DebugView::Shaded);
+        let needs_debug_program = items
+            .iter()
+            .any(|(_, item)| item.debug_view.unwrap_or(global_view).replaces_program());
+        if needs_debug_program && self.debug_program.is_none() {
+            match debug_view_program() {
+                Ok(program) => self.debug_program = Some(program),
+                Err(e) => {
+                    error!(Self, "无法创建调试视图着色器: {}", e);
+                }
+            }
+        }
+        let view_projection = camera.view_projection();
+        let clipping = camera.clip_planes.iter().any(Option::is_some);
+        let mut current_program = u32::MAX;
+        let mut current_texture = u32::MAX;
+        let mut current_pass = None;
+        let mut current_receive = None;
+        let mut switches = 0;
+        for (_, item) in items {
+            if current_pass != Some(item.pass) {
+                match oit {
+                    Some((oit, _)) if current_pass == Some(RenderPass::Transparent) => {
+                        oit.composite();
+                        current_program = u32::MAX;
+                    }
+                    _ => {}
+                }
+                match oit {
+                    Some((oit, depth)) if item.pass == RenderPass::Transparent => oit.begin(depth),
+                    _ => set_pass_state(item.pass),
+                }
+                current_pass = Some(item.pass);
+                current_receive = None;
+            }
+            if writes_depth(item.pass) && current_receive != Some(item.receive_decals) {
+                set_decal_stencil(item.receive_decals);
+                current_receive = Some(item.receive_decals);
+            }
+            let view = item.debug_view.unwrap_or(global_view);
+            let program = match &self.debug_program {
+                Some(debug) if view.replaces_program() => debug,
+                _ => item.program,
+            };
+            if program.id() != current_program {
+                program.bind();
+                program.set("uViewProj", &view_projection);
+                program.set("uTexture", &0i32);
+                if clipping {
+                    ClipPlanes::apply(camera, Some(program));
+                }
+                current_program = program.id();
+                current_texture = u32::MAX;
+                switches += 1;
+            }
+            if item.texture != 0 && item.texture != current_texture {
+                unsafe {
+                    gl::ActiveTexture(gl::TEXTURE0);
+                    gl::BindTexture(gl::TEXTURE_2D, item.texture);
+                }
+                current_texture = item.texture;
+            }
+            program.set("uModel", &item.model);
+            if view.replaces_program() {
+                program.set("uMode", &view.mode());
+            } else if let Some(uniforms) = &item.uniforms {
+                uniforms(item.program);
+            }
+            // 半透明通道绘制到顺序无关透明度目标时保持其混合状态
+            let in_oit = oit.is_some() && item.pass == RenderPass::Transparent;
+            set_debug_state(view, in_oit, true);
+            item.mesh.draw();
+            set_debug_state(view, in_oit, false);
+            if view == DebugView::Overdraw && !in_oit {
+                set_pass_state(item.pass);
+            }
+        }
+        if let (Some((oit, _)), Some(RenderPass::Transparent)) = (oit, current_pass) {
+            oit.composite();
+        }
+        if current_pass.is_some() {
+            set_pass_state(RenderPass::Opaque);
+            Renderer::set_stencil(None);
+            if clipping {
+                ClipPlanes::disable();
+            }
+        }
+        switches
+    }
+}
+
+/// 设置或撤销调试视图所需的光栅化状态，过度绘制视图撤销后需重新设置通道状态
+fn set_debug_state(view: DebugView, in_oit: bool, enable: bool) {
+    unsafe {
+        match view {
+            DebugView::Wireframe => {
+                let mode = if enable { gl::LINE } else { gl::FILL };
+                gl::PolygonMode(gl::FRONT_AND_BACK, mode);
+            }
+            DebugView::Overdraw if enable && !in_oit => {
+                gl::Enable(gl::BLEND);
+                gl::BlendFunc(gl::ONE, gl::ONE);
+                gl::DepthMask(gl::FALSE);
+                gl::DepthFunc(gl::ALWAYS);
+            }
+            _ => {}
+        }
+    }
+}
+
+fn writes_depth(pass: RenderPass) -> bool {
+    matches!(pass, RenderPass::Opaque | RenderPass::AlphaTest)
+}
+
+/// 设置模板写入，使表面以 [`DECAL_STENCIL_BIT`] 记录是否接收贴花
+fn set_decal_stencil(receive: bool) {
+    let reference = if receive { 0 } else { DECAL_STENCIL_BIT };
+    Renderer::set_stencil(Some(StencilState {
+        read_mask: DECAL_STENCIL_BIT,
+        write_mask: DECAL_STENCIL_BIT,
+        ..StencilState::write(reference)
+    }));
+}
+
+fn set_pass_state(pass: RenderPass) {
+    // 写入深度的通道随后由 set_decal_stencil 设置模板状态
+    if !writes_depth(pass) {
+        Renderer::set_stencil(None);
+    }
+    unsafe {
+        match pass {
+            RenderPass::Opaque | RenderPass::AlphaTest => {
+                gl::Disable(gl::BLEND);
+                gl::DepthMask(gl::TRUE);
+                gl::DepthFunc(gl::LESS);
+            }
+            RenderPass::Background => {
+                gl::Disable(gl::BLEND);
+                gl::DepthMask(gl::FALSE);
+                gl::DepthFunc(gl::LEQUAL);
+            }
+            RenderPass::Transparent | RenderPass::Overlay => {
+                gl::Enable(gl::BLEND);
+                gl::BlendFunc(gl::SRC_ALPHA, gl::ONE_MINUS_SRC_ALPHA);
+                gl::DepthMask(gl::FALSE);
+                gl::DepthFunc(gl::LESS);
+            }
+        }
+    }
+}
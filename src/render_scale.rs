@@ -0,0 +1,230 @@
+use std::collections::VecDeque;
+
+use crate::error::Result;
+use crate::{error, App, AttachmentFormat, Framebuffer, GlObjectKind, GlObjects};
+
+/// 同时在途的GPU计时查询数量，查询结果通常延迟两到三帧返回
+const QUERY_COUNT: usize = 4;
+
+/// 放大到窗口时的采样方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum UpscaleFilter {
+    /// 最近点采样，适合像素风格画面
+    Nearest,
+    /// 双线性采样
+    #[default]
+    Linear,
+}
+
+/// 渲染分辨率缩放
+///
+/// 将三维场景绘制到分辨率为窗口的 [`RenderScale::scale`] 倍的内部渲染目标，再放大到窗口的默认帧缓冲，
+/// 界面在放大之后以窗口原生分辨率绘制。启用 [`RenderScale::dynamic`] 后，
+/// 根据最近若干帧的GPU耗时(`GL_TIME_ELAPSED`查询)自动调整缩放比例，使GPU耗时接近 [`RenderScale::target_ms`]
+///
+/// # 示例
+///
+/// ```no_run
+/// use gle::*;
+///
+/// fn render_frame(scale: &mut RenderScale, camera: &mut Camera) {
+///     let (width, height) = scale.begin();
+///     camera.set_viewport_size(width as i32, height as i32);
+///     // ... 绘制三维场景
+///     scale.end();
+///     // ... 以窗口分辨率绘制界面
+/// }
+/// ```
+///
+/// # 注解
+///
+/// 为避免频繁重新创建渲染目标，自动调整的缩放比例取整到`0.05`的倍数，且两次调整之间至少间隔
+/// [`RenderScale::cooldown`] 帧。该类型只能在渲染线程中创建、使用与释放
+pub struct RenderScale {
+    /// 内部分辨率相对窗口的比例，范围`[0.1, 2.0]`，大于1时为超采样(默认值为1)
+    pub scale: f32,
+    /// 是否根据GPU耗时自动调整 [`RenderScale::scale`](默认值为`false`)
+    pub dynamic: bool,
+    /// 自动调整的目标GPU耗时，单位为毫秒(默认值为16)
+    pub target_ms: f32,
+    /// 自动调整的最小比例(默认值为0.5)
+    pub min_scale: f32,
+    /// 自动调整的最大比例(默认值为1)
+    pub max_scale: f32,
+    /// 两次自动调整之间的最少帧数(默认值为30)
+    pub cooldown: u32,
+    /// 放大时的采样方式
+    pub filter: UpscaleFilter,
+    target: Framebuffer,
+    free: Vec<u32>,
+    pending: VecDeque<u32>,
+    timing: bool,
+    gpu_ms: Option<f32>,
+    frames_since_change: u32,
+    window: (u32, u32),
+}
+
+impl RenderScale {
+    /// 创建渲染分辨率缩放
+    ///
+    /// # 返回值
+    /// 成功时返回渲染分辨率缩放，帧缓冲不完整时返回错误
+    pub fn new() -> Result<Self> {
+        let target = Framebuffer::new(
+            1,
+            1,
+            &[AttachmentFormat::Rgba16F],
+            Some(AttachmentFormat::Depth24Stencil8),
+        )?;
+        let mut free = vec![0; QUERY_COUNT];
+        unsafe { gl::GenQueries(QUERY_COUNT as i32, free.as_mut_ptr()) };
+        for &query in &free {
+            GlObjects::track(GlObjectKind::Query, query);
+        }
+        Ok(Self {
+            scale: 1.0,
+            dynamic: false,
+            target_ms: 16.0,
+            min_scale: 0.5,
+            max_scale: 1.0,
+            cooldown: 30,
+            filter: UpscaleFilter::default(),
+            target,
+            free,
+            pending: VecDeque::new(),
+            timing: false,
+            gpu_ms: None,
+            frames_since_change: 0,
+            window: (1, 1),
+        })
+    }
+
+    /// 开始绘制三维场景，绑定内部渲染目标并设置视口
+    ///
+    /// # 返回值
+    /// 返回内部渲染目标的大小，摄像机的宽高比应据此更新
+    ///
+    /// # 注解
+    ///
+    /// 不清除渲染目标的内容，调用者应在绘制前自行清除
+    pub fn begin(&mut self) -> (u32, u32) {
+        self.collect_timings();
+        let (w, h) = App::window_size();
+        self.window = (w.max(1) as u32, h.max(1) as u32);
+        let (width, height) = self.internal_size();
+        if let Err(e) = self.target.resize(width, height) {
+            error!(Self, "无法调整内部渲染目标的大小: {}", e);
+        }
+        self.target.bind();
+        if let Some(query) = self.free.pop() {
+            unsafe { gl::BeginQuery(gl::TIME_ELAPSED, query) };
+            self.pending.push_back(query);
+            self.timing = true;
+        }
+        (self.target.width(), self.target.height())
+    }
+
+    /// 结束绘制三维场景，将内部渲染目标放大到窗口的默认帧缓冲，之后的绘制以窗口分辨率进行
+    pub fn end(&mut self) {
+        if self.timing {
+            unsafe { gl::EndQuery(gl::TIME_ELAPSED) };
+            self.timing = false;
+        }
+        let (w, h) = self.window;
+        let filter = match self.filter {
+            UpscaleFilter::Nearest => gl::NEAREST,
+            UpscaleFilter::Linear => gl::LINEAR,
+        };
+        unsafe {
+            gl::BindFramebuffer(gl::READ_FRAMEBUFFER, self.target.id());
+            gl::BindFramebuffer(gl::DRAW_FRAMEBUFFER, 0);
+            gl::BlitFramebuffer(
+                0,
+                0,
+                self.target.width() as i32,
+                self.target.height() as i32,
+                0,
+                0,
+                w as i32,
+                h as i32,
+                gl::COLOR_BUFFER_BIT,
+                filter,
+            );
+        }
+        Framebuffer::bind_default(w, h);
+    }
+
+    /// 获取内部渲染目标，可在 [`RenderScale::end`] 之前作为后处理的输入
+    pub fn framebuffer(&self) -> &Framebuffer {
+        &self.target
+    }
+
+    /// 获取当前的内部分辨率
+    pub fn internal_size(&self) -> (u32, u32) {
+        let scale = self.scale.clamp(0.1, 2.0);
+        let (w, h) = self.window;
+        let width = ((w as f32 * scale).round() as u32).max(1);
+        let height = ((h as f32 * scale).round() as u32).max(1);
+        (width, height)
+    }
+
+    /// 获取平滑后的GPU耗时，单位为毫秒
+    ///
+    /// # 返回值
+    /// 尚无查询结果时返回`None`
+    pub fn gpu_ms(&self) -> Option<f32> {
+        self.gpu_ms
+    }
+
+    /// 读取已完成的计时查询，并在启用自动调整时更新缩放比例
+    fn collect_timings(&mut self) {
+        while let Some(&query) = self.pending.front() {
+            let mut available = 0;
+            unsafe { gl::GetQueryObjectiv(query, gl::QUERY_RESULT_AVAILABLE, &mut available) };
+            if available == 0 {
+                break;
+            }
+            let mut elapsed = 0u64;
+            unsafe { gl::GetQueryObjectui64v(query, gl::QUERY_RESULT, &mut elapsed) };
+            self.pending.pop_front();
+            self.free.push(query);
+            let ms = elapsed as f32 / 1_000_000.0;
+            self.gpu_ms = Some(self.gpu_ms.map_or(ms, |avg| avg + (ms - avg) * 0.1));
+        }
+
+        self.frames_since_change = self.frames_since_change.saturating_add(1);
+        let Some(gpu_ms) = self.gpu_ms.filter(|_| self.dynamic) else {
+            return;
+        };
+        if self.frames_since_change < self.cooldown || gpu_ms <= 0.0 {
+            return;
+        }
+        // 耗时与像素数量近似成正比，超出目标或低于目标的85%时按面积比例调整
+        let ratio = self.target_ms / gpu_ms;
+        if !(1.0..=1.0 / 0.85).contains(&ratio) {
+            let min = self.min_scale.clamp(0.1, 2.0);
+            let max = self.max_scale.clamp(min, 2.0);
+            let scale = (self.scale * ratio.sqrt()).clamp(min, max);
+            let scale = ((scale * 20.0).round() / 20.0).clamp(min, max);
+            if scale != self.scale {
+                self.scale = scale;
+                self.frames_since_change = 0;
+                // 旧分辨率下的计时不再代表当前负载
+                self.gpu_ms = None;
+            }
+        }
+    }
+}
+
+impl Drop for RenderScale {
+    fn drop(&mut self) {
+        if self.timing {
+            unsafe { gl::EndQuery(gl::TIME_ELAPSED) };
+        }
+        let queries: Vec<u32> = self.free.drain(..).chain(self.pending.drain(..)).collect();
+        for &query in &queries {
+            GlObjects::untrack(GlObjectKind::Query, query);
+        }
+        unsafe { gl::DeleteQueries(queries.len() as i32, queries.as_ptr()) };
+    }
+}
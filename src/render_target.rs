@@ -0,0 +1,206 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use lazy_static::lazy_static;
+
+use crate::error::Result;
+use crate::{error, App, AttachmentFormat, Camera, Framebuffer};
+
+lazy_static! {
+    static ref TARGETS: Mutex<Targets> = Mutex::new(Targets::default());
+}
+
+#[derive(Default)]
+struct Targets {
+    next: u32,
+    entries: HashMap<u32, Target>,
+    /// 已销毁但尚未在渲染线程中释放的帧缓冲
+    garbage: Vec<Framebuffer>,
+}
+
+struct Target {
+    width: u32,
+    height: u32,
+    format: AttachmentFormat,
+    framebuffer: Option<Framebuffer>,
+}
+
+impl Target {
+    /// 按需创建或调整帧缓冲，只能在渲染线程中调用
+    fn framebuffer(&mut self) -> Result<&mut Framebuffer> {
+        let (width, height) = (self.width, self.height);
+        if let Some(framebuffer) = &mut self.framebuffer {
+            framebuffer.resize(width, height)?;
+        } else {
+            let depth = Some(AttachmentFormat::Depth24Stencil8);
+            self.framebuffer = Some(Framebuffer::new(width, height, &[self.format], depth)?);
+        }
+        Ok(self.framebuffer.as_mut().unwrap())
+    }
+}
+
+/// 离屏渲染目标句柄
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct RenderTargetHandle(u32);
+
+/// 摄像机的渲染目标
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CameraTarget {
+    /// 窗口的默认帧缓冲
+    #[default]
+    Window,
+    /// 由 [`RenderTargets`] 管理的离屏帧缓冲
+    Texture(RenderTargetHandle),
+}
+
+/// 离屏渲染目标
+///
+/// 为 [`Camera::target`] 提供可被采样的离屏帧缓冲，用于小地图、监控画面与传送门等。
+/// 渲染目标的颜色纹理可以作为材质纹理绑定，也可以通过 [`Widget::image`](crate::Widget::image)
+/// 在界面中显示，或以 [`Sprite`](crate::Sprite) 绘制。帧缓冲纹理以左下角为原点，与精灵坐标一致，
+/// 无需翻转
+///
+/// # 示例
+///
+/// ```no_run
+/// use gle::{*, math::*};
+///
+/// let minimap = RenderTargets::create(256, 256);
+/// let mut camera = Camera::orthographic(50.0, 0.1, 200.0);
+/// camera.position = Vec3::new(0.0, 100.0, 0.0);
+/// camera.look_at(Vec3::ZERO);
+/// camera.target = CameraTarget::Texture(minimap);
+///
+/// // 渲染线程中
+/// RenderTargets::bind(&mut camera);
+/// // ... 以 camera 绘制场景
+/// let texture = RenderTargets::texture(minimap).unwrap();
+/// ```
+///
+/// # 注解
+///
+/// 创建、调整与销毁可在任意线程中调用，帧缓冲在渲染线程首次绑定或获取纹理时才真正创建，
+/// 销毁的帧缓冲同样延迟到渲染线程中释放
+pub struct RenderTargets;
+
+impl RenderTargets {
+    /// 创建`RGBA8`格式的渲染目标
+    ///
+    /// # 参数
+    /// + `width` - 宽度
+    /// + `height` - 高度
+    pub fn create(width: u32, height: u32) -> RenderTargetHandle {
+        Self::create_with_format(width, height, AttachmentFormat::Rgba8)
+    }
+
+    /// 创建指定颜色格式的渲染目标
+    ///
+    /// # 参数
+    /// + `width` - 宽度
+    /// + `height` - 高度
+    /// + `format` - 颜色附件格式，需要色调映射的HDR画面可使用 [`AttachmentFormat::Rgba16F`]
+    pub fn create_with_format(
+        width: u32,
+        height: u32,
+        format: AttachmentFormat,
+    ) -> RenderTargetHandle {
+        let mut targets = TARGETS.lock().unwrap();
+        targets.next += 1;
+        let id = targets.next;
+        let target = Target {
+            width: width.max(1),
+            height: height.max(1),
+            format,
+            framebuffer: None,
+        };
+        targets.entries.insert(id, target);
+        RenderTargetHandle(id)
+    }
+
+    /// 调整渲染目标的大小，帧缓冲在下次绑定时重新创建
+    ///
+    /// # 返回值
+    /// 渲染目标不存在时返回`false`
+    pub fn resize(handle: RenderTargetHandle, width: u32, height: u32) -> bool {
+        let mut targets = TARGETS.lock().unwrap();
+        let Some(target) = targets.entries.get_mut(&handle.0) else {
+            return false;
+        };
+        target.width = width.max(1);
+        target.height = height.max(1);
+        true
+    }
+
+    /// 销毁渲染目标，之后以该句柄为目标的摄像机将绘制到窗口
+    pub fn destroy(handle: RenderTargetHandle) {
+        let mut targets = TARGETS.lock().unwrap();
+        if let Some(framebuffer) = targets.entries.remove(&handle.0).and_then(|t| t.framebuffer) {
+            targets.garbage.push(framebuffer);
+        }
+    }
+
+    /// 获取渲染目标的大小
+    ///
+    /// # 返回值
+    /// 渲染目标不存在时返回`None`
+    pub fn size(handle: RenderTargetHandle) -> Option<(u32, u32)> {
+        let targets = TARGETS.lock().unwrap();
+        targets.entries.get(&handle.0).map(|t| (t.width, t.height))
+    }
+
+    /// 获取渲染目标的颜色纹理ID，只能在渲染线程中调用
+    ///
+    /// # 返回值
+    /// 渲染目标不存在或帧缓冲创建失败时返回`None`
+    pub fn texture(handle: RenderTargetHandle) -> Option<u32> {
+        let mut targets = TARGETS.lock().unwrap();
+        targets.garbage.clear();
+        let target = targets.entries.get_mut(&handle.0)?;
+        match target.framebuffer() {
+            Ok(framebuffer) => framebuffer.color(0).map(|texture| texture.id()),
+            Err(e) => {
+                error!("RenderTargets", "无法创建渲染目标: {}", e);
+                None
+            }
+        }
+    }
+
+    /// 绑定摄像机的渲染目标并将视口设置为 [`Camera::viewport`] 对应的区域，只能在渲染线程中调用
+    ///
+    /// # 参数
+    /// + `camera` - 摄像机，其宽高比被更新为视口的宽高比
+    ///
+    /// # 返回值
+    /// 返回视口的像素矩形`(x, y, 宽度, 高度)`。渲染目标不存在或帧缓冲创建失败时绑定窗口的默认帧缓冲
+    ///
+    /// # 注解
+    ///
+    /// 不清除渲染目标的内容，调用者应在绘制前自行清除
+    pub fn bind(camera: &mut Camera) -> (i32, i32, i32, i32) {
+        let mut targets = TARGETS.lock().unwrap();
+        targets.garbage.clear();
+        let mut size = None;
+        if let CameraTarget::Texture(handle) = camera.target {
+            if let Some(target) = targets.entries.get_mut(&handle.0) {
+                match target.framebuffer() {
+                    Ok(framebuffer) => {
+                        framebuffer.bind();
+                        size = Some((framebuffer.width() as i32, framebuffer.height() as i32));
+                    }
+                    Err(e) => {
+                        error!("RenderTargets", "无法创建渲染目标: {}", e);
+                    }
+                }
+            }
+        }
+        let (width, height) = size.unwrap_or_else(|| {
+            let (width, height) = App::window_size();
+            Framebuffer::bind_default(width.max(1) as u32, height.max(1) as u32);
+            (width.max(1), height.max(1))
+        });
+        let (x, y, w, h) = camera.pixel_viewport(width, height);
+        unsafe { gl::Viewport(x, y, w, h) };
+        camera.set_viewport_size(w, h);
+        (x, y, w, h)
+    }
+}
@@ -0,0 +1,660 @@
+use std::sync::Mutex;
+
+use lazy_static::lazy_static;
+
+use crate::MAX_CLIP_PLANES;
+
+lazy_static! {
+    static ref STATE: Mutex<StateCache> = Mutex::new(StateCache::default());
+}
+
+/// 渲染状态缓存，记录最近一次提交给 OpenGL 的状态以跳过重复的状态切换
+#[derive(Default)]
+struct StateCache {
+    /// 已提交的裁剪矩形，外层为`None`表示状态未知
+    scissor: Option<Option<ScissorRect>>,
+    /// [`Renderer::with_scissor`] 的嵌套栈
+    scissor_stack: Vec<Option<ScissorRect>>,
+    /// 已提交的模板状态，外层为`None`表示状态未知
+    stencil: Option<Option<StencilState>>,
+    /// 已提交的深度偏移，外层为`None`表示状态未知
+    depth_bias: Option<Option<DepthBias>>,
+    /// 已启用的裁剪距离掩码，为`None`表示状态未知
+    clip_distances: Option<u32>,
+}
+
+impl StateCache {
+    fn apply_scissor(&mut self, rect: Option<ScissorRect>) {
+        if self.scissor == Some(rect) {
+            return;
+        }
+        unsafe {
+            match rect {
+                Some(r) => {
+                    if !matches!(self.scissor, Some(Some(_))) {
+                        gl::Enable(gl::SCISSOR_TEST);
+                    }
+                    gl::Scissor(r.x, r.y, r.width, r.height);
+                }
+                None => gl::Disable(gl::SCISSOR_TEST),
+            }
+        }
+        self.scissor = Some(rect);
+    }
+
+    fn apply_stencil(&mut self, stencil: Option<StencilState>) {
+        if self.stencil == Some(stencil) {
+            return;
+        }
+        unsafe {
+            match stencil {
+                Some(s) => {
+                    if !matches!(self.stencil, Some(Some(_))) {
+                        gl::Enable(gl::STENCIL_TEST);
+                    }
+                    gl::StencilFunc(s.func.to_gl(), s.reference as i32, s.read_mask);
+                    gl::StencilOp(s.fail.to_gl(), s.depth_fail.to_gl(), s.pass.to_gl());
+                    gl::StencilMask(s.write_mask);
+                }
+                None => {
+                    // 关闭时恢复写入掩码，使 glClear 能够清除整个模板缓冲
+                    gl::Disable(gl::STENCIL_TEST);
+                    gl::StencilMask(0xFF);
+                }
+            }
+        }
+        self.stencil = Some(stencil);
+    }
+
+    fn apply_depth_bias(&mut self, bias: Option<DepthBias>) {
+        if self.depth_bias == Some(bias) {
+            return;
+        }
+        unsafe {
+            match bias {
+                Some(b) => {
+                    if !matches!(self.depth_bias, Some(Some(_))) {
+                        gl::Enable(gl::POLYGON_OFFSET_FILL);
+                    }
+                    gl::PolygonOffset(b.slope, b.constant);
+                }
+                None => gl::Disable(gl::POLYGON_OFFSET_FILL),
+            }
+        }
+        self.depth_bias = Some(bias);
+    }
+
+    fn apply_clip_distances(&mut self, mask: u32) {
+        let previous = self.clip_distances;
+        if previous == Some(mask) {
+            return;
+        }
+        for i in 0..MAX_CLIP_PLANES as u32 {
+            let enabled = mask & (1 << i) != 0;
+            if previous.is_some_and(|p| (p & (1 << i) != 0) == enabled) {
+                continue;
+            }
+            unsafe {
+                if enabled {
+                    gl::Enable(gl::CLIP_DISTANCE0 + i);
+                } else {
+                    gl::Disable(gl::CLIP_DISTANCE0 + i);
+                }
+            }
+        }
+        self.clip_distances = Some(mask);
+    }
+}
+
+/// 比较函数
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum CompareFunc {
+    /// 总是不通过
+    Never,
+    /// 小于时通过
+    Less,
+    /// 等于时通过
+    Equal,
+    /// 小于等于时通过
+    LessEqual,
+    /// 大于时通过
+    Greater,
+    /// 不等于时通过
+    NotEqual,
+    /// 大于等于时通过
+    GreaterEqual,
+    /// 总是通过
+    #[default]
+    Always,
+}
+
+impl CompareFunc {
+    fn to_gl(self) -> u32 {
+        match self {
+            CompareFunc::Never => gl::NEVER,
+            CompareFunc::Less => gl::LESS,
+            CompareFunc::Equal => gl::EQUAL,
+            CompareFunc::LessEqual => gl::LEQUAL,
+            CompareFunc::Greater => gl::GREATER,
+            CompareFunc::NotEqual => gl::NOTEQUAL,
+            CompareFunc::GreaterEqual => gl::GEQUAL,
+            CompareFunc::Always => gl::ALWAYS,
+        }
+    }
+}
+
+/// 模板操作
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum StencilOp {
+    /// 保持原值
+    #[default]
+    Keep,
+    /// 置零
+    Zero,
+    /// 替换为参考值
+    Replace,
+    /// 加一，达到最大值时保持不变
+    Increment,
+    /// 加一，溢出时回绕为零
+    IncrementWrap,
+    /// 减一，为零时保持不变
+    Decrement,
+    /// 减一，为零时回绕为最大值
+    DecrementWrap,
+    /// 按位取反
+    Invert,
+}
+
+impl StencilOp {
+    fn to_gl(self) -> u32 {
+        match self {
+            StencilOp::Keep => gl::KEEP,
+            StencilOp::Zero => gl::ZERO,
+            StencilOp::Replace => gl::REPLACE,
+            StencilOp::Increment => gl::INCR,
+            StencilOp::IncrementWrap => gl::INCR_WRAP,
+            StencilOp::Decrement => gl::DECR,
+            StencilOp::DecrementWrap => gl::DECR_WRAP,
+            StencilOp::Invert => gl::INVERT,
+        }
+    }
+}
+
+/// 模板测试状态
+///
+/// 以`(参考值 & read_mask) func (模板值 & read_mask)`进行比较，根据模板测试与深度测试的结果选择操作，
+/// 只修改`write_mask`中的位
+///
+/// # 示例
+///
+/// ```
+/// use gle::*;
+///
+/// // 只在模板值最高位为 0 的像素上绘制
+/// let state = StencilState {
+///     read_mask: 0x80,
+///     ..StencilState::test(CompareFunc::Equal, 0)
+/// };
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct StencilState {
+    /// 比较函数
+    pub func: CompareFunc,
+    /// 参考值
+    pub reference: u32,
+    /// 比较前与参考值和模板值按位与的掩码
+    pub read_mask: u32,
+    /// 允许写入的位
+    pub write_mask: u32,
+    /// 模板测试失败时的操作
+    pub fail: StencilOp,
+    /// 模板测试通过、深度测试失败时的操作
+    pub depth_fail: StencilOp,
+    /// 模板测试与深度测试均通过时的操作
+    pub pass: StencilOp,
+}
+
+impl Default for StencilState {
+    fn default() -> Self {
+        Self {
+            func: CompareFunc::Always,
+            reference: 0,
+            read_mask: 0xFF,
+            write_mask: 0xFF,
+            fail: StencilOp::Keep,
+            depth_fail: StencilOp::Keep,
+            pass: StencilOp::Keep,
+        }
+    }
+}
+
+impl StencilState {
+    /// 在绘制通过深度测试的像素上写入参考值
+    pub fn write(reference: u32) -> Self {
+        Self {
+            reference,
+            pass: StencilOp::Replace,
+            ..Default::default()
+        }
+    }
+
+    /// 只进行模板测试，不修改模板值
+    ///
+    /// # 参数
+    /// + `func` - 比较函数
+    /// + `reference` - 参考值
+    pub fn test(func: CompareFunc, reference: u32) -> Self {
+        Self {
+            func,
+            reference,
+            write_mask: 0,
+            ..Default::default()
+        }
+    }
+
+    /// 只在模板值等于参考值的像素上绘制
+    pub fn equal(reference: u32) -> Self {
+        Self::test(CompareFunc::Equal, reference)
+    }
+}
+
+/// 深度偏移
+///
+/// 光栅化时为填充的三角形加上`slope * 最大深度斜率 + constant * 最小可分辨深度`的深度偏移，
+/// 正值远离摄像机，负值靠近摄像机
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DepthBias {
+    /// 以最小可分辨深度为单位的常量偏移
+    pub constant: f32,
+    /// 与三角形深度斜率成正比的偏移
+    pub slope: f32,
+}
+
+impl DepthBias {
+    /// 阴影贴图的默认偏移，将投射阴影的表面推远以消除阴影痤疮
+    pub const SHADOW: DepthBias = DepthBias { constant: 4.0, slope: 1.5 };
+
+    /// 共面叠加层的默认偏移，将贴花网格、选区高亮等与表面共面的几何体拉近以避免深度冲突
+    pub const OVERLAY: DepthBias = DepthBias { constant: -1.0, slope: -1.0 };
+
+    /// 创建深度偏移
+    ///
+    /// # 参数
+    /// + `constant` - 常量偏移
+    /// + `slope` - 斜率偏移
+    pub fn new(constant: f32, slope: f32) -> Self {
+        Self { constant, slope }
+    }
+}
+
+/// 裁剪矩形，以渲染目标左下角为原点，单位为像素
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct ScissorRect {
+    /// 左下角横坐标
+    pub x: i32,
+    /// 左下角纵坐标
+    pub y: i32,
+    /// 宽度
+    pub width: i32,
+    /// 高度
+    pub height: i32,
+}
+
+impl ScissorRect {
+    /// 创建裁剪矩形，负的宽高视为零
+    pub fn new(x: i32, y: i32, width: i32, height: i32) -> Self {
+        Self {
+            x,
+            y,
+            width: width.max(0),
+            height: height.max(0),
+        }
+    }
+
+    /// 计算两个矩形的交集，不相交时返回大小为零的矩形
+    pub fn intersect(&self, other: &ScissorRect) -> ScissorRect {
+        let x = self.x.max(other.x);
+        let y = self.y.max(other.y);
+        let right = (self.x + self.width).min(other.x + other.width);
+        let top = (self.y + self.height).min(other.y + other.height);
+        ScissorRect::new(x, y, right - x, top - y)
+    }
+
+    /// 判断矩形是否为空
+    pub fn is_empty(&self) -> bool {
+        self.width <= 0 || self.height <= 0
+    }
+}
+
+impl From<(i32, i32, i32, i32)> for ScissorRect {
+    fn from((x, y, width, height): (i32, i32, i32, i32)) -> Self {
+        Self::new(x, y, width, height)
+    }
+}
+
+/// 渲染状态
+///
+/// 通过状态缓存设置 OpenGL 的裁剪矩形、模板、深度偏移与裁剪距离状态，与缓存相同的设置不会产生 OpenGL 调用。
+/// 作用域形式的接口在闭包返回后恢复之前的状态，可以安全地嵌套
+///
+/// # 示例
+///
+/// ```no_run
+/// use gle::*;
+///
+/// // 只在窗口左半部分绘制
+/// Renderer::with_scissor((0, 0, 640, 720), || {
+///     // 嵌套的裁剪矩形与外层取交集
+///     Renderer::with_scissor((320, 0, 640, 360), || {
+///         // 只在 (320, 0) - (640, 360) 范围内绘制
+///     });
+/// });
+/// ```
+///
+/// # 注解
+///
+/// 该类型的函数只能在渲染线程中调用。绕过该类型直接修改对应 OpenGL 状态的代码应在之后调用
+/// [`Renderer::invalidate`]，否则缓存可能与实际状态不一致
+pub struct Renderer;
+
+impl Renderer {
+    /// 在裁剪矩形内执行绘制
+    ///
+    /// # 参数
+    /// + `rect` - 裁剪矩形，与外层 [`Renderer::with_scissor`] 的矩形取交集
+    /// + `f` - 绘制函数
+    ///
+    /// # 返回值
+    /// 返回`f`的返回值
+    ///
+    /// # 注解
+    ///
+    /// `f`返回后恢复之前的裁剪状态，`f`中通过 [`Renderer::set_scissor`] 做出的修改同样被撤销
+    pub fn with_scissor<R>(rect: impl Into<ScissorRect>, f: impl FnOnce() -> R) -> R {
+        let rect = rect.into();
+        {
+            let mut state = STATE.lock().unwrap();
+            let previous = state.scissor.flatten();
+            state.scissor_stack.push(previous);
+            let rect = previous.map_or(rect, |outer| outer.intersect(&rect));
+            state.apply_scissor(Some(rect));
+        }
+        let result = f();
+        let mut state = STATE.lock().unwrap();
+        let previous = state.scissor_stack.pop().flatten();
+        state.apply_scissor(previous);
+        result
+    }
+
+    /// 设置裁剪矩形
+    ///
+    /// # 参数
+    /// + `rect` - 裁剪矩形，为`None`时关闭裁剪测试
+    pub fn set_scissor(rect: Option<ScissorRect>) {
+        STATE.lock().unwrap().apply_scissor(rect);
+    }
+
+    /// 获取当前的裁剪矩形
+    ///
+    /// # 返回值
+    /// 裁剪测试关闭或状态未知时返回`None`
+    pub fn scissor() -> Option<ScissorRect> {
+        STATE.lock().unwrap().scissor.flatten()
+    }
+
+    /// 在模板状态下执行绘制
+    ///
+    /// # 参数
+    /// + `stencil` - 模板状态，为`None`时关闭模板测试
+    /// + `f` - 绘制函数
+    ///
+    /// # 返回值
+    /// 返回`f`的返回值
+    ///
+    /// # 注解
+    ///
+    /// `f`返回后恢复之前的模板状态
+    pub fn with_stencil<R>(stencil: Option<StencilState>, f: impl FnOnce() -> R) -> R {
+        let previous = Self::stencil();
+        Self::set_stencil(stencil);
+        let result = f();
+        Self::set_stencil(previous);
+        result
+    }
+
+    /// 设置模板状态
+    ///
+    /// # 参数
+    /// + `stencil` - 模板状态，为`None`时关闭模板测试并恢复写入掩码
+    ///
+    /// # 注解
+    ///
+    /// 绘制目标需要带模板的深度附件([`AttachmentFormat::Depth24Stencil8`](crate::AttachmentFormat::Depth24Stencil8))
+    pub fn set_stencil(stencil: Option<StencilState>) {
+        STATE.lock().unwrap().apply_stencil(stencil);
+    }
+
+    /// 获取当前的模板状态
+    ///
+    /// # 返回值
+    /// 模板测试关闭或状态未知时返回`None`
+    pub fn stencil() -> Option<StencilState> {
+        STATE.lock().unwrap().stencil.flatten()
+    }
+
+    /// 将当前帧缓冲(裁剪矩形内)的模板值清除为`value`，不受当前写入掩码影响
+    pub fn clear_stencil(value: u32) {
+        let state = STATE.lock().unwrap();
+        unsafe {
+            gl::StencilMask(0xFF);
+            gl::ClearStencil(value as i32);
+            gl::Clear(gl::STENCIL_BUFFER_BIT);
+            if let Some(Some(stencil)) = state.stencil {
+                gl::StencilMask(stencil.write_mask);
+            }
+        }
+    }
+
+    /// 遮罩绘制
+    ///
+    /// 先以`mask`绘制遮罩形状(不写入颜色与深度)并在其覆盖的像素上写入`reference`，
+    /// 再只在这些像素上执行`content`，可用于瞄准镜、小地图圆形遮罩等
+    ///
+    /// # 参数
+    /// + `reference` - 遮罩写入的模板值，不应为零
+    /// + `mask` - 绘制遮罩形状
+    /// + `content` - 绘制遮罩内的内容
+    ///
+    /// # 返回值
+    /// 返回`content`的返回值
+    ///
+    /// # 注解
+    ///
+    /// 遮罩写入的模板值在返回后保留，需要重复使用同一参考值时应先调用 [`Renderer::clear_stencil`]
+    pub fn with_stencil_mask<R>(
+        reference: u32,
+        mask: impl FnOnce(),
+        content: impl FnOnce() -> R,
+    ) -> R {
+        let saved = WriteMasks::save();
+        unsafe {
+            gl::ColorMask(gl::FALSE, gl::FALSE, gl::FALSE, gl::FALSE);
+            gl::DepthMask(gl::FALSE);
+        }
+        let previous = Self::stencil();
+        Self::set_stencil(Some(StencilState::write(reference)));
+        mask();
+        saved.restore();
+        Self::set_stencil(Some(StencilState::equal(reference)));
+        let result = content();
+        Self::set_stencil(previous);
+        result
+    }
+
+    /// 传送门绘制
+    ///
+    /// 以模板值记录传送门的嵌套层级：先在`mask`覆盖且模板值为`level`的像素上加一并将深度重置为最远，
+    /// 再只在这些像素上执行`content`，最后将模板值减回`level`并写入传送门表面的深度。
+    /// `content`中可以以`level + 1`再次调用该函数绘制嵌套的传送门
+    ///
+    /// # 参数
+    /// + `level` - 当前层级，最外层为0，调用前模板缓冲应已清除为0
+    /// + `mask` - 绘制传送门表面，会被调用三次，应使用外层摄像机的矩阵
+    /// + `content` - 以传送门另一侧的摄像机绘制场景
+    ///
+    /// # 返回值
+    /// 返回`content`的返回值
+    ///
+    /// # 示例
+    ///
+    /// ```no_run
+    /// use gle::*;
+    ///
+    /// fn draw_portal(draw_surface: &dyn Fn(), draw_scene_through_portal: &dyn Fn()) {
+    ///     Renderer::clear_stencil(0);
+    ///     Renderer::with_portal(0, draw_surface, draw_scene_through_portal);
+    /// }
+    /// ```
+    pub fn with_portal<R>(level: u32, mut mask: impl FnMut(), content: impl FnOnce() -> R) -> R {
+        let saved = WriteMasks::save();
+        let previous = Self::stencil();
+        let inside = StencilState::equal(level + 1);
+        unsafe {
+            // 标记传送门可见的像素
+            gl::ColorMask(gl::FALSE, gl::FALSE, gl::FALSE, gl::FALSE);
+            gl::DepthMask(gl::FALSE);
+            Self::set_stencil(Some(StencilState {
+                pass: StencilOp::Increment,
+                write_mask: 0xFF,
+                ..StencilState::equal(level)
+            }));
+            mask();
+            // 将传送门内的深度重置为最远，使另一侧的场景不被传送门前的深度遮挡
+            Self::set_stencil(Some(inside));
+            gl::DepthMask(gl::TRUE);
+            gl::DepthFunc(gl::ALWAYS);
+            gl::DepthRange(1.0, 1.0);
+            mask();
+            gl::DepthRange(0.0, 1.0);
+            gl::DepthFunc(saved.depth_func);
+            saved.restore();
+        }
+        let result = content();
+        unsafe {
+            // 恢复层级并写入传送门表面的深度，使之后绘制的物体与传送门正确遮挡
+            gl::ColorMask(gl::FALSE, gl::FALSE, gl::FALSE, gl::FALSE);
+            gl::DepthMask(gl::TRUE);
+            gl::DepthFunc(gl::ALWAYS);
+            Self::set_stencil(Some(StencilState {
+                pass: StencilOp::Decrement,
+                write_mask: 0xFF,
+                ..inside
+            }));
+            mask();
+            gl::DepthFunc(saved.depth_func);
+            saved.restore();
+        }
+        Self::set_stencil(previous);
+        result
+    }
+
+    /// 在深度偏移下执行绘制
+    ///
+    /// # 参数
+    /// + `bias` - 深度偏移，为`None`时不偏移
+    /// + `f` - 绘制函数
+    ///
+    /// # 返回值
+    /// 返回`f`的返回值
+    ///
+    /// # 注解
+    ///
+    /// `f`返回后恢复之前的深度偏移
+    pub fn with_depth_bias<R>(bias: Option<DepthBias>, f: impl FnOnce() -> R) -> R {
+        let previous = Self::depth_bias();
+        Self::set_depth_bias(bias);
+        let result = f();
+        Self::set_depth_bias(previous);
+        result
+    }
+
+    /// 设置深度偏移
+    ///
+    /// # 参数
+    /// + `bias` - 深度偏移，为`None`时关闭`GL_POLYGON_OFFSET_FILL`
+    ///
+    /// # 注解
+    ///
+    /// 只作用于填充的三角形，线框与点不受影响
+    pub fn set_depth_bias(bias: Option<DepthBias>) {
+        STATE.lock().unwrap().apply_depth_bias(bias);
+    }
+
+    /// 获取当前的深度偏移
+    ///
+    /// # 返回值
+    /// 未设置偏移或状态未知时返回`None`
+    pub fn depth_bias() -> Option<DepthBias> {
+        STATE.lock().unwrap().depth_bias.flatten()
+    }
+
+    /// 设置启用的裁剪距离
+    ///
+    /// # 参数
+    /// + `mask` - 按位表示启用的`GL_CLIP_DISTANCEi`，只使用低 [`MAX_CLIP_PLANES`] 位
+    ///
+    /// # 注解
+    ///
+    /// 通常通过 [`ClipPlanes::apply`](crate::ClipPlanes::apply) 间接调用
+    pub fn set_clip_distances(mask: u32) {
+        let mask = mask & ((1 << MAX_CLIP_PLANES) - 1);
+        STATE.lock().unwrap().apply_clip_distances(mask);
+    }
+
+    /// 获取启用的裁剪距离掩码，状态未知时返回0
+    pub fn clip_distances() -> u32 {
+        STATE.lock().unwrap().clip_distances.unwrap_or(0)
+    }
+
+    /// 使状态缓存失效，下一次设置时总是提交给 OpenGL
+    pub fn invalidate() {
+        let mut state = STATE.lock().unwrap();
+        state.scissor = None;
+        state.stencil = None;
+        state.depth_bias = None;
+        state.clip_distances = None;
+    }
+}
+
+/// 保存的颜色、深度写入掩码与深度比较函数
+struct WriteMasks {
+    color: [u8; 4],
+    depth: u8,
+    depth_func: u32,
+}
+
+impl WriteMasks {
+    fn save() -> Self {
+        let mut color = [0; 4];
+        let mut depth = 0;
+        let mut depth_func = 0;
+        unsafe {
+            gl::GetBooleanv(gl::COLOR_WRITEMASK, color.as_mut_ptr());
+            gl::GetBooleanv(gl::DEPTH_WRITEMASK, &mut depth);
+            gl::GetIntegerv(gl::DEPTH_FUNC, &mut depth_func);
+        }
+        Self {
+            color,
+            depth,
+            depth_func: depth_func as u32,
+        }
+    }
+
+    fn restore(&self) {
+        let [r, g, b, a] = self.color;
+        unsafe {
+            gl::ColorMask(r, g, b, a);
+            gl::DepthMask(self.depth);
+        }
+    }
+}
@@ -0,0 +1,138 @@
+use std::fmt;
+use std::marker::PhantomData;
+
+use gom::Registry;
+
+use crate::error;
+
+/// 类型化的资源键
+///
+/// 将全局注册表中的资源ID与资源类型绑定，通过 [`Resources`] 访问时由编译器检查类型，
+/// 不会因ID写错或类型不匹配而在运行时静默地得到`None`
+///
+/// # 示例
+///
+/// ```
+/// use gle::*;
+///
+/// const SCORE: ResourceKey<u32> = ResourceKey::new(id!(SCORE));
+///
+/// Resources::insert(SCORE, 0);
+/// Resources::apply(SCORE, |score| *score += 10);
+/// assert_eq!(Resources::get(SCORE), Some(10));
+/// ```
+pub struct ResourceKey<T> {
+    id: &'static str,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T> ResourceKey<T> {
+    /// 由资源ID创建资源键
+    ///
+    /// # 参数
+    /// + `id` - 资源ID，通常由`id!`宏生成
+    pub const fn new(id: &'static str) -> Self {
+        Self {
+            id,
+            _marker: PhantomData,
+        }
+    }
+
+    /// 获取资源ID
+    pub const fn id(&self) -> &'static str {
+        self.id
+    }
+}
+
+impl<T> Clone for ResourceKey<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for ResourceKey<T> {}
+
+impl<T> fmt::Debug for ResourceKey<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "ResourceKey<{}>({})", std::any::type_name::<T>(), self.id)
+    }
+}
+
+/// 类型化的全局资源
+///
+/// 以 [`ResourceKey`] 访问全局注册表的静态接口，资源类型由键决定，闭包参数无需再标注类型
+///
+/// # 示例
+///
+/// ```no_run
+/// use gle::*;
+///
+/// Resources::apply(WINDOW_KEY, |w| w.set_title("Game"));
+/// let size = Resources::with(WINDOW_KEY, |w| w.get_size());
+/// ```
+///
+/// # 注解
+///
+/// 与直接调用`Registry`访问的是同一份数据，同一ID只应以一种类型访问
+pub struct Resources;
+
+impl Resources {
+    /// 注册资源，已存在的同ID资源将被替换
+    ///
+    /// # 参数
+    /// + `key` - 资源键
+    /// + `value` - 资源
+    ///
+    /// # 返回值
+    /// 注册成功时返回`true`，失败时记录错误日志并返回`false`
+    pub fn insert<T: 'static + Send + Sync>(key: ResourceKey<T>, value: T) -> bool {
+        if Registry::register(key.id, value).is_err() {
+            error!(Self, "无法注册资源 {}", key.id);
+            return false;
+        }
+        true
+    }
+
+    /// 判断资源是否存在
+    pub fn exists<T: 'static + Send + Sync>(key: ResourceKey<T>) -> bool {
+        Registry::<T>::exists(key.id)
+    }
+
+    /// 以只读方式访问资源
+    ///
+    /// # 参数
+    /// + `key` - 资源键
+    /// + `f` - 一个函数，它接受资源的引用
+    ///
+    /// # 返回值
+    /// 资源存在时返回`f`的返回值，否则返回`None`
+    pub fn with<T: 'static + Send + Sync, R>(
+        key: ResourceKey<T>,
+        f: impl FnOnce(&T) -> R,
+    ) -> Option<R> {
+        Registry::with(key.id, f)
+    }
+
+    /// 以可变方式访问资源
+    ///
+    /// # 参数
+    /// + `key` - 资源键
+    /// + `f` - 一个函数，它接受资源的可变引用
+    ///
+    /// # 返回值
+    /// 资源存在时返回`f`的返回值，否则返回`None`
+    pub fn apply<T: 'static + Send + Sync, R>(
+        key: ResourceKey<T>,
+        f: impl FnOnce(&mut T) -> R,
+    ) -> Option<R> {
+        Registry::apply(key.id, f)
+    }
+
+    /// 获取资源的副本
+    ///
+    /// # 返回值
+    /// 资源存在时返回其副本，否则返回`None`
+    pub fn get<T: 'static + Send + Sync + Clone>(key: ResourceKey<T>) -> Option<T> {
+        Self::with(key, T::clone)
+    }
+}
@@ -0,0 +1,245 @@
+use crate::{GlObjectKind, GlObjects};
+
+const TEXTURE_MAX_ANISOTROPY: u32 = 0x84FE;
+const MAX_TEXTURE_MAX_ANISOTROPY: u32 = 0x84FF;
+
+/// 纹理过滤方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Filter {
+    /// 最近邻采样，适合像素风格的贴图
+    Nearest,
+    /// 双线性插值
+    #[default]
+    Linear,
+}
+
+/// 纹理环绕方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Wrap {
+    /// 重复
+    #[default]
+    Repeat,
+    /// 镜像重复
+    MirroredRepeat,
+    /// 截取到边缘像素
+    ClampToEdge,
+    /// 超出范围时使用边框颜色
+    ClampToBorder,
+}
+
+impl Wrap {
+    fn gl(self) -> i32 {
+        (match self {
+            Wrap::Repeat => gl::REPEAT,
+            Wrap::MirroredRepeat => gl::MIRRORED_REPEAT,
+            Wrap::ClampToEdge => gl::CLAMP_TO_EDGE,
+            Wrap::ClampToBorder => gl::CLAMP_TO_BORDER,
+        }) as i32
+    }
+}
+
+/// 纹理采样设置
+///
+/// 可以通过 [`Texture2D::apply_settings`](crate::Texture2D::apply_settings) 写入纹理对象，
+/// 也可以创建独立的 [`Sampler`] 在绑定时覆盖纹理自身的设置
+///
+/// # 示例
+///
+/// ```
+/// use gle::*;
+///
+/// fn render_init() {
+///     let settings = TextureSettings {
+///         anisotropy: 8.0,
+///         ..Default::default()
+///     };
+///     let ground = Texture2D::load_with("assets/ground.png", true, &settings).unwrap();
+///     let sprites = Texture2D::load_with("assets/sprites.png", true, &TextureSettings::pixel_art())
+///         .unwrap();
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TextureSettings {
+    /// 缩小时的过滤方式
+    pub min_filter: Filter,
+    /// 放大时的过滤方式
+    pub mag_filter: Filter,
+    /// 多级渐远纹理之间的过滤方式，为`None`时不使用多级渐远纹理
+    pub mipmap_filter: Option<Filter>,
+    /// 水平方向的环绕方式
+    pub wrap_s: Wrap,
+    /// 竖直方向的环绕方式
+    pub wrap_t: Wrap,
+    /// 环绕方式为 [`Wrap::ClampToBorder`] 时的边框颜色
+    pub border_color: [f32; 4],
+    /// 多级渐远纹理的级别偏移，为负时更清晰，为正时更模糊
+    pub lod_bias: f32,
+    /// 各向异性过滤的最大采样数，为 1 时不启用，超出硬件上限时被截断
+    pub anisotropy: f32,
+    /// 应用到纹理时是否重新生成多级渐远纹理，对 [`Sampler`] 无效
+    pub generate_mipmaps: bool,
+}
+
+impl Default for TextureSettings {
+    /// 三线性过滤、重复环绕并生成多级渐远纹理
+    fn default() -> Self {
+        Self {
+            min_filter: Filter::Linear,
+            mag_filter: Filter::Linear,
+            mipmap_filter: Some(Filter::Linear),
+            wrap_s: Wrap::Repeat,
+            wrap_t: Wrap::Repeat,
+            border_color: [0.0; 4],
+            lod_bias: 0.0,
+            anisotropy: 1.0,
+            generate_mipmaps: true,
+        }
+    }
+}
+
+impl TextureSettings {
+    /// 最近邻过滤且不使用多级渐远纹理，适合像素风格的贴图
+    pub fn pixel_art() -> Self {
+        Self {
+            min_filter: Filter::Nearest,
+            mag_filter: Filter::Nearest,
+            mipmap_filter: None,
+            generate_mipmaps: false,
+            ..Default::default()
+        }
+    }
+
+    /// 双线性过滤、截取到边缘且不使用多级渐远纹理，适合界面与后处理用的纹理
+    pub fn clamped() -> Self {
+        Self {
+            mipmap_filter: None,
+            wrap_s: Wrap::ClampToEdge,
+            wrap_t: Wrap::ClampToEdge,
+            generate_mipmaps: false,
+            ..Default::default()
+        }
+    }
+
+    fn min_filter_gl(&self) -> i32 {
+        (match (self.min_filter, self.mipmap_filter) {
+            (Filter::Nearest, None) => gl::NEAREST,
+            (Filter::Linear, None) => gl::LINEAR,
+            (Filter::Nearest, Some(Filter::Nearest)) => gl::NEAREST_MIPMAP_NEAREST,
+            (Filter::Nearest, Some(Filter::Linear)) => gl::NEAREST_MIPMAP_LINEAR,
+            (Filter::Linear, Some(Filter::Nearest)) => gl::LINEAR_MIPMAP_NEAREST,
+            (Filter::Linear, Some(Filter::Linear)) => gl::LINEAR_MIPMAP_LINEAR,
+        }) as i32
+    }
+
+    fn mag_filter_gl(&self) -> i32 {
+        (match self.mag_filter {
+            Filter::Nearest => gl::NEAREST,
+            Filter::Linear => gl::LINEAR,
+        }) as i32
+    }
+
+    /// 以给定的参数设置函数写入除多级渐远纹理生成外的所有设置
+    fn apply(
+        &self,
+        set_i: impl Fn(u32, i32),
+        set_f: impl Fn(u32, f32),
+        set_fv: impl Fn(u32, &[f32; 4]),
+    ) {
+        set_i(gl::TEXTURE_MIN_FILTER, self.min_filter_gl());
+        set_i(gl::TEXTURE_MAG_FILTER, self.mag_filter_gl());
+        set_i(gl::TEXTURE_WRAP_S, self.wrap_s.gl());
+        set_i(gl::TEXTURE_WRAP_T, self.wrap_t.gl());
+        set_fv(gl::TEXTURE_BORDER_COLOR, &self.border_color);
+        set_f(gl::TEXTURE_LOD_BIAS, self.lod_bias);
+        set_f(TEXTURE_MAX_ANISOTROPY, self.anisotropy.clamp(1.0, max_anisotropy()));
+    }
+
+    /// 将设置写入当前绑定到`target`的纹理，只能在渲染线程中调用
+    pub(crate) fn apply_to_bound(&self, target: u32) {
+        self.apply(
+            |p, v| unsafe { gl::TexParameteri(target, p, v) },
+            |p, v| unsafe { gl::TexParameterf(target, p, v) },
+            |p, v| unsafe { gl::TexParameterfv(target, p, v.as_ptr()) },
+        );
+        if self.generate_mipmaps && self.mipmap_filter.is_some() {
+            unsafe { gl::GenerateMipmap(target) };
+        }
+    }
+}
+
+/// 获取硬件支持的最大各向异性采样数，不支持各向异性过滤时返回 1
+pub fn max_anisotropy() -> f32 {
+    let mut max = 1.0;
+    unsafe {
+        gl::GetFloatv(MAX_TEXTURE_MAX_ANISOTROPY, &mut max);
+        // 不支持时查询产生 GL_INVALID_ENUM，清除该错误
+        while gl::GetError() != gl::NO_ERROR {}
+    }
+    max.max(1.0)
+}
+
+/// 采样器对象
+///
+/// 绑定到纹理单元后，覆盖该单元上纹理自身的采样设置，同一纹理可以在不同的绘制中以不同方式采样
+///
+/// # 示例
+///
+/// ```
+/// use gle::*;
+///
+/// fn render_init() -> Sampler {
+///     Sampler::new(&TextureSettings::pixel_art())
+/// }
+///
+/// fn render_loop(texture: &Texture2D, sampler: &Sampler) {
+///     texture.bind(0);
+///     sampler.bind(0);
+///     // 绘制 ...
+///     Sampler::unbind(0);
+/// }
+/// ```
+///
+/// # 注解
+///
+/// 该类型的所有方法只能在渲染线程中调用
+#[derive(Debug)]
+pub struct Sampler {
+    id: u32,
+}
+
+impl Sampler {
+    /// 以指定设置创建采样器
+    pub fn new(settings: &TextureSettings) -> Self {
+        let mut id = 0;
+        unsafe { gl::GenSamplers(1, &mut id) };
+        GlObjects::track(GlObjectKind::Sampler, id);
+        settings.apply(
+            |p, v| unsafe { gl::SamplerParameteri(id, p, v) },
+            |p, v| unsafe { gl::SamplerParameterf(id, p, v) },
+            |p, v| unsafe { gl::SamplerParameterfv(id, p, v.as_ptr()) },
+        );
+        Self { id }
+    }
+
+    /// 获取 OpenGL 采样器对象ID
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    /// 将采样器绑定到指定纹理单元
+    pub fn bind(&self, unit: u32) {
+        unsafe { gl::BindSampler(unit, self.id) };
+    }
+
+    /// 解除指定纹理单元的采样器绑定，恢复使用纹理自身的设置
+    pub fn unbind(unit: u32) {
+        unsafe { gl::BindSampler(unit, 0) };
+    }
+}
+
+impl Drop for Sampler {
+    fn drop(&mut self) {
+        GlObjects::untrack(GlObjectKind::Sampler, self.id);
+        unsafe { gl::DeleteSamplers(1, &self.id) };
+    }
+}
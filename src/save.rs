@@ -0,0 +1,222 @@
+use std::collections::BTreeMap;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{channel, Receiver, TryRecvError};
+
+use flate2::read::ZlibDecoder;
+use flate2::write::ZlibEncoder;
+use flate2::Compression;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Error, Result};
+use crate::math::*;
+use crate::{debug, BlockRegistry, Chunk, JobPool, VoxelWorld};
+
+/// 存档格式的当前版本，格式发生不兼容变化时递增
+pub const SAVE_FORMAT_VERSION: u32 = 1;
+
+const SAVE_MAGIC: [u8; 4] = *b"GLEW";
+
+/// 世界存档
+///
+/// 包含体素区块、方块名称到ID的映射、实体与玩家状态，实体与玩家的类型由游戏定义，只需实现 serde 的序列化特征。
+/// 编码后的格式为 4 字节标识`GLEW`、小端序的 32 位格式版本，以及 zlib 压缩的 bincode 数据
+///
+/// # 示例
+///
+/// ```
+/// use gle::{*, math::*};
+/// use serde::{Deserialize, Serialize};
+///
+/// #[derive(Serialize, Deserialize)]
+/// struct Player {
+///     position: [f32; 3],
+///     health: f32,
+/// }
+///
+/// let mut world = VoxelWorld::new();
+/// world.set_block(IVec3::new(3, 4, 5), BlockId(1));
+/// let player = Player { position: [0.0; 3], health: 20.0 };
+/// let save = WorldSave::capture(&world, None, player, Vec::<()>::new());
+/// let bytes = save.encode().unwrap();
+///
+/// let loaded = WorldSave::<Player, ()>::decode(&bytes).unwrap();
+/// assert_eq!(loaded.restore_world().block(IVec3::new(3, 4, 5)), BlockId(1));
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorldSave<P, E> {
+    /// 方块名称到ID的映射，读档时交给 [`BlockRegistry::with_id_map`] 以保持方块ID稳定
+    pub block_ids: BTreeMap<String, u16>,
+    /// 区块坐标与区块
+    pub chunks: Vec<([i32; 3], Chunk)>,
+    /// 玩家状态
+    pub player: P,
+    /// 实体
+    pub entities: Vec<E>,
+}
+
+impl<P, E> WorldSave<P, E>
+where
+    P: Serialize + DeserializeOwned,
+    E: Serialize + DeserializeOwned,
+{
+    /// 以体素世界的当前状态创建存档
+    ///
+    /// # 参数
+    /// + `world` - 体素世界，非空的区块被复制到存档中
+    /// + `registry` - 方块注册表，用于保存方块ID映射
+    /// + `player` - 玩家状态
+    /// + `entities` - 实体
+    pub fn capture(
+        world: &VoxelWorld,
+        registry: Option<&BlockRegistry>,
+        player: P,
+        entities: Vec<E>,
+    ) -> Self {
+        let chunks = world
+            .chunks()
+            .filter(|(_, chunk)| !chunk.is_empty())
+            .map(|(coord, chunk)| (coord.to_array(), chunk.clone()))
+            .collect();
+        Self {
+            block_ids: registry.map(|r| r.id_map()).unwrap_or_default(),
+            chunks,
+            player,
+            entities,
+        }
+    }
+
+    /// 由存档中的区块创建体素世界，所有区块均被标记为需要生成网格
+    pub fn restore_world(&self) -> VoxelWorld {
+        let mut world = VoxelWorld::new();
+        for (coord, chunk) in &self.chunks {
+            world.insert_chunk(IVec3::from_array(*coord), chunk.clone());
+        }
+        world
+    }
+
+    /// 编码为压缩的二进制数据
+    pub fn encode(&self) -> Result<Vec<u8>> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(&SAVE_MAGIC);
+        bytes.extend_from_slice(&SAVE_FORMAT_VERSION.to_le_bytes());
+        let mut encoder = ZlibEncoder::new(bytes, Compression::default());
+        bincode::serialize_into(&mut encoder, self).map_err(|e| Error::Parse(e.to_string()))?;
+        Ok(encoder.finish()?)
+    }
+
+    /// 从二进制数据解码
+    ///
+    /// # 返回值
+    /// 成功时返回存档，数据标识不符、版本高于 [`SAVE_FORMAT_VERSION`] 或数据损坏时返回错误
+    pub fn decode(bytes: &[u8]) -> Result<Self> {
+        if bytes.len() < 8 || bytes[..4] != SAVE_MAGIC {
+            return Err(Error::Parse("不是有效的世界存档".to_string()));
+        }
+        let version = u32::from_le_bytes([bytes[4], bytes[5], bytes[6], bytes[7]]);
+        if version > SAVE_FORMAT_VERSION {
+            return Err(Error::Parse(format!(
+                "存档版本 {} 高于支持的版本 {}",
+                version, SAVE_FORMAT_VERSION
+            )));
+        }
+        let mut data = Vec::new();
+        ZlibDecoder::new(&bytes[8..]).read_to_end(&mut data)?;
+        bincode::deserialize(&data).map_err(|e| Error::Parse(e.to_string()))
+    }
+
+    /// 保存到文件
+    ///
+    /// # 注解
+    ///
+    /// 先写入同目录下的临时文件再替换目标文件，写入中途崩溃不会损坏已有存档
+    pub fn save<Q: AsRef<Path>>(&self, path: Q) -> Result<()> {
+        write_atomic(path.as_ref(), &self.encode()?)
+    }
+
+    /// 从文件加载
+    pub fn load<Q: AsRef<Path>>(path: Q) -> Result<Self> {
+        let path = path.as_ref();
+        let bytes = std::fs::read(path)?;
+        Self::decode(&bytes).map_err(|e| match e {
+            Error::Parse(msg) => Error::Parse(format!("{}: {}", path.display(), msg)),
+            e => e,
+        })
+    }
+}
+
+impl<P, E> WorldSave<P, E>
+where
+    P: Serialize + DeserializeOwned + Send + 'static,
+    E: Serialize + DeserializeOwned + Send + 'static,
+{
+    /// 在线程池中编码并保存到文件
+    ///
+    /// 存档数据已在调用前复制(见 [`WorldSave::capture`])，编码、压缩与写入均在工作线程中完成，不阻塞渲染循环
+    ///
+    /// # 参数
+    /// + `pool` - 线程池
+    /// + `path` - 文件路径
+    ///
+    /// # 返回值
+    /// 返回可查询保存进度的句柄
+    pub fn save_async<Q: AsRef<Path>>(self, pool: &JobPool, path: Q) -> SaveHandle {
+        let path = path.as_ref().to_path_buf();
+        let (sender, receiver) = channel();
+        pool.spawn(move || {
+            let _ = sender.send(self.save(&path));
+        });
+        SaveHandle {
+            receiver,
+            result: None,
+        }
+    }
+}
+
+fn write_atomic(path: &Path, bytes: &[u8]) -> Result<()> {
+    let mut temp = PathBuf::from(path);
+    temp.as_mut_os_string().push(".tmp");
+    let mut file = std::fs::File::create(&temp)?;
+    file.write_all(bytes)?;
+    file.sync_all()?;
+    drop(file);
+    std::fs::rename(&temp, path)?;
+    debug!("WorldSave", "已保存 {} ({} 字节)", path.display(), bytes.len());
+    Ok(())
+}
+
+/// 异步保存的句柄
+pub struct SaveHandle {
+    receiver: Receiver<Result<()>>,
+    result: Option<Result<()>>,
+}
+
+impl SaveHandle {
+    /// 保存是否已完成(无论成功与否)
+    pub fn is_finished(&mut self) -> bool {
+        if self.result.is_none() {
+            match self.receiver.try_recv() {
+                Ok(result) => self.result = Some(result),
+                Err(TryRecvError::Disconnected) => {
+                    self.result = Some(Err(Error::Parse("保存任务意外终止".to_string())))
+                }
+                Err(TryRecvError::Empty) => {}
+            }
+        }
+        self.result.is_some()
+    }
+
+    /// 等待保存完成
+    ///
+    /// # 返回值
+    /// 返回保存结果
+    pub fn wait(mut self) -> Result<()> {
+        if let Some(result) = self.result.take() {
+            return result;
+        }
+        self.receiver
+            .recv()
+            .unwrap_or_else(|_| Err(Error::Parse("保存任务意外终止".to_string())))
+    }
+}
@@ -0,0 +1,450 @@
+use std::{
+    any::{Any, TypeId},
+    collections::HashMap,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::math::*;
+
+/// 变换
+///
+/// 依次应用缩放、旋转、平移
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Transform {
+    /// 平移
+    pub translation: Vec3,
+    /// 旋转
+    pub rotation: Quat,
+    /// 缩放
+    pub scale: Vec3,
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+impl Transform {
+    /// 单位变换
+    pub const IDENTITY: Transform = Transform {
+        translation: Vec3::ZERO,
+        rotation: Quat::IDENTITY,
+        scale: Vec3::ONE,
+    };
+
+    /// 创建仅包含平移的变换
+    pub fn from_translation(translation: Vec3) -> Self {
+        Self {
+            translation,
+            ..Self::IDENTITY
+        }
+    }
+
+    /// 创建仅包含旋转的变换
+    pub fn from_rotation(rotation: Quat) -> Self {
+        Self {
+            rotation,
+            ..Self::IDENTITY
+        }
+    }
+
+    /// 创建仅包含缩放的变换
+    pub fn from_scale(scale: Vec3) -> Self {
+        Self {
+            scale,
+            ..Self::IDENTITY
+        }
+    }
+
+    /// 由仿射变换矩阵创建变换
+    ///
+    /// # 参数
+    /// + `m` - 不含切变的仿射变换矩阵
+    pub fn from_matrix(m: &Mat4) -> Self {
+        let (scale, rotation, translation) = m.to_scale_rotation_translation();
+        Self {
+            translation,
+            rotation,
+            scale,
+        }
+    }
+
+    /// 获取变换矩阵
+    pub fn matrix(&self) -> Mat4 {
+        Mat4::from_scale_rotation_translation(self.scale, self.rotation, self.translation)
+    }
+
+    /// 获取变换后的前方向(`-Z`)
+    pub fn forward(&self) -> Vec3 {
+        self.rotation * Vec3::NEG_Z
+    }
+}
+
+/// 场景中的实体标识
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Entity {
+    index: u32,
+    generation: u32,
+}
+
+impl Entity {
+    /// 获取实体在场景中的序号，序号可能被已销毁实体之后创建的实体复用
+    pub fn index(&self) -> u32 {
+        self.index
+    }
+}
+
+/// 场景图节点
+#[derive(Debug, Clone)]
+pub struct Node {
+    /// 节点名称
+    pub name: String,
+    /// 相对于父节点的局部变换
+    pub transform: Transform,
+    parent: Option<Entity>,
+    children: Vec<Entity>,
+    world: Mat4,
+}
+
+impl Node {
+    /// 获取父节点
+    pub fn parent(&self) -> Option<Entity> {
+        self.parent
+    }
+
+    /// 获取子节点
+    pub fn children(&self) -> &[Entity] {
+        &self.children
+    }
+
+    /// 获取最近一次调用 [`Scene::update_world_transforms`] 时计算的世界变换矩阵
+    pub fn world_matrix(&self) -> Mat4 {
+        self.world
+    }
+}
+
+trait ComponentStorage: Any + Send {
+    fn remove_entity(&mut self, entity: Entity);
+    fn as_any(&self) -> &dyn Any;
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+}
+
+impl<T: Any + Send> ComponentStorage for HashMap<Entity, T> {
+    fn remove_entity(&mut self, entity: Entity) {
+        self.remove(&entity);
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+/// 场景图
+///
+/// 以层级结构组织实体，每个实体拥有一个局部变换，并可以附加任意类型的组件
+///
+/// # 示例
+///
+/// ```
+/// use gle::{*, math::*};
+///
+/// struct Velocity(Vec3);
+///
+/// let mut scene = Scene::new();
+/// let root = scene.spawn("root", Transform::from_translation(Vec3::X));
+/// let child = scene.spawn_child(root, "child", Transform::from_translation(Vec3::Y));
+/// scene.insert(child, Velocity(Vec3::Z));
+/// scene.update_world_transforms();
+/// assert_eq!(scene.world_matrix(child).unwrap().w_axis.truncate(), Vec3::new(1.0, 1.0, 0.0));
+/// ```
+#[derive(Default)]
+pub struct Scene {
+    nodes: Vec<Option<Node>>,
+    generations: Vec<u32>,
+    free: Vec<u32>,
+    roots: Vec<Entity>,
+    components: HashMap<TypeId, Box<dyn ComponentStorage>>,
+}
+
+impl Scene {
+    /// 创建一个空场景
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 获取实体数量
+    pub fn len(&self) -> usize {
+        self.nodes.len() - self.free.len()
+    }
+
+    /// 判断场景是否为空
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// 判断实体是否存在
+    pub fn contains(&self, entity: Entity) -> bool {
+        self.get(entity).is_some()
+    }
+
+    /// 创建一个根实体
+    ///
+    /// # 参数
+    /// + `name` - 实体名称
+    /// + `transform` - 局部变换
+    ///
+    /// # 返回值
+    /// 返回新实体的标识
+    pub fn spawn(&mut self, name: &str, transform: Transform) -> Entity {
+        let node = Node {
+            name: name.to_string(),
+            transform,
+            parent: None,
+            children: Vec::new(),
+            world: transform.matrix(),
+        };
+        let entity = match self.free.pop() {
+            Some(index) => {
+                self.nodes[index as usize] = Some(node);
+                Entity {
+                    index,
+                    generation: self.generations[index as usize],
+                }
+            }
+            None => {
+                self.nodes.push(Some(node));
+                self.generations.push(0);
+                Entity {
+                    index: self.nodes.len() as u32 - 1,
+                    generation: 0,
+                }
+            }
+        };
+        self.roots.push(entity);
+        entity
+    }
+
+    /// 创建一个子实体
+    ///
+    /// # 参数
+    /// + `parent` - 父实体，不存在时新实体将成为根实体
+    /// + `name` - 实体名称
+    /// + `transform` - 相对于父实体的局部变换
+    ///
+    /// # 返回值
+    /// 返回新实体的标识
+    pub fn spawn_child(&mut self, parent: Entity, name: &str, transform: Transform) -> Entity {
+        let entity = self.spawn(name, transform);
+        self.set_parent(entity, Some(parent));
+        entity
+    }
+
+    /// 销毁实体及其所有子孙实体，并移除它们的组件
+    ///
+    /// # 参数
+    /// + `entity` - 实体
+    pub fn despawn(&mut self, entity: Entity) {
+        if !self.contains(entity) {
+            return;
+        }
+        self.set_parent(entity, None);
+        self.roots.retain(|&e| e != entity);
+        let mut stack = vec![entity];
+        while let Some(e) = stack.pop() {
+            if let Some(node) = self.nodes[e.index as usize].take() {
+                stack.extend(node.children);
+                self.generations[e.index as usize] += 1;
+                self.free.push(e.index);
+                for storage in self.components.values_mut() {
+                    storage.remove_entity(e);
+                }
+            }
+        }
+    }
+
+    /// 获取实体的节点
+    pub fn get(&self, entity: Entity) -> Option<&Node> {
+        if *self.generations.get(entity.index as usize)? != entity.generation {
+            return None;
+        }
+        self.nodes[entity.index as usize].as_ref()
+    }
+
+    /// 获取实体节点的可变引用
+    pub fn get_mut(&mut self, entity: Entity) -> Option<&mut Node> {
+        if *self.generations.get(entity.index as usize)? != entity.generation {
+            return None;
+        }
+        self.nodes[entity.index as usize].as_mut()
+    }
+
+    /// 获取所有根实体
+    pub fn roots(&self) -> &[Entity] {
+        &self.roots
+    }
+
+    /// 遍历所有实体
+    pub fn iter(&self) -> impl Iterator<Item = (Entity, &Node)> {
+        self.nodes.iter().enumerate().filter_map(|(i, node)| {
+            node.as_ref().map(|node| {
+                (
+                    Entity {
+                        index: i as u32,
+                        generation: self.generations[i],
+                    },
+                    node,
+                )
+            })
+        })
+    }
+
+    /// 按名称查找实体
+    ///
+    /// # 返回值
+    /// 返回第一个名称匹配的实体，不存在时返回`None`
+    pub fn find(&self, name: &str) -> Option<Entity> {
+        self.iter().find(|(_, n)| n.name == name).map(|(e, _)| e)
+    }
+
+    /// 设置实体的父实体
+    ///
+    /// # 参数
+    /// + `entity` - 实体
+    /// + `parent` - 新的父实体，为`None`时实体成为根实体
+    ///
+    /// # 注解
+    ///
+    /// 若新的父实体是该实体自身或其子孙，则不做任何事
+    pub fn set_parent(&mut self, entity: Entity, parent: Option<Entity>) {
+        if !self.contains(entity) || parent.is_some_and(|p| !self.contains(p)) {
+            return;
+        }
+        let mut ancestor = parent;
+        while let Some(a) = ancestor {
+            if a == entity {
+                return;
+            }
+            ancestor = self.get(a).and_then(|n| n.parent);
+        }
+        let old = self.get(entity).unwrap().parent;
+        match old {
+            Some(old) => {
+                if let Some(node) = self.get_mut(old) {
+                    node.children.retain(|&c| c != entity);
+                }
+            }
+            None => self.roots.retain(|&e| e != entity),
+        }
+        match parent {
+            Some(p) => self.get_mut(p).unwrap().children.push(entity),
+            None => self.roots.push(entity),
+        }
+        self.get_mut(entity).unwrap().parent = parent;
+    }
+
+    /// 自根实体起重新计算所有实体的世界变换矩阵
+    pub fn update_world_transforms(&mut self) {
+        let mut stack: Vec<(Entity, Mat4)> = self.roots.iter().map(|&e| (e, Mat4::IDENTITY)).collect();
+        while let Some((e, parent_world)) = stack.pop() {
+            if let Some(node) = self.get_mut(e) {
+                node.world = parent_world * node.transform.matrix();
+                let world = node.world;
+                stack.extend(node.children.iter().map(|&c| (c, world)));
+            }
+        }
+    }
+
+    /// 获取实体的世界变换矩阵
+    ///
+    /// # 返回值
+    /// 沿父链实时计算的世界变换矩阵，实体不存在时返回`None`
+    pub fn world_matrix(&self, entity: Entity) -> Option<Mat4> {
+        let mut node = self.get(entity)?;
+        let mut m = node.transform.matrix();
+        while let Some(parent) = node.parent.and_then(|p| self.get(p)) {
+            m = parent.transform.matrix() * m;
+            node = parent;
+        }
+        Some(m)
+    }
+
+    /// 为实体附加组件，已存在的同类型组件将被替换
+    ///
+    /// # 参数
+    /// + `entity` - 实体
+    /// + `component` - 组件
+    ///
+    /// # 返回值
+    /// 返回被替换的旧组件
+    pub fn insert<T: Any + Send>(&mut self, entity: Entity, component: T) -> Option<T> {
+        if !self.contains(entity) {
+            return None;
+        }
+        self.storage_mut::<T>().insert(entity, component)
+    }
+
+    /// 移除实体的组件
+    ///
+    /// # 返回值
+    /// 返回被移除的组件
+    pub fn remove<T: Any + Send>(&mut self, entity: Entity) -> Option<T> {
+        self.components
+            .get_mut(&TypeId::of::<T>())?
+            .as_any_mut()
+            .downcast_mut::<HashMap<Entity, T>>()?
+            .remove(&entity)
+    }
+
+    /// 获取实体的组件
+    pub fn component<T: Any + Send>(&self, entity: Entity) -> Option<&T> {
+        self.storage::<T>()?.get(&entity)
+    }
+
+    /// 获取实体组件的可变引用
+    pub fn component_mut<T: Any + Send>(&mut self, entity: Entity) -> Option<&mut T> {
+        self.components
+            .get_mut(&TypeId::of::<T>())?
+            .as_any_mut()
+            .downcast_mut::<HashMap<Entity, T>>()?
+            .get_mut(&entity)
+    }
+
+    /// 遍历所有拥有指定类型组件的实体
+    pub fn query<T: Any + Send>(&self) -> impl Iterator<Item = (Entity, &T)> {
+        self.storage::<T>()
+            .into_iter()
+            .flat_map(|s| s.iter().map(|(e, c)| (*e, c)))
+    }
+
+    /// 以可变方式遍历所有拥有指定类型组件的实体
+    pub fn query_mut<T: Any + Send>(&mut self) -> impl Iterator<Item = (Entity, &mut T)> {
+        self.components
+            .get_mut(&TypeId::of::<T>())
+            .and_then(|s| s.as_any_mut().downcast_mut::<HashMap<Entity, T>>())
+            .into_iter()
+            .flat_map(|s| s.iter_mut().map(|(e, c)| (*e, c)))
+    }
+
+    fn storage<T: Any + Send>(&self) -> Option<&HashMap<Entity, T>> {
+        self.components
+            .get(&TypeId::of::<T>())?
+            .as_any()
+            .downcast_ref::<HashMap<Entity, T>>()
+    }
+
+    fn storage_mut<T: Any + Send>(&mut self) -> &mut HashMap<Entity, T> {
+        self.components
+            .entry(TypeId::of::<T>())
+            .or_insert_with(|| Box::new(HashMap::<Entity, T>::new()))
+            .as_any_mut()
+            .downcast_mut::<HashMap<Entity, T>>()
+            .unwrap()
+    }
+}
@@ -0,0 +1,417 @@
+use std::any::Any;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::error::{Error, Result};
+use crate::{warn, Entity, PrefabInstance, PrefabLibrary, PrefabOverrides, Scene, Transform};
+
+/// 场景文件格式的版本
+const VERSION: u32 = 1;
+
+type SaveFn = Box<dyn Fn(&Scene, Entity) -> Option<Result<Value>>>;
+type LoadFn = Box<dyn Fn(&mut Scene, Entity, &Value) -> Result<()>>;
+type RemoveFn = Box<dyn Fn(&mut Scene, Entity)>;
+
+struct ComponentEntry {
+    name: String,
+    save: SaveFn,
+    load: LoadFn,
+    remove: RemoveFn,
+}
+
+/// 可序列化组件的注册表
+///
+/// 场景中的组件可以是任意类型，只有注册过的组件才会被写入场景文件或从场景文件中读取。
+/// 注册表默认包含 [`AssetRefs`]
+///
+/// # 示例
+///
+/// ```
+/// use gle::*;
+/// use serde::{Deserialize, Serialize};
+///
+/// #[derive(Serialize, Deserialize)]
+/// struct Health(f32);
+///
+/// let mut registry = ComponentRegistry::new();
+/// registry.register::<Health>("health");
+/// ```
+pub struct ComponentRegistry {
+    entries: Vec<ComponentEntry>,
+}
+
+impl ComponentRegistry {
+    /// 创建只包含 [`AssetRefs`] 的注册表
+    pub fn new() -> Self {
+        let mut registry = Self {
+            entries: Vec::new(),
+        };
+        registry.register::<AssetRefs>("assets");
+        registry
+    }
+
+    /// 注册组件类型
+    ///
+    /// # 参数
+    /// + `name` - 组件在场景文件中的名称，已注册的同名组件将被替换
+    ///
+    /// # 返回值
+    /// 返回注册表本身
+    pub fn register<T>(&mut self, name: &str) -> &mut Self
+    where
+        T: Any + Send + Serialize + DeserializeOwned,
+    {
+        self.entries.retain(|e| e.name != name);
+        let component = name.to_string();
+        self.entries.push(ComponentEntry {
+            name: name.to_string(),
+            save: Box::new(|scene: &Scene, entity: Entity| {
+                let value = scene.component::<T>(entity)?;
+                Some(serde_json::to_value(value).map_err(|e| Error::Parse(e.to_string())))
+            }),
+            load: Box::new(move |scene: &mut Scene, entity: Entity, value: &Value| {
+                let value = T::deserialize(value)
+                    .map_err(|e| Error::Parse(format!("组件 {}: {}", component, e)))?;
+                scene.insert(entity, value);
+                Ok(())
+            }),
+            remove: Box::new(|scene: &mut Scene, entity: Entity| {
+                scene.remove::<T>(entity);
+            }),
+        });
+        self
+    }
+
+    /// 判断组件名称是否已注册
+    pub fn contains(&self, name: &str) -> bool {
+        self.entries.iter().any(|e| e.name == name)
+    }
+
+    fn get(&self, name: &str) -> Option<&ComponentEntry> {
+        self.entries.iter().find(|e| e.name == name)
+    }
+
+    /// 读取实体上所有注册过的组件，按注册顺序排列
+    pub(crate) fn save_all(&self, scene: &Scene, entity: Entity) -> Vec<(&str, Result<Value>)> {
+        self.entries
+            .iter()
+            .filter_map(|entry| Some((entry.name.as_str(), (entry.save)(scene, entity)?)))
+            .collect()
+    }
+
+    /// 以 JSON 值设置实体的组件
+    pub(crate) fn load(
+        &self,
+        name: &str,
+        scene: &mut Scene,
+        entity: Entity,
+        value: &Value,
+    ) -> Result<()> {
+        let entry = self
+            .get(name)
+            .ok_or_else(|| Error::Parse(format!("组件 {} 未注册", name)))?;
+        (entry.load)(scene, entity, value)
+    }
+
+    /// 移除实体上所有注册过的组件
+    pub(crate) fn remove_all(&self, scene: &mut Scene, entity: Entity) {
+        for entry in &self.entries {
+            (entry.remove)(scene, entity);
+        }
+    }
+}
+
+impl Default for ComponentRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 资源引用组件
+///
+/// 以名称到路径的映射记录实体使用的资源(如`mesh`、`material`、`texture`)，
+/// 使场景文件只保存资源路径而不保存资源本身。加载场景后由程序遍历该组件并加载对应的资源
+///
+/// # 示例
+///
+/// ```
+/// use gle::*;
+///
+/// let mut scene = Scene::new();
+/// let tree = scene.spawn("tree", Transform::default());
+/// scene.insert(tree, AssetRefs::new().with("mesh", "models/tree.obj"));
+/// assert_eq!(scene.component::<AssetRefs>(tree).unwrap().get("mesh"), Some("models/tree.obj"));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct AssetRefs(pub BTreeMap<String, String>);
+
+impl AssetRefs {
+    /// 创建空的资源引用
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 添加资源引用
+    pub fn with(mut self, name: &str, path: &str) -> Self {
+        self.0.insert(name.to_string(), path.to_string());
+        self
+    }
+
+    /// 获取资源路径
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.0.get(name).map(String::as_str)
+    }
+}
+
+/// 场景文件中的实体
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct EntityDesc {
+    /// 实体名称
+    #[serde(default)]
+    pub name: String,
+    /// 相对于父实体的局部变换
+    #[serde(default)]
+    pub transform: Transform,
+    /// 组件，键为 [`ComponentRegistry::register`] 时的名称
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub components: BTreeMap<String, Value>,
+    /// 子实体
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<EntityDesc>,
+    /// 引用的预制体名称，不为空时该实体是预制体的实例，`components`与`children`被忽略
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prefab: Option<String>,
+    /// 预制体实例的参数覆盖，见 [`PrefabOverrides`]
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub overrides: PrefabOverrides,
+}
+
+/// 场景文件
+///
+/// 以 JSON 保存实体层级、局部变换与注册过的组件，用于数据驱动的关卡编辑
+///
+/// # 示例
+///
+/// ```json
+/// {
+///   "version": 1,
+///   "entities": [
+///     {
+///       "name": "house",
+///       "transform": { "translation": [4.0, 0.0, -2.0], "rotation": [0.0, 0.0, 0.0, 1.0] },
+///       "components": { "assets": { "mesh": "models/house.obj" } },
+///       "children": [{ "name": "door", "transform": { "translation": [0.0, 0.0, 1.0] } }]
+///     },
+///     {
+///       "name": "tree1",
+///       "transform": { "translation": [-3.0, 0.0, 5.0] },
+///       "prefab": "tree",
+///       "overrides": { "": { "assets": { "mesh": "models/pine.obj" } } }
+///     }
+///   ]
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SceneFile {
+    /// 格式版本
+    pub version: u32,
+    /// 根实体
+    pub entities: Vec<EntityDesc>,
+}
+
+impl SceneFile {
+    /// 从 JSON 源码解析场景文件
+    pub fn parse(src: &str) -> Result<Self> {
+        let file: Self = serde_json::from_str(src).map_err(|e| Error::Parse(e.to_string()))?;
+        if file.version > VERSION {
+            return Err(Error::Parse(format!("不支持的场景文件版本 {}", file.version)));
+        }
+        Ok(file)
+    }
+
+    /// 从文件加载场景文件
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let src = std::fs::read_to_string(path)?;
+        Self::parse(&src).map_err(|e| Error::Parse(format!("{}: {}", path.display(), e)))
+    }
+
+    /// 转换为格式化的 JSON 源码
+    pub fn to_json(&self) -> Result<String> {
+        serde_json::to_string_pretty(self).map_err(|e| Error::Parse(e.to_string()))
+    }
+
+    /// 将场景文件保存到文件
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        std::fs::write(path, self.to_json()?)?;
+        Ok(())
+    }
+
+    /// 记录场景中的实体
+    ///
+    /// # 参数
+    /// + `scene` - 场景
+    /// + `roots` - 要记录的实体，它们的子孙实体一并记录
+    /// + `registry` - 组件注册表，未注册的组件被忽略
+    pub fn capture(scene: &Scene, roots: &[Entity], registry: &ComponentRegistry) -> Result<Self> {
+        let entities = roots
+            .iter()
+            .filter(|&&e| scene.contains(e))
+            .map(|&e| capture_entity(scene, e, registry))
+            .collect::<Result<_>>()?;
+        Ok(Self {
+            version: VERSION,
+            entities,
+        })
+    }
+
+    /// 在场景中创建文件中的实体
+    ///
+    /// # 参数
+    /// + `scene` - 场景
+    /// + `parent` - 根实体的父实体，为`None`时创建为场景的根实体
+    /// + `registry` - 组件注册表，未注册的组件输出警告后被忽略
+    ///
+    /// # 返回值
+    /// 返回创建的根实体，组件格式错误时返回错误，此时已创建的实体保留在场景中。
+    /// 文件引用了预制体时返回错误，此时应使用 [`PrefabLibrary::instantiate_file`]
+    pub fn instantiate(
+        &self,
+        scene: &mut Scene,
+        parent: Option<Entity>,
+        registry: &ComponentRegistry,
+    ) -> Result<Vec<Entity>> {
+        self.entities
+            .iter()
+            .map(|desc| instantiate_entity(desc, scene, parent, registry, None, 0))
+            .collect()
+    }
+}
+
+fn capture_entity(
+    scene: &Scene,
+    entity: Entity,
+    registry: &ComponentRegistry,
+) -> Result<EntityDesc> {
+    let node = scene.get(entity).unwrap();
+    if let Some(instance) = scene.component::<PrefabInstance>(entity) {
+        return Ok(EntityDesc {
+            name: node.name.clone(),
+            transform: node.transform,
+            prefab: Some(instance.prefab.clone()),
+            overrides: instance.overrides.clone(),
+            ..Default::default()
+        });
+    }
+    let mut components = BTreeMap::new();
+    for entry in &registry.entries {
+        if let Some(value) = (entry.save)(scene, entity) {
+            components.insert(entry.name.clone(), value?);
+        }
+    }
+    let children = node
+        .children()
+        .iter()
+        .map(|&c| capture_entity(scene, c, registry))
+        .collect::<Result<_>>()?;
+    Ok(EntityDesc {
+        name: node.name.clone(),
+        transform: node.transform,
+        components,
+        children,
+        ..Default::default()
+    })
+}
+
+pub(crate) fn instantiate_entity(
+    desc: &EntityDesc,
+    scene: &mut Scene,
+    parent: Option<Entity>,
+    registry: &ComponentRegistry,
+    prefabs: Option<&PrefabLibrary>,
+    depth: usize,
+) -> Result<Entity> {
+    if let Some(prefab) = &desc.prefab {
+        let prefabs = prefabs.ok_or_else(|| {
+            let name = &desc.name;
+            Error::Parse(format!("实体 {} 引用了预制体 {}，需要通过 PrefabLibrary 创建", name, prefab))
+        })?;
+        let placement = (desc.name.as_str(), desc.transform);
+        return prefabs.spawn(scene, prefab, parent, &desc.overrides, Some(placement), depth + 1);
+    }
+    let entity = match parent {
+        Some(parent) => scene.spawn_child(parent, &desc.name, desc.transform),
+        None => scene.spawn(&desc.name, desc.transform),
+    };
+    instantiate_contents(desc, scene, entity, registry, prefabs, depth)?;
+    Ok(entity)
+}
+
+/// 在已有实体上创建描述中的组件与子实体
+pub(crate) fn instantiate_contents(
+    desc: &EntityDesc,
+    scene: &mut Scene,
+    entity: Entity,
+    registry: &ComponentRegistry,
+    prefabs: Option<&PrefabLibrary>,
+    depth: usize,
+) -> Result<()> {
+    for (name, value) in &desc.components {
+        match registry.get(name) {
+            Some(entry) => (entry.load)(scene, entity, value)?,
+            None => {
+                warn!("SceneFile", "实体 {} 的组件 {} 未注册，已忽略", desc.name, name);
+            }
+        }
+    }
+    for child in &desc.children {
+        instantiate_entity(child, scene, Some(entity), registry, prefabs, depth)?;
+    }
+    Ok(())
+}
+
+impl Scene {
+    /// 从场景文件加载场景
+    ///
+    /// # 参数
+    /// + `path` - 场景文件路径，格式见 [`SceneFile`]
+    /// + `registry` - 组件注册表
+    ///
+    /// # 返回值
+    /// 成功时返回世界变换已更新的场景
+    ///
+    /// # 示例
+    ///
+    /// ```no_run
+    /// use gle::*;
+    ///
+    /// let registry = ComponentRegistry::new();
+    /// let scene = Scene::load("levels/forest.scene.json", &registry).unwrap();
+    /// for (entity, assets) in scene.query::<AssetRefs>() {
+    ///     if let Some(mesh) = assets.get("mesh") {
+    ///         println!("{:?} uses {}", entity, mesh);
+    ///     }
+    /// }
+    /// scene.save("levels/forest_copy.scene.json", &registry).unwrap();
+    /// ```
+    pub fn load<P: AsRef<Path>>(path: P, registry: &ComponentRegistry) -> Result<Self> {
+        let mut scene = Scene::new();
+        SceneFile::load(path)?.instantiate(&mut scene, None, registry)?;
+        scene.update_world_transforms();
+        Ok(scene)
+    }
+
+    /// 将整个场景保存到场景文件
+    ///
+    /// # 参数
+    /// + `path` - 场景文件路径
+    /// + `registry` - 组件注册表，未注册的组件不会被保存
+    pub fn save<P: AsRef<Path>>(&self, path: P, registry: &ComponentRegistry) -> Result<()> {
+        SceneFile::capture(self, self.roots(), registry)?.save(path)
+    }
+}
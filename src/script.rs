@@ -0,0 +1,640 @@
+use std::cell::Cell;
+use std::path::{Path, PathBuf};
+
+use rhai::{CallFnOptions, Dynamic, Engine, EvalAltResult, FuncArgs, Scope, AST, FLOAT, INT};
+
+use crate::error::{Error, Result};
+use crate::log::{log, Level};
+use crate::math::*;
+use crate::{
+    debug, error, info, Assets, Entity, Input, JobPool, Key, MouseButton, Node, Scene, SoundBank,
+    Texture2D, Transform,
+};
+
+type ScriptResult<T> = std::result::Result<T, Box<EvalAltResult>>;
+
+thread_local! {
+    static CONTEXT: Cell<*mut ScriptContext<'static>> = const { Cell::new(std::ptr::null_mut()) };
+}
+
+/// 脚本可以访问的引擎对象
+///
+/// 在调用脚本期间借出场景与可选的资源，脚本通过注册的函数读写它们
+///
+/// # 示例
+///
+/// ```no_run
+/// use gle::*;
+///
+/// let mut scene = Scene::new();
+/// let sfx = SoundBank::load_dir("assets/sfx").unwrap();
+/// let mut scripts = ScriptEngine::new();
+/// scripts.update(&mut ScriptContext::new(&mut scene).with_sounds(&sfx), 0.016);
+/// ```
+pub struct ScriptContext<'a> {
+    /// 场景
+    pub scene: &'a mut Scene,
+    /// 音效库，为`None`时脚本调用`play_sound`将失败
+    pub sounds: Option<&'a SoundBank>,
+    /// 纹理资源与加载纹理使用的线程池，为`None`时脚本调用`load_texture`将失败
+    pub textures: Option<(&'a mut Assets<Texture2D>, &'a JobPool)>,
+}
+
+impl<'a> ScriptContext<'a> {
+    /// 创建只包含场景的上下文
+    pub fn new(scene: &'a mut Scene) -> Self {
+        Self {
+            scene,
+            sounds: None,
+            textures: None,
+        }
+    }
+
+    /// 允许脚本播放音效库中的音效
+    pub fn with_sounds(mut self, sounds: &'a SoundBank) -> Self {
+        self.sounds = Some(sounds);
+        self
+    }
+
+    /// 允许脚本加载纹理
+    pub fn with_textures(mut self, textures: &'a mut Assets<Texture2D>, pool: &'a JobPool) -> Self {
+        self.textures = Some((textures, pool));
+        self
+    }
+}
+
+/// 在作用域内将上下文设为当前线程的脚本上下文，离开作用域时恢复
+struct ContextGuard(*mut ScriptContext<'static>);
+
+impl ContextGuard {
+    fn enter(context: &mut ScriptContext) -> Self {
+        let ptr = (context as *mut ScriptContext).cast::<ScriptContext<'static>>();
+        Self(CONTEXT.with(|c| c.replace(ptr)))
+    }
+}
+
+impl Drop for ContextGuard {
+    fn drop(&mut self) {
+        CONTEXT.with(|c| c.set(self.0));
+    }
+}
+
+/// 访问当前线程的脚本上下文
+fn with_context<R>(f: impl FnOnce(&mut ScriptContext) -> ScriptResult<R>) -> ScriptResult<R> {
+    let ptr = CONTEXT.with(|c| c.get());
+    if ptr.is_null() {
+        return Err("只能在 ScriptEngine 调用脚本期间访问场景与资源".into());
+    }
+    // SAFETY: 指针只在 ContextGuard 存活期间有效，此时上下文被 ScriptEngine 以可变引用独占借用；
+    // 注册的函数不会调用脚本，因此不会同时存在两个可变引用
+    f(unsafe { &mut *ptr })
+}
+
+/// 访问实体的节点
+fn with_node<R>(entity: Entity, f: impl FnOnce(&mut Node) -> R) -> ScriptResult<R> {
+    with_context(|ctx| {
+        ctx.scene
+            .get_mut(entity)
+            .map(f)
+            .ok_or_else(|| format!("实体 {} 不存在", entity.index()).into())
+    })
+}
+
+fn optional<T: Clone + Send + Sync + 'static>(value: Option<T>) -> Dynamic {
+    value.map_or(Dynamic::UNIT, Dynamic::from)
+}
+
+/// 按名称查找按键，名称为 glfw 按键枚举的变体名，如`W`、`Space`、`LeftShift`、`F1`
+fn parse_key(name: &str) -> ScriptResult<Key> {
+    const LETTERS: [Key; 26] = [
+        Key::A, Key::B, Key::C, Key::D, Key::E, Key::F, Key::G, Key::H, Key::I, Key::J, Key::K,
+        Key::L, Key::M, Key::N, Key::O, Key::P, Key::Q, Key::R, Key::S, Key::T, Key::U, Key::V,
+        Key::W, Key::X, Key::Y, Key::Z,
+    ];
+    const DIGITS: [Key; 10] = [
+        Key::Num0, Key::Num1, Key::Num2, Key::Num3, Key::Num4, Key::Num5, Key::Num6, Key::Num7,
+        Key::Num8, Key::Num9,
+    ];
+    const FUNCTIONS: [Key; 12] = [
+        Key::F1, Key::F2, Key::F3, Key::F4, Key::F5, Key::F6, Key::F7, Key::F8, Key::F9, Key::F10,
+        Key::F11, Key::F12,
+    ];
+    let mut chars = name.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        if c.is_ascii_alphabetic() {
+            return Ok(LETTERS[(c.to_ascii_uppercase() as u8 - b'A') as usize]);
+        }
+        if let Some(d) = c.to_digit(10) {
+            return Ok(DIGITS[d as usize]);
+        }
+    }
+    if let Some(n) = name.strip_prefix('F').and_then(|n| n.parse::<usize>().ok()) {
+        if (1..=12).contains(&n) {
+            return Ok(FUNCTIONS[n - 1]);
+        }
+    }
+    let key = match name {
+        "Space" => Key::Space,
+        "Enter" => Key::Enter,
+        "Escape" => Key::Escape,
+        "Tab" => Key::Tab,
+        "Backspace" => Key::Backspace,
+        "Delete" => Key::Delete,
+        "Left" => Key::Left,
+        "Right" => Key::Right,
+        "Up" => Key::Up,
+        "Down" => Key::Down,
+        "LeftShift" => Key::LeftShift,
+        "RightShift" => Key::RightShift,
+        "LeftControl" => Key::LeftControl,
+        "RightControl" => Key::RightControl,
+        "LeftAlt" => Key::LeftAlt,
+        "RightAlt" => Key::RightAlt,
+        _ => return Err(format!("未知的按键 {}", name).into()),
+    };
+    Ok(key)
+}
+
+/// 按序号查找鼠标按键，0 为左键，1 为右键，2 为中键
+fn parse_button(index: INT) -> ScriptResult<MouseButton> {
+    const BUTTONS: [MouseButton; 8] = [
+        MouseButton::Button1,
+        MouseButton::Button2,
+        MouseButton::Button3,
+        MouseButton::Button4,
+        MouseButton::Button5,
+        MouseButton::Button6,
+        MouseButton::Button7,
+        MouseButton::Button8,
+    ];
+    usize::try_from(index)
+        .ok()
+        .and_then(|i| BUTTONS.get(i).copied())
+        .ok_or_else(|| format!("未知的鼠标按键 {}", index).into())
+}
+
+fn register_math(engine: &mut Engine) {
+    engine
+        .register_type_with_name::<Vec2>("Vec2")
+        .register_fn("vec2", |x: FLOAT, y: FLOAT| Vec2::new(x, y))
+        .register_get_set("x", |v: &mut Vec2| v.x, |v: &mut Vec2, x: FLOAT| v.x = x)
+        .register_get_set("y", |v: &mut Vec2| v.y, |v: &mut Vec2, y: FLOAT| v.y = y)
+        .register_fn("+", |a: Vec2, b: Vec2| a + b)
+        .register_fn("-", |a: Vec2, b: Vec2| a - b)
+        .register_fn("-", |a: Vec2| -a)
+        .register_fn("*", |a: Vec2, s: FLOAT| a * s)
+        .register_fn("*", |s: FLOAT, a: Vec2| a * s)
+        .register_fn("/", |a: Vec2, s: FLOAT| a / s)
+        .register_fn("==", |a: Vec2, b: Vec2| a == b)
+        .register_fn("length", |v: &mut Vec2| v.length())
+        .register_fn("normalize", |v: &mut Vec2| v.normalize_or_zero())
+        .register_fn("dot", |a: Vec2, b: Vec2| a.dot(b))
+        .register_fn("to_string", |v: &mut Vec2| format!("({}, {})", v.x, v.y))
+        .register_fn("to_debug", |v: &mut Vec2| format!("vec2({}, {})", v.x, v.y));
+    engine
+        .register_type_with_name::<Vec3>("Vec3")
+        .register_fn("vec3", |x: FLOAT, y: FLOAT, z: FLOAT| Vec3::new(x, y, z))
+        .register_get_set("x", |v: &mut Vec3| v.x, |v: &mut Vec3, x: FLOAT| v.x = x)
+        .register_get_set("y", |v: &mut Vec3| v.y, |v: &mut Vec3, y: FLOAT| v.y = y)
+        .register_get_set("z", |v: &mut Vec3| v.z, |v: &mut Vec3, z: FLOAT| v.z = z)
+        .register_fn("+", |a: Vec3, b: Vec3| a + b)
+        .register_fn("-", |a: Vec3, b: Vec3| a - b)
+        .register_fn("-", |a: Vec3| -a)
+        .register_fn("*", |a: Vec3, s: FLOAT| a * s)
+        .register_fn("*", |s: FLOAT, a: Vec3| a * s)
+        .register_fn("/", |a: Vec3, s: FLOAT| a / s)
+        .register_fn("==", |a: Vec3, b: Vec3| a == b)
+        .register_fn("length", |v: &mut Vec3| v.length())
+        .register_fn("normalize", |v: &mut Vec3| v.normalize_or_zero())
+        .register_fn("dot", |a: Vec3, b: Vec3| a.dot(b))
+        .register_fn("cross", |a: Vec3, b: Vec3| a.cross(b))
+        .register_fn("lerp", |a: Vec3, b: Vec3, t: FLOAT| a.lerp(b, t))
+        .register_fn("to_string", |v: &mut Vec3| format!("({}, {}, {})", v.x, v.y, v.z))
+        .register_fn("to_debug", |v: &mut Vec3| format!("vec3({}, {}, {})", v.x, v.y, v.z));
+}
+
+fn register_scene(engine: &mut Engine) {
+    engine
+        .register_type_with_name::<Entity>("Entity")
+        .register_fn("==", |a: Entity, b: Entity| a == b)
+        .register_fn("to_string", |e: &mut Entity| format!("Entity({})", e.index()))
+        .register_fn("to_debug", |e: &mut Entity| format!("Entity({})", e.index()))
+        .register_fn("find", |name: &str| {
+            with_context(|ctx| Ok(optional(ctx.scene.find(name))))
+        })
+        .register_fn("spawn", |name: &str| {
+            with_context(|ctx| Ok(ctx.scene.spawn(name, Transform::default())))
+        })
+        .register_fn("spawn", |name: &str, position: Vec3| {
+            with_context(|ctx| Ok(ctx.scene.spawn(name, Transform::from_translation(position))))
+        })
+        .register_fn("spawn_child", |parent: Entity, name: &str, position: Vec3| {
+            with_context(|ctx| {
+                let transform = Transform::from_translation(position);
+                Ok(ctx.scene.spawn_child(parent, name, transform))
+            })
+        })
+        .register_fn("despawn", |entity: Entity| {
+            with_context(|ctx| {
+                ctx.scene.despawn(entity);
+                Ok(())
+            })
+        })
+        .register_get("alive", |e: &mut Entity| {
+            let entity = *e;
+            with_context(|ctx| Ok(ctx.scene.contains(entity)))
+        })
+        .register_get_set(
+            "name",
+            |e: &mut Entity| with_node(*e, |n| n.name.clone()),
+            |e: &mut Entity, name: String| with_node(*e, |n| n.name = name),
+        )
+        .register_get_set(
+            "position",
+            |e: &mut Entity| with_node(*e, |n| n.transform.translation),
+            |e: &mut Entity, v: Vec3| with_node(*e, |n| n.transform.translation = v),
+        )
+        .register_get_set(
+            "scale",
+            |e: &mut Entity| with_node(*e, |n| n.transform.scale),
+            |e: &mut Entity, v: Vec3| with_node(*e, |n| n.transform.scale = v),
+        )
+        .register_get_set(
+            "rotation",
+            |e: &mut Entity| {
+                with_node(*e, |n| {
+                    let (y, x, z) = n.transform.rotation.to_euler(EulerRot::YXZ);
+                    Vec3::new(x, y, z)
+                })
+            },
+            |e: &mut Entity, v: Vec3| {
+                let rotation = Quat::from_euler(EulerRot::YXZ, v.y, v.x, v.z);
+                with_node(*e, |n| n.transform.rotation = rotation)
+            },
+        )
+        .register_get("forward", |e: &mut Entity| with_node(*e, |n| n.transform.forward()))
+        .register_get("world_position", |e: &mut Entity| {
+            with_node(*e, |n| n.world_matrix().w_axis.truncate())
+        })
+        .register_get("parent", |e: &mut Entity| with_node(*e, |n| optional(n.parent())))
+        .register_fn("translate", |e: &mut Entity, v: Vec3| {
+            with_node(*e, |n| n.transform.translation += v)
+        })
+        .register_fn("rotate", |e: &mut Entity, axis: Vec3, angle: FLOAT| {
+            with_node(*e, |n| {
+                let rotation = Quat::from_axis_angle(axis.normalize_or_zero(), angle);
+                n.transform.rotation = (rotation * n.transform.rotation).normalize();
+            })
+        });
+}
+
+fn register_input(engine: &mut Engine) {
+    engine
+        .register_fn("key_down", |name: &str| parse_key(name).map(Input::key_down))
+        .register_fn("key_pressed", |name: &str| parse_key(name).map(Input::key_pressed))
+        .register_fn("key_released", |name: &str| parse_key(name).map(Input::key_released))
+        .register_fn("mouse_down", |i: INT| parse_button(i).map(Input::mouse_down))
+        .register_fn("mouse_pressed", |i: INT| parse_button(i).map(Input::mouse_pressed))
+        .register_fn("mouse_released", |i: INT| parse_button(i).map(Input::mouse_released))
+        .register_fn("cursor", || {
+            let (x, y) = Input::cursor_pos();
+            Vec2::new(x as f32, y as f32)
+        })
+        .register_fn("cursor_delta", || {
+            let (x, y) = Input::cursor_delta();
+            Vec2::new(x as f32, y as f32)
+        })
+        .register_fn("scroll_delta", || {
+            let (x, y) = Input::scroll_delta();
+            Vec2::new(x as f32, y as f32)
+        });
+}
+
+fn register_assets(engine: &mut Engine) {
+    engine
+        .register_fn("play_sound", |name: &str| {
+            with_context(|ctx| {
+                let sounds = ctx.sounds.ok_or("脚本上下文中没有音效库")?;
+                Ok(sounds.play(name).is_some())
+            })
+        })
+        .register_fn("load_texture", |path: &str| {
+            with_context(|ctx| {
+                let (textures, pool) =
+                    ctx.textures.as_mut().ok_or("脚本上下文中没有纹理资源")?;
+                let handle = textures.load_texture(path, pool, true);
+                Ok(textures.is_ready(&handle))
+            })
+        })
+        .register_fn("texture_ready", |path: &str| {
+            with_context(|ctx| {
+                let (textures, _) =
+                    ctx.textures.as_ref().ok_or("脚本上下文中没有纹理资源")?;
+                Ok(textures.handle(path).is_some_and(|h| textures.is_ready(&h)))
+            })
+        });
+}
+
+/// 已加载的脚本
+struct Script {
+    path: PathBuf,
+    ast: AST,
+    state: Dynamic,
+    failed: bool,
+}
+
+impl Script {
+    fn defines(&self, name: &str) -> bool {
+        self.ast.iter_functions().any(|f| f.name == name)
+    }
+}
+
+/// 脚本引擎
+///
+/// 基于 [`rhai`] 运行游戏逻辑脚本，需要启用`rhai`特性。脚本可以访问：
+/// + 数学：`vec2(x, y)`、`vec3(x, y, z)`及其分量、四则运算、`length`、`normalize`、`dot`、`cross`、`lerp`
+/// + 场景：`find(name)`(不存在时返回`()`)、`spawn(name[, position])`、`spawn_child(parent, name, position)`、
+///   `despawn(entity)`，实体的`alive`、`name`、`position`、`rotation`(YXZ 顺序的欧拉角，单位为弧度)、
+///   `scale`、`forward`、`world_position`、`parent`属性与`translate(v)`、`rotate(axis, angle)`方法
+/// + 输入：`key_down(name)`、`key_pressed(name)`、`key_released(name)`(按键名称如`"W"`、`"Space"`、
+///   `"LeftShift"`、`"F1"`)、`mouse_down(i)`、`mouse_pressed(i)`、`mouse_released(i)`(0 为左键)、
+///   `cursor()`、`cursor_delta()`、`scroll_delta()`
+/// + 资源：`play_sound(name)`、`load_texture(path)`、`texture_ready(path)`，需要上下文提供对应的资源
+///
+/// 脚本中的`print`与`debug`输出到引擎日志。脚本可以定义以下函数，由引擎在相应时机调用：
+/// + `init()` - 首次加载后调用
+/// + `reload()` - 热重载后调用
+/// + `update(dt)` - 每次调用 [`ScriptEngine::update`] 时调用，`dt`的单位为秒
+///
+/// 函数中的`this`是脚本的状态对象，初始为空的对象映射，在热重载时保留，用于保存跨帧的变量
+///
+/// # 示例
+///
+/// ```no_run
+/// use gle::*;
+///
+/// // assets/scripts/player.rhai:
+/// //
+/// // fn init() {
+/// //     this.speed = 4.0;
+/// //     spawn("player", vec3(0.0, 1.0, 0.0));
+/// // }
+/// //
+/// // fn update(dt) {
+/// //     let player = find("player");
+/// //     if key_down("W") { player.translate(player.forward * this.speed * dt); }
+/// //     if key_pressed("Space") { play_sound("jump"); }
+/// // }
+///
+/// let mut scene = Scene::new();
+/// let sfx = SoundBank::load_dir("assets/sfx").unwrap();
+/// let watcher = AssetWatcher::new("assets/scripts");
+/// let mut scripts = ScriptEngine::new();
+/// scripts
+///     .load("assets/scripts/player.rhai", &mut ScriptContext::new(&mut scene))
+///     .unwrap();
+///
+/// // 在事件循环中每帧
+/// let mut ctx = ScriptContext::new(&mut scene).with_sounds(&sfx);
+/// scripts.reload(&watcher.poll(), &mut ctx);
+/// scripts.update(&mut ctx, App::event_ms() as f32 / 1000.0);
+/// ```
+///
+/// # 注解
+///
+/// [`Input`] 的逐帧状态在事件循环函数返回后清空，读取输入的脚本应在事件循环中调用。
+/// 执行出错的脚本会被暂停并输出错误日志，直到重新加载成功
+pub struct ScriptEngine {
+    engine: Engine,
+    scripts: Vec<Script>,
+}
+
+impl ScriptEngine {
+    /// 创建脚本引擎并注册引擎 API
+    pub fn new() -> Self {
+        let mut engine = Engine::new();
+        engine.on_print(|text| {
+            info!("Script", "{}", text);
+        });
+        engine.on_debug(|text, source, pos| {
+            log(Level::Debug, source.unwrap_or("Script"), &format!("{} {}", pos, text));
+        });
+        register_math(&mut engine);
+        register_scene(&mut engine);
+        register_input(&mut engine);
+        register_assets(&mut engine);
+        Self {
+            engine,
+            scripts: Vec::new(),
+        }
+    }
+
+    /// 获取底层的 rhai 引擎，用于注册自定义类型与函数
+    ///
+    /// # 注解
+    ///
+    /// 新注册的函数只对之后编译的脚本可见
+    pub fn engine_mut(&mut self) -> &mut Engine {
+        &mut self.engine
+    }
+
+    /// 获取已加载的脚本数量
+    pub fn len(&self) -> usize {
+        self.scripts.len()
+    }
+
+    /// 是否没有加载任何脚本
+    pub fn is_empty(&self) -> bool {
+        self.scripts.is_empty()
+    }
+
+    /// 加载脚本，执行其顶层语句并调用`init()`
+    ///
+    /// # 参数
+    /// + `path` - 脚本文件路径，已加载的脚本将被重新加载
+    /// + `context` - 脚本可以访问的引擎对象
+    ///
+    /// # 返回值
+    /// 编译或执行失败时返回错误，此时脚本不会被加载
+    pub fn load<P: AsRef<Path>>(&mut self, path: P, context: &mut ScriptContext) -> Result<()> {
+        let path = normalize(path.as_ref());
+        if self.scripts.iter().any(|s| s.path == path) {
+            return self.reload_script(&path, context);
+        }
+        let ast = self.compile(&path)?;
+        let mut script = Script {
+            path,
+            ast,
+            state: Dynamic::from_map(Default::default()),
+            failed: false,
+        };
+        let _guard = ContextGuard::enter(context);
+        self.start(&mut script, "init")?;
+        debug!(Self, "已加载脚本 {}", script.path.display());
+        self.scripts.push(script);
+        Ok(())
+    }
+
+    /// 卸载脚本
+    ///
+    /// # 返回值
+    /// 脚本已加载时返回`true`
+    pub fn unload<P: AsRef<Path>>(&mut self, path: P) -> bool {
+        let path = normalize(path.as_ref());
+        let len = self.scripts.len();
+        self.scripts.retain(|s| s.path != path);
+        self.scripts.len() != len
+    }
+
+    /// 重新加载发生变化的脚本，脚本的状态对象被保留，随后调用`reload()`
+    ///
+    /// # 参数
+    /// + `changed` - 发生变化的文件，通常来自 [`AssetWatcher::poll`](crate::AssetWatcher::poll)，
+    ///   未加载的文件将被忽略
+    /// + `context` - 脚本可以访问的引擎对象
+    ///
+    /// # 返回值
+    /// 返回成功重新加载的脚本数量，失败的脚本保留旧版本并输出错误日志
+    pub fn reload(&mut self, changed: &[PathBuf], context: &mut ScriptContext) -> usize {
+        let mut count = 0;
+        for path in changed.iter().map(|p| normalize(p)) {
+            if !self.scripts.iter().any(|s| s.path == path) {
+                continue;
+            }
+            match self.reload_script(&path, context) {
+                Ok(()) => count += 1,
+                Err(e) => {
+                    error!(Self, "重新加载脚本 {} 失败: {}", path.display(), e);
+                }
+            }
+        }
+        count
+    }
+
+    /// 调用所有脚本的`update(dt)`
+    ///
+    /// # 参数
+    /// + `context` - 脚本可以访问的引擎对象
+    /// + `dt` - 距上次调用的时长，单位为秒
+    pub fn update(&mut self, context: &mut ScriptContext, dt: f32) {
+        self.call_all(context, "update", vec![Dynamic::from(dt as FLOAT)]);
+    }
+
+    /// 调用所有定义了指定函数的脚本
+    ///
+    /// # 参数
+    /// + `context` - 脚本可以访问的引擎对象
+    /// + `name` - 函数名
+    /// + `args` - 参数
+    ///
+    /// # 返回值
+    /// 返回调用成功的脚本数量
+    pub fn call_all(
+        &mut self,
+        context: &mut ScriptContext,
+        name: &str,
+        args: Vec<Dynamic>,
+    ) -> usize {
+        let _guard = ContextGuard::enter(context);
+        let mut count = 0;
+        for script in self.scripts.iter_mut().filter(|s| !s.failed && s.defines(name)) {
+            match call(&self.engine, script, name, args.clone()) {
+                Ok(_) => count += 1,
+                Err(e) => {
+                    script.failed = true;
+                    let path = script.path.display();
+                    error!(Self, "脚本 {} 执行出错，已暂停: {}", path, e);
+                }
+            }
+        }
+        count
+    }
+
+    /// 调用指定脚本中的函数
+    ///
+    /// # 参数
+    /// + `path` - 脚本文件路径
+    /// + `context` - 脚本可以访问的引擎对象
+    /// + `name` - 函数名
+    /// + `args` - 参数，如`(1.0, "text")`
+    ///
+    /// # 返回值
+    /// 返回函数的返回值，脚本未加载或执行出错时返回错误
+    pub fn call<P: AsRef<Path>>(
+        &mut self,
+        path: P,
+        context: &mut ScriptContext,
+        name: &str,
+        args: impl FuncArgs,
+    ) -> Result<Dynamic> {
+        let path = normalize(path.as_ref());
+        let script = self
+            .scripts
+            .iter_mut()
+            .find(|s| s.path == path)
+            .ok_or_else(|| Error::Script(format!("脚本 {} 未加载", path.display())))?;
+        let _guard = ContextGuard::enter(context);
+        call(&self.engine, script, name, args)
+            .map_err(|e| Error::Script(format!("{}: {}", path.display(), e)))
+    }
+
+    fn compile(&self, path: &Path) -> Result<AST> {
+        let mut ast = self
+            .engine
+            .compile_file(path.to_path_buf())
+            .map_err(|e| Error::Script(format!("{}: {}", path.display(), e)))?;
+        ast.set_source(path.display().to_string());
+        Ok(ast)
+    }
+
+    /// 执行脚本的顶层语句，随后调用指定的函数(若存在)
+    fn start(&self, script: &mut Script, entry: &str) -> Result<()> {
+        let result = self
+            .engine
+            .run_ast_with_scope(&mut Scope::new(), &script.ast)
+            .and_then(|_| match script.defines(entry) {
+                true => call(&self.engine, script, entry, ()).map(|_| ()),
+                false => Ok(()),
+            });
+        result.map_err(|e| Error::Script(format!("{}: {}", script.path.display(), e)))
+    }
+
+    fn reload_script(&mut self, path: &Path, context: &mut ScriptContext) -> Result<()> {
+        let ast = self.compile(path)?;
+        let Some(index) = self.scripts.iter().position(|s| s.path == path) else {
+            return Ok(());
+        };
+        let mut script = Script {
+            path: path.to_path_buf(),
+            ast,
+            state: self.scripts[index].state.clone(),
+            failed: false,
+        };
+        let _guard = ContextGuard::enter(context);
+        self.start(&mut script, "reload")?;
+        info!(Self, "已重新加载脚本 {}", path.display());
+        self.scripts[index] = script;
+        Ok(())
+    }
+}
+
+impl Default for ScriptEngine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 以脚本的状态对象为`this`调用函数，不重新执行顶层语句
+fn call(
+    engine: &Engine,
+    script: &mut Script,
+    name: &str,
+    args: impl FuncArgs,
+) -> ScriptResult<Dynamic> {
+    let options = CallFnOptions::new().eval_ast(false).bind_this_ptr(&mut script.state);
+    engine.call_fn_with_options(options, &mut Scope::new(), &script.ast, name, args)
+}
+
+fn normalize(path: &Path) -> PathBuf {
+    path.canonicalize().unwrap_or_else(|_| path.to_path_buf())
+}
@@ -0,0 +1,322 @@
+use std::{collections::HashMap, ffi::CString, sync::Mutex};
+
+use crate::error::{Error, Result};
+use crate::math::Uniform;
+use crate::{debug, error, FeedbackMode, GlObjectKind, GlObjects};
+
+/// 着色器程序
+///
+/// 对 OpenGL 程序对象的封装，在被释放时自动删除程序对象
+///
+/// # 示例
+///
+/// ```no_run
+/// use gle::{*, math::*};
+///
+/// const VS: &str = r#"
+/// #version 330 core
+/// layout(location = 0) in vec3 aPos;
+/// uniform mat4 uMvp;
+/// void main() {
+///     gl_Position = uMvp * vec4(aPos, 1.0);
+/// }
+/// "#;
+///
+/// const FS: &str = r#"
+/// #version 330 core
+/// out vec4 FragColor;
+/// void main() {
+///     FragColor = vec4(1.0);
+/// }
+/// "#;
+///
+/// fn render_init() {
+///     let program = Program::new(VS, FS).unwrap();
+///     program.bind();
+///     program.set("uMvp", &Mat4::IDENTITY);
+/// }
+/// ```
+///
+/// # 注解
+///
+/// 该类型的所有方法只能在渲染线程中调用
+pub struct Program {
+    id: u32,
+    locations: Mutex<HashMap<String, i32>>,
+}
+
+impl Program {
+    /// 由顶点着色器与片段着色器源码创建着色器程序
+    ///
+    /// # 参数
+    /// + `vs` - 顶点着色器源码
+    /// + `fs` - 片段着色器源码
+    ///
+    /// # 返回值
+    /// 成功时返回着色器程序，编译或链接失败时返回包含日志的错误
+    pub fn new(vs: &str, fs: &str) -> Result<Self> {
+        Self::from_sources(&[(gl::VERTEX_SHADER, vs), (gl::FRAGMENT_SHADER, fs)])
+    }
+
+    /// 由顶点着色器、几何着色器与片段着色器源码创建着色器程序
+    ///
+    /// # 参数
+    /// + `vs` - 顶点着色器源码
+    /// + `gs` - 几何着色器源码
+    /// + `fs` - 片段着色器源码
+    ///
+    /// # 返回值
+    /// 成功时返回着色器程序，编译或链接失败时返回包含日志的错误
+    pub fn with_geometry(vs: &str, gs: &str, fs: &str) -> Result<Self> {
+        Self::from_sources(&[
+            (gl::VERTEX_SHADER, vs),
+            (gl::GEOMETRY_SHADER, gs),
+            (gl::FRAGMENT_SHADER, fs),
+        ])
+    }
+
+    /// 由任意阶段的着色器源码创建着色器程序
+    ///
+    /// # 参数
+    /// + `sources` - 着色器阶段(如`gl::VERTEX_SHADER`)与源码的列表
+    ///
+    /// # 返回值
+    /// 成功时返回着色器程序，编译或链接失败时返回包含日志的错误
+    pub fn from_sources(sources: &[(u32, &str)]) -> Result<Self> {
+        let shaders = Self::compile_all(sources)?;
+        let id = unsafe { gl::CreateProgram() };
+        Self::link(id, &shaders)
+    }
+
+    /// 由顶点着色器与片段着色器源码创建着色器变体
+    ///
+    /// # 参数
+    /// + `vs` - 顶点着色器源码
+    /// + `fs` - 片段着色器源码
+    /// + `defines` - 宏定义列表，如`"USE_NORMAL_MAP"`或`"MAX_LIGHTS 8"`，将插入到各阶段源码的`#version`行之后
+    ///
+    /// # 返回值
+    /// 成功时返回着色器程序，编译或链接失败时返回包含日志的错误
+    pub fn with_defines(vs: &str, fs: &str, defines: &[&str]) -> Result<Self> {
+        let vs = inject_defines(vs, defines);
+        let fs = inject_defines(fs, defines);
+        Self::new(&vs, &fs)
+    }
+
+    /// 创建捕获顶点着色器输出的着色器程序，配合 [`TransformFeedback`](crate::TransformFeedback) 使用
+    ///
+    /// # 参数
+    /// + `vs` - 顶点着色器源码
+    /// + `fs` - 片段着色器源码，只捕获不绘制时可为`None`
+    /// + `varyings` - 捕获的顶点着色器输出变量名，交错模式下依次写入同一缓冲，分离模式下依次写入各个缓冲
+    /// + `mode` - 捕获模式
+    ///
+    /// # 返回值
+    /// 成功时返回着色器程序，变量名包含空字符时返回错误，编译或链接失败(如变量名不存在)时返回包含日志的错误
+    pub fn with_feedback(
+        vs: &str,
+        fs: Option<&str>,
+        varyings: &[&str],
+        mode: FeedbackMode,
+    ) -> Result<Self> {
+        // 先检查变量名，避免在返回错误前创建任何 OpenGL 对象
+        let names = varyings
+            .iter()
+            .map(|v| CString::new(*v).map_err(|e| Error::Shader(e.to_string())))
+            .collect::<Result<Vec<_>>>()?;
+        let pointers: Vec<*const std::ffi::c_char> = names.iter().map(|n| n.as_ptr()).collect();
+        let mode = match mode {
+            FeedbackMode::Interleaved => gl::INTERLEAVED_ATTRIBS,
+            FeedbackMode::Separate => gl::SEPARATE_ATTRIBS,
+        };
+        let mut sources = vec![(gl::VERTEX_SHADER, vs)];
+        if let Some(fs) = fs {
+            sources.push((gl::FRAGMENT_SHADER, fs));
+        }
+        Self::build(&sources, |id| unsafe {
+            gl::TransformFeedbackVaryings(id, pointers.len() as i32, pointers.as_ptr(), mode);
+        })
+    }
+
+    /// 创建着色器程序，并在链接前对程序对象进行额外设置(如变换反馈变量)
+    fn build<F: FnOnce(u32)>(sources: &[(u32, &str)], before_link: F) -> Result<Self> {
+        let shaders = Self::compile_all(sources)?;
+        let id = unsafe { gl::CreateProgram() };
+        before_link(id);
+        Self::link(id, &shaders)
+    }
+
+    /// 编译各阶段的着色器，任一阶段失败时删除已编译的着色器
+    fn compile_all(sources: &[(u32, &str)]) -> Result<Vec<u32>> {
+        let mut shaders = Vec::with_capacity(sources.len());
+        for &(stage, source) in sources {
+            match compile(stage, source) {
+                Ok(shader) => shaders.push(shader),
+                Err(e) => {
+                    for shader in shaders {
+                        unsafe { gl::DeleteShader(shader) };
+                    }
+                    error!(Self, "{}", e);
+                    return Err(e);
+                }
+            }
+        }
+        Ok(shaders)
+    }
+
+    /// 将着色器附加到程序对象并链接，之后删除着色器；链接失败时同时删除程序对象
+    fn link(id: u32, shaders: &[u32]) -> Result<Self> {
+        unsafe {
+            for &shader in shaders {
+                gl::AttachShader(id, shader);
+            }
+            gl::LinkProgram(id);
+            for &shader in shaders {
+                gl::DetachShader(id, shader);
+                gl::DeleteShader(shader);
+            }
+            let mut success = gl::FALSE as i32;
+            gl::GetProgramiv(id, gl::LINK_STATUS, &mut success);
+            if success == gl::FALSE as i32 {
+                let mut len = 0;
+                gl::GetProgramiv(id, gl::INFO_LOG_LENGTH, &mut len);
+                let mut buf = vec![0u8; len.max(1) as usize];
+                gl::GetProgramInfoLog(id, len, std::ptr::null_mut(), buf.as_mut_ptr() as *mut _);
+                gl::DeleteProgram(id);
+                let e = Error::Shader(String::from_utf8_lossy(&buf).trim_end_matches('\0').to_string());
+                error!(Self, "{}", e);
+                return Err(e);
+            }
+            debug!(Self, "着色器程序 {} 链接成功", id);
+            GlObjects::track(GlObjectKind::Program, id);
+            Ok(Self {
+                id,
+                locations: Mutex::new(HashMap::new()),
+            })
+        }
+    }
+
+    /// 获取 OpenGL 程序对象ID
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    /// 使用该着色器程序
+    pub fn bind(&self) {
+        unsafe { gl::UseProgram(self.id) };
+    }
+
+    /// 获取 uniform 变量的位置
+    ///
+    /// # 参数
+    /// + `name` - 变量名
+    ///
+    /// # 返回值
+    /// 返回变量位置，变量不存在或被优化掉时返回`-1`
+    pub fn location(&self, name: &str) -> i32 {
+        let mut locations = self.locations.lock().unwrap();
+        if let Some(&location) = locations.get(name) {
+            return location;
+        }
+        let cname = CString::new(name).unwrap();
+        let location = unsafe { gl::GetUniformLocation(self.id, cname.as_ptr()) };
+        locations.insert(name.to_string(), location);
+        location
+    }
+
+    /// 设置 uniform 变量的值
+    ///
+    /// # 参数
+    /// + `name` - 变量名
+    /// + `value` - 变量值
+    ///
+    /// # 注解
+    ///
+    /// 该着色器程序必须是当前正在使用的程序
+    pub fn set<U: Uniform + ?Sized>(&self, name: &str, value: &U) {
+        value.set_uniform(self.location(name));
+    }
+
+    /// 将 uniform 块绑定到指定的绑定点
+    ///
+    /// # 参数
+    /// + `name` - uniform 块名
+    /// + `binding` - 绑定点
+    pub fn set_block_binding(&self, name: &str, binding: u32) {
+        let cname = CString::new(name).unwrap();
+        unsafe {
+            let index = gl::GetUniformBlockIndex(self.id, cname.as_ptr());
+            if index != gl::INVALID_INDEX {
+                gl::UniformBlockBinding(self.id, index, binding);
+            }
+        }
+    }
+}
+
+impl Drop for Program {
+    fn drop(&mut self) {
+        GlObjects::untrack(GlObjectKind::Program, self.id);
+        unsafe { gl::DeleteProgram(self.id) };
+    }
+}
+
+fn compile(stage: u32, source: &str) -> Result<u32> {
+    let csource = CString::new(source).map_err(|e| Error::Shader(e.to_string()))?;
+    unsafe {
+        let shader = gl::CreateShader(stage);
+        gl::ShaderSource(shader, 1, &csource.as_ptr(), std::ptr::null());
+        gl::CompileShader(shader);
+        let mut success = gl::FALSE as i32;
+        gl::GetShaderiv(shader, gl::COMPILE_STATUS, &mut success);
+        if success == gl::FALSE as i32 {
+            let mut len = 0;
+            gl::GetShaderiv(shader, gl::INFO_LOG_LENGTH, &mut len);
+            let mut buf = vec![0u8; len.max(1) as usize];
+            gl::GetShaderInfoLog(shader, len, std::ptr::null_mut(), buf.as_mut_ptr() as *mut _);
+            gl::DeleteShader(shader);
+            let stage = match stage {
+                gl::VERTEX_SHADER => "顶点着色器",
+                gl::GEOMETRY_SHADER => "几何着色器",
+                gl::FRAGMENT_SHADER => "片段着色器",
+                _ => "着色器",
+            };
+            return Err(Error::Shader(format!(
+                "{}编译失败: {}",
+                stage,
+                String::from_utf8_lossy(&buf).trim_end_matches('\0')
+            )));
+        }
+        Ok(shader)
+    }
+}
+
+/// 在着色器源码的`#version`行之后插入宏定义
+///
+/// # 参数
+/// + `source` - 着色器源码
+/// + `defines` - 宏定义列表，每项将生成一行`#define`
+///
+/// # 返回值
+/// 返回插入宏定义后的源码，源码中没有`#version`行时宏定义插入到开头
+pub fn inject_defines(source: &str, defines: &[&str]) -> String {
+    if defines.is_empty() {
+        return source.to_string();
+    }
+    let block: String = defines.iter().map(|d| format!("#define {}\n", d)).collect();
+    match source.find("#version") {
+        Some(start) => {
+            let end = source[start..]
+                .find('\n')
+                .map_or(source.len(), |i| start + i + 1);
+            let mut out = String::with_capacity(source.len() + block.len() + 1);
+            out.push_str(&source[..end]);
+            if !out.ends_with('\n') {
+                out.push('\n');
+            }
+            out.push_str(&block);
+            out.push_str(&source[end..]);
+            out
+        }
+        None => block + source,
+    }
+}
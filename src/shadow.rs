@@ -0,0 +1,464 @@
+use crate::error::Result;
+use crate::math::*;
+use crate::{
+    ActiveLight, Camera, DepthBias, GlObjectKind, GlObjects, LightKind, LightingSystem, Program,
+    Projection, RenderStats, Renderer, UniformBuffer,
+};
+
+/// 阴影贴图数组的层数，每个投射阴影的聚光灯占用一层，平行光按级联数量占用一层或多层
+pub const MAX_SHADOWS: usize = 8;
+
+/// 平行光的最大级联数量
+pub const MAX_CASCADES: u32 = 4;
+
+/// 阴影 uniform 块`Shadows`的绑定点
+pub const SHADOWS_BINDING: u32 = 1;
+
+/// 阴影贴图数组所绑定的纹理单元
+pub const SHADOW_TEXTURE_UNIT: u32 = 12;
+
+/// 阴影设置
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ShadowSettings {
+    /// 深度偏移，用于消除阴影痤疮(默认值为0.0015)
+    pub depth_bias: f32,
+    /// 沿法线方向的采样点偏移，掠射角越大偏移越大(默认值为0.02)
+    pub normal_bias: f32,
+    /// 绘制阴影贴图时的光栅化深度偏移，为`None`时不偏移(默认值为 [`DepthBias::SHADOW`])
+    pub raster_bias: Option<DepthBias>,
+    /// PCF 过滤半径，单位为纹素，0 为不过滤(默认值为1)
+    pub pcf_radius: u32,
+    /// 平行光阴影覆盖区域的半边长，区域以摄像机为中心，仅在不使用级联时生效(默认值为30.0)
+    pub extent: f32,
+    /// 平行光阴影的深度范围，使用级联时为级联包围球之外向光源方向额外延伸的距离(默认值为100.0)
+    pub depth_range: f32,
+    /// 平行光的级联数量，范围为`[1, MAX_CASCADES]`，为1时不使用级联(默认值为1)
+    pub cascades: u32,
+    /// 级联划分系数，0 为均匀划分，1 为对数划分(默认值为0.75)
+    pub split_lambda: f32,
+    /// 级联阴影覆盖的最远距离，不超过摄像机远裁剪面(默认值为150.0)
+    pub max_distance: f32,
+    /// 聚光灯阴影的近裁剪面距离(默认值为0.1)
+    pub near: f32,
+}
+
+impl Default for ShadowSettings {
+    fn default() -> Self {
+        Self {
+            depth_bias: 0.0015,
+            normal_bias: 0.02,
+            raster_bias: Some(DepthBias::SHADOW),
+            pcf_radius: 1,
+            extent: 30.0,
+            depth_range: 100.0,
+            cascades: 1,
+            split_lambda: 0.75,
+            max_distance: 150.0,
+            near: 0.1,
+        }
+    }
+}
+
+impl ShadowSettings {
+    /// 获取光源所需的阴影贴图层数
+    ///
+    /// # 参数
+    /// + `kind` - 光源类型
+    pub fn layer_count(&self, kind: LightKind) -> usize {
+        match kind {
+            LightKind::Directional => self.cascades.clamp(1, MAX_CASCADES) as usize,
+            LightKind::Spot { .. } => 1,
+            LightKind::Point { .. } => 0,
+        }
+    }
+
+    /// 计算级联划分距离
+    ///
+    /// # 参数
+    /// + `near` - 摄像机近裁剪面距离
+    /// + `far` - 阴影覆盖的最远距离
+    ///
+    /// # 返回值
+    /// 返回每个级联的远端距离，长度等于级联数量
+    pub fn cascade_splits(&self, near: f32, far: f32) -> Vec<f32> {
+        let count = self.cascades.clamp(1, MAX_CASCADES);
+        let lambda = self.split_lambda.clamp(0.0, 1.0);
+        (1..=count)
+            .map(|i| {
+                let t = i as f32 / count as f32;
+                let log = near * (far / near).powf(t);
+                let uniform = near + (far - near) * t;
+                lambda * log + (1.0 - lambda) * uniform
+            })
+            .collect()
+    }
+
+    /// 计算平行光各级联的投影矩阵
+    ///
+    /// # 参数
+    /// + `light` - 有效平行光
+    /// + `camera` - 摄像机
+    /// + `resolution` - 阴影贴图分辨率
+    ///
+    /// # 返回值
+    /// 返回每个级联的`(世界空间到光源裁剪空间的矩阵, 级联远端距离)`
+    ///
+    /// # 注解
+    ///
+    /// 每个级联以视锥体切片的包围球为范围，并对齐到纹素，使摄像机旋转与平移时阴影边缘保持稳定
+    pub fn cascade_matrices(&self, light: &ActiveLight, camera: &Camera, resolution: u32) -> Vec<(Mat4, f32)> {
+        let direction = light.direction.normalize_or_zero();
+        let up = if direction.y.abs() > 0.99 { Vec3::Z } else { Vec3::Y };
+        let view = look_at(Vec3::ZERO, direction, up);
+        let (near, far) = camera.clip_planes();
+        let far = far.min(self.max_distance).max(near);
+        let mut previous = near;
+        self.cascade_splits(near, far)
+            .into_iter()
+            .map(|split| {
+                let corners = frustum_slice_corners(camera, previous, split);
+                previous = split;
+                let center = corners.iter().sum::<Vec3>() / 8.0;
+                let radius = corners
+                    .iter()
+                    .map(|c| c.distance(center))
+                    .fold(0.0, f32::max);
+                // 半径取整，避免摄像机旋转时投影尺寸抖动
+                let radius = (radius * 16.0).ceil() / 16.0;
+                let texel = radius * 2.0 / resolution as f32;
+                let center = view.transform_point3(center);
+                let center = Vec3::new(
+                    (center.x / texel).floor() * texel,
+                    (center.y / texel).floor() * texel,
+                    center.z,
+                );
+                let projection = orthographic(
+                    center.x - radius,
+                    center.x + radius,
+                    center.y - radius,
+                    center.y + radius,
+                    -center.z - radius - self.depth_range,
+                    -center.z + radius,
+                );
+                (projection * view, split)
+            })
+            .collect()
+    }
+
+    /// 计算光源的阴影投影矩阵
+    ///
+    /// # 参数
+    /// + `light` - 有效光源
+    /// + `camera` - 摄像机，平行光阴影区域以其为中心
+    /// + `resolution` - 阴影贴图分辨率，用于将平行光阴影区域对齐到纹素以减少闪烁
+    ///
+    /// # 返回值
+    /// 返回从世界空间到光源裁剪空间的矩阵，点光源返回`None`
+    pub fn light_matrix(&self, light: &ActiveLight, camera: &Camera, resolution: u32) -> Option<Mat4> {
+        let direction = light.direction.normalize_or_zero();
+        let up = if direction.y.abs() > 0.99 { Vec3::Z } else { Vec3::Y };
+        match light.light.kind {
+            LightKind::Directional => {
+                let view = look_at(Vec3::ZERO, direction, up);
+                // 在光源空间中将中心对齐到纹素
+                let texel = self.extent * 2.0 / resolution as f32;
+                let center = view.transform_point3(camera.position);
+                let center = Vec3::new(
+                    (center.x / texel).floor() * texel,
+                    (center.y / texel).floor() * texel,
+                    center.z,
+                );
+                let half_depth = self.depth_range * 0.5;
+                let projection = orthographic(
+                    center.x - self.extent,
+                    center.x + self.extent,
+                    center.y - self.extent,
+                    center.y + self.extent,
+                    -center.z - half_depth,
+                    -center.z + half_depth,
+                );
+                Some(projection * view)
+            }
+            LightKind::Spot {
+                range, outer_angle, ..
+            } => {
+                let view = look_at(light.position, light.position + direction, up);
+                let projection = perspective(outer_angle * 2.0, 1.0, self.near, range);
+                Some(projection * view)
+            }
+            LightKind::Point { .. } => None,
+        }
+    }
+}
+
+const DEPTH_VS: &str = r#"
+#version 330 core
+layout (location = 0) in vec3 aPosition;
+
+uniform mat4 uViewProj;
+uniform mat4 uModel;
+
+void main()
+{
+    gl_Position = uViewProj * uModel * vec4(aPosition, 1.0);
+}
+"#;
+
+const DEPTH_FS: &str = r#"
+#version 330 core
+
+void main()
+{
+}
+"#;
+
+/// 计算摄像机视锥体在指定距离区间内的八个角点
+fn frustum_slice_corners(camera: &Camera, near: f32, far: f32) -> [Vec3; 8] {
+    let mut slice = *camera;
+    slice.projection = match camera.projection {
+        Projection::Perspective { fov_y, .. } => Projection::Perspective { fov_y, near, far },
+        Projection::Orthographic { height, .. } => Projection::Orthographic { height, near, far },
+    };
+    let inverse = slice.view_projection().inverse();
+    let mut corners = [Vec3::ZERO; 8];
+    for (i, corner) in corners.iter_mut().enumerate() {
+        let ndc = Vec3::new(
+            if i & 1 == 0 { -1.0 } else { 1.0 },
+            if i & 2 == 0 { -1.0 } else { 1.0 },
+            if i & 4 == 0 { -1.0 } else { 1.0 },
+        );
+        *corner = inverse.project_point3(ndc);
+    }
+    corners
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+struct ShadowBlock {
+    matrices: [Mat4; MAX_SHADOWS],
+    params: [Vec4; MAX_SHADOWS],
+    splits: [Vec4; MAX_SHADOWS],
+    camera_view: Mat4,
+    debug: Vec4,
+}
+
+/// 阴影系统
+///
+/// 管理阴影贴图数组与帧缓冲，为 [`LightingSystem`] 分配了阴影贴图层的平行光与聚光灯渲染深度图，
+/// 并向有光照材质提供阴影矩阵与 PCF 参数
+///
+/// # 示例
+///
+/// ```
+/// use gle::{*, math::*};
+///
+/// // 在渲染线程中
+/// let mut shadows = ShadowSystem::new(2048).unwrap();
+/// lighting.update(&scene, &camera);
+/// shadows.render(&lighting, &camera, |program| {
+///     for (model, mesh) in &meshes {
+///         program.set("uModel", model);
+///         mesh.draw();
+///     }
+/// });
+/// queue.execute(&camera);
+/// ```
+///
+/// # 注解
+///
+/// 该类型只能在渲染线程中创建、使用与释放
+pub struct ShadowSystem {
+    program: Program,
+    fbo: u32,
+    depth: u32,
+    resolution: u32,
+    ubo: UniformBuffer,
+    /// 是否以颜色标示级联范围，用于调试级联划分(默认值为false)
+    pub debug_cascades: bool,
+}
+
+impl ShadowSystem {
+    /// 创建阴影系统
+    ///
+    /// # 参数
+    /// + `resolution` - 每层阴影贴图的边长
+    ///
+    /// # 返回值
+    /// 成功时返回阴影系统，内置着色器编译失败时返回错误
+    pub fn new(resolution: u32) -> Result<Self> {
+        let program = Program::new(DEPTH_VS, DEPTH_FS)?;
+        let (mut fbo, mut depth) = (0, 0);
+        unsafe {
+            gl::GenTextures(1, &mut depth);
+            gl::BindTexture(gl::TEXTURE_2D_ARRAY, depth);
+            gl::TexImage3D(
+                gl::TEXTURE_2D_ARRAY,
+                0,
+                gl::DEPTH_COMPONENT32F as i32,
+                resolution as i32,
+                resolution as i32,
+                MAX_SHADOWS as i32,
+                0,
+                gl::DEPTH_COMPONENT,
+                gl::FLOAT,
+                std::ptr::null(),
+            );
+            for (name, value) in [
+                (gl::TEXTURE_MIN_FILTER, gl::LINEAR),
+                (gl::TEXTURE_MAG_FILTER, gl::LINEAR),
+                (gl::TEXTURE_WRAP_S, gl::CLAMP_TO_BORDER),
+                (gl::TEXTURE_WRAP_T, gl::CLAMP_TO_BORDER),
+                (gl::TEXTURE_COMPARE_MODE, gl::COMPARE_REF_TO_TEXTURE),
+                (gl::TEXTURE_COMPARE_FUNC, gl::LEQUAL),
+            ] {
+                gl::TexParameteri(gl::TEXTURE_2D_ARRAY, name, value as i32);
+            }
+            let border = [1.0f32; 4];
+            gl::TexParameterfv(gl::TEXTURE_2D_ARRAY, gl::TEXTURE_BORDER_COLOR, border.as_ptr());
+            gl::BindTexture(gl::TEXTURE_2D_ARRAY, 0);
+
+            gl::GenFramebuffers(1, &mut fbo);
+            gl::BindFramebuffer(gl::FRAMEBUFFER, fbo);
+            gl::DrawBuffer(gl::NONE);
+            gl::ReadBuffer(gl::NONE);
+            gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
+        }
+        RenderStats::_track_render_target(depth_bytes(resolution));
+        GlObjects::track(GlObjectKind::Texture, depth);
+        GlObjects::track(GlObjectKind::Framebuffer, fbo);
+        Ok(Self {
+            program,
+            fbo,
+            depth,
+            resolution,
+            ubo: UniformBuffer::new::<ShadowBlock>(),
+            debug_cascades: false,
+        })
+    }
+
+    /// 获取阴影贴图数组的纹理ID
+    pub fn depth_texture(&self) -> u32 {
+        self.depth
+    }
+
+    /// 获取每层阴影贴图的边长
+    pub fn resolution(&self) -> u32 {
+        self.resolution
+    }
+
+    /// 渲染所有阴影贴图并更新阴影 uniform 缓冲
+    ///
+    /// # 参数
+    /// + `lighting` - 本帧已更新的光照系统
+    /// + `camera` - 摄像机
+    /// + `draw` - 绘制投射阴影物体的回调，参数为已绑定的深度着色器程序，回调应设置`uModel`并绘制网格
+    ///
+    /// # 注解
+    ///
+    /// 回调会对每个阴影贴图层(每个聚光灯或平行光的每个级联)各调用一次，调用期间已按 [`ShadowSettings::raster_bias`]
+    /// 设置深度偏移。调用结束后帧缓冲绑定、视口与深度偏移将被恢复
+    pub fn render<F: FnMut(&Program)>(&mut self, lighting: &LightingSystem, camera: &Camera, mut draw: F) {
+        let mut block = ShadowBlock {
+            matrices: [Mat4::IDENTITY; MAX_SHADOWS],
+            params: [Vec4::ZERO; MAX_SHADOWS],
+            splits: [Vec4::ZERO; MAX_SHADOWS],
+            camera_view: camera.view_matrix(),
+            debug: Vec4::new(self.debug_cascades as i32 as f32, 0.0, 0.0, 0.0),
+        };
+        let mut saved_fbo = 0;
+        let mut saved_viewport = [0i32; 4];
+        unsafe {
+            gl::GetIntegerv(gl::FRAMEBUFFER_BINDING, &mut saved_fbo);
+            gl::GetIntegerv(gl::VIEWPORT, saved_viewport.as_mut_ptr());
+            gl::BindFramebuffer(gl::FRAMEBUFFER, self.fbo);
+            gl::Viewport(0, 0, self.resolution as i32, self.resolution as i32);
+            gl::Enable(gl::DEPTH_TEST);
+            gl::DepthMask(gl::TRUE);
+        }
+        let saved_bias = Renderer::depth_bias();
+        self.program.bind();
+        for active in lighting.active_lights() {
+            let (Some(index), Some(settings)) = (active.shadow_index, active.light.shadow) else {
+                continue;
+            };
+            let layers = if settings.layer_count(active.light.kind) > 1 {
+                settings.cascade_matrices(active, camera, self.resolution)
+            } else if let Some(matrix) = settings.light_matrix(active, camera, self.resolution) {
+                vec![(matrix, f32::INFINITY)]
+            } else {
+                continue;
+            };
+            let count = layers.len();
+            for (i, (matrix, split)) in layers.into_iter().enumerate() {
+                let layer = index + i;
+                if layer >= MAX_SHADOWS {
+                    break;
+                }
+                block.matrices[layer] = matrix;
+                block.params[layer] = Vec4::new(
+                    settings.depth_bias,
+                    settings.normal_bias,
+                    settings.pcf_radius as f32,
+                    count as f32,
+                );
+                block.splits[layer] = Vec4::new(split.min(f32::MAX), 0.0, 0.0, 0.0);
+                unsafe {
+                    gl::FramebufferTextureLayer(
+                        gl::FRAMEBUFFER,
+                        gl::DEPTH_ATTACHMENT,
+                        self.depth,
+                        0,
+                        layer as i32,
+                    );
+                    gl::Clear(gl::DEPTH_BUFFER_BIT);
+                }
+                self.program.set("uViewProj", &matrix);
+                Renderer::set_depth_bias(settings.raster_bias);
+                draw(&self.program);
+            }
+        }
+        Renderer::set_depth_bias(saved_bias);
+        unsafe {
+            gl::BindFramebuffer(gl::FRAMEBUFFER, saved_fbo as u32);
+            gl::Viewport(
+                saved_viewport[0],
+                saved_viewport[1],
+                saved_viewport[2],
+                saved_viewport[3],
+            );
+        }
+        self.ubo.update(&block);
+        self.bind();
+    }
+
+    /// 将阴影贴图与阴影 uniform 缓冲绑定到 [`SHADOW_TEXTURE_UNIT`] 与 [`SHADOWS_BINDING`]
+    ///
+    /// # 注解
+    ///
+    /// [`ShadowSystem::render`] 已自动绑定，仅在其他代码占用了对应的纹理单元或绑定点后需要调用
+    pub fn bind(&self) {
+        unsafe {
+            gl::ActiveTexture(gl::TEXTURE0 + SHADOW_TEXTURE_UNIT);
+            gl::BindTexture(gl::TEXTURE_2D_ARRAY, self.depth);
+            gl::ActiveTexture(gl::TEXTURE0);
+        }
+        self.ubo.bind_base(SHADOWS_BINDING);
+    }
+}
+
+impl Drop for ShadowSystem {
+    fn drop(&mut self) {
+        RenderStats::_track_render_target(-depth_bytes(self.resolution));
+        GlObjects::untrack(GlObjectKind::Texture, self.depth);
+        GlObjects::untrack(GlObjectKind::Framebuffer, self.fbo);
+        unsafe {
+            gl::DeleteFramebuffers(1, &self.fbo);
+            gl::DeleteTextures(1, &self.depth);
+        }
+    }
+}
+
+/// 阴影贴图数组的字节数，每层为 32 位浮点深度
+fn depth_bytes(resolution: u32) -> i64 {
+    MAX_SHADOWS as i64 * resolution as i64 * resolution as i64 * 4
+}
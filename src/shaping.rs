@@ -0,0 +1,211 @@
+use std::fs;
+use std::path::Path;
+
+use rustybuzz::{Direction, Face, UnicodeBuffer};
+use unicode_bidi::BidiInfo;
+
+use crate::error::{Error, Result};
+use crate::math::*;
+use crate::{PositionedGlyph, SdfFontData};
+
+/// 整形后的文本
+#[derive(Debug, Clone, PartialEq)]
+pub struct ShapedText {
+    /// 按视觉顺序排列的字形，位置相对于文本起点(首行基线的左端)
+    pub glyphs: Vec<PositionedGlyph>,
+    /// 最宽一行的宽度与所有行的总高度
+    pub size: Vec2,
+    /// 首段的基础方向是否为从右到左
+    pub rtl: bool,
+}
+
+/// 文本整形器
+///
+/// 对每行文本执行 Unicode 双向算法，将其划分为方向一致的片段并按视觉顺序排列，
+/// 再以 HarfBuzz 兼容的整形引擎处理连字、字形变体、组合字符与标记定位，
+/// 使阿拉伯文、希伯来文、天城文等复杂文字以及由零宽连接符组成的表情序列正确显示
+///
+/// 整形得到的是字体中的字形编号，应以 [`TextShaper::required_glyphs`] 收集所需字形，
+/// 再由 [`SdfFontBaker::bake_indices`](crate::SdfFontBaker::bake_indices) 以同一字体文件烘焙
+///
+/// # 示例
+///
+/// ```no_run
+/// use gle::*;
+/// use gle::math::*;
+///
+/// let shaper = TextShaper::load("assets/NotoSansArabic-Regular.ttf").unwrap();
+/// let texts = ["مرحبا بالعالم", "Hello عالم!"];
+/// let glyphs = shaper.required_glyphs(&texts);
+/// let data = SdfFontBaker::default().bake_indices(shaper.font_data(), &glyphs).unwrap();
+///
+/// fn render_loop(text: &mut TextRenderer, font: &SdfFont, shaper: &TextShaper) {
+///     let style = TextStyle::default();
+///     let shaped = shaper.shape("مرحبا بالعالم", font.data(), style.size);
+///     text.begin(&orthographic(0.0, 1280.0, 0.0, 720.0, -1.0, 1.0));
+///     text.draw_glyphs(font, &shaped.glyphs, Vec2::new(1240.0 - shaped.size.x, 640.0), &style);
+///     text.end();
+/// }
+/// ```
+///
+/// # 注解
+///
+/// 距离场只保存字形轮廓，彩色表情(CBDT、COLR、SVG 等)以单色轮廓显示，不含轮廓的彩色位图字形无法显示
+#[derive(Debug, Clone)]
+pub struct TextShaper {
+    data: Vec<u8>,
+    index: u32,
+}
+
+impl TextShaper {
+    /// 由字体文件内容创建整形器
+    ///
+    /// # 参数
+    /// + `data` - TrueType 或 OpenType 字体文件内容
+    /// + `index` - 字体集合中的字体序号，单个字体文件为 0
+    ///
+    /// # 返回值
+    /// 成功时返回整形器，字体无法解析时返回错误
+    pub fn new(data: Vec<u8>, index: u32) -> Result<Self> {
+        if Face::from_slice(&data, index).is_none() {
+            return Err(Error::Parse("无法解析字体".to_string()));
+        }
+        Ok(Self { data, index })
+    }
+
+    /// 从字体文件创建整形器
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        Self::new(fs::read(path)?, 0)
+            .map_err(|e| Error::Parse(format!("{}: {}", path.display(), e)))
+    }
+
+    /// 获取字体文件内容
+    pub fn font_data(&self) -> &[u8] {
+        &self.data
+    }
+
+    fn face(&self) -> Face<'_> {
+        // 创建时已验证可以解析
+        Face::from_slice(&self.data, self.index).unwrap()
+    }
+
+    /// 收集显示若干文本所需的所有字形编号
+    ///
+    /// # 返回值
+    /// 返回去重并排序后的字形编号
+    pub fn required_glyphs(&self, texts: &[&str]) -> Vec<u16> {
+        let face = self.face();
+        let mut glyphs = vec![0];
+        for text in texts {
+            for line in text.split('\n') {
+                for (run, rtl) in visual_runs(line) {
+                    let buffer = shape_run(&face, run, rtl);
+                    glyphs.extend(buffer.glyph_infos().iter().map(|info| info.glyph_id as u16));
+                }
+            }
+        }
+        glyphs.sort_unstable();
+        glyphs.dedup();
+        glyphs
+    }
+
+    /// 整形并排版文本
+    ///
+    /// # 参数
+    /// + `text` - 文本，`\n`换行
+    /// + `font` - 以同一字体文件烘焙的距离场字体，未烘焙的字形只保留步进
+    /// + `size` - 字号，单位为像素
+    ///
+    /// # 返回值
+    /// 返回整形后的文本，从右到左的行在 [`ShapedText::size`] 的宽度内右对齐
+    pub fn shape(&self, text: &str, font: &SdfFontData, size: f32) -> ShapedText {
+        let face = self.face();
+        let units = size / face.units_per_em() as f32;
+        let atlas_scale = size / font.size();
+        let line_height = font.line_height(size);
+
+        let mut lines = Vec::new();
+        let mut first_rtl = None;
+        for (row, line) in text.split('\n').enumerate() {
+            let baseline = -(row as f32) * line_height;
+            let mut glyphs = Vec::new();
+            let mut pen = 0.0;
+            let rtl = paragraph_rtl(line);
+            first_rtl.get_or_insert(rtl);
+            for (run, run_rtl) in visual_runs(line) {
+                let buffer = shape_run(&face, run, run_rtl);
+                let positions = buffer.glyph_positions();
+                for (info, position) in buffer.glyph_infos().iter().zip(positions) {
+                    let index = info.glyph_id as u16;
+                    if let Some(glyph) = font.glyph(index).filter(|g| g.size.x > 0.0) {
+                        let origin = Vec2::new(
+                            pen + position.x_offset as f32 * units,
+                            baseline + position.y_offset as f32 * units,
+                        );
+                        glyphs.push(PositionedGlyph {
+                            glyph: index,
+                            position: origin + glyph.offset * atlas_scale,
+                            size: glyph.size * atlas_scale,
+                            uv_rect: glyph.uv_rect,
+                        });
+                    }
+                    pen += position.x_advance as f32 * units;
+                }
+            }
+            lines.push((glyphs, pen, rtl));
+        }
+
+        let width = lines.iter().map(|(_, w, _)| *w).fold(0.0, f32::max);
+        let mut glyphs = Vec::new();
+        for (line, line_width, rtl) in lines.iter_mut() {
+            let shift = if *rtl { width - *line_width } else { 0.0 };
+            glyphs.extend(line.drain(..).map(|mut g| {
+                g.position.x += shift;
+                g
+            }));
+        }
+        ShapedText {
+            glyphs,
+            size: Vec2::new(width, lines.len() as f32 * line_height),
+            rtl: first_rtl.unwrap_or(false),
+        }
+    }
+}
+
+/// 按视觉顺序返回行内方向一致的片段
+fn visual_runs(line: &str) -> Vec<(&str, bool)> {
+    if line.is_empty() {
+        return Vec::new();
+    }
+    let bidi = BidiInfo::new(line, None);
+    let mut runs = Vec::new();
+    for paragraph in &bidi.paragraphs {
+        let (levels, ranges) = bidi.visual_runs(paragraph, paragraph.range.clone());
+        for range in ranges {
+            let rtl = levels[range.start].is_rtl();
+            runs.push((&line[range], rtl));
+        }
+    }
+    runs
+}
+
+/// 行的基础方向是否为从右到左
+fn paragraph_rtl(line: &str) -> bool {
+    BidiInfo::new(line, None)
+        .paragraphs
+        .first()
+        .is_some_and(|p| p.level.is_rtl())
+}
+
+fn shape_run(face: &Face, text: &str, rtl: bool) -> rustybuzz::GlyphBuffer {
+    let mut buffer = UnicodeBuffer::new();
+    buffer.push_str(text);
+    buffer.guess_segment_properties();
+    buffer.set_direction(if rtl {
+        Direction::RightToLeft
+    } else {
+        Direction::LeftToRight
+    });
+    rustybuzz::shape(face, &[], buffer)
+}
@@ -0,0 +1,267 @@
+use std::f32::consts::{FRAC_PI_2, PI};
+
+use crate::error::Result;
+use crate::math::*;
+use crate::{draw_fullscreen_triangle, Camera, Program, FOG_BINDING, FOG_GLSL};
+
+const SKY_VS: &str = r#"
+#version 330 core
+uniform mat4 uInvViewProj;
+
+out vec3 vDirection;
+
+void main()
+{
+    vec2 p = vec2(float((gl_VertexID << 1) & 2), float(gl_VertexID & 2)) * 2.0 - 1.0;
+    vec4 world = uInvViewProj * vec4(p, 1.0, 1.0);
+    vDirection = world.xyz / world.w;
+    // 深度为远裁剪面，只在未被覆盖的像素上绘制
+    gl_Position = vec4(p, 1.0, 1.0);
+}
+"#;
+
+const SKY_FS_HEAD: &str = r#"
+#version 330 core
+in vec3 vDirection;
+out vec4 FragColor;
+
+uniform vec3 uCameraPos;
+"#;
+
+const SKY_FS_MAIN: &str = r#"
+uniform vec3 uPerezA;
+uniform vec3 uPerezB;
+uniform vec3 uPerezC;
+uniform vec3 uPerezD;
+uniform vec3 uPerezE;
+uniform vec3 uZenith;
+uniform vec3 uSunDirection;
+uniform vec3 uSunColor;
+uniform float uSunCosSize;
+uniform float uIntensity;
+uniform vec3 uGroundColor;
+uniform bool uFog;
+
+vec3 perez(float cosTheta, float gamma, float cosGamma)
+{
+    return (1.0 + uPerezA * exp(uPerezB / cosTheta))
+        * (1.0 + uPerezC * exp(uPerezD * gamma) + uPerezE * cosGamma * cosGamma);
+}
+
+vec3 xyYToRgb(vec3 xyY)
+{
+    float Y = xyY.x;
+    float x = xyY.y;
+    float y = max(xyY.z, 1e-4);
+    vec3 XYZ = vec3(x * Y / y, Y, (1.0 - x - y) * Y / y);
+    return mat3(
+        3.2406, -0.9689, 0.0557,
+        -1.5372, 1.8758, -0.2040,
+        -0.4986, 0.0415, 1.0570) * XYZ;
+}
+
+void main()
+{
+    vec3 dir = normalize(vDirection);
+    float cosTheta = max(dir.y, 0.01);
+    float cosGamma = clamp(dot(dir, uSunDirection), -1.0, 1.0);
+    float gamma = acos(cosGamma);
+    vec3 color = max(xyYToRgb(uZenith * perez(cosTheta, gamma, cosGamma)), 0.0) * uIntensity;
+    if (dir.y < 0.0) {
+        color = mix(color, uGroundColor * uIntensity * uZenith.x, smoothstep(0.0, 0.1, -dir.y));
+    } else if (cosGamma > uSunCosSize) {
+        float edge = smoothstep(uSunCosSize, mix(uSunCosSize, 1.0, 0.3), cosGamma);
+        color += uSunColor * edge;
+    }
+    if (uFog) {
+        color = applySkyFog(color, dir, uCameraPos);
+    }
+    FragColor = vec4(color, 1.0);
+}
+"#;
+
+/// 程序化天空参数(Preetham 解析天空模型)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SkyParams {
+    /// 指向太阳的单位方向
+    pub sun_direction: Vec3,
+    /// 大气浑浊度，范围约`[2, 10]`，越大越雾蒙
+    pub turbidity: f32,
+    /// 天空亮度缩放，用于将模型输出的亮度(千坎德拉每平方米)换算到场景单位
+    pub intensity: f32,
+    /// 太阳圆盘的辐射亮度缩放
+    pub sun_intensity: f32,
+    /// 太阳圆盘的角半径，单位为弧度
+    pub sun_size: f32,
+    /// 地平线以下的地面反照率
+    pub ground_color: Vec3,
+    /// 是否对天空施加 [`Fog`](crate::Fog) 的雾效
+    pub fog: bool,
+}
+
+impl Default for SkyParams {
+    fn default() -> Self {
+        Self {
+            sun_direction: Vec3::new(0.3, 0.6, -0.5).normalize(),
+            turbidity: 3.0,
+            intensity: 0.05,
+            sun_intensity: 20.0,
+            sun_size: 0.0093,
+            ground_color: Vec3::new(0.3, 0.28, 0.25),
+            fog: true,
+        }
+    }
+}
+
+impl SkyParams {
+    /// 太阳天顶角(弧度)，地平线以下时截断为略小于`π/2`
+    fn sun_zenith(&self) -> f32 {
+        self.sun_direction
+            .normalize_or(Vec3::Y)
+            .y
+            .clamp(-1.0, 1.0)
+            .acos()
+            .min(FRAC_PI_2 - 0.01)
+    }
+
+    /// 估计经大气衰减后的阳光颜色(线性空间，最大分量为 1)
+    ///
+    /// 太阳越接近地平线，穿过的大气越厚，蓝色成分衰减越多。太阳位于地平线以下时返回零
+    pub fn sun_color(&self) -> Vec3 {
+        let elevation = self.sun_direction.normalize_or(Vec3::Y).y;
+        if elevation <= -0.05 {
+            return Vec3::ZERO;
+        }
+        // 相对大气质量(Kasten-Young 近似)
+        let zenith = elevation.clamp(-1.0, 1.0).acos().to_degrees().min(93.0);
+        let mass = 1.0 / (zenith.to_radians().cos() + 0.50572 * (96.07995 - zenith).powf(-1.6364));
+        let beta = Vec3::new(0.0065, 0.0120, 0.0280) * (1.0 + 0.25 * (self.turbidity - 2.0));
+        let transmittance = (-beta * mass * 8.0).exp();
+        let fade = ((elevation + 0.05) / 0.1).clamp(0.0, 1.0);
+        transmittance / transmittance.max_element().max(1e-4) * fade
+    }
+
+    /// 估计天空在水平面上产生的环境光(线性空间)
+    pub fn ambient(&self) -> Vec3 {
+        let (_, zenith) = self.perez();
+        let elevation = self.sun_direction.normalize_or(Vec3::Y).y;
+        let day = ((elevation + 0.1) / 0.3).clamp(0.0, 1.0);
+        xyy_to_rgb(zenith).max(Vec3::ZERO) * self.intensity * day
+    }
+
+    /// 计算 Perez 分布系数`[A, B, C, D, E]`(每项依次为 Y、x、y)与天顶值
+    fn perez(&self) -> ([Vec3; 5], Vec3) {
+        let t = self.turbidity.clamp(1.0, 32.0);
+        let coefficients = [
+            Vec3::new(0.1787 * t - 1.4630, -0.0193 * t - 0.2592, -0.0167 * t - 0.2608),
+            Vec3::new(-0.3554 * t + 0.4275, -0.0665 * t + 0.0008, -0.0950 * t + 0.0092),
+            Vec3::new(-0.0227 * t + 5.3251, -0.0004 * t + 0.2125, -0.0079 * t + 0.2102),
+            Vec3::new(0.1206 * t - 2.5771, -0.0641 * t - 0.8989, -0.0441 * t - 1.6537),
+            Vec3::new(-0.0670 * t + 0.3703, -0.0033 * t + 0.0452, -0.0109 * t + 0.0529),
+        ];
+        let theta = self.sun_zenith();
+        let chi = (4.0 / 9.0 - t / 120.0) * (PI - 2.0 * theta);
+        let luminance = ((4.0453 * t - 4.9710) * chi.tan() - 0.2155 * t + 2.4192).max(0.0);
+        let (t2, th, th2, th3) = (t * t, theta, theta * theta, theta * theta * theta);
+        let x = t2 * (0.00166 * th3 - 0.00375 * th2 + 0.00209 * th)
+            + t * (-0.02903 * th3 + 0.06377 * th2 - 0.03202 * th + 0.00394)
+            + (0.11693 * th3 - 0.21196 * th2 + 0.06052 * th + 0.25886);
+        let y = t2 * (0.00275 * th3 - 0.00610 * th2 + 0.00317 * th)
+            + t * (-0.04214 * th3 + 0.08970 * th2 - 0.04153 * th + 0.00516)
+            + (0.15346 * th3 - 0.26756 * th2 + 0.06670 * th + 0.26688);
+        // 以天顶方向的分布值归一化：F(0, θs)
+        let [a, b, c, d, e] = coefficients;
+        let f0 = (Vec3::ONE + a * b.exp()) * (Vec3::ONE + c * (d * theta).exp() + e * theta.cos().powi(2));
+        let zenith = Vec3::new(luminance, x, y) / f0;
+        (coefficients, zenith)
+    }
+}
+
+fn xyy_to_rgb(xyy: Vec3) -> Vec3 {
+    let (luminance, x, y) = (xyy.x, xyy.y, xyy.z.max(1e-4));
+    let xyz = Vec3::new(x * luminance / y, luminance, (1.0 - x - y) * luminance / y);
+    Mat3::from_cols(
+        Vec3::new(3.2406, -0.9689, 0.0557),
+        Vec3::new(-1.5372, 1.8758, -0.2040),
+        Vec3::new(-0.4986, 0.0415, 1.0570),
+    ) * xyz
+}
+
+/// 程序化天空渲染器
+///
+/// 以 Preetham 解析模型根据太阳方向与浑浊度计算天空颜色，并绘制太阳圆盘，可替代静态天空盒用于动态的室外场景。
+/// 天空绘制在远裁剪面上，应在不透明物体之后绘制以避免过度绘制
+///
+/// # 示例
+///
+/// ```
+/// use gle::{*, math::*};
+///
+/// fn render_loop(sky: &SkyRenderer, camera: &Camera) {
+///     let mut params = SkyParams::default();
+///     params.sun_direction = Vec3::new(0.0, 0.2, -1.0).normalize();
+///     // 绘制不透明物体……
+///     sky.draw(camera, &params);
+/// }
+/// ```
+///
+/// # 注解
+///
+/// 输出为 HDR 线性颜色，应渲染到 [`HdrPipeline`](crate::HdrPipeline) 等浮点目标中。
+/// 该类型只能在渲染线程中创建、使用与释放
+pub struct SkyRenderer {
+    program: Program,
+}
+
+impl SkyRenderer {
+    /// 创建程序化天空渲染器
+    pub fn new() -> Result<Self> {
+        let fs = format!("{}{}{}", SKY_FS_HEAD, FOG_GLSL, SKY_FS_MAIN);
+        let program = Program::new(SKY_VS, &fs)?;
+        program.set_block_binding("Fog", FOG_BINDING);
+        Ok(Self { program })
+    }
+
+    /// 绘制天空
+    ///
+    /// # 参数
+    /// + `camera` - 摄像机
+    /// + `params` - 天空参数
+    ///
+    /// # 注解
+    ///
+    /// 绘制时关闭深度写入并使用`LEQUAL`深度比较，结束后恢复`LESS`与深度写入
+    pub fn draw(&self, camera: &Camera, params: &SkyParams) {
+        let (coefficients, zenith) = params.perez();
+        let sun = params.sun_direction.normalize_or(Vec3::Y);
+        // 只移除平移，保证远处方向计算的精度
+        let view = Mat4::from_quat(camera.rotation.inverse());
+        let inv_view_proj = (camera.projection_matrix() * view).inverse();
+        self.program.bind();
+        self.program.set("uInvViewProj", &inv_view_proj);
+        self.program.set("uCameraPos", &camera.position);
+        for (name, value) in ["uPerezA", "uPerezB", "uPerezC", "uPerezD", "uPerezE"]
+            .iter()
+            .zip(coefficients.iter())
+        {
+            self.program.set(name, value);
+        }
+        self.program.set("uZenith", &zenith);
+        self.program.set("uSunDirection", &sun);
+        self.program
+            .set("uSunColor", &(params.sun_color() * params.sun_intensity));
+        self.program.set("uSunCosSize", &params.sun_size.cos());
+        self.program.set("uIntensity", &params.intensity);
+        self.program.set("uGroundColor", &params.ground_color);
+        self.program.set("uFog", &params.fog);
+        unsafe {
+            gl::DepthMask(gl::FALSE);
+            gl::DepthFunc(gl::LEQUAL);
+        }
+        draw_fullscreen_triangle();
+        unsafe {
+            gl::DepthMask(gl::TRUE);
+            gl::DepthFunc(gl::LESS);
+        }
+    }
+}
@@ -0,0 +1,546 @@
+use std::cell::Cell;
+
+use crate::math::*;
+
+/// 轴对齐包围盒
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Aabb {
+    /// 最小角点
+    pub min: Vec3,
+    /// 最大角点
+    pub max: Vec3,
+}
+
+impl Aabb {
+    /// 空包围盒，与任何包围盒合并都得到后者
+    pub const EMPTY: Aabb = Aabb {
+        min: Vec3::splat(f32::INFINITY),
+        max: Vec3::splat(f32::NEG_INFINITY),
+    };
+
+    /// 由两个角点创建包围盒
+    ///
+    /// # 参数
+    /// + `min` - 最小角点
+    /// + `max` - 最大角点
+    pub fn new(min: Vec3, max: Vec3) -> Self {
+        Self { min, max }
+    }
+
+    /// 由中心与半边长创建包围盒
+    ///
+    /// # 参数
+    /// + `center` - 中心
+    /// + `half_extents` - 各轴方向上的半边长
+    pub fn from_center_half_extents(center: Vec3, half_extents: Vec3) -> Self {
+        Self {
+            min: center - half_extents,
+            max: center + half_extents,
+        }
+    }
+
+    /// 创建包含所有给定点的最小包围盒
+    ///
+    /// # 参数
+    /// + `points` - 点集，为空时返回 [`Aabb::EMPTY`]
+    pub fn from_points<I: IntoIterator<Item = Vec3>>(points: I) -> Self {
+        points.into_iter().fold(Self::EMPTY, |aabb, p| Self {
+            min: aabb.min.min(p),
+            max: aabb.max.max(p),
+        })
+    }
+
+    /// 判断包围盒是否为空
+    pub fn is_empty(&self) -> bool {
+        self.min.cmpgt(self.max).any()
+    }
+
+    /// 获取中心
+    pub fn center(&self) -> Vec3 {
+        (self.min + self.max) * 0.5
+    }
+
+    /// 获取半边长
+    pub fn half_extents(&self) -> Vec3 {
+        (self.max - self.min) * 0.5
+    }
+
+    /// 获取表面积
+    pub fn surface_area(&self) -> f32 {
+        if self.is_empty() {
+            return 0.0;
+        }
+        let d = self.max - self.min;
+        2.0 * (d.x * d.y + d.y * d.z + d.z * d.x)
+    }
+
+    /// 获取两个包围盒的并集
+    pub fn union(&self, other: &Aabb) -> Aabb {
+        Aabb {
+            min: self.min.min(other.min),
+            max: self.max.max(other.max),
+        }
+    }
+
+    /// 判断两个包围盒是否相交
+    pub fn intersects(&self, other: &Aabb) -> bool {
+        self.min.cmple(other.max).all() && self.max.cmpge(other.min).all()
+    }
+
+    /// 判断点是否位于包围盒内
+    pub fn contains_point(&self, p: Vec3) -> bool {
+        self.min.cmple(p).all() && self.max.cmpge(p).all()
+    }
+
+    /// 获取包围盒到点的最短距离的平方，点位于包围盒内时为零
+    pub fn distance_squared(&self, p: Vec3) -> f32 {
+        let d = (self.min - p).max(p - self.max).max(Vec3::ZERO);
+        d.length_squared()
+    }
+
+    /// 获取经过仿射变换后的包围盒
+    ///
+    /// # 参数
+    /// + `m` - 仿射变换矩阵
+    pub fn transformed(&self, m: &Mat4) -> Aabb {
+        let center = m.transform_point3(self.center());
+        let h = self.half_extents();
+        let abs = Mat3::from_cols(
+            m.x_axis.truncate().abs(),
+            m.y_axis.truncate().abs(),
+            m.z_axis.truncate().abs(),
+        );
+        Aabb::from_center_half_extents(center, abs * h)
+    }
+
+    /// 计算射线与包围盒的交点
+    ///
+    /// # 参数
+    /// + `ray` - 射线
+    ///
+    /// # 返回值
+    /// 若射线与包围盒相交，返回最近交点的射线参数，射线起点位于包围盒内时返回`0.0`，否则返回`None`
+    pub fn ray_intersection(&self, ray: &Ray) -> Option<f32> {
+        let inv = ray.direction.recip();
+        let t1 = (self.min - ray.origin) * inv;
+        let t2 = (self.max - ray.origin) * inv;
+        let t_min = t1.min(t2).max_element().max(0.0);
+        let t_max = t1.max(t2).min_element();
+        (t_min <= t_max).then_some(t_min)
+    }
+}
+
+/// 射线
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Ray {
+    /// 起点
+    pub origin: Vec3,
+    /// 方向，无需归一化，但射线参数以该向量的长度为单位
+    pub direction: Vec3,
+}
+
+impl Ray {
+    /// 创建射线
+    pub fn new(origin: Vec3, direction: Vec3) -> Self {
+        Self { origin, direction }
+    }
+
+    /// 获取射线参数对应的点
+    pub fn at(&self, t: f32) -> Vec3 {
+        self.origin + self.direction * t
+    }
+}
+
+/// 视锥体
+///
+/// 由六个朝内的平面组成，每个平面以`(法线, 距离)`表示为 [`Vec4`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Frustum {
+    /// 左、右、下、上、近、远六个平面
+    pub planes: [Vec4; 6],
+}
+
+impl Frustum {
+    /// 从投影观察矩阵中提取视锥体
+    ///
+    /// # 参数
+    /// + `view_projection` - OpenGL 约定的投影矩阵与观察矩阵之积
+    pub fn from_matrix(view_projection: &Mat4) -> Self {
+        let m = view_projection.transpose();
+        let planes = [
+            m.w_axis + m.x_axis,
+            m.w_axis - m.x_axis,
+            m.w_axis + m.y_axis,
+            m.w_axis - m.y_axis,
+            m.w_axis + m.z_axis,
+            m.w_axis - m.z_axis,
+        ]
+        .map(|p| p / p.truncate().length());
+        Self { planes }
+    }
+
+    /// 判断包围盒是否与视锥体相交(保守判断，可能将视锥体外的包围盒判为相交)
+    pub fn intersects_aabb(&self, aabb: &Aabb) -> bool {
+        self.planes.iter().all(|p| {
+            let n = p.truncate();
+            let positive = Vec3::select(n.cmpge(Vec3::ZERO), aabb.max, aabb.min);
+            n.dot(positive) + p.w >= 0.0
+        })
+    }
+
+    /// 判断球体是否与视锥体相交
+    pub fn intersects_sphere(&self, center: Vec3, radius: f32) -> bool {
+        self.planes
+            .iter()
+            .all(|p| p.truncate().dot(center) + p.w >= -radius)
+    }
+}
+
+/// 空间索引中对象的标识
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct SpatialId(usize);
+
+enum BvhNode {
+    Leaf { aabb: Aabb, items: Vec<usize> },
+    Branch { aabb: Aabb, left: usize, right: usize },
+}
+
+impl BvhNode {
+    fn aabb(&self) -> &Aabb {
+        match self {
+            BvhNode::Leaf { aabb, .. } | BvhNode::Branch { aabb, .. } => aabb,
+        }
+    }
+}
+
+/// 层次包围盒(BVH)空间索引
+///
+/// 适用于静态与动态对象，支持视锥体查询、射线查询与最近邻查询，可作为剔除、拾取与碰撞粗检测的基础
+///
+/// # 示例
+///
+/// ```
+/// use gle::{*, math::*};
+///
+/// let mut bvh = Bvh::new();
+/// let id = bvh.insert(Aabb::new(Vec3::ZERO, Vec3::ONE), "box");
+/// bvh.rebuild();
+/// let hit = bvh.raycast(&Ray::new(Vec3::new(0.5, 0.5, -5.0), Vec3::Z));
+/// assert_eq!(hit.map(|(id, _)| id), Some(id));
+/// ```
+///
+/// # 注解
+///
+/// 插入、移除与更新操作不会立即重建树结构，新插入或更新过的对象在下次 [`Bvh::rebuild`] 之前以线性方式参与查询，
+/// 因此对于每帧移动的对象，应在每帧查询前调用一次 [`Bvh::rebuild`]
+pub struct Bvh<T> {
+    items: Vec<Option<(Aabb, T)>>,
+    free: Vec<usize>,
+    nodes: Vec<BvhNode>,
+    pending: Vec<usize>,
+    in_tree: Vec<bool>,
+    leaf_size: usize,
+}
+
+impl<T> Default for Bvh<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Bvh<T> {
+    /// 创建一个空的空间索引
+    pub fn new() -> Self {
+        Self {
+            items: Vec::new(),
+            free: Vec::new(),
+            nodes: Vec::new(),
+            pending: Vec::new(),
+            in_tree: Vec::new(),
+            leaf_size: 4,
+        }
+    }
+
+    /// 获取对象数量
+    pub fn len(&self) -> usize {
+        self.items.len() - self.free.len()
+    }
+
+    /// 判断是否为空
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// 插入一个对象
+    ///
+    /// # 参数
+    /// + `aabb` - 对象的包围盒
+    /// + `value` - 对象
+    ///
+    /// # 返回值
+    /// 返回对象的标识
+    pub fn insert(&mut self, aabb: Aabb, value: T) -> SpatialId {
+        let index = match self.free.pop() {
+            Some(index) => {
+                self.items[index] = Some((aabb, value));
+                self.in_tree[index] = false;
+                index
+            }
+            None => {
+                self.items.push(Some((aabb, value)));
+                self.in_tree.push(false);
+                self.items.len() - 1
+            }
+        };
+        self.pending.push(index);
+        SpatialId(index)
+    }
+
+    /// 移除一个对象
+    ///
+    /// # 参数
+    /// + `id` - 对象的标识
+    ///
+    /// # 返回值
+    /// 返回被移除的对象，若对象不存在则返回`None`
+    pub fn remove(&mut self, id: SpatialId) -> Option<T> {
+        let (_, value) = self.items.get_mut(id.0)?.take()?;
+        self.pending.retain(|&i| i != id.0);
+        self.in_tree[id.0] = false;
+        self.free.push(id.0);
+        Some(value)
+    }
+
+    /// 更新对象的包围盒
+    ///
+    /// # 参数
+    /// + `id` - 对象的标识
+    /// + `aabb` - 新的包围盒
+    pub fn update(&mut self, id: SpatialId, aabb: Aabb) {
+        if let Some(Some(item)) = self.items.get_mut(id.0) {
+            item.0 = aabb;
+            if self.in_tree[id.0] {
+                self.in_tree[id.0] = false;
+                self.pending.push(id.0);
+            }
+        }
+    }
+
+    /// 获取对象
+    pub fn get(&self, id: SpatialId) -> Option<&T> {
+        self.items.get(id.0)?.as_ref().map(|(_, v)| v)
+    }
+
+    /// 获取对象的可变引用
+    pub fn get_mut(&mut self, id: SpatialId) -> Option<&mut T> {
+        self.items.get_mut(id.0)?.as_mut().map(|(_, v)| v)
+    }
+
+    /// 获取对象的包围盒
+    pub fn aabb(&self, id: SpatialId) -> Option<Aabb> {
+        self.items.get(id.0)?.as_ref().map(|(aabb, _)| *aabb)
+    }
+
+    /// 遍历所有对象
+    pub fn iter(&self) -> impl Iterator<Item = (SpatialId, &Aabb, &T)> {
+        self.items
+            .iter()
+            .enumerate()
+            .filter_map(|(i, item)| item.as_ref().map(|(aabb, v)| (SpatialId(i), aabb, v)))
+    }
+
+    /// 重建树结构
+    ///
+    /// # 注解
+    ///
+    /// 采用按最长轴中位数划分的自顶向下构建方式
+    pub fn rebuild(&mut self) {
+        self.nodes.clear();
+        self.pending.clear();
+        let mut indices: Vec<usize> = Vec::with_capacity(self.len());
+        for (i, item) in self.items.iter().enumerate() {
+            self.in_tree[i] = item.is_some();
+            if item.is_some() {
+                indices.push(i);
+            }
+        }
+        if !indices.is_empty() {
+            self.build_node(&mut indices);
+        }
+    }
+
+    fn build_node(&mut self, indices: &mut [usize]) -> usize {
+        let aabb = indices
+            .iter()
+            .map(|&i| self.items[i].as_ref().unwrap().0)
+            .fold(Aabb::EMPTY, |a, b| a.union(&b));
+        if indices.len() <= self.leaf_size {
+            self.nodes.push(BvhNode::Leaf {
+                aabb,
+                items: indices.to_vec(),
+            });
+            return self.nodes.len() - 1;
+        }
+        let centers = Aabb::from_points(
+            indices
+                .iter()
+                .map(|&i| self.items[i].as_ref().unwrap().0.center()),
+        );
+        let extent = centers.max - centers.min;
+        let axis = if extent.x >= extent.y && extent.x >= extent.z {
+            0
+        } else if extent.y >= extent.z {
+            1
+        } else {
+            2
+        };
+        let mid = indices.len() / 2;
+        let items = &self.items;
+        indices.select_nth_unstable_by(mid, |&a, &b| {
+            let ca = items[a].as_ref().unwrap().0.center()[axis];
+            let cb = items[b].as_ref().unwrap().0.center()[axis];
+            ca.total_cmp(&cb)
+        });
+        let node = self.nodes.len();
+        self.nodes.push(BvhNode::Leaf {
+            aabb,
+            items: Vec::new(),
+        });
+        let (l, r) = indices.split_at_mut(mid);
+        let left = self.build_node(l);
+        let right = self.build_node(r);
+        self.nodes[node] = BvhNode::Branch { aabb, left, right };
+        node
+    }
+
+    fn item_aabb(&self, index: usize) -> &Aabb {
+        &self.items[index].as_ref().unwrap().0
+    }
+
+    /// 遍历树结构，`visit_node`决定是否进入节点，`visit_item`处理通过的对象
+    fn traverse<N, I>(&self, mut visit_node: N, mut visit_item: I)
+    where
+        N: FnMut(&Aabb) -> bool,
+        I: FnMut(usize),
+    {
+        if !self.nodes.is_empty() {
+            let mut stack = vec![0];
+            while let Some(n) = stack.pop() {
+                let node = &self.nodes[n];
+                if !visit_node(node.aabb()) {
+                    continue;
+                }
+                match node {
+                    BvhNode::Leaf { items, .. } => {
+                        for &i in items {
+                            if self.in_tree[i] {
+                                visit_item(i);
+                            }
+                        }
+                    }
+                    BvhNode::Branch { left, right, .. } => {
+                        stack.push(*left);
+                        stack.push(*right);
+                    }
+                }
+            }
+        }
+        for &i in &self.pending {
+            visit_item(i);
+        }
+    }
+
+    /// 查询与视锥体相交的对象
+    ///
+    /// # 参数
+    /// + `frustum` - 视锥体
+    ///
+    /// # 返回值
+    /// 返回所有包围盒与视锥体相交的对象标识
+    pub fn query_frustum(&self, frustum: &Frustum) -> Vec<SpatialId> {
+        let mut result = Vec::new();
+        self.traverse(
+            |aabb| frustum.intersects_aabb(aabb),
+            |i| {
+                if frustum.intersects_aabb(self.item_aabb(i)) {
+                    result.push(SpatialId(i));
+                }
+            },
+        );
+        result
+    }
+
+    /// 查询与包围盒相交的对象
+    ///
+    /// # 参数
+    /// + `aabb` - 查询范围
+    ///
+    /// # 返回值
+    /// 返回所有包围盒与查询范围相交的对象标识
+    pub fn query_aabb(&self, aabb: &Aabb) -> Vec<SpatialId> {
+        let mut result = Vec::new();
+        self.traverse(
+            |node| node.intersects(aabb),
+            |i| {
+                if self.item_aabb(i).intersects(aabb) {
+                    result.push(SpatialId(i));
+                }
+            },
+        );
+        result
+    }
+
+    /// 查询射线最先命中的对象
+    ///
+    /// # 参数
+    /// + `ray` - 射线
+    ///
+    /// # 返回值
+    /// 返回最近的命中对象标识与命中点的射线参数，未命中时返回`None`
+    ///
+    /// # 注解
+    ///
+    /// 命中判断以对象的包围盒为准，需要精确结果时应在返回的候选对象上进一步检测
+    pub fn raycast(&self, ray: &Ray) -> Option<(SpatialId, f32)> {
+        let best: Cell<Option<(SpatialId, f32)>> = Cell::new(None);
+        self.traverse(
+            |aabb| match (aabb.ray_intersection(ray), best.get()) {
+                (Some(t), Some((_, bt))) => t <= bt,
+                (Some(_), None) => true,
+                (None, _) => false,
+            },
+            |i| {
+                if let Some(t) = self.item_aabb(i).ray_intersection(ray) {
+                    if best.get().is_none_or(|(_, bt)| t < bt) {
+                        best.set(Some((SpatialId(i), t)));
+                    }
+                }
+            },
+        );
+        best.get()
+    }
+
+    /// 查询距离指定点最近的对象
+    ///
+    /// # 参数
+    /// + `point` - 查询点
+    /// + `max_distance` - 最大查询距离
+    ///
+    /// # 返回值
+    /// 返回最近对象的标识及其包围盒到查询点的距离，范围内没有对象时返回`None`
+    pub fn nearest(&self, point: Vec3, max_distance: f32) -> Option<(SpatialId, f32)> {
+        let best: Cell<Option<SpatialId>> = Cell::new(None);
+        let best_sq = Cell::new(max_distance * max_distance);
+        self.traverse(
+            |aabb| aabb.distance_squared(point) <= best_sq.get(),
+            |i| {
+                let d = self.item_aabb(i).distance_squared(point);
+                if d <= best_sq.get() {
+                    best_sq.set(d);
+                    best.set(Some(SpatialId(i)));
+                }
+            },
+        );
+        best.get().map(|id| (id, best_sq.get().sqrt()))
+    }
+}
@@ -0,0 +1,377 @@
+use crate::error::Result;
+use crate::math::*;
+use crate::{AtlasRegion, GlObjectKind, GlObjects, Program, RenderStats, StreamingBuffer, Texture2D};
+
+const VS: &str = r#"
+#version 330 core
+layout (location = 0) in vec2 aPos;
+layout (location = 1) in vec2 aUV;
+layout (location = 2) in vec4 aColor;
+
+uniform mat4 uViewProjection;
+
+out vec2 vUV;
+out vec4 vColor;
+
+void main()
+{
+    gl_Position = uViewProjection * vec4(aPos, 0.0, 1.0);
+    vUV = aUV;
+    vColor = aColor;
+}
+"#;
+
+const FS: &str = r#"
+#version 330 core
+in vec2 vUV;
+in vec4 vColor;
+out vec4 FragColor;
+
+uniform sampler2D uTexture;
+
+void main()
+{
+    vec4 color = texture(uTexture, vUV) * vColor;
+    if (color.a <= 0.0) {
+        discard;
+    }
+    FragColor = color;
+}
+"#;
+
+/// 每批最多绘制的精灵数量
+const MAX_SPRITES: usize = 4096;
+/// 每个顶点的浮点数：位置(2)、纹理坐标(2)、颜色(4)
+const VERTEX_FLOATS: usize = 8;
+
+/// 精灵
+///
+/// 世界空间中轴对齐的矩形，采样纹理中的一块区域
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Sprite {
+    /// 左下角坐标
+    pub min: Vec2,
+    /// 右上角坐标
+    pub max: Vec2,
+    /// 左下角对应的纹理坐标，大于`uv_max`时翻转
+    pub uv_min: Vec2,
+    /// 右上角对应的纹理坐标
+    pub uv_max: Vec2,
+    /// 与纹理颜色相乘的颜色
+    pub color: Vec4,
+}
+
+impl Sprite {
+    /// 创建采样整张纹理的白色精灵
+    ///
+    /// # 参数
+    /// + `min` - 左下角坐标
+    /// + `max` - 右上角坐标
+    pub fn new(min: Vec2, max: Vec2) -> Self {
+        Self {
+            min,
+            max,
+            uv_min: Vec2::ZERO,
+            uv_max: Vec2::ONE,
+            color: Vec4::ONE,
+        }
+    }
+}
+
+/// 九宫格切片
+///
+/// 将纹理区域按四边的边框宽度分为九块，绘制到任意尺寸的矩形时四角保持原样，
+/// 四边只沿边的方向拉伸，中心向两个方向拉伸，使面板与按钮的边框不会变形
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NineSlice {
+    /// 纹理区域，依次为左下角`u`、`v`与宽度、高度
+    pub uv_rect: Vec4,
+    /// 纹理区域的像素尺寸
+    pub size: Vec2,
+    /// 四边不拉伸部分的像素宽度，依次为左、下、右、上
+    pub border: Vec4,
+    /// 是否绘制中心块，为`false`时只绘制边框
+    pub fill_center: bool,
+}
+
+impl NineSlice {
+    /// 由整张纹理创建九宫格切片
+    ///
+    /// # 参数
+    /// + `texture` - 纹理
+    /// + `border` - 四边不拉伸部分的像素宽度，依次为左、下、右、上
+    pub fn new(texture: &Texture2D, border: Vec4) -> Self {
+        Self {
+            uv_rect: Vec4::new(0.0, 0.0, 1.0, 1.0),
+            size: Vec2::new(texture.width() as f32, texture.height() as f32),
+            border,
+            fill_center: true,
+        }
+    }
+
+    /// 由图集区域创建九宫格切片
+    ///
+    /// # 参数
+    /// + `region` - 图集区域
+    /// + `border` - 四边不拉伸部分的像素宽度，依次为左、下、右、上
+    pub fn from_region(region: &AtlasRegion, border: Vec4) -> Self {
+        Self {
+            uv_rect: region.uv_rect,
+            size: region.size.as_vec2(),
+            border,
+            fill_center: true,
+        }
+    }
+}
+
+/// 精灵批处理器
+///
+/// 将使用相同纹理的连续精灵合并到同一次绘制调用中，纹理改变或缓冲已满时提交一批
+///
+/// # 示例
+///
+/// ```
+/// use gle::*;
+/// use gle::math::*;
+///
+/// fn render_loop(batch: &mut SpriteBatch, texture: &Texture2D) {
+///     let projection = orthographic(0.0, 800.0, 0.0, 600.0, -1.0, 1.0);
+///     batch.begin(&projection);
+///     batch.draw(texture.id(), &Sprite::new(Vec2::new(10.0, 10.0), Vec2::new(42.0, 42.0)));
+///     batch.end();
+/// }
+/// ```
+///
+/// # 注解
+///
+/// 绘制期间启用透明度混合并禁用深度测试，[`SpriteBatch::end`] 时恢复。该类型只能在渲染线程中创建、使用与释放
+pub struct SpriteBatch {
+    program: Program,
+    vao: u32,
+    stream: StreamingBuffer,
+    ebo: u32,
+    vertices: Vec<f32>,
+    texture: u32,
+    depth_test: bool,
+    draw_calls: usize,
+}
+
+impl SpriteBatch {
+    /// 创建精灵批处理器
+    ///
+    /// # 返回值
+    /// 成功时返回批处理器，内置着色器编译失败时返回错误
+    pub fn new() -> Result<Self> {
+        let program = Program::new(VS, FS)?;
+        let indices: Vec<u32> = (0..MAX_SPRITES as u32)
+            .flat_map(|i| [0, 1, 2, 2, 1, 3].map(|j| i * 4 + j))
+            .collect();
+        let (mut vao, mut ebo) = (0, 0);
+        let stride = VERTEX_FLOATS * std::mem::size_of::<f32>();
+        unsafe {
+            gl::GenVertexArrays(1, &mut vao);
+            gl::GenBuffers(1, &mut ebo);
+            gl::BindVertexArray(vao);
+            gl::BindBuffer(gl::ELEMENT_ARRAY_BUFFER, ebo);
+            gl::BufferData(
+                gl::ELEMENT_ARRAY_BUFFER,
+                std::mem::size_of_val(indices.as_slice()) as isize,
+                indices.as_ptr() as *const _,
+                gl::STATIC_DRAW,
+            );
+            for index in 0..3 {
+                gl::EnableVertexAttribArray(index);
+            }
+            gl::BindVertexArray(0);
+        }
+        GlObjects::track(GlObjectKind::VertexArray, vao);
+        GlObjects::track(GlObjectKind::Buffer, ebo);
+        Ok(Self {
+            program,
+            vao,
+            stream: StreamingBuffer::new(MAX_SPRITES * 4 * stride),
+            ebo,
+            vertices: Vec::with_capacity(MAX_SPRITES * 4 * VERTEX_FLOATS),
+            texture: 0,
+            depth_test: false,
+            draw_calls: 0,
+        })
+    }
+
+    /// 开始一次批处理
+    ///
+    /// # 参数
+    /// + `view_projection` - 将世界坐标变换到裁剪空间的矩阵
+    pub fn begin(&mut self, view_projection: &Mat4) {
+        self.vertices.clear();
+        self.draw_calls = 0;
+        self.program.bind();
+        self.program.set("uViewProjection", view_projection);
+        self.program.set("uTexture", &0i32);
+        unsafe {
+            self.depth_test = gl::IsEnabled(gl::DEPTH_TEST) == gl::TRUE;
+            gl::Disable(gl::DEPTH_TEST);
+            gl::Enable(gl::BLEND);
+            gl::BlendFunc(gl::SRC_ALPHA, gl::ONE_MINUS_SRC_ALPHA);
+        }
+    }
+
+    /// 绘制精灵
+    ///
+    /// # 参数
+    /// + `texture` - OpenGL 纹理对象ID
+    /// + `sprite` - 精灵
+    pub fn draw(&mut self, texture: u32, sprite: &Sprite) {
+        if texture != self.texture || self.vertices.len() >= MAX_SPRITES * 4 * VERTEX_FLOATS {
+            self.flush();
+            self.texture = texture;
+        }
+        let Sprite {
+            min,
+            max,
+            uv_min,
+            uv_max,
+            color,
+        } = *sprite;
+        for (pos, uv) in [
+            (min, uv_min),
+            (Vec2::new(max.x, min.y), Vec2::new(uv_max.x, uv_min.y)),
+            (Vec2::new(min.x, max.y), Vec2::new(uv_min.x, uv_max.y)),
+            (max, uv_max),
+        ] {
+            self.vertices.extend_from_slice(&[pos.x, pos.y, uv.x, uv.y]);
+            self.vertices.extend_from_slice(&color.to_array());
+        }
+    }
+
+    /// 绘制九宫格精灵
+    ///
+    /// # 参数
+    /// + `texture` - OpenGL 纹理对象ID
+    /// + `slice` - 九宫格切片
+    /// + `min` - 左下角坐标
+    /// + `max` - 右上角坐标
+    /// + `color` - 与纹理颜色相乘的颜色
+    /// + `border_scale` - 边框的缩放倍数，为 1 时边框与纹理像素一一对应
+    ///
+    /// # 注解
+    ///
+    /// 矩形小于两侧边框之和时按比例缩小边框
+    pub fn draw_nine_slice(
+        &mut self,
+        texture: u32,
+        slice: &NineSlice,
+        min: Vec2,
+        max: Vec2,
+        color: Vec4,
+        border_scale: f32,
+    ) {
+        let size = (max - min).abs();
+        let (lo, hi) = (min.min(max), min.max(max));
+        let border = slice.border * border_scale;
+        // 两侧边框之和超过矩形尺寸时等比缩小
+        let fit = Vec2::new(
+            (size.x / (border.x + border.z).max(f32::EPSILON)).min(1.0),
+            (size.y / (border.y + border.w).max(f32::EPSILON)).min(1.0),
+        );
+        let xs = [lo.x, lo.x + border.x * fit.x, hi.x - border.z * fit.x, hi.x];
+        let ys = [lo.y, lo.y + border.y * fit.y, hi.y - border.w * fit.y, hi.y];
+        let uv_min = Vec2::new(slice.uv_rect.x, slice.uv_rect.y);
+        let uv_size = Vec2::new(slice.uv_rect.z, slice.uv_rect.w);
+        let texel = uv_size / slice.size.max(Vec2::ONE);
+        let us = [
+            uv_min.x,
+            uv_min.x + slice.border.x * texel.x,
+            uv_min.x + uv_size.x - slice.border.z * texel.x,
+            uv_min.x + uv_size.x,
+        ];
+        let vs = [
+            uv_min.y,
+            uv_min.y + slice.border.y * texel.y,
+            uv_min.y + uv_size.y - slice.border.w * texel.y,
+            uv_min.y + uv_size.y,
+        ];
+        for row in 0..3 {
+            for column in 0..3 {
+                if row == 1 && column == 1 && !slice.fill_center {
+                    continue;
+                }
+                if xs[column + 1] <= xs[column] || ys[row + 1] <= ys[row] {
+                    continue;
+                }
+                self.draw(
+                    texture,
+                    &Sprite {
+                        min: Vec2::new(xs[column], ys[row]),
+                        max: Vec2::new(xs[column + 1], ys[row + 1]),
+                        uv_min: Vec2::new(us[column], vs[row]),
+                        uv_max: Vec2::new(us[column + 1], vs[row + 1]),
+                        color,
+                    },
+                );
+            }
+        }
+    }
+
+    /// 提交剩余的精灵并恢复渲染状态
+    pub fn end(&mut self) {
+        self.flush();
+        unsafe {
+            gl::Disable(gl::BLEND);
+            if self.depth_test {
+                gl::Enable(gl::DEPTH_TEST);
+            }
+        }
+    }
+
+    /// 获取本次批处理至今的绘制调用次数
+    pub fn draw_calls(&self) -> usize {
+        self.draw_calls
+    }
+
+    fn flush(&mut self) {
+        if self.vertices.is_empty() {
+            return;
+        }
+        let sprites = self.vertices.len() / (4 * VERTEX_FLOATS);
+        RenderStats::record_draw(sprites as u64 * 2);
+        let base = self.stream.write(&self.vertices);
+        let stride = VERTEX_FLOATS * std::mem::size_of::<f32>();
+        unsafe {
+            gl::ActiveTexture(gl::TEXTURE0);
+            gl::BindTexture(gl::TEXTURE_2D, self.texture);
+            gl::BindVertexArray(self.vao);
+            gl::BindBuffer(gl::ARRAY_BUFFER, self.stream.id());
+            for (index, (size, offset)) in [(2, 0), (2, 2), (4, 4)].into_iter().enumerate() {
+                gl::VertexAttribPointer(
+                    index as u32,
+                    size,
+                    gl::FLOAT,
+                    gl::FALSE,
+                    stride as i32,
+                    (base + offset * std::mem::size_of::<f32>()) as *const _,
+                );
+            }
+            gl::DrawElements(
+                gl::TRIANGLES,
+                (sprites * 6) as i32,
+                gl::UNSIGNED_INT,
+                std::ptr::null(),
+            );
+            gl::BindVertexArray(0);
+        }
+        self.vertices.clear();
+        self.draw_calls += 1;
+    }
+}
+
+impl Drop for SpriteBatch {
+    fn drop(&mut self) {
+        GlObjects::untrack(GlObjectKind::VertexArray, self.vao);
+        GlObjects::untrack(GlObjectKind::Buffer, self.ebo);
+        unsafe {
+            gl::DeleteBuffers(1, &self.ebo);
+            gl::DeleteVertexArrays(1, &self.vao);
+        }
+    }
+}
@@ -0,0 +1,321 @@
+use crate::error::Result;
+use crate::math::*;
+use crate::{
+    draw_fullscreen_triangle, AttachmentFormat, Framebuffer, GlObjectKind, GlObjects, PostContext,
+    PostEffect, Program, FULLSCREEN_VS,
+};
+
+const SSAO_FS: &str = r#"
+#version 330 core
+in vec2 vUV;
+out float FragColor;
+
+const int MAX_KERNEL = 64;
+
+uniform sampler2D uDepth;
+uniform sampler2D uNormal;
+uniform sampler2D uNoise;
+uniform bool uHasNormal;
+uniform mat4 uProjection;
+uniform mat4 uInvProjection;
+uniform mat4 uView;
+uniform vec3 uKernel[MAX_KERNEL];
+uniform int uKernelSize;
+uniform vec2 uNoiseScale;
+uniform float uRadius;
+uniform float uBias;
+uniform float uPower;
+
+vec3 viewPosition(vec2 uv)
+{
+    float depth = texture(uDepth, uv).r;
+    vec4 clip = vec4(vec3(uv, depth) * 2.0 - 1.0, 1.0);
+    vec4 view = uInvProjection * clip;
+    return view.xyz / view.w;
+}
+
+void main()
+{
+    if (texture(uDepth, vUV).r >= 1.0) {
+        FragColor = 1.0;
+        return;
+    }
+    vec3 P = viewPosition(vUV);
+    vec3 N;
+    if (uHasNormal) {
+        N = normalize(mat3(uView) * texture(uNormal, vUV).xyz);
+    } else {
+        N = normalize(cross(dFdx(P), dFdy(P)));
+    }
+    vec3 random = vec3(texture(uNoise, vUV * uNoiseScale).xy, 0.0);
+    vec3 T = normalize(random - N * dot(random, N));
+    vec3 B = cross(N, T);
+    mat3 TBN = mat3(T, B, N);
+
+    float occlusion = 0.0;
+    for (int i = 0; i < uKernelSize; ++i) {
+        vec3 S = P + TBN * uKernel[i] * uRadius;
+        vec4 offset = uProjection * vec4(S, 1.0);
+        offset.xy = offset.xy / offset.w * 0.5 + 0.5;
+        float sampleDepth = viewPosition(offset.xy).z;
+        float range = smoothstep(0.0, 1.0, uRadius / abs(P.z - sampleDepth));
+        occlusion += (sampleDepth >= S.z + uBias ? 1.0 : 0.0) * range;
+    }
+    FragColor = pow(1.0 - occlusion / float(uKernelSize), uPower);
+}
+"#;
+
+const BLUR_FS: &str = r#"
+#version 330 core
+in vec2 vUV;
+out float FragColor;
+
+uniform sampler2D uInput;
+
+void main()
+{
+    vec2 texel = 1.0 / vec2(textureSize(uInput, 0));
+    float result = 0.0;
+    for (int x = -2; x < 2; ++x) {
+        for (int y = -2; y < 2; ++y) {
+            result += texture(uInput, vUV + (vec2(x, y) + 0.5) * texel).r;
+        }
+    }
+    FragColor = result / 16.0;
+}
+"#;
+
+const COMPOSITE_FS: &str = r#"
+#version 330 core
+in vec2 vUV;
+out vec4 FragColor;
+
+uniform sampler2D uInput;
+uniform sampler2D uOcclusion;
+uniform float uIntensity;
+
+void main()
+{
+    vec4 color = texture(uInput, vUV);
+    float ao = mix(1.0, texture(uOcclusion, vUV).r, uIntensity);
+    FragColor = vec4(color.rgb * ao, color.a);
+}
+"#;
+
+const MAX_KERNEL: usize = 64;
+const NOISE_SIZE: u32 = 4;
+
+/// 屏幕空间环境光遮蔽(SSAO)
+///
+/// 在法线方向的半球内对深度缓冲采样估计遮蔽程度，经 4×4 模糊去除噪声图案后与场景颜色相乘。
+/// 场景法线由 [`PostContext::normal`] 提供，为`None`时从深度重建(边缘处质量较低)
+///
+/// # 示例
+///
+/// ```
+/// use gle::*;
+///
+/// fn render_init() {
+///     let mut ssao = Ssao::new(1280, 720).unwrap();
+///     ssao.radius = 0.75;
+///     let mut chain = PostProcessChain::new(1280, 720, AttachmentFormat::Rgba16F).unwrap();
+///     chain.push(ssao);
+/// }
+/// ```
+///
+/// # 注解
+///
+/// 遮蔽在后处理阶段作用于最终颜色(包括直接光照)，效果上比只作用于环境光更强，可通过`intensity`调节。
+/// 该类型只能在渲染线程中创建、使用与释放
+pub struct Ssao {
+    /// 采样半球半径(观察空间单位)
+    pub radius: f32,
+    /// 深度比较偏移，用于消除自遮蔽
+    pub bias: f32,
+    /// 遮蔽强度，范围`[0, 1]`
+    pub intensity: f32,
+    /// 遮蔽对比度指数
+    pub power: f32,
+    /// 是否模糊遮蔽结果
+    pub blur: bool,
+    kernel: Vec<Vec3>,
+    noise: u32,
+    ssao: Program,
+    blur_program: Program,
+    composite: Program,
+    occlusion: Framebuffer,
+    blurred: Framebuffer,
+}
+
+impl Ssao {
+    /// 使用 32 个采样点创建 SSAO 效果
+    ///
+    /// # 参数
+    /// + `width` - 输出宽度
+    /// + `height` - 输出高度
+    pub fn new(width: u32, height: u32) -> Result<Self> {
+        Self::with_kernel_size(width, height, 32)
+    }
+
+    /// 使用指定采样点数量创建 SSAO 效果
+    ///
+    /// # 参数
+    /// + `width` - 输出宽度
+    /// + `height` - 输出高度
+    /// + `kernel_size` - 采样点数量，最多 64 个
+    pub fn with_kernel_size(width: u32, height: u32, kernel_size: usize) -> Result<Self> {
+        let ssao = Program::new(FULLSCREEN_VS, SSAO_FS)?;
+        let blur_program = Program::new(FULLSCREEN_VS, BLUR_FS)?;
+        let composite = Program::new(FULLSCREEN_VS, COMPOSITE_FS)?;
+        let mut seed = 0x2545_f491_u32;
+        let kernel = (0..kernel_size.clamp(1, MAX_KERNEL))
+            .map(|i| {
+                let dir = Vec3::new(
+                    random(&mut seed) * 2.0 - 1.0,
+                    random(&mut seed) * 2.0 - 1.0,
+                    random(&mut seed),
+                )
+                .normalize_or(Vec3::Z);
+                // 采样点向半球中心聚集
+                let t = i as f32 / kernel_size as f32;
+                dir * random(&mut seed) * (0.1 + 0.9 * t * t)
+            })
+            .collect();
+        let noise_data: Vec<f32> = (0..NOISE_SIZE * NOISE_SIZE)
+            .flat_map(|_| [random(&mut seed) * 2.0 - 1.0, random(&mut seed) * 2.0 - 1.0])
+            .collect();
+        let mut noise = 0;
+        unsafe {
+            gl::GenTextures(1, &mut noise);
+            gl::BindTexture(gl::TEXTURE_2D, noise);
+            gl::TexImage2D(
+                gl::TEXTURE_2D,
+                0,
+                gl::RG16F as i32,
+                NOISE_SIZE as i32,
+                NOISE_SIZE as i32,
+                0,
+                gl::RG,
+                gl::FLOAT,
+                noise_data.as_ptr() as *const _,
+            );
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::NEAREST as i32);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::NEAREST as i32);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, gl::REPEAT as i32);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, gl::REPEAT as i32);
+            gl::BindTexture(gl::TEXTURE_2D, 0);
+        }
+        GlObjects::track(GlObjectKind::Texture, noise);
+        Ok(Self {
+            radius: 0.5,
+            bias: 0.025,
+            intensity: 1.0,
+            power: 1.5,
+            blur: true,
+            kernel,
+            noise,
+            ssao,
+            blur_program,
+            composite,
+            occlusion: Framebuffer::new(width, height, &[AttachmentFormat::R8], None)?,
+            blurred: Framebuffer::new(width, height, &[AttachmentFormat::R8], None)?,
+        })
+    }
+
+    /// 获取最近一次计算的遮蔽纹理(R 通道，1 表示无遮蔽)
+    ///
+    /// 可在延迟着色等需要只对环境光施加遮蔽的场合直接使用
+    pub fn occlusion_texture(&self) -> u32 {
+        if self.blur {
+            self.blurred.colors()[0].id()
+        } else {
+            self.occlusion.colors()[0].id()
+        }
+    }
+
+    /// 只计算遮蔽纹理而不合成到颜色，结果通过 [`Ssao::occlusion_texture`] 获取
+    pub fn compute(&mut self, ctx: &PostContext) {
+        let projection = ctx.camera.projection_matrix();
+        self.occlusion.bind();
+        self.ssao.bind();
+        unsafe {
+            gl::ActiveTexture(gl::TEXTURE0);
+            gl::BindTexture(gl::TEXTURE_2D, ctx.depth);
+            gl::ActiveTexture(gl::TEXTURE1);
+            gl::BindTexture(gl::TEXTURE_2D, ctx.normal.unwrap_or(0));
+            gl::ActiveTexture(gl::TEXTURE2);
+            gl::BindTexture(gl::TEXTURE_2D, self.noise);
+        }
+        self.ssao.set("uDepth", &0);
+        self.ssao.set("uNormal", &1);
+        self.ssao.set("uNoise", &2);
+        self.ssao.set("uHasNormal", &ctx.normal.is_some());
+        self.ssao.set("uProjection", &projection);
+        self.ssao.set("uInvProjection", &projection.inverse());
+        self.ssao.set("uView", &ctx.camera.view_matrix());
+        for (i, sample) in self.kernel.iter().enumerate() {
+            self.ssao.set(&format!("uKernel[{}]", i), sample);
+        }
+        self.ssao.set("uKernelSize", &(self.kernel.len() as i32));
+        self.ssao.set(
+            "uNoiseScale",
+            &Vec2::new(
+                self.occlusion.width() as f32 / NOISE_SIZE as f32,
+                self.occlusion.height() as f32 / NOISE_SIZE as f32,
+            ),
+        );
+        self.ssao.set("uRadius", &self.radius);
+        self.ssao.set("uBias", &self.bias);
+        self.ssao.set("uPower", &self.power);
+        draw_fullscreen_triangle();
+
+        if self.blur {
+            self.blurred.bind();
+            self.blur_program.bind();
+            self.occlusion.colors()[0].bind(0);
+            self.blur_program.set("uInput", &0);
+            draw_fullscreen_triangle();
+        }
+    }
+}
+
+impl PostEffect for Ssao {
+    fn name(&self) -> &str {
+        "ssao"
+    }
+
+    fn resize(&mut self, width: u32, height: u32) -> Result<()> {
+        self.occlusion.resize(width, height)?;
+        self.blurred.resize(width, height)
+    }
+
+    fn apply(&mut self, ctx: &PostContext, input: u32, output: Option<&Framebuffer>) {
+        self.compute(ctx);
+        ctx.bind_output(output);
+        self.composite.bind();
+        unsafe {
+            gl::ActiveTexture(gl::TEXTURE0);
+            gl::BindTexture(gl::TEXTURE_2D, input);
+            gl::ActiveTexture(gl::TEXTURE1);
+            gl::BindTexture(gl::TEXTURE_2D, self.occlusion_texture());
+        }
+        self.composite.set("uInput", &0);
+        self.composite.set("uOcclusion", &1);
+        self.composite.set("uIntensity", &self.intensity.clamp(0.0, 1.0));
+        draw_fullscreen_triangle();
+    }
+}
+
+impl Drop for Ssao {
+    fn drop(&mut self) {
+        GlObjects::untrack(GlObjectKind::Texture, self.noise);
+        unsafe { gl::DeleteTextures(1, &self.noise) };
+    }
+}
+
+fn random(seed: &mut u32) -> f32 {
+    *seed ^= *seed << 13;
+    *seed ^= *seed >> 17;
+    *seed ^= *seed << 5;
+    (*seed >> 8) as f32 / (1u32 << 24) as f32
+}
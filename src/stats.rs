@@ -0,0 +1,234 @@
+use std::cell::Cell;
+use std::sync::atomic::{AtomicI64, AtomicU64, Ordering};
+
+use crate::compressed::has_extension;
+use crate::App;
+
+static DRAW_CALLS: AtomicU64 = AtomicU64::new(0);
+static TRIANGLES: AtomicU64 = AtomicU64::new(0);
+static LAST_DRAW_CALLS: AtomicU64 = AtomicU64::new(0);
+static LAST_TRIANGLES: AtomicU64 = AtomicU64::new(0);
+static TEXTURE_BYTES: AtomicI64 = AtomicI64::new(0);
+static BUFFER_BYTES: AtomicI64 = AtomicI64::new(0);
+static RENDER_TARGET_BYTES: AtomicI64 = AtomicI64::new(0);
+static STREAM_BYTES: AtomicI64 = AtomicI64::new(0);
+
+const GPU_MEMORY_INFO_TOTAL_AVAILABLE_MEMORY_NVX: u32 = 0x9048;
+const GPU_MEMORY_INFO_CURRENT_AVAILABLE_VIDMEM_NVX: u32 = 0x9049;
+const TEXTURE_FREE_MEMORY_ATI: u32 = 0x87FC;
+
+/// 驱动提供的显存查询扩展
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum MemoryInfo {
+    Nvx,
+    Ati,
+    Unsupported,
+}
+
+thread_local! {
+    static MEMORY_INFO: Cell<Option<MemoryInfo>> = const { Cell::new(None) };
+}
+
+/// 渲染统计
+///
+/// 汇总引擎内置渲染器每帧的绘制调用与三角形数量，以及纹理、网格缓冲、帧缓冲附件与流式缓冲占用显存的估计值。
+/// 直接调用 OpenGL 绘制的代码可以通过 [`RenderStats::record_draw`] 计入统计
+///
+/// # 示例
+///
+/// ```
+/// use gle::*;
+///
+/// fn render_loop() {
+///     println!(
+///         "{} draw calls, {} triangles, ~{} bytes of VRAM",
+///         RenderStats::draw_calls(),
+///         RenderStats::triangles(),
+///         RenderStats::vram_estimate()
+///     );
+/// }
+/// ```
+///
+/// # 注解
+///
+/// 计数在每次渲染循环函数返回后归零，查询函数返回最近一个完整帧的结果。
+/// 纹理的显存估计值按未压缩的 RGBA8 与完整的多级纹理计算，帧缓冲附件按其格式计算，与驱动实际分配的大小存在偏差。
+/// 驱动报告的显存容量见 [`App::gpu_memory_stats`]
+pub struct RenderStats;
+
+impl RenderStats {
+    /// 记录一次绘制调用
+    ///
+    /// # 参数
+    /// + `triangles` - 本次绘制的三角形数量，绘制点或线段时为 0
+    pub fn record_draw(triangles: u64) {
+        DRAW_CALLS.fetch_add(1, Ordering::Relaxed);
+        TRIANGLES.fetch_add(triangles, Ordering::Relaxed);
+    }
+
+    /// 获取最近一帧的绘制调用次数
+    pub fn draw_calls() -> u64 {
+        LAST_DRAW_CALLS.load(Ordering::Relaxed)
+    }
+
+    /// 获取最近一帧绘制的三角形数量
+    pub fn triangles() -> u64 {
+        LAST_TRIANGLES.load(Ordering::Relaxed)
+    }
+
+    /// 获取纹理占用显存的估计值，单位为字节
+    pub fn texture_bytes() -> u64 {
+        TEXTURE_BYTES.load(Ordering::Relaxed).max(0) as u64
+    }
+
+    /// 获取网格缓冲占用显存的估计值，单位为字节
+    pub fn buffer_bytes() -> u64 {
+        BUFFER_BYTES.load(Ordering::Relaxed).max(0) as u64
+    }
+
+    /// 获取帧缓冲附件占用显存的估计值，单位为字节
+    pub fn render_target_bytes() -> u64 {
+        RENDER_TARGET_BYTES.load(Ordering::Relaxed).max(0) as u64
+    }
+
+    /// 获取流式缓冲占用显存的估计值，单位为字节
+    pub fn stream_bytes() -> u64 {
+        STREAM_BYTES.load(Ordering::Relaxed).max(0) as u64
+    }
+
+    /// 获取显存占用的估计值，单位为字节
+    pub fn vram_estimate() -> u64 {
+        Self::texture_bytes()
+            + Self::buffer_bytes()
+            + Self::render_target_bytes()
+            + Self::stream_bytes()
+    }
+
+    pub(crate) fn _end_frame() {
+        LAST_DRAW_CALLS.store(DRAW_CALLS.swap(0, Ordering::Relaxed), Ordering::Relaxed);
+        LAST_TRIANGLES.store(TRIANGLES.swap(0, Ordering::Relaxed), Ordering::Relaxed);
+    }
+
+    pub(crate) fn _track_texture(bytes: i64) {
+        TEXTURE_BYTES.fetch_add(bytes, Ordering::Relaxed);
+    }
+
+    pub(crate) fn _track_buffer(bytes: i64) {
+        BUFFER_BYTES.fetch_add(bytes, Ordering::Relaxed);
+    }
+
+    pub(crate) fn _track_render_target(bytes: i64) {
+        RENDER_TARGET_BYTES.fetch_add(bytes, Ordering::Relaxed);
+    }
+
+    pub(crate) fn _track_stream(bytes: i64) {
+        STREAM_BYTES.fetch_add(bytes, Ordering::Relaxed);
+    }
+
+    /// 估计具有完整多级纹理的 RGBA8 纹理的字节数
+    pub(crate) fn _texture_estimate(width: u32, height: u32, layers: u32) -> i64 {
+        width as i64 * height as i64 * layers as i64 * 4 * 4 / 3
+    }
+}
+
+/// 显存使用情况
+///
+/// 由 [`App::gpu_memory_stats`] 获取，包含驱动报告的显存容量与引擎按资源类型统计的分配估计值
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct GpuMemoryStats {
+    /// 驱动报告的显存总量，单位为字节，驱动不支持查询或不报告总量时为`None`
+    pub total: Option<u64>,
+    /// 驱动报告的剩余可用显存，单位为字节，驱动不支持查询时为`None`
+    pub available: Option<u64>,
+    /// 纹理的估计值，单位为字节
+    pub textures: u64,
+    /// 网格缓冲的估计值，单位为字节
+    pub buffers: u64,
+    /// 帧缓冲附件的估计值，单位为字节
+    pub render_targets: u64,
+    /// 流式缓冲的估计值，单位为字节
+    pub streaming: u64,
+}
+
+impl GpuMemoryStats {
+    /// 获取引擎分配的估计总量，单位为字节
+    pub fn estimate(&self) -> u64 {
+        self.textures + self.buffers + self.render_targets + self.streaming
+    }
+
+    /// 获取驱动报告的已用显存，包含其他程序的占用，单位为字节
+    ///
+    /// # 返回值
+    /// 驱动同时报告总量与剩余量时返回两者之差，否则返回`None`
+    pub fn used(&self) -> Option<u64> {
+        Some(self.total?.saturating_sub(self.available?))
+    }
+}
+
+impl App {
+    /// 获取显存使用情况
+    ///
+    /// 支持`GL_NVX_gpu_memory_info`(NVIDIA)或`GL_ATI_meminfo`(AMD)时包含驱动报告的显存容量，
+    /// 其中`GL_ATI_meminfo`只报告剩余量
+    ///
+    /// # 示例
+    ///
+    /// ```no_run
+    /// use gle::*;
+    ///
+    /// let stats = App::gpu_memory_stats();
+    /// if let (Some(available), Some(total)) = (stats.available, stats.total) {
+    ///     println!("{} / {} bytes available", available, total);
+    /// }
+    /// println!("engine allocations: ~{} bytes", stats.estimate());
+    /// ```
+    ///
+    /// # 注解
+    ///
+    /// 该函数只能在渲染线程中调用，估计值的计算方式见 [`RenderStats`]
+    pub fn gpu_memory_stats() -> GpuMemoryStats {
+        let mut stats = GpuMemoryStats {
+            total: None,
+            available: None,
+            textures: RenderStats::texture_bytes(),
+            buffers: RenderStats::buffer_bytes(),
+            render_targets: RenderStats::render_target_bytes(),
+            streaming: RenderStats::stream_bytes(),
+        };
+        let info = MEMORY_INFO.with(|cell| {
+            if let Some(info) = cell.get() {
+                return info;
+            }
+            let info = if has_extension("GL_NVX_gpu_memory_info") {
+                MemoryInfo::Nvx
+            } else if has_extension("GL_ATI_meminfo") {
+                MemoryInfo::Ati
+            } else {
+                MemoryInfo::Unsupported
+            };
+            cell.set(Some(info));
+            info
+        });
+        // 扩展以 KiB 为单位报告
+        let kib = |value: i32| value.max(0) as u64 * 1024;
+        match info {
+            MemoryInfo::Nvx => {
+                let (mut total, mut available) = (0, 0);
+                unsafe {
+                    gl::GetIntegerv(GPU_MEMORY_INFO_TOTAL_AVAILABLE_MEMORY_NVX, &mut total);
+                    gl::GetIntegerv(GPU_MEMORY_INFO_CURRENT_AVAILABLE_VIDMEM_NVX, &mut available);
+                }
+                stats.total = Some(kib(total));
+                stats.available = Some(kib(available));
+            }
+            MemoryInfo::Ati => {
+                // 依次为剩余总量、最大空闲块、辅助内存剩余总量、辅助内存最大空闲块
+                let mut values = [0i32; 4];
+                unsafe { gl::GetIntegerv(TEXTURE_FREE_MEMORY_ATI, values.as_mut_ptr()) };
+                stats.available = Some(kib(values[0]));
+            }
+            MemoryInfo::Unsupported => {}
+        }
+        stats
+    }
+}
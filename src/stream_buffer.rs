@@ -0,0 +1,214 @@
+use crate::{GlObjectKind, GlObjects, GpuFence, RenderStats};
+
+/// 流式缓冲的分段数量，CPU 写入的分段最多领先 GPU 正在读取的分段两段
+pub const STREAM_SEGMENTS: usize = 3;
+
+/// 流式缓冲写入偏移的对齐字节数，满足顶点属性与 uniform 缓冲偏移的对齐要求
+pub const STREAM_ALIGNMENT: usize = 256;
+
+/// 流式缓冲
+///
+/// 用于每帧更新的动态数据(精灵顶点、调试线段、实例矩阵等)的环形缓冲，分为 [`STREAM_SEGMENTS`] 段，
+/// 写满一段后插入栅栏并转入下一段，只在 GPU 仍在读取下一段时等待。
+/// 支持`GL_ARB_buffer_storage`时缓冲以`GL_MAP_PERSISTENT_BIT`持久映射，写入只是一次内存复制；
+/// 否则以不同步的范围映射写入。两种方式都不需要每帧以`glBufferData`重新分配缓冲
+///
+/// # 示例
+///
+/// ```no_run
+/// use gle::{*, math::*};
+///
+/// // 以位置 3~6 的实例属性传递模型矩阵
+/// fn draw_instances(stream: &mut StreamingBuffer, mesh: &GpuMesh, matrices: &[Mat4]) {
+///     let offset = stream.write(matrices);
+///     unsafe {
+///         gl::BindVertexArray(mesh.vao());
+///         gl::BindBuffer(gl::ARRAY_BUFFER, stream.id());
+///         for column in 0..4 {
+///             let location = 3 + column;
+///             gl::EnableVertexAttribArray(location);
+///             gl::VertexAttribPointer(
+///                 location,
+///                 4,
+///                 gl::FLOAT,
+///                 gl::FALSE,
+///                 64,
+///                 (offset + column as usize * 16) as *const _,
+///             );
+///             gl::VertexAttribDivisor(location, 1);
+///         }
+///     }
+///     mesh.draw_instanced(matrices.len() as i32);
+/// }
+/// ```
+///
+/// # 注解
+///
+/// 写入的数据超过分段大小时缓冲会重新分配，[`StreamingBuffer::id`] 随之改变，
+/// 因此应在每次写入后以返回的偏移重新设置顶点属性或绑定范围。该类型只能在渲染线程中创建、使用与释放
+pub struct StreamingBuffer {
+    id: u32,
+    segment_size: usize,
+    segment: usize,
+    head: usize,
+    fences: [Option<GpuFence>; STREAM_SEGMENTS],
+    mapped: *mut u8,
+}
+
+// 映射指针与同步对象只在渲染线程中访问，持有流式缓冲的渲染器需要随渲染系统移入渲染线程
+unsafe impl Send for StreamingBuffer {}
+
+impl StreamingBuffer {
+    /// 创建流式缓冲
+    ///
+    /// # 参数
+    /// + `segment_size` - 每段的字节大小，应能容纳一帧的数据，总大小为其 [`STREAM_SEGMENTS`] 倍
+    pub fn new(segment_size: usize) -> Self {
+        let mut buffer = Self {
+            id: 0,
+            segment_size: align(segment_size.max(1)),
+            segment: 0,
+            head: 0,
+            fences: Default::default(),
+            mapped: std::ptr::null_mut(),
+        };
+        buffer.allocate();
+        buffer
+    }
+
+    /// 获取 OpenGL 缓冲对象ID
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    /// 获取每段的字节大小
+    pub fn segment_size(&self) -> usize {
+        self.segment_size
+    }
+
+    /// 判断缓冲是否被持久映射
+    pub fn is_persistent(&self) -> bool {
+        !self.mapped.is_null()
+    }
+
+    /// 写入数据
+    ///
+    /// # 参数
+    /// + `data` - 数据
+    ///
+    /// # 返回值
+    /// 返回数据在缓冲中的字节偏移，按 [`STREAM_ALIGNMENT`] 对齐
+    ///
+    /// # 注解
+    ///
+    /// 写入的数据在下一次写入覆盖其所在的分段前保持有效，即至少在本次与之后的两段写入期间有效
+    pub fn write<T: Copy>(&mut self, data: &[T]) -> usize {
+        let size = std::mem::size_of_val(data);
+        let mut offset = align(self.head);
+        if offset + size > self.segment_size {
+            if size > self.segment_size {
+                self.grow(size);
+            } else {
+                self.advance();
+            }
+            offset = 0;
+        }
+        let absolute = self.segment * self.segment_size + offset;
+        self.head = offset + size;
+        if size == 0 {
+            return absolute;
+        }
+        unsafe {
+            if !self.mapped.is_null() {
+                std::ptr::copy_nonoverlapping(
+                    data.as_ptr() as *const u8,
+                    self.mapped.add(absolute),
+                    size,
+                );
+            } else {
+                gl::BindBuffer(gl::COPY_WRITE_BUFFER, self.id);
+                let ptr = gl::MapBufferRange(
+                    gl::COPY_WRITE_BUFFER,
+                    absolute as isize,
+                    size as isize,
+                    gl::MAP_WRITE_BIT | gl::MAP_UNSYNCHRONIZED_BIT | gl::MAP_INVALIDATE_RANGE_BIT,
+                );
+                if !ptr.is_null() {
+                    std::ptr::copy_nonoverlapping(data.as_ptr() as *const u8, ptr as *mut u8, size);
+                    gl::UnmapBuffer(gl::COPY_WRITE_BUFFER);
+                }
+                gl::BindBuffer(gl::COPY_WRITE_BUFFER, 0);
+            }
+        }
+        absolute
+    }
+
+    /// 为当前分段插入栅栏并转入下一段，等待 GPU 完成对下一段的读取
+    fn advance(&mut self) {
+        self.fences[self.segment] = Some(GpuFence::insert());
+        self.segment = (self.segment + 1) % STREAM_SEGMENTS;
+        self.head = 0;
+        if let Some(fence) = self.fences[self.segment].take() {
+            fence.wait();
+        }
+    }
+
+    fn grow(&mut self, size: usize) {
+        self.release();
+        self.segment_size = align(size.next_power_of_two());
+        self.segment = 0;
+        self.head = 0;
+        self.allocate();
+    }
+
+    fn allocate(&mut self) {
+        let total = self.segment_size * STREAM_SEGMENTS;
+        let flags = gl::MAP_WRITE_BIT | gl::MAP_PERSISTENT_BIT | gl::MAP_COHERENT_BIT;
+        unsafe {
+            gl::GenBuffers(1, &mut self.id);
+            gl::BindBuffer(gl::COPY_WRITE_BUFFER, self.id);
+            if gl::BufferStorage::is_loaded() {
+                gl::BufferStorage(gl::COPY_WRITE_BUFFER, total as isize, std::ptr::null(), flags);
+                self.mapped =
+                    gl::MapBufferRange(gl::COPY_WRITE_BUFFER, 0, total as isize, flags) as *mut u8;
+            } else {
+                gl::BufferData(
+                    gl::COPY_WRITE_BUFFER,
+                    total as isize,
+                    std::ptr::null(),
+                    gl::STREAM_DRAW,
+                );
+                self.mapped = std::ptr::null_mut();
+            }
+            gl::BindBuffer(gl::COPY_WRITE_BUFFER, 0);
+        }
+        RenderStats::_track_stream(total as i64);
+        GlObjects::track(GlObjectKind::Buffer, self.id);
+    }
+
+    /// 删除缓冲与栅栏，已提交的绘制仍可读取被删除的缓冲直到其完成
+    fn release(&mut self) {
+        unsafe {
+            if !self.mapped.is_null() {
+                gl::BindBuffer(gl::COPY_WRITE_BUFFER, self.id);
+                gl::UnmapBuffer(gl::COPY_WRITE_BUFFER);
+                gl::BindBuffer(gl::COPY_WRITE_BUFFER, 0);
+                self.mapped = std::ptr::null_mut();
+            }
+            gl::DeleteBuffers(1, &self.id);
+        }
+        GlObjects::untrack(GlObjectKind::Buffer, self.id);
+        self.fences = Default::default();
+        RenderStats::_track_stream(-((self.segment_size * STREAM_SEGMENTS) as i64));
+    }
+}
+
+impl Drop for StreamingBuffer {
+    fn drop(&mut self) {
+        self.release();
+    }
+}
+
+fn align(offset: usize) -> usize {
+    offset.div_ceil(STREAM_ALIGNMENT) * STREAM_ALIGNMENT
+}
@@ -0,0 +1,411 @@
+use std::sync::mpsc::{channel, Receiver, Sender};
+
+use image::imageops::{resize, FilterType};
+use image::RgbaImage;
+
+use crate::error::{Error, Result};
+use crate::math::*;
+use crate::{debug, warn, Camera, JobPool, Projection, Texture2D, TextureSettings};
+
+/// 流式纹理的句柄
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct StreamedTexture(usize);
+
+/// 单个流式纹理的驻留情况
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Residency {
+    /// 多级渐远纹理的总级数，解码完成前为 0
+    pub levels: usize,
+    /// 已驻留显存的最精细级别，0 为原始分辨率
+    pub resident_level: usize,
+    /// 按距离与预算计算出的期望级别
+    pub desired_level: usize,
+    /// 驻留显存的字节数
+    pub resident_bytes: usize,
+    /// 上次更新时与摄像机的距离
+    pub distance: f32,
+}
+
+/// 纹理流送的统计信息
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StreamingStats {
+    /// 纹理数量
+    pub textures: usize,
+    /// 正在解码的纹理数量
+    pub loading: usize,
+    /// 驻留显存的总字节数
+    pub resident_bytes: usize,
+    /// 不受预算限制时期望驻留的总字节数
+    pub requested_bytes: usize,
+    /// 显存预算
+    pub budget: usize,
+    /// 上次更新中提高精度的纹理数量
+    pub streamed_in: usize,
+    /// 上次更新中降低精度的纹理数量
+    pub streamed_out: usize,
+}
+
+struct Entry {
+    path: String,
+    srgb: bool,
+    center: Vec3,
+    radius: f32,
+    /// 解码后的多级渐远纹理，0 级为原始分辨率
+    mips: Vec<RgbaImage>,
+    texture: Option<Texture2D>,
+    resident: usize,
+    desired: usize,
+    distance: f32,
+}
+
+impl Entry {
+    fn bytes_from(&self, level: usize) -> usize {
+        self.mips[level.min(self.mips.len())..].iter().map(|m| m.as_raw().len()).sum()
+    }
+
+    fn resident_bytes(&self) -> usize {
+        if self.texture.is_some() {
+            self.bytes_from(self.resident)
+        } else {
+            0
+        }
+    }
+
+    /// 始终驻留的级别中最精细的一级，从该级别起的所有级别边长都不大于`base_size`
+    fn coarsest(&self, base_size: u32) -> usize {
+        self.mips
+            .iter()
+            .position(|m| m.width().max(m.height()) <= base_size)
+            .unwrap_or(self.mips.len() - 1)
+    }
+}
+
+/// 基于距离的纹理流送
+///
+/// 纹理在工作线程中解码并生成完整的多级渐远纹理后，先只上传尺寸不超过`base_size`的低精度级别，
+/// 之后每次 [`TextureStreamer::update`] 根据纹理在屏幕上的投影尺寸逐级流入更精细的级别，
+/// 远离摄像机或超出显存预算时再流出
+///
+/// # 示例
+///
+/// ```
+/// use gle::*;
+///
+/// fn render_init(pool: &JobPool) -> (TextureStreamer, StreamedTexture) {
+///     let mut streamer = TextureStreamer::new(256 * 1024 * 1024);
+///     let rock = streamer.load("assets/rock_4k.png", true, pool);
+///     streamer.set_bounds(rock, Vec3::new(10.0, 0.0, -5.0), 2.0);
+///     (streamer, rock)
+/// }
+///
+/// fn render_loop(streamer: &mut TextureStreamer, rock: StreamedTexture) {
+///     if let Some(camera) = Camera::main() {
+///         streamer.update(&camera, App::window_size().1);
+///     }
+///     streamer.bind(rock, 0);
+///     // 绘制 ...
+/// }
+/// ```
+///
+/// # 注解
+///
+/// 每次改变驻留级别都会以新的存储重建纹理对象，因此不应长期保存 [`TextureStreamer::texture`] 返回的纹理ID。
+/// 解码后的像素数据常驻内存，以便随时流入。除`load`外的方法只能在渲染线程中调用
+pub struct TextureStreamer {
+    /// 显存预算，单位为字节
+    pub budget: usize,
+    /// 级别偏移，为正时整体使用更粗的级别
+    pub bias: f32,
+    /// 始终驻留的级别的最大边长，默认为 64
+    pub base_size: u32,
+    /// 每次更新最多重建的纹理数量，默认为 4
+    pub max_rebuilds_per_update: usize,
+    /// 创建纹理对象时使用的采样设置，其中的`generate_mipmaps`被忽略
+    pub settings: TextureSettings,
+    entries: Vec<Option<Entry>>,
+    sender: Sender<(usize, Result<Vec<RgbaImage>>)>,
+    receiver: Receiver<(usize, Result<Vec<RgbaImage>>)>,
+    last_in: usize,
+    last_out: usize,
+}
+
+impl TextureStreamer {
+    /// 创建纹理流送系统
+    ///
+    /// # 参数
+    /// + `budget` - 显存预算，单位为字节
+    pub fn new(budget: usize) -> Self {
+        let (sender, receiver) = channel();
+        Self {
+            budget,
+            bias: 0.0,
+            base_size: 64,
+            max_rebuilds_per_update: 4,
+            settings: TextureSettings::default(),
+            entries: Vec::new(),
+            sender,
+            receiver,
+            last_in: 0,
+            last_out: 0,
+        }
+    }
+
+    /// 在工作线程中加载纹理
+    ///
+    /// # 参数
+    /// + `path` - 图像文件路径
+    /// + `srgb` - 是否为 sRGB 颜色空间
+    /// + `pool` - 执行解码与多级渐远纹理生成的线程池
+    ///
+    /// # 返回值
+    /// 返回纹理句柄，解码完成前 [`TextureStreamer::texture`] 返回`None`
+    pub fn load(&mut self, path: &str, srgb: bool, pool: &JobPool) -> StreamedTexture {
+        let index = self.entries.len();
+        self.entries.push(Some(Entry {
+            path: path.to_string(),
+            srgb,
+            center: Vec3::ZERO,
+            radius: 1.0,
+            mips: Vec::new(),
+            texture: None,
+            resident: 0,
+            desired: 0,
+            distance: f32::INFINITY,
+        }));
+        let sender = self.sender.clone();
+        let path = path.to_string();
+        pool.spawn(move || {
+            let _ = sender.send((index, Self::decode(&path)));
+        });
+        StreamedTexture(index)
+    }
+
+    fn decode(path: &str) -> Result<Vec<RgbaImage>> {
+        let image = image::open(path)
+            .map_err(|e| Error::Parse(format!("{}: {}", path, e)))?
+            .flipv()
+            .into_rgba8();
+        let mut mips = vec![image];
+        loop {
+            let last = mips.last().unwrap();
+            if last.width() == 1 && last.height() == 1 {
+                break;
+            }
+            let (width, height) = ((last.width() / 2).max(1), (last.height() / 2).max(1));
+            let next = resize(last, width, height, FilterType::Triangle);
+            mips.push(next);
+        }
+        Ok(mips)
+    }
+
+    /// 设置纹理在世界空间中的包围球，用于计算投影尺寸
+    ///
+    /// # 参数
+    /// + `texture` - 纹理句柄
+    /// + `center` - 包围球中心
+    /// + `radius` - 包围球半径
+    pub fn set_bounds(&mut self, texture: StreamedTexture, center: Vec3, radius: f32) {
+        if let Some(Some(entry)) = self.entries.get_mut(texture.0) {
+            entry.center = center;
+            entry.radius = radius.max(0.0);
+        }
+    }
+
+    /// 获取当前驻留的纹理，解码完成前或句柄无效时返回`None`
+    pub fn texture(&self, texture: StreamedTexture) -> Option<&Texture2D> {
+        self.entries.get(texture.0)?.as_ref()?.texture.as_ref()
+    }
+
+    /// 将纹理绑定到指定纹理单元，纹理尚不可用时不做任何操作
+    ///
+    /// # 返回值
+    /// 纹理可用时返回`true`
+    pub fn bind(&self, texture: StreamedTexture, unit: u32) -> bool {
+        match self.texture(texture) {
+            Some(texture) => {
+                texture.bind(unit);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// 移除纹理并释放其显存与像素数据
+    pub fn remove(&mut self, texture: StreamedTexture) {
+        if let Some(entry) = self.entries.get_mut(texture.0) {
+            *entry = None;
+        }
+    }
+
+    /// 获取纹理的驻留情况，句柄无效时返回`None`
+    pub fn residency(&self, texture: StreamedTexture) -> Option<Residency> {
+        let entry = self.entries.get(texture.0)?.as_ref()?;
+        Some(Residency {
+            levels: entry.mips.len(),
+            resident_level: entry.resident,
+            desired_level: entry.desired,
+            resident_bytes: entry.resident_bytes(),
+            distance: entry.distance,
+        })
+    }
+
+    /// 获取统计信息
+    pub fn stats(&self) -> StreamingStats {
+        let mut stats = StreamingStats {
+            budget: self.budget,
+            streamed_in: self.last_in,
+            streamed_out: self.last_out,
+            ..Default::default()
+        };
+        for entry in self.entries.iter().flatten() {
+            stats.textures += 1;
+            if entry.mips.is_empty() {
+                stats.loading += 1;
+                continue;
+            }
+            stats.resident_bytes += entry.resident_bytes();
+            stats.requested_bytes += entry.bytes_from(entry.desired);
+        }
+        stats
+    }
+
+    /// 接收解码结果，按摄像机距离与显存预算更新各纹理的驻留级别
+    ///
+    /// # 参数
+    /// + `camera` - 用于计算距离与投影尺寸的摄像机
+    /// + `viewport_height` - 视口高度，单位为像素
+    pub fn update(&mut self, camera: &Camera, viewport_height: u32) {
+        self.receive();
+
+        // 按投影尺寸计算期望级别
+        let pixels_per_unit = |distance: f32| match camera.projection {
+            Projection::Perspective { fov_y, .. } => {
+                viewport_height as f32 / (2.0 * distance.max(1e-3) * (fov_y * 0.5).tan())
+            }
+            Projection::Orthographic { height, .. } => viewport_height as f32 / height,
+        };
+        let mut order = Vec::new();
+        for (index, entry) in self.entries.iter_mut().enumerate() {
+            let Some(entry) = entry.as_mut().filter(|e| !e.mips.is_empty()) else {
+                continue;
+            };
+            entry.distance = (entry.center - camera.position).length();
+            let screen = (entry.radius * 2.0 * pixels_per_unit(entry.distance)).max(1.0);
+            let size = entry.mips[0].width().max(entry.mips[0].height()) as f32;
+            let level = ((size / screen).log2() + self.bias).floor().max(0.0) as usize;
+            entry.desired = level.min(entry.coarsest(self.base_size));
+            order.push(index);
+        }
+
+        // 超出预算时由远及近逐级降低精度
+        order.sort_by(|a, b| {
+            let distance = |i: &usize| self.entries[*i].as_ref().unwrap().distance;
+            distance(b).total_cmp(&distance(a))
+        });
+        let mut total: usize = order
+            .iter()
+            .map(|i| self.entries[*i].as_ref().unwrap())
+            .map(|e| e.bytes_from(e.desired))
+            .sum();
+        while total > self.budget {
+            let mut reduced = false;
+            for index in &order {
+                let entry = self.entries[*index].as_mut().unwrap();
+                if entry.desired < entry.coarsest(self.base_size) {
+                    let before = entry.bytes_from(entry.desired);
+                    entry.desired += 1;
+                    total -= before - entry.bytes_from(entry.desired);
+                    reduced = true;
+                    if total <= self.budget {
+                        break;
+                    }
+                }
+            }
+            if !reduced {
+                break;
+            }
+        }
+
+        // 先流出以释放显存，再由近及远逐级流入
+        let mut rebuilds = Vec::new();
+        for index in &order {
+            let entry = self.entries[*index].as_ref().unwrap();
+            if entry.desired > entry.resident {
+                rebuilds.push((*index, entry.desired));
+            }
+        }
+        self.last_out = rebuilds.len().min(self.max_rebuilds_per_update);
+        for index in order.iter().rev() {
+            let entry = self.entries[*index].as_ref().unwrap();
+            if entry.desired < entry.resident {
+                rebuilds.push((*index, entry.resident - 1));
+            }
+        }
+        rebuilds.truncate(self.max_rebuilds_per_update);
+        self.last_in = rebuilds.len() - self.last_out;
+        for (index, level) in rebuilds {
+            let entry = self.entries[index].as_mut().unwrap();
+            entry.texture = Some(build(&entry.mips, level, entry.srgb, &self.settings));
+            entry.resident = level;
+        }
+    }
+
+    fn receive(&mut self) {
+        while let Ok((index, result)) = self.receiver.try_recv() {
+            let Some(Some(entry)) = self.entries.get_mut(index) else {
+                continue;
+            };
+            match result {
+                Ok(mips) => {
+                    entry.mips = mips;
+                    let level = entry.coarsest(self.base_size);
+                    entry.texture = Some(build(&entry.mips, level, entry.srgb, &self.settings));
+                    entry.resident = level;
+                    entry.desired = level;
+                    let (path, levels) = (&entry.path, entry.mips.len());
+                    debug!("TextureStreamer", "已加载 {}，共 {} 级", path, levels);
+                }
+                Err(e) => {
+                    warn!("TextureStreamer", "加载 {} 失败: {}", entry.path, e);
+                    self.entries[index] = None;
+                }
+            }
+        }
+    }
+}
+
+/// 以`top`级及更粗的级别创建纹理对象
+fn build(mips: &[RgbaImage], top: usize, srgb: bool, settings: &TextureSettings) -> Texture2D {
+    let levels = &mips[top..];
+    let (width, height) = (levels[0].width(), levels[0].height());
+    let internal = if srgb { gl::SRGB8_ALPHA8 } else { gl::RGBA8 };
+    let mut id = 0;
+    unsafe {
+        gl::GenTextures(1, &mut id);
+        gl::BindTexture(gl::TEXTURE_2D, id);
+        gl::PixelStorei(gl::UNPACK_ALIGNMENT, 1);
+        let count = levels.len() as i32;
+        gl::TexStorage2D(gl::TEXTURE_2D, count, internal, width as i32, height as i32);
+        for (level, image) in levels.iter().enumerate() {
+            gl::TexSubImage2D(
+                gl::TEXTURE_2D,
+                level as i32,
+                0,
+                0,
+                image.width() as i32,
+                image.height() as i32,
+                gl::RGBA,
+                gl::UNSIGNED_BYTE,
+                image.as_raw().as_ptr() as *const _,
+            );
+        }
+        gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAX_LEVEL, count - 1);
+    }
+    TextureSettings {
+        generate_mipmaps: false,
+        ..*settings
+    }
+    .apply_to_bound(gl::TEXTURE_2D);
+    unsafe { gl::BindTexture(gl::TEXTURE_2D, 0) };
+    Texture2D::from_raw(id, width, height)
+}
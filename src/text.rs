@@ -0,0 +1,976 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Error, Result};
+use crate::math::*;
+use crate::{
+    AtlasBuilder, GlObjectKind, GlObjects, LocalizedText, Program, RenderStats, TextShaper,
+    Texture2D, TextureSettings,
+};
+
+const VS: &str = r#"
+#version 330 core
+layout (location = 0) in vec2 aPos;
+layout (location = 1) in vec2 aUV;
+
+uniform mat4 uViewProjection;
+
+out vec2 vUV;
+
+void main()
+{
+    gl_Position = uViewProjection * vec4(aPos, 0.0, 1.0);
+    vUV = aUV;
+}
+"#;
+
+const FS: &str = r#"
+#version 330 core
+in vec2 vUV;
+out vec4 FragColor;
+
+uniform sampler2D uAtlas;
+uniform bool uMsdf;
+uniform vec4 uColor;
+uniform vec4 uOutlineColor;
+uniform float uOutline;
+uniform float uSoftness;
+
+float median(float r, float g, float b)
+{
+    return max(min(r, g), min(max(r, g), b));
+}
+
+void main()
+{
+    vec4 s = texture(uAtlas, vUV);
+    float d = (uMsdf ? median(s.r, s.g, s.b) : s.a) - 0.5;
+    float w = max(fwidth(d), 1e-4);
+    float fill = smoothstep(-w - uSoftness, w, d);
+    float outer = smoothstep(-w - uSoftness, w, d + uOutline);
+    vec4 color = uOutline > 0.0 ? mix(uOutlineColor, uColor, fill) : uColor;
+    color.a *= uOutline > 0.0 ? outer : fill;
+    if (color.a <= 0.0) {
+        discard;
+    }
+    FragColor = color;
+}
+"#;
+
+/// 每批最多绘制的字形数量
+const MAX_GLYPHS: usize = 2048;
+/// 每个顶点的浮点数：位置(2)、纹理坐标(2)
+const VERTEX_FLOATS: usize = 4;
+/// 烘焙时的超采样倍数
+const SUPERSAMPLE: u32 = 4;
+/// 距离变换中表示"无穷远"的平方距离，使用有限值以避免运算产生 NaN
+const FAR: f32 = 1e20;
+
+/// 烘焙到图集中的字形，长度单位均为烘焙字号下的像素
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Glyph {
+    /// 水平步进
+    pub advance: f32,
+    /// 字形四边形左下角相对于基线上笔位置的偏移，包含距离场的扩展范围
+    pub offset: Vec2,
+    /// 字形四边形的尺寸，空白字形为零
+    pub size: Vec2,
+    /// 纹理区域，依次为左下角`u`、`v`与宽度、高度
+    pub uv_rect: Vec4,
+}
+
+/// 排版后的字形
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PositionedGlyph {
+    /// 字形在字体中的编号
+    pub glyph: u16,
+    /// 四边形左下角，相对于文本起点(首行基线的左端)
+    pub position: Vec2,
+    /// 四边形尺寸
+    pub size: Vec2,
+    /// 纹理区域
+    pub uv_rect: Vec4,
+}
+
+#[derive(Serialize, Deserialize)]
+struct GlyphEntry {
+    glyph: u16,
+    advance: f32,
+    offset: [f32; 2],
+    size: [f32; 2],
+    uv_rect: [f32; 4],
+}
+
+#[derive(Serialize, Deserialize)]
+struct CharEntry {
+    ch: char,
+    glyph: u16,
+}
+
+#[derive(Serialize, Deserialize)]
+struct KerningEntry {
+    left: u16,
+    right: u16,
+    amount: f32,
+}
+
+#[derive(Serialize, Deserialize)]
+struct FontFile {
+    image: String,
+    size: f32,
+    spread: f32,
+    msdf: bool,
+    ascent: f32,
+    descent: f32,
+    line_height: f32,
+    chars: Vec<CharEntry>,
+    glyphs: Vec<GlyphEntry>,
+    kerning: Vec<KerningEntry>,
+}
+
+/// 距离场字体数据
+///
+/// 保存在内存中的距离场图集与字形度量，可以由 [`SdfFontBaker`] 烘焙，或通过 [`SdfFontData::load`] 读取离线烘焙的文件。
+/// 排版只依赖该类型，可以在任意线程中进行
+#[derive(Debug, Clone)]
+pub struct SdfFontData {
+    size: f32,
+    spread: f32,
+    msdf: bool,
+    ascent: f32,
+    descent: f32,
+    line_height: f32,
+    width: u32,
+    height: u32,
+    pixels: Vec<u8>,
+    chars: HashMap<char, u16>,
+    glyphs: HashMap<u16, Glyph>,
+    kerning: HashMap<(u16, u16), f32>,
+}
+
+impl SdfFontData {
+    /// 获取烘焙字号，单位为像素
+    pub fn size(&self) -> f32 {
+        self.size
+    }
+
+    /// 获取距离场的扩展范围，单位为烘焙字号下的像素
+    pub fn spread(&self) -> f32 {
+        self.spread
+    }
+
+    /// 是否为多通道距离场
+    pub fn is_msdf(&self) -> bool {
+        self.msdf
+    }
+
+    /// 获取指定字号下的行高
+    pub fn line_height(&self, size: f32) -> f32 {
+        self.line_height * size / self.size
+    }
+
+    /// 获取指定字号下基线以上与以下的高度，下方高度通常为负
+    pub fn vertical_metrics(&self, size: f32) -> (f32, f32) {
+        let scale = size / self.size;
+        (self.ascent * scale, self.descent * scale)
+    }
+
+    /// 获取字符对应的字形编号，未烘焙的字符返回`None`
+    pub fn glyph_index(&self, ch: char) -> Option<u16> {
+        self.chars.get(&ch).copied()
+    }
+
+    /// 获取已烘焙的字形数量
+    pub fn glyph_count(&self) -> usize {
+        self.glyphs.len()
+    }
+
+    /// 由字形编号获取字形
+    pub fn glyph(&self, glyph: u16) -> Option<&Glyph> {
+        self.glyphs.get(&glyph)
+    }
+
+    /// 获取两个字形之间的字距调整，单位为烘焙字号下的像素
+    pub fn kerning(&self, left: u16, right: u16) -> f32 {
+        self.kerning.get(&(left, right)).copied().unwrap_or(0.0)
+    }
+
+    /// 获取图集的 RGBA8 像素数据，自底向上逐行排列
+    pub fn pixels(&self) -> &[u8] {
+        &self.pixels
+    }
+
+    /// 排版文本
+    ///
+    /// # 参数
+    /// + `text` - 文本，`\n`换行
+    /// + `size` - 字号，单位为像素
+    ///
+    /// # 返回值
+    /// 返回各字形的位置，未烘焙的字符以`.notdef`字形(若已烘焙)代替，否则跳过
+    pub fn layout(&self, text: &str, size: f32) -> Vec<PositionedGlyph> {
+        let scale = size / self.size;
+        let mut glyphs = Vec::new();
+        let mut pen = Vec2::ZERO;
+        let mut previous = None;
+        for ch in text.chars() {
+            if ch == '\n' {
+                pen = Vec2::new(0.0, pen.y - self.line_height * scale);
+                previous = None;
+                continue;
+            }
+            let index = self.glyph_index(ch).unwrap_or(0);
+            let Some(glyph) = self.glyphs.get(&index) else {
+                continue;
+            };
+            if let Some(previous) = previous {
+                pen.x += self.kerning(previous, index) * scale;
+            }
+            if glyph.size.x > 0.0 && glyph.size.y > 0.0 {
+                glyphs.push(PositionedGlyph {
+                    glyph: index,
+                    position: pen + glyph.offset * scale,
+                    size: glyph.size * scale,
+                    uv_rect: glyph.uv_rect,
+                });
+            }
+            pen.x += glyph.advance * scale;
+            previous = Some(index);
+        }
+        glyphs
+    }
+
+    /// 测量文本
+    ///
+    /// # 返回值
+    /// 返回最宽一行的步进宽度与所有行的总高度
+    pub fn measure(&self, text: &str, size: f32) -> Vec2 {
+        let scale = size / self.size;
+        let mut width: f32 = 0.0;
+        let mut lines = 0;
+        for line in text.split('\n') {
+            let mut pen = 0.0;
+            let mut previous = None;
+            for ch in line.chars() {
+                let index = self.glyph_index(ch).unwrap_or(0);
+                let Some(glyph) = self.glyphs.get(&index) else {
+                    continue;
+                };
+                if let Some(previous) = previous {
+                    pen += self.kerning(previous, index);
+                }
+                pen += glyph.advance;
+                previous = Some(index);
+            }
+            width = width.max(pen * scale);
+            lines += 1;
+        }
+        Vec2::new(width, lines as f32 * self.line_height * scale)
+    }
+
+    /// 读取离线烘焙的字体
+    ///
+    /// # 参数
+    /// + `path` - 由 [`SdfFontData::save`] 写出的描述文件(`.toml`)路径，图集图像位于同一目录
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let src = fs::read_to_string(path)?;
+        let file = toml::from_str::<FontFile>(&src)
+            .map_err(|e| Error::Parse(format!("{}: {}", path.display(), e)))?;
+        let image_path = path.parent().unwrap_or(Path::new("")).join(&file.image);
+        let image = image::open(&image_path)
+            .map_err(|e| Error::Parse(format!("{}: {}", image_path.display(), e)))?
+            .flipv()
+            .into_rgba8();
+        Ok(Self {
+            size: file.size,
+            spread: file.spread,
+            msdf: file.msdf,
+            ascent: file.ascent,
+            descent: file.descent,
+            line_height: file.line_height,
+            width: image.width(),
+            height: image.height(),
+            pixels: image.into_raw(),
+            chars: file.chars.into_iter().map(|c| (c.ch, c.glyph)).collect(),
+            glyphs: file
+                .glyphs
+                .into_iter()
+                .map(|g| {
+                    let glyph = Glyph {
+                        advance: g.advance,
+                        offset: Vec2::from(g.offset),
+                        size: Vec2::from(g.size),
+                        uv_rect: Vec4::from(g.uv_rect),
+                    };
+                    (g.glyph, glyph)
+                })
+                .collect(),
+            kerning: file
+                .kerning
+                .into_iter()
+                .map(|k| ((k.left, k.right), k.amount))
+                .collect(),
+        })
+    }
+
+    /// 保存为描述文件与 PNG 图集
+    ///
+    /// # 参数
+    /// + `path` - 描述文件(`.toml`)路径，图集以相同的文件名与`.png`扩展名保存在同一目录
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let path = path.as_ref();
+        let image_path = path.with_extension("png");
+        let row = self.width as usize * 4;
+        let flipped: Vec<u8> = self.pixels.chunks_exact(row).rev().flatten().copied().collect();
+        image::save_buffer(
+            &image_path,
+            &flipped,
+            self.width,
+            self.height,
+            image::ColorType::Rgba8,
+        )
+        .map_err(|e| Error::Parse(format!("{}: {}", image_path.display(), e)))?;
+
+        let mut chars: Vec<_> =
+            self.chars.iter().map(|(&ch, &glyph)| CharEntry { ch, glyph }).collect();
+        chars.sort_by_key(|c| c.ch);
+        let mut glyphs: Vec<_> = self
+            .glyphs
+            .iter()
+            .map(|(&glyph, g)| GlyphEntry {
+                glyph,
+                advance: g.advance,
+                offset: g.offset.to_array(),
+                size: g.size.to_array(),
+                uv_rect: g.uv_rect.to_array(),
+            })
+            .collect();
+        glyphs.sort_by_key(|g| g.glyph);
+        let mut kerning: Vec<_> = self
+            .kerning
+            .iter()
+            .map(|(&(left, right), &amount)| KerningEntry {
+                left,
+                right,
+                amount,
+            })
+            .collect();
+        kerning.sort_by_key(|k| (k.left, k.right));
+        let file = FontFile {
+            image: image_path.file_name().unwrap_or_default().to_string_lossy().to_string(),
+            size: self.size,
+            spread: self.spread,
+            msdf: self.msdf,
+            ascent: self.ascent,
+            descent: self.descent,
+            line_height: self.line_height,
+            chars,
+            glyphs,
+            kerning,
+        };
+        let src = toml::to_string(&file).map_err(|e| Error::Parse(e.to_string()))?;
+        fs::write(path, src)?;
+        Ok(())
+    }
+
+    /// 将图集上传为纹理，创建可绘制的字体
+    ///
+    /// # 注解
+    ///
+    /// 只能在渲染线程中调用
+    pub fn upload(self) -> SdfFont {
+        let settings = TextureSettings::clamped();
+        let texture =
+            Texture2D::from_rgba8_with(self.width, self.height, &self.pixels, false, &settings);
+        SdfFont {
+            data: self,
+            texture,
+        }
+    }
+}
+
+/// 距离场字体烘焙器
+///
+/// 以 4 倍分辨率光栅化字形轮廓，经欧氏距离变换得到有向距离后降采样，打包到图集中。
+/// 烘焙较慢，通常离线进行并以 [`SdfFontData::save`] 保存，也可以在工作线程中执行
+///
+/// # 示例
+///
+/// ```no_run
+/// use gle::*;
+///
+/// let ttf = std::fs::read("assets/NotoSans-Regular.ttf").unwrap();
+/// let baker = SdfFontBaker::default();
+/// let font = baker.bake(&ttf, (' '..='~').chain('À'..='ÿ')).unwrap();
+/// font.save("assets/noto_sans.toml").unwrap();
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SdfFontBaker {
+    /// 烘焙字号，单位为像素，默认为 48
+    pub size: f32,
+    /// 距离场的扩展范围，单位为像素，决定描边与阴影的最大宽度，默认为 6
+    pub spread: f32,
+    /// 图集中字形之间的填充像素数，默认为 1
+    pub padding: u32,
+}
+
+impl Default for SdfFontBaker {
+    fn default() -> Self {
+        Self {
+            size: 48.0,
+            spread: 6.0,
+            padding: 1,
+        }
+    }
+}
+
+impl SdfFontBaker {
+    /// 烘焙字体中的指定字符
+    ///
+    /// # 参数
+    /// + `font` - TrueType 或 OpenType 字体文件内容
+    /// + `chars` - 需要烘焙的字符，字体中不存在的字符被忽略
+    ///
+    /// # 返回值
+    /// 成功时返回字体数据，字体解析失败或图集超出最大尺寸时返回错误
+    pub fn bake(&self, font: &[u8], chars: impl IntoIterator<Item = char>) -> Result<SdfFontData> {
+        let parsed = Self::parse(font)?;
+        let mut map = HashMap::new();
+        // 0 号字形为 .notdef，用于显示未烘焙的字符
+        let mut indices = vec![0];
+        for ch in chars {
+            let index = parsed.lookup_glyph_index(ch);
+            if index != 0 {
+                map.insert(ch, index);
+                indices.push(index);
+            }
+        }
+        let mut data = self.bake_glyphs(&parsed, &indices)?;
+        data.chars = map;
+        Ok(data)
+    }
+
+    /// 按字形编号烘焙字体，用于需要连字、变体等不与字符一一对应的字形的场合
+    ///
+    /// # 参数
+    /// + `font` - TrueType 或 OpenType 字体文件内容
+    /// + `glyphs` - 字形编号
+    ///
+    /// # 返回值
+    /// 返回的字体数据不含字符映射，应配合整形结果按字形编号使用
+    pub fn bake_indices(&self, font: &[u8], glyphs: &[u16]) -> Result<SdfFontData> {
+        self.bake_glyphs(&Self::parse(font)?, glyphs)
+    }
+
+    fn parse(font: &[u8]) -> Result<fontdue::Font> {
+        fontdue::Font::from_bytes(font, fontdue::FontSettings::default())
+            .map_err(|e| Error::Parse(e.to_string()))
+    }
+
+    fn bake_glyphs(&self, font: &fontdue::Font, indices: &[u16]) -> Result<SdfFontData> {
+        let mut indices = indices.to_vec();
+        indices.sort_unstable();
+        indices.dedup();
+
+        let mut builder = AtlasBuilder::new().with_padding(self.padding);
+        let mut glyphs = HashMap::new();
+        for &index in &indices {
+            let (glyph, pixels) = self.bake_glyph(font, index);
+            if let Some(pixels) = pixels {
+                let (w, h) = (glyph.size.x as u32, glyph.size.y as u32);
+                builder.add(&index.to_string(), w, h, pixels);
+            }
+            glyphs.insert(index, glyph);
+        }
+        let atlas = builder.build()?;
+        for (name, region) in atlas.regions() {
+            if let Some(glyph) = name.parse().ok().and_then(|i: u16| glyphs.get_mut(&i)) {
+                glyph.uv_rect = region.uv_rect;
+            }
+        }
+
+        let mut kerning = HashMap::new();
+        for &left in &indices {
+            for &right in &indices {
+                if let Some(amount) = font.horizontal_kern_indexed(left, right, self.size) {
+                    if amount != 0.0 {
+                        kerning.insert((left, right), amount);
+                    }
+                }
+            }
+        }
+        let metrics = font.horizontal_line_metrics(self.size);
+        Ok(SdfFontData {
+            size: self.size,
+            spread: self.spread,
+            msdf: false,
+            ascent: metrics.map_or(self.size * 0.8, |m| m.ascent),
+            descent: metrics.map_or(-self.size * 0.2, |m| m.descent),
+            line_height: metrics.map_or(self.size * 1.2, |m| m.new_line_size),
+            width: atlas.width(),
+            height: atlas.height(),
+            pixels: atlas.pixels().to_vec(),
+            chars: HashMap::new(),
+            glyphs,
+            kerning,
+        })
+    }
+
+    /// 烘焙单个字形，空白字形不生成像素
+    fn bake_glyph(&self, font: &fontdue::Font, index: u16) -> (Glyph, Option<Vec<u8>>) {
+        let ss = SUPERSAMPLE as f32;
+        let (metrics, coverage) = font.rasterize_indexed(index, self.size * ss);
+        let advance = metrics.advance_width / ss;
+        if metrics.width == 0 || metrics.height == 0 {
+            let glyph = Glyph {
+                advance,
+                offset: Vec2::ZERO,
+                size: Vec2::ZERO,
+                uv_rect: Vec4::ZERO,
+            };
+            return (glyph, None);
+        }
+
+        // 在四周留出扩展范围后计算有向距离，外部为正
+        let pad = (self.spread * ss).ceil() as usize;
+        let (w, h) = (metrics.width + pad * 2, metrics.height + pad * 2);
+        let mut outside = vec![FAR; w * h];
+        let mut inside = vec![0.0; w * h];
+        for y in 0..metrics.height {
+            for x in 0..metrics.width {
+                if coverage[y * metrics.width + x] >= 128 {
+                    outside[(y + pad) * w + x + pad] = 0.0;
+                    inside[(y + pad) * w + x + pad] = FAR;
+                }
+            }
+        }
+        distance_transform(&mut outside, w, h);
+        distance_transform(&mut inside, w, h);
+
+        // 降采样到烘焙字号，光栅化结果自上而下，图集自下而上
+        let (ow, oh) = (w.div_ceil(SUPERSAMPLE as usize), h.div_ceil(SUPERSAMPLE as usize));
+        let mut pixels = Vec::with_capacity(ow * oh * 4);
+        let range = 2.0 * self.spread * ss;
+        for oy in (0..oh).rev() {
+            for ox in 0..ow {
+                let sx = (ox * SUPERSAMPLE as usize + SUPERSAMPLE as usize / 2).min(w - 1);
+                let sy = (oy * SUPERSAMPLE as usize + SUPERSAMPLE as usize / 2).min(h - 1);
+                let i = sy * w + sx;
+                let distance = outside[i].sqrt() - inside[i].sqrt();
+                let value = ((0.5 - distance / range).clamp(0.0, 1.0) * 255.0).round() as u8;
+                pixels.extend_from_slice(&[value; 4]);
+            }
+        }
+        // 降采样向上取整多出的行位于底部，左下角按此对齐
+        let bottom = (oh * SUPERSAMPLE as usize - h) as f32;
+        let glyph = Glyph {
+            advance,
+            offset: Vec2::new(
+                (metrics.xmin as f32 - pad as f32) / ss,
+                (metrics.ymin as f32 - pad as f32 - bottom) / ss,
+            ),
+            size: Vec2::new(ow as f32, oh as f32),
+            uv_rect: Vec4::ZERO,
+        };
+        (glyph, Some(pixels))
+    }
+}
+
+/// 对平方距离网格做二维欧氏距离变换，见 Felzenszwalb 与 Huttenlocher 的线性时间算法
+fn distance_transform(grid: &mut [f32], width: usize, height: usize) {
+    let n = width.max(height);
+    let mut f = vec![0.0; n];
+    let mut d = vec![0.0; n];
+    let mut v = vec![0; n];
+    let mut z = vec![0.0; n + 1];
+    for x in 0..width {
+        for y in 0..height {
+            f[y] = grid[y * width + x];
+        }
+        distance_transform_1d(&f[..height], &mut d[..height], &mut v, &mut z);
+        for y in 0..height {
+            grid[y * width + x] = d[y];
+        }
+    }
+    for y in 0..height {
+        let row = &mut grid[y * width..(y + 1) * width];
+        f[..width].copy_from_slice(row);
+        distance_transform_1d(&f[..width], row, &mut v, &mut z);
+    }
+}
+
+fn distance_transform_1d(f: &[f32], d: &mut [f32], v: &mut [usize], z: &mut [f32]) {
+    let parabola = |q: usize, p: usize| {
+        ((f[q] + (q * q) as f32) - (f[p] + (p * p) as f32)) / (2 * q - 2 * p) as f32
+    };
+    let mut k = 0;
+    v[0] = 0;
+    z[0] = -FAR;
+    z[1] = FAR;
+    for q in 1..f.len() {
+        let mut s = parabola(q, v[k]);
+        while s <= z[k] {
+            k -= 1;
+            s = parabola(q, v[k]);
+        }
+        k += 1;
+        v[k] = q;
+        z[k] = s;
+        z[k + 1] = FAR;
+    }
+    k = 0;
+    for (q, out) in d.iter_mut().enumerate() {
+        while z[k + 1] < q as f32 {
+            k += 1;
+        }
+        let offset = q as f32 - v[k] as f32;
+        *out = offset * offset + f[v[k]];
+    }
+}
+
+/// 可绘制的距离场字体
+///
+/// 由 [`SdfFontData::upload`] 创建，持有图集纹理
+#[derive(Debug)]
+pub struct SdfFont {
+    data: SdfFontData,
+    texture: Texture2D,
+}
+
+impl SdfFont {
+    /// 读取离线烘焙的字体并上传图集，只能在渲染线程中调用
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Ok(SdfFontData::load(path)?.upload())
+    }
+
+    /// 获取字体数据
+    pub fn data(&self) -> &SdfFontData {
+        &self.data
+    }
+
+    /// 获取图集纹理
+    pub fn texture(&self) -> &Texture2D {
+        &self.texture
+    }
+
+    /// 排版文本，见 [`SdfFontData::layout`]
+    pub fn layout(&self, text: &str, size: f32) -> Vec<PositionedGlyph> {
+        self.data.layout(text, size)
+    }
+
+    /// 测量文本，见 [`SdfFontData::measure`]
+    pub fn measure(&self, text: &str, size: f32) -> Vec2 {
+        self.data.measure(text, size)
+    }
+}
+
+/// 文本样式
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TextStyle {
+    /// 字号，单位为像素
+    pub size: f32,
+    /// 文字颜色
+    pub color: Vec4,
+    /// 描边宽度，单位为像素，为零时不描边，最大不超过烘焙时的扩展范围
+    pub outline_width: f32,
+    /// 描边颜色
+    pub outline_color: Vec4,
+    /// 阴影偏移，单位为像素
+    pub shadow_offset: Vec2,
+    /// 阴影颜色，透明度为零时不绘制阴影
+    pub shadow_color: Vec4,
+    /// 阴影边缘的柔化宽度，单位为像素
+    pub shadow_softness: f32,
+}
+
+impl Default for TextStyle {
+    fn default() -> Self {
+        Self {
+            size: 32.0,
+            color: Vec4::ONE,
+            outline_width: 0.0,
+            outline_color: Vec4::new(0.0, 0.0, 0.0, 1.0),
+            shadow_offset: Vec2::new(2.0, -2.0),
+            shadow_color: Vec4::ZERO,
+            shadow_softness: 0.0,
+        }
+    }
+}
+
+/// 距离场文本渲染器
+///
+/// 字形边缘在片元着色器中按屏幕空间导数抗锯齿，任意缩放下保持锐利；描边与阴影只需调整距离阈值，
+/// 阴影为偏移后的额外一次绘制
+///
+/// # 示例
+///
+/// ```
+/// use gle::*;
+/// use gle::math::*;
+///
+/// fn render_init() -> (TextRenderer, SdfFont) {
+///     let font = SdfFont::load("assets/noto_sans.toml").unwrap();
+///     (TextRenderer::new().unwrap(), font)
+/// }
+///
+/// fn render_loop(text: &mut TextRenderer, font: &SdfFont) {
+///     let style = TextStyle {
+///         size: 48.0,
+///         outline_width: 2.0,
+///         shadow_color: Vec4::new(0.0, 0.0, 0.0, 0.6),
+///         shadow_softness: 2.0,
+///         ..Default::default()
+///     };
+///     text.begin(&orthographic(0.0, 1280.0, 0.0, 720.0, -1.0, 1.0));
+///     text.draw(font, "Hello, SDF!", Vec2::new(40.0, 640.0), &style);
+///     text.end();
+/// }
+/// ```
+///
+/// # 注解
+///
+/// 绘制期间启用透明度混合并禁用深度测试，[`TextRenderer::end`] 时恢复。该类型只能在渲染线程中创建、使用与释放
+pub struct TextRenderer {
+    program: Program,
+    vao: u32,
+    vbo: u32,
+    ebo: u32,
+    vertices: Vec<f32>,
+    depth_test: bool,
+}
+
+impl TextRenderer {
+    /// 创建文本渲染器
+    ///
+    /// # 返回值
+    /// 成功时返回文本渲染器，内置着色器编译失败时返回错误
+    pub fn new() -> Result<Self> {
+        let program = Program::new(VS, FS)?;
+        let indices: Vec<u32> = (0..MAX_GLYPHS as u32)
+            .flat_map(|i| [0, 1, 2, 2, 1, 3].map(|j| i * 4 + j))
+            .collect();
+        let (mut vao, mut vbo, mut ebo) = (0, 0, 0);
+        let stride = (VERTEX_FLOATS * std::mem::size_of::<f32>()) as i32;
+        unsafe {
+            gl::GenVertexArrays(1, &mut vao);
+            gl::GenBuffers(1, &mut vbo);
+            gl::GenBuffers(1, &mut ebo);
+            gl::BindVertexArray(vao);
+            gl::BindBuffer(gl::ARRAY_BUFFER, vbo);
+            gl::BufferData(
+                gl::ARRAY_BUFFER,
+                (MAX_GLYPHS * 4 * stride as usize) as isize,
+                std::ptr::null(),
+                gl::STREAM_DRAW,
+            );
+            gl::BindBuffer(gl::ELEMENT_ARRAY_BUFFER, ebo);
+            gl::BufferData(
+                gl::ELEMENT_ARRAY_BUFFER,
+                std::mem::size_of_val(indices.as_slice()) as isize,
+                indices.as_ptr() as *const _,
+                gl::STATIC_DRAW,
+            );
+            gl::EnableVertexAttribArray(0);
+            gl::VertexAttribPointer(0, 2, gl::FLOAT, gl::FALSE, stride, std::ptr::null());
+            gl::EnableVertexAttribArray(1);
+            gl::VertexAttribPointer(
+                1,
+                2,
+                gl::FLOAT,
+                gl::FALSE,
+                stride,
+                (2 * std::mem::size_of::<f32>()) as *const _,
+            );
+            gl::BindVertexArray(0);
+        }
+        GlObjects::track(GlObjectKind::VertexArray, vao);
+        GlObjects::track(GlObjectKind::Buffer, vbo);
+        GlObjects::track(GlObjectKind::Buffer, ebo);
+        Ok(Self {
+            program,
+            vao,
+            vbo,
+            ebo,
+            vertices: Vec::with_capacity(MAX_GLYPHS * 4 * VERTEX_FLOATS),
+            depth_test: false,
+        })
+    }
+
+    /// 开始绘制文本
+    ///
+    /// # 参数
+    /// + `view_projection` - 将世界坐标变换到裁剪空间的矩阵，以像素为单位的正交投影下字号与屏幕像素一致
+    pub fn begin(&mut self, view_projection: &Mat4) {
+        self.program.bind();
+        self.program.set("uViewProjection", view_projection);
+        self.program.set("uAtlas", &0i32);
+        unsafe {
+            self.depth_test = gl::IsEnabled(gl::DEPTH_TEST) == gl::TRUE;
+            gl::Disable(gl::DEPTH_TEST);
+            gl::Enable(gl::BLEND);
+            gl::BlendFunc(gl::SRC_ALPHA, gl::ONE_MINUS_SRC_ALPHA);
+        }
+    }
+
+    /// 绘制文本
+    ///
+    /// # 参数
+    /// + `font` - 字体
+    /// + `text` - 文本，`\n`换行
+    /// + `position` - 首行基线左端的位置
+    /// + `style` - 文本样式
+    pub fn draw(&mut self, font: &SdfFont, text: &str, position: Vec2, style: &TextStyle) {
+        let glyphs = font.layout(text, style.size);
+        self.draw_glyphs(font, &glyphs, position, style);
+    }
+
+    /// 整形并绘制文本，用于需要双向排序或复杂文字整形的场合
+    ///
+    /// # 参数
+    /// + `font` - 以整形器所用字体文件烘焙的字体
+    /// + `shaper` - 文本整形器
+    /// + `text` - 文本，`\n`换行
+    /// + `position` - 首行基线左端的位置，从右到左的文本在整形结果的宽度内右对齐
+    /// + `style` - 文本样式
+    pub fn draw_shaped(
+        &mut self,
+        font: &SdfFont,
+        shaper: &TextShaper,
+        text: &str,
+        position: Vec2,
+        style: &TextStyle,
+    ) {
+        let shaped = shaper.shape(text, font.data(), style.size);
+        self.draw_glyphs(font, &shaped.glyphs, position, style);
+    }
+
+    /// 绘制当前语言下的本地化文本，语言切换后自动使用新的翻译
+    ///
+    /// # 参数
+    /// + `font` - 包含目标语言字符的字体
+    /// + `text` - 本地化文本
+    /// + `position` - 首行基线左端的位置
+    /// + `style` - 文本样式
+    ///
+    /// # 注解
+    ///
+    /// 该函数不做整形，从右到左的语言(见 [`Locale::is_rtl`])应以 [`TextRenderer::draw_shaped`] 绘制
+    /// [`LocalizedText::text`] 的结果
+    pub fn draw_localized(
+        &mut self,
+        font: &SdfFont,
+        text: &mut LocalizedText,
+        position: Vec2,
+        style: &TextStyle,
+    ) {
+        let glyphs = font.layout(text.text(), style.size);
+        self.draw_glyphs(font, &glyphs, position, style);
+    }
+
+    /// 绘制已排版的字形
+    ///
+    /// # 参数
+    /// + `font` - 排版所用的字体
+    /// + `glyphs` - 字形，通常由 [`SdfFont::layout`] 得到
+    /// + `position` - 文本起点
+    /// + `style` - 文本样式，其中的字号应与排版时一致
+    pub fn draw_glyphs(
+        &mut self,
+        font: &SdfFont,
+        glyphs: &[PositionedGlyph],
+        position: Vec2,
+        style: &TextStyle,
+    ) {
+        if glyphs.is_empty() {
+            return;
+        }
+        // 像素宽度换算为归一化的距离值，距离值 1 对应烘焙字号下扩展范围的两倍
+        let data = font.data();
+        let to_distance = |px: f32| px * data.size / style.size / (2.0 * data.spread);
+        self.program.set("uMsdf", &data.msdf);
+        unsafe {
+            gl::ActiveTexture(gl::TEXTURE0);
+            gl::BindTexture(gl::TEXTURE_2D, font.texture.id());
+        }
+        if style.shadow_color.w > 0.0 {
+            let outline = to_distance(style.outline_width).min(0.5);
+            self.program.set("uColor", &style.shadow_color);
+            self.program.set("uOutlineColor", &style.shadow_color);
+            self.program.set("uOutline", &outline);
+            let softness = to_distance(style.shadow_softness).min(0.5 - outline);
+            self.program.set("uSoftness", &softness);
+            self.submit(glyphs, position + style.shadow_offset);
+        }
+        self.program.set("uColor", &style.color);
+        self.program.set("uOutlineColor", &style.outline_color);
+        self.program.set("uOutline", &to_distance(style.outline_width).min(0.5));
+        self.program.set("uSoftness", &0.0f32);
+        self.submit(glyphs, position);
+    }
+
+    /// 结束绘制并恢复渲染状态
+    pub fn end(&mut self) {
+        unsafe {
+            gl::Disable(gl::BLEND);
+            if self.depth_test {
+                gl::Enable(gl::DEPTH_TEST);
+            }
+        }
+    }
+
+    fn submit(&mut self, glyphs: &[PositionedGlyph], origin: Vec2) {
+        for chunk in glyphs.chunks(MAX_GLYPHS) {
+            self.vertices.clear();
+            for glyph in chunk {
+                let min = origin + glyph.position;
+                let max = min + glyph.size;
+                let uv = glyph.uv_rect;
+                let uv_min = Vec2::new(uv.x, uv.y);
+                let uv_max = uv_min + Vec2::new(uv.z, uv.w);
+                for (pos, uv) in [
+                    (min, uv_min),
+                    (Vec2::new(max.x, min.y), Vec2::new(uv_max.x, uv_min.y)),
+                    (Vec2::new(min.x, max.y), Vec2::new(uv_min.x, uv_max.y)),
+                    (max, uv_max),
+                ] {
+                    self.vertices.extend_from_slice(&[pos.x, pos.y, uv.x, uv.y]);
+                }
+            }
+            unsafe {
+                gl::BindBuffer(gl::ARRAY_BUFFER, self.vbo);
+                gl::BufferSubData(
+                    gl::ARRAY_BUFFER,
+                    0,
+                    (self.vertices.len() * std::mem::size_of::<f32>()) as isize,
+                    self.vertices.as_ptr() as *const _,
+                );
+                gl::BindVertexArray(self.vao);
+                RenderStats::record_draw(chunk.len() as u64 * 2);
+                gl::DrawElements(
+                    gl::TRIANGLES,
+                    (chunk.len() * 6) as i32,
+                    gl::UNSIGNED_INT,
+                    std::ptr::null(),
+                );
+                gl::BindVertexArray(0);
+            }
+        }
+    }
+}
+
+impl Drop for TextRenderer {
+    fn drop(&mut self) {
+        GlObjects::untrack(GlObjectKind::VertexArray, self.vao);
+        GlObjects::untrack(GlObjectKind::Buffer, self.vbo);
+        GlObjects::untrack(GlObjectKind::Buffer, self.ebo);
+        unsafe {
+            gl::DeleteBuffers(1, &self.ebo);
+            gl::DeleteBuffers(1, &self.vbo);
+            gl::DeleteVertexArrays(1, &self.vao);
+        }
+    }
+}
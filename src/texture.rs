@@ -0,0 +1,492 @@
+use std::path::Path;
+
+use crate::error::{Error, Result};
+use crate::{GlObjectKind, GlObjects, PixelBufferRing, RenderStats, TextureSettings};
+
+/// HDR 纹理在显存中的格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum HdrFormat {
+    /// 每通道 16 位半精度浮点，每像素 8 字节，保留透明度
+    #[default]
+    Rgba16F,
+    /// 共享指数的 RGB，每像素 4 字节，不含透明度，适合环境贴图
+    Rgb9E5,
+}
+
+/// 二维纹理
+///
+/// 对 OpenGL 纹理对象的封装，在被释放时自动删除纹理对象
+///
+/// # 示例
+///
+/// ```
+/// use gle::*;
+///
+/// fn render_init() {
+///     let texture = Texture2D::load("assets/brick.png", true).unwrap();
+///     texture.bind(0);
+/// }
+/// ```
+///
+/// # 注解
+///
+/// 该类型的所有方法只能在渲染线程中调用
+#[derive(Debug)]
+pub struct Texture2D {
+    id: u32,
+    width: u32,
+    height: u32,
+}
+
+impl Texture2D {
+    /// 由 RGBA8 像素数据创建纹理
+    ///
+    /// # 参数
+    /// + `width` - 宽度
+    /// + `height` - 高度
+    /// + `pixels` - 像素数据，自底向上逐行排列，长度应为`width * height * 4`
+    /// + `srgb` - 是否为 sRGB 颜色空间，颜色贴图应为`true`，法线等数据贴图应为`false`
+    ///
+    /// # 注解
+    ///
+    /// 纹理使用默认设置 [`TextureSettings::default`]，即生成多级渐远纹理，并使用三线性过滤与重复环绕
+    pub fn from_rgba8(width: u32, height: u32, pixels: &[u8], srgb: bool) -> Self {
+        Self::from_rgba8_with(width, height, pixels, srgb, &TextureSettings::default())
+    }
+
+    /// 由 RGBA8 像素数据以指定采样设置创建纹理
+    ///
+    /// # 参数
+    /// + `width` - 宽度
+    /// + `height` - 高度
+    /// + `pixels` - 像素数据，自底向上逐行排列，长度应为`width * height * 4`
+    /// + `srgb` - 是否为 sRGB 颜色空间
+    /// + `settings` - 采样设置
+    pub fn from_rgba8_with(
+        width: u32,
+        height: u32,
+        pixels: &[u8],
+        srgb: bool,
+        settings: &TextureSettings,
+    ) -> Self {
+        assert_eq!(pixels.len(), (width * height * 4) as usize, "像素数据长度不匹配");
+        let internal = if srgb { gl::SRGB8_ALPHA8 } else { gl::RGBA8 };
+        let mut id = 0;
+        unsafe {
+            gl::GenTextures(1, &mut id);
+            gl::BindTexture(gl::TEXTURE_2D, id);
+            gl::PixelStorei(gl::UNPACK_ALIGNMENT, 1);
+            gl::TexImage2D(
+                gl::TEXTURE_2D,
+                0,
+                internal as i32,
+                width as i32,
+                height as i32,
+                0,
+                gl::RGBA,
+                gl::UNSIGNED_BYTE,
+                pixels.as_ptr() as *const _,
+            );
+        }
+        settings.apply_to_bound(gl::TEXTURE_2D);
+        unsafe { gl::BindTexture(gl::TEXTURE_2D, 0) };
+        Self::from_raw(id, width, height)
+    }
+
+    /// 由浮点像素数据创建 HDR 纹理
+    ///
+    /// # 参数
+    /// + `width` - 宽度
+    /// + `height` - 高度
+    /// + `pixels` - RGBA 浮点像素数据，自底向上逐行排列，长度应为`width * height * 4`
+    /// + `format` - 纹理在显存中的格式
+    ///
+    /// # 注解
+    ///
+    /// 纹理使用默认设置 [`TextureSettings::default`]，即生成多级渐远纹理，并使用三线性过滤与重复环绕
+    pub fn from_rgba32f(width: u32, height: u32, pixels: &[f32], format: HdrFormat) -> Self {
+        Self::from_rgba32f_with(width, height, pixels, format, &TextureSettings::default())
+    }
+
+    /// 由浮点像素数据以指定采样设置创建 HDR 纹理
+    ///
+    /// # 参数
+    /// + `width` - 宽度
+    /// + `height` - 高度
+    /// + `pixels` - RGBA 浮点像素数据，自底向上逐行排列，长度应为`width * height * 4`
+    /// + `format` - 纹理在显存中的格式
+    /// + `settings` - 采样设置
+    pub fn from_rgba32f_with(
+        width: u32,
+        height: u32,
+        pixels: &[f32],
+        format: HdrFormat,
+        settings: &TextureSettings,
+    ) -> Self {
+        assert_eq!(pixels.len(), (width * height * 4) as usize, "像素数据长度不匹配");
+        let internal = match format {
+            HdrFormat::Rgba16F => gl::RGBA16F,
+            HdrFormat::Rgb9E5 => gl::RGB9_E5,
+        };
+        let mut id = 0;
+        unsafe {
+            gl::GenTextures(1, &mut id);
+            gl::BindTexture(gl::TEXTURE_2D, id);
+            gl::PixelStorei(gl::UNPACK_ALIGNMENT, 4);
+            gl::TexImage2D(
+                gl::TEXTURE_2D,
+                0,
+                internal as i32,
+                width as i32,
+                height as i32,
+                0,
+                gl::RGBA,
+                gl::FLOAT,
+                pixels.as_ptr() as *const _,
+            );
+        }
+        settings.apply_to_bound(gl::TEXTURE_2D);
+        unsafe { gl::BindTexture(gl::TEXTURE_2D, 0) };
+        Self::from_raw(id, width, height)
+    }
+
+    /// 从 HDR 图像文件加载纹理
+    ///
+    /// # 参数
+    /// + `path` - 图像文件路径，支持 Radiance HDR(`.hdr`)与 OpenEXR(`.exr`)格式，其他格式按线性颜色转换为浮点
+    /// + `format` - 纹理在显存中的格式
+    ///
+    /// # 返回值
+    /// 成功时返回纹理，文件读取或解码失败时返回错误
+    ///
+    /// # 示例
+    ///
+    /// ```
+    /// use gle::*;
+    ///
+    /// fn render_init() {
+    ///     let sky = Texture2D::load_hdr("assets/sky.hdr", HdrFormat::Rgb9E5).unwrap();
+    ///     let ibl = Ibl::from_equirectangular(sky.id(), 512).unwrap();
+    /// }
+    /// ```
+    pub fn load_hdr<P: AsRef<Path>>(path: P, format: HdrFormat) -> Result<Self> {
+        let path = path.as_ref();
+        let image = image::open(path)
+            .map_err(|e| Error::Parse(format!("{}: {}", path.display(), e)))?
+            .flipv()
+            .into_rgba32f();
+        Ok(Self::from_rgba32f(image.width(), image.height(), image.as_raw(), format))
+    }
+
+    /// 从图像文件加载纹理
+    ///
+    /// # 参数
+    /// + `path` - 图像文件路径，支持 PNG、JPEG、TGA、BMP 等常见格式
+    /// + `srgb` - 是否为 sRGB 颜色空间
+    ///
+    /// # 返回值
+    /// 成功时返回纹理，文件读取或解码失败时返回错误
+    pub fn load<P: AsRef<Path>>(path: P, srgb: bool) -> Result<Self> {
+        Self::load_with(path, srgb, &TextureSettings::default())
+    }
+
+    /// 从图像文件以指定采样设置加载纹理
+    ///
+    /// # 参数
+    /// + `path` - 图像文件路径
+    /// + `srgb` - 是否为 sRGB 颜色空间
+    /// + `settings` - 采样设置
+    ///
+    /// # 返回值
+    /// 成功时返回纹理，文件读取或解码失败时返回错误
+    pub fn load_with<P: AsRef<Path>>(
+        path: P,
+        srgb: bool,
+        settings: &TextureSettings,
+    ) -> Result<Self> {
+        let path = path.as_ref();
+        let image = image::open(path)
+            .map_err(|e| Error::Parse(format!("{}: {}", path.display(), e)))?
+            .flipv()
+            .into_rgba8();
+        let (width, height) = (image.width(), image.height());
+        Ok(Self::from_rgba8_with(width, height, image.as_raw(), srgb, settings))
+    }
+
+    /// 接管已创建的纹理对象，纹理被释放时删除该对象
+    pub(crate) fn from_raw(id: u32, width: u32, height: u32) -> Self {
+        RenderStats::_track_texture(RenderStats::_texture_estimate(width, height, 1));
+        GlObjects::track(GlObjectKind::Texture, id);
+        Self { id, width, height }
+    }
+
+    /// 获取 OpenGL 纹理对象ID
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    /// 获取宽度
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// 获取高度
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// 将纹理绑定到指定纹理单元
+    ///
+    /// # 参数
+    /// + `unit` - 纹理单元序号，从0开始
+    pub fn bind(&self, unit: u32) {
+        unsafe {
+            gl::ActiveTexture(gl::TEXTURE0 + unit);
+            gl::BindTexture(gl::TEXTURE_2D, self.id);
+        }
+    }
+
+    /// 经像素缓冲对象异步更新纹理的一个区域
+    ///
+    /// 像素数据先复制到 [`PixelBufferRing`] 的下一个缓冲中，纹理从缓冲读取数据，调用不等待 GPU 完成上传
+    ///
+    /// # 参数
+    /// + `ring` - 像素缓冲对象环
+    /// + `x` - 区域左下角横坐标
+    /// + `y` - 区域左下角纵坐标
+    /// + `width` - 区域宽度
+    /// + `height` - 区域高度
+    /// + `pixels` - RGBA8 像素数据，自底向上逐行排列，长度应为`width * height * 4`
+    ///
+    /// # 注解
+    ///
+    /// 只更新最高一级纹理，需要时调用 [`Texture2D::generate_mipmaps`] 重新生成多级渐远纹理
+    pub fn update_async(
+        &self,
+        ring: &mut PixelBufferRing,
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+        pixels: &[u8],
+    ) {
+        assert_eq!(pixels.len(), (width * height * 4) as usize, "像素数据长度不匹配");
+        assert!(x + width <= self.width && y + height <= self.height, "更新区域超出纹理范围");
+        ring.begin(pixels);
+        unsafe {
+            gl::BindTexture(gl::TEXTURE_2D, self.id);
+            gl::PixelStorei(gl::UNPACK_ALIGNMENT, 1);
+            gl::TexSubImage2D(
+                gl::TEXTURE_2D,
+                0,
+                x as i32,
+                y as i32,
+                width as i32,
+                height as i32,
+                gl::RGBA,
+                gl::UNSIGNED_BYTE,
+                std::ptr::null(),
+            );
+            gl::BindTexture(gl::TEXTURE_2D, 0);
+        }
+        ring.end();
+    }
+
+    /// 由最高一级纹理重新生成多级渐远纹理
+    pub fn generate_mipmaps(&self) {
+        unsafe {
+            gl::BindTexture(gl::TEXTURE_2D, self.id);
+            gl::GenerateMipmap(gl::TEXTURE_2D);
+            gl::BindTexture(gl::TEXTURE_2D, 0);
+        }
+    }
+
+    /// 修改纹理的采样设置
+    ///
+    /// # 参数
+    /// + `settings` - 采样设置，`generate_mipmaps`为`true`且使用多级渐远纹理时将重新生成多级渐远纹理
+    pub fn apply_settings(&self, settings: &TextureSettings) {
+        unsafe { gl::BindTexture(gl::TEXTURE_2D, self.id) };
+        settings.apply_to_bound(gl::TEXTURE_2D);
+        unsafe { gl::BindTexture(gl::TEXTURE_2D, 0) };
+    }
+}
+
+impl Drop for Texture2D {
+    fn drop(&mut self) {
+        RenderStats::_track_texture(-RenderStats::_texture_estimate(self.width, self.height, 1));
+        GlObjects::untrack(GlObjectKind::Texture, self.id);
+        unsafe { gl::DeleteTextures(1, &self.id) };
+    }
+}
+
+/// 二维纹理数组
+///
+/// 由若干尺寸相同的图层组成，着色器中以`sampler2DArray`采样，常用于体素方块等需要大量同尺寸贴图的场合，
+/// 在被释放时自动删除纹理对象
+///
+/// # 示例
+///
+/// ```
+/// use gle::*;
+///
+/// fn render_init() {
+///     let textures = TextureArray::load(&["assets/dirt.png", "assets/grass.png"], true).unwrap();
+///     textures.bind(0);
+/// }
+/// ```
+///
+/// # 注解
+///
+/// 该类型的所有方法只能在渲染线程中调用
+#[derive(Debug)]
+pub struct TextureArray {
+    id: u32,
+    width: u32,
+    height: u32,
+    layers: u32,
+}
+
+impl TextureArray {
+    /// 由 RGBA8 像素数据创建纹理数组
+    ///
+    /// # 参数
+    /// + `width` - 每层的宽度
+    /// + `height` - 每层的高度
+    /// + `layers` - 层数
+    /// + `pixels` - 所有层的像素数据依次排列，每层自底向上逐行排列，长度应为`width * height * layers * 4`
+    /// + `srgb` - 是否为 sRGB 颜色空间
+    ///
+    /// # 注解
+    ///
+    /// 纹理将生成多级渐远纹理，并使用三线性过滤与重复环绕
+    pub fn from_rgba8(width: u32, height: u32, layers: u32, pixels: &[u8], srgb: bool) -> Self {
+        assert_eq!(
+            pixels.len(),
+            (width * height * layers * 4) as usize,
+            "像素数据长度不匹配"
+        );
+        let internal = if srgb { gl::SRGB8_ALPHA8 } else { gl::RGBA8 };
+        let mut id = 0;
+        unsafe {
+            gl::GenTextures(1, &mut id);
+            gl::BindTexture(gl::TEXTURE_2D_ARRAY, id);
+            gl::PixelStorei(gl::UNPACK_ALIGNMENT, 1);
+            gl::TexImage3D(
+                gl::TEXTURE_2D_ARRAY,
+                0,
+                internal as i32,
+                width as i32,
+                height as i32,
+                layers as i32,
+                0,
+                gl::RGBA,
+                gl::UNSIGNED_BYTE,
+                pixels.as_ptr() as *const _,
+            );
+            gl::GenerateMipmap(gl::TEXTURE_2D_ARRAY);
+            gl::TexParameteri(gl::TEXTURE_2D_ARRAY, gl::TEXTURE_WRAP_S, gl::REPEAT as i32);
+            gl::TexParameteri(gl::TEXTURE_2D_ARRAY, gl::TEXTURE_WRAP_T, gl::REPEAT as i32);
+            gl::TexParameteri(
+                gl::TEXTURE_2D_ARRAY,
+                gl::TEXTURE_MIN_FILTER,
+                gl::LINEAR_MIPMAP_LINEAR as i32,
+            );
+            gl::TexParameteri(gl::TEXTURE_2D_ARRAY, gl::TEXTURE_MAG_FILTER, gl::NEAREST as i32);
+            gl::BindTexture(gl::TEXTURE_2D_ARRAY, 0);
+        }
+        RenderStats::_track_texture(RenderStats::_texture_estimate(width, height, layers));
+        GlObjects::track(GlObjectKind::Texture, id);
+        Self {
+            id,
+            width,
+            height,
+            layers,
+        }
+    }
+
+    /// 从若干图像文件加载纹理数组，每个文件为一层
+    ///
+    /// # 参数
+    /// + `paths` - 图像文件路径，所有图像的尺寸须与第一张相同
+    /// + `srgb` - 是否为 sRGB 颜色空间
+    ///
+    /// # 返回值
+    /// 成功时返回纹理数组，文件读取、解码失败或尺寸不一致时返回错误
+    pub fn load<P: AsRef<Path>>(paths: &[P], srgb: bool) -> Result<Self> {
+        let mut size = None;
+        let mut pixels = Vec::new();
+        for path in paths {
+            let path = path.as_ref();
+            let image = image::open(path)
+                .map_err(|e| Error::Parse(format!("{}: {}", path.display(), e)))?
+                .flipv()
+                .into_rgba8();
+            let dimensions = image.dimensions();
+            if *size.get_or_insert(dimensions) != dimensions {
+                return Err(Error::Parse(format!(
+                    "{}: 尺寸 {}x{} 与第一层不一致",
+                    path.display(),
+                    dimensions.0,
+                    dimensions.1
+                )));
+            }
+            pixels.extend_from_slice(image.as_raw());
+        }
+        let (width, height) = size.unwrap_or((1, 1));
+        if paths.is_empty() {
+            pixels.extend_from_slice(&[255; 4]);
+        }
+        Ok(Self::from_rgba8(width, height, paths.len().max(1) as u32, &pixels, srgb))
+    }
+
+    /// 获取 OpenGL 纹理对象ID
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    /// 获取每层的宽度
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// 获取每层的高度
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// 获取层数
+    pub fn layers(&self) -> u32 {
+        self.layers
+    }
+
+    /// 将纹理数组绑定到指定纹理单元
+    ///
+    /// # 参数
+    /// + `unit` - 纹理单元序号，从0开始
+    pub fn bind(&self, unit: u32) {
+        unsafe {
+            gl::ActiveTexture(gl::TEXTURE0 + unit);
+            gl::BindTexture(gl::TEXTURE_2D_ARRAY, self.id);
+        }
+    }
+
+    /// 修改纹理数组的采样设置
+    ///
+    /// # 参数
+    /// + `settings` - 采样设置，`generate_mipmaps`为`true`且使用多级渐远纹理时将重新生成多级渐远纹理
+    pub fn apply_settings(&self, settings: &TextureSettings) {
+        unsafe { gl::BindTexture(gl::TEXTURE_2D_ARRAY, self.id) };
+        settings.apply_to_bound(gl::TEXTURE_2D_ARRAY);
+        unsafe { gl::BindTexture(gl::TEXTURE_2D_ARRAY, 0) };
+    }
+}
+
+impl Drop for TextureArray {
+    fn drop(&mut self) {
+        let bytes = RenderStats::_texture_estimate(self.width, self.height, self.layers);
+        RenderStats::_track_texture(-bytes);
+        GlObjects::untrack(GlObjectKind::Texture, self.id);
+        unsafe { gl::DeleteTextures(1, &self.id) };
+    }
+}
@@ -0,0 +1,908 @@
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use flate2::read::{GzDecoder, ZlibDecoder};
+use roxmltree::Node;
+use serde_json::Value;
+
+use crate::error::{Error, Result};
+use crate::math::*;
+use crate::{Sprite, SpriteBatch, Texture2D, TextureSettings};
+
+/// 图块地图分块的边长，以图块为单位
+pub const TILE_CHUNK_SIZE: u32 = 16;
+/// 图块编号中的水平翻转标志
+pub const TILE_FLIP_HORIZONTAL: u32 = 0x8000_0000;
+/// 图块编号中的竖直翻转标志
+pub const TILE_FLIP_VERTICAL: u32 = 0x4000_0000;
+/// 图块编号中的对角翻转标志，渲染时忽略
+pub const TILE_FLIP_DIAGONAL: u32 = 0x2000_0000;
+const TILE_FLAGS: u32 = TILE_FLIP_HORIZONTAL | TILE_FLIP_VERTICAL | TILE_FLIP_DIAGONAL;
+
+/// 图块集
+///
+/// 将一张纹理按固定尺寸切分为若干图块，图块自左上角起逐行编号
+#[derive(Debug)]
+pub struct Tileset {
+    texture: Texture2D,
+    first_gid: u32,
+    tile_size: UVec2,
+    margin: u32,
+    spacing: u32,
+    columns: u32,
+    tile_count: u32,
+}
+
+impl Tileset {
+    /// 创建图块集
+    ///
+    /// # 参数
+    /// + `texture` - 图块集纹理，通常以 [`TextureSettings::pixel_art`] 加载
+    /// + `tile_size` - 图块尺寸，单位为像素
+    /// + `margin` - 纹理边缘到第一个图块的距离，单位为像素
+    /// + `spacing` - 相邻图块之间的距离，单位为像素
+    pub fn new(texture: Texture2D, tile_size: UVec2, margin: u32, spacing: u32) -> Self {
+        let step = tile_size + spacing;
+        let columns = (texture.width() + spacing).saturating_sub(margin * 2) / step.x;
+        let rows = (texture.height() + spacing).saturating_sub(margin * 2) / step.y;
+        Self {
+            texture,
+            first_gid: 1,
+            tile_size,
+            margin,
+            spacing,
+            columns,
+            tile_count: columns * rows,
+        }
+    }
+
+    /// 获取图块集纹理
+    pub fn texture(&self) -> &Texture2D {
+        &self.texture
+    }
+
+    /// 获取第一个图块在地图中的全局编号
+    pub fn first_gid(&self) -> u32 {
+        self.first_gid
+    }
+
+    /// 获取图块尺寸
+    pub fn tile_size(&self) -> UVec2 {
+        self.tile_size
+    }
+
+    /// 获取图块数量
+    pub fn tile_count(&self) -> u32 {
+        self.tile_count
+    }
+
+    /// 获取图块的纹理坐标
+    ///
+    /// # 参数
+    /// + `local` - 图块在图块集中的编号，从0开始
+    ///
+    /// # 返回值
+    /// 返回图块左下角与右上角的纹理坐标
+    pub fn uv(&self, local: u32) -> (Vec2, Vec2) {
+        let size = Vec2::new(self.texture.width() as f32, self.texture.height() as f32);
+        let (column, row) = (local % self.columns.max(1), local / self.columns.max(1));
+        let x = (self.margin + column * (self.tile_size.x + self.spacing)) as f32;
+        let y = (self.margin + row * (self.tile_size.y + self.spacing)) as f32;
+        let tile = self.tile_size.as_vec2();
+        // 纹理自底向上存储，图块集图像的首行位于纹理顶部
+        (
+            Vec2::new(x / size.x, 1.0 - (y + tile.y) / size.y),
+            Vec2::new((x + tile.x) / size.x, 1.0 - y / size.y),
+        )
+    }
+}
+
+/// 自动图块规则
+///
+/// 按上、右、下、左四个相邻格子是否属于同一地形得到 4 位掩码(上为 1、右为 2、下为 4、左为 8)，
+/// 以掩码为下标选取图块编号
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AutoTile {
+    /// 各掩码对应的图块全局编号
+    pub tiles: [u32; 16],
+}
+
+#[derive(Debug, Default)]
+struct TileChunk {
+    dirty: bool,
+    /// 按图块集下标排序的精灵
+    sprites: Vec<(usize, Sprite)>,
+}
+
+/// 图块层
+#[derive(Debug)]
+pub struct TileLayer {
+    /// 图层名称
+    pub name: String,
+    /// 是否可见
+    pub visible: bool,
+    /// 不透明度
+    pub opacity: f32,
+    /// 图层在世界空间中的偏移
+    pub offset: Vec2,
+    width: u32,
+    height: u32,
+    tiles: Vec<u32>,
+    terrain: Vec<u16>,
+    chunks: Vec<TileChunk>,
+}
+
+impl TileLayer {
+    fn new(name: &str, width: u32, height: u32) -> Self {
+        let chunks = width.div_ceil(TILE_CHUNK_SIZE) * height.div_ceil(TILE_CHUNK_SIZE);
+        Self {
+            name: name.to_string(),
+            visible: true,
+            opacity: 1.0,
+            offset: Vec2::ZERO,
+            width,
+            height,
+            tiles: vec![0; (width * height) as usize],
+            terrain: vec![0; (width * height) as usize],
+            chunks: (0..chunks)
+                .map(|_| TileChunk {
+                    dirty: true,
+                    sprites: Vec::new(),
+                })
+                .collect(),
+        }
+    }
+
+    /// 获取宽度，以图块为单位
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// 获取高度，以图块为单位
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// 获取图块编号
+    ///
+    /// # 参数
+    /// + `x` - 列，从左向右
+    /// + `y` - 行，从上向下
+    ///
+    /// # 返回值
+    /// 返回包含翻转标志的全局编号，0 表示空，超出范围时返回 0
+    pub fn get(&self, x: u32, y: u32) -> u32 {
+        if x < self.width && y < self.height {
+            self.tiles[(y * self.width + x) as usize]
+        } else {
+            0
+        }
+    }
+
+    /// 设置图块编号，并清除该格的自动图块地形
+    ///
+    /// # 参数
+    /// + `x` - 列，从左向右
+    /// + `y` - 行，从上向下
+    /// + `gid` - 全局编号，可以包含翻转标志，0 表示空
+    pub fn set(&mut self, x: u32, y: u32, gid: u32) {
+        if x < self.width && y < self.height {
+            self.terrain[(y * self.width + x) as usize] = 0;
+            self.set_tile(x, y, gid);
+        }
+    }
+
+    fn set_tile(&mut self, x: u32, y: u32, gid: u32) {
+        self.tiles[(y * self.width + x) as usize] = gid;
+        let columns = self.width.div_ceil(TILE_CHUNK_SIZE);
+        let chunk = (y / TILE_CHUNK_SIZE) * columns + x / TILE_CHUNK_SIZE;
+        self.chunks[chunk as usize].dirty = true;
+    }
+
+    fn terrain(&self, x: i64, y: i64) -> u16 {
+        if x < 0 || y < 0 || x >= self.width as i64 || y >= self.height as i64 {
+            return 0;
+        }
+        self.terrain[(y * self.width as i64 + x) as usize]
+    }
+}
+
+/// 分块的二维图块地图
+///
+/// 地图由若干图块层组成，按图块集纹理经 [`SpriteBatch`] 绘制。每层按 [`TILE_CHUNK_SIZE`] 分块，
+/// 绘制时只处理与视口相交的块，块内的精灵在图块改变后才重新生成
+///
+/// 世界空间以像素为单位，地图左下角位于原点；图块坐标的行则与 Tiled 一致，自上而下编号
+///
+/// # 示例
+///
+/// ```
+/// use gle::*;
+/// use gle::math::*;
+///
+/// fn render_init() -> Tilemap {
+///     let mut map = Tilemap::load_tiled("assets/level1.tmx").unwrap();
+///     let ground = map.layer_index("Ground").unwrap();
+///     map.layer_mut(ground).unwrap().set(3, 4, 17);
+///     map
+/// }
+///
+/// fn render_loop(map: &mut Tilemap, batch: &mut SpriteBatch) {
+///     let (min, max) = (Vec2::ZERO, Vec2::new(640.0, 360.0));
+///     batch.begin(&orthographic(min.x, max.x, min.y, max.y, -1.0, 1.0));
+///     map.draw(batch, min, max);
+///     batch.end();
+/// }
+/// ```
+///
+/// # 注解
+///
+/// 该类型只能在渲染线程中创建与绘制
+#[derive(Debug)]
+pub struct Tilemap {
+    width: u32,
+    height: u32,
+    tile_size: UVec2,
+    tilesets: Vec<Tileset>,
+    layers: Vec<TileLayer>,
+    autotiles: Vec<AutoTile>,
+}
+
+impl Tilemap {
+    /// 创建空地图
+    ///
+    /// # 参数
+    /// + `width` - 宽度，以图块为单位
+    /// + `height` - 高度，以图块为单位
+    /// + `tile_size` - 图块在世界空间中的尺寸
+    pub fn new(width: u32, height: u32, tile_size: UVec2) -> Self {
+        Self {
+            width,
+            height,
+            tile_size,
+            tilesets: Vec::new(),
+            layers: Vec::new(),
+            autotiles: Vec::new(),
+        }
+    }
+
+    /// 获取宽度，以图块为单位
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// 获取高度，以图块为单位
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// 获取图块尺寸
+    pub fn tile_size(&self) -> UVec2 {
+        self.tile_size
+    }
+
+    /// 获取地图在世界空间中的尺寸
+    pub fn world_size(&self) -> Vec2 {
+        (UVec2::new(self.width, self.height) * self.tile_size).as_vec2()
+    }
+
+    /// 添加图块集
+    ///
+    /// # 返回值
+    /// 返回该图块集第一个图块的全局编号
+    pub fn add_tileset(&mut self, mut tileset: Tileset) -> u32 {
+        tileset.first_gid = self
+            .tilesets
+            .iter()
+            .map(|t| t.first_gid + t.tile_count)
+            .max()
+            .unwrap_or(1);
+        let first_gid = tileset.first_gid;
+        self.tilesets.push(tileset);
+        self.mark_dirty();
+        first_gid
+    }
+
+    /// 获取所有图块集
+    pub fn tilesets(&self) -> &[Tileset] {
+        &self.tilesets
+    }
+
+    /// 添加图块层，新图层绘制在已有图层之上
+    ///
+    /// # 返回值
+    /// 返回图层下标
+    pub fn add_layer(&mut self, name: &str) -> usize {
+        self.layers
+            .push(TileLayer::new(name, self.width, self.height));
+        self.layers.len() - 1
+    }
+
+    /// 获取所有图层，自底向上排列
+    pub fn layers(&self) -> &[TileLayer] {
+        &self.layers
+    }
+
+    /// 获取图层
+    pub fn layer(&self, index: usize) -> Option<&TileLayer> {
+        self.layers.get(index)
+    }
+
+    /// 获取可变图层
+    pub fn layer_mut(&mut self, index: usize) -> Option<&mut TileLayer> {
+        self.layers.get_mut(index)
+    }
+
+    /// 按名称查找图层下标
+    pub fn layer_index(&self, name: &str) -> Option<usize> {
+        self.layers.iter().position(|l| l.name == name)
+    }
+
+    /// 添加自动图块规则
+    ///
+    /// # 返回值
+    /// 返回规则下标，用于 [`Tilemap::paint`]
+    pub fn add_autotile(&mut self, autotile: AutoTile) -> usize {
+        self.autotiles.push(autotile);
+        self.autotiles.len() - 1
+    }
+
+    /// 以自动图块规则绘制一格，并更新该格与相邻格子的图块
+    ///
+    /// # 参数
+    /// + `layer` - 图层下标
+    /// + `x` - 列
+    /// + `y` - 行
+    /// + `autotile` - 自动图块规则下标
+    pub fn paint(&mut self, layer: usize, x: u32, y: u32, autotile: usize) {
+        if autotile >= self.autotiles.len() {
+            return;
+        }
+        self.set_terrain(layer, x, y, autotile as u16 + 1);
+    }
+
+    /// 清除一格，并更新相邻格子的自动图块
+    pub fn erase(&mut self, layer: usize, x: u32, y: u32) {
+        self.set_terrain(layer, x, y, 0);
+    }
+
+    fn set_terrain(&mut self, layer: usize, x: u32, y: u32, terrain: u16) {
+        let Some(layer) = self.layers.get_mut(layer) else {
+            return;
+        };
+        if x >= layer.width || y >= layer.height {
+            return;
+        }
+        layer.terrain[(y * layer.width + x) as usize] = terrain;
+        if terrain == 0 {
+            layer.set_tile(x, y, 0);
+        }
+        let (x, y) = (x as i64, y as i64);
+        for (cx, cy) in [(x, y), (x, y - 1), (x + 1, y), (x, y + 1), (x - 1, y)] {
+            let terrain = layer.terrain(cx, cy);
+            if terrain == 0 {
+                continue;
+            }
+            let mask = [(0, -1), (1, 0), (0, 1), (-1, 0)]
+                .iter()
+                .enumerate()
+                .filter(|(_, (dx, dy))| layer.terrain(cx + dx, cy + dy) == terrain)
+                .fold(0, |mask, (bit, _)| mask | (1 << bit));
+            let gid = self.autotiles[terrain as usize - 1].tiles[mask];
+            layer.set_tile(cx as u32, cy as u32, gid);
+        }
+    }
+
+    /// 获取世界坐标所在的图块
+    ///
+    /// # 返回值
+    /// 返回`(列, 行)`，位于地图外时返回`None`
+    pub fn world_to_tile(&self, position: Vec2) -> Option<UVec2> {
+        let tile = (position / self.tile_size.as_vec2()).floor();
+        let (x, y) = (tile.x as i64, self.height as i64 - 1 - tile.y as i64);
+        if x < 0 || y < 0 || x >= self.width as i64 || y >= self.height as i64 {
+            return None;
+        }
+        Some(UVec2::new(x as u32, y as u32))
+    }
+
+    /// 获取图块左下角的世界坐标
+    pub fn tile_to_world(&self, x: u32, y: u32) -> Vec2 {
+        let row = self.height as i64 - 1 - y as i64;
+        Vec2::new(x as f32, row as f32) * self.tile_size.as_vec2()
+    }
+
+    /// 绘制与视口相交的图块
+    ///
+    /// # 参数
+    /// + `batch` - 已调用 [`SpriteBatch::begin`] 的精灵批处理器
+    /// + `view_min` - 视口左下角的世界坐标
+    /// + `view_max` - 视口右上角的世界坐标
+    pub fn draw(&mut self, batch: &mut SpriteBatch, view_min: Vec2, view_max: Vec2) {
+        let chunk_size = (self.tile_size * TILE_CHUNK_SIZE).as_vec2();
+        let rows = self.height.div_ceil(TILE_CHUNK_SIZE) as i64;
+        let columns = self.width.div_ceil(TILE_CHUNK_SIZE) as i64;
+        // 分块自上而下编号，世界坐标自下而上，以地图顶部为基准换算
+        let top = self.world_size().y;
+        for layer in self
+            .layers
+            .iter_mut()
+            .filter(|l| l.visible && l.opacity > 0.0)
+        {
+            let first_column = ((view_min.x - layer.offset.x) / chunk_size.x).floor();
+            let last_column = ((view_max.x - layer.offset.x) / chunk_size.x).ceil();
+            let first_row = ((top - (view_max.y - layer.offset.y)) / chunk_size.y).floor();
+            let last_row = ((top - (view_min.y - layer.offset.y)) / chunk_size.y).ceil();
+            let color = Vec4::new(1.0, 1.0, 1.0, layer.opacity);
+            for cy in (first_row as i64).max(0)..(last_row as i64).min(rows) {
+                for cx in (first_column as i64).max(0)..(last_column as i64).min(columns) {
+                    let index = (cy * columns + cx) as usize;
+                    if layer.chunks[index].dirty {
+                        let sprites = Self::build_chunk(
+                            layer,
+                            &self.tilesets,
+                            self.tile_size,
+                            UVec2::new(cx as u32, cy as u32),
+                        );
+                        layer.chunks[index] = TileChunk {
+                            dirty: false,
+                            sprites,
+                        };
+                    }
+                    for (tileset, sprite) in &layer.chunks[index].sprites {
+                        let sprite = Sprite {
+                            min: sprite.min + layer.offset,
+                            max: sprite.max + layer.offset,
+                            color,
+                            ..*sprite
+                        };
+                        batch.draw(self.tilesets[*tileset].texture.id(), &sprite);
+                    }
+                }
+            }
+        }
+    }
+
+    fn build_chunk(
+        layer: &TileLayer,
+        tilesets: &[Tileset],
+        tile_size: UVec2,
+        chunk: UVec2,
+    ) -> Vec<(usize, Sprite)> {
+        let mut sprites = Vec::new();
+        let size = tile_size.as_vec2();
+        let start = chunk * TILE_CHUNK_SIZE;
+        let end = (start + TILE_CHUNK_SIZE).min(UVec2::new(layer.width, layer.height));
+        for y in start.y..end.y {
+            for x in start.x..end.x {
+                let raw = layer.get(x, y);
+                let gid = raw & !TILE_FLAGS;
+                if gid == 0 {
+                    continue;
+                }
+                let Some(index) = tilesets.iter().rposition(|t| t.first_gid <= gid) else {
+                    continue;
+                };
+                let tileset = &tilesets[index];
+                let (mut uv_min, mut uv_max) = tileset.uv(gid - tileset.first_gid);
+                if raw & TILE_FLIP_HORIZONTAL != 0 {
+                    std::mem::swap(&mut uv_min.x, &mut uv_max.x);
+                }
+                if raw & TILE_FLIP_VERTICAL != 0 {
+                    std::mem::swap(&mut uv_min.y, &mut uv_max.y);
+                }
+                // 大于地图网格的图块与 Tiled 一致，以格子左下角对齐向上延伸
+                let min = Vec2::new(x as f32, (layer.height - 1 - y) as f32) * size;
+                let mut sprite = Sprite::new(min, min + tileset.tile_size.as_vec2());
+                sprite.uv_min = uv_min;
+                sprite.uv_max = uv_max;
+                sprites.push((index, sprite));
+            }
+        }
+        sprites.sort_by_key(|(index, _)| *index);
+        sprites
+    }
+
+    fn mark_dirty(&mut self) {
+        for chunk in self.layers.iter_mut().flat_map(|l| l.chunks.iter_mut()) {
+            chunk.dirty = true;
+        }
+    }
+
+    /// 加载 Tiled 地图
+    ///
+    /// # 参数
+    /// + `path` - 地图文件路径，支持 XML(`.tmx`)与 JSON(`.tmj`、`.json`)格式
+    ///
+    /// # 返回值
+    /// 成功时返回地图，文件读取、解析或图块集纹理加载失败时返回错误
+    ///
+    /// # 注解
+    ///
+    /// 支持外部图块集(`.tsx`、`.tsj`)、CSV 与 Base64(含 zlib、gzip 压缩)编码的图块数据以及图层组，
+    /// 图层组的偏移、不透明度与可见性会合并到其中的图块层。不支持无限地图与由独立图像组成的图块集，
+    /// 对象层与图像层被忽略。图块集纹理以 [`TextureSettings::pixel_art`] 加载
+    pub fn load_tiled<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path)?;
+        let dir = path.parent().unwrap_or(Path::new(""));
+        let tmx = path
+            .extension()
+            .is_some_and(|e| e.eq_ignore_ascii_case("tmx"));
+        let source = if tmx {
+            parse_tmx(&text, dir)
+        } else {
+            parse_tmj(&text, dir)
+        }
+        .map_err(|e| Error::Parse(format!("{}: {}", path.display(), e)))?;
+
+        let mut map = Self::new(source.width, source.height, source.tile_size);
+        for tileset in source.tilesets {
+            let settings = TextureSettings::pixel_art();
+            let texture = Texture2D::load_with(&tileset.image, true, &settings)?;
+            let TilesetSource {
+                first_gid,
+                tile_size,
+                margin,
+                spacing,
+                ..
+            } = tileset;
+            map.tilesets.push(Tileset {
+                first_gid,
+                ..Tileset::new(texture, tile_size, margin, spacing)
+            });
+        }
+        map.tilesets.sort_by_key(|t| t.first_gid);
+        for layer in source.layers {
+            if layer.tiles.len() != (map.width * map.height) as usize {
+                let (path, name) = (path.display(), &layer.name);
+                return Err(Error::Parse(format!(
+                    "{}: 图层 {} 的图块数量不匹配",
+                    path, name
+                )));
+            }
+            let index = map.add_layer(&layer.name);
+            let target = &mut map.layers[index];
+            target.tiles = layer.tiles;
+            target.visible = layer.visible;
+            target.opacity = layer.opacity;
+            target.offset = layer.offset;
+        }
+        Ok(map)
+    }
+}
+
+struct TilesetSource {
+    first_gid: u32,
+    image: PathBuf,
+    tile_size: UVec2,
+    margin: u32,
+    spacing: u32,
+}
+
+struct LayerSource {
+    name: String,
+    tiles: Vec<u32>,
+    visible: bool,
+    opacity: f32,
+    offset: Vec2,
+}
+
+struct MapSource {
+    width: u32,
+    height: u32,
+    tile_size: UVec2,
+    tilesets: Vec<TilesetSource>,
+    layers: Vec<LayerSource>,
+}
+
+/// 图层组累积的偏移、不透明度与可见性
+#[derive(Clone, Copy)]
+struct GroupState {
+    offset: Vec2,
+    opacity: f32,
+    visible: bool,
+}
+
+impl GroupState {
+    const ROOT: Self = Self {
+        offset: Vec2::ZERO,
+        opacity: 1.0,
+        visible: true,
+    };
+
+    /// 合并子图层的属性，Tiled 的纵向偏移向下为正
+    fn child(self, offset: Vec2, opacity: f32, visible: bool) -> Self {
+        Self {
+            offset: self.offset + Vec2::new(offset.x, -offset.y),
+            opacity: self.opacity * opacity,
+            visible: self.visible && visible,
+        }
+    }
+}
+
+/// Tiled 文件的解析结果，错误信息由 [`Tilemap::load_tiled`] 附加文件路径
+type ParseResult<T> = std::result::Result<T, String>;
+
+fn decode_tile_data(data: &str, encoding: &str, compression: &str) -> ParseResult<Vec<u32>> {
+    match encoding {
+        "csv" => data
+            .split(',')
+            .map(|s| s.trim())
+            .filter(|s| !s.is_empty())
+            .map(|s| s.parse::<u32>().map_err(|e| e.to_string()))
+            .collect(),
+        "base64" => {
+            let bytes = STANDARD.decode(data.trim()).map_err(|e| e.to_string())?;
+            let mut raw = Vec::new();
+            let read = match compression {
+                "" => {
+                    raw = bytes;
+                    Ok(0)
+                }
+                "zlib" => ZlibDecoder::new(bytes.as_slice()).read_to_end(&mut raw),
+                "gzip" => GzDecoder::new(bytes.as_slice()).read_to_end(&mut raw),
+                other => return Err(format!("不支持的压缩方式 {}", other)),
+            };
+            read.map_err(|e| e.to_string())?;
+            Ok(raw
+                .chunks_exact(4)
+                .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+                .collect())
+        }
+        other => Err(format!("不支持的编码 {}", other)),
+    }
+}
+
+fn xml_attr<T: FromStr>(node: Node, name: &str) -> ParseResult<T> {
+    let value = node
+        .attribute(name)
+        .ok_or_else(|| format!("<{}> 缺少属性 {}", node.tag_name().name(), name))?;
+    value
+        .parse()
+        .map_err(|_| format!("<{}> 的属性 {} 无效", node.tag_name().name(), name))
+}
+
+fn xml_attr_or<T: FromStr>(node: Node, name: &str, default: T) -> T {
+    node.attribute(name)
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(default)
+}
+
+fn parse_tsx(node: Node, first_gid: u32, dir: &Path) -> ParseResult<TilesetSource> {
+    let image = node
+        .children()
+        .find(|n| n.has_tag_name("image"))
+        .ok_or("不支持由独立图像组成的图块集")?;
+    Ok(TilesetSource {
+        first_gid,
+        image: dir.join(xml_attr::<String>(image, "source")?),
+        tile_size: UVec2::new(xml_attr(node, "tilewidth")?, xml_attr(node, "tileheight")?),
+        margin: xml_attr_or(node, "margin", 0),
+        spacing: xml_attr_or(node, "spacing", 0),
+    })
+}
+
+fn parse_tmx_layers(
+    node: Node,
+    state: GroupState,
+    layers: &mut Vec<LayerSource>,
+) -> ParseResult<()> {
+    for child in node.children().filter(|n| n.is_element()) {
+        let offset = Vec2::new(
+            xml_attr_or(child, "offsetx", 0.0),
+            xml_attr_or(child, "offsety", 0.0),
+        );
+        let state = state.child(
+            offset,
+            xml_attr_or(child, "opacity", 1.0),
+            xml_attr_or(child, "visible", 1) != 0,
+        );
+        match child.tag_name().name() {
+            "group" => parse_tmx_layers(child, state, layers)?,
+            "layer" => {
+                let data = child
+                    .children()
+                    .find(|n| n.has_tag_name("data"))
+                    .ok_or("图层缺少数据")?;
+                let tiles = match data.attribute("encoding") {
+                    Some(encoding) => decode_tile_data(
+                        data.text().unwrap_or(""),
+                        encoding,
+                        data.attribute("compression").unwrap_or(""),
+                    )?,
+                    None => data
+                        .children()
+                        .filter(|n| n.has_tag_name("tile"))
+                        .map(|n| xml_attr_or(n, "gid", 0))
+                        .collect(),
+                };
+                layers.push(LayerSource {
+                    name: xml_attr_or(child, "name", String::new()),
+                    tiles,
+                    visible: state.visible,
+                    opacity: state.opacity,
+                    offset: state.offset,
+                });
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// 加载外部图块集文件，`source`相对于地图所在目录
+fn load_external_tileset(source: &str, first_gid: u32, dir: &Path) -> ParseResult<TilesetSource> {
+    let path = dir.join(source);
+    let text = fs::read_to_string(&path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    let dir = path.parent().unwrap_or(dir);
+    if source.ends_with(".tsx") {
+        let doc = roxmltree::Document::parse(&text).map_err(|e| e.to_string())?;
+        parse_tsx(doc.root_element(), first_gid, dir)
+    } else {
+        let value: Value = serde_json::from_str(&text).map_err(|e| e.to_string())?;
+        parse_tsj(&value, first_gid, dir)
+    }
+}
+
+fn parse_tmx(text: &str, dir: &Path) -> ParseResult<MapSource> {
+    let doc = roxmltree::Document::parse(text).map_err(|e| e.to_string())?;
+    let map = doc.root_element();
+    if xml_attr_or(map, "infinite", 0) != 0 {
+        return Err("不支持无限地图".to_string());
+    }
+    let mut tilesets = Vec::new();
+    for node in map.children().filter(|n| n.has_tag_name("tileset")) {
+        let first_gid = xml_attr(node, "firstgid")?;
+        let tileset = match node.attribute("source") {
+            Some(source) => load_external_tileset(source, first_gid, dir)?,
+            None => parse_tsx(node, first_gid, dir)?,
+        };
+        tilesets.push(tileset);
+    }
+    let mut layers = Vec::new();
+    parse_tmx_layers(map, GroupState::ROOT, &mut layers)?;
+    Ok(MapSource {
+        width: xml_attr(map, "width")?,
+        height: xml_attr(map, "height")?,
+        tile_size: UVec2::new(xml_attr(map, "tilewidth")?, xml_attr(map, "tileheight")?),
+        tilesets,
+        layers,
+    })
+}
+
+fn json_u32(value: &Value, key: &str) -> ParseResult<u32> {
+    value
+        .get(key)
+        .and_then(Value::as_u64)
+        .map(|v| v as u32)
+        .ok_or_else(|| format!("缺少字段 {}", key))
+}
+
+fn json_f32_or(value: &Value, key: &str, default: f32) -> f32 {
+    value
+        .get(key)
+        .and_then(Value::as_f64)
+        .map_or(default, |v| v as f32)
+}
+
+fn parse_tsj(value: &Value, first_gid: u32, dir: &Path) -> ParseResult<TilesetSource> {
+    let image = value
+        .get("image")
+        .and_then(Value::as_str)
+        .ok_or("不支持由独立图像组成的图块集")?;
+    Ok(TilesetSource {
+        first_gid,
+        image: dir.join(image),
+        tile_size: UVec2::new(
+            json_u32(value, "tilewidth")?,
+            json_u32(value, "tileheight")?,
+        ),
+        margin: json_u32(value, "margin").unwrap_or(0),
+        spacing: json_u32(value, "spacing").unwrap_or(0),
+    })
+}
+
+fn parse_tmj_layers(
+    values: &[Value],
+    state: GroupState,
+    layers: &mut Vec<LayerSource>,
+) -> ParseResult<()> {
+    for value in values {
+        let offset = Vec2::new(
+            json_f32_or(value, "offsetx", 0.0),
+            json_f32_or(value, "offsety", 0.0),
+        );
+        let state = state.child(
+            offset,
+            json_f32_or(value, "opacity", 1.0),
+            value
+                .get("visible")
+                .and_then(Value::as_bool)
+                .unwrap_or(true),
+        );
+        match value.get("type").and_then(Value::as_str) {
+            Some("group") => {
+                let children = value.get("layers").and_then(Value::as_array);
+                parse_tmj_layers(
+                    children.map(Vec::as_slice).unwrap_or_default(),
+                    state,
+                    layers,
+                )?;
+            }
+            Some("tilelayer") => {
+                let tiles = match value.get("data") {
+                    Some(Value::Array(data)) => data
+                        .iter()
+                        .map(|v| v.as_u64().map(|v| v as u32).ok_or("图块编号无效"))
+                        .collect::<std::result::Result<_, &str>>()?,
+                    Some(Value::String(data)) => decode_tile_data(
+                        data,
+                        value
+                            .get("encoding")
+                            .and_then(Value::as_str)
+                            .unwrap_or("base64"),
+                        value
+                            .get("compression")
+                            .and_then(Value::as_str)
+                            .unwrap_or(""),
+                    )?,
+                    _ => return Err("图层缺少数据".to_string()),
+                };
+                layers.push(LayerSource {
+                    name: value
+                        .get("name")
+                        .and_then(Value::as_str)
+                        .unwrap_or("")
+                        .to_string(),
+                    tiles,
+                    visible: state.visible,
+                    opacity: state.opacity,
+                    offset: state.offset,
+                });
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+fn parse_tmj(text: &str, dir: &Path) -> ParseResult<MapSource> {
+    let map: Value = serde_json::from_str(text).map_err(|e| e.to_string())?;
+    if map
+        .get("infinite")
+        .and_then(Value::as_bool)
+        .unwrap_or(false)
+    {
+        return Err("不支持无限地图".to_string());
+    }
+    let mut tilesets = Vec::new();
+    for value in map
+        .get("tilesets")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+    {
+        let first_gid = json_u32(value, "firstgid")?;
+        let tileset = match value.get("source").and_then(Value::as_str) {
+            Some(source) => load_external_tileset(source, first_gid, dir)?,
+            None => parse_tsj(value, first_gid, dir)?,
+        };
+        tilesets.push(tileset);
+    }
+    let mut layers = Vec::new();
+    let values = map.get("layers").and_then(Value::as_array);
+    let values = values.map(Vec::as_slice).unwrap_or_default();
+    parse_tmj_layers(values, GroupState::ROOT, &mut layers)?;
+    Ok(MapSource {
+        width: json_u32(&map, "width")?,
+        height: json_u32(&map, "height")?,
+        tile_size: UVec2::new(json_u32(&map, "tilewidth")?, json_u32(&map, "tileheight")?),
+        tilesets,
+        layers,
+    })
+}
@@ -0,0 +1,259 @@
+use std::f32::consts::TAU;
+
+use crate::math::*;
+use crate::{Curve, Entity, Light, Scene, SkyParams};
+
+/// 昼夜循环的参数日程
+///
+/// 每条曲线以归一化的一天(`0`为午夜，`0.5`为正午)为横轴，决定各时刻的光照与天空参数
+#[derive(Debug, Clone, PartialEq)]
+pub struct DaySchedule {
+    /// 太阳光强度，实际颜色还会乘以大气衰减后的阳光颜色
+    pub sun_intensity: Curve<f32>,
+    /// 月光强度
+    pub moon_intensity: Curve<f32>,
+    /// 环境光颜色(线性空间)
+    pub ambient: Curve<Vec3>,
+    /// 大气浑浊度，见 [`SkyParams::turbidity`]
+    pub turbidity: Curve<f32>,
+    /// 天空亮度缩放，见 [`SkyParams::intensity`]
+    pub sky_intensity: Curve<f32>,
+}
+
+impl Default for DaySchedule {
+    fn default() -> Self {
+        let night = Vec3::new(0.01, 0.012, 0.03);
+        let twilight = Vec3::new(0.08, 0.05, 0.04);
+        let day = Vec3::new(0.08, 0.09, 0.1);
+        Self {
+            sun_intensity: Curve::constant(3.0),
+            moon_intensity: Curve::from_keys(vec![
+                (0.0, 0.15),
+                (0.24, 0.15),
+                (0.3, 0.0),
+                (0.7, 0.0),
+                (0.76, 0.15),
+                (1.0, 0.15),
+            ]),
+            ambient: Curve::from_keys(vec![
+                (0.0, night),
+                (0.22, night),
+                (0.27, twilight),
+                (0.35, day),
+                (0.65, day),
+                (0.73, twilight),
+                (0.78, night),
+                (1.0, night),
+            ]),
+            turbidity: Curve::from_keys(vec![(0.25, 4.0), (0.5, 2.5), (0.75, 4.0)]),
+            sky_intensity: Curve::constant(0.05),
+        }
+    }
+}
+
+/// 昼夜循环控制器
+///
+/// 按游戏内时间(小时)推进太阳与月亮的位置，并根据 [`DaySchedule`] 计算天空参数、日月光源与环境光。
+/// 太阳在 6 时从东方(`+X`)升起，正午到达最高点，18 时从西方(`-X`)落下；月亮始终位于太阳的对侧
+///
+/// # 示例
+///
+/// ```
+/// use gle::{*, math::*};
+///
+/// let mut scene = Scene::new();
+/// let sun = scene.spawn("sun", Transform::default());
+/// scene.insert(sun, Light::directional(Vec3::ONE, 3.0));
+/// let moon = scene.spawn("moon", Transform::default());
+/// scene.insert(moon, Light::directional(Vec3::ONE, 0.1));
+///
+/// // 现实中 20 分钟为游戏中的一天，从早上 8 点开始
+/// let mut time = TimeOfDay::new(8.0, 1200.0);
+/// let mut sky = SkyParams::default();
+/// let mut lighting = LightingSystem::new();
+///
+/// // 每帧
+/// time.update(1.0 / 60.0);
+/// time.apply(&mut scene, sun, Some(moon));
+/// sky = time.sky_params(&sky);
+/// lighting.ambient = time.ambient();
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct TimeOfDay {
+    hour: f32,
+    day: u32,
+    paused: bool,
+    /// 现实中经过多少秒为游戏中的一天，不大于 0 时时间不会流逝
+    pub day_length: f32,
+    /// 纬度，单位为弧度，决定正午时太阳偏离天顶的角度(向`+Z`方向倾斜)
+    pub latitude: f32,
+    /// 太阳轨迹绕`Y`轴的旋转角，单位为弧度
+    pub azimuth: f32,
+    /// 月光颜色(线性空间)
+    pub moon_color: Vec3,
+    /// 参数日程
+    pub schedule: DaySchedule,
+}
+
+impl TimeOfDay {
+    /// 创建昼夜循环控制器
+    ///
+    /// # 参数
+    /// + `hour` - 初始时间，单位为小时，范围为`[0, 24)`
+    /// + `day_length` - 现实中经过多少秒为游戏中的一天
+    pub fn new(hour: f32, day_length: f32) -> Self {
+        Self {
+            hour: hour.rem_euclid(24.0),
+            day: 0,
+            paused: false,
+            day_length,
+            latitude: 0.5,
+            azimuth: 0.0,
+            moon_color: Vec3::new(0.6, 0.7, 1.0),
+            schedule: DaySchedule::default(),
+        }
+    }
+
+    /// 使用指定的参数日程
+    pub fn with_schedule(mut self, schedule: DaySchedule) -> Self {
+        self.schedule = schedule;
+        self
+    }
+
+    /// 获取当前时间，单位为小时
+    pub fn hour(&self) -> f32 {
+        self.hour
+    }
+
+    /// 获取当前时间在一天中的比例，范围为`[0, 1)`
+    pub fn day_fraction(&self) -> f32 {
+        self.hour / 24.0
+    }
+
+    /// 获取已经过的完整天数
+    pub fn day(&self) -> u32 {
+        self.day
+    }
+
+    /// 跳转到指定时间，不影响已经过的天数
+    ///
+    /// # 参数
+    /// + `hour` - 时间，单位为小时，超出`[0, 24)`的部分会被折回
+    pub fn set_hour(&mut self, hour: f32) {
+        self.hour = hour.rem_euclid(24.0);
+    }
+
+    /// 暂停时间流逝，暂停期间仍可通过 [`TimeOfDay::set_hour`] 拖动时间
+    pub fn pause(&mut self) {
+        self.paused = true;
+    }
+
+    /// 恢复时间流逝
+    pub fn resume(&mut self) {
+        self.paused = false;
+    }
+
+    /// 是否已暂停
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// 推进时间
+    ///
+    /// # 参数
+    /// + `dt` - 现实中经过的时间，单位为秒
+    pub fn update(&mut self, dt: f32) {
+        if self.paused || self.day_length <= 0.0 {
+            return;
+        }
+        let hour = self.hour + dt * 24.0 / self.day_length;
+        self.day += (hour / 24.0).floor().max(0.0) as u32;
+        self.hour = hour.rem_euclid(24.0);
+    }
+
+    /// 获取指向太阳的单位方向
+    pub fn sun_direction(&self) -> Vec3 {
+        let angle = self.day_fraction() * TAU;
+        let path = Vec3::new(angle.sin(), -angle.cos(), 0.0);
+        Quat::from_rotation_y(self.azimuth) * Quat::from_rotation_x(self.latitude) * path
+    }
+
+    /// 获取指向月亮的单位方向
+    pub fn moon_direction(&self) -> Vec3 {
+        -self.sun_direction()
+    }
+
+    /// 太阳是否位于地平线以上
+    pub fn is_daytime(&self) -> bool {
+        self.sun_direction().y > 0.0
+    }
+
+    /// 获取当前时刻的天空参数
+    ///
+    /// # 参数
+    /// + `base` - 基础天空参数，太阳方向、浑浊度与天空亮度将被覆盖，其余字段保持不变
+    pub fn sky_params(&self, base: &SkyParams) -> SkyParams {
+        let t = self.day_fraction();
+        SkyParams {
+            sun_direction: self.sun_direction(),
+            turbidity: self.schedule.turbidity.sample(t),
+            intensity: self.schedule.sky_intensity.sample(t),
+            ..*base
+        }
+    }
+
+    /// 获取当前时刻的环境光，可赋值给 [`LightingSystem::ambient`](crate::LightingSystem::ambient)
+    pub fn ambient(&self) -> Vec3 {
+        self.schedule.ambient.sample(self.day_fraction())
+    }
+
+    /// 获取当前时刻的太阳光颜色与强度
+    pub fn sun_light(&self) -> (Vec3, f32) {
+        let t = self.day_fraction();
+        let sky = SkyParams {
+            sun_direction: self.sun_direction(),
+            turbidity: self.schedule.turbidity.sample(t),
+            ..Default::default()
+        };
+        (sky.sun_color(), self.schedule.sun_intensity.sample(t))
+    }
+
+    /// 获取当前时刻的月光颜色与强度，月亮位于地平线以下时强度为 0
+    pub fn moon_light(&self) -> (Vec3, f32) {
+        let elevation = self.moon_direction().y;
+        let fade = ((elevation + 0.05) / 0.1).clamp(0.0, 1.0);
+        let intensity = self.schedule.moon_intensity.sample(self.day_fraction()) * fade;
+        (self.moon_color, intensity)
+    }
+
+    /// 将日月的方向与光照写入场景中的平行光
+    ///
+    /// # 参数
+    /// + `scene` - 场景
+    /// + `sun` - 作为太阳的实体，需带有 [`Light`] 组件
+    /// + `moon` - 作为月亮的实体，需带有 [`Light`] 组件
+    ///
+    /// # 注解
+    ///
+    /// 只修改实体的旋转及光源的颜色、强度与启用状态，阴影等其余设置保持不变。
+    /// 位于地平线以下的光源将被禁用，以免占用光源与阴影配额
+    pub fn apply(&self, scene: &mut Scene, sun: Entity, moon: Option<Entity>) {
+        let (color, intensity) = self.sun_light();
+        Self::apply_light(scene, sun, self.sun_direction(), color, intensity);
+        if let Some(moon) = moon {
+            let (color, intensity) = self.moon_light();
+            Self::apply_light(scene, moon, self.moon_direction(), color, intensity);
+        }
+    }
+
+    fn apply_light(scene: &mut Scene, entity: Entity, direction: Vec3, color: Vec3, intensity: f32) {
+        if let Some(node) = scene.get_mut(entity) {
+            node.transform.rotation = Quat::from_rotation_arc(Vec3::NEG_Z, -direction.normalize());
+        }
+        if let Some(light) = scene.component_mut::<Light>(entity) {
+            light.color = color;
+            light.intensity = intensity;
+            light.enabled = intensity > 0.0 && color.max_element() > 0.0;
+        }
+    }
+}
@@ -0,0 +1,391 @@
+use crate::error::Result;
+use crate::{
+    draw_fullscreen_triangle, AttachmentFormat, Framebuffer, GlObjectKind, GlObjects, PostContext,
+    PostEffect, Program, RenderStats, FULLSCREEN_VS,
+};
+
+const LUMINANCE_FS: &str = r#"
+#version 330 core
+in vec2 vUV;
+out float FragColor;
+
+uniform sampler2D uInput;
+uniform float uMinLog;
+uniform float uInvLogRange;
+
+void main()
+{
+    vec3 color = texture(uInput, vUV).rgb;
+    float luminance = dot(color, vec3(0.2126, 0.7152, 0.0722));
+    // 过暗的像素不参与统计
+    FragColor = luminance < 1e-5 ? -1.0 : clamp((log2(luminance) - uMinLog) * uInvLogRange, 0.0, 1.0);
+}
+"#;
+
+const HISTOGRAM_VS: &str = r#"
+#version 330 core
+uniform sampler2D uLuminance;
+uniform int uBins;
+
+void main()
+{
+    ivec2 size = textureSize(uLuminance, 0);
+    float value = texelFetch(uLuminance, ivec2(gl_VertexID % size.x, gl_VertexID / size.x), 0).r;
+    float bin = value < 0.0 ? 0.0 : 1.0 + floor(value * float(uBins - 2) + 0.5);
+    gl_Position = vec4((bin + 0.5) / float(uBins) * 2.0 - 1.0, 0.0, 0.0, 1.0);
+    gl_PointSize = 1.0;
+}
+"#;
+
+const HISTOGRAM_FS: &str = r#"
+#version 330 core
+out float FragColor;
+
+void main()
+{
+    FragColor = 1.0;
+}
+"#;
+
+const ADAPT_FS: &str = r#"
+#version 330 core
+out float FragColor;
+
+uniform sampler2D uHistogram;
+uniform sampler2D uPrevious;
+uniform int uBins;
+uniform float uMinLog;
+uniform float uLogRange;
+uniform float uLowPercent;
+uniform float uHighPercent;
+uniform float uAdapt;
+uniform bool uReset;
+
+void main()
+{
+    // 第 0 个分组为过暗像素，不参与平均
+    float total = 0.0;
+    for (int i = 1; i < uBins; ++i) {
+        total += texelFetch(uHistogram, ivec2(i, 0), 0).r;
+    }
+    float low = total * uLowPercent;
+    float high = total * uHighPercent;
+    float accumulated = 0.0;
+    float weighted = 0.0;
+    float count = 0.0;
+    for (int i = 1; i < uBins; ++i) {
+        float n = texelFetch(uHistogram, ivec2(i, 0), 0).r;
+        float lo = max(accumulated, low);
+        float hi = min(accumulated + n, high);
+        float used = max(hi - lo, 0.0);
+        float t = float(i - 1) / float(uBins - 2);
+        weighted += used * (uMinLog + t * uLogRange);
+        count += used;
+        accumulated += n;
+    }
+    float target = count > 0.0 ? exp2(weighted / count) : exp2(uMinLog);
+    float previous = texelFetch(uPrevious, ivec2(0, 0), 0).r;
+    FragColor = uReset ? target : previous + (target - previous) * uAdapt;
+}
+"#;
+
+const TONEMAP_FS: &str = r#"
+#version 330 core
+in vec2 vUV;
+out vec4 FragColor;
+
+uniform sampler2D uInput;
+uniform sampler2D uAverage;
+uniform bool uAuto;
+uniform float uExposure;
+uniform int uOperator;
+uniform float uWhite;
+uniform float uInvGamma;
+
+vec3 aces(vec3 x)
+{
+    const mat3 inputMatrix = mat3(
+        0.59719, 0.07600, 0.02840,
+        0.35458, 0.90834, 0.13383,
+        0.04823, 0.01566, 0.83777);
+    const mat3 outputMatrix = mat3(
+        1.60475, -0.10208, -0.00327,
+        -0.53108, 1.10813, -0.07276,
+        -0.07367, -0.00605, 1.07602);
+    vec3 v = inputMatrix * x;
+    vec3 a = v * (v + 0.0245786) - 0.000090537;
+    vec3 b = v * (0.983729 * v + 0.4329510) + 0.238081;
+    return outputMatrix * (a / b);
+}
+
+vec3 reinhard(vec3 x)
+{
+    float l = dot(x, vec3(0.2126, 0.7152, 0.0722));
+    float mapped = l * (1.0 + l / (uWhite * uWhite)) / (1.0 + l);
+    return x * (mapped / max(l, 1e-5));
+}
+
+void main()
+{
+    vec4 color = texture(uInput, vUV);
+    float exposure = uExposure;
+    if (uAuto) {
+        exposure *= 0.18 / max(texelFetch(uAverage, ivec2(0, 0), 0).r, 1e-5);
+    }
+    vec3 x = color.rgb * exposure;
+    vec3 mapped;
+    if (uOperator == 0) {
+        mapped = aces(x);
+    } else if (uOperator == 1) {
+        mapped = reinhard(x);
+    } else {
+        mapped = x;
+    }
+    mapped = clamp(mapped, 0.0, 1.0);
+    FragColor = vec4(pow(mapped, vec3(uInvGamma)), color.a);
+}
+"#;
+
+const LUMINANCE_SIZE: u32 = 64;
+const HISTOGRAM_BINS: u32 = 64;
+
+/// 色调映射算子
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ToneMapOperator {
+    /// ACES 拟合曲线(Stephen Hill)
+    #[default]
+    Aces,
+    /// 基于亮度的扩展 Reinhard 曲线，白点由 [`ToneMapping::white_point`] 指定
+    Reinhard,
+    /// 不做映射，仅截断到`[0, 1]`
+    Linear,
+}
+
+/// 曝光控制方式
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Exposure {
+    /// 固定曝光，值为颜色的线性乘数
+    Manual(f32),
+    /// 根据亮度直方图自动曝光
+    Auto(AutoExposure),
+}
+
+impl Default for Exposure {
+    fn default() -> Self {
+        Exposure::Manual(1.0)
+    }
+}
+
+/// 自动曝光参数
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AutoExposure {
+    /// 直方图统计的最小亮度(log2)
+    pub min_log_luminance: f32,
+    /// 直方图统计的最大亮度(log2)
+    pub max_log_luminance: f32,
+    /// 忽略的最暗像素比例
+    pub low_percent: f32,
+    /// 统计到的最亮像素比例，超过该比例的像素被忽略
+    pub high_percent: f32,
+    /// 曝光适应速度，越大适应越快
+    pub speed: f32,
+    /// 曝光补偿(EV)，正值使画面更亮
+    pub compensation: f32,
+}
+
+impl Default for AutoExposure {
+    fn default() -> Self {
+        Self {
+            min_log_luminance: -8.0,
+            max_log_luminance: 8.0,
+            low_percent: 0.5,
+            high_percent: 0.95,
+            speed: 1.5,
+            compensation: 0.0,
+        }
+    }
+}
+
+/// 色调映射
+///
+/// 对 HDR 颜色施加曝光与色调映射算子并进行伽马校正，输出可直接显示的 LDR 颜色，应作为后处理链的最后一个 HDR 效果。
+/// 自动曝光在 GPU 上统计亮度直方图并平滑适应，不需要回读数据
+///
+/// # 示例
+///
+/// ```
+/// use gle::*;
+///
+/// fn render_init() {
+///     let mut tonemap = ToneMapping::new().unwrap();
+///     tonemap.operator = ToneMapOperator::Reinhard;
+///     tonemap.exposure = Exposure::Auto(AutoExposure::default());
+/// }
+/// ```
+///
+/// # 注解
+///
+/// 该类型只能在渲染线程中创建、使用与释放
+pub struct ToneMapping {
+    /// 色调映射算子
+    pub operator: ToneMapOperator,
+    /// 曝光控制方式
+    pub exposure: Exposure,
+    /// Reinhard 算子的白点，亮度达到该值时映射为纯白
+    pub white_point: f32,
+    /// 显示伽马值
+    pub gamma: f32,
+    luminance_program: Program,
+    histogram_program: Program,
+    adapt_program: Program,
+    tonemap_program: Program,
+    luminance: Framebuffer,
+    histogram: Framebuffer,
+    average: [Framebuffer; 2],
+    current: usize,
+    reset: bool,
+    vao: u32,
+}
+
+impl ToneMapping {
+    /// 创建使用 ACES 算子与固定曝光的色调映射效果
+    pub fn new() -> Result<Self> {
+        let mut vao = 0;
+        unsafe { gl::GenVertexArrays(1, &mut vao) };
+        GlObjects::track(GlObjectKind::VertexArray, vao);
+        Ok(Self {
+            operator: ToneMapOperator::default(),
+            exposure: Exposure::default(),
+            white_point: 4.0,
+            gamma: 2.2,
+            luminance_program: Program::new(FULLSCREEN_VS, LUMINANCE_FS)?,
+            histogram_program: Program::new(HISTOGRAM_VS, HISTOGRAM_FS)?,
+            adapt_program: Program::new(FULLSCREEN_VS, ADAPT_FS)?,
+            tonemap_program: Program::new(FULLSCREEN_VS, TONEMAP_FS)?,
+            luminance: Framebuffer::new(
+                LUMINANCE_SIZE,
+                LUMINANCE_SIZE,
+                &[AttachmentFormat::R16F],
+                None,
+            )?,
+            histogram: Framebuffer::new(HISTOGRAM_BINS, 1, &[AttachmentFormat::R32F], None)?,
+            average: [
+                Framebuffer::new(1, 1, &[AttachmentFormat::R32F], None)?,
+                Framebuffer::new(1, 1, &[AttachmentFormat::R32F], None)?,
+            ],
+            current: 0,
+            reset: true,
+            vao,
+        })
+    }
+
+    /// 获取当前的平均亮度纹理(1×1，R 通道)，仅在自动曝光时更新
+    pub fn average_luminance_texture(&self) -> u32 {
+        self.average[self.current].colors()[0].id()
+    }
+
+    /// 立即采用当前画面的亮度而不经过平滑适应，适用于切换场景或镜头时
+    pub fn reset_adaptation(&mut self) {
+        self.reset = true;
+    }
+
+    fn update_auto_exposure(&mut self, settings: &AutoExposure, input: u32, dt: f32) {
+        let min_log = settings.min_log_luminance;
+        let range = (settings.max_log_luminance - min_log).max(1e-3);
+
+        self.luminance.bind();
+        self.luminance_program.bind();
+        unsafe {
+            gl::ActiveTexture(gl::TEXTURE0);
+            gl::BindTexture(gl::TEXTURE_2D, input);
+        }
+        self.luminance_program.set("uInput", &0);
+        self.luminance_program.set("uMinLog", &min_log);
+        self.luminance_program.set("uInvLogRange", &(1.0 / range));
+        draw_fullscreen_triangle();
+
+        self.histogram.bind();
+        self.histogram_program.bind();
+        self.luminance.colors()[0].bind(0);
+        self.histogram_program.set("uLuminance", &0);
+        self.histogram_program.set("uBins", &(HISTOGRAM_BINS as i32));
+        unsafe {
+            gl::ClearColor(0.0, 0.0, 0.0, 0.0);
+            gl::Clear(gl::COLOR_BUFFER_BIT);
+            gl::Enable(gl::BLEND);
+            gl::BlendFunc(gl::ONE, gl::ONE);
+            gl::BindVertexArray(self.vao);
+            gl::DrawArrays(gl::POINTS, 0, (LUMINANCE_SIZE * LUMINANCE_SIZE) as i32);
+            RenderStats::record_draw(0);
+            gl::BindVertexArray(0);
+            gl::Disable(gl::BLEND);
+        }
+
+        let previous = self.current;
+        self.current = 1 - self.current;
+        self.average[self.current].bind();
+        self.adapt_program.bind();
+        self.histogram.colors()[0].bind(0);
+        self.average[previous].colors()[0].bind(1);
+        self.adapt_program.set("uHistogram", &0);
+        self.adapt_program.set("uPrevious", &1);
+        self.adapt_program.set("uBins", &(HISTOGRAM_BINS as i32));
+        self.adapt_program.set("uMinLog", &min_log);
+        self.adapt_program.set("uLogRange", &range);
+        self.adapt_program
+            .set("uLowPercent", &settings.low_percent.clamp(0.0, 1.0));
+        self.adapt_program.set(
+            "uHighPercent",
+            &settings.high_percent.clamp(settings.low_percent, 1.0),
+        );
+        self.adapt_program
+            .set("uAdapt", &(1.0 - (-dt.max(0.0) * settings.speed).exp()));
+        self.adapt_program.set("uReset", &self.reset);
+        draw_fullscreen_triangle();
+        self.reset = false;
+    }
+}
+
+impl PostEffect for ToneMapping {
+    fn name(&self) -> &str {
+        "tonemap"
+    }
+
+    fn apply(&mut self, ctx: &PostContext, input: u32, output: Option<&Framebuffer>) {
+        let (auto, exposure) = match self.exposure {
+            Exposure::Manual(exposure) => (false, exposure),
+            Exposure::Auto(settings) => {
+                self.update_auto_exposure(&settings, input, ctx.dt);
+                (true, 2f32.powf(settings.compensation))
+            }
+        };
+        ctx.bind_output(output);
+        self.tonemap_program.bind();
+        unsafe {
+            gl::ActiveTexture(gl::TEXTURE0);
+            gl::BindTexture(gl::TEXTURE_2D, input);
+        }
+        self.average[self.current].colors()[0].bind(1);
+        self.tonemap_program.set("uInput", &0);
+        self.tonemap_program.set("uAverage", &1);
+        self.tonemap_program.set("uAuto", &auto);
+        self.tonemap_program.set("uExposure", &exposure);
+        let operator = match self.operator {
+            ToneMapOperator::Aces => 0,
+            ToneMapOperator::Reinhard => 1,
+            ToneMapOperator::Linear => 2,
+        };
+        self.tonemap_program.set("uOperator", &operator);
+        self.tonemap_program.set("uWhite", &self.white_point.max(1e-3));
+        self.tonemap_program
+            .set("uInvGamma", &(1.0 / self.gamma.max(1e-3)));
+        draw_fullscreen_triangle();
+    }
+}
+
+impl Drop for ToneMapping {
+    fn drop(&mut self) {
+        GlObjects::untrack(GlObjectKind::VertexArray, self.vao);
+        unsafe { gl::DeleteVertexArrays(1, &self.vao) };
+    }
+}
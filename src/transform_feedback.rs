@@ -0,0 +1,246 @@
+use crate::{GlObjectKind, GlObjects, RenderStats};
+
+/// 变换反馈的捕获模式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum FeedbackMode {
+    /// 所有变量交错写入第一个缓冲
+    #[default]
+    Interleaved,
+    /// 每个变量写入各自的缓冲
+    Separate,
+}
+
+/// 变换反馈捕获的图元类型，绘制调用的图元必须与之兼容
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum FeedbackPrimitive {
+    /// 点，对应`GL_POINTS`
+    #[default]
+    Points,
+    /// 线段，对应`GL_LINES`、`GL_LINE_STRIP`等
+    Lines,
+    /// 三角形，对应`GL_TRIANGLES`、`GL_TRIANGLE_STRIP`等
+    Triangles,
+}
+
+impl FeedbackPrimitive {
+    fn to_gl(self) -> u32 {
+        match self {
+            FeedbackPrimitive::Points => gl::POINTS,
+            FeedbackPrimitive::Lines => gl::LINES,
+            FeedbackPrimitive::Triangles => gl::TRIANGLES,
+        }
+    }
+}
+
+/// 变换反馈
+///
+/// 将顶点着色器的输出写入GPU缓冲，无需计算着色器即可在 GL 3.3 上实现GPU粒子推进、网格预处理
+/// (如蒙皮结果缓存)等。着色器程序需通过 [`Program::with_feedback`](crate::Program::with_feedback) 创建，
+/// 捕获得到的缓冲可以作为下一次绘制或捕获的顶点输入
+///
+/// # 示例
+///
+/// ```no_run
+/// use gle::*;
+///
+/// // 以两组缓冲交替推进粒子：读取 a 的粒子状态，写入 b
+/// fn advect(program: &Program, a: &TransformFeedback, b: &mut TransformFeedback, vao: u32) {
+///     program.bind();
+///     b.capture(FeedbackPrimitive::Points, true, || unsafe {
+///         gl::BindVertexArray(vao);
+///         gl::BindBuffer(gl::ARRAY_BUFFER, a.buffer(0));
+///         // ... 设置顶点属性
+///         gl::DrawArrays(gl::POINTS, 0, 1024);
+///     });
+/// }
+/// ```
+///
+/// # 注解
+///
+/// 使用默认的变换反馈绑定点，不依赖 GL 4.0 的变换反馈对象。该类型只能在渲染线程中创建、使用与释放
+pub struct TransformFeedback {
+    buffers: Vec<u32>,
+    sizes: Vec<usize>,
+    query: u32,
+    pending: bool,
+    primitives: Option<u64>,
+}
+
+impl TransformFeedback {
+    /// 创建变换反馈
+    ///
+    /// # 参数
+    /// + `sizes` - 各捕获缓冲的字节大小，交错模式只需一个缓冲，分离模式每个变量一个缓冲
+    pub fn new(sizes: &[usize]) -> Self {
+        let mut buffers = vec![0; sizes.len()];
+        let mut query = 0;
+        unsafe {
+            gl::GenBuffers(buffers.len() as i32, buffers.as_mut_ptr());
+            for (&buffer, &size) in buffers.iter().zip(sizes) {
+                gl::BindBuffer(gl::TRANSFORM_FEEDBACK_BUFFER, buffer);
+                gl::BufferData(
+                    gl::TRANSFORM_FEEDBACK_BUFFER,
+                    size as isize,
+                    std::ptr::null(),
+                    gl::DYNAMIC_COPY,
+                );
+            }
+            gl::BindBuffer(gl::TRANSFORM_FEEDBACK_BUFFER, 0);
+            gl::GenQueries(1, &mut query);
+        }
+        for &buffer in &buffers {
+            GlObjects::track(GlObjectKind::Buffer, buffer);
+        }
+        GlObjects::track(GlObjectKind::Query, query);
+        Self {
+            buffers,
+            sizes: sizes.to_vec(),
+            query,
+            pending: false,
+            primitives: None,
+        }
+    }
+
+    /// 获取第`index`个捕获缓冲的缓冲对象ID
+    pub fn buffer(&self, index: usize) -> u32 {
+        self.buffers[index]
+    }
+
+    /// 获取第`index`个捕获缓冲的字节大小
+    pub fn size(&self, index: usize) -> usize {
+        self.sizes[index]
+    }
+
+    /// 获取捕获缓冲的数量
+    pub fn len(&self) -> usize {
+        self.buffers.len()
+    }
+
+    /// 判断是否没有捕获缓冲
+    pub fn is_empty(&self) -> bool {
+        self.buffers.is_empty()
+    }
+
+    /// 捕获绘制中顶点着色器的输出
+    ///
+    /// # 参数
+    /// + `primitive` - 捕获的图元类型
+    /// + `discard` - 是否丢弃光栅化，只捕获不绘制时应为`true`
+    /// + `f` - 绘制函数，调用前应已绑定以 [`Program::with_feedback`](crate::Program::with_feedback)
+    ///   创建的着色器程序
+    ///
+    /// # 返回值
+    /// 返回`f`的返回值
+    ///
+    /// # 注解
+    ///
+    /// 写入的图元数量通过 [`TransformFeedback::primitives_written`] 异步读取，超出缓冲容量的图元被丢弃。
+    /// 捕获期间不能将捕获缓冲作为顶点输入
+    pub fn capture<R>(
+        &mut self,
+        primitive: FeedbackPrimitive,
+        discard: bool,
+        f: impl FnOnce() -> R,
+    ) -> R {
+        unsafe {
+            for (i, &buffer) in self.buffers.iter().enumerate() {
+                gl::BindBufferBase(gl::TRANSFORM_FEEDBACK_BUFFER, i as u32, buffer);
+            }
+            if discard {
+                gl::Enable(gl::RASTERIZER_DISCARD);
+            }
+            gl::BeginQuery(gl::TRANSFORM_FEEDBACK_PRIMITIVES_WRITTEN, self.query);
+            gl::BeginTransformFeedback(primitive.to_gl());
+        }
+        let result = f();
+        unsafe {
+            gl::EndTransformFeedback();
+            gl::EndQuery(gl::TRANSFORM_FEEDBACK_PRIMITIVES_WRITTEN);
+            if discard {
+                gl::Disable(gl::RASTERIZER_DISCARD);
+            }
+            for i in 0..self.buffers.len() {
+                gl::BindBufferBase(gl::TRANSFORM_FEEDBACK_BUFFER, i as u32, 0);
+            }
+        }
+        self.pending = true;
+        result
+    }
+
+    /// 读取最近一次捕获写入的图元数量，不阻塞
+    ///
+    /// # 返回值
+    /// 返回最近一次已返回的结果，尚无结果时返回`None`
+    pub fn primitives_written(&mut self) -> Option<u64> {
+        if self.pending {
+            let mut available = 0;
+            unsafe {
+                gl::GetQueryObjectuiv(self.query, gl::QUERY_RESULT_AVAILABLE, &mut available);
+            }
+            if available != 0 {
+                let mut primitives = 0u64;
+                unsafe { gl::GetQueryObjectui64v(self.query, gl::QUERY_RESULT, &mut primitives) };
+                self.pending = false;
+                self.primitives = Some(primitives);
+            }
+        }
+        self.primitives
+    }
+
+    /// 将捕获缓冲的内容读回CPU，会阻塞渲染线程直到捕获完成，适用于离线的网格预处理
+    ///
+    /// # 参数
+    /// + `index` - 捕获缓冲索引
+    /// + `count` - 读取的元素数量，超出缓冲大小的部分被截去
+    pub fn read<T: Copy + Default>(&self, index: usize, count: usize) -> Vec<T> {
+        let stride = std::mem::size_of::<T>().max(1);
+        let count = count.min(self.sizes[index] / stride);
+        let mut data = vec![T::default(); count];
+        unsafe {
+            gl::BindBuffer(gl::TRANSFORM_FEEDBACK_BUFFER, self.buffers[index]);
+            gl::GetBufferSubData(
+                gl::TRANSFORM_FEEDBACK_BUFFER,
+                0,
+                (count * stride) as isize,
+                data.as_mut_ptr() as *mut _,
+            );
+            gl::BindBuffer(gl::TRANSFORM_FEEDBACK_BUFFER, 0);
+        }
+        data
+    }
+
+    /// 以捕获的顶点绘制，顶点数量由最近一次已返回的图元数量决定
+    ///
+    /// # 参数
+    /// + `mode` - 绘制的图元类型，如`gl::POINTS`
+    /// + `vertices_per_primitive` - 每个捕获图元的顶点数，点为1、线段为2、三角形为3
+    ///
+    /// # 返回值
+    /// 返回绘制的顶点数量，尚无结果时不绘制并返回0
+    ///
+    /// # 注解
+    ///
+    /// 调用前应已绑定以捕获缓冲为顶点输入的顶点数组对象
+    pub fn draw(&mut self, mode: u32, vertices_per_primitive: usize) -> usize {
+        let vertices = self.primitives_written().unwrap_or(0) as usize * vertices_per_primitive;
+        if vertices > 0 {
+            unsafe { gl::DrawArrays(mode, 0, vertices as i32) };
+            let triangles = if mode == gl::TRIANGLES { vertices as u64 / 3 } else { 0 };
+            RenderStats::record_draw(triangles);
+        }
+        vertices
+    }
+}
+
+impl Drop for TransformFeedback {
+    fn drop(&mut self) {
+        for &buffer in &self.buffers {
+            GlObjects::untrack(GlObjectKind::Buffer, buffer);
+        }
+        GlObjects::untrack(GlObjectKind::Query, self.query);
+        unsafe {
+            gl::DeleteBuffers(self.buffers.len() as i32, self.buffers.as_ptr());
+            gl::DeleteQueries(1, &self.query);
+        }
+    }
+}
@@ -0,0 +1,1243 @@
+use crate::error::Result;
+use crate::math::*;
+use crate::ui_layout::{self, LayoutItem};
+use crate::{
+    Anchor, App, Container, Input, Key, MouseButton, NineSlice, Renderer, ScissorRect, SdfFont,
+    Sprite, SpriteBatch, TextRenderer, TextStyle, Texture2D,
+};
+
+/// 界面中的矩形，以左上角为原点、向下为正
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Rect {
+    /// 左上角坐标
+    pub position: Vec2,
+    /// 宽度与高度
+    pub size: Vec2,
+}
+
+impl Rect {
+    /// 创建矩形
+    pub fn new(x: f32, y: f32, width: f32, height: f32) -> Self {
+        Self {
+            position: Vec2::new(x, y),
+            size: Vec2::new(width, height),
+        }
+    }
+
+    /// 获取右下角坐标
+    pub fn max(&self) -> Vec2 {
+        self.position + self.size
+    }
+
+    /// 判断点是否位于矩形内
+    pub fn contains(&self, point: Vec2) -> bool {
+        point.cmpge(self.position).all() && point.cmplt(self.max()).all()
+    }
+
+    /// 获取与另一个矩形的交集，不相交时尺寸为零
+    pub fn intersect(&self, other: &Rect) -> Rect {
+        let min = self.position.max(other.position);
+        let max = self.max().min(other.max());
+        Rect {
+            position: min,
+            size: (max - min).max(Vec2::ZERO),
+        }
+    }
+
+    /// 向内收缩
+    pub fn shrink(&self, amount: f32) -> Rect {
+        Rect {
+            position: self.position + amount,
+            size: (self.size - 2.0 * amount).max(Vec2::ZERO),
+        }
+    }
+}
+
+/// 控件句柄
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct WidgetId(usize);
+
+/// 控件种类与状态
+#[derive(Debug, Clone, PartialEq)]
+pub enum WidgetKind {
+    /// 面板，只绘制背景并容纳子控件
+    Panel,
+    /// 文本标签
+    Label {
+        /// 文本
+        text: String,
+    },
+    /// 按钮
+    Button {
+        /// 文本
+        text: String,
+    },
+    /// 复选框
+    Checkbox {
+        /// 文本
+        text: String,
+        /// 是否选中
+        checked: bool,
+    },
+    /// 滑动条
+    Slider {
+        /// 当前值
+        value: f32,
+        /// 最小值
+        min: f32,
+        /// 最大值
+        max: f32,
+        /// 步长，为 0 时连续取值
+        step: f32,
+    },
+    /// 单行文本输入框
+    TextInput {
+        /// 文本
+        text: String,
+        /// 文本为空时显示的提示
+        placeholder: String,
+        /// 光标位置，以字符计
+        cursor: usize,
+        /// 最大字符数，为 0 时不限制
+        max_len: usize,
+    },
+    /// 滚动区域，子控件超出区域的部分被裁剪，鼠标滚轮或焦点移动时竖直滚动
+    ScrollArea {
+        /// 滚动距离，单位为像素
+        scroll: f32,
+    },
+    /// 图像，将纹理拉伸绘制到整个矩形，可用于显示 [`RenderTargets`](crate::RenderTargets) 的画面
+    Image {
+        /// OpenGL 纹理对象ID，必须在控件使用期间保持存活
+        texture: u32,
+        /// 纹理区域，依次为左下角`u`、`v`与宽度、高度
+        uv_rect: Vec4,
+        /// 与纹理颜色相乘的颜色
+        tint: Vec4,
+    },
+}
+
+/// 控件
+#[derive(Debug, Clone, PartialEq)]
+pub struct Widget {
+    /// 种类与状态
+    pub kind: WidgetKind,
+    /// 相对于锚定区域的位置与尺寸，单位为逻辑像素，见 [`Anchor`]；
+    /// 父控件为弹性布局时只使用尺寸
+    pub rect: Rect,
+    /// 锚点，默认固定在父控件左上角
+    pub anchor: Anchor,
+    /// 排列子控件的方式
+    pub container: Container,
+    /// 父控件为弹性布局时分配剩余空间的比例，为 0 时保持 [`Widget::rect`] 的尺寸
+    pub grow: f32,
+    /// 是否可见，不可见的控件及其子控件既不绘制也不响应输入
+    pub visible: bool,
+    /// 是否可用，不可用的控件以暗色绘制且不响应输入
+    pub enabled: bool,
+    parent: Option<WidgetId>,
+    children: Vec<WidgetId>,
+}
+
+impl Widget {
+    /// 创建控件
+    pub fn new(kind: WidgetKind, rect: Rect) -> Self {
+        Self {
+            kind,
+            rect,
+            anchor: Anchor::default(),
+            container: Container::default(),
+            grow: 0.0,
+            visible: true,
+            enabled: true,
+            parent: None,
+            children: Vec::new(),
+        }
+    }
+
+    /// 设置锚点
+    pub fn with_anchor(mut self, anchor: Anchor) -> Self {
+        self.anchor = anchor;
+        self
+    }
+
+    /// 设置排列子控件的方式
+    pub fn with_container(mut self, container: Container) -> Self {
+        self.container = container;
+        self
+    }
+
+    /// 设置在弹性布局中分配剩余空间的比例
+    pub fn with_grow(mut self, grow: f32) -> Self {
+        self.grow = grow;
+        self
+    }
+
+    /// 创建面板
+    pub fn panel(rect: Rect) -> Self {
+        Self::new(WidgetKind::Panel, rect)
+    }
+
+    /// 创建文本标签
+    pub fn label(text: &str, rect: Rect) -> Self {
+        Self::new(
+            WidgetKind::Label {
+                text: text.to_string(),
+            },
+            rect,
+        )
+    }
+
+    /// 创建按钮
+    pub fn button(text: &str, rect: Rect) -> Self {
+        Self::new(
+            WidgetKind::Button {
+                text: text.to_string(),
+            },
+            rect,
+        )
+    }
+
+    /// 创建复选框
+    pub fn checkbox(text: &str, checked: bool, rect: Rect) -> Self {
+        let text = text.to_string();
+        Self::new(WidgetKind::Checkbox { text, checked }, rect)
+    }
+
+    /// 创建滑动条
+    ///
+    /// # 参数
+    /// + `value` - 初始值
+    /// + `min` - 最小值
+    /// + `max` - 最大值
+    /// + `rect` - 矩形
+    pub fn slider(value: f32, min: f32, max: f32, rect: Rect) -> Self {
+        let value = value.clamp(min, max);
+        Self::new(
+            WidgetKind::Slider {
+                value,
+                min,
+                max,
+                step: 0.0,
+            },
+            rect,
+        )
+    }
+
+    /// 创建单行文本输入框
+    pub fn text_input(placeholder: &str, rect: Rect) -> Self {
+        let kind = WidgetKind::TextInput {
+            text: String::new(),
+            placeholder: placeholder.to_string(),
+            cursor: 0,
+            max_len: 0,
+        };
+        Self::new(kind, rect)
+    }
+
+    /// 创建滚动区域
+    pub fn scroll_area(rect: Rect) -> Self {
+        Self::new(WidgetKind::ScrollArea { scroll: 0.0 }, rect)
+    }
+
+    /// 创建显示整张纹理的图像
+    pub fn image(texture: u32, rect: Rect) -> Self {
+        let kind = WidgetKind::Image {
+            texture,
+            uv_rect: Vec4::new(0.0, 0.0, 1.0, 1.0),
+            tint: Vec4::ONE,
+        };
+        Self::new(kind, rect)
+    }
+
+    /// 获取父控件
+    pub fn parent(&self) -> Option<WidgetId> {
+        self.parent
+    }
+
+    /// 获取子控件，按绘制顺序排列
+    pub fn children(&self) -> &[WidgetId] {
+        &self.children
+    }
+
+    /// 是否可以获得键盘焦点
+    pub fn focusable(&self) -> bool {
+        matches!(
+            self.kind,
+            WidgetKind::Button { .. }
+                | WidgetKind::Checkbox { .. }
+                | WidgetKind::Slider { .. }
+                | WidgetKind::TextInput { .. }
+        )
+    }
+}
+
+/// 界面事件
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum UiEvent {
+    /// 按钮被点击或在获得焦点时按下回车、空格
+    Clicked(WidgetId),
+    /// 复选框状态改变
+    Toggled(WidgetId, bool),
+    /// 滑动条的值改变
+    ValueChanged(WidgetId, f32),
+    /// 文本输入框的内容改变
+    TextChanged(WidgetId),
+    /// 在文本输入框中按下回车
+    Submitted(WidgetId),
+    /// 键盘焦点改变
+    FocusChanged(Option<WidgetId>),
+}
+
+/// 九宫格皮肤
+///
+/// 以纹理绘制控件背景，颜色与主题中对应状态的颜色相乘，因此使用皮肤时通常将这些颜色设为接近白色，
+/// 边框随 [`Ui::scale_factor`] 缩放
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Skin {
+    /// OpenGL 纹理对象ID
+    pub texture: u32,
+    /// 九宫格切片
+    pub slice: NineSlice,
+}
+
+impl Skin {
+    /// 由整张纹理创建皮肤
+    ///
+    /// # 参数
+    /// + `texture` - 纹理，必须在皮肤使用期间保持存活
+    /// + `border` - 四边不拉伸部分的像素宽度，依次为左、下、右、上
+    pub fn new(texture: &Texture2D, border: Vec4) -> Self {
+        Self {
+            texture: texture.id(),
+            slice: NineSlice::new(texture, border),
+        }
+    }
+}
+
+/// 界面主题
+#[derive(Debug, Clone, PartialEq)]
+pub struct Theme {
+    /// 字号
+    pub text_size: f32,
+    /// 文本颜色
+    pub text_color: Vec4,
+    /// 不可用控件与提示文本的颜色
+    pub disabled_color: Vec4,
+    /// 面板背景色
+    pub panel_color: Vec4,
+    /// 控件背景色
+    pub widget_color: Vec4,
+    /// 鼠标悬停时的控件背景色
+    pub hover_color: Vec4,
+    /// 按下时的控件背景色
+    pub active_color: Vec4,
+    /// 强调色，用于复选框的勾选标记、滑动条的已填充部分与文本光标
+    pub accent_color: Vec4,
+    /// 焦点框颜色
+    pub focus_color: Vec4,
+    /// 控件内边距
+    pub padding: f32,
+    /// 焦点框宽度
+    pub focus_width: f32,
+    /// 滚动区域每格滚轮的滚动距离
+    pub scroll_speed: f32,
+    /// 面板的九宫格皮肤，为`None`时绘制纯色矩形
+    pub panel_skin: Option<Skin>,
+    /// 按钮、复选框与滑块的九宫格皮肤
+    pub button_skin: Option<Skin>,
+    /// 文本输入框与滑动条轨道的九宫格皮肤
+    pub field_skin: Option<Skin>,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            text_size: 20.0,
+            text_color: Vec4::new(0.92, 0.92, 0.94, 1.0),
+            disabled_color: Vec4::new(0.5, 0.5, 0.54, 1.0),
+            panel_color: Vec4::new(0.1, 0.1, 0.12, 0.92),
+            widget_color: Vec4::new(0.2, 0.21, 0.24, 1.0),
+            hover_color: Vec4::new(0.27, 0.28, 0.32, 1.0),
+            active_color: Vec4::new(0.16, 0.17, 0.2, 1.0),
+            accent_color: Vec4::new(0.26, 0.59, 0.98, 1.0),
+            focus_color: Vec4::new(0.26, 0.59, 0.98, 1.0),
+            padding: 6.0,
+            focus_width: 2.0,
+            scroll_speed: 40.0,
+            panel_skin: None,
+            button_skin: None,
+            field_skin: None,
+        }
+    }
+}
+
+/// 已放置的控件：窗口坐标下的矩形与裁剪区域
+#[derive(Debug, Clone, Copy)]
+struct Placed {
+    id: WidgetId,
+    rect: Rect,
+    clip: Rect,
+}
+
+/// 保留模式界面
+///
+/// 以树的形式保存控件及其状态，每帧在事件循环中调用 [`Ui::update`] 消费 [`Input`] 的输入并产生 [`UiEvent`]，
+/// 在渲染循环中由 [`UiRenderer`] 绘制。控件按 [`Anchor`] 与 [`Container`] 布局，长度以逻辑像素为单位，
+/// 随窗口大小与 [`Ui::scale_factor`] 自动调整。
+///
+/// 键盘导航：`Tab`/`Shift+Tab`或方向键上下在可获得焦点的控件间移动，回车或空格激活按钮与复选框，
+/// 方向键左右调整滑动条，`Esc`取消焦点。文本输入框支持左右移动光标、`Home`/`End`、退格与删除
+///
+/// # 示例
+///
+/// ```no_run
+/// use std::sync::{Arc, Mutex};
+/// use gle::*;
+///
+/// let ui = Arc::new(Mutex::new(Ui::new()));
+/// let (play, volume) = {
+///     let mut ui = ui.lock().unwrap();
+///     let root = ui.root();
+///     let menu = Widget::panel(Rect::new(0.0, 0.0, 320.0, 240.0))
+///         .with_anchor(Anchor::CENTER)
+///         .with_container(Container::Flex(FlexLayout::column(12.0, 20.0)));
+///     let menu = ui.add(root, menu);
+///     let play = ui.add(menu, Widget::button("Play", Rect::new(0.0, 0.0, 0.0, 40.0)));
+///     let slider = Widget::slider(0.8, 0.0, 1.0, Rect::new(0.0, 0.0, 0.0, 32.0));
+///     let volume = ui.add(menu, slider);
+///     ui.set_focus(Some(play));
+///     (play, volume)
+/// };
+///
+/// let event_ui = ui.clone();
+/// let mut renderer = None;
+/// AppBuilder::new(1280, 720, "Menu")
+///     .set_event_loop(move || {
+///         let mut ui = event_ui.lock().unwrap();
+///         ui.update();
+///         for event in ui.events() {
+///             match event {
+///                 UiEvent::Clicked(id) if id == play => println!("play"),
+///                 UiEvent::ValueChanged(id, v) if id == volume => Audio::set_master_volume(v),
+///                 _ => {}
+///             }
+///         }
+///     })
+///     .set_render_loop(move || {
+///         let (renderer, font) = renderer.get_or_insert_with(|| {
+///             let font = SdfFont::load("assets/noto_sans.toml").unwrap();
+///             (UiRenderer::new().unwrap(), font)
+///         });
+///         renderer.draw(&ui.lock().unwrap(), font);
+///     })
+///     .build()
+///     .exec();
+/// ```
+#[derive(Debug, Clone)]
+pub struct Ui {
+    /// 主题
+    pub theme: Theme,
+    /// 界面缩放倍数，与窗口的内容缩放比例相乘得到逻辑像素到物理像素的缩放比例
+    pub scale: f32,
+    widgets: Vec<Option<Widget>>,
+    hovered: Option<WidgetId>,
+    active: Option<WidgetId>,
+    focus: Option<WidgetId>,
+    events: Vec<UiEvent>,
+}
+
+impl Default for Ui {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Ui {
+    /// 创建界面，包含一个覆盖整个窗口的透明根控件
+    pub fn new() -> Self {
+        let root = Widget::new(WidgetKind::Panel, Rect::default());
+        Self {
+            theme: Theme::default(),
+            scale: 1.0,
+            widgets: vec![Some(root)],
+            hovered: None,
+            active: None,
+            focus: None,
+            events: Vec::new(),
+        }
+    }
+
+    /// 获取根控件
+    pub fn root(&self) -> WidgetId {
+        WidgetId(0)
+    }
+
+    /// 添加控件
+    ///
+    /// # 参数
+    /// + `parent` - 父控件
+    /// + `widget` - 控件
+    ///
+    /// # 返回值
+    /// 返回新控件的句柄，绘制在父控件已有的子控件之上
+    pub fn add(&mut self, parent: WidgetId, mut widget: Widget) -> WidgetId {
+        widget.parent = Some(parent);
+        widget.children.clear();
+        let id = match self.widgets.iter().position(Option::is_none) {
+            Some(index) => {
+                self.widgets[index] = Some(widget);
+                WidgetId(index)
+            }
+            None => {
+                self.widgets.push(Some(widget));
+                WidgetId(self.widgets.len() - 1)
+            }
+        };
+        if let Some(parent) = self.widget_mut(parent) {
+            parent.children.push(id);
+        }
+        id
+    }
+
+    /// 移除控件及其所有子控件，根控件无法移除
+    pub fn remove(&mut self, id: WidgetId) {
+        if id == self.root() {
+            return;
+        }
+        let Some(widget) = self.widgets.get_mut(id.0).and_then(Option::take) else {
+            return;
+        };
+        if let Some(parent) = widget.parent.and_then(|p| self.widget_mut(p)) {
+            parent.children.retain(|c| *c != id);
+        }
+        for child in widget.children {
+            self.remove(child);
+        }
+        for state in [&mut self.hovered, &mut self.active] {
+            if *state == Some(id) {
+                *state = None;
+            }
+        }
+        if self.focus == Some(id) {
+            self.set_focus(None);
+        }
+    }
+
+    /// 获取控件
+    pub fn widget(&self, id: WidgetId) -> Option<&Widget> {
+        self.widgets.get(id.0).and_then(Option::as_ref)
+    }
+
+    /// 获取控件的可变引用
+    pub fn widget_mut(&mut self, id: WidgetId) -> Option<&mut Widget> {
+        self.widgets.get_mut(id.0).and_then(Option::as_mut)
+    }
+
+    /// 获取按钮、标签、复选框或文本输入框的文本
+    pub fn text(&self, id: WidgetId) -> Option<&str> {
+        match &self.widget(id)?.kind {
+            WidgetKind::Label { text }
+            | WidgetKind::Button { text }
+            | WidgetKind::Checkbox { text, .. }
+            | WidgetKind::TextInput { text, .. } => Some(text),
+            _ => None,
+        }
+    }
+
+    /// 设置按钮、标签、复选框或文本输入框的文本，文本输入框的光标移到末尾
+    pub fn set_text(&mut self, id: WidgetId, value: &str) {
+        match self.widget_mut(id).map(|w| &mut w.kind) {
+            Some(
+                WidgetKind::Label { text }
+                | WidgetKind::Button { text }
+                | WidgetKind::Checkbox { text, .. },
+            ) => *text = value.to_string(),
+            Some(WidgetKind::TextInput { text, cursor, .. }) => {
+                *text = value.to_string();
+                *cursor = text.chars().count();
+            }
+            _ => {}
+        }
+    }
+
+    /// 获取复选框是否选中
+    pub fn checked(&self, id: WidgetId) -> bool {
+        matches!(
+            self.widget(id).map(|w| &w.kind),
+            Some(WidgetKind::Checkbox { checked: true, .. })
+        )
+    }
+
+    /// 获取滑动条的值
+    pub fn value(&self, id: WidgetId) -> f32 {
+        match self.widget(id).map(|w| &w.kind) {
+            Some(WidgetKind::Slider { value, .. }) => *value,
+            _ => 0.0,
+        }
+    }
+
+    /// 获取鼠标悬停的控件
+    pub fn hovered(&self) -> Option<WidgetId> {
+        self.hovered
+    }
+
+    /// 获取正在被鼠标按住的控件
+    pub fn active(&self) -> Option<WidgetId> {
+        self.active
+    }
+
+    /// 获取拥有键盘焦点的控件
+    pub fn focus(&self) -> Option<WidgetId> {
+        self.focus
+    }
+
+    /// 设置键盘焦点，焦点位于滚动区域内时滚动使其可见
+    pub fn set_focus(&mut self, id: Option<WidgetId>) {
+        if self.focus == id {
+            return;
+        }
+        self.focus = id;
+        self.events.push(UiEvent::FocusChanged(id));
+        if let Some(id) = id {
+            self.scroll_into_view(id);
+        }
+    }
+
+    /// 界面是否正在使用鼠标或键盘，此时游戏逻辑应忽略对应的输入
+    ///
+    /// # 返回值
+    /// 鼠标悬停在根控件以外的控件上、有控件被按住或有控件拥有焦点时返回`true`
+    pub fn wants_input(&self) -> bool {
+        let hovered = self.hovered.is_some_and(|h| h != self.root());
+        hovered || self.active.is_some() || self.focus.is_some()
+    }
+
+    /// 取出自上次调用以来产生的事件
+    pub fn events(&mut self) -> Vec<UiEvent> {
+        std::mem::take(&mut self.events)
+    }
+
+    /// 获取逻辑像素到物理像素的缩放比例，即 [`Ui::scale`] 与窗口内容缩放比例之积
+    pub fn scale_factor(&self) -> f32 {
+        let (x, _) = App::content_scale();
+        (self.scale * x).max(f32::EPSILON)
+    }
+
+    /// 按绘制顺序计算可见控件在窗口中的矩形与裁剪区域
+    fn placed(&self) -> Vec<Placed> {
+        let (w, h) = App::window_size();
+        let window = Rect::new(0.0, 0.0, w as f32, h as f32);
+        let scale = self.scale_factor();
+        let mut out = Vec::new();
+        self.place(self.root(), window, window, scale, &mut out);
+        out
+    }
+
+    fn place(&self, id: WidgetId, rect: Rect, clip: Rect, scale: f32, out: &mut Vec<Placed>) {
+        let Some(widget) = self.widget(id).filter(|w| w.visible) else {
+            return;
+        };
+        out.push(Placed { id, rect, clip });
+        let (content, clip) = match widget.kind {
+            WidgetKind::ScrollArea { scroll } => {
+                let position = rect.position - Vec2::new(0.0, scroll * scale);
+                (Rect { position, ..rect }, clip.intersect(&rect))
+            }
+            _ => (rect, clip),
+        };
+        for (child, rect) in self.arrange(widget, content, scale) {
+            self.place(child, rect, clip, scale, out);
+        }
+    }
+
+    /// 按父控件的排列方式计算可见子控件的矩形
+    fn arrange(&self, widget: &Widget, content: Rect, scale: f32) -> Vec<(WidgetId, Rect)> {
+        let children: Vec<(WidgetId, &Widget)> = widget
+            .children
+            .iter()
+            .filter_map(|c| self.widget(*c).filter(|w| w.visible).map(|w| (*c, w)))
+            .collect();
+        let items: Vec<LayoutItem> = children
+            .iter()
+            .map(|(_, w)| LayoutItem {
+                anchor: w.anchor,
+                rect: w.rect,
+                grow: w.grow,
+            })
+            .collect();
+        let rects = ui_layout::arrange(&widget.container, content, &items, scale);
+        children.into_iter().map(|(id, _)| id).zip(rects).collect()
+    }
+
+    /// 按逻辑像素滚动滚动区域，限制在内容范围内
+    fn scroll_by(&mut self, id: WidgetId, delta: f32) {
+        let scale = self.scale_factor();
+        let placed = self.placed();
+        let (Some(area), Some(widget)) = (placed.iter().find(|p| p.id == id), self.widget(id))
+        else {
+            return;
+        };
+        let top = area.rect.position.y;
+        let padding = widget.container.padding() * scale;
+        let bottom = self
+            .arrange(widget, area.rect, scale)
+            .iter()
+            .map(|(_, r)| r.max().y + padding)
+            .fold(top, f32::max);
+        let limit = ((bottom - top - area.rect.size.y) / scale).max(0.0);
+        if let Some(WidgetKind::ScrollArea { scroll }) = self.widget_mut(id).map(|w| &mut w.kind) {
+            *scroll = (*scroll + delta).clamp(0.0, limit);
+        }
+    }
+
+    /// 滚动最近的滚动区域祖先，使控件完整可见
+    fn scroll_into_view(&mut self, id: WidgetId) {
+        let mut current = self.widget(id).and_then(|w| w.parent);
+        while let Some(parent) = current {
+            let widget = self.widget(parent);
+            if matches!(widget.map(|w| &w.kind), Some(WidgetKind::ScrollArea { .. })) {
+                break;
+            }
+            current = widget.and_then(|w| w.parent);
+        }
+        let Some(area) = current else {
+            return;
+        };
+        let placed = self.placed();
+        let find = |id: WidgetId| placed.iter().find(|p| p.id == id).map(|p| p.rect);
+        let (Some(target), Some(bounds)) = (find(id), find(area)) else {
+            return;
+        };
+        let delta = if target.position.y < bounds.position.y {
+            target.position.y - bounds.position.y
+        } else if target.max().y > bounds.max().y {
+            (target.max().y - bounds.max().y).min(target.position.y - bounds.position.y)
+        } else {
+            0.0
+        };
+        self.scroll_by(area, delta / self.scale_factor());
+    }
+
+    fn enabled(&self, id: WidgetId) -> bool {
+        let mut current = Some(id);
+        while let Some(widget) = current.and_then(|c| self.widget(c)) {
+            if !widget.enabled || !widget.visible {
+                return false;
+            }
+            current = widget.parent;
+        }
+        true
+    }
+
+    /// 处理本帧输入
+    ///
+    /// # 注解
+    ///
+    /// 应在事件循环中每帧调用一次，[`Input`] 的本帧状态在事件循环函数返回后被清空
+    pub fn update(&mut self) {
+        let placed = self.placed();
+        let (x, y) = Input::cursor_pos();
+        let cursor = Vec2::new(x as f32, y as f32);
+        self.hovered = placed
+            .iter()
+            .rev()
+            .find(|p| p.rect.contains(cursor) && p.clip.contains(cursor))
+            .map(|p| p.id);
+        self.update_mouse(&placed, cursor);
+        self.update_keyboard(&placed);
+    }
+
+    fn update_mouse(&mut self, placed: &[Placed], cursor: Vec2) {
+        let (_, wheel) = Input::scroll_delta();
+        if wheel != 0.0 {
+            let mut current = self.hovered;
+            while let Some(id) = current {
+                if matches!(
+                    self.widget(id).map(|w| &w.kind),
+                    Some(WidgetKind::ScrollArea { .. })
+                ) {
+                    self.scroll_by(id, -wheel as f32 * self.theme.scroll_speed);
+                    break;
+                }
+                current = self.widget(id).and_then(|w| w.parent);
+            }
+        }
+
+        if Input::mouse_pressed(MouseButton::Button1) {
+            let target = self
+                .hovered
+                .filter(|h| self.enabled(*h) && self.widget(*h).is_some_and(Widget::focusable));
+            self.active = target;
+            self.set_focus(target);
+        }
+        let Some(active) = self.active else {
+            return;
+        };
+        if let Some(rect) = placed.iter().find(|p| p.id == active).map(|p| p.rect) {
+            if Input::mouse_down(MouseButton::Button1) {
+                let t = (cursor.x - rect.position.x) / rect.size.x.max(1.0);
+                self.set_slider_fraction(active, t);
+            }
+        }
+        if Input::mouse_released(MouseButton::Button1) {
+            if self.hovered == Some(active) {
+                self.activate(active);
+            }
+            self.active = None;
+        }
+    }
+
+    fn update_keyboard(&mut self, placed: &[Placed]) {
+        let shift = Input::key_down(Key::LeftShift) || Input::key_down(Key::RightShift);
+        let focus_kind = self
+            .focus
+            .and_then(|f| self.widget(f))
+            .map(|w| w.kind.clone());
+        let editing = matches!(focus_kind, Some(WidgetKind::TextInput { .. }));
+        if Input::key_repeated(Key::Tab) {
+            self.move_focus(placed, !shift);
+        } else if !editing && Input::key_repeated(Key::Down) {
+            self.move_focus(placed, true);
+        } else if !editing && Input::key_repeated(Key::Up) {
+            self.move_focus(placed, false);
+        } else if Input::key_pressed(Key::Escape) {
+            self.set_focus(None);
+        }
+        let Some(focus) = self.focus.filter(|f| self.enabled(*f)) else {
+            return;
+        };
+        let enter = Input::key_pressed(Key::Enter) || Input::key_pressed(Key::KpEnter);
+        match focus_kind {
+            Some(WidgetKind::Button { .. } | WidgetKind::Checkbox { .. })
+                if enter || Input::key_pressed(Key::Space) =>
+            {
+                self.activate(focus);
+            }
+            Some(WidgetKind::Slider {
+                value,
+                min,
+                max,
+                step,
+            }) => {
+                let step = if step > 0.0 { step } else { (max - min) * 0.01 };
+                if Input::key_repeated(Key::Left) {
+                    self.set_slider_value(focus, value - step);
+                }
+                if Input::key_repeated(Key::Right) {
+                    self.set_slider_value(focus, value + step);
+                }
+            }
+            Some(WidgetKind::TextInput { .. }) => {
+                self.edit_text(focus);
+                if enter {
+                    self.events.push(UiEvent::Submitted(focus));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// 按绘制顺序将焦点移到下一个或上一个可获得焦点的控件
+    fn move_focus(&mut self, placed: &[Placed], forward: bool) {
+        let candidates: Vec<WidgetId> = placed
+            .iter()
+            .map(|p| p.id)
+            .filter(|id| self.enabled(*id) && self.widget(*id).is_some_and(Widget::focusable))
+            .collect();
+        if candidates.is_empty() {
+            return;
+        }
+        let current = self
+            .focus
+            .and_then(|f| candidates.iter().position(|c| *c == f));
+        let next = match (current, forward) {
+            (None, true) => 0,
+            (None, false) => candidates.len() - 1,
+            (Some(i), true) => (i + 1) % candidates.len(),
+            (Some(i), false) => (i + candidates.len() - 1) % candidates.len(),
+        };
+        self.set_focus(Some(candidates[next]));
+    }
+
+    /// 点击按钮或切换复选框
+    fn activate(&mut self, id: WidgetId) {
+        match self.widget_mut(id).map(|w| &mut w.kind) {
+            Some(WidgetKind::Button { .. }) => self.events.push(UiEvent::Clicked(id)),
+            Some(WidgetKind::Checkbox { checked, .. }) => {
+                *checked = !*checked;
+                let checked = *checked;
+                self.events.push(UiEvent::Toggled(id, checked));
+            }
+            _ => {}
+        }
+    }
+
+    fn set_slider_fraction(&mut self, id: WidgetId, t: f32) {
+        if let Some(WidgetKind::Slider { min, max, .. }) = self.widget(id).map(|w| &w.kind) {
+            let value = min + (max - min) * t.clamp(0.0, 1.0);
+            self.set_slider_value(id, value);
+        }
+    }
+
+    /// 设置滑动条的值，按步长取整并限制在范围内
+    pub fn set_slider_value(&mut self, id: WidgetId, new_value: f32) {
+        let Some(WidgetKind::Slider {
+            value,
+            min,
+            max,
+            step,
+        }) = self.widget_mut(id).map(|w| &mut w.kind)
+        else {
+            return;
+        };
+        let mut v = new_value;
+        if *step > 0.0 {
+            v = *min + ((v - *min) / *step).round() * *step;
+        }
+        let v = v.clamp(min.min(*max), max.max(*min));
+        if v != *value {
+            *value = v;
+            self.events.push(UiEvent::ValueChanged(id, v));
+        }
+    }
+
+    fn edit_text(&mut self, id: WidgetId) {
+        let Some(WidgetKind::TextInput {
+            text,
+            cursor,
+            max_len,
+            ..
+        }) = self.widget_mut(id).map(|w| &mut w.kind)
+        else {
+            return;
+        };
+        let mut chars: Vec<char> = text.chars().collect();
+        let mut pos = (*cursor).min(chars.len());
+        let before = chars.clone();
+        for ch in Input::text_input().chars().filter(|c| !c.is_control()) {
+            if *max_len == 0 || chars.len() < *max_len {
+                chars.insert(pos, ch);
+                pos += 1;
+            }
+        }
+        if Input::key_repeated(Key::Backspace) && pos > 0 {
+            pos -= 1;
+            chars.remove(pos);
+        }
+        if Input::key_repeated(Key::Delete) && pos < chars.len() {
+            chars.remove(pos);
+        }
+        if Input::key_repeated(Key::Left) {
+            pos = pos.saturating_sub(1);
+        }
+        if Input::key_repeated(Key::Right) {
+            pos = (pos + 1).min(chars.len());
+        }
+        if Input::key_pressed(Key::Home) {
+            pos = 0;
+        }
+        if Input::key_pressed(Key::End) {
+            pos = chars.len();
+        }
+        *cursor = pos;
+        if chars != before {
+            *text = chars.into_iter().collect();
+            self.events.push(UiEvent::TextChanged(id));
+        }
+    }
+}
+
+/// 一组共享裁剪区域、按顺序绘制的图元
+struct Layer {
+    clip: Rect,
+    quads: Vec<(Rect, Vec4, Option<Skin>)>,
+    texts: Vec<(String, Vec2, Vec4)>,
+}
+
+/// 界面渲染器
+///
+/// 以 [`SpriteBatch`] 绘制控件的背景与装饰，以 [`TextRenderer`] 绘制文本，滚动区域通过裁剪测试限制绘制范围
+///
+/// # 注解
+///
+/// 该类型只能在渲染线程中创建、使用与释放
+pub struct UiRenderer {
+    batch: SpriteBatch,
+    text: TextRenderer,
+    white: Texture2D,
+}
+
+impl UiRenderer {
+    /// 创建界面渲染器
+    ///
+    /// # 返回值
+    /// 成功时返回渲染器，内置着色器编译失败时返回错误
+    pub fn new() -> Result<Self> {
+        Ok(Self {
+            batch: SpriteBatch::new()?,
+            text: TextRenderer::new()?,
+            white: Texture2D::from_rgba8(1, 1, &[255; 4], false),
+        })
+    }
+
+    /// 绘制界面
+    ///
+    /// # 参数
+    /// + `ui` - 界面
+    /// + `font` - 字体
+    pub fn draw(&mut self, ui: &Ui, font: &SdfFont) {
+        let (w, h) = App::window_size();
+        let window = Vec2::new(w as f32, h as f32);
+        let layers = Self::build(ui, font);
+        let projection = orthographic(0.0, window.x, 0.0, window.y, -1.0, 1.0);
+        let flip = |p: Vec2| Vec2::new(p.x, window.y - p.y);
+        let scale = ui.scale_factor();
+        let size = ui.theme.text_size * scale;
+        for layer in layers {
+            if layer.clip.size.x <= 0.0 || layer.clip.size.y <= 0.0 {
+                continue;
+            }
+            let clip = ScissorRect::new(
+                layer.clip.position.x as i32,
+                (window.y - layer.clip.max().y) as i32,
+                layer.clip.size.x.ceil() as i32,
+                layer.clip.size.y.ceil() as i32,
+            );
+            Renderer::with_scissor(clip, || {
+                self.batch.begin(&projection);
+                for (rect, color, skin) in &layer.quads {
+                    let min = Vec2::new(rect.position.x, window.y - rect.max().y);
+                    let max = Vec2::new(rect.max().x, window.y - rect.position.y);
+                    match skin {
+                        Some(skin) => {
+                            let (texture, slice) = (skin.texture, &skin.slice);
+                            self.batch
+                                .draw_nine_slice(texture, slice, min, max, *color, scale);
+                        }
+                        None => {
+                            let sprite = Sprite {
+                                color: *color,
+                                ..Sprite::new(min, max)
+                            };
+                            self.batch.draw(self.white.id(), &sprite);
+                        }
+                    }
+                }
+                self.batch.end();
+                self.text.begin(&projection);
+                for (text, baseline, color) in &layer.texts {
+                    let style = TextStyle {
+                        size,
+                        color: *color,
+                        ..Default::default()
+                    };
+                    self.text.draw(font, text, flip(*baseline), &style);
+                }
+                self.text.end();
+            });
+        }
+    }
+
+    fn build(ui: &Ui, font: &SdfFont) -> Vec<Layer> {
+        let theme = &ui.theme;
+        let scale = ui.scale_factor();
+        let size = theme.text_size * scale;
+        let padding = theme.padding * scale;
+        let (ascent, descent) = font.data().vertical_metrics(size);
+        // 文本在矩形中竖直居中时基线相对于矩形中心的偏移(向下为正)
+        let baseline = (ascent + descent) * 0.5;
+        let mut layers: Vec<Layer> = Vec::new();
+        for placed in ui.placed() {
+            let Some(widget) = ui.widget(placed.id) else {
+                continue;
+            };
+            // 面板可能覆盖之前的文本，因此另起一层，使其背景绘制在之前的文本之上
+            let opaque = matches!(widget.kind, WidgetKind::Panel) && placed.id != ui.root();
+            let same = matches!(layers.last(), Some(l) if l.clip == placed.clip);
+            if !same || (opaque && layers.last().is_some_and(|l| !l.texts.is_empty())) {
+                layers.push(Layer {
+                    clip: placed.clip,
+                    quads: Vec::new(),
+                    texts: Vec::new(),
+                });
+            }
+            let layer = layers.last_mut().unwrap();
+            let rect = placed.rect;
+            let enabled = ui.enabled(placed.id);
+            let text_color = if enabled {
+                theme.text_color
+            } else {
+                theme.disabled_color
+            };
+            let background = if !enabled {
+                theme.widget_color * Vec4::new(1.0, 1.0, 1.0, 0.5)
+            } else if ui.active == Some(placed.id) {
+                theme.active_color
+            } else if ui.hovered == Some(placed.id) {
+                theme.hover_color
+            } else {
+                theme.widget_color
+            };
+            let text_y = rect.position.y + rect.size.y * 0.5 + baseline;
+            let text_x = rect.position.x + padding;
+            match &widget.kind {
+                WidgetKind::Panel if placed.id != ui.root() => {
+                    layer
+                        .quads
+                        .push((rect, theme.panel_color, theme.panel_skin));
+                }
+                WidgetKind::Panel | WidgetKind::ScrollArea { .. } => {}
+                WidgetKind::Image {
+                    texture,
+                    uv_rect,
+                    tint,
+                } => {
+                    let slice = NineSlice {
+                        uv_rect: *uv_rect,
+                        size: Vec2::ONE,
+                        border: Vec4::ZERO,
+                        fill_center: true,
+                    };
+                    let skin = Skin {
+                        texture: *texture,
+                        slice,
+                    };
+                    let color = if enabled {
+                        *tint
+                    } else {
+                        *tint * Vec4::new(1.0, 1.0, 1.0, 0.5)
+                    };
+                    layer.quads.push((rect, color, Some(skin)));
+                }
+                WidgetKind::Label { text } => {
+                    layer
+                        .texts
+                        .push((text.clone(), Vec2::new(text_x, text_y), text_color));
+                }
+                WidgetKind::Button { text } => {
+                    layer.quads.push((rect, background, theme.button_skin));
+                    let width = font.measure(text, size).x;
+                    let x = rect.position.x + (rect.size.x - width) * 0.5;
+                    layer
+                        .texts
+                        .push((text.clone(), Vec2::new(x, text_y), text_color));
+                }
+                WidgetKind::Checkbox { text, checked } => {
+                    let side = rect.size.y;
+                    let bounds = Rect {
+                        position: rect.position,
+                        size: Vec2::splat(side),
+                    };
+                    layer.quads.push((bounds, background, theme.button_skin));
+                    if *checked {
+                        let mark = bounds.shrink(side * 0.25);
+                        layer.quads.push((mark, theme.accent_color, None));
+                    }
+                    let x = rect.position.x + side + padding;
+                    layer
+                        .texts
+                        .push((text.clone(), Vec2::new(x, text_y), text_color));
+                }
+                WidgetKind::Slider {
+                    value, min, max, ..
+                } => {
+                    let t = if max > min {
+                        (value - min) / (max - min)
+                    } else {
+                        0.0
+                    };
+                    let track = Rect::new(
+                        rect.position.x,
+                        rect.position.y + rect.size.y * 0.4,
+                        rect.size.x,
+                        rect.size.y * 0.2,
+                    );
+                    layer
+                        .quads
+                        .push((track, theme.widget_color, theme.field_skin));
+                    let filled = Rect {
+                        size: Vec2::new(track.size.x * t, track.size.y),
+                        ..track
+                    };
+                    layer
+                        .quads
+                        .push((filled, theme.accent_color, theme.field_skin));
+                    let knob_w = rect.size.y * 0.5;
+                    let knob = Rect::new(
+                        rect.position.x + (rect.size.x - knob_w) * t,
+                        rect.position.y,
+                        knob_w,
+                        rect.size.y,
+                    );
+                    layer.quads.push((knob, text_color, theme.button_skin));
+                }
+                WidgetKind::TextInput {
+                    text,
+                    placeholder,
+                    cursor,
+                    ..
+                } => {
+                    layer
+                        .quads
+                        .push((rect, theme.active_color, theme.field_skin));
+                    let origin = Vec2::new(text_x, text_y);
+                    if text.is_empty() {
+                        layer
+                            .texts
+                            .push((placeholder.clone(), origin, theme.disabled_color));
+                    }
+                    // 文本超出输入框时截去两端的字符，使光标保持可见
+                    let avail = rect.size.x - 2.0 * padding;
+                    let chars: Vec<char> = text.chars().collect();
+                    let cursor = (*cursor).min(chars.len());
+                    let width = |from: usize, to: usize| {
+                        font.measure(&chars[from..to].iter().collect::<String>(), size)
+                            .x
+                    };
+                    let mut start = 0;
+                    while start < cursor && width(start, cursor) > avail {
+                        start += 1;
+                    }
+                    let mut end = chars.len();
+                    while end > cursor && width(start, end) > avail {
+                        end -= 1;
+                    }
+                    if !text.is_empty() {
+                        let visible = chars[start..end].iter().collect();
+                        layer.texts.push((visible, origin, text_color));
+                    }
+                    if ui.focus == Some(placed.id) {
+                        let caret = Rect::new(
+                            text_x + width(start, cursor),
+                            rect.position.y + padding,
+                            2.0 * scale,
+                            rect.size.y - 2.0 * padding,
+                        );
+                        layer.quads.push((caret, theme.accent_color, None));
+                    }
+                }
+            }
+            if ui.focus == Some(placed.id) {
+                let width = theme.focus_width * scale;
+                let color = theme.focus_color;
+                let (min, max) = (rect.position, rect.max());
+                layer.quads.extend([
+                    (Rect::new(min.x, min.y, rect.size.x, width), color, None),
+                    (
+                        Rect::new(min.x, max.y - width, rect.size.x, width),
+                        color,
+                        None,
+                    ),
+                    (Rect::new(min.x, min.y, width, rect.size.y), color, None),
+                    (
+                        Rect::new(max.x - width, min.y, width, rect.size.y),
+                        color,
+                        None,
+                    ),
+                ]);
+            }
+        }
+        layers
+    }
+}
@@ -0,0 +1,276 @@
+use crate::math::*;
+use crate::Rect;
+
+/// 锚点
+///
+/// 描述控件相对于父控件内容区的位置与尺寸。`min`与`max`是父控件内容区中的比例坐标(左上角为`(0, 0)`，
+/// 右下角为`(1, 1)`)，两者围成锚定区域；`pivot`是控件自身的轴心比例坐标。
+///
+/// 控件的尺寸为锚定区域尺寸加上 [`Widget::rect`](crate::Widget::rect) 的尺寸，
+/// 控件轴心与锚定区域中对应比例的点对齐后再偏移 [`Widget::rect`](crate::Widget::rect) 的位置：
+/// + `min`与`max`相同时锚定区域退化为一点，控件保持固定尺寸并随该点移动
+/// + `min`与`max`不同时控件随父控件拉伸，此时矩形尺寸为相对于锚定区域的增量，通常为负的边距之和
+///
+/// 所有长度的单位为逻辑像素，绘制时乘以 [`Ui::scale_factor`](crate::Ui::scale_factor)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Anchor {
+    /// 锚定区域的左上角
+    pub min: Vec2,
+    /// 锚定区域的右下角
+    pub max: Vec2,
+    /// 控件轴心
+    pub pivot: Vec2,
+}
+
+impl Anchor {
+    /// 固定在父控件左上角
+    pub const TOP_LEFT: Anchor = Anchor::point(0.0, 0.0);
+    /// 固定在父控件上边的中点
+    pub const TOP: Anchor = Anchor::point(0.5, 0.0);
+    /// 固定在父控件右上角
+    pub const TOP_RIGHT: Anchor = Anchor::point(1.0, 0.0);
+    /// 固定在父控件左边的中点
+    pub const LEFT: Anchor = Anchor::point(0.0, 0.5);
+    /// 固定在父控件中心
+    pub const CENTER: Anchor = Anchor::point(0.5, 0.5);
+    /// 固定在父控件右边的中点
+    pub const RIGHT: Anchor = Anchor::point(1.0, 0.5);
+    /// 固定在父控件左下角
+    pub const BOTTOM_LEFT: Anchor = Anchor::point(0.0, 1.0);
+    /// 固定在父控件下边的中点
+    pub const BOTTOM: Anchor = Anchor::point(0.5, 1.0);
+    /// 固定在父控件右下角
+    pub const BOTTOM_RIGHT: Anchor = Anchor::point(1.0, 1.0);
+    /// 填满父控件
+    pub const FILL: Anchor = Anchor::new(Vec2::ZERO, Vec2::ONE, Vec2::ZERO);
+    /// 横向填满父控件，固定在上边
+    pub const FILL_TOP: Anchor = Anchor::new(Vec2::ZERO, Vec2::new(1.0, 0.0), Vec2::ZERO);
+    /// 横向填满父控件，固定在下边
+    pub const FILL_BOTTOM: Anchor =
+        Anchor::new(Vec2::new(0.0, 1.0), Vec2::ONE, Vec2::new(0.0, 1.0));
+
+    /// 创建锚点
+    ///
+    /// # 参数
+    /// + `min` - 锚定区域的左上角
+    /// + `max` - 锚定区域的右下角
+    /// + `pivot` - 控件轴心
+    pub const fn new(min: Vec2, max: Vec2, pivot: Vec2) -> Self {
+        Self { min, max, pivot }
+    }
+
+    /// 创建固定在父控件中一点的锚点，控件轴心与该点的比例坐标相同
+    pub const fn point(x: f32, y: f32) -> Self {
+        let p = Vec2::new(x, y);
+        Self::new(p, p, p)
+    }
+
+    /// 计算控件矩形
+    ///
+    /// # 参数
+    /// + `parent` - 父控件内容区，单位为物理像素
+    /// + `offset` - 轴心相对于锚定区域的偏移，单位为物理像素
+    /// + `delta` - 尺寸相对于锚定区域的增量，单位为物理像素
+    pub fn resolve(&self, parent: Rect, offset: Vec2, delta: Vec2) -> Rect {
+        let min = parent.position + self.min * parent.size;
+        let region = (self.max - self.min) * parent.size;
+        let size = (region + delta).max(Vec2::ZERO);
+        let pivot = min + self.pivot * region + offset;
+        Rect {
+            position: pivot - self.pivot * size,
+            size,
+        }
+    }
+}
+
+impl Default for Anchor {
+    fn default() -> Self {
+        Self::TOP_LEFT
+    }
+}
+
+/// 弹性布局的主轴方向
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FlexDirection {
+    /// 从左到右横向排列
+    Row,
+    /// 从上到下竖直排列
+    #[default]
+    Column,
+}
+
+/// 主轴上的对齐方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Justify {
+    /// 靠近起点
+    #[default]
+    Start,
+    /// 居中
+    Center,
+    /// 靠近终点
+    End,
+    /// 首尾贴边，剩余空间均分到子控件之间
+    SpaceBetween,
+    /// 剩余空间均分到每个子控件两侧
+    SpaceAround,
+}
+
+/// 交叉轴上的对齐方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Align {
+    /// 靠近起点
+    Start,
+    /// 居中
+    Center,
+    /// 靠近终点
+    End,
+    /// 拉伸填满
+    #[default]
+    Stretch,
+}
+
+/// 弹性布局
+///
+/// 子控件沿主轴依次排列，主轴尺寸取 [`Widget::rect`](crate::Widget::rect) 的尺寸，
+/// 剩余空间按 [`Widget::grow`](crate::Widget::grow) 的比例分配；子控件的位置与锚点被忽略
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct FlexLayout {
+    /// 主轴方向
+    pub direction: FlexDirection,
+    /// 相邻子控件的间距，单位为逻辑像素
+    pub gap: f32,
+    /// 内边距，单位为逻辑像素
+    pub padding: f32,
+    /// 主轴上的对齐方式，存在可伸展的子控件时无效
+    pub justify: Justify,
+    /// 交叉轴上的对齐方式
+    pub align: Align,
+}
+
+impl FlexLayout {
+    /// 创建横向排列的弹性布局
+    pub fn row(gap: f32, padding: f32) -> Self {
+        Self {
+            direction: FlexDirection::Row,
+            gap,
+            padding,
+            ..Default::default()
+        }
+    }
+
+    /// 创建竖直排列的弹性布局
+    pub fn column(gap: f32, padding: f32) -> Self {
+        Self {
+            direction: FlexDirection::Column,
+            gap,
+            padding,
+            ..Default::default()
+        }
+    }
+}
+
+/// 控件排列子控件的方式
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Container {
+    /// 子控件按各自的锚点定位
+    #[default]
+    Free,
+    /// 子控件按弹性布局排列
+    Flex(FlexLayout),
+}
+
+impl Container {
+    /// 获取内边距，单位为逻辑像素
+    pub fn padding(&self) -> f32 {
+        match self {
+            Container::Free => 0.0,
+            Container::Flex(flex) => flex.padding,
+        }
+    }
+}
+
+/// 参与布局的子控件
+pub(crate) struct LayoutItem {
+    pub anchor: Anchor,
+    pub rect: Rect,
+    pub grow: f32,
+}
+
+/// 计算子控件的矩形
+///
+/// # 参数
+/// + `container` - 排列方式
+/// + `content` - 父控件内容区，单位为物理像素
+/// + `items` - 子控件，长度单位为逻辑像素
+/// + `scale` - 逻辑像素到物理像素的缩放比例
+pub(crate) fn arrange(
+    container: &Container,
+    content: Rect,
+    items: &[LayoutItem],
+    scale: f32,
+) -> Vec<Rect> {
+    let flex = match container {
+        Container::Free => {
+            return items
+                .iter()
+                .map(|i| i.anchor.resolve(content, i.rect.position * scale, i.rect.size * scale))
+                .collect();
+        }
+        Container::Flex(flex) => flex,
+    };
+    if items.is_empty() {
+        return Vec::new();
+    }
+    let inner = content.shrink(flex.padding * scale);
+    let (main, cross) = match flex.direction {
+        FlexDirection::Row => (0, 1),
+        FlexDirection::Column => (1, 0),
+    };
+    let gap = flex.gap * scale;
+    let mut sizes: Vec<f32> = items.iter().map(|i| i.rect.size[main].max(0.0) * scale).collect();
+    let used = sizes.iter().sum::<f32>() + gap * (items.len() - 1) as f32;
+    let free = (inner.size[main] - used).max(0.0);
+    let total_grow: f32 = items.iter().map(|i| i.grow.max(0.0)).sum();
+
+    let (mut cursor, mut spacing) = (0.0, gap);
+    if total_grow > 0.0 {
+        for (size, item) in sizes.iter_mut().zip(items) {
+            *size += free * item.grow.max(0.0) / total_grow;
+        }
+    } else {
+        let n = items.len() as f32;
+        match flex.justify {
+            Justify::Start => {}
+            Justify::Center => cursor = free * 0.5,
+            Justify::End => cursor = free,
+            Justify::SpaceBetween if items.len() > 1 => spacing += free / (n - 1.0),
+            Justify::SpaceBetween => {}
+            Justify::SpaceAround => {
+                spacing += free / n;
+                cursor = free / n * 0.5;
+            }
+        }
+    }
+
+    let mut rects = Vec::with_capacity(items.len());
+    for (size, item) in sizes.into_iter().zip(items) {
+        let available = inner.size[cross];
+        let extent = match flex.align {
+            Align::Stretch => available,
+            _ => (item.rect.size[cross] * scale).min(available),
+        };
+        let along = match flex.align {
+            Align::Start | Align::Stretch => 0.0,
+            Align::Center => (available - extent) * 0.5,
+            Align::End => available - extent,
+        };
+        let mut rect = Rect::default();
+        rect.position[main] = inner.position[main] + cursor;
+        rect.position[cross] = inner.position[cross] + along;
+        rect.size[main] = size;
+        rect.size[cross] = extent;
+        rects.push(rect);
+        cursor += size + spacing;
+    }
+    rects
+}
@@ -0,0 +1,120 @@
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::Mutex;
+use std::thread::spawn;
+
+use glfw::{Context, PWindow};
+use gom::*;
+use lazy_static::lazy_static;
+
+use crate::{debug, App, GpuFence, WINDOW};
+
+const UPLOAD_WINDOW: &str = id!(@WINDOW.UPLOAD_WINDOW);
+
+type UploadCommand = Box<dyn FnOnce() + Send>;
+
+lazy_static! {
+    static ref UPLOAD_COMMANDS: Mutex<Option<Sender<UploadCommand>>> = Mutex::new(None);
+}
+
+pub(crate) fn start_upload_thread(window: PWindow) {
+    Registry::register(UPLOAD_WINDOW, window).unwrap();
+    let (sender, receiver) = channel::<UploadCommand>();
+    spawn(move || {
+        App::set_current_thread_name("UploadThread");
+        Registry::apply(UPLOAD_WINDOW, |w: &mut PWindow| w.make_current());
+        debug!("Upload", "上传线程已启动");
+        for command in receiver {
+            command();
+        }
+        debug!("Upload", "上传线程退出");
+    });
+    *UPLOAD_COMMANDS.lock().unwrap() = Some(sender);
+}
+
+/// 上传任务的结果
+///
+/// 上传线程中的 GL 命令完成后结果才可用，此时创建的纹理、缓冲等对象可以在渲染线程中直接使用
+pub struct Upload<T> {
+    receiver: Receiver<(T, Option<GpuFence>)>,
+    pending: Option<(T, Option<GpuFence>)>,
+}
+
+impl<T> Upload<T> {
+    /// 尝试取出结果
+    ///
+    /// # 返回值
+    /// 上传完成且 GPU 已执行完相关命令时返回结果，否则返回`None`，结果只能取出一次
+    ///
+    /// # 注解
+    ///
+    /// 该函数只能在渲染线程中调用
+    pub fn try_take(&mut self) -> Option<T> {
+        if self.pending.is_none() {
+            self.pending = Some(self.receiver.try_recv().ok()?);
+        }
+        // 上传线程中执行的 GL 命令完成后栅栏才会触发，在渲染线程中执行时无需等待
+        if let Some(fence) = &self.pending.as_ref()?.1 {
+            if !fence.is_signaled() {
+                return None;
+            }
+        }
+        self.pending.take().map(|(value, _)| value)
+    }
+}
+
+impl App {
+    /// 提交上传任务
+    ///
+    /// 启用上传线程(见 [`AppBuilder::set_upload_thread`](crate::AppBuilder::set_upload_thread))时，
+    /// 任务在拥有共享上下文的上传线程中执行，完成后插入栅栏，大纹理、缓冲的上传与多级渐远纹理的生成不会阻塞渲染循环；
+    /// 否则任务经 [`App::run_on_render_thread`] 在渲染线程中执行
+    ///
+    /// # 参数
+    /// + `f` - 上传函数，可以创建纹理、缓冲等共享对象
+    ///
+    /// # 返回值
+    /// 返回上传结果，应在渲染线程中通过 [`Upload::try_take`] 取出
+    ///
+    /// # 示例
+    ///
+    /// ```no_run
+    /// use gle::*;
+    ///
+    /// let pixels = vec![255u8; 2048 * 2048 * 4];
+    /// let mut upload = App::upload(move || Texture2D::from_rgba8(2048, 2048, &pixels, true));
+    ///
+    /// // 在渲染循环中
+    /// if let Some(texture) = upload.try_take() {
+    ///     texture.bind(0);
+    /// }
+    /// ```
+    ///
+    /// # 注解
+    ///
+    /// 顶点数组对象与帧缓冲对象不在上下文之间共享，应在渲染线程中创建
+    pub fn upload<T, F>(f: F) -> Upload<T>
+    where
+        T: Send + 'static,
+        F: FnOnce() -> T + Send + 'static,
+    {
+        let (sender, receiver) = channel();
+        let upload_thread = UPLOAD_COMMANDS.lock().unwrap().clone();
+        match upload_thread {
+            Some(commands) => {
+                let _ = commands.send(Box::new(move || {
+                    let value = f();
+                    let fence = GpuFence::insert();
+                    unsafe { gl::Flush() };
+                    let _ = sender.send((value, Some(fence)));
+                }));
+            }
+            None => App::run_on_render_thread(move || {
+                let _ = sender.send((f(), None));
+            }),
+        }
+        Upload {
+            receiver,
+            pending: None,
+        }
+    }
+}
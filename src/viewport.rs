@@ -0,0 +1,164 @@
+use crate::math::*;
+use crate::{Camera, CameraTarget, RenderTargets, Renderer};
+
+/// 分屏布局
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SplitLayout {
+    /// 单个视口占满整个渲染目标
+    Single,
+    /// 从左到右等宽排列
+    Columns(u32),
+    /// 从上到下等高排列
+    Rows(u32),
+    /// 网格，从左上角开始按行排列
+    Grid {
+        /// 列数
+        columns: u32,
+        /// 行数
+        rows: u32,
+    },
+}
+
+impl SplitLayout {
+    /// 计算布局中第`index`个视口的归一化矩形
+    ///
+    /// # 返回值
+    /// 返回`(x, y, 宽度, 高度)`，以左下角为原点，可以直接赋给 [`Camera::viewport`]。
+    /// 超出布局格数的索引返回`None`
+    pub fn viewport(&self, index: usize) -> Option<Vec4> {
+        let (columns, rows) = match *self {
+            SplitLayout::Single => (1, 1),
+            SplitLayout::Columns(n) => (n.max(1), 1),
+            SplitLayout::Rows(n) => (1, n.max(1)),
+            SplitLayout::Grid { columns, rows } => (columns.max(1), rows.max(1)),
+        };
+        let (columns, rows) = (columns as usize, rows as usize);
+        if index >= columns * rows {
+            return None;
+        }
+        let (column, row) = (index % columns, index / columns);
+        let (w, h) = (1.0 / columns as f32, 1.0 / rows as f32);
+        Some(Vec4::new(column as f32 * w, 1.0 - (row + 1) as f32 * h, w, h))
+    }
+}
+
+/// 多视口渲染
+///
+/// 每个摄像机以 [`Camera::viewport`] 指定其在渲染目标中的区域，[`Viewports::render`]
+/// 依次为每个摄像机设置视口与裁剪矩形后调用绘制回调，用于本地多人分屏与编辑器的多窗格视图。
+/// 摄像机可以通过 [`Camera::target`] 绘制到不同的渲染目标
+///
+/// # 示例
+///
+/// ```no_run
+/// use gle::{*, math::*};
+///
+/// let player = Camera::perspective(60f32.to_radians(), 0.1, 500.0);
+/// let mut viewports = Viewports::new(vec![player; 2]);
+/// viewports.split(SplitLayout::Columns(2));
+///
+/// // 渲染线程中
+/// viewports.render(|camera, index| {
+///     // 以 camera 绘制场景，index 为玩家编号
+/// });
+/// ```
+///
+/// # 注解
+///
+/// 回调在 [`Renderer::with_scissor`] 中执行，自行绑定其他帧缓冲(如后处理)的回调应在绘制结束后重新调用
+/// [`RenderTargets::bind`]
+pub struct Viewports {
+    /// 按绘制顺序排列的摄像机，后绘制的视口覆盖先绘制的视口
+    pub cameras: Vec<Camera>,
+    /// 绘制前清除视口区域的颜色，为`None`时不清除(默认值为黑色)
+    pub clear_color: Option<Vec4>,
+}
+
+impl Viewports {
+    /// 创建多视口渲染
+    ///
+    /// # 参数
+    /// + `cameras` - 摄像机，按绘制顺序排列
+    pub fn new(cameras: Vec<Camera>) -> Self {
+        Self {
+            cameras,
+            clear_color: Some(Vec4::new(0.0, 0.0, 0.0, 1.0)),
+        }
+    }
+
+    /// 按布局依次设置摄像机的视口
+    ///
+    /// # 注解
+    ///
+    /// 超出布局格数的摄像机保持原视口不变
+    pub fn split(&mut self, layout: SplitLayout) {
+        for (index, camera) in self.cameras.iter_mut().enumerate() {
+            if let Some(viewport) = layout.viewport(index) {
+                camera.viewport = viewport;
+            }
+        }
+    }
+
+    /// 查找包含窗口中某点的视口
+    ///
+    /// # 参数
+    /// + `position` - 窗口坐标，以左上角为原点，与光标位置一致
+    /// + `window_size` - 窗口大小
+    ///
+    /// # 返回值
+    /// 返回最后绘制的、绘制到窗口且包含该点的摄像机索引，用于将输入分派到编辑器窗格
+    pub fn at(&self, position: Vec2, window_size: Vec2) -> Option<usize> {
+        if window_size.x <= 0.0 || window_size.y <= 0.0 {
+            return None;
+        }
+        let p = Vec2::new(position.x, window_size.y - position.y) / window_size;
+        self.cameras.iter().rposition(|camera| {
+            let v = camera.viewport;
+            camera.target == CameraTarget::Window
+                && p.x >= v.x
+                && p.y >= v.y
+                && p.x < v.x + v.z
+                && p.y < v.y + v.w
+        })
+    }
+
+    /// 依次绘制每个视口
+    ///
+    /// # 参数
+    /// + `draw` - 绘制回调，以摄像机与其索引调用，调用前已绑定渲染目标并设置视口与裁剪矩形
+    ///
+    /// # 注解
+    ///
+    /// 摄像机的宽高比被更新为其视口的宽高比。结束后恢复调用前的裁剪状态、帧缓冲与视口。
+    /// 该函数只能在渲染线程中调用
+    pub fn render(&mut self, mut draw: impl FnMut(&Camera, usize)) {
+        let mut previous = 0;
+        let mut viewport = [0; 4];
+        unsafe {
+            gl::GetIntegerv(gl::FRAMEBUFFER_BINDING, &mut previous);
+            gl::GetIntegerv(gl::VIEWPORT, viewport.as_mut_ptr());
+        }
+        for (index, camera) in self.cameras.iter_mut().enumerate() {
+            let (x, y, w, h) = RenderTargets::bind(camera);
+            if w <= 0 || h <= 0 {
+                continue;
+            }
+            let clear_color = self.clear_color;
+            Renderer::with_scissor((x, y, w, h), || {
+                if let Some(color) = clear_color {
+                    unsafe {
+                        gl::ClearColor(color.x, color.y, color.z, color.w);
+                        gl::Clear(
+                            gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT | gl::STENCIL_BUFFER_BIT,
+                        );
+                    }
+                }
+                draw(camera, index);
+            });
+        }
+        unsafe {
+            gl::BindFramebuffer(gl::FRAMEBUFFER, previous as u32);
+            gl::Viewport(viewport[0], viewport[1], viewport[2], viewport[3]);
+        }
+    }
+}
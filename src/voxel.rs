@@ -0,0 +1,566 @@
+use std::collections::{HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+
+use crate::math::*;
+
+/// 区块边长(方块数)
+pub const CHUNK_SIZE: i32 = 16;
+/// 区块中的方块数
+pub const CHUNK_VOLUME: usize = (CHUNK_SIZE * CHUNK_SIZE * CHUNK_SIZE) as usize;
+
+/// 方块类型标识，`0`为空气
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default, Serialize, Deserialize,
+)]
+#[serde(transparent)]
+pub struct BlockId(pub u16);
+
+impl BlockId {
+    /// 空气
+    pub const AIR: BlockId = BlockId(0);
+
+    /// 是否为空气
+    pub fn is_air(self) -> bool {
+        self == Self::AIR
+    }
+}
+
+/// 区块内方块的线性索引，按`x`、`z`、`y`的顺序由内到外排列
+#[inline]
+fn block_index(local: UVec3) -> usize {
+    debug_assert!(local.max_element() < CHUNK_SIZE as u32, "区块内坐标越界");
+    ((local.y as usize * CHUNK_SIZE as usize) + local.z as usize) * CHUNK_SIZE as usize
+        + local.x as usize
+}
+
+/// 16³ 的体素区块
+///
+/// 以调色板压缩存储：区块只保存其中出现过的方块类型列表，每个方块以位宽为 0、1、2、4、8 或 16 的调色板索引表示，
+/// 只含一种方块的区块不分配索引数据。调色板只增不减，大量修改后可调用 [`Chunk::compact`] 回收
+///
+/// # 示例
+///
+/// ```
+/// use gle::{*, math::*};
+///
+/// let mut chunk = Chunk::new();
+/// chunk.set(UVec3::new(1, 2, 3), BlockId(5));
+/// assert_eq!(chunk.get(UVec3::new(1, 2, 3)), BlockId(5));
+/// assert_eq!(chunk.get(UVec3::ZERO), BlockId::AIR);
+/// ```
+///
+/// # 注解
+///
+/// 序列化时直接保存调色板与压缩后的索引数据，反序列化时会校验数据长度
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(into = "ChunkRepr", try_from = "ChunkRepr")]
+pub struct Chunk {
+    palette: Vec<BlockId>,
+    bits: u32,
+    data: Vec<u64>,
+    solid: u32,
+}
+
+impl Default for Chunk {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Chunk {
+    /// 创建全部为空气的区块
+    pub fn new() -> Self {
+        Self::filled(BlockId::AIR)
+    }
+
+    /// 创建全部为指定方块的区块
+    pub fn filled(block: BlockId) -> Self {
+        Self {
+            palette: vec![block],
+            bits: 0,
+            data: Vec::new(),
+            solid: if block.is_air() { 0 } else { CHUNK_VOLUME as u32 },
+        }
+    }
+
+    /// 由按 [`Chunk::to_array`] 顺序排列的方块创建区块
+    ///
+    /// # 参数
+    /// + `blocks` - 方块，长度须为 [`CHUNK_VOLUME`]
+    pub fn from_array(blocks: &[BlockId]) -> Self {
+        assert_eq!(blocks.len(), CHUNK_VOLUME, "方块数量不匹配");
+        let mut palette: Vec<BlockId> = blocks.to_vec();
+        palette.sort_unstable();
+        palette.dedup();
+        let mut chunk = Self {
+            bits: bits_for(palette.len()),
+            palette,
+            data: Vec::new(),
+            solid: blocks.iter().filter(|b| !b.is_air()).count() as u32,
+        };
+        chunk.data = vec![0; words_for(chunk.bits)];
+        if chunk.bits > 0 {
+            for (i, block) in blocks.iter().enumerate() {
+                let index = chunk.palette.binary_search(block).unwrap_or(0);
+                chunk.write(i, index as u64);
+            }
+        }
+        chunk
+    }
+
+    /// 获取方块
+    ///
+    /// # 参数
+    /// + `local` - 区块内坐标，各分量须小于 [`CHUNK_SIZE`]
+    pub fn get(&self, local: UVec3) -> BlockId {
+        self.palette[self.read(block_index(local))]
+    }
+
+    /// 设置方块
+    ///
+    /// # 参数
+    /// + `local` - 区块内坐标，各分量须小于 [`CHUNK_SIZE`]
+    /// + `block` - 方块
+    ///
+    /// # 返回值
+    /// 返回原来的方块
+    pub fn set(&mut self, local: UVec3, block: BlockId) -> BlockId {
+        let i = block_index(local);
+        let previous = self.palette[self.read(i)];
+        if previous == block {
+            return previous;
+        }
+        let index = match self.palette.iter().position(|b| *b == block) {
+            Some(index) => index,
+            None => {
+                self.palette.push(block);
+                let bits = bits_for(self.palette.len());
+                if bits != self.bits {
+                    self.repack(bits);
+                }
+                self.palette.len() - 1
+            }
+        };
+        self.write(i, index as u64);
+        match (previous.is_air(), block.is_air()) {
+            (true, false) => self.solid += 1,
+            (false, true) => self.solid -= 1,
+            _ => {}
+        }
+        previous
+    }
+
+    /// 将所有方块设置为指定方块
+    pub fn fill(&mut self, block: BlockId) {
+        *self = Self::filled(block);
+    }
+
+    /// 是否全部为空气
+    pub fn is_empty(&self) -> bool {
+        self.solid == 0
+    }
+
+    /// 非空气方块的数量
+    pub fn solid_count(&self) -> usize {
+        self.solid as usize
+    }
+
+    /// 获取调色板，可能包含已不再使用的方块
+    pub fn palette(&self) -> &[BlockId] {
+        &self.palette
+    }
+
+    /// 每个方块索引的位宽
+    pub fn bits_per_block(&self) -> u32 {
+        self.bits
+    }
+
+    /// 解压为按`x`、`z`、`y`顺序由内到外排列的方块数组，便于批量读取
+    pub fn to_array(&self) -> Vec<BlockId> {
+        (0..CHUNK_VOLUME).map(|i| self.palette[self.read(i)]).collect()
+    }
+
+    /// 移除调色板中不再使用的方块并按需降低位宽
+    pub fn compact(&mut self) {
+        if self.bits > 0 {
+            *self = Self::from_array(&self.to_array());
+        }
+    }
+
+    #[inline]
+    fn read(&self, i: usize) -> usize {
+        if self.bits == 0 {
+            return 0;
+        }
+        let per_word = 64 / self.bits as usize;
+        let shift = (i % per_word) as u32 * self.bits;
+        ((self.data[i / per_word] >> shift) & ((1 << self.bits) - 1)) as usize
+    }
+
+    #[inline]
+    fn write(&mut self, i: usize, value: u64) {
+        if self.bits == 0 {
+            return;
+        }
+        let per_word = 64 / self.bits as usize;
+        let shift = (i % per_word) as u32 * self.bits;
+        let mask = ((1u64 << self.bits) - 1) << shift;
+        let word = &mut self.data[i / per_word];
+        *word = (*word & !mask) | (value << shift);
+    }
+
+    fn repack(&mut self, bits: u32) {
+        let indices: Vec<u64> = (0..CHUNK_VOLUME).map(|i| self.read(i) as u64).collect();
+        self.bits = bits;
+        self.data = vec![0; words_for(bits)];
+        for (i, index) in indices.into_iter().enumerate() {
+            self.write(i, index);
+        }
+    }
+}
+
+/// [`Chunk`] 的序列化形式，不含可由数据推导出的非空气方块计数
+#[derive(Serialize, Deserialize)]
+struct ChunkRepr {
+    palette: Vec<BlockId>,
+    bits: u32,
+    data: Vec<u64>,
+}
+
+impl From<Chunk> for ChunkRepr {
+    fn from(chunk: Chunk) -> Self {
+        Self {
+            palette: chunk.palette,
+            bits: chunk.bits,
+            data: chunk.data,
+        }
+    }
+}
+
+impl TryFrom<ChunkRepr> for Chunk {
+    type Error = String;
+
+    fn try_from(repr: ChunkRepr) -> Result<Self, String> {
+        if repr.palette.is_empty()
+            || repr.bits != bits_for(repr.palette.len())
+            || repr.data.len() != words_for(repr.bits)
+        {
+            return Err(format!(
+                "区块数据无效: 调色板 {} 项，位宽 {}，数据 {} 字",
+                repr.palette.len(),
+                repr.bits,
+                repr.data.len()
+            ));
+        }
+        let mut chunk = Self {
+            palette: repr.palette,
+            bits: repr.bits,
+            data: repr.data,
+            solid: 0,
+        };
+        let mut solid = 0;
+        for i in 0..CHUNK_VOLUME {
+            match chunk.palette.get(chunk.read(i)) {
+                Some(block) => solid += !block.is_air() as u32,
+                None => return Err("区块数据无效: 调色板索引越界".to_string()),
+            }
+        }
+        chunk.solid = solid;
+        Ok(chunk)
+    }
+}
+
+/// 容纳`len`个调色板项所需的位宽，取 2 的幂以使索引不跨越字边界
+fn bits_for(len: usize) -> u32 {
+    match len {
+        0 | 1 => 0,
+        2 => 1,
+        3..=4 => 2,
+        5..=16 => 4,
+        17..=256 => 8,
+        _ => 16,
+    }
+}
+
+fn words_for(bits: u32) -> usize {
+    if bits == 0 {
+        0
+    } else {
+        CHUNK_VOLUME / (64 / bits as usize)
+    }
+}
+
+/// 体素射线检测的命中结果
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct VoxelHit {
+    /// 命中方块的世界方块坐标
+    pub position: IVec3,
+    /// 命中的方块
+    pub block: BlockId,
+    /// 射线进入方块时穿过的面的朝外法线，射线起点位于方块内部时为零向量
+    pub normal: IVec3,
+    /// 与命中面相邻的方块坐标，即在该面上放置方块时的位置
+    pub place: IVec3,
+    /// 起点到命中点的距离
+    pub distance: f32,
+    /// 命中点的世界坐标
+    pub point: Vec3,
+}
+
+/// 体素世界
+///
+/// 以区块坐标索引的稀疏区块集合，提供以世界方块坐标读写方块的接口，并记录内容发生变化、需要重新生成网格的区块。
+/// 修改位于区块边界的方块时，相邻区块也会被标记，因为它们的面剔除结果可能随之改变
+///
+/// # 示例
+///
+/// ```
+/// use gle::{*, math::*};
+///
+/// let mut world = VoxelWorld::new();
+/// world.set_block(IVec3::new(-1, 5, 20), BlockId(1));
+/// assert_eq!(world.block(IVec3::new(-1, 5, 20)), BlockId(1));
+/// for coord in world.take_dirty() {
+///     // 重新生成区块网格……
+/// }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct VoxelWorld {
+    chunks: HashMap<IVec3, Chunk>,
+    dirty: HashSet<IVec3>,
+}
+
+impl VoxelWorld {
+    /// 创建空的体素世界
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 将世界方块坐标拆分为区块坐标与区块内坐标
+    pub fn split(position: IVec3) -> (IVec3, UVec3) {
+        let chunk = position.div_euclid(IVec3::splat(CHUNK_SIZE));
+        let local = position.rem_euclid(IVec3::splat(CHUNK_SIZE));
+        (chunk, local.as_uvec3())
+    }
+
+    /// 获取区块原点的世界方块坐标
+    pub fn chunk_origin(coord: IVec3) -> IVec3 {
+        coord * CHUNK_SIZE
+    }
+
+    /// 区块数量
+    pub fn len(&self) -> usize {
+        self.chunks.len()
+    }
+
+    /// 是否没有区块
+    pub fn is_empty(&self) -> bool {
+        self.chunks.is_empty()
+    }
+
+    /// 获取区块
+    pub fn chunk(&self, coord: IVec3) -> Option<&Chunk> {
+        self.chunks.get(&coord)
+    }
+
+    /// 获取区块的可变引用，通过该引用进行的修改不会被自动标记，需调用 [`VoxelWorld::mark_dirty`]
+    pub fn chunk_mut(&mut self, coord: IVec3) -> Option<&mut Chunk> {
+        self.chunks.get_mut(&coord)
+    }
+
+    /// 插入区块，该区块及其相邻区块被标记为需要更新
+    ///
+    /// # 返回值
+    /// 返回该坐标原有的区块
+    pub fn insert_chunk(&mut self, coord: IVec3, chunk: Chunk) -> Option<Chunk> {
+        self.mark_dirty_with_neighbors(coord);
+        self.chunks.insert(coord, chunk)
+    }
+
+    /// 移除区块，其相邻区块被标记为需要更新
+    pub fn remove_chunk(&mut self, coord: IVec3) -> Option<Chunk> {
+        let chunk = self.chunks.remove(&coord)?;
+        self.dirty.remove(&coord);
+        for offset in NEIGHBORS {
+            self.mark_dirty(coord + offset);
+        }
+        Some(chunk)
+    }
+
+    /// 遍历所有区块
+    pub fn chunks(&self) -> impl Iterator<Item = (IVec3, &Chunk)> {
+        self.chunks.iter().map(|(coord, chunk)| (*coord, chunk))
+    }
+
+    /// 获取方块，所在区块不存在时返回空气
+    ///
+    /// # 参数
+    /// + `position` - 世界方块坐标
+    pub fn block(&self, position: IVec3) -> BlockId {
+        let (coord, local) = Self::split(position);
+        self.chunks
+            .get(&coord)
+            .map_or(BlockId::AIR, |chunk| chunk.get(local))
+    }
+
+    /// 设置方块，所在区块不存在时自动创建
+    ///
+    /// # 参数
+    /// + `position` - 世界方块坐标
+    /// + `block` - 方块
+    ///
+    /// # 返回值
+    /// 返回原来的方块
+    pub fn set_block(&mut self, position: IVec3, block: BlockId) -> BlockId {
+        let (coord, local) = Self::split(position);
+        let chunk = self.chunks.entry(coord).or_default();
+        let previous = chunk.set(local, block);
+        if previous != block {
+            self.dirty.insert(coord);
+            let last = CHUNK_SIZE as u32 - 1;
+            for axis in 0..3 {
+                let offset = IVec3::AXES[axis];
+                if local[axis] == 0 {
+                    self.mark_dirty(coord - offset);
+                } else if local[axis] == last {
+                    self.mark_dirty(coord + offset);
+                }
+            }
+        }
+        previous
+    }
+
+    /// 沿射线查找第一个非空气方块
+    ///
+    /// # 参数
+    /// + `origin` - 射线起点的世界坐标
+    /// + `direction` - 射线方向，无需归一化
+    /// + `max_distance` - 最大检测距离
+    ///
+    /// # 返回值
+    /// 返回命中结果，范围内没有方块或方向为零向量时返回`None`
+    ///
+    /// # 示例
+    ///
+    /// ```
+    /// use gle::{*, math::*};
+    ///
+    /// let mut world = VoxelWorld::new();
+    /// world.set_block(IVec3::new(0, 0, -5), BlockId(1));
+    /// let hit = world.raycast(Vec3::new(0.5, 0.5, 0.5), Vec3::NEG_Z, 8.0).unwrap();
+    /// assert_eq!(hit.position, IVec3::new(0, 0, -5));
+    /// assert_eq!(hit.normal, IVec3::Z);
+    /// assert_eq!(hit.place, IVec3::new(0, 0, -4));
+    /// // 破坏方块
+    /// world.set_block(hit.position, BlockId::AIR);
+    /// ```
+    pub fn raycast(&self, origin: Vec3, direction: Vec3, max_distance: f32) -> Option<VoxelHit> {
+        self.raycast_by(origin, direction, max_distance, |block| !block.is_air())
+    }
+
+    /// 沿射线查找第一个满足条件的方块
+    ///
+    /// 以三维 DDA 逐个遍历射线穿过的方块，不会漏掉擦过棱边的方块
+    ///
+    /// # 参数
+    /// + `origin` - 射线起点的世界坐标
+    /// + `direction` - 射线方向，无需归一化
+    /// + `max_distance` - 最大检测距离
+    /// + `solid` - 判断方块能否被射线命中的函数，可用于忽略水、草等不可选中的方块
+    ///
+    /// # 返回值
+    /// 返回命中结果，范围内没有满足条件的方块或方向为零向量时返回`None`
+    pub fn raycast_by<F: Fn(BlockId) -> bool>(
+        &self,
+        origin: Vec3,
+        direction: Vec3,
+        max_distance: f32,
+        solid: F,
+    ) -> Option<VoxelHit> {
+        let direction = direction.normalize_or_zero();
+        if direction == Vec3::ZERO {
+            return None;
+        }
+        let mut position = origin.floor().as_ivec3();
+        let step = IVec3::new(
+            (direction.x > 0.0) as i32 - (direction.x < 0.0) as i32,
+            (direction.y > 0.0) as i32 - (direction.y < 0.0) as i32,
+            (direction.z > 0.0) as i32 - (direction.z < 0.0) as i32,
+        );
+        let t_delta = direction.recip().abs();
+        // 到达各轴下一个方块边界时的射线参数
+        let mut t_max = Vec3::ZERO;
+        for axis in 0..3 {
+            let cell = position[axis] as f32;
+            t_max[axis] = match step[axis] {
+                1 => (cell + 1.0 - origin[axis]) * t_delta[axis],
+                -1 => (origin[axis] - cell) * t_delta[axis],
+                _ => f32::INFINITY,
+            };
+        }
+        let mut normal = IVec3::ZERO;
+        let mut distance = 0.0;
+        loop {
+            let block = self.block(position);
+            if solid(block) {
+                return Some(VoxelHit {
+                    position,
+                    block,
+                    normal,
+                    place: position + normal,
+                    distance,
+                    point: origin + direction * distance,
+                });
+            }
+            let axis = if t_max.x < t_max.y {
+                if t_max.x < t_max.z { 0 } else { 2 }
+            } else if t_max.y < t_max.z {
+                1
+            } else {
+                2
+            };
+            distance = t_max[axis];
+            if distance > max_distance {
+                return None;
+            }
+            position[axis] += step[axis];
+            t_max[axis] += t_delta[axis];
+            normal = IVec3::ZERO;
+            normal[axis] = -step[axis];
+        }
+    }
+
+    /// 将区块标记为需要更新，区块不存在时忽略
+    pub fn mark_dirty(&mut self, coord: IVec3) {
+        if self.chunks.contains_key(&coord) {
+            self.dirty.insert(coord);
+        }
+    }
+
+    /// 区块是否需要更新
+    pub fn is_dirty(&self, coord: IVec3) -> bool {
+        self.dirty.contains(&coord)
+    }
+
+    /// 取出并清空所有需要更新的区块坐标
+    pub fn take_dirty(&mut self) -> Vec<IVec3> {
+        self.dirty.drain().collect()
+    }
+
+    fn mark_dirty_with_neighbors(&mut self, coord: IVec3) {
+        self.dirty.insert(coord);
+        for offset in NEIGHBORS {
+            self.mark_dirty(coord + offset);
+        }
+    }
+}
+
+const NEIGHBORS: [IVec3; 6] = [
+    IVec3::X,
+    IVec3::NEG_X,
+    IVec3::Y,
+    IVec3::NEG_Y,
+    IVec3::Z,
+    IVec3::NEG_Z,
+];
@@ -0,0 +1,535 @@
+use serde::{Deserialize, Serialize};
+
+use crate::math::*;
+use crate::{
+    BlockId, GlObjectKind, GlObjects, RenderStats, VoxelWorld, ATTRIB_NORMAL, ATTRIB_POSITION,
+    ATTRIB_UV, CHUNK_SIZE,
+};
+
+/// [`GpuVoxelMesh`] 的顶点属性位置：纹理层序号
+pub const ATTRIB_VOXEL_TEXTURE: u32 = 3;
+/// [`GpuVoxelMesh`] 的顶点属性位置：环境光遮蔽
+pub const ATTRIB_VOXEL_AO: u32 = 4;
+/// [`GpuVoxelMesh`] 的顶点属性位置：自发光强度
+pub const ATTRIB_VOXEL_EMISSION: u32 = 5;
+
+const S: i32 = CHUNK_SIZE;
+const PADDED: i32 = CHUNK_SIZE + 2;
+
+/// 方块的六个面
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Face {
+    /// `+X`
+    PosX,
+    /// `-X`
+    NegX,
+    /// `+Y`，顶面
+    PosY,
+    /// `-Y`，底面
+    NegY,
+    /// `+Z`
+    PosZ,
+    /// `-Z`
+    NegZ,
+}
+
+impl Face {
+    /// 所有面
+    pub const ALL: [Face; 6] = [
+        Face::PosX,
+        Face::NegX,
+        Face::PosY,
+        Face::NegY,
+        Face::PosZ,
+        Face::NegZ,
+    ];
+
+    /// 由坐标轴(`0`为 X，`1`为 Y，`2`为 Z)与方向获取面
+    pub fn from_axis(axis: usize, positive: bool) -> Self {
+        match (axis, positive) {
+            (0, true) => Face::PosX,
+            (0, false) => Face::NegX,
+            (1, true) => Face::PosY,
+            (1, false) => Face::NegY,
+            (2, true) => Face::PosZ,
+            _ => Face::NegZ,
+        }
+    }
+
+    /// 获取面的法线所在坐标轴
+    pub fn axis(self) -> usize {
+        match self {
+            Face::PosX | Face::NegX => 0,
+            Face::PosY | Face::NegY => 1,
+            Face::PosZ | Face::NegZ => 2,
+        }
+    }
+
+    /// 获取面的朝外法线
+    pub fn normal(self) -> IVec3 {
+        match self {
+            Face::PosX => IVec3::X,
+            Face::NegX => IVec3::NEG_X,
+            Face::PosY => IVec3::Y,
+            Face::NegY => IVec3::NEG_Y,
+            Face::PosZ => IVec3::Z,
+            Face::NegZ => IVec3::NEG_Z,
+        }
+    }
+}
+
+/// 方块外观，决定面剔除与每个面使用的纹理
+///
+/// 对于`Fn(BlockId, Face) -> u32`闭包，所有非空气方块均视为不透明，闭包返回面的纹理层序号
+pub trait BlockAppearance {
+    /// 方块是否完全遮挡相邻方块的面
+    fn is_opaque(&self, block: BlockId) -> bool;
+
+    /// 获取方块指定面的纹理层序号
+    fn face_texture(&self, block: BlockId, face: Face) -> u32;
+
+    /// 获取方块的发光等级，范围为`[0, 15]`
+    fn light_emission(&self, _block: BlockId) -> u8 {
+        0
+    }
+}
+
+impl<F: Fn(BlockId, Face) -> u32> BlockAppearance for F {
+    fn is_opaque(&self, block: BlockId) -> bool {
+        !block.is_air()
+    }
+
+    fn face_texture(&self, block: BlockId, face: Face) -> u32 {
+        self(block, face)
+    }
+}
+
+/// 区块及其周围一圈方块的快照
+///
+/// 生成网格时需要读取相邻区块边界上的方块以进行面剔除与环境光遮蔽计算。
+/// 快照与世界无关，可以发送到其他线程生成网格
+#[derive(Debug, Clone)]
+pub struct ChunkNeighborhood {
+    blocks: Vec<BlockId>,
+}
+
+impl ChunkNeighborhood {
+    /// 从世界中复制区块及其周围一圈方块，不存在的区块视为空气
+    ///
+    /// # 参数
+    /// + `world` - 体素世界
+    /// + `coord` - 区块坐标
+    pub fn from_world(world: &VoxelWorld, coord: IVec3) -> Self {
+        let origin = VoxelWorld::chunk_origin(coord);
+        let mut blocks = vec![BlockId::AIR; (PADDED * PADDED * PADDED) as usize];
+        let center = world.chunk(coord).map(|chunk| chunk.to_array());
+        for y in -1..=S {
+            for z in -1..=S {
+                for x in -1..=S {
+                    let p = IVec3::new(x, y, z);
+                    let inside = p.cmpge(IVec3::ZERO).all() && p.cmplt(IVec3::splat(S)).all();
+                    let block = match &center {
+                        Some(center) if inside => center[((y * S + z) * S + x) as usize],
+                        None if inside => BlockId::AIR,
+                        _ => world.block(origin + p),
+                    };
+                    blocks[Self::index(p)] = block;
+                }
+            }
+        }
+        Self { blocks }
+    }
+
+    #[inline]
+    fn index(p: IVec3) -> usize {
+        let p = p + 1;
+        ((p.y * PADDED + p.z) * PADDED + p.x) as usize
+    }
+
+    /// 获取方块
+    ///
+    /// # 参数
+    /// + `p` - 相对区块原点的坐标，各分量范围为`[-1, CHUNK_SIZE]`
+    pub fn get(&self, p: IVec3) -> BlockId {
+        self.blocks[Self::index(p)]
+    }
+}
+
+/// 体素网格的顶点
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct VoxelVertex {
+    /// 相对区块原点的位置
+    pub position: Vec3,
+    /// 法线
+    pub normal: Vec3,
+    /// 纹理坐标，以方块为单位，合并后的面跨越多个单位，需以重复方式采样
+    pub uv: Vec2,
+    /// 纹理层序号
+    pub texture: f32,
+    /// 环境光遮蔽，`0`为完全遮蔽，`1`为无遮蔽
+    pub ao: f32,
+    /// 自发光强度，范围为`[0, 1]`
+    pub emission: f32,
+}
+
+/// 体素网格数据
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct VoxelMesh {
+    /// 顶点
+    pub vertices: Vec<VoxelVertex>,
+    /// 三角形索引
+    pub indices: Vec<u32>,
+}
+
+impl VoxelMesh {
+    /// 是否不含任何三角形
+    pub fn is_empty(&self) -> bool {
+        self.indices.is_empty()
+    }
+
+    /// 获取面(四边形)的数量
+    pub fn quad_count(&self) -> usize {
+        self.indices.len() / 6
+    }
+
+    /// 将网格上传到 GPU
+    ///
+    /// # 返回值
+    /// 返回 GPU 网格，其顶点属性位置为：
+    /// + `0` - 位置`vec3`
+    /// + `1` - 法线`vec3`
+    /// + `2` - 纹理坐标`vec2`
+    /// + `3` - 纹理层序号`float`
+    /// + `4` - 环境光遮蔽`float`
+    /// + `5` - 自发光强度`float`
+    ///
+    /// # 注解
+    ///
+    /// 只能在渲染线程中调用
+    pub fn upload(&self) -> GpuVoxelMesh {
+        let (mut vao, mut vbo, mut ebo) = (0, 0, 0);
+        let stride = std::mem::size_of::<VoxelVertex>() as i32;
+        let float = std::mem::size_of::<f32>();
+        unsafe {
+            gl::GenVertexArrays(1, &mut vao);
+            gl::GenBuffers(1, &mut vbo);
+            gl::GenBuffers(1, &mut ebo);
+            gl::BindVertexArray(vao);
+            gl::BindBuffer(gl::ARRAY_BUFFER, vbo);
+            gl::BindBuffer(gl::ELEMENT_ARRAY_BUFFER, ebo);
+            for (attrib, size, offset) in [
+                (ATTRIB_POSITION, 3, 0),
+                (ATTRIB_NORMAL, 3, 3),
+                (ATTRIB_UV, 2, 6),
+                (ATTRIB_VOXEL_TEXTURE, 1, 8),
+                (ATTRIB_VOXEL_AO, 1, 9),
+                (ATTRIB_VOXEL_EMISSION, 1, 10),
+            ] {
+                gl::EnableVertexAttribArray(attrib);
+                gl::VertexAttribPointer(
+                    attrib,
+                    size,
+                    gl::FLOAT,
+                    gl::FALSE,
+                    stride,
+                    (offset * float) as *const _,
+                );
+            }
+            gl::BindVertexArray(0);
+        }
+        GlObjects::track(GlObjectKind::VertexArray, vao);
+        GlObjects::track(GlObjectKind::Buffer, vbo);
+        GlObjects::track(GlObjectKind::Buffer, ebo);
+        let mut mesh = GpuVoxelMesh {
+            vao,
+            vbo,
+            ebo,
+            index_count: 0,
+            bytes: 0,
+        };
+        mesh.update(self);
+        mesh
+    }
+}
+
+/// 已上传到 GPU 的体素网格
+///
+/// 在被释放时自动删除其顶点数组对象与缓冲对象，因此只能在渲染线程中被释放
+#[derive(Debug)]
+pub struct GpuVoxelMesh {
+    vao: u32,
+    vbo: u32,
+    ebo: u32,
+    index_count: i32,
+    bytes: i64,
+}
+
+impl GpuVoxelMesh {
+    /// 获取顶点数组对象ID
+    pub fn vao(&self) -> u32 {
+        self.vao
+    }
+
+    /// 获取索引数量
+    pub fn index_count(&self) -> i32 {
+        self.index_count
+    }
+
+    /// 以新的网格数据替换缓冲内容，复用已有的缓冲对象
+    pub fn update(&mut self, mesh: &VoxelMesh) {
+        let vertex_bytes = std::mem::size_of_val(mesh.vertices.as_slice());
+        let index_bytes = std::mem::size_of_val(mesh.indices.as_slice());
+        unsafe {
+            gl::BindVertexArray(self.vao);
+            gl::BindBuffer(gl::ARRAY_BUFFER, self.vbo);
+            gl::BufferData(
+                gl::ARRAY_BUFFER,
+                vertex_bytes as isize,
+                mesh.vertices.as_ptr() as *const _,
+                gl::STATIC_DRAW,
+            );
+            gl::BufferData(
+                gl::ELEMENT_ARRAY_BUFFER,
+                index_bytes as isize,
+                mesh.indices.as_ptr() as *const _,
+                gl::STATIC_DRAW,
+            );
+            gl::BindVertexArray(0);
+        }
+        let bytes = (vertex_bytes + index_bytes) as i64;
+        RenderStats::_track_buffer(bytes - self.bytes);
+        self.bytes = bytes;
+        self.index_count = mesh.indices.len() as i32;
+    }
+
+    /// 绘制网格
+    pub fn draw(&self) {
+        if self.index_count == 0 {
+            return;
+        }
+        unsafe {
+            gl::BindVertexArray(self.vao);
+            gl::DrawElements(gl::TRIANGLES, self.index_count, gl::UNSIGNED_INT, std::ptr::null());
+            RenderStats::record_draw(self.index_count as u64 / 3);
+            gl::BindVertexArray(0);
+        }
+    }
+}
+
+impl Drop for GpuVoxelMesh {
+    fn drop(&mut self) {
+        RenderStats::_track_buffer(-self.bytes);
+        GlObjects::untrack(GlObjectKind::VertexArray, self.vao);
+        GlObjects::untrack(GlObjectKind::Buffer, self.vbo);
+        GlObjects::untrack(GlObjectKind::Buffer, self.ebo);
+        unsafe {
+            gl::DeleteVertexArrays(1, &self.vao);
+            gl::DeleteBuffers(1, &self.vbo);
+            gl::DeleteBuffers(1, &self.ebo);
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct FaceKey {
+    texture: u32,
+    ao: [u8; 4],
+    emission: u8,
+}
+
+/// 体素网格生成器
+///
+/// 剔除被不透明方块遮挡的面，再将同一平面上纹理、发光与环境光遮蔽均相同的相邻面贪心合并为尽量大的矩形，
+/// 大幅减少地形等大片平面的三角形数量。每个顶点带有由相邻方块计算的环境光遮蔽值，
+/// 并按遮蔽值选择四边形的对角线方向以避免插值各向异性
+///
+/// # 示例
+///
+/// ```
+/// use gle::{*, math::*};
+///
+/// let mut world = VoxelWorld::new();
+/// for x in 0..16 {
+///     for z in 0..16 {
+///         world.set_block(IVec3::new(x, 0, z), BlockId(1));
+///     }
+/// }
+/// let mut mesher = VoxelMesher::new();
+/// let neighborhood = ChunkNeighborhood::from_world(&world, IVec3::ZERO);
+/// let mesh = mesher.mesh(&neighborhood, &|_block: BlockId, _face: Face| 0u32);
+/// // 整层地面合并后只剩 6 个面
+/// assert_eq!(mesh.quad_count(), 6);
+/// ```
+#[derive(Debug, Clone)]
+pub struct VoxelMesher {
+    /// 是否合并相邻面，关闭时每个可见面单独输出，便于调试
+    pub greedy: bool,
+    mask: Vec<Option<FaceKey>>,
+}
+
+impl Default for VoxelMesher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl VoxelMesher {
+    /// 创建体素网格生成器
+    pub fn new() -> Self {
+        Self {
+            greedy: true,
+            mask: vec![None; (S * S) as usize],
+        }
+    }
+
+    /// 生成区块网格
+    ///
+    /// # 参数
+    /// + `neighborhood` - 区块及其周围方块的快照
+    /// + `appearance` - 方块外观
+    ///
+    /// # 返回值
+    /// 返回顶点位置相对区块原点的网格
+    pub fn mesh<A: BlockAppearance + ?Sized>(
+        &mut self,
+        neighborhood: &ChunkNeighborhood,
+        appearance: &A,
+    ) -> VoxelMesh {
+        let mut mesh = VoxelMesh::default();
+        for face in Face::ALL {
+            let d = face.axis();
+            let (u, v) = ((d + 1) % 3, (d + 2) % 3);
+            let normal = face.normal();
+            for slice in 0..S {
+                self.build_mask(neighborhood, appearance, face, slice);
+                for j in 0..S {
+                    let mut i = 0;
+                    while i < S {
+                        let Some(key) = self.mask[(j * S + i) as usize] else {
+                            i += 1;
+                            continue;
+                        };
+                        let (w, h) = self.extent(key, i, j);
+                        for y in j..j + h {
+                            for x in i..i + w {
+                                self.mask[(y * S + x) as usize] = None;
+                            }
+                        }
+                        let mut corners = [IVec3::ZERO; 4];
+                        for (corner, (cu, cv)) in corners
+                            .iter_mut()
+                            .zip([(i, j), (i + w, j), (i + w, j + h), (i, j + h)])
+                        {
+                            corner[d] = slice + normal[d].max(0);
+                            corner[u] = cu;
+                            corner[v] = cv;
+                        }
+                        emit_quad(&mut mesh, face, corners, key);
+                        i += w;
+                    }
+                }
+            }
+        }
+        mesh
+    }
+
+    fn build_mask<A: BlockAppearance + ?Sized>(
+        &mut self,
+        neighborhood: &ChunkNeighborhood,
+        appearance: &A,
+        face: Face,
+        slice: i32,
+    ) {
+        let d = face.axis();
+        let (u, v) = ((d + 1) % 3, (d + 2) % 3);
+        let normal = face.normal();
+        let opaque = |p: IVec3| appearance.is_opaque(neighborhood.get(p)) as u8;
+        for j in 0..S {
+            for i in 0..S {
+                let mut p = IVec3::ZERO;
+                p[d] = slice;
+                p[u] = i;
+                p[v] = j;
+                let block = neighborhood.get(p);
+                let front = p + normal;
+                let neighbor = neighborhood.get(front);
+                let visible =
+                    !block.is_air() && !appearance.is_opaque(neighbor) && neighbor != block;
+                self.mask[(j * S + i) as usize] = visible.then(|| {
+                    let (du, dv) = (IVec3::AXES[u], IVec3::AXES[v]);
+                    let mut ao = [0; 4];
+                    let offsets = [(-1, -1), (1, -1), (1, 1), (-1, 1)];
+                    for (value, (su, sv)) in ao.iter_mut().zip(offsets) {
+                        let side1 = opaque(front + du * su);
+                        let side2 = opaque(front + dv * sv);
+                        let corner = opaque(front + du * su + dv * sv);
+                        *value = if side1 + side2 == 2 {
+                            0
+                        } else {
+                            3 - side1 - side2 - corner
+                        };
+                    }
+                    FaceKey {
+                        texture: appearance.face_texture(block, face),
+                        ao,
+                        emission: appearance.light_emission(block).min(15),
+                    }
+                });
+            }
+        }
+    }
+
+    /// 以`(i, j)`为起点向`u`、`v`方向扩展的最大矩形
+    fn extent(&self, key: FaceKey, i: i32, j: i32) -> (i32, i32) {
+        if !self.greedy {
+            return (1, 1);
+        }
+        let same = |x: i32, y: i32| self.mask[(y * S + x) as usize] == Some(key);
+        let mut w = 1;
+        while i + w < S && same(i + w, j) {
+            w += 1;
+        }
+        let mut h = 1;
+        while j + h < S && (i..i + w).all(|x| same(x, j + h)) {
+            h += 1;
+        }
+        (w, h)
+    }
+}
+
+fn emit_quad(mesh: &mut VoxelMesh, face: Face, corners: [IVec3; 4], key: FaceKey) {
+    // 角点在切平面内按逆时针排列，负方向的面需反转环绕方向
+    let order = if face.normal()[face.axis()] > 0 {
+        [0, 1, 2, 3]
+    } else {
+        [0, 3, 2, 1]
+    };
+    let normal = face.normal().as_vec3();
+    let base = mesh.vertices.len() as u32;
+    for k in order {
+        let position = corners[k].as_vec3();
+        // 侧面的纹理纵向始终对齐世界 Y 轴
+        let uv = match face.axis() {
+            0 => Vec2::new(position.z, position.y),
+            1 => Vec2::new(position.x, position.z),
+            _ => Vec2::new(position.x, position.y),
+        };
+        mesh.vertices.push(VoxelVertex {
+            position,
+            normal,
+            uv,
+            texture: key.texture as f32,
+            ao: key.ao[k] as f32 / 3.0,
+            emission: key.emission as f32 / 15.0,
+        });
+    }
+    let ao = order.map(|k| key.ao[k]);
+    let quad = if ao[0] as u32 + ao[2] as u32 >= ao[1] as u32 + ao[3] as u32 {
+        [0, 1, 2, 0, 2, 3]
+    } else {
+        [1, 2, 3, 1, 3, 0]
+    };
+    mesh.indices.extend(quad.iter().map(|k| base + k));
+}
@@ -0,0 +1,154 @@
+use crate::error::Result;
+use crate::{
+    Camera, ChunkMeshes, Program, TextureArray, FOG_BINDING, FOG_GLSL, LIGHTING_GLSL,
+    LIGHTS_BINDING, PBR_BRDF_GLSL, SHADOWS_BINDING, SHADOW_TEXTURE_UNIT,
+};
+
+const VOXEL_VS: &str = r#"
+#version 330 core
+layout (location = 0) in vec3 aPosition;
+layout (location = 1) in vec3 aNormal;
+layout (location = 2) in vec2 aUV;
+layout (location = 3) in float aTexture;
+layout (location = 4) in float aAO;
+layout (location = 5) in float aEmission;
+
+uniform mat4 uModel;
+uniform mat4 uViewProj;
+
+out vec3 vWorldPos;
+out vec3 vNormal;
+out vec2 vUV;
+flat out float vLayer;
+out float vAO;
+out float vEmission;
+
+void main()
+{
+    vec4 world = uModel * vec4(aPosition, 1.0);
+    vWorldPos = world.xyz;
+    vNormal = aNormal;
+    vUV = aUV;
+    vLayer = aTexture;
+    vAO = aAO;
+    vEmission = aEmission;
+    gl_Position = uViewProj * world;
+}
+"#;
+
+const VOXEL_FS_HEAD: &str = r#"
+#version 330 core
+in vec3 vWorldPos;
+in vec3 vNormal;
+in vec2 vUV;
+flat in float vLayer;
+in float vAO;
+in float vEmission;
+out vec4 FragColor;
+
+uniform sampler2DArray uBlocks;
+uniform vec3 uCameraPos;
+uniform float uAlphaCutoff;
+uniform float uRoughness;
+uniform float uEmissionStrength;
+"#;
+
+const VOXEL_FS_MAIN: &str = r#"
+void main()
+{
+    vec4 albedo = texture(uBlocks, vec3(vUV, vLayer));
+    if (albedo.a < uAlphaCutoff) {
+        discard;
+    }
+    vec3 N = normalize(vNormal);
+    vec3 V = normalize(uCameraPos - vWorldPos);
+    vec3 color = albedo.rgb * uAmbient.rgb * vAO;
+    color += evaluateLights(N, V, vWorldPos, albedo.rgb, 0.0, uRoughness, vec3(0.04));
+    color += albedo.rgb * vEmission * uEmissionStrength;
+    color = applyFog(color, vWorldPos, uCameraPos);
+    FragColor = vec4(color, 1.0);
+}
+"#;
+
+/// 体素渲染器
+///
+/// 以 [`BlockRegistry`](crate::BlockRegistry) 生成的纹理数组绘制 [`ChunkMeshes`] 中的区块网格。
+/// 环境光按顶点的环境光遮蔽衰减，直接光照使用当前绑定的光源与阴影，自发光方块按发光等级叠加自身颜色，
+/// 纹理 alpha 低于 [`VoxelRenderer::alpha_cutoff`] 的像素被丢弃，用于树叶、玻璃等镂空方块
+///
+/// # 示例
+///
+/// ```
+/// use gle::*;
+///
+/// fn render_init(registry: &BlockRegistry) -> (VoxelRenderer, TextureArray) {
+///     let textures = registry.load_textures("assets/blocks", true).unwrap();
+///     (VoxelRenderer::new().unwrap(), textures)
+/// }
+///
+/// fn render_loop(renderer: &VoxelRenderer, textures: &TextureArray, camera: &Camera, meshes: &ChunkMeshes) {
+///     renderer.draw(camera, meshes, textures);
+/// }
+/// ```
+///
+/// # 注解
+///
+/// 绘制使用当前绑定的光源、阴影与雾 uniform 缓冲。该类型只能在渲染线程中创建、使用与释放
+pub struct VoxelRenderer {
+    /// alpha 镂空阈值
+    pub alpha_cutoff: f32,
+    /// 计算高光时使用的粗糙度
+    pub roughness: f32,
+    /// 发光等级为 15 时自发光相对纹理颜色的强度
+    pub emission_strength: f32,
+    program: Program,
+}
+
+impl VoxelRenderer {
+    /// 创建体素渲染器
+    pub fn new() -> Result<Self> {
+        let fs = format!(
+            "{}{}{}{}{}",
+            VOXEL_FS_HEAD, PBR_BRDF_GLSL, LIGHTING_GLSL, FOG_GLSL, VOXEL_FS_MAIN
+        );
+        let program = Program::new(VOXEL_VS, &fs)?;
+        program.set_block_binding("Lights", LIGHTS_BINDING);
+        program.set_block_binding("Shadows", SHADOWS_BINDING);
+        program.set_block_binding("Fog", FOG_BINDING);
+        program.bind();
+        program.set("uShadowMap", &(SHADOW_TEXTURE_UNIT as i32));
+        program.set("uBlocks", &0);
+        Ok(Self {
+            alpha_cutoff: 0.5,
+            roughness: 0.9,
+            emission_strength: 2.0,
+            program,
+        })
+    }
+
+    /// 获取着色器程序
+    pub fn program(&self) -> &Program {
+        &self.program
+    }
+
+    /// 绘制所有区块
+    ///
+    /// # 参数
+    /// + `camera` - 摄像机
+    /// + `meshes` - 区块网格集合
+    /// + `textures` - 方块纹理数组，层序号应与生成网格时使用的方块外观一致
+    pub fn draw(&self, camera: &Camera, meshes: &ChunkMeshes, textures: &TextureArray) {
+        self.program.bind();
+        self.program.set("uViewProj", &camera.view_projection());
+        self.program.set("uCameraPos", &camera.position);
+        self.program.set("uAlphaCutoff", &self.alpha_cutoff);
+        self.program.set("uRoughness", &self.roughness);
+        self.program.set("uEmissionStrength", &self.emission_strength);
+        textures.bind(0);
+        unsafe {
+            gl::Disable(gl::BLEND);
+            gl::Enable(gl::CULL_FACE);
+        }
+        meshes.draw(&self.program);
+    }
+}
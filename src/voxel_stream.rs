@@ -0,0 +1,304 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+
+use crate::math::*;
+use crate::{
+    App, BlockAppearance, Chunk, ChunkNeighborhood, GpuVoxelMesh, JobPool, Program, VoxelMesh,
+    VoxelMesher, VoxelWorld,
+};
+
+enum StreamResult {
+    Generated(IVec3, Chunk),
+    Meshed(IVec3, u64, VoxelMesh),
+}
+
+/// 已上传到 GPU 的区块网格集合
+///
+/// 由 [`ChunkStreamer`] 通过渲染线程命令维护，可克隆后交给渲染循环绘制
+///
+/// # 注解
+///
+/// 区块网格只能在渲染线程中绘制
+#[derive(Clone, Default)]
+pub struct ChunkMeshes {
+    meshes: Arc<Mutex<HashMap<IVec3, GpuVoxelMesh>>>,
+}
+
+impl ChunkMeshes {
+    /// 已上传的区块网格数量
+    pub fn len(&self) -> usize {
+        self.meshes.lock().unwrap().len()
+    }
+
+    /// 是否没有区块网格
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// 遍历所有区块网格
+    ///
+    /// # 参数
+    /// + `f` - 以区块坐标与网格调用的函数
+    pub fn for_each<F: FnMut(IVec3, &GpuVoxelMesh)>(&self, mut f: F) {
+        for (coord, mesh) in self.meshes.lock().unwrap().iter() {
+            f(*coord, mesh);
+        }
+    }
+
+    /// 以已绑定的着色器程序绘制所有区块，每个区块的平移矩阵通过`uModel`传入
+    pub fn draw(&self, program: &Program) {
+        self.for_each(|coord, mesh| {
+            let origin = VoxelWorld::chunk_origin(coord).as_vec3();
+            program.set("uModel", &Mat4::from_translation(origin));
+            mesh.draw();
+        });
+    }
+}
+
+/// 区块流式加载器
+///
+/// 以摄像机为中心，在后台线程中生成视距内缺失的区块并为内容变化的区块重新生成网格，
+/// 生成的网格通过 [`App::run_on_render_thread`] 在渲染线程中上传，超出视距的区块被卸载。
+/// 渲染循环只需绘制 [`ChunkStreamer::meshes`]，不再承担任何耗时工作
+///
+/// # 示例
+///
+/// ```
+/// use gle::{*, math::*};
+///
+/// let noise = Fbm::new(Simplex::new(1), 4).with_frequency(0.01);
+/// let mut streamer = ChunkStreamer::new(
+///     move |coord: IVec3| {
+///         let mut chunk = Chunk::new();
+///         let origin = VoxelWorld::chunk_origin(coord);
+///         for x in 0..CHUNK_SIZE {
+///             for z in 0..CHUNK_SIZE {
+///                 let p = origin + IVec3::new(x, 0, z);
+///                 let height = (noise.noise2(p.xz().as_vec2()) * 24.0) as i32;
+///                 for y in 0..CHUNK_SIZE {
+///                     if origin.y + y <= height {
+///                         chunk.set(UVec3::new(x as u32, y as u32, z as u32), BlockId(1));
+///                     }
+///                 }
+///             }
+///         }
+///         chunk
+///     },
+///     |_block: BlockId, _face: Face| 0u32,
+/// );
+/// let meshes = streamer.meshes();
+///
+/// // 在逻辑线程中每帧
+/// streamer.update(Vec3::new(0.0, 10.0, 0.0));
+/// // 在渲染线程中
+/// meshes.draw(&program);
+/// ```
+///
+/// # 注解
+///
+/// 区块被修改后(通过 [`ChunkStreamer::world_mut`])会在下一次 [`ChunkStreamer::update`] 时重新生成网格。
+/// 网格的释放同样经由渲染线程命令完成，因此卸载后显存在渲染线程执行命令后才被回收
+pub struct ChunkStreamer {
+    /// 水平视距，单位为区块
+    pub view_distance: i32,
+    /// 竖直视距，单位为区块
+    pub vertical_distance: i32,
+    /// 卸载距离相对视距的余量，避免在边界附近反复加载与卸载
+    pub unload_margin: i32,
+    /// 同时进行的区块生成任务上限
+    pub max_generating: usize,
+    world: VoxelWorld,
+    pool: Arc<JobPool>,
+    generator: Arc<dyn Fn(IVec3) -> Chunk + Send + Sync>,
+    appearance: Arc<dyn BlockAppearance + Send + Sync>,
+    sender: Sender<StreamResult>,
+    receiver: Receiver<StreamResult>,
+    generating: HashSet<IVec3>,
+    revisions: HashMap<IVec3, u64>,
+    next_revision: u64,
+    meshes: ChunkMeshes,
+}
+
+impl ChunkStreamer {
+    /// 创建区块流式加载器，使用新建的默认线程池
+    ///
+    /// # 参数
+    /// + `generator` - 区块生成函数，在工作线程中以区块坐标调用
+    /// + `appearance` - 方块外观，用于生成网格
+    pub fn new<G, A>(generator: G, appearance: A) -> Self
+    where
+        G: Fn(IVec3) -> Chunk + Send + Sync + 'static,
+        A: BlockAppearance + Send + Sync + 'static,
+    {
+        Self::with_job_pool(Arc::new(JobPool::default()), generator, appearance)
+    }
+
+    /// 创建使用指定线程池的区块流式加载器
+    ///
+    /// # 参数
+    /// + `pool` - 线程池，可与其他系统共享
+    /// + `generator` - 区块生成函数，在工作线程中以区块坐标调用
+    /// + `appearance` - 方块外观，用于生成网格
+    pub fn with_job_pool<G, A>(pool: Arc<JobPool>, generator: G, appearance: A) -> Self
+    where
+        G: Fn(IVec3) -> Chunk + Send + Sync + 'static,
+        A: BlockAppearance + Send + Sync + 'static,
+    {
+        let (sender, receiver) = channel();
+        Self {
+            view_distance: 8,
+            vertical_distance: 4,
+            unload_margin: 2,
+            max_generating: 16,
+            world: VoxelWorld::new(),
+            pool,
+            generator: Arc::new(generator),
+            appearance: Arc::new(appearance),
+            sender,
+            receiver,
+            generating: HashSet::new(),
+            revisions: HashMap::new(),
+            next_revision: 0,
+            meshes: ChunkMeshes::default(),
+        }
+    }
+
+    /// 获取体素世界
+    pub fn world(&self) -> &VoxelWorld {
+        &self.world
+    }
+
+    /// 获取体素世界的可变引用，修改过的区块将在下一次更新时重新生成网格
+    pub fn world_mut(&mut self) -> &mut VoxelWorld {
+        &mut self.world
+    }
+
+    /// 获取区块网格集合的句柄，供渲染线程绘制
+    pub fn meshes(&self) -> ChunkMeshes {
+        self.meshes.clone()
+    }
+
+    /// 正在生成或生成网格的任务数量
+    pub fn pending_jobs(&self) -> usize {
+        self.generating.len() + self.revisions.len()
+    }
+
+    /// 更新流式加载状态
+    ///
+    /// # 参数
+    /// + `center` - 加载中心的世界坐标，通常为摄像机位置
+    pub fn update(&mut self, center: Vec3) {
+        let (center, _) = VoxelWorld::split(center.floor().as_ivec3());
+        self.receive(center);
+        self.unload(center);
+        self.request(center);
+        self.remesh();
+    }
+
+    fn in_range(&self, center: IVec3, coord: IVec3, margin: i32) -> bool {
+        let d = coord - center;
+        let radius = self.view_distance + margin;
+        d.x * d.x + d.z * d.z <= radius * radius
+            && d.y.abs() <= self.vertical_distance + margin
+    }
+
+    fn receive(&mut self, center: IVec3) {
+        while let Ok(result) = self.receiver.try_recv() {
+            match result {
+                StreamResult::Generated(coord, chunk) => {
+                    self.generating.remove(&coord);
+                    if self.in_range(center, coord, self.unload_margin) {
+                        self.world.insert_chunk(coord, chunk);
+                    }
+                }
+                StreamResult::Meshed(coord, revision, mesh) => {
+                    if self.revisions.get(&coord) != Some(&revision) {
+                        continue;
+                    }
+                    self.revisions.remove(&coord);
+                    if self.world.chunk(coord).is_none() {
+                        continue;
+                    }
+                    let meshes = self.meshes.meshes.clone();
+                    App::run_on_render_thread(move || {
+                        let mut meshes = meshes.lock().unwrap();
+                        if mesh.is_empty() {
+                            meshes.remove(&coord);
+                        } else if let Some(gpu) = meshes.get_mut(&coord) {
+                            gpu.update(&mesh);
+                        } else {
+                            meshes.insert(coord, mesh.upload());
+                        }
+                    });
+                }
+            }
+        }
+    }
+
+    fn unload(&mut self, center: IVec3) {
+        let far: Vec<IVec3> = self
+            .world
+            .chunks()
+            .map(|(coord, _)| coord)
+            .filter(|coord| !self.in_range(center, *coord, self.unload_margin))
+            .collect();
+        for coord in far {
+            self.world.remove_chunk(coord);
+            self.revisions.remove(&coord);
+            let meshes = self.meshes.meshes.clone();
+            App::run_on_render_thread(move || {
+                meshes.lock().unwrap().remove(&coord);
+            });
+        }
+    }
+
+    fn request(&mut self, center: IVec3) {
+        if self.generating.len() >= self.max_generating {
+            return;
+        }
+        let (r, v) = (self.view_distance, self.vertical_distance);
+        let mut missing = Vec::new();
+        for y in -v..=v {
+            for z in -r..=r {
+                for x in -r..=r {
+                    let coord = center + IVec3::new(x, y, z);
+                    if self.in_range(center, coord, 0)
+                        && self.world.chunk(coord).is_none()
+                        && !self.generating.contains(&coord)
+                    {
+                        missing.push(coord);
+                    }
+                }
+            }
+        }
+        missing.sort_by_key(|coord| (*coord - center).length_squared());
+        let available = self.max_generating - self.generating.len();
+        for coord in missing.into_iter().take(available) {
+            self.generating.insert(coord);
+            let generator = self.generator.clone();
+            let sender = self.sender.clone();
+            self.pool.spawn(move || {
+                let _ = sender.send(StreamResult::Generated(coord, generator(coord)));
+            });
+        }
+    }
+
+    fn remesh(&mut self) {
+        for coord in self.world.take_dirty() {
+            if self.world.chunk(coord).is_none() {
+                continue;
+            }
+            self.next_revision += 1;
+            let revision = self.next_revision;
+            self.revisions.insert(coord, revision);
+            let neighborhood = ChunkNeighborhood::from_world(&self.world, coord);
+            let appearance = self.appearance.clone();
+            let sender = self.sender.clone();
+            self.pool.spawn(move || {
+                let mesh = VoxelMesher::new().mesh(&neighborhood, appearance.as_ref());
+                let _ = sender.send(StreamResult::Meshed(coord, revision, mesh));
+            });
+        }
+    }
+}
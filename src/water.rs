@@ -0,0 +1,401 @@
+use std::f32::consts::TAU;
+use std::sync::Arc;
+
+use crate::error::Result;
+use crate::math::*;
+use crate::{
+    AttachmentFormat, Camera, Framebuffer, GpuMesh, Mesh, Program, Scene, Texture2D, FOG_BINDING,
+    FOG_GLSL, LIGHTING_GLSL, LIGHTS_BINDING, PBR_BRDF_GLSL, SHADOWS_BINDING, SHADOW_TEXTURE_UNIT,
+};
+
+const WATER_VS: &str = r#"
+#version 330 core
+layout (location = 0) in vec3 aPosition;
+
+uniform mat4 uModel;
+uniform mat4 uViewProj;
+
+out vec3 vWorldPos;
+out vec4 vClip;
+
+void main()
+{
+    vec4 world = uModel * vec4(aPosition, 1.0);
+    vWorldPos = world.xyz;
+    vClip = uViewProj * world;
+    gl_Position = vClip;
+}
+"#;
+
+const WATER_FS_HEAD: &str = r#"
+#version 330 core
+in vec3 vWorldPos;
+in vec4 vClip;
+out vec4 FragColor;
+
+uniform sampler2D uReflection;
+uniform sampler2D uRefraction;
+uniform sampler2D uRefractionDepth;
+uniform sampler2D uNormalMap;
+uniform vec3 uCameraPos;
+uniform vec2 uClipPlanes;
+uniform float uTime;
+"#;
+
+const WATER_FS_MAIN: &str = r#"
+uniform vec4 uWaveVelocity;
+uniform float uWaveScale;
+uniform float uDistortion;
+uniform vec3 uTint;
+uniform vec3 uDeepColor;
+uniform float uAbsorption;
+uniform float uReflectivity;
+uniform float uRoughness;
+uniform float uEdgeFade;
+
+float linearDepth(float depth)
+{
+    float z = depth * 2.0 - 1.0;
+    float n = uClipPlanes.x;
+    float f = uClipPlanes.y;
+    return 2.0 * n * f / (f + n - z * (f - n));
+}
+
+void main()
+{
+    // 两层以不同速度滚动的法线贴图叠加(切线空间 z 朝上)
+    vec2 uv0 = vWorldPos.xz * uWaveScale + uWaveVelocity.xy * uTime;
+    vec2 uv1 = vWorldPos.xz * uWaveScale * 1.7 + uWaveVelocity.zw * uTime;
+    vec3 n0 = texture(uNormalMap, uv0).rgb * 2.0 - 1.0;
+    vec3 n1 = texture(uNormalMap, uv1).rgb * 2.0 - 1.0;
+    vec3 n = normalize(vec3(n0.xy + n1.xy, n0.z * n1.z));
+    vec3 N = normalize(vec3(n.x, n.z, n.y));
+
+    vec2 screen = vClip.xy / vClip.w * 0.5 + 0.5;
+    float thickness = max(linearDepth(texture(uRefractionDepth, screen).r) - linearDepth(gl_FragCoord.z), 0.0);
+    // 岸边水浅处减弱扰动，避免采样到水面以上的物体
+    vec2 offset = n.xy * uDistortion * clamp(thickness, 0.0, 1.0);
+    vec3 background = texture(uRefraction, screen).rgb;
+    vec3 refraction = texture(uRefraction, screen + offset).rgb * uTint;
+    refraction = mix(refraction, uDeepColor, 1.0 - exp(-thickness * uAbsorption));
+    // 反射相机的图像上下颠倒
+    vec3 reflection = texture(uReflection, vec2(screen.x, 1.0 - screen.y) + offset).rgb * uReflectivity;
+
+    vec3 V = normalize(uCameraPos - vWorldPos);
+    float fresnel = 0.02 + 0.98 * pow(1.0 - max(dot(N, V), 0.0), 5.0);
+    vec3 color = mix(refraction, reflection, fresnel);
+    color += evaluateLights(N, V, vWorldPos, vec3(0.0), 0.0, uRoughness, vec3(0.02));
+    color = applyFog(color, vWorldPos, uCameraPos);
+    color = mix(background, color, clamp(thickness / max(uEdgeFade, 1e-4), 0.0, 1.0));
+    FragColor = vec4(color, 1.0);
+}
+"#;
+
+/// 水面组件
+///
+/// 附加到场景中的实体上即可在实体所在位置绘制水平的矩形水面，由 [`WaterRenderer`] 绘制。
+/// 水面位于实体局部坐标系的 XZ 平面上，实体的旋转应只绕`Y`轴
+///
+/// # 示例
+///
+/// ```
+/// use gle::{*, math::*};
+///
+/// let mut scene = Scene::new();
+/// let lake = scene.spawn("lake", Transform::from_translation(Vec3::new(0.0, -1.0, 0.0)));
+/// scene.insert(lake, Water {
+///     size: Vec2::new(200.0, 200.0),
+///     deep_color: Vec3::new(0.01, 0.05, 0.08),
+///     ..Default::default()
+/// });
+/// ```
+#[derive(Debug, Clone)]
+pub struct Water {
+    /// 水面在局部 X 与 Z 方向的尺寸
+    pub size: Vec2,
+    /// 折射颜色的染色(线性空间)
+    pub tint: Vec3,
+    /// 深水处的颜色(线性空间)
+    pub deep_color: Vec3,
+    /// 光线在水中每单位距离的吸收率，越大水越浑浊
+    pub absorption: f32,
+    /// 法线贴图在每单位世界距离上的重复次数
+    pub wave_scale: f32,
+    /// 两层法线贴图的滚动速度，单位为纹理坐标每秒
+    pub wave_velocity: [Vec2; 2],
+    /// 法线对反射与折射的扰动强度，单位为屏幕纹理坐标
+    pub distortion: f32,
+    /// 反射强度
+    pub reflectivity: f32,
+    /// 计算高光时使用的粗糙度
+    pub roughness: f32,
+    /// 岸边淡入的水深，用于柔化水面与地形的交界
+    pub edge_fade: f32,
+    /// 法线贴图(切线空间，非 sRGB)，为`None`时使用内置的程序化波纹
+    pub normal_map: Option<Arc<Texture2D>>,
+}
+
+impl Default for Water {
+    fn default() -> Self {
+        Self {
+            size: Vec2::splat(100.0),
+            tint: Vec3::new(0.85, 0.95, 1.0),
+            deep_color: Vec3::new(0.02, 0.08, 0.12),
+            absorption: 0.3,
+            wave_scale: 0.1,
+            wave_velocity: [Vec2::new(0.02, 0.01), Vec2::new(-0.013, 0.017)],
+            distortion: 0.02,
+            reflectivity: 1.0,
+            roughness: 0.08,
+            edge_fade: 0.3,
+            normal_map: None,
+        }
+    }
+}
+
+/// 水面渲染器
+///
+/// 以平面反射与折射渲染目标实现水面：先在水面高度处镜像摄像机绘制反射图像，再以原摄像机绘制水下的折射图像，
+/// 最后以动画法线贴图扰动两者并按菲涅尔项混合，同时根据折射深度计算水色吸收与岸边淡入
+///
+/// # 示例
+///
+/// ```
+/// use gle::*;
+///
+/// fn render_frame(
+///     water: &WaterRenderer,
+///     scene: &Scene,
+///     camera: &Camera,
+///     queue: &mut RenderQueue,
+///     time: f32,
+/// ) {
+///     water.render_targets(camera, 0.0, |camera| {
+///         queue.execute(camera);
+///     });
+///     // 绘制场景……
+///     water.draw(scene, camera, time);
+/// }
+/// ```
+///
+/// # 注解
+///
+/// 所有水面共享同一组反射目标，应使它们位于同一高度。折射目标的深度用于计算水深，
+/// 目前只正确支持透视投影。绘制使用当前绑定的光源、阴影与雾 uniform 缓冲。
+/// 该类型只能在渲染线程中创建、使用与释放
+pub struct WaterRenderer {
+    reflection: Framebuffer,
+    refraction: Framebuffer,
+    program: Program,
+    plane: GpuMesh,
+    normal_map: Texture2D,
+}
+
+impl WaterRenderer {
+    /// 创建水面渲染器
+    ///
+    /// # 参数
+    /// + `width` - 反射与折射目标的宽度，可小于屏幕以节省开销
+    /// + `height` - 反射与折射目标的高度
+    pub fn new(width: u32, height: u32) -> Result<Self> {
+        let targets = [AttachmentFormat::Rgba16F];
+        let depth = Some(AttachmentFormat::Depth32F);
+        let reflection = Framebuffer::new(width, height, &targets, depth)?;
+        let refraction = Framebuffer::new(width, height, &targets, depth)?;
+        let fs = format!(
+            "{}{}{}{}{}",
+            WATER_FS_HEAD, PBR_BRDF_GLSL, LIGHTING_GLSL, FOG_GLSL, WATER_FS_MAIN
+        );
+        let program = Program::new(WATER_VS, &fs)?;
+        program.set_block_binding("Lights", LIGHTS_BINDING);
+        program.set_block_binding("Shadows", SHADOWS_BINDING);
+        program.set_block_binding("Fog", FOG_BINDING);
+        program.bind();
+        program.set("uShadowMap", &(SHADOW_TEXTURE_UNIT as i32));
+        program.set("uReflection", &0);
+        program.set("uRefraction", &1);
+        program.set("uRefractionDepth", &2);
+        program.set("uNormalMap", &3);
+        Ok(Self {
+            reflection,
+            refraction,
+            program,
+            plane: Mesh::plane(1.0, 1.0, 1, 1).upload(),
+            normal_map: ripple_normal_map(128),
+        })
+    }
+
+    /// 获取反射渲染目标
+    pub fn reflection(&self) -> &Framebuffer {
+        &self.reflection
+    }
+
+    /// 获取折射渲染目标
+    pub fn refraction(&self) -> &Framebuffer {
+        &self.refraction
+    }
+
+    /// 修改反射与折射目标的大小
+    pub fn resize(&mut self, width: u32, height: u32) -> Result<()> {
+        self.reflection.resize(width, height)?;
+        self.refraction.resize(width, height)
+    }
+
+    /// 计算以水平面镜像后的反射摄像机
+    ///
+    /// # 参数
+    /// + `camera` - 原摄像机
+    /// + `height` - 水面高度
+    ///
+    /// # 注解
+    ///
+    /// 反射摄像机的图像上下颠倒且三角形环绕方向相反，绘制时需将正面设为顺时针。
+    /// 反射摄像机不显示编辑器网格与坐标轴指示器
+    pub fn reflection_camera(camera: &Camera, height: f32) -> Camera {
+        let mirror = Mat3::from_diagonal(Vec3::new(1.0, -1.0, 1.0));
+        let rotation = mirror * Mat3::from_quat(camera.rotation) * mirror;
+        let mut reflected = *camera;
+        reflected.position.y = 2.0 * height - camera.position.y;
+        reflected.rotation = Quat::from_mat3(&rotation).normalize();
+        reflected.show_grid = false;
+        reflected.show_gizmo = false;
+        reflected
+    }
+
+    /// 获取水面的裁剪平面`(法线, 距离)`，满足`dot(plane.xyz, P) + plane.w >= 0`的点位于保留一侧
+    ///
+    /// # 参数
+    /// + `height` - 水面高度
+    /// + `above` - 为`true`时保留水面以上的部分(用于反射)，否则保留水面以下的部分(用于折射)
+    ///
+    /// # 注解
+    ///
+    /// 自定义着色器可将其写入`gl_ClipDistance`，以免水下物体出现在反射中
+    pub fn clip_plane(height: f32, above: bool) -> Vec4 {
+        if above {
+            Vec4::new(0.0, 1.0, 0.0, -height)
+        } else {
+            Vec4::new(0.0, -1.0, 0.0, height)
+        }
+    }
+
+    /// 绘制反射与折射图像
+    ///
+    /// # 参数
+    /// + `camera` - 主摄像机
+    /// + `height` - 水面高度
+    /// + `draw` - 绘制场景的回调，分别以反射摄像机与原摄像机调用，不应绘制水面本身
+    ///
+    /// # 注解
+    ///
+    /// 回调调用前已绑定并清除对应的渲染目标，结束后恢复调用前绑定的帧缓冲与视口
+    pub fn render_targets(&self, camera: &Camera, height: f32, mut draw: impl FnMut(&Camera)) {
+        let mut previous = 0;
+        let mut viewport = [0; 4];
+        unsafe {
+            gl::GetIntegerv(gl::FRAMEBUFFER_BINDING, &mut previous);
+            gl::GetIntegerv(gl::VIEWPORT, viewport.as_mut_ptr());
+        }
+        let reflected = Self::reflection_camera(camera, height);
+        self.reflection.bind();
+        unsafe {
+            gl::ClearColor(0.0, 0.0, 0.0, 1.0);
+            gl::Clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT);
+            gl::FrontFace(gl::CW);
+        }
+        draw(&reflected);
+        self.refraction.bind();
+        unsafe {
+            gl::FrontFace(gl::CCW);
+            gl::Clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT);
+        }
+        draw(camera);
+        unsafe {
+            gl::BindFramebuffer(gl::FRAMEBUFFER, previous as u32);
+            gl::Viewport(viewport[0], viewport[1], viewport[2], viewport[3]);
+        }
+    }
+
+    /// 绘制场景中所有的 [`Water`] 组件
+    ///
+    /// # 参数
+    /// + `scene` - 场景，使用其中实体的世界变换
+    /// + `camera` - 摄像机，应与 [`WaterRenderer::render_targets`] 使用的一致
+    /// + `time` - 动画时间，单位为秒
+    pub fn draw(&self, scene: &Scene, camera: &Camera, time: f32) {
+        let mut waters = scene.query::<Water>().peekable();
+        if waters.peek().is_none() {
+            return;
+        }
+        let (near, far) = camera.clip_planes();
+        self.program.bind();
+        self.program.set("uViewProj", &camera.view_projection());
+        self.program.set("uCameraPos", &camera.position);
+        self.program.set("uClipPlanes", &Vec2::new(near, far));
+        self.program.set("uTime", &time);
+        let textures = [
+            self.reflection.color(0),
+            self.refraction.color(0),
+            self.refraction.depth(),
+        ];
+        for (unit, texture) in textures.into_iter().enumerate() {
+            if let Some(texture) = texture {
+                texture.bind(unit as u32);
+            }
+        }
+        unsafe {
+            gl::Disable(gl::BLEND);
+            gl::Disable(gl::CULL_FACE);
+        }
+        for (entity, water) in waters {
+            let Some(node) = scene.get(entity) else {
+                continue;
+            };
+            let model = node.world_matrix()
+                * Mat4::from_scale(Vec3::new(water.size.x, 1.0, water.size.y));
+            match &water.normal_map {
+                Some(texture) => texture.bind(3),
+                None => self.normal_map.bind(3),
+            }
+            let [a, b] = water.wave_velocity;
+            self.program.set("uModel", &model);
+            self.program.set("uWaveVelocity", &Vec4::new(a.x, a.y, b.x, b.y));
+            self.program.set("uWaveScale", &water.wave_scale);
+            self.program.set("uDistortion", &water.distortion);
+            self.program.set("uTint", &water.tint);
+            self.program.set("uDeepColor", &water.deep_color);
+            self.program.set("uAbsorption", &water.absorption);
+            self.program.set("uReflectivity", &water.reflectivity);
+            self.program.set("uRoughness", &water.roughness);
+            self.program.set("uEdgeFade", &water.edge_fade);
+            self.plane.draw();
+        }
+    }
+}
+
+/// 由若干整数频率的正弦波叠加生成可无缝平铺的波纹法线贴图
+fn ripple_normal_map(size: u32) -> Texture2D {
+    const WAVES: [(f32, f32, f32, f32); 6] = [
+        (1.0, 2.0, 0.5, 0.0),
+        (-3.0, 1.0, 0.25, 1.3),
+        (4.0, -5.0, 0.12, 2.1),
+        (-7.0, -3.0, 0.08, 0.7),
+        (9.0, 11.0, 0.04, 4.2),
+        (-13.0, 6.0, 0.03, 3.3),
+    ];
+    let mut pixels = Vec::with_capacity((size * size * 4) as usize);
+    for y in 0..size {
+        for x in 0..size {
+            let p = Vec2::new(x as f32, y as f32) / size as f32;
+            let mut slope = Vec2::ZERO;
+            for (kx, ky, amplitude, phase) in WAVES {
+                let k = Vec2::new(kx, ky);
+                slope += k * amplitude * (TAU * k.dot(p) + phase).cos();
+            }
+            let normal = Vec3::new(-slope.x * 0.05, -slope.y * 0.05, 1.0).normalize();
+            let encoded = (normal * 0.5 + 0.5) * 255.0;
+            pixels.extend_from_slice(&[encoded.x as u8, encoded.y as u8, encoded.z as u8, 255]);
+        }
+    }
+    Texture2D::from_rgba8(size, size, &pixels, false)
+}
@@ -0,0 +1,62 @@
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Error, Result};
+
+/// 窗口的位置、大小与最大化状态
+///
+/// 由 [`AppBuilder::set_remember_geometry`](crate::AppBuilder::set_remember_geometry) 启用后，
+/// 在程序退出时保存到平台配置目录，并在下次启动时恢复
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WindowGeometry {
+    /// 窗口左上角横坐标
+    pub x: i32,
+    /// 窗口左上角纵坐标
+    pub y: i32,
+    /// 窗口未最大化时的宽度
+    pub width: i32,
+    /// 窗口未最大化时的高度
+    pub height: i32,
+    /// 是否最大化
+    pub maximized: bool,
+}
+
+impl WindowGeometry {
+    /// 获取保存窗口状态的文件路径
+    ///
+    /// # 参数
+    /// + `name` - 程序名称，作为配置目录下的子目录名
+    ///
+    /// # 返回值
+    /// Windows 下返回`%APPDATA%\<name>\window.toml`，其他平台返回`$XDG_CONFIG_HOME/<name>/window.toml`
+    /// 或`~/.config/<name>/window.toml`，无法确定配置目录时返回`None`
+    pub fn path(name: &str) -> Option<PathBuf> {
+        let dir = if cfg!(windows) {
+            std::env::var_os("APPDATA").map(PathBuf::from)
+        } else {
+            std::env::var_os("XDG_CONFIG_HOME")
+                .map(PathBuf::from)
+                .or_else(|| std::env::var_os("HOME").map(|home| Path::new(&home).join(".config")))
+        };
+        Some(dir?.join(name).join("window.toml"))
+    }
+
+    /// 从文件加载窗口状态
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let src = std::fs::read_to_string(path)?;
+        toml::from_str(&src).map_err(|e| Error::Parse(format!("{}: {}", path.display(), e)))
+    }
+
+    /// 将窗口状态保存到文件，自动创建所在目录
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let path = path.as_ref();
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let src = toml::to_string(self).map_err(|e| Error::Parse(e.to_string()))?;
+        std::fs::write(path, src)?;
+        Ok(())
+    }
+}